@@ -0,0 +1,51 @@
+//! Compares the algorithms of the convolutional layers over a 32x32 input, for kernels of
+//! increasing size and for several channels.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rann_base::{
+    activ::Logistic,
+    conv::{ConvAlgorithm, Convolutional},
+    image::Image,
+    testing,
+};
+use rann_traits::{Network, Scalar};
+
+const ALGORITHMS: [ConvAlgorithm; 2] = [ConvAlgorithm::Direct, ConvAlgorithm::Im2col];
+
+fn bench_kernel<const K: usize, const OUT: usize, const C: usize, const OC: usize>(
+    c: &mut Criterion,
+) {
+    let inputs = Image::<32, 32, C>::from_fn(|x, y, c| ((x + y + c) % 7) as Scalar / 7.0);
+    let gradients = Image::<OUT, OUT, OC>::filled(0.1);
+    let mut group = c.benchmark_group("conv");
+    for algorithm in ALGORITHMS {
+        let mut net = Convolutional::<32, 32, OUT, OUT, _, C, OC>::new(
+            Logistic,
+            [K, K],
+            testing::seeded_gen(1),
+        )
+        .with_algorithm(algorithm);
+        let id = BenchmarkId::new(
+            format!("{algorithm:?}"),
+            format!("{K}x{K}, {C} to {OC} channels"),
+        );
+        group.bench_with_input(id, &inputs, |b, inputs| {
+            b.iter(|| {
+                let inter = net.intermediate(inputs);
+                net.train_deriv(inputs, &inter, &gradients, 0.0)
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_conv(c: &mut Criterion) {
+    bench_kernel::<1, 32, 1, 1>(c);
+    bench_kernel::<3, 30, 1, 1>(c);
+    bench_kernel::<5, 28, 1, 1>(c);
+    bench_kernel::<9, 24, 1, 1>(c);
+    bench_kernel::<3, 30, 3, 8>(c);
+}
+
+criterion_group!(benches, bench_conv);
+criterion_main!(benches);
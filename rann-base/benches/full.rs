@@ -0,0 +1,106 @@
+//! Compares evaluating fully connected layers with and without keeping their intermediate
+//! calculations, for layers of increasing size, the evaluation of tiny layers with the general
+//! evaluation, and the evaluation of batches of inputs at once with evaluating them one by one.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use nalgebra::{Const, MatrixView, SMatrix};
+use rann_base::{
+    activ::{LeakyRelu, Logistic},
+    testing, Full,
+};
+use rann_traits::{deriv::Deriv, params::Parameterized, Intermediate, Network, Scalar};
+
+fn inputs<const N: usize>() -> [Scalar; N] {
+    std::array::from_fn(|i| (i % 7) as Scalar / 7.0)
+}
+
+fn bench_layer<const NUM_IN: usize, const NUM_OUT: usize>(c: &mut Criterion) {
+    let net = Full::<NUM_IN, NUM_OUT, _>::new(Logistic, testing::seeded_gen(1));
+    let mut group = c.benchmark_group("full");
+    let size = format!("{NUM_IN} to {NUM_OUT}");
+    group.bench_with_input(BenchmarkId::new("eval", &size), &inputs(), |b, inputs| {
+        b.iter(|| net.eval(black_box(inputs)))
+    });
+    group.bench_with_input(
+        BenchmarkId::new("intermediate", &size),
+        &inputs(),
+        |b, inputs| b.iter(|| net.intermediate(black_box(inputs)).into_output()),
+    );
+    group.finish();
+}
+
+fn bench_tiny<const NUM_IN: usize, const NUM_OUT: usize>(c: &mut Criterion) {
+    let net = Full::<NUM_IN, NUM_OUT, _>::new(LeakyRelu(0.1), testing::seeded_gen(1));
+    // A cheap activation function, such that the time of the product is measured. The same layer
+    // is evaluated like layers that are not tiny.
+    let params = net.params();
+    let weights =
+        SMatrix::<Scalar, NUM_OUT, NUM_IN>::from_column_slice(&params[..NUM_IN * NUM_OUT]);
+    let biases = &params[NUM_IN * NUM_OUT..];
+    let mut group = c.benchmark_group("tiny");
+    let size = format!("{NUM_IN} to {NUM_OUT}");
+    group.bench_with_input(
+        BenchmarkId::new("tiny", &size),
+        &inputs::<NUM_IN>(),
+        |b, inputs| b.iter(|| net.eval(black_box(inputs))),
+    );
+    group.bench_with_input(
+        BenchmarkId::new("general", &size),
+        &inputs::<NUM_IN>(),
+        |b, inputs| {
+            b.iter(|| {
+                let mat =
+                    MatrixView::from_slice_generic(black_box(inputs), Const::<NUM_IN>, Const::<1>);
+                let mut sums = weights * mat;
+                for (sum, bias) in sums.iter_mut().zip(biases) {
+                    *sum += bias;
+                }
+                sums.map(|sum| LeakyRelu(0.1).call(&sum))
+            })
+        },
+    );
+    group.finish();
+}
+
+fn bench_batch<const NUM_IN: usize, const NUM_OUT: usize>(c: &mut Criterion) {
+    let net = Full::<NUM_IN, NUM_OUT, _>::new(LeakyRelu(0.1), testing::seeded_gen(1));
+    let batch = vec![inputs::<NUM_IN>(); 256];
+    let mut group = c.benchmark_group("batch");
+    let size = format!("{NUM_IN} to {NUM_OUT}");
+    group.bench_with_input(BenchmarkId::new("eval_many", &size), &batch, |b, batch| {
+        b.iter(|| net.eval_many(black_box(batch)))
+    });
+    group.bench_with_input(BenchmarkId::new("eval", &size), &batch, |b, batch| {
+        b.iter(|| {
+            black_box(batch)
+                .iter()
+                .map(|inputs| net.eval(inputs))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.finish();
+}
+
+fn bench_full(c: &mut Criterion) {
+    bench_layer::<2, 3>(c);
+    bench_layer::<16, 16>(c);
+    bench_layer::<64, 64>(c);
+    bench_layer::<256, 64>(c);
+}
+
+fn bench_tiny_layers(c: &mut Criterion) {
+    bench_tiny::<2, 1>(c);
+    bench_tiny::<2, 3>(c);
+    bench_tiny::<4, 4>(c);
+    bench_tiny::<8, 8>(c);
+}
+
+fn bench_batches(c: &mut Criterion) {
+    bench_batch::<2, 3>(c);
+    bench_batch::<8, 8>(c);
+    bench_batch::<64, 64>(c);
+    bench_batch::<256, 64>(c);
+}
+
+criterion_group!(benches, bench_full, bench_tiny_layers, bench_batches);
+criterion_main!(benches);
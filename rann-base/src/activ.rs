@@ -1,4 +1,4 @@
-use rann_traits::deriv::Deriv;
+use rann_traits::{deriv::Deriv, Scalar};
 
 /// Leaky Rectified Linear unit activation function.
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -38,11 +38,15 @@ impl Deriv for Tanh {
     }
 
     fn deriv(&self, x: &Self::In) -> Self::Out {
-        x.tanh()
+        1.0 - x.tanh().powi(2)
     }
 }
 
 /// Logistic activation function.
+///
+/// Computed piecewise from the exponential of `-|x|`, which cannot overflow, such that small
+/// outputs keep their precision instead of rounding to `0.0`, and the derivative is accurate for
+/// large inputs of either sign.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Logistic;
 
@@ -51,12 +55,104 @@ impl Deriv for Logistic {
 
     type Out = f32;
 
-    fn call(&self, x: &Self::In) -> Self::Out {
-        1.0 / (1.0 + (-x).exp())
+    fn call(&self, &x: &Self::In) -> Self::Out {
+        let e = (-x.abs()).exp();
+        if x >= 0.0 {
+            1.0 / (1.0 + e)
+        } else {
+            e / (1.0 + e)
+        }
+    }
+
+    fn deriv(&self, &x: &Self::In) -> Self::Out {
+        // Equal to `a * (1 - a)`, without cancellation in `1 - a` when `a` is close to one.
+        let e = (-x.abs()).exp();
+        e / ((1.0 + e) * (1.0 + e))
+    }
+}
+
+/// Softplus activation function, `ln(1 + exp(x))`: a smooth approximation of a rectified linear
+/// unit, whose derivative is the [`Logistic`] function.
+///
+/// Computed as `max(x, 0) + ln(1 + exp(-|x|))`, which does not overflow for large inputs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Softplus;
+
+impl Deriv for Softplus {
+    type In = f32;
+
+    type Out = f32;
+
+    fn call(&self, &x: &Self::In) -> Self::Out {
+        x.max(0.0) + (-x.abs()).exp().ln_1p()
     }
 
     fn deriv(&self, x: &Self::In) -> Self::Out {
-        let a = <Self as Deriv>::call(self, x);
-        a * (1.0 - a)
+        Logistic.call(x)
+    }
+}
+
+/// Scaled exponential linear unit activation function, `λ * x` for positive inputs and
+/// `λ * α * (exp(x) - 1)` otherwise.
+///
+/// With the constants `λ` and `α` of Klambauer et al. (2017), the activations of a stack of
+/// [`Full`](crate::Full) layers with weights drawn from a normal distribution with a variance of
+/// one over the number of inputs converge to zero mean and unit variance, without normalization
+/// layers. Regularize such self-normalizing networks with
+/// [`AlphaDropout`](crate::dropout::AlphaDropout) instead of plain dropout, which would break this.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Selu;
+
+impl Selu {
+    /// The scale `λ` of the activation.
+    pub const LAMBDA: f32 = 1.050_701;
+    /// The saturation `α` of negative inputs.
+    pub const ALPHA: f32 = 1.673_263_2;
+}
+
+impl Deriv for Selu {
+    type In = f32;
+
+    type Out = f32;
+
+    fn call(&self, &x: &Self::In) -> Self::Out {
+        if x > 0.0 {
+            Self::LAMBDA * x
+        } else {
+            Self::LAMBDA * Self::ALPHA * x.exp_m1()
+        }
+    }
+
+    fn deriv(&self, &x: &Self::In) -> Self::Out {
+        if x > 0.0 {
+            Self::LAMBDA
+        } else {
+            Self::LAMBDA * Self::ALPHA * x.exp()
+        }
     }
 }
+
+/// Returns the softmax of `logits`: the probability of each of them.
+pub fn softmax<const N: usize>(logits: &[Scalar; N]) -> [Scalar; N] {
+    let max = max_logit(logits);
+    let exps = logits.map(|l| (l - max).exp());
+    let sum: Scalar = exps.iter().sum();
+    exps.map(|e| e / sum)
+}
+
+/// Returns the logarithm of the softmax of `logits`, which unlike the logarithm of [`softmax()`]
+/// stays finite for very unlikely logits.
+pub fn log_softmax<const N: usize>(logits: &[Scalar; N]) -> [Scalar; N] {
+    let max = max_logit(logits);
+    let log_sum = logits.iter().map(|l| (l - max).exp()).sum::<Scalar>().ln();
+    logits.map(|l| l - max - log_sum)
+}
+
+// The largest logit, which is subtracted from all logits such that their exponentials cannot
+// overflow.
+fn max_logit(logits: &[Scalar]) -> Scalar {
+    logits
+        .iter()
+        .copied()
+        .fold(Scalar::NEG_INFINITY, Scalar::max)
+}
@@ -0,0 +1,177 @@
+/*!
+Adversarial examples and adversarial training.
+
+An adversarial example is an input perturbed slightly, within a distance `epsilon` of the
+original in the L∞ norm, such that the error of a network increases as much as possible. They are
+found using the [input gradient](rann_traits::Supervised::input_gradient) of the network: the
+fast gradient sign method (FGSM) takes a single step of size `epsilon` along the sign of the
+gradient, and projected gradient descent (PGD) takes several smaller steps, projecting back onto
+the allowed perturbations after each step.
+
+Training on adversarial examples in addition to the original samples makes networks more robust
+against them, see [`train_step()`].
+
+# Examples
+```rust
+use rann_base::{activ::Logistic, adversarial::Attack, error::SquareError, testing, Full};
+use rann_traits::{Network, Supervised};
+
+let mut net = Full::<2, 1, _>::new(Logistic, testing::seeded_gen(1))
+    .chain(SquareError { expected: [0.0] });
+let (inputs, target) = ([1.0, 0.0], [1.0]);
+
+let adversarial = Attack::fgsm(0.1).perturb(&net, &inputs, &target);
+net.set_target(&target);
+assert!(net.eval(&adversarial)[0] > net.eval(&inputs)[0]);
+```
+*/
+
+use rann_traits::{Scalar, Supervised};
+
+use crate::train;
+
+/// A method to find adversarial examples. See [module level documentation](self) for more info.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Attack {
+    /// The largest allowed change of each input.
+    pub epsilon: Scalar,
+    /// The number of gradient steps.
+    pub steps: usize,
+    /// The change of each input per step.
+    pub step_size: Scalar,
+    /// The range the inputs are clamped to, such as `(0.0, 1.0)` for pixel intensities.
+    pub bounds: Option<(Scalar, Scalar)>,
+}
+
+impl Attack {
+    /// Creates a fast gradient sign method attack, taking a single step of size `epsilon`.
+    pub fn fgsm(epsilon: Scalar) -> Self {
+        Self {
+            epsilon,
+            steps: 1,
+            step_size: epsilon,
+            bounds: None,
+        }
+    }
+
+    /// Creates a projected gradient descent attack, taking `steps` steps of size
+    /// `2.5 * epsilon / steps`, such that the boundary can be reached from anywhere in the
+    /// allowed range.
+    pub fn pgd(epsilon: Scalar, steps: usize) -> Self {
+        Self {
+            epsilon,
+            steps,
+            step_size: 2.5 * epsilon / steps as Scalar,
+            bounds: None,
+        }
+    }
+
+    /// Clamps the adversarial examples to `min..=max`.
+    pub fn with_bounds(self, min: Scalar, max: Scalar) -> Self {
+        Self {
+            bounds: Some((min, max)),
+            ..self
+        }
+    }
+
+    /// Returns an adversarial example for `net` near `inputs`, increasing the error towards
+    /// `target`. The network is not changed.
+    pub fn perturb<N, const I: usize>(
+        &self,
+        net: &N,
+        inputs: &[Scalar; I],
+        target: &N::Target,
+    ) -> [Scalar; I]
+    where
+        N: Supervised<In = [Scalar; I]> + Clone,
+    {
+        let mut adversarial = *inputs;
+        for _ in 0..self.steps {
+            let gradient = net.input_gradient(&adversarial, target);
+            for ((x, original), g) in adversarial.iter_mut().zip(inputs).zip(gradient) {
+                *x = (*x + self.step_size * sign(g))
+                    .clamp(original - self.epsilon, original + self.epsilon);
+                if let Some((min, max)) = self.bounds {
+                    *x = x.clamp(min, max);
+                }
+            }
+        }
+        adversarial
+    }
+}
+
+// Unlike `Scalar::signum()`, the sign of zero is zero, such that inputs without influence on the
+// error are not changed.
+fn sign(x: Scalar) -> Scalar {
+    if x > 0.0 {
+        1.0
+    } else if x < 0.0 {
+        -1.0
+    } else {
+        0.0
+    }
+}
+
+/// Trains `net` on `inputs` and on an adversarial example of them found by `attack`, and returns
+/// the error on `inputs` before training.
+///
+/// The learning rate of the adversarial example is scaled by `weight`, and that of the original
+/// inputs by `1.0 - weight`, such that the mixed objective
+/// `(1 - weight) * error(inputs) + weight * error(adversarial)` is minimized.
+pub fn train_step<N, const I: usize>(
+    net: &mut N,
+    inputs: &[Scalar; I],
+    target: &N::Target,
+    attack: &Attack,
+    weight: Scalar,
+    learning_rate: Scalar,
+) -> Scalar
+where
+    N: Supervised<In = [Scalar; I]> + Clone,
+{
+    // The example is found before training, such that both steps use the same network.
+    let adversarial = attack.perturb(net, inputs, target);
+    let error = train::train_step(net, inputs, target, (1.0 - weight) * learning_rate);
+    train::train_step(net, &adversarial, target, weight * learning_rate);
+    error
+}
+
+/// Trains `net` on every sample of `dataset` in order using [`train_step()`], and returns the
+/// mean error over the original samples before they were trained on.
+pub fn train_epoch<N, const I: usize>(
+    net: &mut N,
+    dataset: &[([Scalar; I], N::Target)],
+    attack: &Attack,
+    weight: Scalar,
+    learning_rate: Scalar,
+) -> Scalar
+where
+    N: Supervised<In = [Scalar; I]> + Clone,
+{
+    let sum: Scalar = dataset
+        .iter()
+        .map(|(inputs, target)| train_step(net, inputs, target, attack, weight, learning_rate))
+        .sum();
+    sum / dataset.len() as Scalar
+}
+
+/// Returns the mean error of `net` over adversarial examples of the samples in `dataset`,
+/// without training it.
+pub fn adversarial_error<N, const I: usize>(
+    net: &mut N,
+    dataset: &[([Scalar; I], N::Target)],
+    attack: &Attack,
+) -> Scalar
+where
+    N: Supervised<In = [Scalar; I]> + Clone,
+{
+    let sum: Scalar = dataset
+        .iter()
+        .map(|(inputs, target)| {
+            let adversarial = attack.perturb(net, inputs, target);
+            net.set_target(target);
+            net.eval(&adversarial)[0]
+        })
+        .sum();
+    sum / dataset.len() as Scalar
+}
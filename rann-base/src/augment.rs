@@ -0,0 +1,241 @@
+/*!
+Data augmentation.
+
+Augmentation trains a network on randomly transformed copies of the samples, such that it
+generalizes to variations of the inputs not present in the dataset. An [`Augment`]ation changes
+the inputs of a sample in place, and augmentations compose into a pipeline with
+[`Augment::then()`]:
+
+- [`Noise`] adds Gaussian noise to every input,
+- [`Scale`] multiplies all inputs by a random factor,
+- [`FeatureDropout`] sets random inputs to zero,
+- [`FlipHorizontal`], [`FlipVertical`] and [`RandomCrop`] transform inputs holding images.
+
+Augmentations are applied lazily, to a fresh copy of every sample every epoch, using a seeded
+random generator such that training is reproducible. See [`augmented()`] and
+[`Trainer::fit_augmented()`](crate::train::Trainer::fit_augmented).
+
+# Examples
+```rust
+use fastrand::Rng;
+use rann_base::augment::{Augment, FeatureDropout, Noise, Scale};
+
+let pipeline = Noise { std_dev: 0.01 }
+    .then(Scale { min: 0.9, max: 1.1 })
+    .then(FeatureDropout { p: 0.1 });
+
+let mut inputs = [1.0; 16];
+pipeline.augment(&mut inputs, &mut Rng::with_seed(1));
+assert!(inputs.iter().all(|&x| x == 0.0 || (0.8..1.2).contains(&x)));
+```
+*/
+
+use fastrand::Rng;
+use rann_traits::Scalar;
+
+use crate::gen::gaussian;
+
+/// A random transformation of the inputs of a sample. See [module level documentation](self)
+/// for more info.
+pub trait Augment {
+    /// Transforms `inputs` in place, drawing random numbers from `rng`.
+    fn augment(&self, inputs: &mut [Scalar], rng: &mut Rng);
+
+    /// Returns a pipeline applying this augmentation, and then `next`.
+    fn then<A: Augment>(self, next: A) -> Then<Self, A>
+    where
+        Self: Sized,
+    {
+        Then { first: self, next }
+    }
+}
+
+impl<A: Augment + ?Sized> Augment for &A {
+    fn augment(&self, inputs: &mut [Scalar], rng: &mut Rng) {
+        (**self).augment(inputs, rng);
+    }
+}
+
+/// Two augmentations applied one after another, see [`Augment::then()`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Then<A, B> {
+    /// The augmentation applied first.
+    pub first: A,
+    /// The augmentation applied next.
+    pub next: B,
+}
+
+impl<A: Augment, B: Augment> Augment for Then<A, B> {
+    fn augment(&self, inputs: &mut [Scalar], rng: &mut Rng) {
+        self.first.augment(inputs, rng);
+        self.next.augment(inputs, rng);
+    }
+}
+
+/// Adds Gaussian noise with standard deviation `std_dev` to every input.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Noise {
+    /// The standard deviation of the noise.
+    pub std_dev: Scalar,
+}
+
+impl Augment for Noise {
+    fn augment(&self, inputs: &mut [Scalar], rng: &mut Rng) {
+        for x in inputs {
+            *x += self.std_dev * gaussian(rng);
+        }
+    }
+}
+
+/// Multiplies all inputs by the same factor, drawn uniformly from `min..max`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Scale {
+    /// The smallest factor.
+    pub min: Scalar,
+    /// The largest factor.
+    pub max: Scalar,
+}
+
+impl Augment for Scale {
+    fn augment(&self, inputs: &mut [Scalar], rng: &mut Rng) {
+        let factor = self.min + (self.max - self.min) * rng.f32();
+        inputs.iter_mut().for_each(|x| *x *= factor);
+    }
+}
+
+/// Sets every input to zero with probability `p`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FeatureDropout {
+    /// The probability of dropping an input.
+    pub p: Scalar,
+}
+
+impl Augment for FeatureDropout {
+    fn augment(&self, inputs: &mut [Scalar], rng: &mut Rng) {
+        for x in inputs {
+            if rng.f32() < self.p {
+                *x = 0.0;
+            }
+        }
+    }
+}
+
+/// Mirrors images left to right with probability 0.5.
+///
+/// The inputs hold one or more channels of `width` by `height` pixels, one channel after another,
+/// with the pixels of every channel row by row.
+///
+/// # Panics
+/// Panics when applied to inputs that are not a whole number of channels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlipHorizontal {
+    /// The width of the images.
+    pub width: usize,
+    /// The height of the images.
+    pub height: usize,
+}
+
+impl Augment for FlipHorizontal {
+    fn augment(&self, inputs: &mut [Scalar], rng: &mut Rng) {
+        if rng.bool() {
+            for row in rows(inputs, self.width, self.height) {
+                row.reverse();
+            }
+        }
+    }
+}
+
+/// Mirrors images top to bottom with probability 0.5. See [`FlipHorizontal`] for the layout of
+/// the inputs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlipVertical {
+    /// The width of the images.
+    pub width: usize,
+    /// The height of the images.
+    pub height: usize,
+}
+
+impl Augment for FlipVertical {
+    fn augment(&self, inputs: &mut [Scalar], rng: &mut Rng) {
+        if rng.bool() {
+            for channel in channels(inputs, self.width, self.height) {
+                for y in 0..self.height / 2 {
+                    let (top, bottom) = channel.split_at_mut((self.height - 1 - y) * self.width);
+                    top[y * self.width..(y + 1) * self.width]
+                        .swap_with_slice(&mut bottom[..self.width]);
+                }
+            }
+        }
+    }
+}
+
+/// Crops images at a random offset of at most `padding` pixels in each direction, keeping their
+/// size: the images are shifted, and the pixels shifted in are zero. See [`FlipHorizontal`] for
+/// the layout of the inputs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RandomCrop {
+    /// The width of the images.
+    pub width: usize,
+    /// The height of the images.
+    pub height: usize,
+    /// The largest shift, in pixels.
+    pub padding: usize,
+}
+
+impl Augment for RandomCrop {
+    fn augment(&self, inputs: &mut [Scalar], rng: &mut Rng) {
+        let padding = self.padding as isize;
+        let dx = rng.isize(-padding..=padding);
+        let dy = rng.isize(-padding..=padding);
+        let (width, height) = (self.width as isize, self.height as isize);
+        for channel in channels(inputs, self.width, self.height) {
+            let original = channel.to_vec();
+            for y in 0..height {
+                for x in 0..width {
+                    let (sx, sy) = (x + dx, y + dy);
+                    let inside = (0..width).contains(&sx) && (0..height).contains(&sy);
+                    channel[(y * width + x) as usize] = if inside {
+                        original[(sy * width + sx) as usize]
+                    } else {
+                        0.0
+                    };
+                }
+            }
+        }
+    }
+}
+
+fn channels(
+    inputs: &mut [Scalar],
+    width: usize,
+    height: usize,
+) -> impl Iterator<Item = &mut [Scalar]> {
+    assert!(
+        inputs.len().is_multiple_of(width * height),
+        "The inputs should be a whole number of {width}x{height} channels."
+    );
+    inputs.chunks_exact_mut(width * height)
+}
+
+fn rows(inputs: &mut [Scalar], width: usize, height: usize) -> impl Iterator<Item = &mut [Scalar]> {
+    channels(inputs, width, height).flat_map(move |channel| channel.chunks_exact_mut(width))
+}
+
+/// Returns an iterator over augmented copies of the samples of `dataset`, augmenting the inputs
+/// of every sample with `augment` as it is produced.
+pub fn augmented<'a, I, T, A>(
+    dataset: &'a [(I, T)],
+    augment: &'a A,
+    rng: &'a mut Rng,
+) -> impl Iterator<Item = (I, T)> + 'a
+where
+    I: Clone + AsMut<[Scalar]>,
+    T: Clone,
+    A: Augment + ?Sized,
+{
+    dataset.iter().map(move |(inputs, target)| {
+        let mut inputs = inputs.clone();
+        augment.augment(inputs.as_mut(), rng);
+        (inputs, target.clone())
+    })
+}
@@ -0,0 +1,244 @@
+/*!
+Autoencoders and their pretraining.
+
+An [`Autoencoder`] consists of an encoder network and a decoder network that mirrors the encoder.
+It is trained to reconstruct its inputs, after which the trained encoder can be extracted and
+chained to other networks.
+
+The decoder is constructed from the encoder using the [`Mirror`] trait. Layers wrapped in a
+[`Shared`] are mirrored with tied weights: the decoder then uses the transposed weights of the
+encoder.
+
+# Examples
+```rust
+use rann_base::{activ::Logistic, autoencoder::Autoencoder, Full};
+use rann_traits::{compose::Shared, Network};
+
+let gen = (|i, j| ((i + 2 * j) % 3) as f32 * 0.5 - 0.5, |_| 0.0);
+// Wrap the layers in a `Shared` to tie the weights of the encoder and decoder.
+let encoder = Shared::new(Full::<4, 2, _>::new(Logistic, gen));
+let mut auto = Autoencoder::new(encoder, gen);
+
+let inputs = [1.0, 0.0, 0.0, 1.0];
+for _ in 0..100 {
+    auto.train_reconstruction(&inputs, 0.5);
+}
+
+// Continue with the pretrained encoder.
+let net = auto.into_encoder().chain(Full::<2, 1, _>::new(Logistic, gen));
+```
+*/
+
+use std::any::Any;
+
+use rann_traits::{
+    compose::{Chain, ChainInter, Shared},
+    deriv::Deriv,
+    inspect::{DotGraph, Inspect, LayerView},
+    params::Parameterized,
+    Intermediate, Network, Scalar, Terminal,
+};
+
+use crate::{error::SquareError, Full, TiedFull};
+
+/// Trait for networks that can construct their mirror image: a network mapping their outputs
+/// back to their inputs.
+pub trait Mirror: Network {
+    /// The type of the mirrored network.
+    type Mirror: Network<In = Self::Out, Out = Self::In>;
+
+    /// Constructs the mirrored network, with its parameters generated by `gen`.
+    fn mirror<T, F, G>(&self, gen: T) -> Self::Mirror
+    where
+        T: Into<(F, G)> + Clone,
+        F: FnMut(usize, usize) -> Scalar,
+        G: FnMut(usize) -> Scalar;
+}
+
+// A fully connected layer is mirrored by an independent layer with the same activation.
+impl<const NUM_IN: usize, const NUM_OUT: usize, A> Mirror for Full<NUM_IN, NUM_OUT, A>
+where
+    A: Deriv<In = Scalar, Out = Scalar> + Clone,
+{
+    type Mirror = Full<NUM_OUT, NUM_IN, A>;
+
+    fn mirror<T, F, G>(&self, gen: T) -> Self::Mirror
+    where
+        T: Into<(F, G)> + Clone,
+        F: FnMut(usize, usize) -> Scalar,
+        G: FnMut(usize) -> Scalar,
+    {
+        Full::new(self.activation().clone(), gen)
+    }
+}
+
+// A shared fully connected layer is mirrored by a layer with tied weights.
+impl<const NUM_IN: usize, const NUM_OUT: usize, A> Mirror for Shared<Full<NUM_IN, NUM_OUT, A>>
+where
+    A: Deriv<In = Scalar, Out = Scalar> + Clone,
+{
+    type Mirror = TiedFull<NUM_OUT, NUM_IN, A, A>;
+
+    fn mirror<T, F, G>(&self, gen: T) -> Self::Mirror
+    where
+        T: Into<(F, G)> + Clone,
+        F: FnMut(usize, usize) -> Scalar,
+        G: FnMut(usize) -> Scalar,
+    {
+        let activation = self.borrow().activation().clone();
+        let (_, bias_gen) = gen.into();
+        TiedFull::new(self.clone(), activation, bias_gen)
+    }
+}
+
+// A chain is mirrored by chaining the mirrors in reverse order.
+impl<T, U> Mirror for Chain<T, U>
+where
+    T: Mirror,
+    U: Mirror<In = T::Out>,
+{
+    type Mirror = Chain<U::Mirror, T::Mirror>;
+
+    fn mirror<V, F, G>(&self, gen: V) -> Self::Mirror
+    where
+        V: Into<(F, G)> + Clone,
+        F: FnMut(usize, usize) -> Scalar,
+        G: FnMut(usize) -> Scalar,
+    {
+        self.second
+            .mirror(gen.clone())
+            .chain(self.first.mirror(gen))
+    }
+}
+
+/// An encoder chained to its mirrored decoder, trained to reconstruct its inputs.
+pub struct Autoencoder<E, D> {
+    /// The encoder, followed by the decoder.
+    pub net: Chain<E, D>,
+}
+
+impl<E> Autoencoder<E, E::Mirror>
+where
+    E: Mirror,
+{
+    /// Creates an autoencoder from `encoder` and its mirror, with the parameters of the decoder
+    /// generated by `gen`.
+    pub fn new<T, F, G>(encoder: E, gen: T) -> Self
+    where
+        T: Into<(F, G)> + Clone,
+        F: FnMut(usize, usize) -> Scalar,
+        G: FnMut(usize) -> Scalar,
+    {
+        let decoder = encoder.mirror(gen);
+        Self {
+            net: encoder.chain(decoder),
+        }
+    }
+}
+
+impl<E, D> Autoencoder<E, D> {
+    /// Borrows the encoder.
+    pub fn encoder(&self) -> &E {
+        &self.net.first
+    }
+
+    /// Borrows the decoder.
+    pub fn decoder(&self) -> &D {
+        &self.net.second
+    }
+
+    /// Returns the (trained) encoder, discarding the decoder.
+    pub fn into_encoder(self) -> E {
+        self.net.first
+    }
+}
+
+impl<E, D, const N: usize> Autoencoder<E, D>
+where
+    E: Network<In = [Scalar; N]>,
+    D: Network<In = E::Out, Out = [Scalar; N]>,
+{
+    /// Trains the autoencoder to reconstruct `inputs` and returns the reconstruction error before
+    /// training, measured by a [`SquareError`].
+    pub fn train_reconstruction(&mut self, inputs: &[Scalar; N], learning_rate: Scalar) -> Scalar {
+        let mut error = SquareError { expected: *inputs };
+        let inter = self.net.intermediate(inputs);
+        let err = error.intermediate(inter.output());
+        let gradients = error.train_error(inter.output(), &err, learning_rate);
+        self.net
+            .train_deriv(inputs, &inter, &gradients, learning_rate);
+        err[0]
+    }
+}
+
+impl<E, D> Network for Autoencoder<E, D>
+where
+    E: Network,
+    D: Network<In = E::Out>,
+{
+    type In = E::In;
+
+    type Out = D::Out;
+
+    type Inter = ChainInter<E::Inter, D::Inter>;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        self.net.intermediate(inputs)
+    }
+
+    fn train_deriv(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        self.net
+            .train_deriv(inputs, intermediate, gradients, learning_rate)
+    }
+
+    fn find_layer(&self, name: &str) -> Option<&dyn Any> {
+        self.net.find_layer(name)
+    }
+
+    fn find_layer_mut(&mut self, name: &str) -> Option<&mut dyn Any> {
+        self.net.find_layer_mut(name)
+    }
+}
+
+impl<E, D> Inspect for Autoencoder<E, D>
+where
+    E: Inspect,
+    D: Inspect<In = E::Out>,
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        self.net.visit_layers(intermediate, f);
+    }
+
+    fn write_dot(
+        &self,
+        intermediate: &Self::Inter,
+        dot: &mut DotGraph,
+        inputs: Vec<usize>,
+    ) -> Vec<usize> {
+        self.net.write_dot(intermediate, dot, inputs)
+    }
+}
+
+impl<E, D> Parameterized for Autoencoder<E, D>
+where
+    E: Parameterized,
+    D: Parameterized,
+{
+    fn num_params(&self) -> usize {
+        self.net.num_params()
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        self.net.write_params(params);
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        self.net.read_params(params);
+    }
+}
@@ -0,0 +1,210 @@
+/*!
+Baking trained models into binaries as Rust source, such that inference needs no file system and
+no parsing at runtime.
+
+A [`Baker`] converts the parameters of a network of fully connected layers, stored in an [`Npz`]
+as written by [`Full::write_npz()`](crate::Full::write_npz) with the index of every layer as
+prefix, into a Rust source file. The file declares a `static` array for the weights and the biases
+of every layer, and a `const fn` named after the model, that returns the network as a chain of
+[`FullView`](crate::FullView) layers borrowing those arrays.
+
+The source is typically written by a build script into `OUT_DIR`, and included into the crate
+with `include!`. The generated source uses the paths `rann_base` and `rann_traits`, which should
+be dependencies of the crate it is included into.
+
+# Examples
+Baking a model, as in a build script:
+```rust
+use rann_base::{activ::Logistic, bake::Baker, npy::Npz, testing, Full};
+use rann_traits::Network;
+
+let net = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1))
+    .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)));
+let mut npz = Npz::new();
+net.first.write_npz(&mut npz, "0.");
+net.second.write_npz(&mut npz, "1.");
+
+let source = Baker::new("xor")
+    .with_layer("rann_base::activ::Logistic")
+    .with_layer("rann_base::activ::Logistic")
+    .source(&npz)?;
+assert!(source.contains("pub const fn xor()"));
+assert!(source.contains("static XOR_WEIGHT_0: [rann_traits::Scalar; 6]"));
+// In a build script, the source would be written to `OUT_DIR` using `Baker::save()`.
+# Ok::<(), std::io::Error>(())
+```
+
+Using a baked model, which would be included from `concat!(env!("OUT_DIR"), "/xor.rs")`:
+```rust
+use rann_traits::Network;
+
+include!("../tests/data/xor_baked.rs");
+
+fn main() {
+    assert_eq!(xor().eval(&[1.0, 0.0]), [1.0]);
+    assert_eq!(xor().eval(&[1.0, 1.0]), [0.0]);
+}
+```
+*/
+
+use std::{
+    fmt::Write as _,
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use rann_traits::Scalar;
+
+use crate::{checkpoint::invalid, npy::Npz};
+
+/// Converts the parameters of a network into Rust source. See [module level documentation](self)
+/// for more info.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Baker {
+    name: String,
+    activations: Vec<String>,
+}
+
+impl Baker {
+    /// Creates a baker for a model without layers, of which the constructor is named `name`.
+    ///
+    /// # Panics
+    /// Panics if `name` is not a lowercase identifier.
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+        let mut chars = name.chars();
+        assert!(
+            chars
+                .next()
+                .is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+                && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_'),
+            "The name should be a lowercase identifier."
+        );
+        Self {
+            name,
+            activations: Vec::new(),
+        }
+    }
+
+    /// Adds a layer after the other layers, with an activation that is constructed by the
+    /// expression `activation`, such as `rann_base::activ::LeakyRelu(0.1)`. The expression must
+    /// be usable in a `const fn`, and should use absolute paths.
+    ///
+    /// The parameters of the layer are the arrays `{index}.weight` and `{index}.bias`, where
+    /// `index` is the number of layers before it.
+    pub fn with_layer(mut self, activation: impl Into<String>) -> Self {
+        self.activations.push(activation.into());
+        self
+    }
+
+    /// Writes the source of the model with the parameters in `npz` to `writer`.
+    ///
+    /// # Errors
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if the weights of a layer are
+    /// missing, if the arrays have the wrong shapes, if the outputs of a layer do not match the
+    /// inputs of the next layer, or if a parameter is not finite.
+    pub fn write_source(&self, npz: &Npz, mut writer: impl Write) -> io::Result<()> {
+        writer.write_all(self.source(npz)?.as_bytes())
+    }
+
+    /// Returns the source of the model with the parameters in `npz`, see
+    /// [`Baker::write_source()`].
+    pub fn source(&self, npz: &Npz) -> io::Result<String> {
+        if self.activations.is_empty() {
+            return Err(invalid("a baked model should have layers"));
+        }
+        let upper = self.name.to_ascii_uppercase();
+        let mut statics = String::new();
+        let mut layers = Vec::new();
+        let mut num_in = None;
+        for (i, activation) in self.activations.iter().enumerate() {
+            let name = format!("{i}.weight");
+            let weights = npz
+                .get(&name)
+                .ok_or_else(|| invalid(format!("missing array {name}")))?;
+            let &[outputs, inputs] = &weights.shape[..] else {
+                return Err(invalid(format!("array {name} is not a matrix")));
+            };
+            if let Some(num_in) = num_in.filter(|&num_in| num_in != inputs) {
+                return Err(invalid(format!(
+                    "layer {i} has {inputs} inputs, but the layer before it has {num_in} outputs"
+                )));
+            }
+            num_in = Some(outputs);
+            write_static(&mut statics, &format!("{upper}_WEIGHT_{i}"), &weights.data)?;
+            let name = format!("{i}.bias");
+            let biases = match npz.get(&name) {
+                Some(_) => {
+                    let biases = npz.get_shaped(&name, &[outputs])?;
+                    write_static(&mut statics, &format!("{upper}_BIAS_{i}"), biases)?;
+                    format!("&{upper}_BIAS_{i}")
+                }
+                None => "&[]".to_owned(),
+            };
+            layers.push((
+                inputs,
+                outputs,
+                format!(
+                    "rann_base::FullView::<{inputs}, {outputs}, _>::new({activation}, \
+                     &{upper}_WEIGHT_{i}, {biases})"
+                ),
+            ));
+        }
+
+        let num_in = layers[0].0;
+        let num_out = layers[layers.len() - 1].1;
+        let mut layers = layers.into_iter().map(|(_, _, layer)| layer);
+        let mut net = layers.next().unwrap();
+        for layer in layers {
+            net = format!("rann_traits::compose::Chain {{ first: {net}, second: {layer} }}");
+        }
+
+        let mut source = String::new();
+        writeln!(source, "// Baked by `rann_base::bake`, do not edit.").unwrap();
+        source.push_str(&statics);
+        writeln!(source).unwrap();
+        writeln!(source, "/// Returns the baked model `{}`.", self.name).unwrap();
+        writeln!(
+            source,
+            "pub const fn {}() -> impl rann_traits::inspect::Inspect<\
+             In = [rann_traits::Scalar; {num_in}], Out = [rann_traits::Scalar; {num_out}]> {{",
+            self.name
+        )
+        .unwrap();
+        writeln!(source, "    {net}").unwrap();
+        writeln!(source, "}}").unwrap();
+        Ok(source)
+    }
+
+    /// Writes the source of the model with the parameters in `npz` to a file at `path`, unless
+    /// it already holds that source, such that build scripts do not cause needless rebuilds.
+    pub fn save(&self, npz: &Npz, path: impl AsRef<Path>) -> io::Result<()> {
+        let source = self.source(npz)?;
+        if fs::read(&path).is_ok_and(|existing| existing == source.as_bytes()) {
+            return Ok(());
+        }
+        fs::write(path, source)
+    }
+}
+
+fn write_static(source: &mut String, name: &str, values: &[Scalar]) -> io::Result<()> {
+    if let Some(value) = values.iter().find(|value| !value.is_finite()) {
+        return Err(invalid(format!("{name} has a non-finite value {value}")));
+    }
+    write!(
+        source,
+        "static {name}: [rann_traits::Scalar; {}] = [",
+        values.len()
+    )
+    .unwrap();
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            source.push_str(", ");
+        }
+        // The debug representation of floats is a literal that rounds to the same value.
+        write!(source, "{value:?}").unwrap();
+    }
+    writeln!(source, "];").unwrap();
+    Ok(())
+}
@@ -0,0 +1,141 @@
+/*!
+Beam search decoding, for generating sequences with a network that scores the next token.
+
+A [`BeamSearch`] keeps the [`width`](BeamSearch::width) most likely sequences, the hypotheses,
+while extending them one token at a time. Every step, the step network is evaluated on the input
+of every hypothesis at once, with [`Network::eval_many()`], and its outputs are taken as the
+logits of the next token. The score of a hypothesis is the sum of the log-probabilities of its
+tokens. A hypothesis ends when it emits the [`end`](BeamSearch::end) token, or when it is
+[`max_len`](BeamSearch::max_len) tokens long.
+
+The step network is generic: the input of a hypothesis holds whatever state it needs, such as the
+last tokens for a feed-forward network or the hidden state for a recurrent one, and is updated by
+the closure passed to [`BeamSearch::search()`] for every token that is appended.
+
+A width of one is greedy decoding, which picks the most likely token every step.
+
+# Examples
+```rust
+use rann_base::{activ::LeakyRelu, beam::BeamSearch, FullView};
+
+// The logits of the next token, given the previous token as one-hot input. Token 2 ends a
+// sequence.
+let bigrams = FullView::<3, 3, _>::new(
+    LeakyRelu(1.0),
+    &[0.0, 2.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.5, 3.0],
+    &[],
+);
+let one_hot = |_: &[f32; 3], token: usize| std::array::from_fn(|i| (i == token) as u8 as f32);
+
+let search = BeamSearch { width: 2, max_len: 8, end: Some(2) };
+let best = &search.search(&bigrams, one_hot(&[0.0; 3], 0), one_hot)[0];
+assert_eq!(best.tokens, [1, 2]);
+assert!(best.finished);
+```
+*/
+
+use rann_traits::{Network, Scalar};
+
+use crate::activ::log_softmax;
+
+/// Decodes sequences with a step network, keeping the most likely hypotheses. See
+/// [module level documentation](self) for more info.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BeamSearch {
+    /// The number of hypotheses that are kept every step.
+    pub width: usize,
+    /// The maximum number of tokens of a hypothesis.
+    pub max_len: usize,
+    /// The token that ends a hypothesis, if any.
+    pub end: Option<usize>,
+}
+
+impl Default for BeamSearch {
+    fn default() -> Self {
+        Self {
+            width: 4,
+            max_len: 32,
+            end: None,
+        }
+    }
+}
+
+/// A sequence of tokens found by a [`BeamSearch`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hypothesis {
+    /// The tokens, including the end token if the hypothesis is finished.
+    pub tokens: Vec<usize>,
+    /// The sum of the log-probabilities of the tokens.
+    pub score: Scalar,
+    /// Whether the hypothesis ended with the end token.
+    pub finished: bool,
+}
+
+impl BeamSearch {
+    /// Decodes sequences with `net`, starting from the input `start`. The outputs of `net` are the
+    /// logits of the next token, and `next` returns the input after appending a token to a
+    /// hypothesis with an input.
+    ///
+    /// Returns at most [`Self::width`] hypotheses, from the highest to the lowest score.
+    ///
+    /// # Panics
+    /// Panics if the width is zero.
+    pub fn search<N, const V: usize>(
+        &self,
+        net: &N,
+        start: N::In,
+        mut next: impl FnMut(&N::In, usize) -> N::In,
+    ) -> Vec<Hypothesis>
+    where
+        N: Network<Out = [Scalar; V]>,
+    {
+        assert!(self.width > 0, "The width should be positive.");
+        let mut beams = vec![Hypothesis {
+            tokens: Vec::new(),
+            score: 0.0,
+            finished: false,
+        }];
+        let mut inputs = vec![start];
+        let mut finished = Vec::new();
+        for _ in 0..self.max_len {
+            if beams.is_empty() {
+                break;
+            }
+            let mut candidates = Vec::with_capacity(beams.len() * V);
+            for (beam, (hypothesis, logits)) in beams.iter().zip(net.eval_many(&inputs)).enumerate()
+            {
+                for (token, log_prob) in log_softmax(&logits).into_iter().enumerate() {
+                    candidates.push((beam, token, hypothesis.score + log_prob));
+                }
+            }
+            // The sort is stable, such that ties are broken by the earlier hypothesis and token.
+            candidates.sort_by(|a, b| b.2.total_cmp(&a.2));
+            candidates.truncate(self.width);
+
+            let mut next_beams = Vec::with_capacity(candidates.len());
+            let mut next_inputs = Vec::with_capacity(candidates.len());
+            for (beam, token, score) in candidates {
+                let mut tokens = beams[beam].tokens.clone();
+                tokens.push(token);
+                let is_end = self.end == Some(token);
+                let hypothesis = Hypothesis {
+                    tokens,
+                    score,
+                    finished: is_end,
+                };
+                if is_end {
+                    finished.push(hypothesis);
+                } else {
+                    next_inputs.push(next(&inputs[beam], token));
+                    next_beams.push(hypothesis);
+                }
+            }
+            beams = next_beams;
+            inputs = next_inputs;
+        }
+        finished.extend(beams);
+        finished.sort_by(|a, b| b.score.total_cmp(&a.score));
+        finished.truncate(self.width);
+        finished
+    }
+}
@@ -0,0 +1,226 @@
+/*!
+Decision surfaces of networks with two inputs, for visualizing what they learned.
+
+A [`DecisionSurface`] evaluates a network over a grid of points in the plane, such as the
+square around the samples of XOR or of the [`spirals`](crate::synthetic::spirals). It exports
+the output at every point as a CSV matrix, or as a grayscale PNG image, without any
+dependencies.
+
+# Examples
+```rust
+use rann_base::{activ::Logistic, boundary::DecisionSurface, error::SquareError, testing, Full};
+use rann_traits::Network;
+
+let mut net = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1))
+    .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)))
+    .chain(SquareError { expected: [0.0] });
+testing::assert_network_converges(&mut net, &testing::XOR, 2000, 1.0, 0.01);
+
+// The outputs before the error function.
+let surface = DecisionSurface::new(&net.first, (-0.5, 1.5), (-0.5, 1.5), 64, 64);
+// The corners of XOR are dark where the output is zero, and bright where it is one.
+let png = surface.to_png();
+assert_eq!(png[1..4], *b"PNG");
+let csv = surface.to_csv();
+assert_eq!(csv.lines().count(), 64);
+# let _ = (png, csv);
+```
+*/
+
+use std::fmt::Write;
+
+use rann_traits::{Network, Scalar};
+
+/// The outputs of a network over a grid of points in the plane. See
+/// [module level documentation](self) for more info.
+///
+/// The grid has `width` points along the first input, and `height` along the second. Like an
+/// image, its rows go from the largest value of the second input at the top to the smallest at
+/// the bottom, and every row goes from the smallest value of the first input to the largest.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecisionSurface {
+    /// The range of the first input, along the width of the grid.
+    pub x: (Scalar, Scalar),
+    /// The range of the second input, along the height of the grid.
+    pub y: (Scalar, Scalar),
+    /// The number of points along the first input.
+    pub width: usize,
+    /// The number of points along the second input.
+    pub height: usize,
+    /// The outputs at every point, row by row.
+    pub values: Vec<Scalar>,
+}
+
+impl DecisionSurface {
+    /// Evaluates `net` over a `width` by `height` grid of points, from `x.0` to `x.1` in the first
+    /// input and from `y.0` to `y.1` in the second, both inclusive.
+    pub fn new<N>(
+        net: &N,
+        x: (Scalar, Scalar),
+        y: (Scalar, Scalar),
+        width: usize,
+        height: usize,
+    ) -> Self
+    where
+        N: Network<In = [Scalar; 2], Out = [Scalar; 1]>,
+    {
+        Self::from_fn(|point| net.eval(point)[0], x, y, width, height)
+    }
+
+    /// Evaluates `f` over a grid of points like [`Self::new()`], such as to plot the
+    /// probability of one class of a network with more outputs.
+    pub fn from_fn(
+        mut f: impl FnMut(&[Scalar; 2]) -> Scalar,
+        x: (Scalar, Scalar),
+        y: (Scalar, Scalar),
+        width: usize,
+        height: usize,
+    ) -> Self {
+        let mut values = Vec::with_capacity(width * height);
+        for row in 0..height {
+            let b = lerp(y.1, y.0, row, height);
+            for column in 0..width {
+                values.push(f(&[lerp(x.0, x.1, column, width), b]));
+            }
+        }
+        Self {
+            x,
+            y,
+            width,
+            height,
+            values,
+        }
+    }
+
+    /// Returns the point of the grid at `row` and `column`.
+    pub fn point(&self, row: usize, column: usize) -> [Scalar; 2] {
+        [
+            lerp(self.x.0, self.x.1, column, self.width),
+            lerp(self.y.1, self.y.0, row, self.height),
+        ]
+    }
+
+    /// Returns the outputs as CSV, with a line of comma-separated values for every row.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        for row in self.values.chunks(self.width.max(1)) {
+            for (i, value) in row.iter().enumerate() {
+                let separator = if i == 0 { "" } else { "," };
+                write!(csv, "{separator}{value}").expect("Writing to a String should not fail.");
+            }
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Returns the outputs as an 8-bit grayscale PNG image with a pixel for every point, from
+    /// black for the smallest output to white for the largest. Non-finite outputs are black.
+    pub fn to_png(&self) -> Vec<u8> {
+        let finite = self.values.iter().copied().filter(|v| v.is_finite());
+        let min = finite.clone().fold(Scalar::INFINITY, Scalar::min);
+        let max = finite.fold(Scalar::NEG_INFINITY, Scalar::max);
+        let scale = if max > min { 255.0 / (max - min) } else { 0.0 };
+        let pixels: Vec<u8> = self
+            .values
+            .iter()
+            .map(|v| {
+                if v.is_finite() {
+                    ((v - min) * scale).round() as u8
+                } else {
+                    0
+                }
+            })
+            .collect();
+        png::grayscale(&pixels, self.width, self.height)
+    }
+}
+
+// Returns the `i`th of `n` evenly spaced values from `a` to `b`, inclusive.
+fn lerp(a: Scalar, b: Scalar, i: usize, n: usize) -> Scalar {
+    if n <= 1 {
+        (a + b) / 2.0
+    } else {
+        a + (b - a) * i as Scalar / (n - 1) as Scalar
+    }
+}
+
+// A minimal PNG encoder, storing the image data without compression.
+pub(crate) mod png {
+    const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+    // The largest length of a stored deflate block.
+    const MAX_BLOCK: usize = 65535;
+
+    // Encodes 8-bit grayscale `pixels`, row by row.
+    pub fn grayscale(pixels: &[u8], width: usize, height: usize) -> Vec<u8> {
+        let mut png = SIGNATURE.to_vec();
+
+        let mut header = Vec::with_capacity(13);
+        header.extend((width as u32).to_be_bytes());
+        header.extend((height as u32).to_be_bytes());
+        // Bit depth 8, grayscale, deflate, adaptive filtering, no interlacing.
+        header.extend([8, 0, 0, 0, 0]);
+        chunk(&mut png, b"IHDR", &header);
+
+        // Every row starts with its filter type, none.
+        let mut raw = Vec::with_capacity((width + 1) * height);
+        for row in pixels.chunks(width.max(1)).take(height) {
+            raw.push(0);
+            raw.extend(row);
+        }
+        chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+        chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+        png.extend((data.len() as u32).to_be_bytes());
+        let start = png.len();
+        png.extend(kind);
+        png.extend(data);
+        let crc = crc32(&png[start..]);
+        png.extend(crc.to_be_bytes());
+    }
+
+    // Wraps `data` in a zlib stream of stored deflate blocks.
+    fn zlib_stored(data: &[u8]) -> Vec<u8> {
+        let mut out = vec![0x78, 0x01];
+        let mut blocks = data.chunks(MAX_BLOCK).peekable();
+        if blocks.peek().is_none() {
+            out.extend([1, 0, 0, 0xFF, 0xFF]);
+        }
+        while let Some(block) = blocks.next() {
+            let last = blocks.peek().is_none();
+            out.push(last as u8);
+            let len = block.len() as u16;
+            out.extend(len.to_le_bytes());
+            out.extend((!len).to_le_bytes());
+            out.extend(block);
+        }
+        out.extend(adler32(data).to_be_bytes());
+        out
+    }
+
+    pub fn crc32(data: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for &byte in data {
+            crc ^= byte as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 == 1 {
+                    (crc >> 1) ^ 0xEDB8_8320
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+        !crc
+    }
+
+    fn adler32(data: &[u8]) -> u32 {
+        let (mut a, mut b) = (1u32, 0u32);
+        for &byte in data {
+            a = (a + byte as u32) % 65521;
+            b = (b + a) % 65521;
+        }
+        (b << 16) | a
+    }
+}
@@ -0,0 +1,337 @@
+/*!
+Post-training calibration of classifiers.
+
+Networks trained with a [`CrossEntropy`](crate::error::CrossEntropy) loss tend to be
+overconfident: the softmax of their logits assigns more probability to the predicted class than
+its actual accuracy. Temperature scaling (Guo et al., 2017) divides the logits by a single
+temperature, fitted after training to minimize the cross-entropy on a validation set. It leaves
+the predicted classes unchanged, but makes the predicted probabilities reflect how often they are
+right.
+
+- [`fit_temperature()`] fits the temperature to the logits of a network on a validation set,
+- [`Calibrated`] wraps a network, dividing its logits by the temperature at inference,
+- [`ReliabilityDiagram`] bins predictions by their confidence and compares it to their accuracy,
+  for plotting and for the expected calibration error.
+
+# Examples
+```rust
+use rann_base::{
+    calibration::{Calibrated, ReliabilityDiagram},
+    Full,
+};
+use rann_traits::Scalar;
+
+// A classifier with logits that are three times too large: for an input `x`, the first class is
+// right with probability `logistic(2x)`, but the classifier predicts `logistic(6x)`.
+let linear = (|x: Scalar| x, |_: Scalar| 1.0);
+let net = Full::<1, 2, _>::new(linear, (|r, _| if r == 0 { 3.0 } else { -3.0 }, |_| 0.0));
+let validation: Vec<_> = (-10..=10)
+    .flat_map(|i| {
+        let x = i as Scalar / 10.0;
+        let p = 1.0 / (1.0 + (-2.0 * x).exp());
+        (0..100).map(move |j| {
+            let first = (j as Scalar) < 100.0 * p;
+            ([x], if first { [1.0, 0.0] } else { [0.0, 1.0] })
+        })
+    })
+    .collect();
+
+let before = ReliabilityDiagram::new(&net, &validation, 10);
+let calibrated = Calibrated::fit(net, &validation);
+assert!((calibrated.temperature - 3.0).abs() < 0.2);
+let after = ReliabilityDiagram::new(&calibrated, &validation, 10);
+assert!(after.expected_calibration_error() < before.expected_calibration_error());
+println!("{}", after.to_csv());
+```
+*/
+
+use std::{any::Any, fmt::Write};
+
+use rann_traits::{
+    inspect::{DotGraph, Inspect, LayerView},
+    params::Parameterized,
+    Intermediate, Network, Scalar,
+};
+
+use crate::activ::softmax;
+
+/// The smallest and largest temperatures [`fit_temperature()`] considers.
+pub const TEMPERATURE_RANGE: (Scalar, Scalar) = (0.01, 100.0);
+
+/// Returns the mean cross-entropy between the softmax of `logits` divided by `temperature`, and
+/// the `targets`: the probability of each class, such as a one-hot vector.
+pub fn nll<const N: usize>(logits: &[([Scalar; N], [Scalar; N])], temperature: Scalar) -> Scalar {
+    let total: Scalar = logits
+        .iter()
+        .map(|(logits, targets)| {
+            let probs = softmax(&logits.map(|l| l / temperature));
+            probs
+                .iter()
+                .zip(targets)
+                .filter(|(_, t)| **t != 0.0)
+                .map(|(p, t)| -t * p.max(Scalar::MIN_POSITIVE).ln())
+                .sum::<Scalar>()
+        })
+        .sum();
+    total / logits.len().max(1) as Scalar
+}
+
+/// Fits the temperature minimizing the cross-entropy of `net` on `validation`, a dataset of
+/// inputs and target class probabilities, where `net` returns logits.
+///
+/// The temperature is searched in [`TEMPERATURE_RANGE`] with a golden-section search over its
+/// logarithm, as the cross-entropy has a single minimum in the temperature.
+pub fn fit_temperature<N, const C: usize>(net: &N, validation: &[(N::In, [Scalar; C])]) -> Scalar
+where
+    N: Network<Out = [Scalar; C]>,
+{
+    let logits: Vec<_> = validation
+        .iter()
+        .map(|(inputs, targets)| (net.eval(inputs), *targets))
+        .collect();
+    let loss = |log_t: Scalar| nll(&logits, log_t.exp());
+
+    const INV_PHI: Scalar = 0.618_034;
+    let (mut lo, mut hi) = (TEMPERATURE_RANGE.0.ln(), TEMPERATURE_RANGE.1.ln());
+    let (mut a, mut b) = (hi - INV_PHI * (hi - lo), lo + INV_PHI * (hi - lo));
+    let (mut loss_a, mut loss_b) = (loss(a), loss(b));
+    while hi - lo > 1e-4 {
+        if loss_a <= loss_b {
+            (hi, b, loss_b) = (b, a, loss_a);
+            a = hi - INV_PHI * (hi - lo);
+            loss_a = loss(a);
+        } else {
+            (lo, a, loss_a) = (a, b, loss_b);
+            b = lo + INV_PHI * (hi - lo);
+            loss_b = loss(b);
+        }
+    }
+    ((lo + hi) / 2.0).exp()
+}
+
+/// A classifier whose logits are divided by a temperature. See
+/// [module level documentation](self) for more info.
+///
+/// Training passes through to the wrapped network, with the gradients scaled accordingly.
+#[derive(Clone, Debug)]
+pub struct Calibrated<T> {
+    /// The wrapped network, returning logits.
+    pub net: T,
+    /// The temperature the logits are divided by.
+    pub temperature: Scalar,
+}
+
+impl<T> Calibrated<T> {
+    /// Wraps `net`, dividing its logits by `temperature`.
+    pub fn new(net: T, temperature: Scalar) -> Self {
+        Self { net, temperature }
+    }
+
+    /// Wraps `net` with the temperature fitted on `validation`, see [`fit_temperature()`].
+    pub fn fit<const C: usize>(net: T, validation: &[(T::In, [Scalar; C])]) -> Self
+    where
+        T: Network<Out = [Scalar; C]>,
+    {
+        let temperature = fit_temperature(&net, validation);
+        Self { net, temperature }
+    }
+
+    /// Returns the calibrated probability of each class for `inputs`.
+    pub fn probabilities<const C: usize>(&self, inputs: &T::In) -> [Scalar; C]
+    where
+        T: Network<Out = [Scalar; C]>,
+    {
+        softmax(&self.eval(inputs))
+    }
+}
+
+/// The intermediate calculations for an evaluation of [`Calibrated`].
+#[derive(Clone, Debug)]
+pub struct CalibratedInter<I, const C: usize> {
+    /// The intermediate calculations of the wrapped network.
+    pub inner: I,
+    logits: [Scalar; C],
+}
+
+impl<I, const C: usize> Intermediate for CalibratedInter<I, C> {
+    type Out = [Scalar; C];
+
+    fn output(&self) -> &Self::Out {
+        &self.logits
+    }
+
+    fn into_output(self) -> Self::Out {
+        self.logits
+    }
+}
+
+impl<T, const C: usize> Network for Calibrated<T>
+where
+    T: Network<Out = [Scalar; C]>,
+{
+    type In = T::In;
+
+    type Out = [Scalar; C];
+
+    type Inter = CalibratedInter<T::Inter, C>;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        let inner = self.net.intermediate(inputs);
+        let logits = inner.output().map(|l| l / self.temperature);
+        CalibratedInter { inner, logits }
+    }
+
+    fn train_deriv(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        let gradients = gradients.map(|g| g / self.temperature);
+        self.net
+            .train_deriv(inputs, &intermediate.inner, &gradients, learning_rate)
+    }
+
+    fn find_layer(&self, name: &str) -> Option<&dyn Any> {
+        self.net.find_layer(name)
+    }
+
+    fn find_layer_mut(&mut self, name: &str) -> Option<&mut dyn Any> {
+        self.net.find_layer_mut(name)
+    }
+}
+
+impl<T, const C: usize> Inspect for Calibrated<T>
+where
+    T: Inspect<Out = [Scalar; C]>,
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        self.net.visit_layers(&intermediate.inner, f);
+    }
+
+    fn write_dot(
+        &self,
+        intermediate: &Self::Inter,
+        dot: &mut DotGraph,
+        inputs: Vec<usize>,
+    ) -> Vec<usize> {
+        self.net.write_dot(&intermediate.inner, dot, inputs)
+    }
+}
+
+// The temperature is fitted separately, so it is not a parameter.
+impl<T: Parameterized> Parameterized for Calibrated<T> {
+    fn num_params(&self) -> usize {
+        self.net.num_params()
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        self.net.write_params(params);
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        self.net.read_params(params);
+    }
+}
+
+/// A bin of a [`ReliabilityDiagram`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Bin {
+    /// The number of predictions in the bin.
+    pub count: usize,
+    /// The mean confidence of the predictions: the probability of the predicted class.
+    pub confidence: Scalar,
+    /// The fraction of the predictions that were right.
+    pub accuracy: Scalar,
+}
+
+/// The predictions of a classifier, binned by their confidence. A calibrated classifier has an
+/// accuracy equal to its confidence in every bin.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReliabilityDiagram {
+    /// The bins, of equal width from a confidence of zero to one.
+    pub bins: Vec<Bin>,
+}
+
+impl ReliabilityDiagram {
+    /// Bins the predictions of `net` on `dataset` into `num_bins` bins, where `net` returns
+    /// logits and the predicted class is right if it is the most probable target class.
+    ///
+    /// # Panics
+    /// Panics if `num_bins` is zero.
+    pub fn new<N, const C: usize>(
+        net: &N,
+        dataset: &[(N::In, [Scalar; C])],
+        num_bins: usize,
+    ) -> Self
+    where
+        N: Network<Out = [Scalar; C]>,
+    {
+        assert!(
+            num_bins > 0,
+            "A reliability diagram needs at least one bin."
+        );
+        let mut bins = vec![Bin::default(); num_bins];
+        for (inputs, targets) in dataset {
+            let probs = softmax(&net.eval(inputs));
+            let (predicted, confidence) = argmax(&probs);
+            let right = argmax(targets).0 == predicted;
+            let bin = &mut bins[((confidence * num_bins as Scalar) as usize).min(num_bins - 1)];
+            bin.count += 1;
+            bin.confidence += confidence;
+            bin.accuracy += right as u8 as Scalar;
+        }
+        for bin in bins.iter_mut().filter(|bin| bin.count > 0) {
+            bin.confidence /= bin.count as Scalar;
+            bin.accuracy /= bin.count as Scalar;
+        }
+        Self { bins }
+    }
+
+    /// Returns the expected calibration error: the mean absolute difference between the
+    /// confidence and the accuracy of the bins, weighted by the number of predictions in them.
+    pub fn expected_calibration_error(&self) -> Scalar {
+        let total: usize = self.bins.iter().map(|bin| bin.count).sum();
+        self.bins
+            .iter()
+            .map(|bin| bin.count as Scalar * (bin.confidence - bin.accuracy).abs())
+            .sum::<Scalar>()
+            / total.max(1) as Scalar
+    }
+
+    /// Returns the bins as CSV, with a header and a row of the confidence range, count, mean
+    /// confidence and accuracy of every bin, for plotting.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("lower,upper,count,confidence,accuracy\n");
+        let width = 1.0 / self.bins.len() as Scalar;
+        for (i, bin) in self.bins.iter().enumerate() {
+            let lower = i as Scalar * width;
+            writeln!(
+                csv,
+                "{lower},{},{},{},{}",
+                lower + width,
+                bin.count,
+                bin.confidence,
+                bin.accuracy
+            )
+            .expect("Writing to a String should not fail.");
+        }
+        csv
+    }
+}
+
+// Returns the index and value of the largest element, the first if there are several.
+fn argmax(values: &[Scalar]) -> (usize, Scalar) {
+    values
+        .iter()
+        .copied()
+        .enumerate()
+        .fold((0, Scalar::NEG_INFINITY), |best, (i, v)| {
+            if v > best.1 {
+                (i, v)
+            } else {
+                best
+            }
+        })
+}
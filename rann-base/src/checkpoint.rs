@@ -0,0 +1,306 @@
+/*!
+Periodic checkpointing of training runs.
+
+A [`Checkpoint`] holds the parameters of a [`Parameterized`] network together with the state of
+the [`Trainer`](crate::train::Trainer) training it: the number of completed epochs, the learning
+rate, and the error history. A [`Checkpointer`] writes a checkpoint to a directory every few
+epochs, and deletes old checkpoints according to its retention policy: the last few checkpoints
+are kept, and optionally the checkpoint with the lowest validation error.
+
+Training can be resumed from the latest checkpoint in a directory using
+[`Trainer::resume()`](crate::train::Trainer::resume).
+
+# Format
+Checkpoints are stored in a little-endian binary format: the magic bytes `RANNCKPT`, a `u32`
+format version, the epoch as a `u64`, the learning rate as an `f32`, a `u8` flag followed by the
+validation error as an `f32`, and finally the error history and the parameters, each as a `u64`
+length followed by that many `f32`s.
+*/
+
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use rann_traits::{params::Parameterized, LearningRate, Scalar};
+
+const MAGIC: &[u8; 8] = b"RANNCKPT";
+const VERSION: u32 = 1;
+const EXTENSION: &str = "ckpt";
+const PREFIX: &str = "checkpoint-";
+
+/// The state of a training run after an epoch.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Checkpoint {
+    /// The number of completed epochs.
+    pub epoch: usize,
+    /// The learning rate used for the next epoch.
+    pub learning_rate: LearningRate,
+    /// The mean training error of every completed epoch.
+    pub errors: Vec<Scalar>,
+    /// The error on the validation set after the last epoch, if any.
+    pub validation_error: Option<Scalar>,
+    /// The parameters of the network, see [`Parameterized`].
+    pub params: Vec<Scalar>,
+}
+
+impl Checkpoint {
+    /// Restores the parameters of `net` from this checkpoint.
+    ///
+    /// # Panics
+    /// Panics if the number of parameters does not match the network.
+    pub fn restore<N: Parameterized>(&self, net: &mut N) {
+        net.read_params(&self.params);
+    }
+
+    /// Returns the error the checkpoint is ranked by: the validation error, or the training error
+    /// of the last epoch if there is no validation error.
+    pub fn score(&self) -> Option<Scalar> {
+        self.validation_error.or(self.errors.last().copied())
+    }
+
+    /// Writes the checkpoint to `writer`.
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&(self.epoch as u64).to_le_bytes())?;
+        writer.write_all(&self.learning_rate.get().to_le_bytes())?;
+        writer.write_all(&[self.validation_error.is_some() as u8])?;
+        writer.write_all(&self.validation_error.unwrap_or(0.0).to_le_bytes())?;
+        write_scalars(&mut writer, &self.errors)?;
+        write_scalars(&mut writer, &self.params)
+    }
+
+    /// Reads a checkpoint from `reader`.
+    ///
+    /// # Errors
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if the data is not a checkpoint
+    /// or has an unsupported version.
+    pub fn read_from(mut reader: impl Read) -> io::Result<Self> {
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("not a checkpoint"));
+        }
+        let version = u32::from_le_bytes(read_array(&mut reader)?);
+        if version != VERSION {
+            return Err(invalid(format!("unsupported checkpoint version {version}")));
+        }
+        let epoch = u64::from_le_bytes(read_array(&mut reader)?) as usize;
+        let learning_rate = LearningRate(Scalar::from_le_bytes(read_array(&mut reader)?));
+        let [has_validation] = read_array(&mut reader)?;
+        let validation_error = Scalar::from_le_bytes(read_array(&mut reader)?);
+        Ok(Self {
+            epoch,
+            learning_rate,
+            validation_error: (has_validation != 0).then_some(validation_error),
+            errors: read_scalars(&mut reader)?,
+            params: read_scalars(&mut reader)?,
+        })
+    }
+
+    /// Writes the checkpoint to the file at `path`, replacing it if it exists.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        // Write to a temporary file first, such that an interrupted write never replaces a
+        // valid checkpoint.
+        let path = path.as_ref();
+        let tmp = path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&tmp)?);
+        self.write_to(&mut writer)?;
+        writer.into_inner()?.sync_all()?;
+        fs::rename(tmp, path)
+    }
+
+    /// Reads a checkpoint from the file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+}
+
+pub(crate) fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+pub(crate) fn read_array<const N: usize>(reader: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+pub(crate) fn write_scalars(writer: &mut impl Write, values: &[Scalar]) -> io::Result<()> {
+    writer.write_all(&(values.len() as u64).to_le_bytes())?;
+    for value in values {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+pub(crate) fn read_scalars(reader: &mut impl Read) -> io::Result<Vec<Scalar>> {
+    let len = u64::from_le_bytes(read_array(reader)?);
+    let size = len
+        .checked_mul(4)
+        .ok_or_else(|| invalid(format!("invalid number of scalars {len}")))?;
+    let mut bytes = Vec::new();
+    // Limit the read to the data actually present, instead of trusting the length.
+    reader.take(size).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != size {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|b| Scalar::from_le_bytes(b.try_into().expect("Chunks should have length 4.")))
+        .collect())
+}
+
+/// The configuration of a [`Checkpointer`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct CheckpointConfig {
+    /// The directory to write checkpoints to. It is created if it does not exist.
+    pub dir: PathBuf,
+    /// The number of epochs between checkpoints.
+    pub every: usize,
+    /// The number of most recent checkpoints to keep.
+    pub keep_last: usize,
+    /// Whether to also keep the checkpoint with the lowest [score](Checkpoint::score).
+    pub keep_best: bool,
+}
+
+impl CheckpointConfig {
+    /// Creates a configuration writing a checkpoint to `dir` after every epoch, keeping the last
+    /// three and the best checkpoint.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            every: 1,
+            keep_last: 3,
+            keep_best: true,
+        }
+    }
+}
+
+/// Writes checkpoints to a directory and applies the retention policy. See
+/// [module level documentation](self) for more info.
+#[derive(Debug)]
+pub struct Checkpointer {
+    /// The configuration.
+    pub config: CheckpointConfig,
+    // The epoch, path and score of each checkpoint in the directory, ordered by epoch.
+    saved: Vec<(usize, PathBuf, Option<Scalar>)>,
+    // The checkpoints that could not be read when the checkpointer was created.
+    skipped: Vec<(PathBuf, io::Error)>,
+}
+
+impl Checkpointer {
+    /// Creates a checkpointer, taking the checkpoints already in the directory into account.
+    ///
+    /// Checkpoints that cannot be read, such as those corrupted by a crash, are skipped and
+    /// reported by [`Self::skipped()`]. Temporary files left by interrupted writes are deleted.
+    pub fn new(config: CheckpointConfig) -> io::Result<Self> {
+        fs::create_dir_all(&config.dir)?;
+        let mut saved = Vec::new();
+        let mut skipped = Vec::new();
+        for entry in fs::read_dir(&config.dir)? {
+            let path = entry?.path();
+            let Some(ext) = path.extension() else {
+                continue;
+            };
+            if ext == EXTENSION {
+                match Checkpoint::load(&path) {
+                    Ok(checkpoint) => saved.push((checkpoint.epoch, path, checkpoint.score())),
+                    Err(error) => skipped.push((path, error)),
+                }
+            } else if ext == "tmp" && is_checkpoint_name(&path) {
+                fs::remove_file(&path)?;
+            }
+        }
+        saved.sort_by_key(|(epoch, ..)| *epoch);
+        skipped.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(Self {
+            config,
+            saved,
+            skipped,
+        })
+    }
+
+    /// Returns the files with the checkpoint extension that could not be read when the
+    /// checkpointer was created, with the reason. They are left in the directory, but otherwise
+    /// ignored.
+    pub fn skipped(&self) -> impl Iterator<Item = (&Path, &io::Error)> {
+        self.skipped
+            .iter()
+            .map(|(path, error)| (path.as_path(), error))
+    }
+
+    /// Returns the path of the checkpoint of `epoch`.
+    pub fn path(&self, epoch: usize) -> PathBuf {
+        self.config
+            .dir
+            .join(format!("{PREFIX}{epoch:08}.{EXTENSION}"))
+    }
+
+    /// Returns whether a checkpoint should be written after `epoch` epochs.
+    pub fn is_due(&self, epoch: usize) -> bool {
+        self.config.every > 0 && epoch.is_multiple_of(self.config.every)
+    }
+
+    /// Writes `checkpoint` to the directory, deletes the checkpoints that are no longer retained,
+    /// and returns the path of the written checkpoint.
+    pub fn save(&mut self, checkpoint: &Checkpoint) -> io::Result<PathBuf> {
+        let path = self.path(checkpoint.epoch);
+        checkpoint.save(&path)?;
+        self.saved.retain(|(epoch, ..)| *epoch != checkpoint.epoch);
+        self.saved
+            .push((checkpoint.epoch, path.clone(), checkpoint.score()));
+        self.saved.sort_by_key(|(epoch, ..)| *epoch);
+
+        let best = self.config.keep_best.then(|| self.best_index()).flatten();
+        let first_kept = self.saved.len().saturating_sub(self.config.keep_last);
+        let saved = std::mem::take(&mut self.saved);
+        for (i, entry) in saved.into_iter().enumerate() {
+            if i >= first_kept || Some(i) == best {
+                self.saved.push(entry);
+            } else {
+                fs::remove_file(&entry.1)?;
+            }
+        }
+        Ok(path)
+    }
+
+    /// Returns the checkpoints in the directory, ordered by epoch.
+    pub fn checkpoints(&self) -> impl Iterator<Item = &Path> {
+        self.saved.iter().map(|(_, path, _)| path.as_path())
+    }
+
+    /// Loads the checkpoint with the highest epoch, if any.
+    pub fn latest(&self) -> io::Result<Option<Checkpoint>> {
+        self.saved
+            .last()
+            .map(|(_, path, _)| Checkpoint::load(path))
+            .transpose()
+    }
+
+    /// Loads the checkpoint with the lowest score, if any.
+    pub fn best(&self) -> io::Result<Option<Checkpoint>> {
+        self.best_index()
+            .map(|i| Checkpoint::load(&self.saved[i].1))
+            .transpose()
+    }
+
+    fn best_index(&self) -> Option<usize> {
+        self.saved
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (_, _, score))| Some((i, (*score)?)))
+            .filter(|(_, score)| !score.is_nan())
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, _)| i)
+    }
+}
+
+// Returns whether the file at `path` is named like the checkpoints written by a [`Checkpointer`].
+fn is_checkpoint_name(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with(PREFIX))
+}
@@ -0,0 +1,207 @@
+/*!
+Constraints on the weights of layers, applied after every training step.
+
+A [`WeightConstraint`] projects the weights of a layer back onto an allowed set after they have
+been updated: max-norm constraints limit the L2 norm of the incoming weights of every neuron, as
+used together with dropout, and non-negativity constraints clamp negative weights to zero, as
+used in non-negative networks. Biases are never constrained.
+
+Constraints are configured per layer, by wrapping the layer in a [`Constrained`] network. Layers
+support constraints by implementing [`Constrain`].
+
+# Examples
+```rust
+use rann_base::{
+    activ::Logistic,
+    constraint::{Constrained, WeightConstraint},
+    error::SquareError,
+    testing, train, Full,
+};
+use rann_traits::Network;
+
+let hidden = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1));
+let mut net = Constrained::new(hidden, WeightConstraint::max_norm(2.0).and_non_negative())
+    .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)))
+    .chain(SquareError { expected: [0.0] });
+for _ in 0..100 {
+    train::train_epoch(&mut net, &testing::XOR, 0.5);
+}
+```
+*/
+
+use std::any::Any;
+
+use rann_traits::{
+    inspect::{DotGraph, Inspect, LayerView},
+    params::Parameterized,
+    Network, Scalar, Supervised,
+};
+
+/// Constraints on the weights of a layer. See [module level documentation](self) for more info.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WeightConstraint {
+    /// The largest allowed L2 norm of the incoming weights of each neuron. Weights of neurons
+    /// with a larger norm are scaled down to this norm.
+    pub max_norm: Option<Scalar>,
+    /// Whether negative weights are clamped to zero.
+    pub non_negative: bool,
+}
+
+impl WeightConstraint {
+    /// Creates a max-norm constraint.
+    pub fn max_norm(max_norm: Scalar) -> Self {
+        Self {
+            max_norm: Some(max_norm),
+            non_negative: false,
+        }
+    }
+
+    /// Creates a non-negativity constraint.
+    pub fn non_negative() -> Self {
+        Self {
+            max_norm: None,
+            non_negative: true,
+        }
+    }
+
+    /// Adds a non-negativity constraint.
+    pub fn and_non_negative(self) -> Self {
+        Self {
+            non_negative: true,
+            ..self
+        }
+    }
+
+    /// Applies the constraint to the incoming `weights` of a single neuron.
+    ///
+    /// Negative weights are clamped before the norm is limited, such that both constraints hold
+    /// afterwards.
+    pub fn apply(&self, weights: &mut [Scalar]) {
+        if self.non_negative {
+            for w in weights.iter_mut() {
+                *w = w.max(0.0);
+            }
+        }
+        if let Some(max_norm) = self.max_norm {
+            let norm = weights.iter().map(|w| w * w).sum::<Scalar>().sqrt();
+            if norm > max_norm {
+                let scale = max_norm / norm;
+                for w in weights {
+                    *w *= scale;
+                }
+            }
+        }
+    }
+}
+
+/// Trait implemented by layers whose weights can be constrained.
+pub trait Constrain {
+    /// Projects the weights of this layer onto the set allowed by `constraint`.
+    fn constrain(&mut self, constraint: &WeightConstraint);
+}
+
+/// A layer whose weights are constrained after every training step. See
+/// [module level documentation](self) for more info.
+#[derive(Clone, Debug)]
+pub struct Constrained<T> {
+    /// The constrained layer.
+    pub net: T,
+    /// The constraint on its weights.
+    pub constraint: WeightConstraint,
+}
+
+impl<T: Constrain> Constrained<T> {
+    /// Wraps `net`, and applies `constraint` to it right away.
+    pub fn new(mut net: T, constraint: WeightConstraint) -> Self {
+        net.constrain(&constraint);
+        Self { net, constraint }
+    }
+}
+
+impl<T> Network for Constrained<T>
+where
+    T: Network + Constrain,
+{
+    type In = T::In;
+
+    type Out = T::Out;
+
+    type Inter = T::Inter;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        self.net.intermediate(inputs)
+    }
+
+    fn train_deriv(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        let gradients = self
+            .net
+            .train_deriv(inputs, intermediate, gradients, learning_rate);
+        self.net.constrain(&self.constraint);
+        gradients
+    }
+
+    fn eval(&self, inputs: &Self::In) -> Self::Out {
+        self.net.eval(inputs)
+    }
+
+    fn find_layer(&self, name: &str) -> Option<&dyn Any> {
+        self.net.find_layer(name)
+    }
+
+    fn find_layer_mut(&mut self, name: &str) -> Option<&mut dyn Any> {
+        self.net.find_layer_mut(name)
+    }
+}
+
+impl<T> Supervised for Constrained<T>
+where
+    T: Supervised + Constrain,
+{
+    type Target = T::Target;
+
+    fn set_target(&mut self, target: &Self::Target) {
+        self.net.set_target(target);
+    }
+}
+
+impl<T> Inspect for Constrained<T>
+where
+    T: Inspect + Constrain,
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        self.net.visit_layers(intermediate, f);
+    }
+
+    fn write_dot(
+        &self,
+        intermediate: &Self::Inter,
+        dot: &mut DotGraph,
+        inputs: Vec<usize>,
+    ) -> Vec<usize> {
+        self.net.write_dot(intermediate, dot, inputs)
+    }
+}
+
+// Parameters read from elsewhere are not constrained until the next training step.
+impl<T> Parameterized for Constrained<T>
+where
+    T: Parameterized,
+{
+    fn num_params(&self) -> usize {
+        self.net.num_params()
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        self.net.write_params(params);
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        self.net.read_params(params);
+    }
+}
@@ -1,24 +1,381 @@
-use nalgebra::SMatrix;
-use rann_traits::{deriv::Deriv, Network, Scalar};
+/*!
+Convolutional layers over [images](crate::image::Image).
 
-pub struct Convolutional<const NUM_IN: usize, const NUM_OUT: usize, const ND: usize, A> {
-    weights: SMatrix<Scalar, 1, NUM_IN>,
-    bias: Scalar,
+A [`Convolutional`] layer slides a kernel over its inputs, and only keeps the outputs where the
+kernel fits entirely, so its outputs are smaller than its inputs. A [`TransposedConv`] layer does
+the opposite: every input scatters the kernel over the outputs, so its outputs are larger than its
+inputs. Chained, they form a convolutional autoencoder.
+
+The shapes of the inputs and outputs are part of the types of the layers, such that chaining
+layers of different shapes does not compile. The shape of the kernel, and optionally the stride,
+padding and dilation, are given as a [`ConvConfig`] when creating a layer, and must match the
+shapes of the images, which [`ConvConfig::output_shape()`] and
+[`ConvConfig::transposed_output_shape()`] help to compute. All shapes are given as
+`[width, height]`.
+
+The images may have several channels, such as the colors of a photo, or the features found by a
+previous layer. Every output channel of a [`Convolutional`] layer has its own kernel over all input
+channels, and its own bias. Likewise, every input channel of a [`TransposedConv`] layer scatters its
+own kernel over all output channels, which each have a bias.
+
+By default, the layers slide the kernel over the inputs element by element. With
+[`ConvAlgorithm::Im2col`], they instead copy the patches of the inputs under the kernel into the
+columns of a matrix, and multiply it with the kernel. This is faster for all but the smallest
+kernels: in the `conv` benchmark of this crate over a 32x32 input, run with
+`cargo bench --bench conv`, the direct algorithm is slightly faster for 1x1 kernels, and im2col is
+faster from 3x3 kernels on, up to twice as fast for 9x9 kernels or for several channels. The
+matrix is stored in a scratch buffer per thread, which is reused by all layers on that thread.
+
+# Examples
+```rust
+use rann_base::{
+    activ::Logistic,
+    conv::{ConvConfig, Convolutional, TransposedConv},
+    image::Image,
+    testing,
+};
+use rann_traits::{deriv::Elements, Intermediate, Network};
+
+// A 5x5 image is encoded to 3x3 by a 3x3 kernel, and decoded back to 5x5.
+let config = ConvConfig::new([3, 3]);
+assert_eq!(config.output_shape([5, 5]), Some([3, 3]));
+assert_eq!(config.transposed_output_shape([3, 3]), Some([5, 5]));
+let encoder = Convolutional::<5, 5, 3, 3, _>::new(Logistic, config, testing::seeded_gen(1));
+let decoder = TransposedConv::<3, 3, 5, 5, _>::new(Logistic, config, testing::seeded_gen(2));
+let mut net = encoder.chain(decoder);
+
+// Train the autoencoder to reconstruct vertical stripes, with the square error.
+let image = Image::<5, 5>::from_fn(|x, _, _| (x % 2) as f32);
+let error = |net: &dyn Fn(&Image<5, 5>) -> Image<5, 5>| -> f32 {
+    let reconstruction = net(&image);
+    reconstruction.zip_elements(&image, |o, t| (o - t).powi(2)).as_slice().iter().sum()
+};
+let before = error(&|x| net.eval(x));
+for _ in 0..100 {
+    let inter = net.intermediate(&image);
+    let gradients = inter.output().zip_elements(&image, |o, t| o - t);
+    net.train_deriv(&image, &inter, &gradients, 0.5);
+}
+assert!(error(&|x| net.eval(x)) < before);
+```
+
+A classifier of 5x5 images with three color channels, with four kernels that each span all three
+colors:
+```rust
+use rann_base::{
+    activ::Logistic,
+    conv::Convolutional,
+    error::SquareError,
+    image::{GlobalAvgPool, Image},
+    testing, Full,
+};
+use rann_traits::Network;
+
+let mut net = Convolutional::<5, 5, 3, 3, _, 3, 4>::new(Logistic, [3, 3], testing::seeded_gen(1))
+    .chain(GlobalAvgPool::<3, 3, 4>::new())
+    .chain(Full::<4, 1, _>::new(Logistic, testing::seeded_gen(2)))
+    .chain(SquareError { expected: [0.0] });
+// Red and blue images, with some texture.
+let color = |c| Image::from_fn(move |x, y, i| if i == c { ((x + y) % 2) as f32 } else { 0.0 });
+let dataset = [(color(0), [1.0]), (color(2), [0.0])];
+testing::assert_network_converges(&mut net, &dataset, 1000, 1.0, 0.01);
+```
+*/
+
+use std::cell::RefCell;
+
+use nalgebra::{DMatrixView, DMatrixViewMut, Dyn, MatrixView, MatrixViewMut};
+use rann_traits::{
+    deriv::{Deriv, Elementwise},
+    inspect::{short_type_name, Inspect, LayerView},
+    params::Parameterized,
+    Intermediate, Network, Scalar,
+};
+
+use crate::image::Image;
+
+/// How the kernel of a convolutional layer is placed over its inputs. See
+/// [module level documentation](self) for more info.
+///
+/// Like all shapes in this module, these are given as `[width, height]`.
+///
+/// # Examples
+/// ```rust
+/// use rann_base::conv::{ConvConfig, Padding};
+///
+/// let config = ConvConfig::new([3, 3]).with_stride([2, 2]);
+/// assert_eq!(config.output_shape([9, 8]), Some([4, 3]));
+/// let same = config.with_padding(Padding::Same);
+/// assert_eq!(same.output_shape([9, 8]), Some([5, 4]));
+/// // A dilation of two spreads the kernel over 5x5 pixels.
+/// let dilated = ConvConfig::new([3, 3]).with_dilation([2, 2]);
+/// assert_eq!(dilated.output_shape([9, 8]), Some([5, 4]));
+/// assert_eq!(dilated.output_shape([4, 4]), None);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConvConfig {
+    /// The shape of the kernel.
+    pub kernel: [usize; 2],
+    /// The distance between the positions of the kernel, one by default.
+    pub stride: [usize; 2],
+    /// How the inputs are padded with zeros, [`Padding::Valid`] by default.
+    pub padding: Padding,
+    /// The distance between the pixels under the elements of the kernel, one by default.
+    pub dilation: [usize; 2],
+}
+
+/// How the inputs of a convolutional layer are padded with zeros.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Padding {
+    /// No padding: the kernel is only placed where it fits entirely in the inputs.
+    #[default]
+    Valid,
+    /// Enough padding, evenly divided over both sides, for an output for every `stride` inputs,
+    /// such that a stride of one keeps the shape of the inputs.
+    Same,
+}
+
+impl ConvConfig {
+    /// Creates a configuration for a kernel of shape `kernel`, with a stride and dilation of one
+    /// and no padding.
+    pub fn new(kernel: [usize; 2]) -> Self {
+        Self {
+            kernel,
+            stride: [1; 2],
+            padding: Padding::Valid,
+            dilation: [1; 2],
+        }
+    }
+
+    /// Sets the stride.
+    pub fn with_stride(self, stride: [usize; 2]) -> Self {
+        Self { stride, ..self }
+    }
+
+    /// Sets the padding.
+    pub fn with_padding(self, padding: Padding) -> Self {
+        Self { padding, ..self }
+    }
+
+    /// Sets the dilation.
+    pub fn with_dilation(self, dilation: [usize; 2]) -> Self {
+        Self { dilation, ..self }
+    }
+
+    /// Returns the shape of the outputs of a [`Convolutional`] layer over inputs of shape `input`,
+    /// or `None` if the kernel, stride or dilation is zero, or if the inputs are empty or smaller
+    /// than the dilated kernel without padding.
+    pub fn output_shape(&self, input: [usize; 2]) -> Option<[usize; 2]> {
+        self.check()?;
+        let mut shape = [0; 2];
+        for d in 0..2 {
+            let span = self.span(d);
+            if input[d] == 0 {
+                return None;
+            }
+            shape[d] = match self.padding {
+                Padding::Valid if span > input[d] => return None,
+                Padding::Valid => (input[d] - span) / self.stride[d] + 1,
+                Padding::Same => input[d].div_ceil(self.stride[d]),
+            };
+        }
+        Some(shape)
+    }
+
+    /// Returns the shape of the outputs of a [`TransposedConv`] layer over inputs of shape `input`:
+    /// the smallest shape of inputs of a [`Convolutional`] layer with outputs of shape `input`.
+    /// Returns `None` if the kernel, stride or dilation is zero, or if the inputs are empty.
+    pub fn transposed_output_shape(&self, input: [usize; 2]) -> Option<[usize; 2]> {
+        self.check()?;
+        let mut shape = [0; 2];
+        for d in 0..2 {
+            if input[d] == 0 {
+                return None;
+            }
+            shape[d] = match self.padding {
+                Padding::Valid => (input[d] - 1) * self.stride[d] + self.span(d),
+                Padding::Same => (input[d] - 1) * self.stride[d] + 1,
+            };
+        }
+        Some(shape)
+    }
+
+    fn check(&self) -> Option<()> {
+        let mut sizes = self.kernel.iter().chain(&self.stride).chain(&self.dilation);
+        sizes.all(|size| *size > 0).then_some(())
+    }
+
+    // The number of inputs covered by the dilated kernel along dimension `d`.
+    fn span(&self, d: usize) -> usize {
+        self.dilation[d] * (self.kernel[d] - 1) + 1
+    }
+
+    // Returns the padding before the inputs along dimension `d`, for a convolution from `input`
+    // to `output` values.
+    fn padding_before(&self, d: usize, input: usize, output: usize) -> usize {
+        match self.padding {
+            Padding::Valid => 0,
+            Padding::Same => {
+                ((output - 1) * self.stride[d] + self.span(d)).saturating_sub(input) / 2
+            }
+        }
+    }
+}
+
+impl From<[usize; 2]> for ConvConfig {
+    fn from(kernel: [usize; 2]) -> Self {
+        Self::new(kernel)
+    }
+}
+
+/// The intermediate calculations for an evaluation of a layer with images of `W` by `H` pixels with
+/// `C` channels as outputs.
+#[derive(Clone, Debug)]
+pub struct ConvInter<const W: usize, const H: usize, const C: usize = 1> {
+    pub(crate) weighted_sums: Image<W, H, C>,
+    pub(crate) outputs: Image<W, H, C>,
+}
+
+impl<const W: usize, const H: usize, const C: usize> Intermediate for ConvInter<W, H, C> {
+    type Out = Image<W, H, C>;
+
+    fn output(&self) -> &Self::Out {
+        &self.outputs
+    }
+
+    fn into_output(self) -> Self::Out {
+        self.outputs
+    }
+}
+
+/// The algorithm used by a convolutional layer to compute its outputs and gradients. See
+/// [module level documentation](self) for more info.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConvAlgorithm {
+    /// Slides the kernel over the inputs, one element at a time.
+    #[default]
+    Direct,
+    /// Copies the patches of the inputs under the kernel into a matrix, and multiplies it with
+    /// the kernel.
+    Im2col,
+}
+
+/// A convolutional layer from `W` by `H` images with `C` channels to `OW` by `OH` images with `OC`
+/// channels, with a kernel and a bias for every output channel, and an activation function. See
+/// [module level documentation](self) for more info.
+///
+/// Every kernel spans all input channels, so every output channel is a weighted sum over all of
+/// them.
+#[derive(Clone, Debug)]
+pub struct Convolutional<
+    const W: usize,
+    const H: usize,
+    const OW: usize,
+    const OH: usize,
+    A,
+    const C: usize = 1,
+    const OC: usize = 1,
+> {
+    // The kernels of the output channels one after another, each row by row, with the weights of
+    // all input channels of every element together.
+    weights: Vec<[Scalar; C]>,
+    biases: [Scalar; OC],
     act: A,
-    dimensions: [usize; ND],
+    config: ConvConfig,
+    plan: Plan<C, OC>,
+    // The norm of the gradients over the parameters in the last training step.
+    grad_norm: Scalar,
 }
 
-impl<const NUM_IN: usize, const NUM_OUT: usize, const ND: usize, A> Network
-    for Convolutional<NUM_IN, NUM_OUT, ND, A>
+impl<
+        const W: usize,
+        const H: usize,
+        const OW: usize,
+        const OH: usize,
+        A,
+        const C: usize,
+        const OC: usize,
+    > Convolutional<W, H, OW, OH, A, C, OC>
+{
+    /// Creates a convolutional layer with the given activation and `config`, which is either a
+    /// [`ConvConfig`] or the shape of the kernel, and with the weights of the kernels and the
+    /// biases generated using the given generator functions.
+    ///
+    /// # Panics
+    /// Panics if [`ConvConfig::output_shape()`] is not `[OW, OH]` for inputs of `[W, H]`.
+    pub fn new<T, F, G>(activation: A, config: impl Into<ConvConfig>, gen: T) -> Self
+    where
+        T: Into<(F, G)>,
+        F: FnMut(usize, usize) -> Scalar,
+        G: FnMut(usize) -> Scalar,
+    {
+        let config = config.into();
+        let output = config.output_shape([W, H]).expect(INVALID_CONFIG);
+        assert_eq!(output, [OW, OH], "Outputs should be OW by OH.");
+        let geometry = Geometry::new(&config, [W, H], [OW, OH]);
+        let plan = Plan::new(geometry);
+        let (weights, biases) = kernel_params(&plan, gen);
+        Self {
+            weights,
+            biases,
+            act: activation,
+            config,
+            plan,
+            grad_norm: 0.0,
+        }
+    }
+
+    /// Returns the shape of the kernels.
+    pub fn kernel(&self) -> [usize; 2] {
+        self.config.kernel
+    }
+
+    /// Returns how the kernels are placed over the inputs.
+    pub fn config(&self) -> ConvConfig {
+        self.config
+    }
+
+    /// Uses `algorithm` to compute the outputs and gradients of this layer.
+    pub fn with_algorithm(mut self, algorithm: ConvAlgorithm) -> Self {
+        self.plan.set_algorithm(algorithm);
+        self
+    }
+
+    /// Returns the algorithm used to compute the outputs and gradients of this layer.
+    pub fn algorithm(&self) -> ConvAlgorithm {
+        self.plan.algorithm
+    }
+}
+
+impl<
+        const W: usize,
+        const H: usize,
+        const OW: usize,
+        const OH: usize,
+        A,
+        const C: usize,
+        const OC: usize,
+    > Network for Convolutional<W, H, OW, OH, A, C, OC>
 where
     A: Deriv<In = Scalar, Out = Scalar>,
 {
-    type In = [Scalar; NUM_IN];
-    type Out = [Scalar; NUM_OUT];
-    type Inter = [Scalar; NUM_OUT];
+    type In = Image<W, H, C>;
+    type Out = Image<OW, OH, OC>;
+    type Inter = ConvInter<OW, OH, OC>;
 
     fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
-        todo!()
+        let mut sums = Image {
+            pixels: [[self.biases; OW]; OH],
+        };
+        self.plan.gather(
+            &self.weights,
+            inputs.pixels.as_flattened(),
+            sums.pixels.as_flattened_mut(),
+        );
+        ConvInter {
+            outputs: Elementwise::new(&self.act).call(&sums),
+            weighted_sums: sums,
+        }
     }
 
     fn train_deriv(
@@ -32,6 +389,624 @@ where
         // The learning rate.
         learning_rate: Scalar,
     ) -> Self::In {
-        todo!()
+        let grad = Elementwise::new(&self.act).backprop(&intermediate.weighted_sums, gradients);
+        let mut weight_grads = vec![[0.0; C]; self.weights.len()];
+        let mut input_grads = Image::default();
+        self.plan.backprop_to_large(
+            &self.weights,
+            inputs.pixels.as_flattened(),
+            grad.pixels.as_flattened(),
+            &mut weight_grads,
+            input_grads.pixels.as_flattened_mut(),
+        );
+        let bias_grads = channel_sums(&grad);
+        self.grad_norm = update(
+            self.weights.as_flattened_mut(),
+            &mut self.biases,
+            weight_grads.as_flattened(),
+            &bias_grads,
+            learning_rate,
+        );
+        input_grads
+    }
+}
+
+impl<
+        const W: usize,
+        const H: usize,
+        const OW: usize,
+        const OH: usize,
+        A,
+        const C: usize,
+        const OC: usize,
+    > Inspect for Convolutional<W, H, OW, OH, A, C, OC>
+where
+    A: Deriv<In = Scalar, Out = Scalar>,
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        let kind = format!("Convolutional ({})", short_type_name::<A>());
+        f(&LayerView {
+            kind: &kind,
+            num_inputs: W * H * C,
+            params: &[self.weights.as_flattened(), &self.biases],
+            activations: intermediate.outputs.as_slice(),
+            gradient_norm: self.grad_norm,
+        });
+    }
+}
+
+// The weights of the kernels are followed by the biases.
+impl<
+        const W: usize,
+        const H: usize,
+        const OW: usize,
+        const OH: usize,
+        A,
+        const C: usize,
+        const OC: usize,
+    > Parameterized for Convolutional<W, H, OW, OH, A, C, OC>
+{
+    fn num_params(&self) -> usize {
+        self.weights.as_flattened().len() + OC
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        write_kernel_params(self.weights.as_flattened(), &self.biases, params);
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        read_kernel_params(self.weights.as_flattened_mut(), &mut self.biases, params);
+    }
+}
+
+/// A transposed convolutional (deconvolution) layer from `W` by `H` images with `C` channels to
+/// `OW` by `OH` images with `OC` channels, with a kernel for every input channel, a bias for every
+/// output channel, and an activation function. See [module level documentation](self) for more
+/// info.
+///
+/// Without the biases and activation, this is the transpose of the [`Convolutional`] layer with the
+/// same kernels from the outputs of this layer to its inputs: every input channel scatters its
+/// kernel over all output channels.
+#[derive(Clone, Debug)]
+pub struct TransposedConv<
+    const W: usize,
+    const H: usize,
+    const OW: usize,
+    const OH: usize,
+    A,
+    const C: usize = 1,
+    const OC: usize = 1,
+> {
+    // The kernels of the input channels, laid out like those of the corresponding convolution.
+    weights: Vec<[Scalar; OC]>,
+    biases: [Scalar; OC],
+    act: A,
+    config: ConvConfig,
+    plan: Plan<OC, C>,
+    // The norm of the gradients over the parameters in the last training step.
+    grad_norm: Scalar,
+}
+
+impl<
+        const W: usize,
+        const H: usize,
+        const OW: usize,
+        const OH: usize,
+        A,
+        const C: usize,
+        const OC: usize,
+    > TransposedConv<W, H, OW, OH, A, C, OC>
+{
+    /// Creates a transposed convolutional layer with the given activation and `config`, which is
+    /// either a [`ConvConfig`] or the shape of the kernel, and with the weights of the kernels and
+    /// the biases generated using the given generator functions.
+    ///
+    /// # Panics
+    /// Panics if [`ConvConfig::output_shape()`] is not `[W, H]` for inputs of `[OW, OH]`, as the
+    /// outputs of this layer are the inputs of the corresponding convolution. Its smallest
+    /// outputs are given by [`ConvConfig::transposed_output_shape()`].
+    pub fn new<T, F, G>(activation: A, config: impl Into<ConvConfig>, gen: T) -> Self
+    where
+        T: Into<(F, G)>,
+        F: FnMut(usize, usize) -> Scalar,
+        G: FnMut(usize) -> Scalar,
+    {
+        let config = config.into();
+        // The outputs are the inputs of the corresponding convolution.
+        let input = config.output_shape([OW, OH]).expect(INVALID_CONFIG);
+        assert_eq!(input, [W, H], "Inputs should be W by H.");
+        let geometry = Geometry::new(&config, [OW, OH], [W, H]);
+        let plan = Plan::new(geometry);
+        let (weights, biases) = kernel_params(&plan, gen);
+        Self {
+            weights,
+            biases,
+            act: activation,
+            config,
+            plan,
+            grad_norm: 0.0,
+        }
+    }
+
+    /// Returns the shape of the kernels.
+    pub fn kernel(&self) -> [usize; 2] {
+        self.config.kernel
+    }
+
+    /// Returns how the kernels are placed over the outputs.
+    pub fn config(&self) -> ConvConfig {
+        self.config
+    }
+
+    /// Uses `algorithm` to compute the outputs and gradients of this layer.
+    pub fn with_algorithm(mut self, algorithm: ConvAlgorithm) -> Self {
+        self.plan.set_algorithm(algorithm);
+        self
+    }
+
+    /// Returns the algorithm used to compute the outputs and gradients of this layer.
+    pub fn algorithm(&self) -> ConvAlgorithm {
+        self.plan.algorithm
+    }
+}
+
+impl<
+        const W: usize,
+        const H: usize,
+        const OW: usize,
+        const OH: usize,
+        A,
+        const C: usize,
+        const OC: usize,
+    > Network for TransposedConv<W, H, OW, OH, A, C, OC>
+where
+    A: Deriv<In = Scalar, Out = Scalar>,
+{
+    type In = Image<W, H, C>;
+    type Out = Image<OW, OH, OC>;
+    type Inter = ConvInter<OW, OH, OC>;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        let mut sums = Image {
+            pixels: [[self.biases; OW]; OH],
+        };
+        self.plan.scatter(
+            &self.weights,
+            inputs.pixels.as_flattened(),
+            sums.pixels.as_flattened_mut(),
+        );
+        ConvInter {
+            outputs: Elementwise::new(&self.act).call(&sums),
+            weighted_sums: sums,
+        }
+    }
+
+    fn train_deriv(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        let grad = Elementwise::new(&self.act).backprop(&intermediate.weighted_sums, gradients);
+        let mut weight_grads = vec![[0.0; OC]; self.weights.len()];
+        let mut input_grads = Image::default();
+        self.plan.backprop_to_small(
+            &self.weights,
+            grad.pixels.as_flattened(),
+            inputs.pixels.as_flattened(),
+            &mut weight_grads,
+            input_grads.pixels.as_flattened_mut(),
+        );
+        let bias_grads = channel_sums(&grad);
+        self.grad_norm = update(
+            self.weights.as_flattened_mut(),
+            &mut self.biases,
+            weight_grads.as_flattened(),
+            &bias_grads,
+            learning_rate,
+        );
+        input_grads
+    }
+}
+
+impl<
+        const W: usize,
+        const H: usize,
+        const OW: usize,
+        const OH: usize,
+        A,
+        const C: usize,
+        const OC: usize,
+    > Inspect for TransposedConv<W, H, OW, OH, A, C, OC>
+where
+    A: Deriv<In = Scalar, Out = Scalar>,
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        let kind = format!("TransposedConv ({})", short_type_name::<A>());
+        f(&LayerView {
+            kind: &kind,
+            num_inputs: W * H * C,
+            params: &[self.weights.as_flattened(), &self.biases],
+            activations: intermediate.outputs.as_slice(),
+            gradient_norm: self.grad_norm,
+        });
+    }
+}
+
+// The weights of the kernels are followed by the biases.
+impl<
+        const W: usize,
+        const H: usize,
+        const OW: usize,
+        const OH: usize,
+        A,
+        const C: usize,
+        const OC: usize,
+    > Parameterized for TransposedConv<W, H, OW, OH, A, C, OC>
+{
+    fn num_params(&self) -> usize {
+        self.weights.as_flattened().len() + OC
     }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        write_kernel_params(self.weights.as_flattened(), &self.biases, params);
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        read_kernel_params(self.weights.as_flattened_mut(), &mut self.biases, params);
+    }
+}
+
+// Generates the weights of every kernel of `plan`, and the biases.
+fn kernel_params<T, F, G, const LC: usize, const SC: usize, const N: usize>(
+    plan: &Plan<LC, SC>,
+    gen: T,
+) -> (Vec<[Scalar; LC]>, [Scalar; N])
+where
+    T: Into<(F, G)>,
+    F: FnMut(usize, usize) -> Scalar,
+    G: FnMut(usize) -> Scalar,
+{
+    let (mut weight_gen, mut bias_gen) = gen.into();
+    let elements = plan.kernel_elements();
+    let weights = (0..SC * elements)
+        .map(|i| {
+            let (kernel, k) = (i / elements, i % elements);
+            std::array::from_fn(|lc| weight_gen(kernel, k * LC + lc))
+        })
+        .collect();
+    (weights, std::array::from_fn(&mut bias_gen))
+}
+
+fn write_kernel_params(weights: &[Scalar], biases: &[Scalar], params: &mut [Scalar]) {
+    let (w, b) = params.split_at_mut(weights.len());
+    w.copy_from_slice(weights);
+    b.copy_from_slice(biases);
+}
+
+fn read_kernel_params(weights: &mut [Scalar], biases: &mut [Scalar], params: &[Scalar]) {
+    let (w, b) = params.split_at(weights.len());
+    weights.copy_from_slice(w);
+    biases.copy_from_slice(b);
+}
+
+// Returns the sum of every channel of `image` over all pixels.
+fn channel_sums<const W: usize, const H: usize, const C: usize>(
+    image: &Image<W, H, C>,
+) -> [Scalar; C] {
+    let mut sums = [0.0; C];
+    for pixel in image.pixels.iter().flatten() {
+        for (sum, value) in sums.iter_mut().zip(pixel) {
+            *sum += value;
+        }
+    }
+    sums
+}
+
+// Applies the gradients to the parameters, and returns the norm of the gradients.
+fn update(
+    weights: &mut [Scalar],
+    biases: &mut [Scalar],
+    weight_grads: &[Scalar],
+    bias_grads: &[Scalar],
+    learning_rate: Scalar,
+) -> Scalar {
+    let params = weights.iter_mut().chain(biases);
+    let mut squared = 0.0;
+    for (param, grad) in params.zip(weight_grads.iter().chain(bias_grads)) {
+        *param -= grad * learning_rate;
+        squared += grad * grad;
+    }
+    squared.sqrt()
+}
+
+const INVALID_CONFIG: &str =
+    "Kernel, stride and dilation should be positive, and the kernel should fit in the inputs.";
+
+// The positions of the kernel of a convolution from a larger image to a smaller image, with all
+// shapes as `[height, width]`, the order of the dimensions of images in memory.
+#[derive(Clone, Copy, Debug)]
+struct Geometry {
+    large: [usize; 2],
+    small: [usize; 2],
+    kernel: [usize; 2],
+    stride: [usize; 2],
+    dilation: [usize; 2],
+    // The padding before the first row and column of the larger image.
+    padding: [usize; 2],
+}
+
+impl Geometry {
+    // Creates the geometry of a convolution from `large` to `small` images, given as
+    // `[width, height]` like `config`.
+    fn new(config: &ConvConfig, large: [usize; 2], small: [usize; 2]) -> Self {
+        let padding = [1, 0].map(|d| config.padding_before(d, large[d], small[d]));
+        let rows_first = |[width, height]: [usize; 2]| [height, width];
+        Self {
+            large: rows_first(large),
+            small: rows_first(small),
+            kernel: rows_first(config.kernel),
+            stride: rows_first(config.stride),
+            dilation: rows_first(config.dilation),
+            padding,
+        }
+    }
+
+    // Calls `f` with the index of every pixel of the larger image under every element of the
+    // kernel, the index of that element of the kernel, and the index of the position of the
+    // kernel in the smaller image, skipping the elements of the kernel over the padding.
+    fn for_each_tap(&self, mut f: impl FnMut(usize, usize, usize)) {
+        self.for_each_padded_tap(|large, k, small| {
+            if let Some(large) = large {
+                f(large, k, small);
+            }
+        });
+    }
+
+    // Like `for_each_tap`, with `None` for the elements of the kernel over the padding.
+    fn for_each_padded_tap(&self, mut f: impl FnMut(Option<usize>, usize, usize)) {
+        // Returns the index along dimension `d` of the pixel under kernel element `k` at kernel
+        // position `pos`, if it is not in the padding.
+        let index = |d: usize, pos: usize, k: usize| {
+            let index = (pos * self.stride[d] + k * self.dilation[d]).checked_sub(self.padding[d]);
+            index.filter(|index| *index < self.large[d])
+        };
+        for row in 0..self.small[0] {
+            for column in 0..self.small[1] {
+                let small = row * self.small[1] + column;
+                for k_row in 0..self.kernel[0] {
+                    for k_column in 0..self.kernel[1] {
+                        let large = index(0, row, k_row)
+                            .zip(index(1, column, k_column))
+                            .map(|(y, x)| y * self.large[1] + x);
+                        f(large, k_row * self.kernel[1] + k_column, small);
+                    }
+                }
+            }
+        }
+    }
+
+    // Returns the index of the pixel of the larger image under every element of the kernel, at
+    // every position of the kernel.
+    fn taps(&self) -> Vec<Option<usize>> {
+        let mut taps = Vec::new();
+        self.for_each_padded_tap(|large, _, _| taps.push(large));
+        taps
+    }
+}
+
+// How a convolution from a larger image with `LC` channels to a smaller image with `SC` channels
+// is computed, shared by both layers.
+//
+// The kernels are stored one after another for every channel of the smaller image, each element
+// by element with the weights of all channels of the larger image together. So, the weights of
+// kernel element `k` from the larger image to small channel `sc` are at
+// `sc * kernel_elements + k`.
+#[derive(Clone, Debug)]
+struct Plan<const LC: usize, const SC: usize> {
+    geometry: Geometry,
+    algorithm: ConvAlgorithm,
+    // The taps of the geometry for `ConvAlgorithm::Im2col`, or empty.
+    taps: Vec<Option<usize>>,
+}
+
+impl<const LC: usize, const SC: usize> Plan<LC, SC> {
+    fn new(geometry: Geometry) -> Self {
+        Self {
+            geometry,
+            algorithm: ConvAlgorithm::Direct,
+            taps: Vec::new(),
+        }
+    }
+
+    fn set_algorithm(&mut self, algorithm: ConvAlgorithm) {
+        self.algorithm = algorithm;
+        self.taps = match algorithm {
+            ConvAlgorithm::Direct => Vec::new(),
+            ConvAlgorithm::Im2col => self.geometry.taps(),
+        };
+    }
+
+    // The number of elements of every kernel.
+    fn kernel_elements(&self) -> usize {
+        self.geometry.kernel[0] * self.geometry.kernel[1]
+    }
+
+    // Calls `f` with the pixel of the larger image under every element of the kernel, the index of
+    // that element, and the pixel of the smaller image at the position of the kernel, skipping
+    // the padding.
+    fn for_each_tap(&self, f: impl FnMut(usize, usize, usize)) {
+        match self.algorithm {
+            ConvAlgorithm::Direct => self.geometry.for_each_tap(f),
+            ConvAlgorithm::Im2col => self.for_each_stored_tap(f),
+        }
+    }
+
+    // Like `for_each_tap`, using the taps of `ConvAlgorithm::Im2col`.
+    fn for_each_stored_tap(&self, mut f: impl FnMut(usize, usize, usize)) {
+        for (small, taps) in self.taps.chunks(self.kernel_elements()).enumerate() {
+            for (k, large) in taps.iter().enumerate() {
+                if let Some(large) = large {
+                    f(*large, k, small);
+                }
+            }
+        }
+    }
+
+    // Adds the convolution of `large` with the kernels to `small`.
+    fn gather(&self, weights: &[[Scalar; LC]], large: &[[Scalar; LC]], small: &mut [[Scalar; SC]]) {
+        let elements = self.kernel_elements();
+        match self.algorithm {
+            ConvAlgorithm::Direct => self.for_each_tap(|l, k, s| {
+                for (sc, out) in small[s].iter_mut().enumerate() {
+                    *out += dot(&weights[sc * elements + k], &large[l]);
+                }
+            }),
+            ConvAlgorithm::Im2col => self.with_patches(large, |patches| {
+                transposed_mut(small).gemm_tr(1.0, &patches, &self.kernels(weights), 1.0);
+            }),
+        }
+    }
+
+    // Adds the kernels scaled by the values of `small` to `large`, the transpose of `gather`.
+    // With `ConvAlgorithm::Im2col`, this scatters the columns of the patches back to the larger
+    // image (col2im).
+    fn scatter(
+        &self,
+        weights: &[[Scalar; LC]],
+        small: &[[Scalar; SC]],
+        large: &mut [[Scalar; LC]],
+    ) {
+        let elements = self.kernel_elements();
+        self.for_each_tap(|l, k, s| {
+            for (sc, x) in small[s].iter().enumerate() {
+                axpy(&mut large[l], *x, &weights[sc * elements + k]);
+            }
+        });
+    }
+
+    // Given the values of the larger image and the gradients over the smaller image, adds the
+    // gradients of the weights to `weight_grads`, and the gradients over the larger image to
+    // `large_grads`.
+    fn backprop_to_large(
+        &self,
+        weights: &[[Scalar; LC]],
+        large: &[[Scalar; LC]],
+        small: &[[Scalar; SC]],
+        weight_grads: &mut [[Scalar; LC]],
+        large_grads: &mut [[Scalar; LC]],
+    ) {
+        let elements = self.kernel_elements();
+        match self.algorithm {
+            ConvAlgorithm::Direct => self.for_each_tap(|l, k, s| {
+                for (sc, g) in small[s].iter().enumerate() {
+                    let w = sc * elements + k;
+                    axpy(&mut weight_grads[w], *g, &large[l]);
+                    axpy(&mut large_grads[l], *g, &weights[w]);
+                }
+            }),
+            ConvAlgorithm::Im2col => {
+                self.with_patches(large, |patches| {
+                    self.kernels_mut(weight_grads)
+                        .gemm(1.0, &patches, &transposed(small), 1.0);
+                });
+                self.scatter(weights, small, large_grads);
+            }
+        }
+    }
+
+    // Given the gradients over the larger image and the values of the smaller image, adds the
+    // gradients of the weights to `weight_grads`, and the gradients over the smaller image to
+    // `small_grads`.
+    fn backprop_to_small(
+        &self,
+        weights: &[[Scalar; LC]],
+        large: &[[Scalar; LC]],
+        small: &[[Scalar; SC]],
+        weight_grads: &mut [[Scalar; LC]],
+        small_grads: &mut [[Scalar; SC]],
+    ) {
+        let elements = self.kernel_elements();
+        match self.algorithm {
+            ConvAlgorithm::Direct => self.for_each_tap(|l, k, s| {
+                for (sc, x) in small[s].iter().enumerate() {
+                    let w = sc * elements + k;
+                    axpy(&mut weight_grads[w], *x, &large[l]);
+                    small_grads[s][sc] += dot(&weights[w], &large[l]);
+                }
+            }),
+            // The gradients over the larger image are gathered like the values of a convolution.
+            ConvAlgorithm::Im2col => self.with_patches(large, |patches| {
+                self.kernels_mut(weight_grads)
+                    .gemm(1.0, &patches, &transposed(small), 1.0);
+                transposed_mut(small_grads).gemm_tr(1.0, &patches, &self.kernels(weights), 1.0);
+            }),
+        }
+    }
+
+    // Returns the kernels as the columns of a matrix.
+    fn kernels<'a>(&self, weights: &'a [[Scalar; LC]]) -> DMatrixView<'a, Scalar> {
+        DMatrixView::from_slice(weights.as_flattened(), self.kernel_elements() * LC, SC)
+    }
+
+    fn kernels_mut<'a>(&self, weights: &'a mut [[Scalar; LC]]) -> DMatrixViewMut<'a, Scalar> {
+        let rows = self.kernel_elements() * LC;
+        DMatrixViewMut::from_slice(weights.as_flattened_mut(), rows, SC)
+    }
+
+    // Calls `f` with a matrix of the values of `large` under the kernel, or zero in the padding,
+    // with a column for every position of the kernel (im2col).
+    fn with_patches<R>(
+        &self,
+        large: &[[Scalar; LC]],
+        f: impl FnOnce(DMatrixView<'_, Scalar>) -> R,
+    ) -> R {
+        PATCHES.with(|patches| {
+            let mut patches = patches.borrow_mut();
+            let len = self.taps.len() * LC;
+            if patches.len() < len {
+                patches.resize(len, 0.0);
+            }
+            let patches = &mut patches[..len];
+            for (patch, tap) in patches.chunks_exact_mut(LC).zip(&self.taps) {
+                match tap {
+                    Some(tap) => patch.copy_from_slice(&large[*tap]),
+                    None => patch.fill(0.0),
+                }
+            }
+            let rows = self.kernel_elements() * LC;
+            f(DMatrixView::from_slice(patches, rows, len / rows))
+        })
+    }
+}
+
+// Returns the pixels of an image as the rows of a matrix.
+fn transposed<const C: usize>(pixels: &[[Scalar; C]]) -> StridedView<'_> {
+    StridedView::from_slice_with_strides(pixels.as_flattened(), pixels.len(), C, C, 1)
+}
+
+fn transposed_mut<const C: usize>(pixels: &mut [[Scalar; C]]) -> StridedViewMut<'_> {
+    let rows = pixels.len();
+    StridedViewMut::from_slice_with_strides_mut(pixels.as_flattened_mut(), rows, C, C, 1)
+}
+
+fn dot<const N: usize>(a: &[Scalar; N], b: &[Scalar; N]) -> Scalar {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+// Adds `b` scaled by `x` to `a`.
+fn axpy<const N: usize>(a: &mut [Scalar; N], x: Scalar, b: &[Scalar; N]) {
+    for (a, b) in a.iter_mut().zip(b) {
+        *a += x * b;
+    }
+}
+
+// Views of matrices with rows that are not contiguous.
+type StridedView<'a> = MatrixView<'a, Scalar, Dyn, Dyn, Dyn, Dyn>;
+type StridedViewMut<'a> = MatrixViewMut<'a, Scalar, Dyn, Dyn, Dyn, Dyn>;
+
+thread_local! {
+    // The matrix of patches of `ConvAlgorithm::Im2col`, reused between calls.
+    static PATCHES: RefCell<Vec<Scalar>> = const { RefCell::new(Vec::new()) };
 }
@@ -0,0 +1,114 @@
+/*!
+Curriculum learning: training in stages of increasing difficulty.
+
+A [`Curriculum`] is a sequence of [`Stage`]s, each training on the samples of the dataset
+selected by its filter, with its own learning rate and number of epochs. A stage can end early
+when the mean training error of an epoch reaches its threshold, after which training continues
+with the next stage. Curricula are trained by
+[`Trainer::fit_curriculum()`](crate::train::Trainer::fit_curriculum).
+
+# Examples
+```rust
+use rann_base::{
+    activ::Logistic, curriculum::{Curriculum, Stage}, error::SquareError, testing, train::Trainer,
+    Full,
+};
+use rann_traits::Network;
+
+let mut net = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1))
+    .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)))
+    .chain(SquareError { expected: [0.0] });
+
+// First learn the samples with a first input of zero, then all of XOR.
+let curriculum = Curriculum::new()
+    .stage(
+        Stage::new(500, 0.5)
+            .with_filter(|(inputs, _): &([f32; 2], [f32; 1])| inputs[0] == 0.0)
+            .until(0.01),
+    )
+    .stage(Stage::new(500, 0.5));
+let fits = Trainer::default().fit_curriculum(&mut net, &testing::XOR, &curriculum);
+assert_eq!(fits.len(), 2);
+assert!(fits[0].errors.len() < 500, "The first stage should end early.");
+```
+*/
+
+use std::fmt::{self, Debug};
+
+use rann_traits::{LearningRate, Scalar};
+
+/// A stage of a [`Curriculum`], training on samples of type `S`.
+pub struct Stage<'a, S> {
+    /// Selects the samples of the dataset to train on in this stage.
+    pub filter: Box<dyn Fn(&S) -> bool + 'a>,
+    /// The learning rate.
+    pub learning_rate: LearningRate,
+    /// The largest number of epochs to train for.
+    pub epochs: usize,
+    /// The mean training error of an epoch at or below which the stage ends early.
+    pub threshold: Option<Scalar>,
+}
+
+impl<'a, S> Stage<'a, S> {
+    /// Creates a stage training on all samples for `epochs` epochs.
+    pub fn new(epochs: usize, learning_rate: impl Into<LearningRate>) -> Self {
+        Self {
+            filter: Box::new(|_| true),
+            learning_rate: learning_rate.into(),
+            epochs,
+            threshold: None,
+        }
+    }
+
+    /// Only trains on the samples for which `filter` returns `true`.
+    pub fn with_filter(self, filter: impl Fn(&S) -> bool + 'a) -> Self {
+        Self {
+            filter: Box::new(filter),
+            ..self
+        }
+    }
+
+    /// Ends the stage as soon as the mean training error of an epoch is at most `threshold`.
+    pub fn until(self, threshold: Scalar) -> Self {
+        Self {
+            threshold: Some(threshold),
+            ..self
+        }
+    }
+}
+
+impl<S> Debug for Stage<'_, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stage")
+            .field("learning_rate", &self.learning_rate)
+            .field("epochs", &self.epochs)
+            .field("threshold", &self.threshold)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A sequence of training [`Stage`]s. See [module level documentation](self) for more info.
+#[derive(Debug)]
+pub struct Curriculum<'a, S> {
+    /// The stages, in order of training.
+    pub stages: Vec<Stage<'a, S>>,
+}
+
+impl<'a, S> Curriculum<'a, S> {
+    /// Creates a curriculum without stages.
+    pub fn new() -> Self {
+        Self { stages: Vec::new() }
+    }
+
+    /// Appends `stage` to the curriculum.
+    pub fn stage(mut self, stage: Stage<'a, S>) -> Self {
+        self.stages.push(stage);
+        self
+    }
+}
+
+impl<S> Default for Curriculum<'_, S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
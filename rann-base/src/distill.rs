@@ -0,0 +1,193 @@
+/*!
+Knowledge distillation.
+
+Distillation (Hinton et al., 2015) trains a small student network to imitate a large, trained
+teacher network. Besides the hard labels of the dataset, the student learns from the soft
+targets of the teacher: the probabilities of its softmax, softened by a temperature such that
+the relative probabilities of the wrong classes, which tell how similar the classes are, carry
+weight.
+
+The student ends in a [`DistillationLoss`], mixing the divergence from the soft targets with the
+cross-entropy of the hard labels. [`Trainer::fit_distilled()`](crate::train::Trainer::fit_distilled)
+trains it, evaluating the teacher on every sample as it is trained on.
+
+# Examples
+```rust
+use rann_base::{
+    activ::Logistic, distill::DistillationLoss, error::CrossEntropy, testing, train::Trainer, Full,
+};
+use rann_traits::{LearningRate, Network, Scalar};
+
+let linear = (|x: Scalar| x, |_: Scalar| 1.0);
+let dataset = testing::XOR.map(|(inputs, [t])| (inputs, [1.0 - t, t]));
+
+// A trained teacher, returning logits.
+let mut teacher = Full::<2, 8, _>::new(Logistic, testing::seeded_gen(1))
+    .chain(Full::<8, 2, _>::new(linear, testing::seeded_gen(2)))
+    .chain(CrossEntropy::new());
+Trainer { epochs: 2000, learning_rate: LearningRate(0.5), ..Default::default() }.fit(&mut teacher, &dataset);
+let teacher = teacher.first;
+
+// A smaller student.
+let mut student = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(3))
+    .chain(Full::<3, 2, _>::new(linear, testing::seeded_gen(4)))
+    .chain(DistillationLoss::new(2.0, 0.5));
+let trainer = Trainer { epochs: 2000, learning_rate: LearningRate(0.5), ..Default::default() };
+let fit = trainer.fit_distilled(&teacher, &mut student, &dataset);
+assert!(fit.errors.last().unwrap() < &fit.errors[0]);
+```
+*/
+
+use rann_traits::{
+    inspect::{Inspect, LayerView},
+    params::Parameterized,
+    Intermediate, Network, Scalar, Supervised,
+};
+
+use crate::activ::softmax;
+
+/// An error function for distillation over the logits of `C` classes. See
+/// [module level documentation](self) for more info.
+///
+/// The error is `alpha * T² * KL(teacher || student) + (1 - alpha) * CE(labels, student)`, where
+/// the Kullback-Leibler divergence is between the softmax of the teacher and student logits
+/// divided by the temperature `T`. The divergence is scaled by `T²`, such that its gradients
+/// keep their magnitude for any temperature.
+///
+/// Its target is a pair of the teacher logits and the hard labels, such as a one-hot vector.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DistillationLoss<const C: usize> {
+    /// The temperature the logits are divided by for the soft targets.
+    pub temperature: Scalar,
+    /// The weight of the soft targets, between `0.0` (only the labels) and `1.0` (only the
+    /// teacher).
+    pub alpha: Scalar,
+    /// The logits of the teacher.
+    pub teacher: [Scalar; C],
+    /// The expected probability of each class.
+    pub expected: [Scalar; C],
+}
+
+impl<const C: usize> DistillationLoss<C> {
+    /// Creates a distillation loss with `temperature`, weighting the soft targets by `alpha`.
+    pub fn new(temperature: Scalar, alpha: Scalar) -> Self {
+        Self {
+            temperature,
+            alpha,
+            teacher: [0.0; C],
+            expected: [0.0; C],
+        }
+    }
+}
+
+/// The intermediate values of an evaluation of a [`DistillationLoss`].
+#[derive(Clone, Debug)]
+pub struct DistillationLossInter<const C: usize> {
+    /// The softmax of the student logits.
+    pub probs: [Scalar; C],
+    /// The softmax of the student logits divided by the temperature.
+    pub soft_probs: [Scalar; C],
+    /// The softmax of the teacher logits divided by the temperature: the soft targets.
+    pub soft_targets: [Scalar; C],
+    /// The error.
+    pub error: [Scalar; 1],
+}
+
+impl<const C: usize> Intermediate for DistillationLossInter<C> {
+    type Out = [Scalar; 1];
+
+    fn output(&self) -> &Self::Out {
+        &self.error
+    }
+
+    fn into_output(self) -> Self::Out {
+        self.error
+    }
+}
+
+impl<const C: usize> Network for DistillationLoss<C> {
+    type In = [Scalar; C];
+
+    type Out = [Scalar; 1];
+
+    type Inter = DistillationLossInter<C>;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        let t = self.temperature;
+        let probs = softmax(inputs);
+        let soft_probs = softmax(&inputs.map(|x| x / t));
+        let soft_targets = softmax(&self.teacher.map(|x| x / t));
+        let ln = |p: Scalar| p.max(Scalar::MIN_POSITIVE).ln();
+        let divergence: Scalar = soft_targets
+            .iter()
+            .zip(soft_probs)
+            .filter(|(q, _)| **q != 0.0)
+            .map(|(q, p)| q * (ln(*q) - ln(p)))
+            .sum();
+        let cross_entropy: Scalar = self
+            .expected
+            .iter()
+            .zip(probs)
+            .filter(|(y, _)| **y != 0.0)
+            .map(|(y, p)| -y * ln(p))
+            .sum();
+        DistillationLossInter {
+            probs,
+            soft_probs,
+            soft_targets,
+            error: [self.alpha * t * t * divergence + (1.0 - self.alpha) * cross_entropy],
+        }
+    }
+
+    fn train_deriv(
+        &mut self,
+        _inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        _learning_rate: Scalar,
+    ) -> Self::In {
+        let mut grads = [0.0; C];
+        for (i, g) in grads.iter_mut().enumerate() {
+            let soft = intermediate.soft_probs[i] - intermediate.soft_targets[i];
+            let hard = intermediate.probs[i] - self.expected[i];
+            *g = (self.alpha * self.temperature * soft + (1.0 - self.alpha) * hard) * gradients[0];
+        }
+        grads
+    }
+}
+
+impl<const C: usize> Supervised for DistillationLoss<C> {
+    /// The teacher logits, and the expected probability of each class.
+    type Target = ([Scalar; C], [Scalar; C]);
+
+    fn set_target(&mut self, (teacher, expected): &Self::Target) {
+        self.teacher = *teacher;
+        self.expected = *expected;
+    }
+}
+
+impl<const C: usize> Inspect for DistillationLoss<C> {
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        f(&LayerView {
+            kind: "DistillationLoss",
+            num_inputs: C,
+            params: &[],
+            activations: &intermediate.error,
+            gradient_norm: 0.0,
+        });
+    }
+}
+
+impl<const C: usize> Parameterized for DistillationLoss<C> {
+    fn num_params(&self) -> usize {
+        0
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        assert!(params.is_empty(), "Error functions have no parameters.");
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        assert!(params.is_empty(), "Error functions have no parameters.");
+    }
+}
@@ -0,0 +1,377 @@
+/*!
+Dropout, and uncertainty estimates with Monte Carlo dropout.
+
+A [`Dropout`] layer sets every input to zero with probability `p` while training, and scales the
+kept inputs by `1 / (1 - p)` such that their expected value is unchanged. This regularizes the
+preceding layers, which can not rely on any single feature.
+
+Keeping dropout active when evaluating turns a network into an ensemble of random subnetworks.
+[`McDropout::mc_dropout_eval()`] evaluates a network many times, and returns the mean and
+variance of its outputs: the variance is a cheap estimate of the uncertainty of the prediction,
+as proposed by Gal and Ghahramani (2016).
+
+Self-normalizing networks of [`Selu`](crate::activ::Selu) activations are regularized with
+[`AlphaDropout`] instead, which sets dropped inputs to the value negative inputs saturate to, and
+transforms the outputs such that their mean and variance are kept.
+
+# Examples
+```rust
+use rann_base::{
+    activ::Logistic,
+    dropout::{Dropout, McDropout},
+    testing, Full,
+};
+use rann_traits::Network;
+
+let net = Full::<2, 8, _>::new(Logistic, testing::seeded_gen(1))
+    .chain(Dropout::<8>::new(0.5, 7))
+    .chain(Full::<8, 1, _>::new(Logistic, testing::seeded_gen(2)));
+let estimate = net.mc_dropout_eval(&[1.0, 0.0], 100);
+assert!(estimate.variance[0] > 0.0);
+```
+*/
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use fastrand::Rng;
+use rann_traits::{
+    inspect::{Inspect, LayerView},
+    params::Parameterized,
+    Intermediate, Network, Scalar,
+};
+
+use crate::activ::Selu;
+
+/// Inverted dropout over `N` inputs. See [module level documentation](self) for more info.
+///
+/// The dropped inputs are drawn from a generator seeded when the layer is created, such that
+/// training is reproducible. Every evaluation draws new dropped inputs, and the same inputs are
+/// dropped when training on that evaluation.
+#[derive(Debug)]
+pub struct Dropout<const N: usize> {
+    /// The probability of dropping an input.
+    pub p: Scalar,
+    /// Whether inputs are dropped. If `false`, the layer passes its inputs through unchanged,
+    /// such as for deterministic predictions after training.
+    pub active: bool,
+    seed: u64,
+    // The number of evaluations so far, from which the generator of each is seeded.
+    passes: AtomicU64,
+}
+
+impl<const N: usize> Dropout<N> {
+    /// Creates an active dropout layer dropping inputs with probability `p`, seeded by `seed`.
+    ///
+    /// # Panics
+    /// Panics if `p` is not in `[0, 1)`.
+    pub fn new(p: Scalar, seed: u64) -> Self {
+        assert!(
+            (0.0..1.0).contains(&p),
+            "The dropout probability {p} should be in [0, 1)."
+        );
+        Self {
+            p,
+            active: true,
+            seed,
+            passes: AtomicU64::new(0),
+        }
+    }
+}
+
+impl<const N: usize> Clone for Dropout<N> {
+    fn clone(&self) -> Self {
+        Self {
+            p: self.p,
+            active: self.active,
+            seed: self.seed,
+            passes: AtomicU64::new(self.passes.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+impl<const N: usize> Network for Dropout<N> {
+    type In = [Scalar; N];
+
+    type Out = [Scalar; N];
+
+    type Inter = DropoutInter<N>;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        let mut mask = [1.0; N];
+        if self.active {
+            let mut rng = next_rng(self.seed, &self.passes);
+            let scale = 1.0 / (1.0 - self.p);
+            mask = mask.map(|_| if rng.f32() < self.p { 0.0 } else { scale });
+        }
+        let mut outputs = *inputs;
+        for (out, m) in outputs.iter_mut().zip(mask) {
+            *out *= m;
+        }
+        DropoutInter { mask, outputs }
+    }
+
+    fn train_deriv(
+        &mut self,
+        _inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        _learning_rate: Scalar,
+    ) -> Self::In {
+        let mut out = *gradients;
+        for (gr, m) in out.iter_mut().zip(intermediate.mask) {
+            *gr *= m;
+        }
+        out
+    }
+}
+
+impl<const N: usize> Inspect for Dropout<N> {
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        f(&LayerView {
+            kind: "Dropout",
+            num_inputs: N,
+            params: &[],
+            activations: intermediate.output(),
+            gradient_norm: 0.0,
+        });
+    }
+}
+
+// The dropout probability is a hyperparameter, not a parameter.
+impl<const N: usize> Parameterized for Dropout<N> {
+    fn num_params(&self) -> usize {
+        0
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        assert!(params.is_empty(), "Dropout has no parameters.");
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        assert!(params.is_empty(), "Dropout has no parameters.");
+    }
+}
+
+/// Alpha dropout over `N` inputs, for self-normalizing networks of
+/// [`Selu`](crate::activ::Selu) activations. See [module level documentation](self) for more
+/// info.
+///
+/// While training, every input is set to `-λα`, the value the SELU activation saturates to, with
+/// probability `p`. The outputs are then scaled and shifted, such that inputs with zero mean and
+/// unit variance keep zero mean and unit variance, as described by Klambauer et al. (2017). The
+/// dropped inputs are drawn like those of [`Dropout`].
+///
+/// # Examples
+/// ```rust
+/// use rann_base::{activ::Selu, dropout::AlphaDropout, testing, Full};
+/// use rann_traits::Network;
+///
+/// let mut net = Full::<2, 16, _>::new(Selu, testing::seeded_gen(1))
+///     .chain(AlphaDropout::<16>::new(0.1, 7))
+///     .chain(Full::<16, 1, _>::new(Selu, testing::seeded_gen(2)));
+/// // Without dropout, such as after training, the inputs are passed through unchanged.
+/// net.first.second.active = false;
+/// let hidden = net.first.first.eval(&[1.0, 0.0]);
+/// assert_eq!(net.eval(&[1.0, 0.0]), net.second.eval(&hidden));
+/// ```
+#[derive(Debug)]
+pub struct AlphaDropout<const N: usize> {
+    /// The probability of dropping an input.
+    pub p: Scalar,
+    /// Whether inputs are dropped. If `false`, the layer passes its inputs through unchanged.
+    pub active: bool,
+    seed: u64,
+    // The number of evaluations so far, from which the generator of each is seeded.
+    passes: AtomicU64,
+}
+
+impl<const N: usize> AlphaDropout<N> {
+    /// The value dropped inputs are set to, `-λα` of [`Selu`](crate::activ::Selu).
+    pub const DROPPED: Scalar = -Selu::LAMBDA * Selu::ALPHA;
+
+    /// Creates an active alpha dropout layer dropping inputs with probability `p`, seeded by
+    /// `seed`.
+    ///
+    /// # Panics
+    /// Panics if `p` is not in `[0, 1)`.
+    pub fn new(p: Scalar, seed: u64) -> Self {
+        assert!(
+            (0.0..1.0).contains(&p),
+            "The dropout probability {p} should be in [0, 1)."
+        );
+        Self {
+            p,
+            active: true,
+            seed,
+            passes: AtomicU64::new(0),
+        }
+    }
+
+    // Returns the scale and shift of the outputs, which keep zero mean and unit variance.
+    fn affine(&self) -> (Scalar, Scalar) {
+        let keep = 1.0 - self.p;
+        let scale = (keep * (1.0 + self.p * Self::DROPPED * Self::DROPPED)).powf(-0.5);
+        (scale, -scale * Self::DROPPED * self.p)
+    }
+}
+
+impl<const N: usize> Clone for AlphaDropout<N> {
+    fn clone(&self) -> Self {
+        Self {
+            p: self.p,
+            active: self.active,
+            seed: self.seed,
+            passes: AtomicU64::new(self.passes.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+impl<const N: usize> Network for AlphaDropout<N> {
+    type In = [Scalar; N];
+
+    type Out = [Scalar; N];
+
+    type Inter = DropoutInter<N>;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        if !self.active {
+            return DropoutInter {
+                mask: [1.0; N],
+                outputs: *inputs,
+            };
+        }
+        let mut rng = next_rng(self.seed, &self.passes);
+        let (scale, shift) = self.affine();
+        let mut mask = [scale; N];
+        let mut outputs = *inputs;
+        for (out, m) in outputs.iter_mut().zip(&mut mask) {
+            if rng.f32() < self.p {
+                *out = Self::DROPPED;
+                *m = 0.0;
+            }
+            *out = scale * *out + shift;
+        }
+        DropoutInter { mask, outputs }
+    }
+
+    fn train_deriv(
+        &mut self,
+        _inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        _learning_rate: Scalar,
+    ) -> Self::In {
+        let mut out = *gradients;
+        for (gr, m) in out.iter_mut().zip(intermediate.mask) {
+            *gr *= m;
+        }
+        out
+    }
+}
+
+impl<const N: usize> Inspect for AlphaDropout<N> {
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        f(&LayerView {
+            kind: "AlphaDropout",
+            num_inputs: N,
+            params: &[],
+            activations: intermediate.output(),
+            gradient_norm: 0.0,
+        });
+    }
+}
+
+// The dropout probability is a hyperparameter, not a parameter.
+impl<const N: usize> Parameterized for AlphaDropout<N> {
+    fn num_params(&self) -> usize {
+        0
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        assert!(params.is_empty(), "AlphaDropout has no parameters.");
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        assert!(params.is_empty(), "AlphaDropout has no parameters.");
+    }
+}
+
+// Returns the generator of the next evaluation of a dropout layer seeded by `seed`.
+fn next_rng(seed: u64, passes: &AtomicU64) -> Rng {
+    let pass = passes.fetch_add(1, Ordering::Relaxed);
+    Rng::with_seed(seed ^ pass.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
+
+/// The intermediate calculations for an evaluation of [`Dropout`] or [`AlphaDropout`].
+#[derive(Clone, Debug)]
+pub struct DropoutInter<const N: usize> {
+    // The factor the gradient of every input is multiplied by: zero if dropped.
+    mask: [Scalar; N],
+    outputs: [Scalar; N],
+}
+
+impl<const N: usize> DropoutInter<N> {
+    /// Returns whether every input was dropped.
+    pub fn dropped(&self) -> [bool; N] {
+        self.mask.map(|m| m == 0.0)
+    }
+}
+
+impl<const N: usize> Intermediate for DropoutInter<N> {
+    type Out = [Scalar; N];
+
+    fn output(&self) -> &Self::Out {
+        &self.outputs
+    }
+
+    fn into_output(self) -> Self::Out {
+        self.outputs
+    }
+}
+
+/// The mean and variance of the outputs of a network over repeated stochastic evaluations, see
+/// [`McDropout::mc_dropout_eval()`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Estimate<const M: usize> {
+    /// The mean of every output, the prediction.
+    pub mean: [Scalar; M],
+    /// The variance of every output, the uncertainty of the prediction.
+    pub variance: [Scalar; M],
+}
+
+impl<const M: usize> Estimate<M> {
+    /// Returns the standard deviation of every output.
+    pub fn std_dev(&self) -> [Scalar; M] {
+        self.variance.map(Scalar::sqrt)
+    }
+}
+
+/// Uncertainty estimates for networks containing active [`Dropout`] layers. Implemented for all
+/// networks with an array of outputs.
+pub trait McDropout<const M: usize>: Network<Out = [Scalar; M]> {
+    /// Evaluates the network `samples` times on `inputs`, and returns the mean and variance of
+    /// its outputs. Without active dropout layers, the variance is zero.
+    ///
+    /// # Panics
+    /// Panics if `samples` is zero.
+    fn mc_dropout_eval(&self, inputs: &Self::In, samples: usize) -> Estimate<M> {
+        assert!(samples > 0, "At least one sample is needed.");
+        // Welford's algorithm, which is numerically stable.
+        let (mut mean, mut sq_diffs) = ([0.0; M], [0.0; M]);
+        for i in 0..samples {
+            let outputs = self.eval(inputs);
+            let n = (i + 1) as Scalar;
+            for ((mean, sq_diffs), x) in mean.iter_mut().zip(&mut sq_diffs).zip(outputs) {
+                let delta = x - *mean;
+                *mean += delta / n;
+                *sq_diffs += delta * (x - *mean);
+            }
+        }
+        Estimate {
+            mean,
+            variance: sq_diffs.map(|s| s / samples as Scalar),
+        }
+    }
+}
+
+impl<T, const M: usize> McDropout<M> for T where T: Network<Out = [Scalar; M]> {}
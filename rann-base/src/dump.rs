@@ -0,0 +1,274 @@
+/*!
+Dumps of the layers of networks during training, to analyze how their parameters evolved after a
+run, such as one that diverged.
+
+A [`Dump`] is a snapshot of every layer of an [`Inspect`] network: its kind, its parameters, its
+activations in an evaluation and the norm of its gradients in the last training step. A
+[`Dumper`] writes dumps to numbered files in a directory, as text to read or diff, or as binary to
+load them back exactly using [`Dump::load()`].
+[`Trainer::fit_dumped()`](crate::train::Trainer::fit_dumped) writes a dump after every epoch.
+
+# Format
+Text dumps start with a line `epoch <epoch>`, followed by a block for every layer, separated by
+empty lines: `layer <index>: <kind>`, `inputs: <number>`, `gradient norm: <norm>`, a line
+`params <index>: <values>` for every slice of parameters, and `activations: <values>`, with the
+values separated by spaces.
+
+Binary dumps are stored in little-endian: the magic bytes `RANNDUMP`, a `u32` format version, the
+epoch and the number of layers as `u64`s, and for every layer its kind as a `u64` length followed
+by that many bytes of UTF-8, the number of inputs as a `u64`, the gradient norm as an `f32`, the
+number of slices of parameters as a `u64`, and the slices of parameters and the activations, each
+as a `u64` length followed by that many `f32`s.
+
+# Examples
+```rust
+use rann_base::{
+    activ::Logistic,
+    dump::{Dump, DumpFormat, Dumper},
+    error::SquareError,
+    testing, train, Full,
+};
+use rann_traits::Network;
+
+let dir = std::env::temp_dir().join("rann-dump-example");
+let dumper = Dumper::new(&dir).with_format(DumpFormat::Binary);
+let mut net = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1))
+    .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)))
+    .chain(SquareError { expected: [0.0] });
+for epoch in 1..=3 {
+    train::train_epoch(&mut net, &testing::XOR, 0.5);
+    dumper.dump(&net, &[0.0, 1.0], epoch)?;
+}
+
+let dump = Dump::load(dumper.path(3))?;
+assert_eq!(dump.epoch, 3);
+assert_eq!(dump.layers[0].kind, "Full (Logistic)");
+// The weights and the biases of the first layer.
+assert_eq!(dump.layers[0].params[0].len(), 6);
+assert_eq!(dump.layers[0].params[1].len(), 3);
+# std::fs::remove_dir_all(&dir)?;
+# Ok::<(), std::io::Error>(())
+```
+*/
+
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use rann_traits::{inspect::Inspect, Scalar};
+
+use crate::checkpoint::{invalid, read_array, read_scalars, write_scalars};
+
+const MAGIC: &[u8; 8] = b"RANNDUMP";
+const VERSION: u32 = 1;
+
+/// A snapshot of the layers of a network. See [module level documentation](self) for more info.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dump {
+    /// The epoch of the dump.
+    pub epoch: usize,
+    /// The layers of the network, in evaluation order.
+    pub layers: Vec<LayerDump>,
+}
+
+/// A snapshot of a single layer of a network, see
+/// [`LayerView`](rann_traits::inspect::LayerView).
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayerDump {
+    /// A short description of the kind of layer, such as `Full (Logistic)`.
+    pub kind: String,
+    /// The number of inputs of the layer.
+    pub num_inputs: usize,
+    /// The parameters of the layer, such as its weights and biases.
+    pub params: Vec<Vec<Scalar>>,
+    /// The outputs of the layer in an evaluation.
+    pub activations: Vec<Scalar>,
+    /// The L2 norm of the gradients over all parameters of the layer, from the last training step.
+    pub gradient_norm: Scalar,
+}
+
+impl Dump {
+    /// Takes a snapshot of the layers of `net`, with the activations of `intermediate`.
+    pub fn new<N: Inspect>(net: &N, intermediate: &N::Inter, epoch: usize) -> Self {
+        let mut layers = Vec::new();
+        net.visit_layers(intermediate, &mut |layer| {
+            layers.push(LayerDump {
+                kind: layer.kind.to_owned(),
+                num_inputs: layer.num_inputs,
+                params: layer.params.iter().map(|p| p.to_vec()).collect(),
+                activations: layer.activations.to_vec(),
+                gradient_norm: layer.gradient_norm,
+            });
+        });
+        Self { epoch, layers }
+    }
+
+    /// Writes the dump to `writer` as text.
+    pub fn write_text(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "epoch {}", self.epoch)?;
+        for (i, layer) in self.layers.iter().enumerate() {
+            writeln!(writer)?;
+            writeln!(writer, "layer {i}: {}", layer.kind)?;
+            writeln!(writer, "inputs: {}", layer.num_inputs)?;
+            writeln!(writer, "gradient norm: {}", layer.gradient_norm)?;
+            for (j, params) in layer.params.iter().enumerate() {
+                write!(writer, "params {j}:")?;
+                write_values(&mut writer, params)?;
+            }
+            write!(writer, "activations:")?;
+            write_values(&mut writer, &layer.activations)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the dump to `writer` as binary.
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&(self.epoch as u64).to_le_bytes())?;
+        writer.write_all(&(self.layers.len() as u64).to_le_bytes())?;
+        for layer in &self.layers {
+            writer.write_all(&(layer.kind.len() as u64).to_le_bytes())?;
+            writer.write_all(layer.kind.as_bytes())?;
+            writer.write_all(&(layer.num_inputs as u64).to_le_bytes())?;
+            writer.write_all(&layer.gradient_norm.to_le_bytes())?;
+            writer.write_all(&(layer.params.len() as u64).to_le_bytes())?;
+            for params in &layer.params {
+                write_scalars(&mut writer, params)?;
+            }
+            write_scalars(&mut writer, &layer.activations)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a binary dump from `reader`.
+    ///
+    /// # Errors
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if the data is not a binary dump
+    /// or has an unsupported version.
+    pub fn read_from(mut reader: impl Read) -> io::Result<Self> {
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid("not a binary dump"));
+        }
+        let version = u32::from_le_bytes(read_array(&mut reader)?);
+        if version != VERSION {
+            return Err(invalid(format!("unsupported dump version {version}")));
+        }
+        let epoch = read_len(&mut reader)?;
+        let num_layers = read_len(&mut reader)?;
+        let mut layers = Vec::new();
+        for _ in 0..num_layers {
+            let mut kind = Vec::new();
+            let len = read_len(&mut reader)?;
+            (&mut reader).take(len as u64).read_to_end(&mut kind)?;
+            if kind.len() != len {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            let kind = String::from_utf8(kind).map_err(|_| invalid("kind is not UTF-8"))?;
+            let num_inputs = read_len(&mut reader)?;
+            let gradient_norm = Scalar::from_le_bytes(read_array(&mut reader)?);
+            let params = (0..read_len(&mut reader)?)
+                .map(|_| read_scalars(&mut reader))
+                .collect::<io::Result<_>>()?;
+            layers.push(LayerDump {
+                kind,
+                num_inputs,
+                params,
+                activations: read_scalars(&mut reader)?,
+                gradient_norm,
+            });
+        }
+        Ok(Self { epoch, layers })
+    }
+
+    /// Reads a binary dump from the file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+}
+
+fn write_values(writer: &mut impl Write, values: &[Scalar]) -> io::Result<()> {
+    for value in values {
+        write!(writer, " {value}")?;
+    }
+    writeln!(writer)
+}
+
+fn read_len(reader: &mut impl Read) -> io::Result<usize> {
+    Ok(u64::from_le_bytes(read_array(reader)?) as usize)
+}
+
+/// The format of the files written by a [`Dumper`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DumpFormat {
+    /// Human readable text, see [`Dump::write_text()`].
+    #[default]
+    Text,
+    /// Exact binary, see [`Dump::write_to()`].
+    Binary,
+}
+
+impl DumpFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            DumpFormat::Text => "txt",
+            DumpFormat::Binary => "dump",
+        }
+    }
+}
+
+/// Writes dumps to numbered files in a directory. See [module level documentation](self) for
+/// more info.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dumper {
+    /// The directory to write dumps to. It is created if it does not exist.
+    pub dir: PathBuf,
+    /// The format of the dumps.
+    pub format: DumpFormat,
+}
+
+impl Dumper {
+    /// Creates a dumper writing text dumps to `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            format: DumpFormat::default(),
+        }
+    }
+
+    /// Writes dumps in `format`.
+    pub fn with_format(self, format: DumpFormat) -> Self {
+        Self { format, ..self }
+    }
+
+    /// Returns the path of the dump of `epoch`.
+    pub fn path(&self, epoch: usize) -> PathBuf {
+        let extension = self.format.extension();
+        self.dir.join(format!("dump-{epoch:08}.{extension}"))
+    }
+
+    /// Evaluates `net` on `inputs`, and writes a dump of its layers with the activations of that
+    /// evaluation as the dump of `epoch`. Returns the path of the written dump.
+    pub fn dump<N: Inspect>(&self, net: &N, inputs: &N::In, epoch: usize) -> io::Result<PathBuf> {
+        let intermediate = net.intermediate(inputs);
+        self.write(&Dump::new(net, &intermediate, epoch))
+    }
+
+    /// Writes `dump` to the directory, replacing the dump of the same epoch if it exists, and
+    /// returns its path.
+    pub fn write(&self, dump: &Dump) -> io::Result<PathBuf> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(dump.epoch);
+        let mut writer = BufWriter::new(File::create(&path)?);
+        match self.format {
+            DumpFormat::Text => dump.write_text(&mut writer)?,
+            DumpFormat::Binary => dump.write_to(&mut writer)?,
+        }
+        writer.flush()?;
+        Ok(path)
+    }
+}
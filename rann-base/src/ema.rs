@@ -0,0 +1,182 @@
+/*!
+Exponential moving averages (Polyak averaging) of network parameters.
+
+An [`EmaWeights`] network trains the wrapped network as usual, but also keeps an exponential
+moving average of its parameters, updated after every training step. Evaluating with the
+averaged parameters smooths out the noise of the last training steps, which often improves the
+accuracy on test data.
+
+# Examples
+```rust
+use rann_base::{activ::Logistic, ema::EmaWeights, error::SquareError, testing, train, Full};
+use rann_traits::Network;
+
+let net = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1))
+    .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)));
+let mut ema = EmaWeights::new(net, 0.99).chain(SquareError { expected: [0.0] });
+for _ in 0..100 {
+    train::train_epoch(&mut ema, &testing::XOR, 0.5);
+}
+
+// Evaluate with the averaged parameters.
+let averaged = ema.first.averaged();
+println!("{:?}", averaged.eval(&[1.0, 0.0]));
+```
+*/
+
+use std::any::Any;
+
+use rann_traits::{
+    inspect::{DotGraph, Inspect, LayerView},
+    params::Parameterized,
+    Network, Scalar, Supervised,
+};
+
+/// A network that keeps an exponential moving average of its parameters. See
+/// [module level documentation](self) for more info.
+#[derive(Clone, Debug)]
+pub struct EmaWeights<T> {
+    /// The trained network, with the raw parameters.
+    pub net: T,
+    /// The weight of the previous average in every update, such as `0.999`. Higher values
+    /// average over more training steps.
+    pub decay: Scalar,
+    average: Vec<Scalar>,
+}
+
+impl<T: Parameterized> EmaWeights<T> {
+    /// Wraps `net`, starting the average at its current parameters.
+    pub fn new(net: T, decay: Scalar) -> Self {
+        let average = net.params();
+        Self {
+            net,
+            decay,
+            average,
+        }
+    }
+
+    /// Returns the averaged parameters, in the order of [`Parameterized`].
+    pub fn average(&self) -> &[Scalar] {
+        &self.average
+    }
+
+    /// Updates the average with the current parameters of the network. This is done after every
+    /// training step, so only needs to be called when the network is changed otherwise.
+    pub fn update(&mut self) {
+        let params = self.net.params();
+        for (avg, param) in self.average.iter_mut().zip(params) {
+            *avg = self.decay * *avg + (1.0 - self.decay) * param;
+        }
+    }
+
+    /// Restarts the average at the current parameters of the network.
+    pub fn reset(&mut self) {
+        self.average = self.net.params();
+    }
+
+    /// Returns a copy of the network with the averaged parameters, to evaluate with.
+    pub fn averaged(&self) -> T
+    where
+        T: Clone,
+    {
+        let mut net = self.net.clone();
+        net.read_params(&self.average);
+        net
+    }
+
+    /// Replaces the parameters of the network by the averaged parameters, such as at the end of
+    /// training, and returns it.
+    pub fn into_averaged(mut self) -> T {
+        self.net.read_params(&self.average);
+        self.net
+    }
+}
+
+impl<T> Network for EmaWeights<T>
+where
+    T: Network + Parameterized,
+{
+    type In = T::In;
+
+    type Out = T::Out;
+
+    type Inter = T::Inter;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        self.net.intermediate(inputs)
+    }
+
+    fn train_deriv(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        let gradients = self
+            .net
+            .train_deriv(inputs, intermediate, gradients, learning_rate);
+        self.update();
+        gradients
+    }
+
+    fn eval(&self, inputs: &Self::In) -> Self::Out {
+        self.net.eval(inputs)
+    }
+
+    fn find_layer(&self, name: &str) -> Option<&dyn Any> {
+        self.net.find_layer(name)
+    }
+
+    fn find_layer_mut(&mut self, name: &str) -> Option<&mut dyn Any> {
+        self.net.find_layer_mut(name)
+    }
+}
+
+impl<T> Supervised for EmaWeights<T>
+where
+    T: Supervised + Parameterized,
+{
+    type Target = T::Target;
+
+    fn set_target(&mut self, target: &Self::Target) {
+        self.net.set_target(target);
+    }
+}
+
+impl<T> Inspect for EmaWeights<T>
+where
+    T: Inspect + Parameterized,
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        self.net.visit_layers(intermediate, f);
+    }
+
+    fn write_dot(
+        &self,
+        intermediate: &Self::Inter,
+        dot: &mut DotGraph,
+        inputs: Vec<usize>,
+    ) -> Vec<usize> {
+        self.net.write_dot(intermediate, dot, inputs)
+    }
+}
+
+// Only the raw parameters are exposed, as those are the ones being trained. Reading parameters
+// does not change the average; use `reset()` to restart it.
+impl<T> Parameterized for EmaWeights<T>
+where
+    T: Parameterized,
+{
+    fn num_params(&self) -> usize {
+        self.net.num_params()
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        self.net.write_params(params);
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        self.net.read_params(params);
+    }
+}
@@ -1,6 +1,17 @@
+use std::{convert::Infallible, error::Error, fmt::Display};
+
 use arrayvec::ArrayVec;
-use rann_traits::{Network, Scalar};
+use rann_traits::{
+    deriv::Deriv,
+    inspect::{DotGraph, Inspect, LayerView},
+    mask::Masking,
+    params::Parameterized,
+    Intermediate, Network, Scalar, Supervised,
+};
+
+use crate::activ::{log_softmax, Logistic, Softplus};
 
+#[derive(Clone, Debug, PartialEq)]
 pub struct SquareError<const N: usize> {
     pub expected: [Scalar; N],
 }
@@ -27,20 +38,21 @@ impl<const N: usize> Network for SquareError<N> {
         // The intermediate results of the calculation associated to the inputs.
         _intermediate: &Self::Inter,
         // The gradients of the output relative to the error.
-        _gradients: &Self::Out,
+        gradients: &Self::Out,
         // The learning rate.
         _learning_rate: Scalar,
     ) -> Self::In {
         inputs
             .iter()
             .zip(self.expected)
-            .map(|(i, e)| 2.0 * (i - e))
+            .map(|(i, e)| 2.0 * (i - e) * gradients[0])
             .collect::<ArrayVec<Scalar, N>>()
             .into_inner()
             .expect("Capacity of ArrayVec should equal N.")
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
 pub struct SumError<const N: usize> {
     pub expected: [Scalar; N],
 }
@@ -67,16 +79,1058 @@ impl<const N: usize> Network for SumError<N> {
         // The intermediate results of the calculation associated to the inputs.
         _intermediate: &Self::Inter,
         // The gradients of the output relative to the error.
-        _gradients: &Self::Out,
+        gradients: &Self::Out,
         // The learning rate.
         _learning_rate: Scalar,
     ) -> Self::In {
         inputs
             .iter()
             .zip(self.expected)
-            .map(|(i, e)| i - e)
+            .map(|(i, e)| (i - e) * gradients[0])
             .collect::<ArrayVec<Scalar, N>>()
             .into_inner()
             .expect("Capacity of ArrayVec should equal N.")
     }
 }
+
+impl<const N: usize> Supervised for SquareError<N> {
+    type Target = [Scalar; N];
+
+    fn set_target(&mut self, target: &Self::Target) {
+        self.expected = *target;
+    }
+}
+
+impl<const N: usize> Supervised for SumError<N> {
+    type Target = [Scalar; N];
+
+    fn set_target(&mut self, target: &Self::Target) {
+        self.expected = *target;
+    }
+}
+
+// Error functions have no parameters, so only the error is exposed as their activation.
+impl<const N: usize> Inspect for SquareError<N> {
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        f(&LayerView {
+            kind: "SquareError",
+            num_inputs: N,
+            params: &[],
+            activations: intermediate,
+            gradient_norm: 0.0,
+        });
+    }
+}
+
+impl<const N: usize> Inspect for SumError<N> {
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        f(&LayerView {
+            kind: "SumError",
+            num_inputs: N,
+            params: &[],
+            activations: intermediate,
+            gradient_norm: 0.0,
+        });
+    }
+}
+
+// Error functions have no parameters.
+impl<const N: usize> Parameterized for SquareError<N> {
+    fn num_params(&self) -> usize {
+        0
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        assert!(params.is_empty(), "Error functions have no parameters.");
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        assert!(params.is_empty(), "Error functions have no parameters.");
+    }
+}
+
+impl<const N: usize> Parameterized for SumError<N> {
+    fn num_params(&self) -> usize {
+        0
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        assert!(params.is_empty(), "Error functions have no parameters.");
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        assert!(params.is_empty(), "Error functions have no parameters.");
+    }
+}
+
+/// The expected outputs of a [`TargetedLoss`]: either a value for each output, or a single value
+/// broadcast to all outputs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Target<const N: usize> {
+    /// A value for each output.
+    Each([Scalar; N]),
+    /// The same value for all outputs.
+    All(Scalar),
+}
+
+impl<const N: usize> Target<N> {
+    /// Returns the value expected for each output.
+    pub fn to_array(&self) -> [Scalar; N] {
+        match *self {
+            Target::Each(values) => values,
+            Target::All(value) => [value; N],
+        }
+    }
+}
+
+impl<const N: usize> From<[Scalar; N]> for Target<N> {
+    fn from(values: [Scalar; N]) -> Self {
+        Target::Each(values)
+    }
+}
+
+impl<const N: usize> From<Scalar> for Target<N> {
+    fn from(value: Scalar) -> Self {
+        Target::All(value)
+    }
+}
+
+// Slices of length one are broadcast, like scalars.
+impl<const N: usize> TryFrom<&[Scalar]> for Target<N> {
+    type Error = TargetLengthError;
+
+    fn try_from(values: &[Scalar]) -> Result<Self, Self::Error> {
+        match values.len() {
+            1 => Ok(Target::All(values[0])),
+            len if len == N => Ok(Target::Each(
+                values.try_into().expect("Length should equal N."),
+            )),
+            found => Err(TargetLengthError { expected: N, found }),
+        }
+    }
+}
+
+/// Error returned when a target does not have as many values as the network has outputs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TargetLengthError {
+    /// The number of outputs of the network.
+    pub expected: usize,
+    /// The number of values in the target.
+    pub found: usize,
+}
+
+impl Display for TargetLengthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "target has {} values, but expected 1 or {}",
+            self.found, self.expected
+        )
+    }
+}
+
+impl Error for TargetLengthError {}
+
+// Allows infallible conversions, such as from arrays and scalars, wherever targets are validated.
+impl From<Infallible> for TargetLengthError {
+    fn from(never: Infallible) -> Self {
+        match never {}
+    }
+}
+
+/// Converts rows of targets, such as those read from a dataset, into [`Target`]s, validating
+/// that every row has either one value or a value for each output.
+pub fn targets<const N: usize, R: AsRef<[Scalar]>>(
+    rows: impl IntoIterator<Item = R>,
+) -> Result<Vec<Target<N>>, TargetLengthError> {
+    rows.into_iter()
+        .map(|row| Target::try_from(row.as_ref()))
+        .collect()
+}
+
+/// An error function that is trained towards [`Target`]s, which can be broadcast from a single
+/// value.
+///
+/// # Examples
+/// ```rust
+/// use rann_base::error::{SquareError, TargetedLoss};
+/// use rann_traits::Network;
+///
+/// // Every output should be 0.5.
+/// let loss = TargetedLoss::<SquareError<3>>::square(0.5).unwrap();
+/// assert_eq!(loss.eval(&[0.5, 1.5, 0.5]), [1.0]);
+///
+/// // Targets with the wrong length are rejected.
+/// assert!(TargetedLoss::<SquareError<3>>::square(&[1.0, 2.0][..]).is_err());
+/// ```
+#[derive(Clone, Debug)]
+pub struct TargetedLoss<L> {
+    /// The wrapped error function.
+    pub loss: L,
+}
+
+impl<L, const N: usize> TargetedLoss<L>
+where
+    L: Supervised<In = [Scalar; N], Target = [Scalar; N]>,
+{
+    /// Wraps `loss`, and sets its target to `target`.
+    pub fn new<T>(mut loss: L, target: T) -> Result<Self, TargetLengthError>
+    where
+        T: TryInto<Target<N>>,
+        TargetLengthError: From<T::Error>,
+    {
+        loss.set_target(&target.try_into()?.to_array());
+        Ok(Self { loss })
+    }
+}
+
+impl<const N: usize> TargetedLoss<SquareError<N>> {
+    /// Creates a [`SquareError`] with the given target.
+    pub fn square<T>(target: T) -> Result<Self, TargetLengthError>
+    where
+        T: TryInto<Target<N>>,
+        TargetLengthError: From<T::Error>,
+    {
+        Self::new(SquareError { expected: [0.0; N] }, target)
+    }
+}
+
+impl<const N: usize> TargetedLoss<SumError<N>> {
+    /// Creates a [`SumError`] with the given target.
+    pub fn sum<T>(target: T) -> Result<Self, TargetLengthError>
+    where
+        T: TryInto<Target<N>>,
+        TargetLengthError: From<T::Error>,
+    {
+        Self::new(SumError { expected: [0.0; N] }, target)
+    }
+}
+
+impl<L> Network for TargetedLoss<L>
+where
+    L: Network,
+{
+    type In = L::In;
+
+    type Out = L::Out;
+
+    type Inter = L::Inter;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        self.loss.intermediate(inputs)
+    }
+
+    fn train_deriv(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        self.loss
+            .train_deriv(inputs, intermediate, gradients, learning_rate)
+    }
+}
+
+impl<L, const N: usize> Supervised for TargetedLoss<L>
+where
+    L: Supervised<In = [Scalar; N], Target = [Scalar; N]>,
+{
+    type Target = Target<N>;
+
+    fn set_target(&mut self, target: &Self::Target) {
+        self.loss.set_target(&target.to_array());
+    }
+}
+
+impl<L> Inspect for TargetedLoss<L>
+where
+    L: Inspect,
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        self.loss.visit_layers(intermediate, f);
+    }
+
+    fn write_dot(
+        &self,
+        intermediate: &Self::Inter,
+        dot: &mut DotGraph,
+        inputs: Vec<usize>,
+    ) -> Vec<usize> {
+        self.loss.write_dot(intermediate, dot, inputs)
+    }
+}
+
+impl<L> Parameterized for TargetedLoss<L>
+where
+    L: Parameterized,
+{
+    fn num_params(&self) -> usize {
+        self.loss.num_params()
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        self.loss.write_params(params);
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        self.loss.read_params(params);
+    }
+}
+
+/// An error function whose error and gradients are scaled by the weight of the class of the
+/// target, such that imbalanced classification datasets can be learned without resampling them.
+///
+/// The weight of a target is the sum of the class weights, weighted by the target values. For
+/// one-hot targets, this is the weight of the target class; for smoothed or soft targets, it is a
+/// mix of the weights. The weight is updated when the target is set, and is `1.0` before that.
+///
+/// # Examples
+/// ```rust
+/// use rann_base::error::{ClassWeighted, SquareError};
+/// use rann_traits::{Network, Supervised};
+///
+/// // The second class is three times as rare as the first.
+/// let mut loss = ClassWeighted::new(SquareError { expected: [0.0; 2] }, [1.0, 3.0]);
+/// loss.set_target(&[0.0, 1.0]);
+/// assert_eq!(loss.eval(&[0.5, 0.5]), [3.0 * 0.5]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ClassWeighted<L, const N: usize> {
+    /// The wrapped error function.
+    pub loss: L,
+    /// The weight of each class.
+    pub weights: [Scalar; N],
+    // The weight of the current target.
+    weight: Scalar,
+}
+
+impl<L, const N: usize> ClassWeighted<L, N> {
+    /// Weighs the error of `loss` by the `weights` of the classes.
+    pub fn new(loss: L, weights: [Scalar; N]) -> Self {
+        Self {
+            loss,
+            weights,
+            weight: 1.0,
+        }
+    }
+
+    /// Returns the weight of the current target.
+    pub fn weight(&self) -> Scalar {
+        self.weight
+    }
+}
+
+/// The intermediate values of an evaluation of a [`ClassWeighted`] error function.
+#[derive(Clone, Debug)]
+pub struct ClassWeightedInter<I> {
+    /// The intermediate values of the wrapped error function.
+    pub inner: I,
+    /// The weighted error.
+    pub output: [Scalar; 1],
+}
+
+impl<I> Intermediate for ClassWeightedInter<I> {
+    type Out = [Scalar; 1];
+
+    fn output(&self) -> &Self::Out {
+        &self.output
+    }
+
+    fn into_output(self) -> Self::Out {
+        self.output
+    }
+}
+
+impl<L, const N: usize> Network for ClassWeighted<L, N>
+where
+    L: Network<Out = [Scalar; 1]>,
+{
+    type In = L::In;
+
+    type Out = [Scalar; 1];
+
+    type Inter = ClassWeightedInter<L::Inter>;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        let inner = self.loss.intermediate(inputs);
+        let output = [inner.output()[0] * self.weight];
+        ClassWeightedInter { inner, output }
+    }
+
+    fn train_deriv(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        self.loss.train_deriv(
+            inputs,
+            &intermediate.inner,
+            &[gradients[0] * self.weight],
+            learning_rate,
+        )
+    }
+}
+
+impl<L, const N: usize> Supervised for ClassWeighted<L, N>
+where
+    L: Supervised<Target = [Scalar; N]>,
+{
+    type Target = [Scalar; N];
+
+    fn set_target(&mut self, target: &Self::Target) {
+        self.weight = target.iter().zip(self.weights).map(|(t, w)| t * w).sum();
+        self.loss.set_target(target);
+    }
+}
+
+impl<L, const N: usize> Inspect for ClassWeighted<L, N>
+where
+    L: Inspect<Out = [Scalar; 1]>,
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        f(&LayerView {
+            kind: "ClassWeighted",
+            num_inputs: N,
+            params: &[],
+            activations: &intermediate.output,
+            gradient_norm: 0.0,
+        });
+    }
+}
+
+// The class weights are hyperparameters, so only the parameters of the error function are exposed.
+impl<L, const N: usize> Parameterized for ClassWeighted<L, N>
+where
+    L: Parameterized,
+{
+    fn num_params(&self) -> usize {
+        self.loss.num_params()
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        self.loss.write_params(params);
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        self.loss.read_params(params);
+    }
+}
+
+/// How the losses of the samples of a batch are reduced by a [`BatchLoss`]: summed by
+/// [`ReduceSum`], averaged by [`ReduceMean`], or kept by [`ReduceNone`].
+pub trait Reduction<const B: usize>: Clone + std::fmt::Debug {
+    /// The reduced losses.
+    type Out;
+
+    /// Reduces the losses of the samples of a batch.
+    fn reduce(&self, losses: &[Scalar; B]) -> Self::Out;
+
+    /// Returns the gradient over the loss of every sample, for the `gradients` over the reduced
+    /// losses.
+    fn gradients(&self, gradients: &Self::Out) -> [Scalar; B];
+
+    /// Reduces the losses of the samples of which the flag in `mask` is `true`, as if the other
+    /// samples were not part of the batch.
+    ///
+    /// # Implementation note
+    /// The default implementation reduces the losses with the losses of the other samples set to
+    /// zero, which is correct for reductions that do not depend on the number of samples.
+    fn reduce_masked(&self, losses: &[Scalar; B], mask: &[bool; B]) -> Self::Out {
+        self.reduce(&std::array::from_fn(
+            |i| if mask[i] { losses[i] } else { 0.0 },
+        ))
+    }
+
+    /// Returns the gradient over the loss of every sample like [`Self::gradients()`], for losses
+    /// reduced by [`Self::reduce_masked()`]. The gradients of the other samples are zero.
+    fn gradients_masked(&self, gradients: &Self::Out, mask: &[bool; B]) -> [Scalar; B] {
+        let gradients = self.gradients(gradients);
+        std::array::from_fn(|i| if mask[i] { gradients[i] } else { 0.0 })
+    }
+}
+
+/// Reduces the losses of a batch to their sum, such that every sample is trained as if it was
+/// trained on alone.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReduceSum;
+
+impl<const B: usize> Reduction<B> for ReduceSum {
+    type Out = [Scalar; 1];
+
+    fn reduce(&self, losses: &[Scalar; B]) -> Self::Out {
+        [losses.iter().sum()]
+    }
+
+    fn gradients(&self, gradients: &Self::Out) -> [Scalar; B] {
+        [gradients[0]; B]
+    }
+}
+
+/// Reduces the losses of a batch to their mean, such that losses are comparable across batch
+/// sizes, and the size of an update does not grow with the batch size.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReduceMean;
+
+impl<const B: usize> Reduction<B> for ReduceMean {
+    type Out = [Scalar; 1];
+
+    fn reduce(&self, losses: &[Scalar; B]) -> Self::Out {
+        [losses.iter().sum::<Scalar>() / B as Scalar]
+    }
+
+    fn gradients(&self, gradients: &Self::Out) -> [Scalar; B] {
+        [gradients[0] / B as Scalar; B]
+    }
+
+    // The mean is taken over the samples in the mask, or is zero if there are none.
+    fn reduce_masked(&self, losses: &[Scalar; B], mask: &[bool; B]) -> Self::Out {
+        let count = mask.iter().filter(|&&valid| valid).count().max(1);
+        let sum: Scalar = losses
+            .iter()
+            .zip(mask)
+            .filter(|(_, &valid)| valid)
+            .map(|(loss, _)| loss)
+            .sum();
+        [sum / count as Scalar]
+    }
+
+    fn gradients_masked(&self, gradients: &Self::Out, mask: &[bool; B]) -> [Scalar; B] {
+        let count = mask.iter().filter(|&&valid| valid).count().max(1);
+        mask.map(|valid| {
+            if valid {
+                gradients[0] / count as Scalar
+            } else {
+                0.0
+            }
+        })
+    }
+}
+
+/// Keeps the loss of every sample of a batch, such as to weigh the samples by the gradients over
+/// their losses, or to find the hardest samples.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReduceNone;
+
+impl<const B: usize> Reduction<B> for ReduceNone {
+    type Out = [Scalar; B];
+
+    fn reduce(&self, losses: &[Scalar; B]) -> Self::Out {
+        *losses
+    }
+
+    fn gradients(&self, gradients: &Self::Out) -> [Scalar; B] {
+        *gradients
+    }
+}
+
+/// An error function over batches of `B` samples, that evaluates an error function on every
+/// sample and reduces their losses with `R`. Batches reduced to a single loss by [`ReduceSum`] or
+/// [`ReduceMean`] can be trained like any error function; the loss of every sample is kept by the
+/// intermediate values, see [`BatchLossInter::losses()`].
+///
+/// The samples can also be the steps of a sequence, such as the outputs of a
+/// [`TimeDistributed`](rann_traits::compose::TimeDistributed) network. With a
+/// [mask](rann_traits::mask), padded steps are left out of the reduced loss.
+///
+/// # Examples
+/// ```rust
+/// use rann_base::error::{BatchLoss, ReduceMean, ReduceNone, SquareError};
+/// use rann_traits::{Network, Supervised};
+///
+/// let mut loss = BatchLoss::<_, _, 2>::new(SquareError { expected: [0.0] }, ReduceMean);
+/// loss.set_target(&[[1.0], [0.0]]);
+/// let inter = loss.intermediate(&[[3.0], [2.0]]);
+/// assert_eq!(inter.losses(), [4.0, 4.0]);
+/// assert_eq!(inter.output, [4.0]);
+///
+/// // Without reduction, every sample has its own loss and gradient.
+/// let unreduced = BatchLoss::<_, _, 2>::new(SquareError { expected: [0.0] }, ReduceNone);
+/// assert_eq!(unreduced.eval(&[[3.0], [2.0]]), [9.0, 4.0]);
+/// assert_eq!(unreduced.gradient(&[[3.0], [2.0]], &[1.0, 0.0]), [[6.0], [0.0]]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchLoss<L, R, const B: usize> {
+    /// The error function of every sample, which holds its target.
+    pub losses: [L; B],
+    /// How the losses of the samples are reduced.
+    pub reduction: R,
+}
+
+impl<L: Clone, R, const B: usize> BatchLoss<L, R, B> {
+    /// Evaluates `loss` on every sample of a batch, and reduces the losses with `reduction`.
+    pub fn new(loss: L, reduction: R) -> Self {
+        Self {
+            losses: std::array::from_fn(|_| loss.clone()),
+            reduction,
+        }
+    }
+}
+
+/// The intermediate values of an evaluation of a [`BatchLoss`].
+#[derive(Clone, Debug)]
+pub struct BatchLossInter<I, O, const B: usize> {
+    /// The intermediate values of the error function of every sample.
+    pub samples: [I; B],
+    /// The reduced losses.
+    pub output: O,
+}
+
+impl<I, O, const B: usize> BatchLossInter<I, O, B>
+where
+    I: Intermediate<Out = [Scalar; 1]>,
+{
+    /// Returns the loss of every sample, before they were reduced.
+    pub fn losses(&self) -> [Scalar; B] {
+        std::array::from_fn(|i| self.samples[i].output()[0])
+    }
+}
+
+impl<I, O, const B: usize> Intermediate for BatchLossInter<I, O, B> {
+    type Out = O;
+
+    fn output(&self) -> &Self::Out {
+        &self.output
+    }
+
+    fn into_output(self) -> Self::Out {
+        self.output
+    }
+}
+
+impl<L, R, const B: usize> Network for BatchLoss<L, R, B>
+where
+    L: Network<Out = [Scalar; 1]>,
+    R: Reduction<B>,
+{
+    type In = [L::In; B];
+
+    type Out = R::Out;
+
+    type Inter = BatchLossInter<L::Inter, R::Out, B>;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        let samples: [L::Inter; B] =
+            std::array::from_fn(|i| self.losses[i].intermediate(&inputs[i]));
+        let losses = std::array::from_fn(|i| samples[i].output()[0]);
+        BatchLossInter {
+            samples,
+            output: self.reduction.reduce(&losses),
+        }
+    }
+
+    fn train_deriv(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        let gradients = self.reduction.gradients(gradients);
+        std::array::from_fn(|i| {
+            self.losses[i].train_deriv(
+                &inputs[i],
+                &intermediate.samples[i],
+                &[gradients[i]],
+                learning_rate,
+            )
+        })
+    }
+}
+
+// Padded samples are evaluated like the other samples, but are left out of the reduced loss.
+impl<L, R, const I: usize, const B: usize> Masking<B> for BatchLoss<L, R, B>
+where
+    L: Network<In = [Scalar; I], Out = [Scalar; 1]>,
+    R: Reduction<B>,
+{
+    fn intermediate_masked(&self, inputs: &Self::In, mask: &[bool; B]) -> Self::Inter {
+        let samples: [L::Inter; B] =
+            std::array::from_fn(|i| self.losses[i].intermediate(&inputs[i]));
+        let losses = std::array::from_fn(|i| samples[i].output()[0]);
+        BatchLossInter {
+            samples,
+            output: self.reduction.reduce_masked(&losses, mask),
+        }
+    }
+
+    fn train_masked(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        mask: &[bool; B],
+        learning_rate: Scalar,
+    ) -> Self::In {
+        let gradients = self.reduction.gradients_masked(gradients, mask);
+        std::array::from_fn(|i| {
+            if !mask[i] {
+                return [0.0; I];
+            }
+            self.losses[i].train_deriv(
+                &inputs[i],
+                &intermediate.samples[i],
+                &[gradients[i]],
+                learning_rate,
+            )
+        })
+    }
+}
+
+impl<L, R, const B: usize> Supervised for BatchLoss<L, R, B>
+where
+    L: Supervised,
+    R: Reduction<B, Out = [Scalar; 1]>,
+{
+    type Target = [L::Target; B];
+
+    fn set_target(&mut self, target: &Self::Target) {
+        for (loss, target) in self.losses.iter_mut().zip(target) {
+            loss.set_target(target);
+        }
+    }
+}
+
+// The losses of the samples are exposed as the activations.
+impl<L, R, const B: usize> Inspect for BatchLoss<L, R, B>
+where
+    L: Network<Out = [Scalar; 1]>,
+    R: Reduction<B>,
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        f(&LayerView {
+            kind: "BatchLoss",
+            num_inputs: B,
+            params: &[],
+            activations: &intermediate.losses(),
+            gradient_norm: 0.0,
+        });
+    }
+}
+
+// The parameters of the error functions of the samples, in order.
+impl<L, R, const B: usize> Parameterized for BatchLoss<L, R, B>
+where
+    L: Parameterized,
+{
+    fn num_params(&self) -> usize {
+        self.losses.iter().map(L::num_params).sum()
+    }
+
+    fn write_params(&self, mut params: &mut [Scalar]) {
+        for loss in &self.losses {
+            let (head, tail) = params.split_at_mut(loss.num_params());
+            loss.write_params(head);
+            params = tail;
+        }
+    }
+
+    fn read_params(&mut self, mut params: &[Scalar]) {
+        for loss in &mut self.losses {
+            let (head, tail) = params.split_at(loss.num_params());
+            loss.read_params(head);
+            params = tail;
+        }
+    }
+}
+
+/// The cross-entropy between the softmax of the inputs (the logits) and the expected class
+/// probabilities, for classification.
+///
+/// With label smoothing, the expected probabilities are mixed with a uniform distribution:
+/// targets become `(1 - smoothing) * expected + smoothing / N`. This keeps the network from
+/// becoming overconfident, as the logits are no longer pushed towards infinity.
+///
+/// # Examples
+/// ```rust
+/// use rann_base::error::CrossEntropy;
+/// use rann_traits::{Network, Supervised};
+///
+/// let mut loss = CrossEntropy::<3>::new().with_label_smoothing(0.1);
+/// loss.set_target(&[0.0, 1.0, 0.0]);
+/// // The gradients are the softmax minus the smoothed targets.
+/// let gradients = loss.gradient(&[0.0; 3], &[1.0]);
+/// let expected = [1.0 / 3.0 - 0.1 / 3.0, 1.0 / 3.0 - (0.9 + 0.1 / 3.0), 1.0 / 3.0 - 0.1 / 3.0];
+/// for (g, e) in gradients.iter().zip(expected) {
+///     assert!((g - e).abs() < 1e-6);
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct CrossEntropy<const N: usize> {
+    /// The expected probability of each class, such as a one-hot vector.
+    pub expected: [Scalar; N],
+    /// The amount of label smoothing, between `0.0` (none) and `1.0`.
+    pub smoothing: Scalar,
+}
+
+impl<const N: usize> CrossEntropy<N> {
+    /// Creates a cross-entropy without label smoothing, expecting the first class.
+    pub fn new() -> Self {
+        let mut expected = [0.0; N];
+        if let Some(first) = expected.first_mut() {
+            *first = 1.0;
+        }
+        Self {
+            expected,
+            smoothing: 0.0,
+        }
+    }
+
+    /// Sets the amount of label smoothing.
+    pub fn with_label_smoothing(self, smoothing: Scalar) -> Self {
+        Self { smoothing, ..self }
+    }
+
+    /// Returns the smoothed target probabilities.
+    pub fn targets(&self) -> [Scalar; N] {
+        let uniform = self.smoothing / N as Scalar;
+        self.expected.map(|e| (1.0 - self.smoothing) * e + uniform)
+    }
+}
+
+impl<const N: usize> Default for CrossEntropy<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The intermediate values of an evaluation of a [`CrossEntropy`].
+#[derive(Clone, Debug)]
+pub struct CrossEntropyInter<const N: usize> {
+    /// The softmax of the logits: the predicted probability of each class.
+    pub probs: [Scalar; N],
+    /// The cross-entropy.
+    pub error: [Scalar; 1],
+}
+
+impl<const N: usize> Intermediate for CrossEntropyInter<N> {
+    type Out = [Scalar; 1];
+
+    fn output(&self) -> &Self::Out {
+        &self.error
+    }
+
+    fn into_output(self) -> Self::Out {
+        self.error
+    }
+}
+
+impl<const N: usize> Network for CrossEntropy<N> {
+    type In = [Scalar; N];
+
+    type Out = [Scalar; 1];
+
+    type Inter = CrossEntropyInter<N>;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        let log_probs = log_softmax(inputs);
+        let error = self
+            .targets()
+            .iter()
+            .zip(log_probs)
+            .filter(|(t, _)| **t != 0.0)
+            .map(|(t, log_p)| -t * log_p)
+            .sum();
+        CrossEntropyInter {
+            probs: log_probs.map(Scalar::exp),
+            error: [error],
+        }
+    }
+
+    fn train_deriv(
+        &mut self,
+        _inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        _learning_rate: Scalar,
+    ) -> Self::In {
+        let targets = self.targets();
+        let mut grads = intermediate.probs;
+        for (g, t) in grads.iter_mut().zip(targets) {
+            *g = (*g - t) * gradients[0];
+        }
+        grads
+    }
+}
+
+impl<const N: usize> Supervised for CrossEntropy<N> {
+    type Target = [Scalar; N];
+
+    fn set_target(&mut self, target: &Self::Target) {
+        self.expected = *target;
+    }
+}
+
+impl<const N: usize> Inspect for CrossEntropy<N> {
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        f(&LayerView {
+            kind: "CrossEntropy",
+            num_inputs: N,
+            params: &[],
+            activations: &intermediate.error,
+            gradient_norm: 0.0,
+        });
+    }
+}
+
+impl<const N: usize> Parameterized for CrossEntropy<N> {
+    fn num_params(&self) -> usize {
+        0
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        assert!(params.is_empty(), "Error functions have no parameters.");
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        assert!(params.is_empty(), "Error functions have no parameters.");
+    }
+}
+
+/// The fused softmax and cross-entropy of [`CrossEntropy`], under the name of the fused nodes.
+/// See [`LogisticWithBce`] for when to prefer fused nodes.
+pub type SoftmaxWithCe<const N: usize> = CrossEntropy<N>;
+
+/// The binary cross-entropy between the logistic function of the inputs (the logits) and the
+/// expected probabilities, summed over the outputs, fused into one node for multi-label or
+/// binary classification.
+///
+/// Chaining a [`Logistic`](crate::activ::Logistic) layer and a separately computed binary
+/// cross-entropy is numerically unstable: for large logits, the logistic function rounds to
+/// exactly `0.0` or `1.0`, after which the logarithm of the cross-entropy overflows and its
+/// derivative divides by zero. Moreover, the derivative of the logistic function vanishes, so
+/// confidently wrong outputs barely train. This node computes the error as
+/// `max(x, 0) - t * x + ln(1 + exp(-|x|))`, which is finite for any logit, and its gradient as
+/// the simplified `logistic(x) - t`.
+///
+/// Prefer fused nodes, this or [`SoftmaxWithCe`], whenever a network is trained with a
+/// cross-entropy: end the network in a linear layer, and apply the logistic function (or the
+/// softmax) only when the probabilities themselves are needed, such as with
+/// [`LogisticWithBce::probabilities()`]. Separate activations and errors are only needed when
+/// other layers consume the probabilities.
+///
+/// # Examples
+/// ```rust
+/// use rann_base::error::LogisticWithBce;
+/// use rann_traits::{Network, Supervised};
+///
+/// let mut loss = LogisticWithBce::<2>::new();
+/// loss.set_target(&[1.0, 0.0]);
+/// // Confidently wrong logits have a large, finite error and a gradient of one.
+/// assert_eq!(loss.eval(&[-200.0, 0.0]), [200.0 + 2f32.ln()]);
+/// assert_eq!(loss.gradient(&[-200.0, 200.0], &[1.0]), [-1.0, 1.0]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogisticWithBce<const N: usize> {
+    /// The expected probability of each output.
+    pub expected: [Scalar; N],
+}
+
+impl<const N: usize> LogisticWithBce<N> {
+    /// Creates a binary cross-entropy expecting zero for every output.
+    pub fn new() -> Self {
+        Self { expected: [0.0; N] }
+    }
+
+    /// Returns the logistic function of `logits`: the predicted probability of each output.
+    pub fn probabilities(logits: &[Scalar; N]) -> [Scalar; N] {
+        logits.map(|x| Logistic.call(&x))
+    }
+}
+
+impl<const N: usize> Default for LogisticWithBce<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The intermediate values of an evaluation of a [`LogisticWithBce`].
+#[derive(Clone, Debug)]
+pub struct LogisticWithBceInter<const N: usize> {
+    /// The logistic function of the logits: the predicted probability of each output.
+    pub probs: [Scalar; N],
+    /// The binary cross-entropy.
+    pub error: [Scalar; 1],
+}
+
+impl<const N: usize> Intermediate for LogisticWithBceInter<N> {
+    type Out = [Scalar; 1];
+
+    fn output(&self) -> &Self::Out {
+        &self.error
+    }
+
+    fn into_output(self) -> Self::Out {
+        self.error
+    }
+}
+
+impl<const N: usize> Network for LogisticWithBce<N> {
+    type In = [Scalar; N];
+
+    type Out = [Scalar; 1];
+
+    type Inter = LogisticWithBceInter<N>;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        let error = inputs
+            .iter()
+            .zip(self.expected)
+            .map(|(x, t)| Softplus.call(x) - t * x)
+            .sum();
+        LogisticWithBceInter {
+            probs: Self::probabilities(inputs),
+            error: [error],
+        }
+    }
+
+    fn train_deriv(
+        &mut self,
+        _inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        _learning_rate: Scalar,
+    ) -> Self::In {
+        let mut grads = intermediate.probs;
+        for (g, t) in grads.iter_mut().zip(self.expected) {
+            *g = (*g - t) * gradients[0];
+        }
+        grads
+    }
+}
+
+impl<const N: usize> Supervised for LogisticWithBce<N> {
+    type Target = [Scalar; N];
+
+    fn set_target(&mut self, target: &Self::Target) {
+        self.expected = *target;
+    }
+}
+
+impl<const N: usize> Inspect for LogisticWithBce<N> {
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        f(&LayerView {
+            kind: "LogisticWithBce",
+            num_inputs: N,
+            params: &[],
+            activations: &intermediate.error,
+            gradient_norm: 0.0,
+        });
+    }
+}
+
+impl<const N: usize> Parameterized for LogisticWithBce<N> {
+    fn num_params(&self) -> usize {
+        0
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        assert!(params.is_empty(), "Error functions have no parameters.");
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        assert!(params.is_empty(), "Error functions have no parameters.");
+    }
+}
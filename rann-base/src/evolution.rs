@@ -0,0 +1,215 @@
+/*!
+Evolutionary training, as an alternative to backpropagation.
+
+[`Evolution`] optimizes the parameters of any [`Parameterized`] network without gradients. It
+maintains a population of parameter vectors, which are evaluated by a user supplied fitness
+function. Each generation, the fittest individuals are kept, and the rest of the population is
+replaced by offspring of individuals chosen by tournament selection, created by uniform crossover
+and Gaussian mutation.
+
+Only a single network is kept: the parameters of each individual are read into it to evaluate
+the individual.
+
+# Examples
+```rust
+use rann_base::{
+    activ::Logistic, error::SquareError, evolution::{Evolution, EvolutionConfig}, testing, train,
+    Full,
+};
+use rann_traits::Network;
+
+let mut evolution = Evolution::new(EvolutionConfig::default(), |rng| {
+    Full::<2, 3, _>::new(Logistic, testing::seeded_gen(rng.u64(..)))
+        .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(rng.u64(..))))
+        .chain(SquareError { expected: [0.0] })
+});
+for _ in 0..10 {
+    // The fitness is the mean error over the dataset, which is minimized.
+    evolution.generation(|net| train::mean_error(net, &testing::XOR));
+}
+let net = evolution.into_best();
+```
+*/
+
+use fastrand::Rng;
+use rann_traits::{params::Parameterized, Scalar};
+
+use crate::gen::gaussian;
+
+/// The configuration of an [`Evolution`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EvolutionConfig {
+    /// The number of individuals in the population.
+    pub population: usize,
+    /// The number of fittest individuals that are kept unchanged in the next generation.
+    pub elites: usize,
+    /// The number of individuals competing in a tournament to become a parent.
+    pub tournament: usize,
+    /// The probability for each parameter of an offspring to be mutated.
+    pub mutation_rate: Scalar,
+    /// The standard deviation of the Gaussian noise added to mutated parameters.
+    pub mutation_strength: Scalar,
+    /// The seed of the random number generator.
+    pub seed: u64,
+}
+
+impl Default for EvolutionConfig {
+    fn default() -> Self {
+        Self {
+            population: 50,
+            elites: 2,
+            tournament: 3,
+            mutation_rate: 0.1,
+            mutation_strength: 0.5,
+            seed: 0,
+        }
+    }
+}
+
+/// A member of the population of an [`Evolution`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Individual {
+    /// The parameters of the network.
+    pub params: Vec<Scalar>,
+    /// The fitness of the parameters, where lower is better. This is NaN if the individual has
+    /// not been evaluated yet.
+    pub fitness: Scalar,
+}
+
+/// Evolutionary optimization of the parameters of a network. See
+/// [module level documentation](self) for more info.
+pub struct Evolution<N> {
+    net: N,
+    population: Vec<Individual>,
+    // The fittest individual evaluated so far.
+    best: Option<Individual>,
+    rng: Rng,
+    /// The configuration of the evolution.
+    pub config: EvolutionConfig,
+}
+
+impl<N> Evolution<N>
+where
+    N: Parameterized,
+{
+    /// Creates a population of `config.population` networks created by `factory`.
+    ///
+    /// # Panics
+    /// Panics if the population is empty, or if it is smaller than the number of elites.
+    pub fn new(config: EvolutionConfig, mut factory: impl FnMut(&mut Rng) -> N) -> Self {
+        assert!(
+            0 < config.population && config.elites <= config.population,
+            "The population should not be empty and at least as large as the number of elites."
+        );
+        let mut rng = Rng::with_seed(config.seed);
+        let population = (0..config.population)
+            .map(|_| Individual {
+                params: factory(&mut rng).params(),
+                fitness: Scalar::NAN,
+            })
+            .collect();
+        Self {
+            net: factory(&mut rng),
+            population,
+            best: None,
+            rng,
+            config,
+        }
+    }
+
+    /// Borrows the population, starting with the elites after a generation.
+    pub fn population(&self) -> &[Individual] {
+        &self.population
+    }
+
+    /// Evaluates every individual using `fitness` and breeds the next generation. Returns the
+    /// best fitness of the evaluated generation.
+    ///
+    /// `fitness` is called with a network holding the parameters of an individual, and should
+    /// return a value to minimize, such as an error. Individuals with a NaN fitness are considered
+    /// the least fit.
+    pub fn generation<F>(&mut self, mut fitness: F) -> Scalar
+    where
+        F: FnMut(&mut N) -> Scalar,
+    {
+        for individual in &mut self.population {
+            self.net.read_params(&individual.params);
+            individual.fitness = fitness(&mut self.net);
+        }
+        self.population
+            .sort_by(|a, b| fitness_key(a).total_cmp(&fitness_key(b)));
+        let best = &self.population[0];
+        if self
+            .best
+            .as_ref()
+            .is_none_or(|b| fitness_key(best) < fitness_key(b))
+        {
+            self.best = Some(best.clone());
+        }
+        let best = best.fitness;
+
+        // The elites survive, and the rest of the population is replaced by offspring.
+        let offspring: Vec<_> = (self.config.elites..self.population.len())
+            .map(|_| {
+                let a = self.select();
+                let b = self.select();
+                self.breed(a, b)
+            })
+            .collect();
+        self.population.truncate(self.config.elites);
+        self.population.extend(offspring);
+        best
+    }
+
+    /// Returns the fittest individual evaluated so far, if any generation has been evaluated.
+    pub fn best(&self) -> Option<&Individual> {
+        self.best.as_ref()
+    }
+
+    /// Returns a network with the parameters of the fittest individual evaluated so far, or of
+    /// the first individual if no generation has been evaluated.
+    pub fn into_best(mut self) -> N {
+        let best = self.best.as_ref().unwrap_or(&self.population[0]);
+        self.net.read_params(&best.params);
+        self.net
+    }
+
+    // Selects a parent by tournament, returning its index.
+    fn select(&mut self) -> usize {
+        (0..self.config.tournament.max(1))
+            .map(|_| self.rng.usize(..self.population.len()))
+            .min_by(|&a, &b| {
+                fitness_key(&self.population[a]).total_cmp(&fitness_key(&self.population[b]))
+            })
+            .expect("The tournament should not be empty.")
+    }
+
+    // Creates an offspring of two parents by uniform crossover and mutation.
+    fn breed(&mut self, a: usize, b: usize) -> Individual {
+        let (a, b) = (&self.population[a].params, &self.population[b].params);
+        let params = a
+            .iter()
+            .zip(b)
+            .map(|(&a, &b)| {
+                let gene = if self.rng.bool() { a } else { b };
+                if self.rng.f32() < self.config.mutation_rate {
+                    gene + self.config.mutation_strength * gaussian(&mut self.rng)
+                } else {
+                    gene
+                }
+            })
+            .collect();
+        Individual {
+            params,
+            fitness: Scalar::NAN,
+        }
+    }
+}
+
+fn fitness_key(individual: &Individual) -> Scalar {
+    if individual.fitness.is_nan() {
+        Scalar::INFINITY
+    } else {
+        individual.fitness
+    }
+}
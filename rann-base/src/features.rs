@@ -0,0 +1,265 @@
+/*!
+Fixed feature expansions.
+
+A feature expansion maps the inputs to a larger number of nonlinear features, such that a linear
+layer following it can fit nonlinear problems. The expansions have no parameters and are not
+trained, but they pass the gradients through to the layers before them:
+
+- [`Polynomial`] computes all monomials of the inputs up to a given degree,
+- [`Rbf`] computes Gaussian radial basis functions around chosen centers.
+
+# Examples
+A single layer can not fit XOR on its inputs, but it can on their polynomial features:
+```rust
+use rann_base::{
+    activ::Logistic,
+    error::SquareError,
+    features::{self, Polynomial},
+    testing, Full,
+};
+use rann_traits::Network;
+
+const FEATURES: usize = features::num_monomials(2, 2);
+
+// The features are x, y, x², xy and y².
+let mut net = Polynomial::<2, FEATURES>::new(2)
+    .chain(Full::<FEATURES, 1, _>::new(Logistic, testing::seeded_gen(2)))
+    .chain(SquareError { expected: [0.0] });
+testing::assert_network_converges(&mut net, &testing::XOR, 5000, 1.0, 0.01);
+```
+*/
+
+use rann_traits::{
+    inspect::{Inspect, LayerView},
+    params::Parameterized,
+    Network, Scalar,
+};
+
+/// Returns the number of monomials of `num_inputs` inputs with a degree from one up to and
+/// including `degree`, which is the number of features of a [`Polynomial`].
+pub const fn num_monomials(num_inputs: usize, degree: usize) -> usize {
+    // The binomial coefficient (num_inputs + degree choose degree), minus the constant monomial.
+    let mut count = 1;
+    let mut i = 1;
+    while i <= degree {
+        count = count * (num_inputs + i) / i;
+        i += 1;
+    }
+    count - 1
+}
+
+/// Polynomial features: all monomials of the `N` inputs with a degree from one up to a chosen
+/// degree, such as `x`, `y`, `x²`, `xy` and `y²` for two inputs and degree two.
+///
+/// The monomials are ordered by degree, and then by their exponents in descending lexicographical
+/// order. `M` is the number of monomials, see [`num_monomials()`]. The constant monomial is left
+/// out, as the following layer has biases.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Polynomial<const N: usize, const M: usize> {
+    exponents: [[u32; N]; M],
+}
+
+impl<const N: usize, const M: usize> Polynomial<N, M> {
+    /// Creates polynomial features up to and including `degree`.
+    ///
+    /// # Panics
+    /// Panics if `M` is not [`num_monomials(N, degree)`](num_monomials).
+    pub fn new(degree: u32) -> Self {
+        assert_eq!(
+            M,
+            num_monomials(N, degree as usize),
+            "Polynomial features of {N} inputs up to degree {degree} should have {} features.",
+            num_monomials(N, degree as usize)
+        );
+        let mut exponents = Vec::with_capacity(M);
+        for d in 1..=degree {
+            push_monomials(&mut [0; N], 0, d, &mut exponents);
+        }
+        Self {
+            exponents: exponents
+                .try_into()
+                .expect("The number of monomials should equal M."),
+        }
+    }
+
+    /// Returns the exponents of the inputs in every monomial.
+    pub fn exponents(&self) -> &[[u32; N]; M] {
+        &self.exponents
+    }
+}
+
+// Pushes the exponents of the monomials of degree `remaining` in the inputs from `first` on,
+// onto those already in `current`, in descending lexicographical order.
+fn push_monomials<const N: usize>(
+    current: &mut [u32; N],
+    first: usize,
+    remaining: u32,
+    out: &mut Vec<[u32; N]>,
+) {
+    if remaining == 0 {
+        out.push(*current);
+        return;
+    }
+    if first == N {
+        return;
+    }
+    for e in (0..=remaining).rev() {
+        current[first] = e;
+        push_monomials(current, first + 1, remaining - e, out);
+    }
+    current[first] = 0;
+}
+
+impl<const N: usize, const M: usize> Network for Polynomial<N, M> {
+    type In = [Scalar; N];
+
+    type Out = [Scalar; M];
+
+    type Inter = [Scalar; M];
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        self.exponents.map(|exponents| {
+            inputs
+                .iter()
+                .zip(exponents)
+                .map(|(x, e)| x.powi(e as i32))
+                .product()
+        })
+    }
+
+    fn train_deriv(
+        &mut self,
+        inputs: &Self::In,
+        _intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        _learning_rate: Scalar,
+    ) -> Self::In {
+        let mut out = [0.0; N];
+        for (exponents, gr) in self.exponents.iter().zip(gradients) {
+            // The partial derivative of the monomial to every input it contains.
+            for (i, &e) in exponents.iter().enumerate().filter(|(_, &e)| e > 0) {
+                let partial: Scalar = inputs
+                    .iter()
+                    .zip(exponents)
+                    .enumerate()
+                    .map(|(j, (x, &ej))| {
+                        if j == i {
+                            x.powi(ej as i32 - 1)
+                        } else {
+                            x.powi(ej as i32)
+                        }
+                    })
+                    .product();
+                out[i] += e as Scalar * partial * gr;
+            }
+        }
+        out
+    }
+}
+
+/// Gaussian radial basis function features: the feature of every center `c` is
+/// `exp(-gamma * |x - c|²)` for the inputs `x`, which is one at the center and decays with the
+/// distance from it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rbf<const N: usize, const M: usize> {
+    /// The centers of the basis functions.
+    pub centers: [[Scalar; N]; M],
+    /// The width parameter, where larger values give narrower basis functions.
+    pub gamma: Scalar,
+}
+
+impl<const N: usize, const M: usize> Rbf<N, M> {
+    /// Creates RBF features around `centers`, with width parameter `gamma`.
+    pub fn new(centers: [[Scalar; N]; M], gamma: Scalar) -> Self {
+        Self { centers, gamma }
+    }
+}
+
+impl<const N: usize, const M: usize> Network for Rbf<N, M> {
+    type In = [Scalar; N];
+
+    type Out = [Scalar; M];
+
+    type Inter = [Scalar; M];
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        self.centers.map(|center| {
+            let dist: Scalar = inputs
+                .iter()
+                .zip(center)
+                .map(|(x, c)| (x - c) * (x - c))
+                .sum();
+            (-self.gamma * dist).exp()
+        })
+    }
+
+    fn train_deriv(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        _learning_rate: Scalar,
+    ) -> Self::In {
+        let mut out = [0.0; N];
+        for ((center, phi), gr) in self.centers.iter().zip(intermediate).zip(gradients) {
+            for ((o, x), c) in out.iter_mut().zip(inputs).zip(center) {
+                *o += -2.0 * self.gamma * (x - c) * phi * gr;
+            }
+        }
+        out
+    }
+}
+
+// Feature expansions have no parameters, so only the features are exposed as their activations.
+impl<const N: usize, const M: usize> Inspect for Polynomial<N, M> {
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        f(&LayerView {
+            kind: "Polynomial",
+            num_inputs: N,
+            params: &[],
+            activations: intermediate,
+            gradient_norm: 0.0,
+        });
+    }
+}
+
+impl<const N: usize, const M: usize> Inspect for Rbf<N, M> {
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        f(&LayerView {
+            kind: "Rbf",
+            num_inputs: N,
+            params: &[],
+            activations: intermediate,
+            gradient_norm: 0.0,
+        });
+    }
+}
+
+// The exponents and centers are fixed, so they are not parameters.
+impl<const N: usize, const M: usize> Parameterized for Polynomial<N, M> {
+    fn num_params(&self) -> usize {
+        0
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        assert!(params.is_empty(), "Feature expansions have no parameters.");
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        assert!(params.is_empty(), "Feature expansions have no parameters.");
+    }
+}
+
+impl<const N: usize, const M: usize> Parameterized for Rbf<N, M> {
+    fn num_params(&self) -> usize {
+        0
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        assert!(params.is_empty(), "Feature expansions have no parameters.");
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        assert!(params.is_empty(), "Feature expansions have no parameters.");
+    }
+}
@@ -1,17 +1,105 @@
+use std::{error::Error, fmt::Display, io};
+
 use arrayvec::ArrayVec;
-use nalgebra::{Const, MatrixView, SMatrix};
-use rann_traits::{deriv::Deriv, Intermediate, Network, Scalar};
+use nalgebra::{Const, Dyn, MatrixView, MatrixViewMut, SMatrix, SVector};
+use rann_traits::{
+    compose::Shared,
+    deriv::{Deriv, Elementwise},
+    inspect::{short_type_name, Inspect, LayerView},
+    params::Parameterized,
+    Intermediate, Network, Scalar,
+};
+
+use crate::{
+    checkpoint::invalid,
+    constraint::{Constrain, WeightConstraint},
+    npy::{Array, Npz},
+};
+
+/// The largest number of inputs and outputs of a [`Full`] layer that copies its inputs into a
+/// static vector when it is evaluated, such that the product with its weights uses the kernels for
+/// small static matrices, instead of the general product with a view of the inputs.
+pub const TINY: usize = 8;
 
 /// A fully connected network layer, with a given input and output size and an activation function.
-pub struct Full<const NUM_IN: usize, const NUM_OUT: usize, A> {
+///
+/// The biases are stored in `B`: an array by default, or [`NoBias`] for layers without biases,
+/// created by [`Full::without_bias()`]. Layers with at most [`TINY`] inputs and outputs are
+/// evaluated with a faster path for small matrices.
+#[derive(Clone, Debug)]
+pub struct Full<const NUM_IN: usize, const NUM_OUT: usize, A, B = [Scalar; NUM_OUT]> {
     weights: SMatrix<Scalar, NUM_OUT, NUM_IN>,
-    biases: [Scalar; NUM_OUT],
+    biases: B,
     act: A,
+    // The norm of the gradients over the parameters in the last training step.
+    grad_norm: Scalar,
+    // The number of largest gradients to backpropagate, or all if `None`.
+    top_k: Option<usize>,
+}
+
+/// The biases of a [`Full`] layer with `N` outputs.
+pub trait Biases<const N: usize>: Clone + std::fmt::Debug {
+    /// Borrows the biases, which are either `N` or none.
+    fn as_slice(&self) -> &[Scalar];
+    /// Mutably borrows the biases.
+    fn as_mut_slice(&mut self) -> &mut [Scalar];
 }
 
-impl<const NUM_IN: usize, const NUM_OUT: usize, A> Network for Full<NUM_IN, NUM_OUT, A>
+impl<const N: usize> Biases<N> for [Scalar; N] {
+    fn as_slice(&self) -> &[Scalar] {
+        self
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [Scalar] {
+        self
+    }
+}
+
+/// The biases of a [`Full`] layer without biases, such as right before a normalization layer,
+/// which takes no memory and no time to train.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NoBias;
+
+impl<const N: usize> Biases<N> for NoBias {
+    fn as_slice(&self) -> &[Scalar] {
+        &[]
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [Scalar] {
+        &mut []
+    }
+}
+
+impl<const NUM_IN: usize, const NUM_OUT: usize, A, B: Biases<NUM_OUT>> Full<NUM_IN, NUM_OUT, A, B> {
+    fn weighted_sums(&self, input: &[Scalar; NUM_IN]) -> [Scalar; NUM_OUT] {
+        if NUM_IN <= TINY && NUM_OUT <= TINY {
+            return self.tiny_weighted_sums(input);
+        }
+        let mat = MatrixView::from_slice_generic(input, Const::<NUM_IN>, Const::<1>);
+        // Multiply the matrices to find the weighted sums.
+        let mut out = self.weights * mat;
+        // Apply bias to the weighted sums.
+        for (sum, bias) in out.iter_mut().zip(self.biases.as_slice()) {
+            *sum += bias;
+        }
+        out.data.0[0]
+    }
+
+    // Finds the weighted sums of tiny layers like the general path, but multiplies with a copy
+    // of the inputs, for which the product is unrolled at compile time.
+    fn tiny_weighted_sums(&self, input: &[Scalar; NUM_IN]) -> [Scalar; NUM_OUT] {
+        let mut sums = self.weights * SVector::from(*input);
+        for (sum, bias) in sums.iter_mut().zip(self.biases.as_slice()) {
+            *sum += bias;
+        }
+        sums.data.0[0]
+    }
+}
+
+impl<const NUM_IN: usize, const NUM_OUT: usize, A, B> Network for Full<NUM_IN, NUM_OUT, A, B>
 where
     A: Deriv<In = Scalar, Out = Scalar>,
+    B: Biases<NUM_OUT>,
 {
     type In = [Scalar; NUM_IN];
 
@@ -20,25 +108,31 @@ where
     type Inter = FullInter<NUM_OUT>;
 
     fn intermediate(&self, input: &Self::In) -> Self::Inter {
-        let mat = MatrixView::from_slice_generic(input, Const::<NUM_IN>, Const::<1>);
-        // Multiply the matrices to find the weighted sums.
-        let mut out = self.weights * mat;
-        // Apply bias to the weighted sums.
-        for (sum, bias) in out.iter_mut().zip(self.biases) {
-            *sum += bias;
-        }
-        // Clone the weighted sums to store them.
-        let sums: [Scalar; NUM_OUT] = out.data.0[0].clone();
+        let sums = self.weighted_sums(input);
         // Apply the activation function to the weighted sums.
-        for sum in out.iter_mut() {
-            *sum = self.act.call(&sum);
-        }
         FullInter {
+            outputs: Elementwise::new(&self.act).call(&sums),
             weighted_sums: sums,
-            outputs: out.data.0[0],
         }
     }
 
+    fn eval(&self, input: &Self::In) -> Self::Out {
+        // The weighted sums are not kept, so the activation function is applied in place.
+        let mut outputs = self.weighted_sums(input);
+        activate(&self.act, &mut outputs);
+        outputs
+    }
+
+    fn eval_many(&self, inputs: &[Self::In]) -> Vec<Self::Out> {
+        // Every column holds the inputs or the outputs of a sample, so the weighted sums of the
+        // whole batch are a single matrix product.
+        let mut outputs = vec![[0.0; NUM_OUT]; inputs.len()];
+        let mut sums = batch_view_mut(&mut outputs);
+        sums.gemm(1.0, &self.weights, &batch_view(inputs), 0.0);
+        finish_batch(&mut outputs, self.biases.as_slice(), &self.act);
+        outputs
+    }
+
     fn train_deriv(
         &mut self,
         input: &Self::In,
@@ -47,13 +141,18 @@ where
         learning_rate: Scalar,
     ) -> Self::In {
         // Calculate the gradients over the activation
-        let grad: ArrayVec<Scalar, NUM_OUT> = gradients
-            .iter()
-            .zip(intermediate.weighted_sums.iter())
-            .map(|(gr, sum)| gr * self.act.deriv(sum))
-            .collect();
+        let mut grad = Elementwise::new(&self.act).backprop(&intermediate.weighted_sums, gradients);
+        if let Some(k) = self.top_k {
+            keep_top_k(&mut grad, k, &mut [0; NUM_OUT]);
+        }
+        let biases = self.biases.as_mut_slice();
+        self.grad_norm = if biases.is_empty() {
+            (squared_norm(&grad) * squared_norm(input)).sqrt()
+        } else {
+            param_grad_norm(&grad, input)
+        };
         // Update the biases
-        for (bias, grad) in self.biases.iter_mut().zip(grad.iter()) {
+        for (bias, grad) in biases.iter_mut().zip(grad.iter()) {
             *bias -= grad * learning_rate;
         }
         // Calculate the gradients over each weight and update it correspondingly.
@@ -98,22 +197,293 @@ where
         G: FnMut(usize) -> Scalar,
     {
         let (weight_gen, bias_gen) = gen.into();
-        let weights = SMatrix::from_fn(weight_gen);
-        let biases: ArrayVec<_, NUM_OUT> = (0..NUM_OUT).into_iter().map(bias_gen).collect();
         Self {
             act: activation,
-            weights,
-            biases: biases
-                .into_inner()
-                .expect("Capacity of ArrayVec should equal NUM_OUT."),
+            weights: SMatrix::from_fn(weight_gen),
+            biases: std::array::from_fn(bias_gen),
+            grad_norm: 0.0,
+            top_k: None,
         }
     }
+
+    /// Creates a fully connected layer like [`Self::new()`], but returns an error if the
+    /// generators produce a weight or bias that is NaN or infinite, rather than a layer that
+    /// only outputs NaN.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use rann_base::{activ::Logistic, full::FullError, Full};
+    ///
+    /// let gen = (|i, j| 1.0 / (i + j) as f32, |_| 0.0);
+    /// let layer = Full::<2, 2, _>::try_new(Logistic, gen);
+    /// let error = FullError::NonFiniteWeight { output: 0, input: 0, value: f32::INFINITY };
+    /// assert_eq!(layer.unwrap_err(), error);
+    /// ```
+    pub fn try_new<T, F, G>(activation: A, gen: T) -> Result<Self, FullError>
+    where
+        T: Into<(F, G)>,
+        F: FnMut(usize, usize) -> Scalar,
+        G: FnMut(usize) -> Scalar,
+    {
+        let layer = Self::new(activation, gen);
+        layer.check_finite()?;
+        Ok(layer)
+    }
+}
+
+impl<const NUM_IN: usize, const NUM_OUT: usize, A> Full<NUM_IN, NUM_OUT, A, NoBias>
+where
+    A: Deriv<In = Scalar, Out = Scalar>,
+{
+    /// Creates a fully connected layer without biases, with the given activation and with
+    /// weights generated like [`Self::new()`]. The bias generator is not used.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use rann_base::{activ::Logistic, gen::Random, Full};
+    /// use rann_traits::{params::Parameterized, Network};
+    ///
+    /// let layer = Full::<3, 2, _, _>::without_bias(Logistic, Random);
+    /// assert_eq!(layer.num_params(), 3 * 2);
+    /// // Without biases, zero inputs have weighted sums of zero.
+    /// assert_eq!(layer.eval(&[0.0; 3]), [0.5; 2]);
+    /// ```
+    pub fn without_bias<T, F, G>(activation: A, gen: T) -> Self
+    where
+        T: Into<(F, G)>,
+        F: FnMut(usize, usize) -> Scalar,
+        G: FnMut(usize) -> Scalar,
+    {
+        let (weight_gen, _) = gen.into();
+        Self {
+            act: activation,
+            weights: SMatrix::from_fn(weight_gen),
+            biases: NoBias,
+            grad_norm: 0.0,
+            top_k: None,
+        }
+    }
+
+    /// Creates a fully connected layer without biases like [`Self::without_bias()`], but returns
+    /// an error if the generator produces a weight that is NaN or infinite.
+    pub fn try_without_bias<T, F, G>(activation: A, gen: T) -> Result<Self, FullError>
+    where
+        T: Into<(F, G)>,
+        F: FnMut(usize, usize) -> Scalar,
+        G: FnMut(usize) -> Scalar,
+    {
+        let layer = Self::without_bias(activation, gen);
+        layer.check_finite()?;
+        Ok(layer)
+    }
+}
+
+impl<const NUM_IN: usize, const NUM_OUT: usize, A, B> Full<NUM_IN, NUM_OUT, A, B>
+where
+    B: Biases<NUM_OUT>,
+{
+    // Returns an error for the first weight or bias that is not finite.
+    fn check_finite(&self) -> Result<(), FullError> {
+        for output in 0..NUM_OUT {
+            for input in 0..NUM_IN {
+                let value = self.weights[(output, input)];
+                if !value.is_finite() {
+                    return Err(FullError::NonFiniteWeight {
+                        output,
+                        input,
+                        value,
+                    });
+                }
+            }
+        }
+        match self.biases.as_slice().iter().position(|b| !b.is_finite()) {
+            Some(output) => Err(FullError::NonFiniteBias {
+                output,
+                value: self.biases.as_slice()[output],
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Error returned when the generators of a [`Full`] layer produce invalid parameters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FullError {
+    /// The weight from `input` to `output` is NaN or infinite.
+    NonFiniteWeight {
+        /// The output the weight belongs to.
+        output: usize,
+        /// The input the weight belongs to.
+        input: usize,
+        /// The generated weight.
+        value: Scalar,
+    },
+    /// The bias of `output` is NaN or infinite.
+    NonFiniteBias {
+        /// The output the bias belongs to.
+        output: usize,
+        /// The generated bias.
+        value: Scalar,
+    },
+}
+
+impl Display for FullError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FullError::NonFiniteWeight {
+                output,
+                input,
+                value,
+            } => write!(
+                f,
+                "weight from input {input} to output {output} is {value}, but should be finite"
+            ),
+            FullError::NonFiniteBias { output, value } => {
+                write!(
+                    f,
+                    "bias of output {output} is {value}, but should be finite"
+                )
+            }
+        }
+    }
+}
+
+impl Error for FullError {}
+
+impl<const NUM_IN: usize, const NUM_OUT: usize, A, B> Full<NUM_IN, NUM_OUT, A, B> {
+    /// Only backpropagates the `k` gradients over the outputs with the largest magnitude in each
+    /// training step, zeroing the rest (meProp). Only the weights and biases of those outputs are
+    /// updated, which sparsifies training and can speed it up without losing much accuracy.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use rann_base::{activ::LeakyRelu, Full};
+    /// use rann_traits::Network;
+    ///
+    /// let gen = (|_, _| 1.0, |_| 0.0);
+    /// let mut layer = Full::<1, 3, _>::new(LeakyRelu(1.0), gen).with_top_k(1);
+    /// let inter = layer.intermediate(&[1.0]);
+    /// layer.train_deriv(&[1.0], &inter, &[0.1, -0.5, 0.2], 1.0);
+    /// // Only the output with the largest gradient was trained.
+    /// assert_eq!(layer.eval(&[1.0]), [1.0, 2.0, 1.0]);
+    /// ```
+    pub fn with_top_k(self, k: usize) -> Self {
+        Self {
+            top_k: Some(k),
+            ..self
+        }
+    }
+
+    /// Returns the number of gradients backpropagated in each training step, if limited.
+    pub fn top_k(&self) -> Option<usize> {
+        self.top_k
+    }
+
+    /// Borrows the activation function of this layer.
+    pub fn activation(&self) -> &A {
+        &self.act
+    }
+}
+
+impl<const NUM_IN: usize, const NUM_OUT: usize, A, B> Full<NUM_IN, NUM_OUT, A, B>
+where
+    B: Biases<NUM_OUT>,
+{
+    /// Adds the parameters of this layer to `npz`: the weights as `{prefix}weight`, with shape
+    /// `[NUM_OUT, NUM_IN]`, and the biases, if any, as `{prefix}bias`, with shape `[NUM_OUT]`.
+    /// Every row of the weights holds the incoming weights of an output, like the weights of a
+    /// PyTorch `Linear` layer, and the transpose of the kernel of a Keras `Dense` layer. See
+    /// [`crate::npy`] for more info.
+    pub fn write_npz(&self, npz: &mut Npz, prefix: &str) {
+        let weights = self.weights.transpose().as_slice().to_vec();
+        npz.insert(
+            format!("{prefix}weight"),
+            Array::new(vec![NUM_OUT, NUM_IN], weights),
+        );
+        if !self.biases.as_slice().is_empty() {
+            let biases = self.biases.as_slice().to_vec();
+            npz.insert(format!("{prefix}bias"), Array::new(vec![NUM_OUT], biases));
+        }
+    }
+
+    /// Reads the parameters of this layer from `npz`, as written by [`Self::write_npz()`].
+    ///
+    /// # Errors
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if an array is missing or has the
+    /// wrong shape, or if `npz` holds biases for a layer without biases. The layer is unchanged
+    /// on errors.
+    pub fn read_npz(&mut self, npz: &Npz, prefix: &str) -> io::Result<()> {
+        let weights = npz.get_shaped(&format!("{prefix}weight"), &[NUM_OUT, NUM_IN])?;
+        let bias = format!("{prefix}bias");
+        let biases = match self.biases.as_slice().len() {
+            0 if npz.get(&bias).is_some() => {
+                return Err(invalid(format!("array {bias} is for a layer with biases")));
+            }
+            0 => &[],
+            _ => npz.get_shaped(&bias, &[NUM_OUT])?,
+        };
+        self.weights = SMatrix::from_row_slice(weights);
+        self.biases.as_mut_slice().copy_from_slice(biases);
+        Ok(())
+    }
+}
+
+impl<const NUM_IN: usize, const NUM_OUT: usize, A, B> Inspect for Full<NUM_IN, NUM_OUT, A, B>
+where
+    A: Deriv<In = Scalar, Out = Scalar>,
+    B: Biases<NUM_OUT>,
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        let kind = format!("Full ({})", short_type_name::<A>());
+        f(&LayerView {
+            kind: &kind,
+            num_inputs: NUM_IN,
+            params: &[self.weights.as_slice(), self.biases.as_slice()],
+            activations: intermediate.output(),
+            gradient_norm: self.grad_norm,
+        });
+    }
+}
+
+// Each row of the weight matrix holds the incoming weights of a neuron.
+impl<const NUM_IN: usize, const NUM_OUT: usize, A, B> Constrain for Full<NUM_IN, NUM_OUT, A, B> {
+    fn constrain(&mut self, constraint: &WeightConstraint) {
+        for mut row in self.weights.row_iter_mut() {
+            let mut weights: ArrayVec<Scalar, NUM_IN> = row.iter().copied().collect();
+            constraint.apply(&mut weights);
+            for (w, constrained) in row.iter_mut().zip(weights) {
+                *w = constrained;
+            }
+        }
+    }
+}
+
+// The weights, in column-major order, are followed by the biases, if any.
+impl<const NUM_IN: usize, const NUM_OUT: usize, A, B> Parameterized for Full<NUM_IN, NUM_OUT, A, B>
+where
+    B: Biases<NUM_OUT>,
+{
+    fn num_params(&self) -> usize {
+        NUM_IN * NUM_OUT + self.biases.as_slice().len()
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        let (weights, biases) = params.split_at_mut(NUM_IN * NUM_OUT);
+        weights.copy_from_slice(self.weights.as_slice());
+        biases.copy_from_slice(self.biases.as_slice());
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        let (weights, biases) = params.split_at(NUM_IN * NUM_OUT);
+        self.weights.copy_from_slice(weights);
+        self.biases.as_mut_slice().copy_from_slice(biases);
+    }
 }
 
 /// The intermediate calculations for an evaluation of [`Full`].
 pub struct FullInter<const NUM_OUT: usize> {
-    weighted_sums: [Scalar; NUM_OUT],
-    outputs: [Scalar; NUM_OUT],
+    pub(crate) weighted_sums: [Scalar; NUM_OUT],
+    pub(crate) outputs: [Scalar; NUM_OUT],
 }
 
 impl<const NUM_OUT: usize> Intermediate for FullInter<NUM_OUT> {
@@ -127,3 +497,351 @@ impl<const NUM_OUT: usize> Intermediate for FullInter<NUM_OUT> {
         self.outputs
     }
 }
+
+/// A fully connected network layer whose weights are the transpose of those of a shared [`Full`]
+/// layer, with its own biases and activation function.
+///
+/// This is used to tie the weights of a decoder to those of its encoder. Training this layer also
+/// trains the weights of the tied layer.
+pub struct TiedFull<const NUM_IN: usize, const NUM_OUT: usize, A, TA> {
+    tied: Shared<Full<NUM_OUT, NUM_IN, TA>>,
+    biases: [Scalar; NUM_OUT],
+    act: A,
+    // The norm of the gradients over the parameters in the last training step.
+    grad_norm: Scalar,
+}
+
+impl<const NUM_IN: usize, const NUM_OUT: usize, A, TA> TiedFull<NUM_IN, NUM_OUT, A, TA>
+where
+    A: Deriv<In = Scalar, Out = Scalar>,
+{
+    // Creates a fully connected layer with the given activation, with the transposed weights of
+    // `tied` and with biases generated using the given generator function.
+    pub fn new<G>(
+        // The layer to share the weights with.
+        tied: Shared<Full<NUM_OUT, NUM_IN, TA>>,
+        // The activation function for this layer.
+        activation: A,
+        // Function to generate the biases for the layer.
+        bias_gen: G,
+    ) -> Self
+    where
+        G: FnMut(usize) -> Scalar,
+    {
+        Self {
+            tied,
+            act: activation,
+            biases: std::array::from_fn(bias_gen),
+            grad_norm: 0.0,
+        }
+    }
+}
+
+impl<const NUM_IN: usize, const NUM_OUT: usize, A, TA> TiedFull<NUM_IN, NUM_OUT, A, TA> {
+    fn weighted_sums(&self, input: &[Scalar; NUM_IN]) -> [Scalar; NUM_OUT] {
+        let mat = MatrixView::from_slice_generic(input, Const::<NUM_IN>, Const::<1>);
+        // Multiply with the transposed weights to find the weighted sums.
+        let mut out = self.tied.borrow().weights.tr_mul(&mat);
+        // Apply bias to the weighted sums.
+        for (sum, bias) in out.iter_mut().zip(self.biases) {
+            *sum += bias;
+        }
+        out.data.0[0]
+    }
+}
+
+impl<const NUM_IN: usize, const NUM_OUT: usize, A, TA> Network for TiedFull<NUM_IN, NUM_OUT, A, TA>
+where
+    A: Deriv<In = Scalar, Out = Scalar>,
+{
+    type In = [Scalar; NUM_IN];
+
+    type Out = [Scalar; NUM_OUT];
+
+    type Inter = FullInter<NUM_OUT>;
+
+    fn intermediate(&self, input: &Self::In) -> Self::Inter {
+        let sums = self.weighted_sums(input);
+        // Apply the activation function to the weighted sums.
+        FullInter {
+            outputs: Elementwise::new(&self.act).call(&sums),
+            weighted_sums: sums,
+        }
+    }
+
+    fn eval(&self, input: &Self::In) -> Self::Out {
+        // The weighted sums are not kept, so the activation function is applied in place.
+        let mut outputs = self.weighted_sums(input);
+        activate(&self.act, &mut outputs);
+        outputs
+    }
+
+    fn train_deriv(
+        &mut self,
+        input: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        // Calculate the gradients over the activation
+        let grad = Elementwise::new(&self.act).backprop(&intermediate.weighted_sums, gradients);
+        self.grad_norm = param_grad_norm(&grad, input);
+        // Update the biases
+        for (bias, grad) in self.biases.iter_mut().zip(grad.iter()) {
+            *bias -= grad * learning_rate;
+        }
+        let mut tied = self.tied.borrow_mut();
+        // The transposed weights have a row for each input, so every column of the tied weights
+        // belongs to one of the outputs.
+        let out: ArrayVec<Scalar, NUM_IN> = tied
+            .weights
+            .row_iter()
+            .map(|row| row.iter().zip(grad.iter()).map(|(w, g)| w * g).sum())
+            .collect();
+        // Calculate the gradients over each weight and update it correspondingly.
+        for (mut weights, grad) in tied.weights.column_iter_mut().zip(grad.iter()) {
+            for (w, input) in weights.iter_mut().zip(input.iter()) {
+                *w -= input * grad * learning_rate;
+            }
+        }
+
+        out.into_inner()
+            .expect("Capacity of ArrayVec should equal NUM_IN.")
+    }
+}
+
+impl<const NUM_IN: usize, const NUM_OUT: usize, A, TA> Inspect for TiedFull<NUM_IN, NUM_OUT, A, TA>
+where
+    A: Deriv<In = Scalar, Out = Scalar>,
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        // The tied weights are exposed by the tied layer, so only the biases are exposed here.
+        let kind = format!("TiedFull ({})", short_type_name::<A>());
+        f(&LayerView {
+            kind: &kind,
+            num_inputs: NUM_IN,
+            params: &[&self.biases],
+            activations: intermediate.output(),
+            gradient_norm: self.grad_norm,
+        });
+    }
+}
+
+// The tied weights are parameters of the tied layer, so only the biases are parameters of this
+// layer.
+impl<const NUM_IN: usize, const NUM_OUT: usize, A, TA> Parameterized
+    for TiedFull<NUM_IN, NUM_OUT, A, TA>
+{
+    fn num_params(&self) -> usize {
+        NUM_OUT
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        params.copy_from_slice(&self.biases);
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        self.biases.copy_from_slice(params);
+    }
+}
+
+/// A fully connected layer like [`Full`], whose weights and biases are borrowed instead of
+/// owned, such as from a [memory-mapped](crate::mapped) model file, for inference without copying
+/// them.
+///
+/// The weights are stored row by row, with the incoming weights of every output in a row, like
+/// [`Full::write_npz()`] writes them. As the parameters are borrowed, they are not updated in
+/// training, but the gradients over the inputs are still backpropagated, such that the layer can
+/// be a frozen part of a network that is trained. Views can be chained like other layers, to
+/// borrow a whole network, and can be constants, as [`FullView::new()`] is a `const fn`.
+///
+/// # Examples
+/// ```rust
+/// use rann_base::{activ::LeakyRelu, FullView};
+/// use rann_traits::Network;
+///
+/// let weights = [1.0, 2.0, 3.0, -1.0, 0.0, 1.0];
+/// let layer = FullView::<3, 2, _>::new(LeakyRelu(0.0), &weights, &[0.5, 0.0]);
+/// assert_eq!(layer.eval(&[1.0, 1.0, 1.0]), [6.5, 0.0]);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct FullView<'a, const NUM_IN: usize, const NUM_OUT: usize, A> {
+    weights: &'a [Scalar],
+    biases: &'a [Scalar],
+    act: A,
+}
+
+impl<'a, const NUM_IN: usize, const NUM_OUT: usize, A> FullView<'a, NUM_IN, NUM_OUT, A> {
+    /// Creates a layer with the given activation, borrowing `weights`, row by row, and `biases`,
+    /// which are either `NUM_OUT` or none for a layer without biases.
+    ///
+    /// # Panics
+    /// Panics if there are not `NUM_IN * NUM_OUT` weights, or if there are biases but not
+    /// `NUM_OUT`.
+    pub const fn new(activation: A, weights: &'a [Scalar], biases: &'a [Scalar]) -> Self {
+        assert!(
+            weights.len() == NUM_IN * NUM_OUT,
+            "There should be NUM_IN * NUM_OUT weights."
+        );
+        assert!(
+            biases.is_empty() || biases.len() == NUM_OUT,
+            "There should be NUM_OUT biases or none."
+        );
+        Self {
+            weights,
+            biases,
+            act: activation,
+        }
+    }
+
+    /// Borrows the weights, row by row.
+    pub fn weights(&self) -> &'a [Scalar] {
+        self.weights
+    }
+
+    /// Borrows the biases, which are empty for a layer without biases.
+    pub fn biases(&self) -> &'a [Scalar] {
+        self.biases
+    }
+
+    /// Borrows the weights as a matrix with a column of incoming weights for every output.
+    pub fn weight_matrix(&self) -> MatrixView<'a, Scalar, Const<NUM_IN>, Const<NUM_OUT>> {
+        MatrixView::from_slice_generic(self.weights, Const::<NUM_IN>, Const::<NUM_OUT>)
+    }
+
+    fn weighted_sums(&self, input: &[Scalar; NUM_IN]) -> [Scalar; NUM_OUT] {
+        let mat = MatrixView::from_slice_generic(input, Const::<NUM_IN>, Const::<1>);
+        // Multiply with the transposed rows of weights to find the weighted sums.
+        let mut out = self.weight_matrix().tr_mul(&mat);
+        // Apply bias to the weighted sums.
+        for (sum, bias) in out.iter_mut().zip(self.biases) {
+            *sum += bias;
+        }
+        out.data.0[0]
+    }
+}
+
+impl<const NUM_IN: usize, const NUM_OUT: usize, A> Network for FullView<'_, NUM_IN, NUM_OUT, A>
+where
+    A: Deriv<In = Scalar, Out = Scalar>,
+{
+    type In = [Scalar; NUM_IN];
+
+    type Out = [Scalar; NUM_OUT];
+
+    type Inter = FullInter<NUM_OUT>;
+
+    fn intermediate(&self, input: &Self::In) -> Self::Inter {
+        let sums = self.weighted_sums(input);
+        // Apply the activation function to the weighted sums.
+        FullInter {
+            outputs: Elementwise::new(&self.act).call(&sums),
+            weighted_sums: sums,
+        }
+    }
+
+    fn eval(&self, input: &Self::In) -> Self::Out {
+        // The weighted sums are not kept, so the activation function is applied in place.
+        let mut outputs = self.weighted_sums(input);
+        activate(&self.act, &mut outputs);
+        outputs
+    }
+
+    fn eval_many(&self, inputs: &[Self::In]) -> Vec<Self::Out> {
+        // The weights are stored transposed, see `Full::eval_many()`.
+        let mut outputs = vec![[0.0; NUM_OUT]; inputs.len()];
+        let mut sums = batch_view_mut(&mut outputs);
+        sums.gemm_tr(1.0, &self.weight_matrix(), &batch_view(inputs), 0.0);
+        finish_batch(&mut outputs, self.biases, &self.act);
+        outputs
+    }
+
+    fn train_deriv(
+        &mut self,
+        _input: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        _learning_rate: Scalar,
+    ) -> Self::In {
+        // The borrowed parameters are frozen, so only the gradients over the inputs are needed.
+        let grad = Elementwise::new(&self.act).backprop(&intermediate.weighted_sums, gradients);
+        let grad = MatrixView::from_slice_generic(&grad, Const::<NUM_OUT>, Const::<1>);
+        (self.weight_matrix() * grad).data.0[0]
+    }
+}
+
+impl<const NUM_IN: usize, const NUM_OUT: usize, A> Inspect for FullView<'_, NUM_IN, NUM_OUT, A>
+where
+    A: Deriv<In = Scalar, Out = Scalar>,
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        let kind = format!("FullView ({})", short_type_name::<A>());
+        f(&LayerView {
+            kind: &kind,
+            num_inputs: NUM_IN,
+            params: &[self.weights, self.biases],
+            activations: intermediate.output(),
+            gradient_norm: 0.0,
+        });
+    }
+}
+
+// Views a batch of samples as a matrix, with a sample in every column.
+fn batch_view<const N: usize>(batch: &[[Scalar; N]]) -> MatrixView<'_, Scalar, Const<N>, Dyn> {
+    MatrixView::from_slice_generic(batch.as_flattened(), Const::<N>, Dyn(batch.len()))
+}
+
+fn batch_view_mut<const N: usize>(
+    batch: &mut [[Scalar; N]],
+) -> MatrixViewMut<'_, Scalar, Const<N>, Dyn> {
+    let len = batch.len();
+    MatrixViewMut::from_slice_generic(batch.as_flattened_mut(), Const::<N>, Dyn(len))
+}
+
+// Adds the biases to the weighted sums of every sample of a batch, and activates them in place.
+fn finish_batch<const N: usize>(
+    batch: &mut [[Scalar; N]],
+    biases: &[Scalar],
+    act: &impl Deriv<In = Scalar, Out = Scalar>,
+) {
+    for sums in batch {
+        for (sum, bias) in sums.iter_mut().zip(biases) {
+            *sum += bias;
+        }
+        activate(act, sums);
+    }
+}
+
+// Applies `act` to every value of `values`.
+fn activate(act: &impl Deriv<In = Scalar, Out = Scalar>, values: &mut [Scalar]) {
+    for value in values {
+        *value = act.call(value);
+    }
+}
+
+pub(crate) fn squared_norm(x: &[Scalar]) -> Scalar {
+    x.iter().map(|x| x * x).sum()
+}
+
+// Zeroes all but the `k` gradients with the largest magnitude, using `order`, which is at least as
+// long as `grad`, to order their indices.
+pub(crate) fn keep_top_k(grad: &mut [Scalar], k: usize, order: &mut [usize]) {
+    if k >= grad.len() {
+        return;
+    }
+    let order = &mut order[..grad.len()];
+    for (i, index) in order.iter_mut().enumerate() {
+        *index = i;
+    }
+    order.select_nth_unstable_by(k, |&a, &b| grad[b].abs().total_cmp(&grad[a].abs()));
+    for &i in &order[k..] {
+        grad[i] = 0.0;
+    }
+}
+
+// The gradients over the weights are the outer product of the gradients over the weighted sums
+// and the inputs, and the gradients over the biases equal those over the weighted sums, so the
+// norm of all parameter gradients can be found without calculating them separately.
+pub(crate) fn param_grad_norm(grad: &[Scalar], input: &[Scalar]) -> Scalar {
+    (squared_norm(grad) * (squared_norm(input) + 1.0)).sqrt()
+}
@@ -1,12 +1,10 @@
-use rann_traits::Scalar;
+use fastrand::Rng;
+use rann_traits::{function_tuple, Scalar};
 
-#[derive(Clone, Copy, Debug)]
-pub struct Random;
-
-impl Into<(fn(usize, usize) -> Scalar, fn(usize) -> Scalar)> for Random {
-    fn into(self) -> (fn(usize, usize) -> Scalar, fn(usize) -> Scalar) {
-        (random_weights, random_biases)
-    }
+function_tuple! {
+    /// Generates weights and biases drawn uniformly from `[-2, 2)`.
+    pub struct Random: (fn(usize, usize) -> Scalar, fn(usize) -> Scalar) =
+        (random_weights, random_biases);
 }
 
 pub fn random_weights(_: usize, _: usize) -> f32 {
@@ -16,3 +14,10 @@ pub fn random_weights(_: usize, _: usize) -> f32 {
 pub fn random_biases(_: usize) -> f32 {
     fastrand::f32() * 4.0 - 2.0
 }
+
+/// Samples the standard normal distribution using the Box-Muller transform.
+pub fn gaussian(rng: &mut Rng) -> Scalar {
+    let u = 1.0 - rng.f32();
+    let v = rng.f32();
+    (-2.0 * u.ln()).sqrt() * (std::f32::consts::TAU * v).cos()
+}
@@ -0,0 +1,299 @@
+/*!
+Images with their shape in their type, as the inputs and outputs of [convolutional](crate::conv)
+layers.
+
+An [`Image<W, H, C>`] has `W` by `H` pixels with `C` channels each, such that chaining layers with
+images of different shapes is a compile time error, rather than a panic or silently wrong results.
+
+The [`Flatten`] and [`GlobalAvgPool`] layers turn images into the arrays that layers such as
+[`Full`](crate::Full) take, to classify images with a convolutional network.
+
+# Examples
+```rust
+use rann_base::image::Image;
+
+let image = Image::<3, 2>::from_fn(|x, y, _| (10 * y + x) as f32);
+assert_eq!(image.pixel(2, 1), &[12.0]);
+// The pixels are stored row by row.
+assert_eq!(image.as_slice(), [0.0, 1.0, 2.0, 10.0, 11.0, 12.0]);
+assert_eq!(Image::from([[0.0, 1.0, 2.0], [10.0, 11.0, 12.0]]), image);
+```
+
+Chaining a layer with 3x3 outputs to a layer with 4x4 inputs does not compile:
+```rust,compile_fail
+use rann_base::{activ::Logistic, conv::Convolutional, gen::Random};
+use rann_traits::Network;
+
+let first = Convolutional::<5, 5, 3, 3, _>::new(Logistic, [3, 3], Random);
+let second = Convolutional::<4, 4, 2, 2, _>::new(Logistic, [3, 3], Random);
+let net = first.chain(second);
+```
+
+A convolutional classifier of 4x4 images:
+```rust
+use rann_base::{
+    activ::Logistic,
+    conv::Convolutional,
+    error::SquareError,
+    image::{Flatten, Image},
+    testing, Full,
+};
+use rann_traits::Network;
+
+let mut net = Convolutional::<4, 4, 3, 3, _>::new(Logistic, [2, 2], testing::seeded_gen(1))
+    .chain(Flatten::<3, 3, 1, 9>::new())
+    .chain(Full::<9, 1, _>::new(Logistic, testing::seeded_gen(2)))
+    .chain(SquareError { expected: [0.0] });
+// Vertical and horizontal stripes.
+let dataset = [
+    (Image::from_fn(|x, _, _| (x % 2) as f32), [1.0]),
+    (Image::from_fn(|_, y, _| (y % 2) as f32), [0.0]),
+];
+testing::assert_network_converges(&mut net, &dataset, 500, 1.0, 0.01);
+```
+*/
+
+use rann_traits::{
+    deriv::Elements,
+    inspect::{Inspect, LayerView},
+    params::Parameterized,
+    Gradient, Network, Scalar,
+};
+
+/// An image of `W` by `H` pixels with `C` channels each. See [module level documentation](self)
+/// for more info.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Image<const W: usize, const H: usize, const C: usize = 1> {
+    /// The rows of pixels, from top to bottom, each with the channels of its pixels from left to
+    /// right.
+    pub pixels: [[[Scalar; C]; W]; H],
+}
+
+impl<const W: usize, const H: usize, const C: usize> Image<W, H, C> {
+    /// The number of values in the image.
+    pub const LEN: usize = W * H * C;
+
+    /// Creates an image with every channel of every pixel equal to `value`.
+    pub fn filled(value: Scalar) -> Self {
+        Self {
+            pixels: [[[value; C]; W]; H],
+        }
+    }
+
+    /// Creates an image with channel `c` of the pixel at `x`, `y` equal to `f(x, y, c)`.
+    pub fn from_fn(mut f: impl FnMut(usize, usize, usize) -> Scalar) -> Self {
+        Self {
+            pixels: std::array::from_fn(|y| {
+                std::array::from_fn(|x| std::array::from_fn(|c| f(x, y, c)))
+            }),
+        }
+    }
+
+    /// Borrows the channels of the pixel at `x`, `y`.
+    pub fn pixel(&self, x: usize, y: usize) -> &[Scalar; C] {
+        &self.pixels[y][x]
+    }
+
+    /// Borrows the values of the image, row by row and pixel by pixel.
+    pub fn as_slice(&self) -> &[Scalar] {
+        self.pixels.as_flattened().as_flattened()
+    }
+
+    /// Mutably borrows the values of the image, row by row and pixel by pixel.
+    pub fn as_mut_slice(&mut self) -> &mut [Scalar] {
+        self.pixels.as_flattened_mut().as_flattened_mut()
+    }
+}
+
+impl<const W: usize, const H: usize, const C: usize> Default for Image<W, H, C> {
+    fn default() -> Self {
+        Self::filled(0.0)
+    }
+}
+
+impl<const W: usize, const H: usize, const C: usize> AsRef<[Scalar]> for Image<W, H, C> {
+    fn as_ref(&self) -> &[Scalar] {
+        self.as_slice()
+    }
+}
+
+impl<const W: usize, const H: usize> From<[[Scalar; W]; H]> for Image<W, H> {
+    fn from(rows: [[Scalar; W]; H]) -> Self {
+        Self {
+            pixels: rows.map(|row| row.map(|value| [value])),
+        }
+    }
+}
+
+impl<const W: usize, const H: usize, const C: usize> Elements for Image<W, H, C> {
+    fn map_elements(&self, mut f: impl FnMut(Scalar) -> Scalar) -> Self {
+        Self {
+            pixels: self.pixels.map(|row| row.map(|pixel| pixel.map(&mut f))),
+        }
+    }
+
+    fn zip_elements(&self, other: &Self, mut f: impl FnMut(Scalar, Scalar) -> Scalar) -> Self {
+        let mut out = *self;
+        for (a, b) in out.as_mut_slice().iter_mut().zip(other.as_slice()) {
+            *a = f(*a, *b);
+        }
+        out
+    }
+}
+
+impl<const W: usize, const H: usize, const C: usize> Gradient for Image<W, H, C> {
+    fn ones_like(&self) -> Self {
+        Self::filled(1.0)
+    }
+}
+
+/// A layer that flattens `W` by `H` images with `C` channels into arrays of all `N` values, row by
+/// row and pixel by pixel. See [module level documentation](self) for more info.
+///
+/// `N` should be `W * H * C`, which is checked at compile time:
+/// ```rust,compile_fail
+/// use rann_base::image::Flatten;
+///
+/// let flatten = Flatten::<3, 3, 1, 8>::new();
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Flatten<const W: usize, const H: usize, const C: usize, const N: usize>;
+
+impl<const W: usize, const H: usize, const C: usize, const N: usize> Flatten<W, H, C, N> {
+    const CHECK_LEN: () = assert!(N == W * H * C, "N should be W * H * C.");
+
+    /// Creates a flattening layer.
+    pub fn new() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::CHECK_LEN;
+        Self
+    }
+}
+
+impl<const W: usize, const H: usize, const C: usize, const N: usize> Network
+    for Flatten<W, H, C, N>
+{
+    type In = Image<W, H, C>;
+    type Out = [Scalar; N];
+    type Inter = [Scalar; N];
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        inputs
+            .as_slice()
+            .try_into()
+            .expect("N should be W * H * C.")
+    }
+
+    fn train_deriv(
+        &mut self,
+        _inputs: &Self::In,
+        _intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        _learning_rate: Scalar,
+    ) -> Self::In {
+        let mut out = Image::default();
+        out.as_mut_slice().copy_from_slice(gradients);
+        out
+    }
+}
+
+impl<const W: usize, const H: usize, const C: usize, const N: usize> Inspect
+    for Flatten<W, H, C, N>
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        f(&LayerView {
+            kind: "Flatten",
+            num_inputs: N,
+            params: &[],
+            activations: intermediate,
+            gradient_norm: 0.0,
+        });
+    }
+}
+
+impl<const W: usize, const H: usize, const C: usize, const N: usize> Parameterized
+    for Flatten<W, H, C, N>
+{
+    fn num_params(&self) -> usize {
+        0
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        assert!(params.is_empty(), "Flatten has no parameters.");
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        assert!(params.is_empty(), "Flatten has no parameters.");
+    }
+}
+
+/// A layer that averages every channel of `W` by `H` images with `C` channels over all pixels.
+/// See [module level documentation](self) for more info.
+///
+/// Unlike [`Flatten`], the number of outputs does not depend on the size of the images, so the
+/// layers after it do not either.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GlobalAvgPool<const W: usize, const H: usize, const C: usize = 1>;
+
+impl<const W: usize, const H: usize, const C: usize> GlobalAvgPool<W, H, C> {
+    /// Creates a global average pooling layer.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<const W: usize, const H: usize, const C: usize> Network for GlobalAvgPool<W, H, C> {
+    type In = Image<W, H, C>;
+    type Out = [Scalar; C];
+    type Inter = [Scalar; C];
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        let mut sums = [0.0; C];
+        for pixel in inputs.pixels.iter().flatten() {
+            for (sum, value) in sums.iter_mut().zip(pixel) {
+                *sum += value;
+            }
+        }
+        sums.map(|sum| sum / (W * H) as Scalar)
+    }
+
+    fn train_deriv(
+        &mut self,
+        _inputs: &Self::In,
+        _intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        _learning_rate: Scalar,
+    ) -> Self::In {
+        // Every pixel contributes equally to the average.
+        let pixel = gradients.map(|g| g / (W * H) as Scalar);
+        Image {
+            pixels: [[pixel; W]; H],
+        }
+    }
+}
+
+impl<const W: usize, const H: usize, const C: usize> Inspect for GlobalAvgPool<W, H, C> {
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        f(&LayerView {
+            kind: "GlobalAvgPool",
+            num_inputs: W * H * C,
+            params: &[],
+            activations: intermediate,
+            gradient_norm: 0.0,
+        });
+    }
+}
+
+impl<const W: usize, const H: usize, const C: usize> Parameterized for GlobalAvgPool<W, H, C> {
+    fn num_params(&self) -> usize {
+        0
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        assert!(params.is_empty(), "GlobalAvgPool has no parameters.");
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        assert!(params.is_empty(), "GlobalAvgPool has no parameters.");
+    }
+}
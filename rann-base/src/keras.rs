@@ -0,0 +1,1105 @@
+/*!
+Importing the weights of multilayer perceptrons trained in Keras, from HDF5 files.
+
+A [`KerasModel`] reads the [`Dense`] layers of a model saved by Keras in the HDF5 format, by
+`model.save("model.h5")` or by `model.save_weights("model.weights.h5")`.
+[`KerasModel::import()`] copies them into a chain of [`Full`] layers, checking that the shapes
+of the layers match, and that their activation functions do if the file holds the configuration
+of the model. Layers without weights that do not change inference, such as `Dropout`, are
+skipped, while any other layer is rejected.
+
+The kernel of a Dense layer has the shape `[inputs, outputs]`, which is the transpose of the
+weights of a [`Full`] layer, see [`crate::npy`].
+
+The files are read without depending on the HDF5 library, which limits them to the subset of the
+format that Keras writes through h5py: datasets must not be chunked or compressed.
+
+This module is only available with the `keras` feature.
+
+# Examples
+```rust,no_run
+use rann_base::{
+    activ::{LeakyRelu, Logistic},
+    error::SquareError,
+    gen::Random,
+    keras::KerasModel,
+    Full,
+};
+use rann_traits::Network;
+
+// A model of `Dense(16, activation="relu")` and `Dense(1, activation="sigmoid")`.
+let mut net = Full::<4, 16, _>::new(LeakyRelu(0.0), Random)
+    .chain(Full::<16, 1, _>::new(Logistic, Random))
+    .chain(SquareError { expected: [0.0] });
+KerasModel::load("model.h5")?.import(&mut net.first)?;
+# Ok::<(), std::io::Error>(())
+```
+*/
+
+use std::{
+    fs,
+    io::{self, Read},
+    path::Path,
+    slice,
+};
+
+use rann_traits::{
+    compose::Chain, deriv::Deriv, inspect::short_type_name, params::Parameterized, Scalar,
+};
+
+use crate::{
+    activ::{LeakyRelu, Logistic, Selu, Softplus, Tanh},
+    checkpoint::invalid,
+    full::Biases,
+    npy::{Array, Npz},
+    Full,
+};
+
+// Layers without weights that are the identity during inference.
+const SKIPPED: &[&str] = &["InputLayer", "Dropout", "GaussianNoise", "GaussianDropout"];
+
+/// A Dense layer of a Keras model.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dense {
+    /// The name of the layer, such as `dense_1`.
+    pub name: String,
+    /// The name of the activation function, such as `relu`, if the file holds the configuration
+    /// of the model.
+    pub activation: Option<String>,
+    /// The weights, with shape `[inputs, outputs]`.
+    pub kernel: Array,
+    /// The biases, with shape `[outputs]`, if the layer has any.
+    pub bias: Option<Array>,
+}
+
+/// The Dense layers of a Keras model, in order. See [module level documentation](self) for more
+/// info.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KerasModel {
+    /// The layers.
+    pub layers: Vec<Dense>,
+}
+
+impl KerasModel {
+    /// Reads a model from the bytes of an HDF5 file.
+    ///
+    /// # Errors
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if the bytes are not an HDF5 file
+    /// written by Keras, if it uses parts of the format that are not supported, or if the model
+    /// has layers other than Dense layers and layers that are skipped.
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let file = hdf5::File::new(bytes)?;
+        let root = file.root();
+        // Saved models hold their weights in a group, while saved weights are at the root.
+        let weights = match file.child(root, "model_weights")? {
+            Some(group) => group,
+            None => root,
+        };
+        let config = match file.strings(root, "model_config")? {
+            Some(config) => {
+                let config = config.concat();
+                json::parse(&config)?
+            }
+            None => json::Value::Null,
+        };
+        let layer_names = file
+            .strings(weights, "layer_names")?
+            .ok_or_else(|| invalid("missing layer_names, not a Keras file"))?;
+
+        let mut layers = Vec::new();
+        for name in layer_names {
+            let config = layer_config(&config, &name);
+            let class = config.and_then(|c| c.get("class_name")?.as_str());
+            let group = file
+                .child(weights, &name)?
+                .ok_or_else(|| invalid(format!("missing weights of layer {name}")))?;
+            let weight_names = file.strings(group, "weight_names")?.unwrap_or_default();
+            if weight_names.is_empty() && class.is_none_or(|class| SKIPPED.contains(&class)) {
+                continue;
+            }
+            let is_dense = class.map_or_else(
+                || is_weight(&weight_names[0], "kernel") && weight_names.len() <= 2,
+                |class| class == "Dense",
+            );
+            if !is_dense || weight_names.is_empty() {
+                let class = class.unwrap_or("unknown");
+                return Err(invalid(format!(
+                    "layer {name} is a {class} layer, which is not supported"
+                )));
+            }
+
+            let activation = match config.and_then(|c| c.get("config")?.get("activation")) {
+                Some(json::Value::String(activation)) => Some(activation.clone()),
+                Some(_) => {
+                    return Err(invalid(format!(
+                        "layer {name} has a custom activation, which is not supported"
+                    )));
+                }
+                None => None,
+            };
+            let kernel = file.dataset(file.path(group, &weight_names[0])?)?;
+            let bias = weight_names
+                .get(1)
+                .map(|bias| file.dataset(file.path(group, bias)?))
+                .transpose()?;
+            let [_, outputs] = kernel.shape[..] else {
+                return Err(invalid(format!("kernel of layer {name} is not a matrix")));
+            };
+            if bias.as_ref().is_some_and(|bias| bias.shape != [outputs]) {
+                return Err(invalid(format!(
+                    "bias of layer {name} does not match its kernel"
+                )));
+            }
+            layers.push(Dense {
+                name,
+                activation,
+                kernel,
+                bias,
+            });
+        }
+        Ok(Self { layers })
+    }
+
+    /// Reads a model from an HDF5 file in `reader`.
+    pub fn read_from(mut reader: impl Read) -> io::Result<Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Reads a model from the HDF5 file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    /// Copies the weights of the layers into `net`, such as a chain of [`Full`] layers.
+    ///
+    /// # Errors
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if the number of layers, their
+    /// shapes or their activation functions do not match. The layers before the mismatch are
+    /// imported.
+    pub fn import<N: ImportDense>(&self, net: &mut N) -> io::Result<()> {
+        let mut layers = self.layers.iter();
+        net.import_dense(&mut layers)?;
+        match layers.next() {
+            Some(layer) => Err(invalid(format!(
+                "the network has no layer for layer {}",
+                layer.name
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+// Returns the configuration of the layer named `name` in the configuration of a model.
+fn layer_config<'a>(config: &'a json::Value, name: &str) -> Option<&'a json::Value> {
+    let config = config.get("config")?;
+    // Older versions of Keras store the layers of sequential models directly.
+    let layers = match config {
+        json::Value::Array(layers) => layers,
+        config => config.get("layers")?.as_array()?,
+    };
+    layers.iter().find(|layer| {
+        layer
+            .get("config")
+            .and_then(|c| c.get("name"))
+            .or_else(|| layer.get("name"))
+            .and_then(json::Value::as_str)
+            == Some(name)
+    })
+}
+
+// Returns whether the weight at `path` is named `name`, such as `dense/kernel:0`.
+fn is_weight(path: &str, name: &str) -> bool {
+    let last = path.rsplit('/').next().unwrap_or(path);
+    last.split(':').next() == Some(name)
+}
+
+/// Networks that the Dense layers of a Keras model can be imported into, see
+/// [`KerasModel::import()`].
+pub trait ImportDense {
+    /// Copies the weights of the next layers of `layers` into this network, one for every
+    /// [`Full`] layer.
+    fn import_dense(&mut self, layers: &mut slice::Iter<'_, Dense>) -> io::Result<()>;
+}
+
+impl<const NUM_IN: usize, const NUM_OUT: usize, A, B> ImportDense for Full<NUM_IN, NUM_OUT, A, B>
+where
+    A: KerasActivation,
+    B: Biases<NUM_OUT>,
+{
+    fn import_dense(&mut self, layers: &mut slice::Iter<'_, Dense>) -> io::Result<()> {
+        let layer = layers
+            .next()
+            .ok_or_else(|| invalid("the model has fewer layers than the network"))?;
+        let name = &layer.name;
+        if let Some(activation) = &layer.activation {
+            if self.activation().keras_name() != Some(activation) {
+                return Err(invalid(format!(
+                    "layer {name} has activation {activation}, which does not match {}",
+                    short_type_name::<A>()
+                )));
+            }
+        }
+        if layer.kernel.shape != [NUM_IN, NUM_OUT] {
+            return Err(invalid(format!(
+                "layer {name} has a kernel of shape {:?}, but should have shape {:?}",
+                layer.kernel.shape,
+                [NUM_IN, NUM_OUT]
+            )));
+        }
+        let has_biases = self.num_params() > NUM_IN * NUM_OUT;
+        if layer.bias.is_some() != has_biases {
+            let which = if has_biases { "no biases" } else { "biases" };
+            return Err(invalid(format!(
+                "layer {name} has {which}, unlike the network"
+            )));
+        }
+
+        let mut weights = vec![0.0; NUM_IN * NUM_OUT];
+        for (i, row) in layer.kernel.data.chunks_exact(NUM_OUT).enumerate() {
+            for (o, &weight) in row.iter().enumerate() {
+                weights[o * NUM_IN + i] = weight;
+            }
+        }
+        let mut npz = Npz::new();
+        npz.insert("weight", Array::new(vec![NUM_OUT, NUM_IN], weights));
+        if let Some(bias) = &layer.bias {
+            npz.insert("bias", bias.clone());
+        }
+        self.read_npz(&npz, "")
+    }
+}
+
+impl<T: ImportDense, U: ImportDense> ImportDense for Chain<T, U> {
+    fn import_dense(&mut self, layers: &mut slice::Iter<'_, Dense>) -> io::Result<()> {
+        self.first.import_dense(layers)?;
+        self.second.import_dense(layers)
+    }
+}
+
+/// Activation functions with an equivalent in Keras.
+pub trait KerasActivation: Deriv<In = Scalar, Out = Scalar> {
+    /// Returns the name of the equivalent activation function in Keras, if there is one.
+    fn keras_name(&self) -> Option<&'static str>;
+}
+
+impl KerasActivation for Logistic {
+    fn keras_name(&self) -> Option<&'static str> {
+        Some("sigmoid")
+    }
+}
+
+impl KerasActivation for Tanh {
+    fn keras_name(&self) -> Option<&'static str> {
+        Some("tanh")
+    }
+}
+
+impl KerasActivation for Softplus {
+    fn keras_name(&self) -> Option<&'static str> {
+        Some("softplus")
+    }
+}
+
+impl KerasActivation for Selu {
+    fn keras_name(&self) -> Option<&'static str> {
+        Some("selu")
+    }
+}
+
+impl KerasActivation for LeakyRelu {
+    fn keras_name(&self) -> Option<&'static str> {
+        (self.0 == 0.0).then_some("relu")
+    }
+}
+
+// A reader of the subset of HDF5 that h5py writes: groups stored in symbol tables or as links in
+// their object headers, contiguous and compact datasets of floats, and attributes of strings.
+mod hdf5 {
+    use std::io;
+
+    use crate::{checkpoint::invalid, npy::Array};
+
+    const SIGNATURE: &[u8; 8] = b"\x89HDF\r\n\x1a\n";
+    // The most object header blocks and levels of B-trees to follow, against cycles.
+    const MAX_BLOCKS: usize = 1 << 16;
+    const MAX_DEPTH: usize = 64;
+
+    const DATASPACE: u16 = 0x01;
+    const LINK_INFO: u16 = 0x02;
+    const DATATYPE: u16 = 0x03;
+    const LINK: u16 = 0x06;
+    const LAYOUT: u16 = 0x08;
+    const ATTRIBUTE: u16 = 0x0C;
+    const CONTINUATION: u16 = 0x10;
+    const SYMBOL_TABLE: u16 = 0x11;
+    // Message flag of messages stored elsewhere in the file.
+    const SHARED: u8 = 0x02;
+
+    #[derive(Clone, Copy)]
+    struct Sizes {
+        offset: usize,
+        length: usize,
+    }
+
+    #[derive(Clone, Copy)]
+    struct Cursor<'a> {
+        bytes: &'a [u8],
+        pos: usize,
+        sizes: Sizes,
+    }
+
+    impl<'a> Cursor<'a> {
+        fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+            let end = self
+                .pos
+                .checked_add(len)
+                .filter(|&end| end <= self.bytes.len())
+                .ok_or_else(truncated)?;
+            let taken = &self.bytes[self.pos..end];
+            self.pos = end;
+            Ok(taken)
+        }
+
+        fn skip(&mut self, len: usize) -> io::Result<()> {
+            self.take(len).map(|_| ())
+        }
+
+        fn uint(&mut self, len: usize) -> io::Result<u64> {
+            let bytes = self.take(len)?;
+            Ok(bytes.iter().rev().fold(0, |n, &b| (n << 8) | b as u64))
+        }
+
+        fn u8(&mut self) -> io::Result<u8> {
+            Ok(self.take(1)?[0])
+        }
+
+        fn u16(&mut self) -> io::Result<u16> {
+            Ok(self.uint(2)? as u16)
+        }
+
+        fn u32(&mut self) -> io::Result<u32> {
+            Ok(self.uint(4)? as u32)
+        }
+
+        fn offset(&mut self) -> io::Result<u64> {
+            self.uint(self.sizes.offset)
+        }
+
+        fn length(&mut self) -> io::Result<usize> {
+            Ok(self.uint(self.sizes.length)? as usize)
+        }
+
+        fn signature(&mut self, signature: &[u8]) -> io::Result<()> {
+            if self.take(signature.len())? != signature {
+                return Err(invalid("invalid signature in HDF5 file"));
+            }
+            Ok(())
+        }
+
+        // Returns a cursor over `data`, with the sizes of this file.
+        fn over<'b>(&self, data: &'b [u8]) -> Cursor<'b> {
+            Cursor {
+                bytes: data,
+                pos: 0,
+                sizes: self.sizes,
+            }
+        }
+    }
+
+    fn truncated() -> io::Error {
+        invalid("truncated HDF5 file")
+    }
+
+    fn unsupported(what: &str) -> io::Error {
+        invalid(format!("{what} are not supported"))
+    }
+
+    // A message in an object header: its type, its flags and its data.
+    type Message<'a> = (u16, u8, &'a [u8]);
+
+    #[derive(Clone, Copy)]
+    enum Datatype {
+        Float { size: usize, big_endian: bool },
+        String { size: usize, space_padded: bool },
+        VarString { size: usize },
+        Other,
+    }
+
+    pub struct File<'a> {
+        cursor: Cursor<'a>,
+        base: u64,
+        root: u64,
+    }
+
+    impl<'a> File<'a> {
+        pub fn new(bytes: &'a [u8]) -> io::Result<Self> {
+            // The superblock may follow a user block of a power of two of at least 512 bytes.
+            let mut start = 0;
+            while !bytes[start.min(bytes.len())..].starts_with(SIGNATURE) {
+                start = (start * 2).max(512);
+                if start >= bytes.len() {
+                    return Err(invalid("not an HDF5 file"));
+                }
+            }
+            let mut c = Cursor {
+                bytes,
+                pos: start + SIGNATURE.len(),
+                sizes: Sizes {
+                    offset: 8,
+                    length: 8,
+                },
+            };
+            let version = c.u8()?;
+            if version <= 1 {
+                // The versions of the free space storage, the root group and the shared header
+                // message formats, and a reserved byte.
+                c.skip(4)?;
+            }
+            c.sizes = Sizes {
+                offset: c.u8()? as usize,
+                length: c.u8()? as usize,
+            };
+            if !(1..=8).contains(&c.sizes.offset) || !(1..=8).contains(&c.sizes.length) {
+                return Err(invalid("invalid sizes in HDF5 superblock"));
+            }
+            let (base, root) = match version {
+                0 | 1 => {
+                    // A reserved byte, the B-tree parameters and the consistency flags.
+                    c.skip(1 + 2 + 2 + 4 + if version == 1 { 4 } else { 0 })?;
+                    let base = c.offset()?;
+                    // The free space, end of file and driver information addresses, and the
+                    // link name offset of the root group.
+                    c.skip(4 * c.sizes.offset)?;
+                    (base, c.offset()?)
+                }
+                2 | 3 => {
+                    c.skip(1)?;
+                    let base = c.offset()?;
+                    // The superblock extension and end of file addresses.
+                    c.skip(2 * c.sizes.offset)?;
+                    (base, c.offset()?)
+                }
+                _ => return Err(invalid(format!("unsupported HDF5 version {version}"))),
+            };
+            Ok(Self {
+                cursor: c,
+                base,
+                root,
+            })
+        }
+
+        pub fn root(&self) -> u64 {
+            self.root
+        }
+
+        fn at(&self, address: u64) -> io::Result<Cursor<'a>> {
+            let pos = self
+                .base
+                .checked_add(address)
+                .filter(|&pos| pos <= self.cursor.bytes.len() as u64)
+                .ok_or_else(truncated)?;
+            Ok(Cursor {
+                pos: pos as usize,
+                ..self.cursor
+            })
+        }
+
+        fn is_undefined(&self, address: u64) -> bool {
+            address == u64::MAX >> (64 - 8 * self.cursor.sizes.offset)
+        }
+
+        // Returns the messages in the object header at `address`.
+        fn messages(&self, address: u64) -> io::Result<Vec<Message<'a>>> {
+            let mut c = self.at(address)?;
+            let mut blocks = Vec::new();
+            // Version 2 headers have a signature, shorter message headers, and optionally the
+            // creation order of every message.
+            let (v1, creation_order) = if c.bytes[c.pos..].starts_with(b"OHDR") {
+                c.skip(4)?;
+                if c.u8()? != 2 {
+                    return Err(unsupported("object header versions other than 1 and 2"));
+                }
+                let flags = c.u8()?;
+                // The times, and the limits of compact attributes.
+                c.skip(if flags & 0x20 != 0 { 16 } else { 0 })?;
+                c.skip(if flags & 0x10 != 0 { 4 } else { 0 })?;
+                let len = c.uint(1 << (flags & 0x03))? as usize;
+                blocks.push((c.pos, len));
+                (false, flags & 0x04 != 0)
+            } else {
+                if c.u8()? != 1 {
+                    return Err(unsupported("object header versions other than 1 and 2"));
+                }
+                // A reserved byte, the number of messages and the reference count.
+                c.skip(1 + 2 + 4)?;
+                let len = c.u32()? as usize;
+                // Messages are aligned to 8 bytes.
+                c.skip(4)?;
+                blocks.push((c.pos, len));
+                (true, false)
+            };
+            let header_len = match (v1, creation_order) {
+                (true, _) => 8,
+                (false, true) => 6,
+                (false, false) => 4,
+            };
+
+            let mut messages = Vec::new();
+            let mut block = 0;
+            while let Some(&(pos, len)) = blocks.get(block) {
+                block += 1;
+                if block > MAX_BLOCKS {
+                    return Err(invalid("too many object header blocks"));
+                }
+                let mut c = Cursor { pos, ..c };
+                let end = pos.checked_add(len).ok_or_else(truncated)?;
+                while c.pos + header_len <= end {
+                    let (kind, len, flags) = if v1 {
+                        let (kind, len, flags) = (c.u16()?, c.u16()?, c.u8()?);
+                        c.skip(3)?;
+                        (kind, len, flags)
+                    } else {
+                        let (kind, len, flags) = (c.u8()? as u16, c.u16()?, c.u8()?);
+                        c.skip(if creation_order { 2 } else { 0 })?;
+                        (kind, len, flags)
+                    };
+                    let data = c.take(len as usize)?;
+                    if kind == CONTINUATION {
+                        let mut data = c.over(data);
+                        let mut continued = self.at(data.offset()?)?;
+                        let len = data.length()?;
+                        if v1 {
+                            blocks.push((continued.pos, len));
+                        } else {
+                            // The signature and the checksum of the block.
+                            continued.signature(b"OCHK")?;
+                            blocks.push((continued.pos, len.saturating_sub(8)));
+                        }
+                    } else {
+                        messages.push((kind, flags, data));
+                    }
+                }
+            }
+            Ok(messages)
+        }
+
+        // Returns the objects in the group at `address`, with their names.
+        fn children(&self, address: u64) -> io::Result<Vec<(String, u64)>> {
+            let mut children = Vec::new();
+            for (kind, _, data) in self.messages(address)? {
+                let mut c = self.cursor.over(data);
+                match kind {
+                    SYMBOL_TABLE => {
+                        let (tree, heap) = (c.offset()?, c.offset()?);
+                        let mut heap = self.at(heap)?;
+                        heap.signature(b"HEAP")?;
+                        // The version, reserved bytes, the size of the data segment and the
+                        // offset of the free list.
+                        heap.skip(4 + 2 * c.sizes.length)?;
+                        let names = self.at(heap.offset()?)?;
+                        self.symbols(tree, names, 0, &mut children)?;
+                    }
+                    LINK_INFO => {
+                        c.skip(1)?;
+                        let flags = c.u8()?;
+                        // The maximum creation index.
+                        c.skip(if flags & 0x01 != 0 { 8 } else { 0 })?;
+                        if !self.is_undefined(c.offset()?) {
+                            return Err(unsupported("groups with many links"));
+                        }
+                    }
+                    LINK => {
+                        c.skip(1)?;
+                        let flags = c.u8()?;
+                        let link_type = if flags & 0x08 != 0 { c.u8()? } else { 0 };
+                        c.skip(if flags & 0x04 != 0 { 8 } else { 0 })?;
+                        c.skip(if flags & 0x10 != 0 { 1 } else { 0 })?;
+                        let len = c.uint(1 << (flags & 0x03))? as usize;
+                        let name = String::from_utf8_lossy(c.take(len)?).into_owned();
+                        // Soft and external links are skipped.
+                        if link_type == 0 {
+                            children.push((name, c.offset()?));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Ok(children)
+        }
+
+        // Adds the symbols in the B-tree of a group at `address` to `children`, with their names
+        // in the local heap data at `names`.
+        fn symbols(
+            &self,
+            address: u64,
+            names: Cursor<'a>,
+            depth: usize,
+            children: &mut Vec<(String, u64)>,
+        ) -> io::Result<()> {
+            if depth > MAX_DEPTH {
+                return Err(invalid("too deep B-tree in HDF5 file"));
+            }
+            let mut c = self.at(address)?;
+            c.signature(b"TREE")?;
+            let (kind, level, entries) = (c.u8()?, c.u8()?, c.u16()?);
+            if kind != 0 {
+                return Err(invalid("invalid group B-tree in HDF5 file"));
+            }
+            // The siblings.
+            c.skip(2 * c.sizes.offset)?;
+            for _ in 0..entries {
+                // The key before every child.
+                c.skip(c.sizes.length)?;
+                let child = c.offset()?;
+                if level > 0 {
+                    self.symbols(child, names, depth + 1, children)?;
+                    continue;
+                }
+                let mut node = self.at(child)?;
+                node.signature(b"SNOD")?;
+                node.skip(2)?;
+                for _ in 0..node.u16()? {
+                    let mut name = names;
+                    name.skip(node.offset()? as usize)?;
+                    let name = &name.bytes[name.pos..];
+                    let name = &name[..name.iter().position(|&b| b == 0).ok_or_else(truncated)?];
+                    children.push((String::from_utf8_lossy(name).into_owned(), node.offset()?));
+                    // The cache type, a reserved field and the scratch pad.
+                    node.skip(4 + 4 + 16)?;
+                }
+            }
+            Ok(())
+        }
+
+        // Returns the object named `name` in the group at `address`, if any.
+        pub fn child(&self, address: u64, name: &str) -> io::Result<Option<u64>> {
+            let children = self.children(address)?;
+            Ok(children
+                .into_iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, a)| a))
+        }
+
+        // Returns the object at `path` relative to the group at `address`.
+        pub fn path(&self, address: u64, path: &str) -> io::Result<u64> {
+            path.split('/')
+                .filter(|part| !part.is_empty())
+                .try_fold(address, |address, part| {
+                    self.child(address, part)?
+                        .ok_or_else(|| invalid(format!("missing object {path}")))
+                })
+        }
+
+        // Returns the strings of the attribute `name` of the object at `address`, if it has
+        // one.
+        pub fn strings(&self, address: u64, name: &str) -> io::Result<Option<Vec<String>>> {
+            for (kind, flags, data) in self.messages(address)? {
+                if kind != ATTRIBUTE {
+                    continue;
+                }
+                let mut c = self.cursor.over(data);
+                let version = c.u8()?;
+                let shared = c.u8()?;
+                let sizes = [c.u16()?, c.u16()?, c.u16()?].map(usize::from);
+                // The name, datatype and dataspace are aligned to 8 bytes in version 1.
+                let align = |len: usize| match version {
+                    1 => len.next_multiple_of(8),
+                    _ => len,
+                };
+                match version {
+                    1 | 2 => {}
+                    3 => c.skip(1)?,
+                    _ => return Err(unsupported("attribute versions other than 1 to 3")),
+                }
+                let attribute = c.take(align(sizes[0]))?;
+                let attribute = &attribute[..sizes[0].min(attribute.len())];
+                if attribute.split(|&b| b == 0).next() != Some(name.as_bytes()) {
+                    continue;
+                }
+                if flags & SHARED != 0 || (version > 1 && shared & 0x03 != 0) {
+                    return Err(unsupported("shared attribute types"));
+                }
+                let datatype = datatype(c.take(align(sizes[1]))?)?;
+                let count = dataspace(self.cursor.over(c.take(align(sizes[2]))?))?;
+                return self.read_strings(datatype, count, c).map(Some);
+            }
+            Ok(None)
+        }
+
+        fn read_strings(
+            &self,
+            datatype: Datatype,
+            count: usize,
+            mut c: Cursor<'a>,
+        ) -> io::Result<Vec<String>> {
+            let mut strings = Vec::with_capacity(count.min(c.bytes.len()));
+            for _ in 0..count {
+                let bytes = match datatype {
+                    Datatype::String { size, space_padded } => {
+                        let bytes = c.take(size)?;
+                        let bytes = bytes.split(|&b| b == 0).next().unwrap_or(bytes);
+                        match space_padded {
+                            true => bytes.trim_ascii_end(),
+                            false => bytes,
+                        }
+                    }
+                    Datatype::VarString { size } => {
+                        let element = c.take(size)?;
+                        let mut element = c.over(element);
+                        let len = element.u32()? as usize;
+                        let (heap, index) = (element.offset()?, element.u32()?);
+                        self.global(heap, index)?.get(..len).ok_or_else(truncated)?
+                    }
+                    _ => return Err(invalid("attribute is not a string")),
+                };
+                strings.push(String::from_utf8_lossy(bytes).into_owned());
+            }
+            Ok(strings)
+        }
+
+        // Returns the object `index` in the global heap collection at `address`.
+        fn global(&self, address: u64, index: u32) -> io::Result<&'a [u8]> {
+            let mut c = self.at(address)?;
+            let start = c.pos;
+            c.signature(b"GCOL")?;
+            c.skip(4)?;
+            // The size of the collection includes its header.
+            let end = start.checked_add(c.length()?).ok_or_else(truncated)?;
+            while c.pos < end {
+                let object = c.u16()?;
+                // The reference count and a reserved field.
+                c.skip(2 + 4)?;
+                let len = c.length()?;
+                if object as u32 == index {
+                    return c.take(len);
+                }
+                if object == 0 {
+                    break;
+                }
+                c.skip(len.next_multiple_of(8))?;
+            }
+            Err(invalid("missing global heap object in HDF5 file"))
+        }
+
+        // Reads the dataset at `address` as an array.
+        pub fn dataset(&self, address: u64) -> io::Result<Array> {
+            let (mut shape, mut datatype, mut data) = (None, None, None);
+            for (kind, flags, message) in self.messages(address)? {
+                if flags & SHARED != 0 && matches!(kind, DATASPACE | DATATYPE | LAYOUT) {
+                    return Err(unsupported("shared dataset types"));
+                }
+                let mut c = self.cursor.over(message);
+                match kind {
+                    DATASPACE => shape = Some(dims(&mut c)?.unwrap_or_default()),
+                    DATATYPE => datatype = Some(self::datatype(message)?),
+                    LAYOUT => data = Some(self.layout(c)?),
+                    _ => {}
+                }
+            }
+            let (Some(shape), Some(datatype), Some(data)) = (shape, datatype, data) else {
+                return Err(invalid("object is not a dataset"));
+            };
+            let Datatype::Float { size, big_endian } = datatype else {
+                return Err(invalid("dataset does not hold floats"));
+            };
+            let len = shape.iter().product::<usize>();
+            let data = data
+                .get(..len.checked_mul(size).ok_or_else(truncated)?)
+                .ok_or_else(truncated)?;
+            let data = data
+                .chunks_exact(size)
+                .map(|b| {
+                    let mut bytes = [0; 8];
+                    bytes[..size].copy_from_slice(b);
+                    if big_endian {
+                        bytes[..size].reverse();
+                    }
+                    match size {
+                        4 => f32::from_le_bytes(bytes[..4].try_into().expect("Sizes should fit.")),
+                        _ => f64::from_le_bytes(bytes) as f32,
+                    }
+                })
+                .collect();
+            Ok(Array::new(shape, data))
+        }
+
+        // Returns the raw data of a dataset with the layout `c`.
+        fn layout(&self, mut c: Cursor<'a>) -> io::Result<&'a [u8]> {
+            let version = c.u8()?;
+            let (class, address) = match version {
+                1 | 2 => {
+                    let dimensions = c.u8()? as usize;
+                    let class = c.u8()?;
+                    c.skip(5)?;
+                    let address = if class == 0 { 0 } else { c.offset()? };
+                    c.skip(4 * dimensions)?;
+                    if class == 0 {
+                        let len = c.u32()? as usize;
+                        return c.take(len);
+                    }
+                    (class, address)
+                }
+                3 | 4 => match c.u8()? {
+                    0 => {
+                        let len = c.u16()? as usize;
+                        return c.take(len);
+                    }
+                    class => (class, c.offset()?),
+                },
+                _ => return Err(unsupported("data layout versions other than 1 to 4")),
+            };
+            if class != 1 {
+                return Err(unsupported("chunked datasets, such as compressed ones,"));
+            }
+            if self.is_undefined(address) {
+                return Err(invalid("dataset has no data"));
+            }
+            let c = self.at(address)?;
+            Ok(&c.bytes[c.pos..])
+        }
+    }
+
+    fn datatype(data: &[u8]) -> io::Result<Datatype> {
+        let [class, bits, _, _, size @ ..] = data else {
+            return Err(truncated());
+        };
+        let size =
+            u32::from_le_bytes(size.get(..4).ok_or_else(truncated)?.try_into().unwrap()) as usize;
+        Ok(match class & 0x0F {
+            1 if bits & 0x40 != 0 => return Err(unsupported("VAX floats")),
+            1 if size == 4 || size == 8 => Datatype::Float {
+                size,
+                big_endian: bits & 0x01 != 0,
+            },
+            3 => Datatype::String {
+                size,
+                space_padded: bits & 0x0F == 2,
+            },
+            9 if bits & 0x0F == 1 => Datatype::VarString { size },
+            _ => Datatype::Other,
+        })
+    }
+
+    // Returns the number of elements in the dataspace `c`.
+    fn dataspace(mut c: Cursor<'_>) -> io::Result<usize> {
+        Ok(dims(&mut c)?.map_or(0, |dims| dims.iter().product()))
+    }
+
+    // Returns the dimensions of the dataspace `c`, or none for null dataspaces.
+    fn dims(c: &mut Cursor<'_>) -> io::Result<Option<Vec<usize>>> {
+        let version = c.u8()?;
+        let dimensions = c.u8()? as usize;
+        let _flags = c.u8()?;
+        match version {
+            1 => c.skip(5)?,
+            2 if c.u8()? == 2 => return Ok(None),
+            2 => {}
+            _ => return Err(unsupported("dataspace versions other than 1 and 2")),
+        }
+        (0..dimensions)
+            .map(|_| c.length())
+            .collect::<io::Result<_>>()
+            .map(Some)
+    }
+}
+
+// A parser of the JSON configurations of models.
+mod json {
+    use std::io;
+
+    use crate::checkpoint::invalid;
+
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum Value {
+        Null,
+        Bool(bool),
+        Number(f64),
+        String(String),
+        Array(Vec<Value>),
+        Object(Vec<(String, Value)>),
+    }
+
+    impl Value {
+        pub fn get(&self, key: &str) -> Option<&Value> {
+            match self {
+                Value::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+                _ => None,
+            }
+        }
+
+        pub fn as_str(&self) -> Option<&str> {
+            match self {
+                Value::String(s) => Some(s),
+                _ => None,
+            }
+        }
+
+        pub fn as_array(&self) -> Option<&[Value]> {
+            match self {
+                Value::Array(values) => Some(values),
+                _ => None,
+            }
+        }
+    }
+
+    pub fn parse(text: &str) -> io::Result<Value> {
+        let mut parser = Parser {
+            text: text.as_bytes(),
+            pos: 0,
+        };
+        let value = parser.value(0)?;
+        parser.whitespace();
+        if parser.pos != parser.text.len() {
+            return Err(error());
+        }
+        Ok(value)
+    }
+
+    fn error() -> io::Error {
+        invalid("invalid model configuration")
+    }
+
+    // The deepest nesting of arrays and objects.
+    const MAX_DEPTH: usize = 256;
+
+    struct Parser<'a> {
+        text: &'a [u8],
+        pos: usize,
+    }
+
+    impl Parser<'_> {
+        fn whitespace(&mut self) {
+            while self.text.get(self.pos).is_some_and(u8::is_ascii_whitespace) {
+                self.pos += 1;
+            }
+        }
+
+        fn eat(&mut self, byte: u8) -> bool {
+            self.whitespace();
+            let eaten = self.text.get(self.pos) == Some(&byte);
+            self.pos += eaten as usize;
+            eaten
+        }
+
+        fn literal(&mut self, literal: &str, value: Value) -> io::Result<Value> {
+            if !self.text[self.pos..].starts_with(literal.as_bytes()) {
+                return Err(error());
+            }
+            self.pos += literal.len();
+            Ok(value)
+        }
+
+        fn value(&mut self, depth: usize) -> io::Result<Value> {
+            if depth > MAX_DEPTH {
+                return Err(error());
+            }
+            self.whitespace();
+            match self.text.get(self.pos).ok_or_else(error)? {
+                b'n' => self.literal("null", Value::Null),
+                b't' => self.literal("true", Value::Bool(true)),
+                b'f' => self.literal("false", Value::Bool(false)),
+                // Python writes non-finite floats as these.
+                b'N' => self.literal("NaN", Value::Number(f64::NAN)),
+                b'I' => self.literal("Infinity", Value::Number(f64::INFINITY)),
+                b'"' => self.string().map(Value::String),
+                b'[' => {
+                    self.pos += 1;
+                    let mut values = Vec::new();
+                    if !self.eat(b']') {
+                        loop {
+                            values.push(self.value(depth + 1)?);
+                            if self.eat(b']') {
+                                break;
+                            }
+                            if !self.eat(b',') {
+                                return Err(error());
+                            }
+                        }
+                    }
+                    Ok(Value::Array(values))
+                }
+                b'{' => {
+                    self.pos += 1;
+                    let mut entries = Vec::new();
+                    if !self.eat(b'}') {
+                        loop {
+                            self.whitespace();
+                            let key = self.string()?;
+                            if !self.eat(b':') {
+                                return Err(error());
+                            }
+                            entries.push((key, self.value(depth + 1)?));
+                            if self.eat(b'}') {
+                                break;
+                            }
+                            if !self.eat(b',') {
+                                return Err(error());
+                            }
+                        }
+                    }
+                    Ok(Value::Object(entries))
+                }
+                _ => {
+                    let start = self.pos;
+                    while self
+                        .text
+                        .get(self.pos)
+                        // Including `-Infinity`, as Python writes it.
+                        .is_some_and(|b| b"+-.eE0123456789Inity".contains(b))
+                    {
+                        self.pos += 1;
+                    }
+                    let number = std::str::from_utf8(&self.text[start..self.pos]);
+                    number
+                        .ok()
+                        .and_then(|n| n.parse().ok())
+                        .map(Value::Number)
+                        .ok_or_else(error)
+                }
+            }
+        }
+
+        fn string(&mut self) -> io::Result<String> {
+            if self.text.get(self.pos) != Some(&b'"') {
+                return Err(error());
+            }
+            self.pos += 1;
+            let mut string = Vec::new();
+            loop {
+                let byte = *self.text.get(self.pos).ok_or_else(error)?;
+                self.pos += 1;
+                match byte {
+                    b'"' => break,
+                    b'\\' => {
+                        let escaped = *self.text.get(self.pos).ok_or_else(error)?;
+                        self.pos += 1;
+                        let c = match escaped {
+                            b'b' => '\u{8}',
+                            b'f' => '\u{c}',
+                            b'n' => '\n',
+                            b'r' => '\r',
+                            b't' => '\t',
+                            b'u' => {
+                                let hex =
+                                    self.text.get(self.pos..self.pos + 4).ok_or_else(error)?;
+                                self.pos += 4;
+                                let code = std::str::from_utf8(hex)
+                                    .ok()
+                                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                                    .ok_or_else(error)?;
+                                // Surrogate pairs are not needed for the names of layers.
+                                char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+                            }
+                            other => other as char,
+                        };
+                        string.extend(c.encode_utf8(&mut [0; 4]).as_bytes());
+                    }
+                    byte => string.push(byte),
+                }
+            }
+            String::from_utf8(string).map_err(|_| error())
+        }
+    }
+}
@@ -1,8 +1,53 @@
-
 pub mod activ;
+pub mod adversarial;
+pub mod augment;
+pub mod autoencoder;
+pub mod bake;
+pub mod beam;
+pub mod boundary;
+pub mod calibration;
+pub mod checkpoint;
+pub mod constraint;
 pub mod conv;
+pub mod curriculum;
+pub mod distill;
+pub mod dropout;
+pub mod dump;
+pub mod ema;
 pub mod error;
+pub mod evolution;
+pub mod features;
 pub mod full;
 pub mod gen;
+pub mod image;
+#[cfg(feature = "keras")]
+pub mod keras;
+pub mod mapped;
+pub mod metrics;
+pub mod mining;
+pub mod mixed;
+pub mod mlp;
+pub mod model;
+pub mod noise;
+pub mod norm;
+pub mod npy;
+pub mod online;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+pub mod prelude;
+pub mod recurrent;
+pub mod reduce;
+pub mod rl;
+pub mod sample;
+#[cfg(feature = "rayon")]
+pub mod search;
+pub mod seq2seq;
+#[cfg(feature = "serve")]
+pub mod serve;
+pub mod stream;
+pub mod synthetic;
+pub mod testing;
+pub mod train;
 
-pub use full::{Full, FullInter};
+pub use full::{Full, FullError, FullInter, FullView, NoBias, TiedFull};
+pub use norm::{LayerNorm, LayerNormInter};
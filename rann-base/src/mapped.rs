@@ -0,0 +1,310 @@
+/*!
+Model files that are memory-mapped and used in place, to load large models without copying their
+parameters.
+
+[`write_to()`] stores the arrays of an [`Npz`] in a file in which every array is aligned, such
+that it can be borrowed directly from the bytes of the file. A [`Mapped`] file parses the header of
+such a file, and borrows its arrays as slices of [`Scalar`]s, or as [`FullView`] layers using
+[`Mapped::full()`]. With the `mmap` feature, [`Mapped::open()`] maps a file into memory, such that
+only the pages of the parameters that are used are read from disk, by the operating system.
+Files can also be embedded into a binary with `include_bytes!`, by wrapping them in [`Aligned`],
+such that the views of a model borrow static data and need no file system at all.
+
+The arrays are named like the arrays of [`Full::write_npz()`](crate::Full::write_npz), such that an
+[`Npz`] of a trained network can be converted. Mapped files use the extension `.rannmap`, as they
+are not [`model`](crate::model) files: those store the parameters of a network without the names and
+shapes of its arrays, so a model is mapped by loading it into its network and writing the [`Npz`] of
+its layers.
+
+# Examples
+```rust
+use rann_base::{activ::Logistic, mapped::{self, Mapped}, npy::Npz, testing, Full};
+use rann_traits::Network;
+
+let net = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1))
+    .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)));
+let mut npz = Npz::new();
+net.first.write_npz(&mut npz, "0.");
+net.second.write_npz(&mut npz, "1.");
+let mut bytes = Vec::new();
+mapped::write_to(&npz, &mut bytes)?;
+
+let file = Mapped::new(bytes)?;
+let view = file
+    .full::<2, 3, _>("0.", Logistic)?
+    .chain(file.full::<3, 1, _>("1.", Logistic)?);
+assert_eq!(view.eval(&[1.0, 0.0]), net.eval(&[1.0, 0.0]));
+# Ok::<(), std::io::Error>(())
+```
+
+Embedding a model, of which the views borrow static data:
+```rust
+use std::sync::LazyLock;
+
+use rann_base::{activ::LeakyRelu, mapped::{Aligned, Mapped}, FullView};
+use rann_traits::Network;
+
+static MODEL: &Aligned<[u8]> = &Aligned(*include_bytes!("../tests/data/xor.rannmap"));
+static FILE: LazyLock<Mapped<&[u8]>> = LazyLock::new(|| Mapped::new(&MODEL.0).unwrap());
+
+let net: FullView<'static, 2, 2, _> = FILE.full("0.", LeakyRelu(0.0))?;
+let net = net.chain(FILE.full::<2, 1, _>("1.", LeakyRelu(0.0))?);
+assert_eq!(net.eval(&[1.0, 0.0]), [1.0]);
+assert_eq!(net.eval(&[1.0, 1.0]), [0.0]);
+# Ok::<(), std::io::Error>(())
+```
+
+# Format
+Files are stored in little-endian: the magic bytes `RANNMMAP`, a `u32` format version, the number
+of arrays as a `u64`, and for every array its name as a `u64` length followed by that many bytes
+of UTF-8, its number of dimensions as a `u64` followed by that many dimensions as `u64`s, and the
+offset of its values from the start of the file as a `u64`. The values of every array follow as
+`f32`s in row-major order, starting at an offset that is a multiple of [`ALIGNMENT`].
+*/
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Read, Write},
+    path::Path,
+};
+
+use rann_traits::Scalar;
+
+use crate::{
+    checkpoint::{invalid, read_array},
+    model,
+    npy::Npz,
+    FullView,
+};
+
+const MAGIC: &[u8; 8] = b"RANNMMAP";
+const VERSION: u32 = 1;
+/// The alignment in bytes of the values of every array in a file, relative to the start of the
+/// file.
+pub const ALIGNMENT: usize = 64;
+
+/// Bytes aligned to [`ALIGNMENT`], such that the arrays in a file embedded with `include_bytes!`
+/// can be borrowed in place, as `&Aligned(*include_bytes!(...))`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[repr(C, align(64))]
+pub struct Aligned<B: ?Sized>(pub B);
+
+/// Writes the arrays of `npz` to `writer` as a file that can be mapped. See
+/// [module level documentation](self) for more info.
+pub fn write_to(npz: &Npz, mut writer: impl Write) -> io::Result<()> {
+    let header_len = MAGIC.len()
+        + 4
+        + 8
+        + npz
+            .arrays
+            .iter()
+            .map(|(name, array)| 8 + name.len() + 8 + 8 * array.shape.len() + 8)
+            .sum::<usize>();
+    let mut header = Vec::with_capacity(header_len);
+    header.extend(MAGIC);
+    header.extend(VERSION.to_le_bytes());
+    header.extend((npz.arrays.len() as u64).to_le_bytes());
+    let mut offset = header_len;
+    for (name, array) in &npz.arrays {
+        offset = offset.next_multiple_of(ALIGNMENT);
+        header.extend((name.len() as u64).to_le_bytes());
+        header.extend(name.as_bytes());
+        header.extend((array.shape.len() as u64).to_le_bytes());
+        for &dim in &array.shape {
+            header.extend((dim as u64).to_le_bytes());
+        }
+        header.extend((offset as u64).to_le_bytes());
+        offset += 4 * array.data.len();
+    }
+    writer.write_all(&header)?;
+
+    let mut written = header_len;
+    for (_, array) in &npz.arrays {
+        let padding = written.next_multiple_of(ALIGNMENT) - written;
+        writer.write_all(&[0; ALIGNMENT][..padding])?;
+        for value in &array.data {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        written += padding + 4 * array.data.len();
+    }
+    Ok(())
+}
+
+/// Writes the arrays of `npz` to a file at `path` that can be mapped.
+pub fn save(npz: &Npz, path: impl AsRef<Path>) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_to(npz, &mut writer)?;
+    writer.flush()
+}
+
+// The shape of an array and the range of its values, in `f32`s from the start of the file.
+#[derive(Clone, Debug)]
+struct Entry {
+    name: String,
+    shape: Vec<usize>,
+    start: usize,
+    len: usize,
+}
+
+/// The bytes of a file written by [`write_to()`], of which the arrays are borrowed in place. See
+/// [module level documentation](self) for more info.
+#[derive(Debug)]
+pub struct Mapped<D> {
+    data: D,
+    entries: Vec<Entry>,
+}
+
+impl<D: AsRef<[u8]>> Mapped<D> {
+    /// Parses the header of the file in `data`.
+    ///
+    /// # Errors
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if `data` is not a valid file, if
+    /// it is not aligned to 4 bytes, or on big-endian targets, on which the values can not be
+    /// borrowed in place.
+    pub fn new(data: D) -> io::Result<Self> {
+        if cfg!(target_endian = "big") {
+            return Err(invalid(
+                "mapped files can not be used on big-endian targets",
+            ));
+        }
+        let bytes = data.as_ref();
+        if bytes.as_ptr().align_offset(std::mem::align_of::<Scalar>()) != 0 {
+            return Err(invalid("mapped file is not aligned"));
+        }
+        let mut reader = bytes;
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic == model::MAGIC {
+            return Err(invalid(
+                "model files can not be mapped, write the arrays of the network instead",
+            ));
+        }
+        if &magic != MAGIC {
+            return Err(invalid("not a mapped file"));
+        }
+        let version = u32::from_le_bytes(read_array(&mut reader)?);
+        if version != VERSION {
+            return Err(invalid(format!(
+                "unsupported mapped file version {version}"
+            )));
+        }
+        let mut entries = Vec::new();
+        for _ in 0..read_len(&mut reader)? {
+            let len = read_len(&mut reader)?;
+            if len > reader.len() {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            let (name, rest) = reader.split_at(len);
+            reader = rest;
+            let name =
+                String::from_utf8(name.to_vec()).map_err(|_| invalid("name is not UTF-8"))?;
+            let rank = read_len(&mut reader)?;
+            if rank > reader.len() / 8 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            let shape = (0..rank)
+                .map(|_| read_len(&mut reader))
+                .collect::<io::Result<Vec<_>>>()?;
+            let offset = read_len(&mut reader)?;
+            let len = shape
+                .iter()
+                .try_fold(1usize, |len, &dim| len.checked_mul(dim))
+                .filter(|len| {
+                    offset % ALIGNMENT == 0
+                        && len
+                            .checked_mul(4)
+                            .and_then(|size| size.checked_add(offset))
+                            .is_some_and(|end| end <= bytes.len())
+                })
+                .ok_or_else(|| invalid(format!("array {name} is out of bounds")))?;
+            entries.push(Entry {
+                name,
+                shape,
+                start: offset / 4,
+                len,
+            });
+        }
+        Ok(Self { data, entries })
+    }
+
+    /// Borrows the bytes of the file.
+    pub fn data(&self) -> &D {
+        &self.data
+    }
+
+    /// Returns the names of the arrays, in the order they are stored in.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|entry| entry.name.as_str())
+    }
+
+    /// Borrows the shape and the values of the array named `name`, if there is one.
+    ///
+    /// # Panics
+    /// Panics if the data was moved to an address that is not aligned to 4 bytes since it was
+    /// parsed, which is only possible if it is stored inline in `D`.
+    pub fn get(&self, name: &str) -> Option<(&[usize], &[Scalar])> {
+        let entry = self.entries.iter().find(|entry| entry.name == name)?;
+        let bytes = &self.data.as_ref()[4 * entry.start..4 * (entry.start + entry.len)];
+        // SAFETY: Any bit pattern is a valid `f32`.
+        let (prefix, values, _) = unsafe { bytes.align_to::<Scalar>() };
+        assert!(prefix.is_empty(), "The mapped file should be aligned.");
+        Some((&entry.shape, values))
+    }
+
+    /// Borrows the values of the array named `name`.
+    ///
+    /// # Errors
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if there is no such array, or if
+    /// it does not have `shape`.
+    pub fn get_shaped(&self, name: &str, shape: &[usize]) -> io::Result<&[Scalar]> {
+        let (found, values) = self
+            .get(name)
+            .ok_or_else(|| invalid(format!("missing array {name}")))?;
+        if found != shape {
+            return Err(invalid(format!(
+                "array {name} has shape {found:?}, but should have shape {shape:?}"
+            )));
+        }
+        Ok(values)
+    }
+
+    /// Borrows the arrays `{prefix}weight` and `{prefix}bias` as a layer with `activation`, like
+    /// [`Full::read_npz()`](crate::Full::read_npz) reads them. If there is no array
+    /// `{prefix}bias`, the layer has no biases.
+    ///
+    /// # Errors
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if the weights are missing, or if
+    /// the arrays have the wrong shapes.
+    pub fn full<const NUM_IN: usize, const NUM_OUT: usize, A>(
+        &self,
+        prefix: &str,
+        activation: A,
+    ) -> io::Result<FullView<'_, NUM_IN, NUM_OUT, A>> {
+        let weights = self.get_shaped(&format!("{prefix}weight"), &[NUM_OUT, NUM_IN])?;
+        let name = format!("{prefix}bias");
+        let biases = match self.get(&name) {
+            Some(_) => self.get_shaped(&name, &[NUM_OUT])?,
+            None => &[],
+        };
+        Ok(FullView::new(activation, weights, biases))
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl Mapped<memmap2::Mmap> {
+    /// Maps the file at `path` into memory and parses its header.
+    ///
+    /// # Safety
+    /// The file must not be modified or truncated while it is mapped, as that changes the
+    /// borrowed values or makes them inaccessible.
+    pub unsafe fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: Upheld by the caller.
+        Self::new(unsafe { memmap2::Mmap::map(&file)? })
+    }
+}
+
+fn read_len(reader: &mut impl Read) -> io::Result<usize> {
+    usize::try_from(u64::from_le_bytes(read_array(reader)?))
+        .map_err(|_| invalid("length overflows"))
+}
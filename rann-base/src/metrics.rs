@@ -0,0 +1,458 @@
+/*!
+Metrics for validating networks beyond their mean error.
+
+A [`Validate`] metric scores a network on a validation set, and reports the result. Metrics are
+computed after every epoch by
+[`Trainer::fit_validated()`](crate::train::Trainer::fit_validated), which passes each report to
+a callback for logging, and returns them.
+
+For classification, [`Classification`] counts the predictions of a network in a
+[`ConfusionMatrix`] and reports the precision, recall and F1 score of every class. The
+predictions of a network ending in an error function are the outputs before it. For
+regression, [`Regression`] reports the mean absolute error, root mean square error, coefficient
+of determination (R²) and explained variance of the predictions. For binary classifiers,
+[`RocCurve`] sweeps the decision threshold, for the ROC curve and the area under it.
+
+# Examples
+```rust
+use rann_base::{
+    activ::Logistic, error::SquareError, metrics::Classification, testing, train::Trainer, Full,
+};
+use rann_traits::{LearningRate, Network};
+
+let mut net = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1))
+    .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)))
+    .chain(SquareError { expected: [0.0] });
+let trainer = Trainer { epochs: 2000, learning_rate: LearningRate(1.0), ..Default::default() };
+let validation = testing::XOR;
+let validated = trainer.fit_validated(&mut net, &testing::XOR, &validation, &Classification, |fit, report| {
+    println!("Epoch {}:\n{report}", fit.errors.len());
+});
+assert_eq!(validated.reports.last().unwrap().accuracy, 1.0);
+```
+*/
+
+use std::fmt::{self, Display};
+
+use rann_traits::{compose::Chain, Network, Scalar, Supervised};
+
+use crate::train::Fit;
+
+/// A metric scoring a network on a validation set.
+pub trait Validate<N: Supervised> {
+    /// The result of scoring a network.
+    type Report;
+
+    /// Scores `net` on `validation`.
+    fn validate(&self, net: &N, validation: &[(N::In, N::Target)]) -> Self::Report;
+}
+
+/// The result of [`Trainer::fit_validated()`](crate::train::Trainer::fit_validated).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Validated<R> {
+    /// The result of training.
+    pub fit: Fit,
+    /// The report of the metric after every completed epoch.
+    pub reports: Vec<R>,
+}
+
+/// Returns the class predicted by `outputs`: the index of the largest output, or for a single
+/// output, `1` if it is at least `0.5` and `0` otherwise. Also used for one-hot and binary
+/// targets.
+pub fn class_of(outputs: &[Scalar]) -> usize {
+    match outputs {
+        [output] => (*output >= 0.5) as usize,
+        _ => {
+            outputs
+                .iter()
+                .enumerate()
+                .fold((0, Scalar::NEG_INFINITY), |best, (i, &o)| {
+                    if o > best.1 {
+                        (i, o)
+                    } else {
+                        best
+                    }
+                })
+                .0
+        }
+    }
+}
+
+/// The number of classes for `n` outputs or targets, see [`class_of()`].
+fn num_classes(n: usize) -> usize {
+    n.max(2)
+}
+
+/// Counts of the predicted classes for every actual class.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfusionMatrix {
+    // The counts of row `actual`, column `predicted`.
+    counts: Vec<Vec<usize>>,
+}
+
+impl ConfusionMatrix {
+    /// Creates an empty confusion matrix of `classes` classes.
+    pub fn new(classes: usize) -> Self {
+        Self {
+            counts: vec![vec![0; classes]; classes],
+        }
+    }
+
+    /// Counts a prediction of class `predicted` for a sample of class `actual`.
+    pub fn add(&mut self, actual: usize, predicted: usize) {
+        self.counts[actual][predicted] += 1;
+    }
+
+    /// Returns the number of classes.
+    pub fn classes(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Returns the number of samples of class `actual` predicted as class `predicted`.
+    pub fn count(&self, actual: usize, predicted: usize) -> usize {
+        self.counts[actual][predicted]
+    }
+
+    /// Returns the total number of samples.
+    pub fn total(&self) -> usize {
+        self.counts.iter().flatten().sum()
+    }
+
+    /// Returns the fraction of samples predicted correctly, or zero if there are none.
+    pub fn accuracy(&self) -> Scalar {
+        let correct: usize = (0..self.classes()).map(|c| self.counts[c][c]).sum();
+        ratio(correct, self.total())
+    }
+
+    /// Returns the precision, recall and F1 score of class `class`.
+    pub fn class_metrics(&self, class: usize) -> ClassMetrics {
+        let tp = self.counts[class][class];
+        let predicted: usize = self.counts.iter().map(|row| row[class]).sum();
+        let support: usize = self.counts[class].iter().sum();
+        let precision = ratio(tp, predicted);
+        let recall = ratio(tp, support);
+        let f1 = if precision + recall > 0.0 {
+            2.0 * precision * recall / (precision + recall)
+        } else {
+            0.0
+        };
+        ClassMetrics {
+            precision,
+            recall,
+            f1,
+            support,
+        }
+    }
+}
+
+// Returns `a / b`, or zero if `b` is zero.
+fn ratio(a: usize, b: usize) -> Scalar {
+    if b == 0 {
+        0.0
+    } else {
+        a as Scalar / b as Scalar
+    }
+}
+
+/// The metrics of a single class.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClassMetrics {
+    /// The fraction of the predictions of the class that were right.
+    pub precision: Scalar,
+    /// The fraction of the samples of the class that were predicted.
+    pub recall: Scalar,
+    /// The harmonic mean of the precision and recall.
+    pub f1: Scalar,
+    /// The number of samples of the class.
+    pub support: usize,
+}
+
+/// The report of [`Classification`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClassificationReport {
+    /// The predictions.
+    pub confusion: ConfusionMatrix,
+    /// The metrics of every class.
+    pub classes: Vec<ClassMetrics>,
+    /// The fraction of samples predicted correctly.
+    pub accuracy: Scalar,
+    /// The mean F1 score over the classes.
+    pub macro_f1: Scalar,
+}
+
+impl ClassificationReport {
+    /// Computes the metrics of the predictions in `confusion`.
+    pub fn new(confusion: ConfusionMatrix) -> Self {
+        let classes: Vec<_> = (0..confusion.classes())
+            .map(|c| confusion.class_metrics(c))
+            .collect();
+        let macro_f1 =
+            classes.iter().map(|c| c.f1).sum::<Scalar>() / classes.len().max(1) as Scalar;
+        Self {
+            accuracy: confusion.accuracy(),
+            confusion,
+            classes,
+            macro_f1,
+        }
+    }
+}
+
+// A table with a row for every class.
+impl Display for ClassificationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "class  precision  recall  f1      support")?;
+        for (i, c) in self.classes.iter().enumerate() {
+            writeln!(
+                f,
+                "{i:<5}  {:<9.4}  {:<6.4}  {:<6.4}  {}",
+                c.precision, c.recall, c.f1, c.support
+            )?;
+        }
+        write!(
+            f,
+            "accuracy {:.4}, macro F1 {:.4}",
+            self.accuracy, self.macro_f1
+        )
+    }
+}
+
+/// A classification metric, for networks ending in an error function, whose predictions are the
+/// outputs before it. The classes of the outputs and targets are found by [`class_of()`], so
+/// targets are one-hot vectors, or a single target of `0.0` or `1.0` for binary classification.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Classification;
+
+impl<P, L, const C: usize> Validate<Chain<P, L>> for Classification
+where
+    P: Network<Out = [Scalar; C]>,
+    L: Supervised<In = [Scalar; C], Target = [Scalar; C]>,
+{
+    type Report = ClassificationReport;
+
+    fn validate(&self, net: &Chain<P, L>, validation: &[(P::In, [Scalar; C])]) -> Self::Report {
+        let mut confusion = ConfusionMatrix::new(num_classes(C));
+        for (inputs, target) in validation {
+            confusion.add(class_of(target), class_of(&net.first.eval(inputs)));
+        }
+        ClassificationReport::new(confusion)
+    }
+}
+
+/// The report of [`Regression`], for a batch of predictions and targets.
+///
+/// Every metric is computed for every output separately, and averaged over the outputs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RegressionReport {
+    /// The mean absolute error.
+    pub mae: Scalar,
+    /// The root of the mean square error.
+    pub rmse: Scalar,
+    /// The coefficient of determination: the fraction of the variance of the targets predicted,
+    /// which is one for perfect predictions and zero for predicting the mean.
+    pub r2: Scalar,
+    /// The fraction of the variance of the targets explained by the predictions, like
+    /// [`Self::r2`] but ignoring a constant offset of the predictions.
+    pub explained_variance: Scalar,
+}
+
+impl RegressionReport {
+    /// Computes the metrics of `predictions` of `targets`.
+    ///
+    /// For targets without variance, [`Self::r2`] and [`Self::explained_variance`] are one for
+    /// perfect predictions, and zero otherwise.
+    ///
+    /// # Panics
+    /// Panics if the number of predictions and targets, or their lengths, differ.
+    pub fn new<P: AsRef<[Scalar]>>(predictions: &[P], targets: &[P]) -> Self {
+        assert_eq!(
+            predictions.len(),
+            targets.len(),
+            "Every prediction should have a target."
+        );
+        let outputs = targets.first().map_or(0, |t| t.as_ref().len());
+        let n = targets.len() as f64;
+        let (mut mae, mut rmse, mut r2, mut explained) = (0.0, 0.0, 0.0, 0.0);
+        for o in 0..outputs {
+            // Accumulate in double precision, as the sums can be large.
+            let (mut abs, mut sq, mut sum_y, mut sum_sq_y, mut sum_e, mut sum_sq_e) =
+                (0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+            for (p, t) in predictions.iter().zip(targets) {
+                let (p, t) = (p.as_ref(), t.as_ref());
+                assert_eq!(p.len(), t.len(), "Predictions and targets should match.");
+                let (y, e) = (t[o] as f64, (t[o] - p[o]) as f64);
+                abs += e.abs();
+                sq += e * e;
+                sum_y += y;
+                sum_sq_y += y * y;
+                sum_e += e;
+                sum_sq_e += e * e;
+            }
+            let var_y = sum_sq_y / n - (sum_y / n).powi(2);
+            let var_e = sum_sq_e / n - (sum_e / n).powi(2);
+            let fraction = |unexplained: f64| {
+                if var_y > f64::EPSILON {
+                    1.0 - unexplained / var_y
+                } else if unexplained <= f64::EPSILON {
+                    1.0
+                } else {
+                    0.0
+                }
+            };
+            mae += abs / n;
+            rmse += (sq / n).sqrt();
+            r2 += fraction(sq / n);
+            explained += fraction(var_e.max(0.0));
+        }
+        let outputs = outputs.max(1) as f64;
+        Self {
+            mae: (mae / outputs) as Scalar,
+            rmse: (rmse / outputs) as Scalar,
+            r2: (r2 / outputs) as Scalar,
+            explained_variance: (explained / outputs) as Scalar,
+        }
+    }
+}
+
+impl Display for RegressionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "MAE {:.4}, RMSE {:.4}, R² {:.4}, explained variance {:.4}",
+            self.mae, self.rmse, self.r2, self.explained_variance
+        )
+    }
+}
+
+/// A regression metric, for networks ending in an error function, whose predictions are the
+/// outputs before it.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Regression;
+
+impl<P, L, const N: usize> Validate<Chain<P, L>> for Regression
+where
+    P: Network<Out = [Scalar; N]>,
+    L: Supervised<In = [Scalar; N], Target = [Scalar; N]>,
+{
+    type Report = RegressionReport;
+
+    fn validate(&self, net: &Chain<P, L>, validation: &[(P::In, [Scalar; N])]) -> Self::Report {
+        let (predictions, targets): (Vec<_>, Vec<_>) = validation
+            .iter()
+            .map(|(inputs, target)| (net.first.eval(inputs), *target))
+            .unzip();
+        RegressionReport::new(&predictions, &targets)
+    }
+}
+
+/// Returns the confusion matrix of binary predictions of `labels` by `scores`, where a score of
+/// at least `threshold` predicts the positive class `1`.
+///
+/// # Panics
+/// Panics if the number of scores and labels differ.
+pub fn confusion_at(scores: &[Scalar], labels: &[bool], threshold: Scalar) -> ConfusionMatrix {
+    assert_eq!(
+        scores.len(),
+        labels.len(),
+        "Every score should have a label."
+    );
+    let mut confusion = ConfusionMatrix::new(2);
+    for (score, label) in scores.iter().zip(labels) {
+        confusion.add(*label as usize, (*score >= threshold) as usize);
+    }
+    confusion
+}
+
+/// A point of a [`RocCurve`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RocPoint {
+    /// The threshold: scores of at least it predict the positive class.
+    pub threshold: Scalar,
+    /// The fraction of negative samples predicted positive.
+    pub false_positive_rate: Scalar,
+    /// The fraction of positive samples predicted positive.
+    pub true_positive_rate: Scalar,
+}
+
+/// The receiver operating characteristic of a binary classifier: its true and false positive
+/// rates for every threshold on its scores.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RocCurve {
+    /// The points for every distinct score as threshold, from the highest threshold to the
+    /// lowest, preceded by the point of an infinite threshold predicting nothing positive.
+    pub points: Vec<RocPoint>,
+}
+
+impl RocCurve {
+    /// Computes the curve of `scores` for `labels`, where `true` is the positive class.
+    ///
+    /// If all labels are equal, the rates of the missing class are zero.
+    ///
+    /// # Panics
+    /// Panics if the number of scores and labels differ.
+    pub fn new(scores: &[Scalar], labels: &[bool]) -> Self {
+        assert_eq!(
+            scores.len(),
+            labels.len(),
+            "Every score should have a label."
+        );
+        let positives = labels.iter().filter(|l| **l).count();
+        let negatives = labels.len() - positives;
+        let mut samples: Vec<_> = scores.iter().copied().zip(labels.iter().copied()).collect();
+        samples.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mut points = vec![RocPoint {
+            threshold: Scalar::INFINITY,
+            false_positive_rate: 0.0,
+            true_positive_rate: 0.0,
+        }];
+        let (mut tp, mut fp) = (0, 0);
+        // Every group of equal scores moves the threshold past all of them at once.
+        for group in samples.chunk_by(|a, b| a.0 == b.0) {
+            let group_tp = group.iter().filter(|(_, label)| *label).count();
+            tp += group_tp;
+            fp += group.len() - group_tp;
+            points.push(RocPoint {
+                threshold: group[0].0,
+                false_positive_rate: ratio(fp, negatives),
+                true_positive_rate: ratio(tp, positives),
+            });
+        }
+        Self { points }
+    }
+
+    /// Computes the curve of a binary classifier ending in an error function on `dataset`, with
+    /// its single output before the error function as score, and targets of `0.0` or `1.0`.
+    pub fn of<P, L>(net: &Chain<P, L>, dataset: &[(P::In, [Scalar; 1])]) -> Self
+    where
+        P: Network<Out = [Scalar; 1]>,
+    {
+        let (scores, labels): (Vec<_>, Vec<_>) = dataset
+            .iter()
+            .map(|(inputs, [target])| (net.first.eval(inputs)[0], *target >= 0.5))
+            .unzip();
+        Self::new(&scores, &labels)
+    }
+
+    /// Returns the area under the curve: the probability that a random positive sample has a
+    /// higher score than a random negative sample, counting ties as one half.
+    pub fn auc(&self) -> Scalar {
+        self.points
+            .windows(2)
+            .map(|w| {
+                let width = w[1].false_positive_rate - w[0].false_positive_rate;
+                width * (w[0].true_positive_rate + w[1].true_positive_rate) / 2.0
+            })
+            .sum()
+    }
+
+    /// Returns the point maximizing Youden's J statistic, the true positive rate minus the false
+    /// positive rate, whose threshold balances both rates. Of equal points, the one with the
+    /// highest threshold is returned.
+    pub fn best_threshold(&self) -> RocPoint {
+        let j = |p: &RocPoint| p.true_positive_rate - p.false_positive_rate;
+        self.points[1..].iter().fold(
+            self.points[0],
+            |best, p| if j(p) > j(&best) { *p } else { best },
+        )
+    }
+}
@@ -0,0 +1,75 @@
+/*!
+Hard example mining: training more often on the samples a network gets most wrong.
+
+With [`Trainer::mining`](crate::train::Trainer::mining) set, the loss of every sample before it
+is trained on is tracked, like the unreduced losses of a [`BatchLoss`](crate::error::BatchLoss)
+with [`ReduceNone`](crate::error::ReduceNone). After the first epoch, every epoch trains on all
+samples of the dataset in order, followed by the hardest samples again: those with the largest
+losses when they were last trained on. The number of repeated samples is the
+[`HardMining::ratio`] of the size of the dataset.
+
+Oversampling the hardest samples speeds up learning rare or difficult cases, but also amplifies
+samples with wrong labels, so the ratio is best kept small.
+
+# Examples
+```rust
+use rann_base::{
+    activ::Logistic, error::SquareError, mining::HardMining, testing, train::Trainer, Full,
+};
+use rann_traits::{LearningRate, Network};
+
+let mut net = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1))
+    .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)))
+    .chain(SquareError { expected: [0.0] });
+let trainer = Trainer {
+    epochs: 10,
+    learning_rate: LearningRate(0.5),
+    mining: Some(HardMining { ratio: 0.25 }),
+    ..Default::default()
+};
+// Every epoch after the first trains on the hardest of the four samples twice.
+let fit = trainer.fit(&mut net, &testing::XOR);
+assert_eq!(fit.steps, 4 + 9 * 5);
+```
+*/
+
+use rann_traits::Scalar;
+
+/// The configuration of hard example mining. See [module level documentation](self) for more
+/// info.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HardMining {
+    /// The number of hardest samples that are trained on again every epoch, as a fraction of the
+    /// number of samples, rounded to the nearest integer. A ratio of one trains twice on every
+    /// sample.
+    pub ratio: Scalar,
+}
+
+impl HardMining {
+    /// Returns the number of hardest samples to train on again every epoch, for a dataset of
+    /// `len` samples.
+    pub fn count(&self, len: usize) -> usize {
+        ((self.ratio.max(0.0) * len as Scalar).round() as usize).min(len)
+    }
+
+    /// Returns the indices of the hardest samples to train on again, in order of decreasing
+    /// loss, for the `losses` of the samples.
+    pub fn hardest(&self, losses: &[Scalar]) -> Vec<usize> {
+        hardest(losses, self.count(losses.len()))
+    }
+}
+
+impl Default for HardMining {
+    fn default() -> Self {
+        Self { ratio: 0.1 }
+    }
+}
+
+/// Returns the indices of the `count` largest of `losses`, in order of decreasing loss. Ties are
+/// broken by the lowest index, and `NaN` losses are the largest.
+pub fn hardest(losses: &[Scalar], count: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..losses.len()).collect();
+    order.sort_by(|&a, &b| losses[b].total_cmp(&losses[a]).then(a.cmp(&b)));
+    order.truncate(count);
+    order
+}
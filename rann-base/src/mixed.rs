@@ -0,0 +1,268 @@
+/*!
+Mixed precision layers.
+
+The parameters of a [`MixedFull`] layer are stored in a [`Precision`] of choice, while its
+weighted sums and gradients are always calculated in `f64`. The inputs and outputs of the layer
+remain [`Scalar`]s, so mixed precision layers can be chained with any other layer, and the
+precision can be chosen per layer:
+- `f64` storage is the most accurate, at the cost of twice the memory of a [`Full`] layer,
+- `f32` storage uses the same memory as a [`Full`] layer, but sums more accurately,
+- `f16` storage (with the `half` feature) halves the memory, but small updates are lost to
+  rounding.
+
+# Examples
+```rust
+use rann_base::{activ::Logistic, mixed::MixedFull, Full};
+use rann_traits::Network;
+
+let gen = (|i, j| (i + j) as f32 * 0.1, |_| 0.0);
+let full = Full::<3, 2, _>::new(Logistic, gen);
+// Convert the layer to one storing its parameters in double precision.
+let mixed = MixedFull::<3, 2, _, f64>::from_full(&full);
+assert_eq!(full.eval(&[1.0, 2.0, 3.0]), mixed.eval(&[1.0, 2.0, 3.0]));
+```
+*/
+
+use std::fmt::Debug;
+
+use arrayvec::ArrayVec;
+use rann_traits::{
+    deriv::Deriv,
+    inspect::{short_type_name, Inspect, LayerView},
+    params::Parameterized,
+    Intermediate, Network, Scalar,
+};
+
+use crate::{full::param_grad_norm, Full, FullInter};
+
+/// Trait for the types that parameters can be stored in.
+pub trait Precision: Copy + Debug {
+    /// Converts from the computation precision, rounding if necessary.
+    fn from_f64(x: f64) -> Self;
+    /// Converts to the computation precision.
+    fn to_f64(self) -> f64;
+}
+
+impl Precision for f32 {
+    fn from_f64(x: f64) -> Self {
+        x as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Precision for f64 {
+    fn from_f64(x: f64) -> Self {
+        x
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+#[cfg(feature = "half")]
+impl Precision for half::f16 {
+    fn from_f64(x: f64) -> Self {
+        half::f16::from_f64(x)
+    }
+
+    fn to_f64(self) -> f64 {
+        half::f16::to_f64(self)
+    }
+}
+
+/// A fully connected network layer storing its parameters in precision `S`, and calculating in
+/// `f64`. See [module level documentation](self) for more info.
+#[derive(Clone, Debug)]
+pub struct MixedFull<const NUM_IN: usize, const NUM_OUT: usize, A, S> {
+    // The weights of each input, such that they are stored in the same order as those of `Full`.
+    weights: [[S; NUM_OUT]; NUM_IN],
+    biases: [S; NUM_OUT],
+    act: A,
+    // The norm of the gradients over the parameters in the last training step.
+    grad_norm: Scalar,
+}
+
+impl<const NUM_IN: usize, const NUM_OUT: usize, A, S> MixedFull<NUM_IN, NUM_OUT, A, S>
+where
+    A: Deriv<In = Scalar, Out = Scalar>,
+    S: Precision,
+{
+    // Creates a fully connected layer with the given activation and with weights and biases
+    // generated using the given generator functions, like `Full::new`.
+    pub fn new<T, F, G>(
+        // The activation function for this layer.
+        activation: A,
+        // Tuple of functions to generate the (weights, biases) for the layer.
+        gen: T,
+    ) -> Self
+    where
+        T: Into<(F, G)>,
+        F: FnMut(usize, usize) -> Scalar,
+        G: FnMut(usize) -> Scalar,
+    {
+        let (mut weight_gen, mut bias_gen) = gen.into();
+        // Generate the weights in the same order as `Full::new`.
+        let mut weights = [[S::from_f64(0.0); NUM_OUT]; NUM_IN];
+        for (col, weights) in weights.iter_mut().enumerate() {
+            for (row, w) in weights.iter_mut().enumerate() {
+                *w = S::from_f64(weight_gen(row, col) as f64);
+            }
+        }
+        Self {
+            weights,
+            biases: std::array::from_fn(|i| S::from_f64(bias_gen(i) as f64)),
+            act: activation,
+            grad_norm: 0.0,
+        }
+    }
+
+    /// Converts `full` into a layer with the same activation and parameters, rounded to `S`.
+    pub fn from_full(full: &Full<NUM_IN, NUM_OUT, A>) -> Self
+    where
+        A: Clone,
+    {
+        let mut mixed = Self::new(full.activation().clone(), (|_, _| 0.0, |_| 0.0));
+        mixed.read_params(&full.params());
+        mixed
+    }
+
+    /// Converts this layer into a [`Full`] layer with the same activation and parameters,
+    /// rounded to [`Scalar`]s.
+    pub fn to_full(&self) -> Full<NUM_IN, NUM_OUT, A>
+    where
+        A: Clone,
+    {
+        let mut full = Full::new(self.act.clone(), (|_, _| 0.0, |_| 0.0));
+        full.read_params(&self.params());
+        full
+    }
+}
+
+impl<const NUM_IN: usize, const NUM_OUT: usize, A, S> Network for MixedFull<NUM_IN, NUM_OUT, A, S>
+where
+    A: Deriv<In = Scalar, Out = Scalar>,
+    S: Precision,
+{
+    type In = [Scalar; NUM_IN];
+
+    type Out = [Scalar; NUM_OUT];
+
+    type Inter = FullInter<NUM_OUT>;
+
+    fn intermediate(&self, input: &Self::In) -> Self::Inter {
+        // Find the weighted sums in the computation precision.
+        let mut sums = self.biases.map(S::to_f64);
+        for (weights, x) in self.weights.iter().zip(input) {
+            for (sum, w) in sums.iter_mut().zip(weights) {
+                *sum += w.to_f64() * *x as f64;
+            }
+        }
+        let weighted_sums = sums.map(|sum| sum as Scalar);
+        FullInter {
+            weighted_sums,
+            outputs: weighted_sums.map(|sum| self.act.call(&sum)),
+        }
+    }
+
+    fn train_deriv(
+        &mut self,
+        input: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        let rate = learning_rate as f64;
+        // Calculate the gradients over the activation
+        let grad: ArrayVec<f64, NUM_OUT> = gradients
+            .iter()
+            .zip(intermediate.weighted_sums.iter())
+            .map(|(gr, sum)| (gr * self.act.deriv(sum)) as f64)
+            .collect();
+        let grad_scalar: ArrayVec<Scalar, NUM_OUT> = grad.iter().map(|&g| g as Scalar).collect();
+        self.grad_norm = param_grad_norm(&grad_scalar, input);
+        // Calculate the gradients over the inputs, before updating the weights.
+        let out: ArrayVec<Scalar, NUM_IN> = self
+            .weights
+            .iter()
+            .map(|weights| {
+                let sum: f64 = weights.iter().zip(&grad).map(|(w, g)| w.to_f64() * g).sum();
+                sum as Scalar
+            })
+            .collect();
+        // Update the biases
+        for (bias, grad) in self.biases.iter_mut().zip(&grad) {
+            *bias = S::from_f64(bias.to_f64() - grad * rate);
+        }
+        // Calculate the gradients over each weight and update it correspondingly.
+        for (weights, x) in self.weights.iter_mut().zip(input) {
+            for (w, grad) in weights.iter_mut().zip(&grad) {
+                *w = S::from_f64(w.to_f64() - *x as f64 * grad * rate);
+            }
+        }
+
+        out.into_inner()
+            .expect("Capacity of ArrayVec should equal NUM_IN.")
+    }
+}
+
+// The weights, in the same order as those of `Full`, are followed by the biases.
+impl<const NUM_IN: usize, const NUM_OUT: usize, A, S> Parameterized
+    for MixedFull<NUM_IN, NUM_OUT, A, S>
+where
+    S: Precision,
+{
+    fn num_params(&self) -> usize {
+        NUM_IN * NUM_OUT + NUM_OUT
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        assert_eq!(
+            params.len(),
+            self.num_params(),
+            "Wrong number of parameters."
+        );
+        let stored = self.weights.iter().flatten().chain(&self.biases);
+        for (p, s) in params.iter_mut().zip(stored) {
+            *p = s.to_f64() as Scalar;
+        }
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        assert_eq!(
+            params.len(),
+            self.num_params(),
+            "Wrong number of parameters."
+        );
+        let stored = self.weights.iter_mut().flatten().chain(&mut self.biases);
+        for (s, p) in stored.zip(params) {
+            *s = S::from_f64(*p as f64);
+        }
+    }
+}
+
+impl<const NUM_IN: usize, const NUM_OUT: usize, A, S> Inspect for MixedFull<NUM_IN, NUM_OUT, A, S>
+where
+    A: Deriv<In = Scalar, Out = Scalar>,
+    S: Precision,
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        // The parameters are not stored as scalars, so they have to be converted.
+        let params = self.params();
+        let kind = format!(
+            "MixedFull ({}, {})",
+            short_type_name::<A>(),
+            short_type_name::<S>()
+        );
+        f(&LayerView {
+            kind: &kind,
+            num_inputs: NUM_IN,
+            params: &[&params],
+            activations: intermediate.output(),
+            gradient_norm: self.grad_norm,
+        });
+    }
+}
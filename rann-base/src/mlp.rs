@@ -0,0 +1,776 @@
+/*!
+Multilayer perceptrons with layer sizes chosen at runtime.
+
+The layers of RANN have their sizes fixed at compile time. An [`Mlp`] is a chain of fully
+connected layers with sizes and [`Activation`]s read at runtime instead, such as from a config
+file or from the bindings to other languages.
+
+# Examples
+```rust
+use rann_base::{
+    mlp::{Activation, Mlp, SquareLoss},
+    train::Trainer,
+};
+use rann_traits::{LearningRate, Network};
+
+let dataset: Vec<_> = [([0.0, 0.0], 0.0), ([0.0, 1.0], 1.0), ([1.0, 0.0], 1.0), ([1.0, 1.0], 0.0)]
+    .into_iter()
+    .map(|(inputs, target)| (inputs.to_vec(), vec![target]))
+    .collect();
+let mut net = SquareLoss::new(Mlp::new(&[2, 3, 1], Activation::Logistic, 1));
+let trainer = Trainer { epochs: 3000, learning_rate: LearningRate(0.5), ..Default::default() };
+let fit = trainer.fit(&mut net, &dataset);
+assert!(fit.errors.last().unwrap() < &0.05);
+assert_eq!(net.mlp.eval(&vec![1.0, 0.0]).len(), 1);
+```
+
+An [`MlpBuilder`] configures every layer separately: its activation, the [`Init`]ializer of its
+parameters, whether it has biases and how many gradients it backpropagates.
+
+```rust
+use rann_base::mlp::{Activation, Init, Mlp};
+use rann_traits::params::Parameterized;
+
+let mlp = Mlp::builder(4)
+    .seed(7)
+    .layer(8)
+    .activation(Activation::LeakyRelu(0.0))
+    .initializer(Init::He)
+    .no_bias()
+    .layer(2)
+    .activation(Activation::Logistic)
+    .build()
+    .unwrap();
+assert_eq!(mlp.sizes(), [4, 8, 2]);
+assert_eq!(mlp.num_params(), 4 * 8 + 8 * 2 + 2);
+assert!(Mlp::builder(4).activation(Activation::Tanh).build().is_err());
+```
+*/
+
+use std::{
+    fmt::{self, Display},
+    io,
+    path::Path,
+    str::FromStr,
+};
+
+use rann_traits::{
+    deriv::{Deriv, Elementwise},
+    inspect::{Inspect, LayerView},
+    params::Parameterized,
+    Intermediate, Network, Scalar, Supervised,
+};
+
+use crate::{
+    activ::{LeakyRelu, Logistic, Tanh},
+    full::{keep_top_k, param_grad_norm, squared_norm},
+    model::{self, Metadata},
+    train,
+};
+
+/// An activation function chosen at runtime, delegating to those of [`crate::activ`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Activation {
+    /// See [`Logistic`].
+    Logistic,
+    /// See [`Tanh`].
+    Tanh,
+    /// See [`LeakyRelu`], with the slope for negative inputs.
+    LeakyRelu(Scalar),
+}
+
+impl Deriv for Activation {
+    type In = Scalar;
+
+    type Out = Scalar;
+
+    fn call(&self, x: &Scalar) -> Scalar {
+        match *self {
+            Self::Logistic => Logistic.call(x),
+            Self::Tanh => Tanh.call(x),
+            Self::LeakyRelu(slope) => LeakyRelu(slope).call(x),
+        }
+    }
+
+    fn deriv(&self, x: &Scalar) -> Scalar {
+        match *self {
+            Self::Logistic => Logistic.deriv(x),
+            Self::Tanh => Tanh.deriv(x),
+            Self::LeakyRelu(slope) => LeakyRelu(slope).deriv(x),
+        }
+    }
+}
+
+impl FromStr for Activation {
+    type Err = ParseActivationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "logistic" => Ok(Self::Logistic),
+            "tanh" => Ok(Self::Tanh),
+            "relu" => Ok(Self::LeakyRelu(0.0)),
+            "leaky_relu" => Ok(Self::LeakyRelu(0.01)),
+            _ => Err(ParseActivationError(s.to_string())),
+        }
+    }
+}
+
+impl Display for Activation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Logistic => f.write_str("logistic"),
+            Self::Tanh => f.write_str("tanh"),
+            Self::LeakyRelu(0.0) => f.write_str("relu"),
+            Self::LeakyRelu(_) => f.write_str("leaky_relu"),
+        }
+    }
+}
+
+/// Error returned when parsing an unknown [`Activation`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseActivationError(String);
+
+impl Display for ParseActivationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown activation {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseActivationError {}
+
+/// A fully connected layer with sizes chosen at runtime.
+#[derive(Clone, Debug)]
+struct Dense {
+    inputs: usize,
+    outputs: usize,
+    // The weights of every output neuron, one row of `inputs` weights each.
+    weights: Vec<Scalar>,
+    // The biases of every output neuron, or none if the layer has no biases.
+    biases: Vec<Scalar>,
+    activation: Activation,
+    // The number of largest gradients to backpropagate, or all if `None`.
+    top_k: Option<usize>,
+    // The norm of the gradients over the parameters in the last training step.
+    grad_norm: Scalar,
+}
+
+// The gradient norm is the state of the last training step rather than part of the layer, so it
+// is not compared.
+impl PartialEq for Dense {
+    fn eq(&self, other: &Self) -> bool {
+        self.inputs == other.inputs
+            && self.outputs == other.outputs
+            && self.weights == other.weights
+            && self.biases == other.biases
+            && self.activation == other.activation
+            && self.top_k == other.top_k
+    }
+}
+
+/// A chain of fully connected layers with sizes chosen at runtime. See
+/// [module level documentation](self) for more info.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mlp {
+    layers: Vec<Dense>,
+}
+
+impl Mlp {
+    /// Creates a network with layers of the given `sizes`, from the number of inputs to the number
+    /// of outputs, all using `activation`, with parameters drawn uniformly from `-2.0..2.0` using
+    /// `seed`.
+    ///
+    /// # Panics
+    /// Panics if there are fewer than two sizes, or if any size is zero.
+    pub fn new(sizes: &[usize], activation: Activation, seed: u64) -> Self {
+        assert!(sizes.len() >= 2, "A network needs inputs and outputs.");
+        sizes[1..]
+            .iter()
+            .fold(Self::builder(sizes[0]).seed(seed), |builder, &size| {
+                builder.layer(size).activation(activation)
+            })
+            .build()
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Starts building a network with `inputs` inputs. See [`MlpBuilder`].
+    pub fn builder(inputs: usize) -> MlpBuilder {
+        MlpBuilder {
+            inputs,
+            seed: 0,
+            layers: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Returns the sizes of the layers, from the number of inputs to the number of outputs.
+    pub fn sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![self.layers[0].inputs];
+        sizes.extend(self.layers.iter().map(|layer| layer.outputs));
+        sizes
+    }
+
+    /// Returns the activation of every layer.
+    pub fn activations(&self) -> Vec<Activation> {
+        self.layers.iter().map(|layer| layer.activation).collect()
+    }
+
+    /// Returns the activation of all layers, or `None` if the layers use different activations.
+    pub fn activation(&self) -> Option<Activation> {
+        let first = self.layers[0].activation;
+        self.layers
+            .iter()
+            .all(|layer| layer.activation == first)
+            .then_some(first)
+    }
+
+    /// Returns whether every layer has biases.
+    pub fn biases(&self) -> Vec<bool> {
+        self.layers
+            .iter()
+            .map(|layer| !layer.biases.is_empty())
+            .collect()
+    }
+
+    /// Returns metadata named `name` for saving this network with [`model::save()`]. The sizes of
+    /// the layers are stored as the `layers` hyperparameter, the activation as `activation`, or
+    /// the activations of the layers as `activations` if they differ, and which layers have
+    /// biases as `biases` if not all of them do, such that the network can be loaded with
+    /// [`Self::load()`].
+    pub fn metadata(&self, name: impl Into<String>) -> Metadata {
+        let list = |items: Vec<String>| items.join(",");
+        let sizes = self.sizes().iter().map(usize::to_string).collect();
+        let mut metadata =
+            Metadata::describe(self, name).with_hyperparameter("layers", list(sizes));
+        metadata = match self.activation() {
+            Some(activation) => metadata.with_hyperparameter("activation", activation),
+            None => {
+                let activations = self.activations().iter().map(|a| a.to_string()).collect();
+                metadata.with_hyperparameter("activations", list(activations))
+            }
+        };
+        let biases = self.biases();
+        if biases.contains(&false) {
+            let biases = biases.iter().map(bool::to_string).collect();
+            metadata = metadata.with_hyperparameter("biases", list(biases));
+        }
+        metadata
+    }
+
+    /// Loads the network saved at `path` with metadata from [`Self::metadata()`], and returns it
+    /// with its metadata.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<(Self, Metadata)> {
+        let path = path.as_ref();
+        let metadata = model::peek_metadata(path)?;
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let hyperparameter = |key: &str| {
+            metadata.hyperparameters.get(key).ok_or_else(|| {
+                invalid(format!(
+                    "missing hyperparameter {key:?}, the model is not an MLP"
+                ))
+            })
+        };
+        let parse_error = |key: &str| invalid(format!("invalid hyperparameter {key:?}"));
+        let sizes = hyperparameter("layers")?
+            .split(',')
+            .map(|size| size.trim().parse())
+            .collect::<Result<Vec<usize>, _>>()
+            .map_err(|_| parse_error("layers"))?;
+        let num_layers = sizes.len().saturating_sub(1);
+        let activations: Vec<Activation> = match metadata.hyperparameters.get("activations") {
+            Some(activations) => activations
+                .split(',')
+                .map(|a| a.trim().parse())
+                .collect::<Result<_, _>>()
+                .map_err(|_| parse_error("activations"))?,
+            None => vec![
+                hyperparameter("activation")?
+                    .parse()
+                    .map_err(|_| parse_error("activation"))?;
+                num_layers
+            ],
+        };
+        let biases: Vec<bool> = match metadata.hyperparameters.get("biases") {
+            Some(biases) => biases
+                .split(',')
+                .map(|b| b.trim().parse())
+                .collect::<Result<_, _>>()
+                .map_err(|_| parse_error("biases"))?,
+            None => vec![true; num_layers],
+        };
+        if activations.len() != num_layers || biases.len() != num_layers {
+            return Err(invalid(
+                "hyperparameters describe different numbers of layers".into(),
+            ));
+        }
+        let mut builder = Self::builder(sizes.first().copied().unwrap_or(0));
+        for ((&size, activation), bias) in sizes.iter().skip(1).zip(activations).zip(biases) {
+            builder = builder.layer(size).activation(activation);
+            if !bias {
+                builder = builder.no_bias();
+            }
+        }
+        let mut mlp = builder.build().map_err(|e| invalid(e.to_string()))?;
+        let metadata = model::load(&mut mlp, path)?;
+        Ok((mlp, metadata))
+    }
+}
+
+/// How the parameters of a layer of an [`Mlp`] are initialized.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Init {
+    /// Weights and biases drawn uniformly from `-limit..limit`.
+    Uniform(Scalar),
+    /// Weights drawn uniformly from `±sqrt(6 / (inputs + outputs))` and zero biases, for layers
+    /// with logistic or tanh activations.
+    Xavier,
+    /// Weights drawn uniformly from `±sqrt(6 / inputs)` and zero biases, for layers with
+    /// (leaky) rectified linear activations.
+    He,
+}
+
+impl Default for Init {
+    /// Uniform from `-2.0..2.0`, like [`Mlp::new()`].
+    fn default() -> Self {
+        Self::Uniform(2.0)
+    }
+}
+
+// The configuration of a layer in an `MlpBuilder`.
+#[derive(Clone, Copy, Debug)]
+struct LayerConfig {
+    size: usize,
+    activation: Activation,
+    init: Init,
+    bias: bool,
+    top_k: Option<usize>,
+}
+
+/// Builds an [`Mlp`] layer by layer, started by [`Mlp::builder()`].
+///
+/// Every [`Self::layer()`] adds a layer, and the methods after it configure that layer. By
+/// default, a layer has the [`Activation::Logistic`] activation, [`Init::default()`]
+/// parameters and biases, and backpropagates all gradients. The parameters are drawn in order of
+/// the layers using [`Self::seed()`].
+#[derive(Clone, Debug)]
+pub struct MlpBuilder {
+    inputs: usize,
+    seed: u64,
+    layers: Vec<LayerConfig>,
+    // The first misuse of the builder, reported when building.
+    error: Option<BuildError>,
+}
+
+impl MlpBuilder {
+    /// Sets the seed of the initial parameters, zero by default.
+    pub fn seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+
+    /// Adds a layer with `size` outputs.
+    pub fn layer(mut self, size: usize) -> Self {
+        self.layers.push(LayerConfig {
+            size,
+            activation: Activation::Logistic,
+            init: Init::default(),
+            bias: true,
+            top_k: None,
+        });
+        self
+    }
+
+    /// Sets the activation of the last layer.
+    pub fn activation(self, activation: Activation) -> Self {
+        self.configure("activation", |layer| layer.activation = activation)
+    }
+
+    /// Sets how the parameters of the last layer are initialized.
+    pub fn initializer(self, init: Init) -> Self {
+        self.configure("initializer", |layer| layer.init = init)
+    }
+
+    /// Removes the biases of the last layer.
+    pub fn no_bias(self) -> Self {
+        self.configure("no_bias", |layer| layer.bias = false)
+    }
+
+    /// Backpropagates only the `k` gradients over the outputs of the last layer with the largest
+    /// magnitude, like [`Full::with_top_k()`](crate::Full::with_top_k). This is a training option,
+    /// which is not saved in model files.
+    pub fn top_k(self, k: usize) -> Self {
+        self.configure("top_k", |layer| layer.top_k = Some(k))
+    }
+
+    fn configure(mut self, option: &str, f: impl FnOnce(&mut LayerConfig)) -> Self {
+        match self.layers.last_mut() {
+            Some(layer) => f(layer),
+            None => {
+                self.error.get_or_insert_with(|| {
+                    BuildError(format!("{option} is set before the first layer"))
+                });
+            }
+        }
+        self
+    }
+
+    /// Builds the network, or returns an error if it has no inputs or layers, if a layer has no
+    /// outputs, or if a layer was configured before any layer was added.
+    pub fn build(self) -> Result<Mlp, BuildError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        if self.inputs == 0 {
+            return Err(BuildError("the network has no inputs".into()));
+        }
+        if self.layers.is_empty() {
+            return Err(BuildError("the network has no layers".into()));
+        }
+        if let Some(i) = self.layers.iter().position(|layer| layer.size == 0) {
+            return Err(BuildError(format!("layer {i} has no outputs")));
+        }
+        let mut rng = fastrand::Rng::with_seed(self.seed);
+        let mut inputs = self.inputs;
+        let layers = self
+            .layers
+            .iter()
+            .map(|layer| {
+                let outputs = layer.size;
+                let (limit, bias_limit) = match layer.init {
+                    Init::Uniform(limit) => (limit, limit),
+                    Init::Xavier => ((6.0 / (inputs + outputs) as Scalar).sqrt(), 0.0),
+                    Init::He => ((6.0 / inputs as Scalar).sqrt(), 0.0),
+                };
+                let mut random =
+                    |n, limit: Scalar| (0..n).map(|_| (rng.f32() * 2.0 - 1.0) * limit).collect();
+                let dense = Dense {
+                    inputs,
+                    outputs,
+                    weights: random(inputs * outputs, limit),
+                    biases: if layer.bias {
+                        random(outputs, bias_limit)
+                    } else {
+                        Vec::new()
+                    },
+                    activation: layer.activation,
+                    top_k: layer.top_k,
+                    grad_norm: 0.0,
+                };
+                inputs = outputs;
+                dense
+            })
+            .collect();
+        Ok(Mlp { layers })
+    }
+}
+
+/// Error returned by [`MlpBuilder::build()`] for an invalid network.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BuildError(String);
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// The [`Intermediate`] of an [`Mlp`]: the weighted sums and outputs of every layer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MlpInter {
+    /// The weighted sums of every layer, before the activation function.
+    pub weighted_sums: Vec<Vec<Scalar>>,
+    /// The outputs of every layer.
+    pub outputs: Vec<Vec<Scalar>>,
+}
+
+impl Intermediate for MlpInter {
+    type Out = Vec<Scalar>;
+
+    fn output(&self) -> &Self::Out {
+        self.outputs.last().unwrap()
+    }
+
+    fn into_output(mut self) -> Self::Out {
+        self.outputs.pop().unwrap()
+    }
+}
+
+impl Network for Mlp {
+    type In = Vec<Scalar>;
+
+    type Out = Vec<Scalar>;
+
+    type Inter = MlpInter;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        let mut inter = MlpInter {
+            weighted_sums: Vec::with_capacity(self.layers.len()),
+            outputs: Vec::with_capacity(self.layers.len()),
+        };
+        self.intermediate_into(inputs, &mut inter);
+        inter
+    }
+
+    // The weighted sums and outputs of every layer are written to the buffers of `inter`, such
+    // that evaluating into the intermediate calculations of a previous evaluation does not
+    // allocate.
+    fn intermediate_into(&self, inputs: &Self::In, inter: &mut Self::Inter) {
+        inter.weighted_sums.resize_with(self.layers.len(), Vec::new);
+        inter.outputs.resize_with(self.layers.len(), Vec::new);
+        for (i, layer) in self.layers.iter().enumerate() {
+            let (previous, rest) = inter.outputs.split_at_mut(i);
+            let inputs = previous.last().unwrap_or(inputs);
+            assert_eq!(inputs.len(), layer.inputs, "Wrong number of inputs.");
+            let sums = &mut inter.weighted_sums[i];
+            sums.clear();
+            sums.extend(
+                layer
+                    .weights
+                    .chunks(layer.inputs)
+                    .map(|row| row.iter().zip(inputs).map(|(w, x)| w * x).sum::<Scalar>()),
+            );
+            for (sum, bias) in sums.iter_mut().zip(&layer.biases) {
+                *sum += bias;
+            }
+            let outputs = &mut rest[0];
+            outputs.clear();
+            outputs.extend(sums.iter().map(|sum| layer.activation.call(sum)));
+        }
+    }
+
+    fn train_deriv(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        let mut gradients = gradients.clone();
+        // The indices of the gradients of a layer, reused by the layers keeping the top k.
+        let mut order = Vec::new();
+        for (i, layer) in self.layers.iter_mut().enumerate().rev() {
+            let inputs = match i {
+                0 => inputs,
+                _ => &intermediate.outputs[i - 1],
+            };
+            // The gradients over the weighted sums.
+            let mut grad = Elementwise::new(layer.activation)
+                .backprop(&intermediate.weighted_sums[i], &gradients);
+            if let Some(k) = layer.top_k {
+                order.resize(grad.len(), 0);
+                keep_top_k(&mut grad, k, &mut order);
+            }
+            layer.grad_norm = if layer.biases.is_empty() {
+                (squared_norm(&grad) * squared_norm(inputs)).sqrt()
+            } else {
+                param_grad_norm(&grad, inputs)
+            };
+            gradients = vec![0.0; layer.inputs];
+            for (row, grad) in layer.weights.chunks_mut(layer.inputs).zip(&grad) {
+                for ((w, x), g) in row.iter_mut().zip(inputs).zip(gradients.iter_mut()) {
+                    *g += *w * grad;
+                    *w -= x * grad * learning_rate;
+                }
+            }
+            for (bias, grad) in layer.biases.iter_mut().zip(&grad) {
+                *bias -= grad * learning_rate;
+            }
+        }
+        gradients
+    }
+}
+
+impl Inspect for Mlp {
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        for (layer, activations) in self.layers.iter().zip(&intermediate.outputs) {
+            f(&LayerView {
+                kind: &format!("Full ({})", layer.activation),
+                num_inputs: layer.inputs,
+                params: &[&layer.weights, &layer.biases],
+                activations,
+                gradient_norm: layer.grad_norm,
+            });
+        }
+    }
+}
+
+impl Parameterized for Mlp {
+    fn num_params(&self) -> usize {
+        self.layers
+            .iter()
+            .map(|layer| layer.weights.len() + layer.biases.len())
+            .sum()
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        let mut params = params.iter_mut();
+        for layer in &self.layers {
+            for (value, param) in layer
+                .weights
+                .iter()
+                .chain(&layer.biases)
+                .zip(params.by_ref())
+            {
+                *param = *value;
+            }
+        }
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        let mut params = params.iter();
+        for layer in &mut self.layers {
+            for (value, param) in layer
+                .weights
+                .iter_mut()
+                .chain(&mut layer.biases)
+                .zip(params.by_ref())
+            {
+                *value = *param;
+            }
+        }
+    }
+}
+
+/// An [`Mlp`] ending in the sum of squared differences between its outputs and the expected
+/// outputs, to train it with [`crate::train`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SquareLoss {
+    /// The network.
+    pub mlp: Mlp,
+    /// The expected outputs.
+    pub expected: Vec<Scalar>,
+}
+
+impl SquareLoss {
+    /// Wraps `mlp`, expecting zeros.
+    pub fn new(mlp: Mlp) -> Self {
+        let expected = vec![0.0; *mlp.sizes().last().unwrap()];
+        Self { mlp, expected }
+    }
+}
+
+/// The [`Intermediate`] of a [`SquareLoss`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SquareLossInter {
+    /// The intermediate of the network.
+    pub mlp: MlpInter,
+    /// The error.
+    pub error: [Scalar; 1],
+}
+
+impl Intermediate for SquareLossInter {
+    type Out = [Scalar; 1];
+
+    fn output(&self) -> &Self::Out {
+        &self.error
+    }
+
+    fn into_output(self) -> Self::Out {
+        self.error
+    }
+}
+
+impl Network for SquareLoss {
+    type In = Vec<Scalar>;
+
+    type Out = [Scalar; 1];
+
+    type Inter = SquareLossInter;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        let inter = self.mlp.intermediate(inputs);
+        let error = inter
+            .output()
+            .iter()
+            .zip(&self.expected)
+            .map(|(o, e)| (o - e) * (o - e))
+            .sum();
+        SquareLossInter {
+            mlp: inter,
+            error: [error],
+        }
+    }
+
+    fn train_deriv(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        let inter = &intermediate.mlp;
+        let gradients = inter
+            .output()
+            .iter()
+            .zip(&self.expected)
+            .map(|(o, e)| 2.0 * (o - e) * gradients[0])
+            .collect();
+        self.mlp
+            .train_deriv(inputs, inter, &gradients, learning_rate)
+    }
+}
+
+impl Supervised for SquareLoss {
+    type Target = Vec<Scalar>;
+
+    fn set_target(&mut self, target: &Self::Target) {
+        self.expected.clone_from(target);
+    }
+}
+
+impl Inspect for SquareLoss {
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        self.mlp.visit_layers(&intermediate.mlp, f);
+        f(&LayerView {
+            kind: "SquareError",
+            num_inputs: self.expected.len(),
+            params: &[],
+            activations: &intermediate.error,
+            gradient_norm: 0.0,
+        });
+    }
+}
+
+impl Parameterized for SquareLoss {
+    fn num_params(&self) -> usize {
+        self.mlp.num_params()
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        self.mlp.write_params(params);
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        self.mlp.read_params(params);
+    }
+}
+
+/// The metrics of a network on a dataset, see [`SquareLoss::metrics()`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Metrics {
+    /// The mean error.
+    pub error: Scalar,
+    /// The fraction of samples of which every output rounds to its target, which is meaningful
+    /// for classification with targets of `0.0` and `1.0`.
+    pub accuracy: Scalar,
+}
+
+impl SquareLoss {
+    /// Returns the metrics of the network on `dataset`, which are zero if it is empty.
+    pub fn metrics(&mut self, dataset: &[(Vec<Scalar>, Vec<Scalar>)]) -> Metrics {
+        let correct = dataset
+            .iter()
+            .filter(|(inputs, targets)| {
+                self.mlp
+                    .eval(inputs)
+                    .iter()
+                    .zip(targets)
+                    .all(|(o, t)| o.round() == t.round())
+            })
+            .count();
+        Metrics {
+            error: train::mean_error(self, dataset),
+            accuracy: train::mean(correct as Scalar, dataset.len()),
+        }
+    }
+}
@@ -0,0 +1,294 @@
+/*!
+Saving and loading trained models, with metadata.
+
+A model file holds the parameters of a [`Parameterized`] network together with its
+[`Metadata`]: the version of RANN that saved it, a description of the architecture, a name and
+comment, the hyperparameters it was trained with and the history of its metrics. The metadata
+can be read without loading the parameters using [`peek_metadata()`], such that shared models can
+be identified cheaply. Model files use the extension `.rann`; they can not be memory-mapped, see
+[`mapped`](crate::mapped) for files that can.
+
+# Format
+Model files start with the magic bytes `RANNMODL` and a little-endian `u32` format version, followed
+by the length of the metadata as a `u64` and the metadata itself. The metadata is UTF-8 text with
+one `key=value` entry per line, where newlines, backslashes and (in keys) equals signs are escaped
+with a backslash. Hyperparameters are stored under keys starting with `hyper.`, and metrics under
+keys starting with `metric.` with comma separated values. Finally, the parameters are stored as a
+`u64` length followed by that many `f32`s.
+
+# Examples
+```rust
+use rann_base::{activ::Logistic, model::{self, Metadata}, testing, Full};
+use rann_traits::params::Parameterized;
+
+let net = Full::<2, 1, _>::new(Logistic, testing::seeded_gen(1));
+let metadata = Metadata::describe(&net, "xor")
+    .with_comment("A single layer can not learn XOR.")
+    .with_hyperparameter("learning_rate", 0.1);
+
+let mut file = Vec::new();
+model::write_to(&net, &metadata, &mut file).unwrap();
+
+let peeked = model::read_metadata(&file[..]).unwrap();
+assert_eq!(peeked.name, "xor");
+assert_eq!(peeked.hyperparameters["learning_rate"], "0.1");
+
+let mut loaded = Full::<2, 1, _>::new(Logistic, (|_, _| 0.0, |_| 0.0));
+model::read_from(&mut loaded, &file[..]).unwrap();
+assert_eq!(loaded.params(), net.params());
+```
+*/
+
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use rann_traits::{params::Parameterized, Scalar};
+
+use crate::{
+    checkpoint::{read_scalars, write_scalars},
+    train::{Fit, Trainer},
+};
+
+pub(crate) const MAGIC: &[u8; 8] = b"RANNMODL";
+/// The version of the model format written by this version of RANN.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The metadata of a saved model. See [module level documentation](self) for more info.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metadata {
+    /// The version of RANN that saved the model.
+    pub rann_version: String,
+    /// A description of the architecture of the network, such as its type.
+    pub architecture: String,
+    /// The number of parameters of the network.
+    pub num_params: usize,
+    /// The name of the model.
+    pub name: String,
+    /// A free form comment.
+    pub comment: String,
+    /// The hyperparameters the model was trained with.
+    pub hyperparameters: BTreeMap<String, String>,
+    /// The history of metrics during training, such as the error after every epoch.
+    pub metrics: BTreeMap<String, Vec<Scalar>>,
+}
+
+impl Metadata {
+    /// Creates metadata named `name` for `net`, describing its architecture by its type.
+    pub fn describe<N: Parameterized>(net: &N, name: impl Into<String>) -> Self {
+        Self {
+            rann_version: env!("CARGO_PKG_VERSION").to_string(),
+            architecture: std::any::type_name::<N>().to_string(),
+            num_params: net.num_params(),
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the comment.
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = comment.into();
+        self
+    }
+
+    /// Adds a hyperparameter.
+    pub fn with_hyperparameter(mut self, key: impl Into<String>, value: impl Display) -> Self {
+        self.hyperparameters.insert(key.into(), value.to_string());
+        self
+    }
+
+    /// Adds the history of a metric.
+    pub fn with_metric(mut self, key: impl Into<String>, history: Vec<Scalar>) -> Self {
+        self.metrics.insert(key.into(), history);
+        self
+    }
+
+    /// Adds the hyperparameters of `trainer`, and the error history of `fit` as the `error`
+    /// metric.
+    pub fn with_training(self, trainer: &Trainer, fit: &Fit) -> Self {
+        self.with_hyperparameter("epochs", trainer.epochs)
+            .with_hyperparameter("learning_rate", trainer.learning_rate)
+            .with_metric("error", fit.errors.clone())
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = String::new();
+        let mut entry = |key: &str, value: &str| {
+            text.push_str(&escape(key).replace('=', "\\="));
+            text.push('=');
+            text.push_str(&escape(value));
+            text.push('\n');
+        };
+        entry("rann_version", &self.rann_version);
+        entry("architecture", &self.architecture);
+        entry("num_params", &self.num_params.to_string());
+        entry("name", &self.name);
+        entry("comment", &self.comment);
+        for (key, value) in &self.hyperparameters {
+            entry(&format!("hyper.{key}"), value);
+        }
+        for (key, history) in &self.metrics {
+            let values: Vec<_> = history.iter().map(Scalar::to_string).collect();
+            entry(&format!("metric.{key}"), &values.join(","));
+        }
+        text
+    }
+
+    fn from_text(text: &str) -> io::Result<Self> {
+        let mut metadata = Self::default();
+        for line in text.lines() {
+            let (key, value) = split_entry(line)
+                .ok_or_else(|| invalid(format!("invalid metadata entry `{line}`")))?;
+            let (key, value) = (unescape(key), unescape(value));
+            let key = key.as_str();
+            if let Some(key) = key.strip_prefix("hyper.") {
+                metadata.hyperparameters.insert(key.to_string(), value);
+            } else if let Some(key) = key.strip_prefix("metric.") {
+                let history = value
+                    .split(',')
+                    .filter(|v| !v.is_empty())
+                    .map(str::parse)
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| invalid(format!("invalid metric `{key}`")))?;
+                metadata.metrics.insert(key.to_string(), history);
+            } else {
+                match key {
+                    "rann_version" => metadata.rann_version = value,
+                    "architecture" => metadata.architecture = value,
+                    "num_params" => {
+                        metadata.num_params = value
+                            .parse()
+                            .map_err(|_| invalid("invalid number of parameters"))?
+                    }
+                    "name" => metadata.name = value,
+                    "comment" => metadata.comment = value,
+                    // Ignore entries of later versions.
+                    _ => {}
+                }
+            }
+        }
+        Ok(metadata)
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+// Splits an entry at the first unescaped `=`.
+fn split_entry(line: &str) -> Option<(&str, &str)> {
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '=' if !escaped => return Some((&line[..i], &line[i + 1..])),
+            '\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+/// Writes the parameters of `net` and `metadata` to `writer`.
+pub fn write_to<N: Parameterized>(
+    net: &N,
+    metadata: &Metadata,
+    mut writer: impl Write,
+) -> io::Result<()> {
+    let text = metadata.to_text();
+    writer.write_all(MAGIC)?;
+    writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+    writer.write_all(&(text.len() as u64).to_le_bytes())?;
+    writer.write_all(text.as_bytes())?;
+    write_scalars(&mut writer, &net.params())
+}
+
+/// Reads the metadata of a model from `reader`, leaving the reader at the parameters.
+///
+/// # Errors
+/// Returns an error of kind [`io::ErrorKind::InvalidData`] if the data is not a model or has an
+/// unsupported version.
+pub fn read_metadata(mut reader: impl Read) -> io::Result<Metadata> {
+    let mut header = [0; 12];
+    reader.read_exact(&mut header)?;
+    if &header[..8] != MAGIC {
+        return Err(invalid("not a model"));
+    }
+    let version = u32::from_le_bytes(header[8..].try_into().expect("Length should be 4."));
+    if version > FORMAT_VERSION {
+        return Err(invalid(format!("unsupported model version {version}")));
+    }
+    let mut len = [0; 8];
+    reader.read_exact(&mut len)?;
+    let len = u64::from_le_bytes(len);
+    let mut text = String::new();
+    reader.by_ref().take(len).read_to_string(&mut text)?;
+    if text.len() as u64 != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Metadata::from_text(&text)
+}
+
+/// Reads a model from `reader` into `net`, and returns its metadata.
+///
+/// # Errors
+/// Returns an error of kind [`io::ErrorKind::InvalidData`] if the data is not a model, has an
+/// unsupported version, or does not have as many parameters as `net`.
+pub fn read_from<N: Parameterized>(net: &mut N, mut reader: impl Read) -> io::Result<Metadata> {
+    let metadata = read_metadata(&mut reader)?;
+    let params = read_scalars(&mut reader)?;
+    if params.len() != net.num_params() {
+        return Err(invalid(format!(
+            "model has {} parameters, but the network has {}",
+            params.len(),
+            net.num_params()
+        )));
+    }
+    net.read_params(&params);
+    Ok(metadata)
+}
+
+/// Saves the parameters of `net` and `metadata` to the file at `path`.
+pub fn save<N: Parameterized>(
+    net: &N,
+    metadata: &Metadata,
+    path: impl AsRef<Path>,
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_to(net, metadata, &mut writer)?;
+    writer.flush()
+}
+
+/// Loads the model at `path` into `net`, and returns its metadata. See [`read_from()`].
+pub fn load<N: Parameterized>(net: &mut N, path: impl AsRef<Path>) -> io::Result<Metadata> {
+    read_from(net, BufReader::new(File::open(path)?))
+}
+
+/// Reads the metadata of the model at `path`, without reading its parameters.
+pub fn peek_metadata(path: impl AsRef<Path>) -> io::Result<Metadata> {
+    read_metadata(BufReader::new(File::open(path)?))
+}
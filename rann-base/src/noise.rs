@@ -0,0 +1,167 @@
+/*!
+Gradient noise injection.
+
+A [`GradientNoise`] network adds annealed Gaussian noise to the gradients of the parameters of
+the wrapped network in every training step, as proposed by Neelakantan et al. (2015). The noise
+helps small networks escape poor local minima early in training, and fades out over time: at
+training step `t`, its variance is `eta / (1 + t)^gamma`.
+
+As the parameters are updated by the wrapped network itself, the noise is applied to the
+parameters after each training step, scaled by the learning rate, which is equivalent to adding
+it to the gradients. The gradients passed to preceding networks are not affected.
+
+# Examples
+```rust
+use rann_base::{activ::Logistic, error::SquareError, noise::GradientNoise, testing, train, Full};
+use rann_traits::Network;
+
+let net = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1))
+    .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)));
+let mut net = GradientNoise::new(net, 0.01, 7).chain(SquareError { expected: [0.0] });
+for _ in 0..100 {
+    train::train_epoch(&mut net, &testing::XOR, 0.5);
+}
+```
+*/
+
+use std::any::Any;
+
+use fastrand::Rng;
+use rann_traits::{
+    inspect::{DotGraph, Inspect, LayerView},
+    params::Parameterized,
+    Network, Scalar, Supervised,
+};
+
+use crate::gen::gaussian;
+
+/// A network whose parameter gradients are perturbed by annealed Gaussian noise. See
+/// [module level documentation](self) for more info.
+#[derive(Clone, Debug)]
+pub struct GradientNoise<T> {
+    /// The noisily trained network.
+    pub net: T,
+    /// The variance of the noise in the first training step.
+    pub eta: Scalar,
+    /// How quickly the variance decays with the number of training steps.
+    pub gamma: Scalar,
+    step: usize,
+    rng: Rng,
+}
+
+impl<T> GradientNoise<T> {
+    /// Wraps `net`, adding noise with an initial variance of `eta` that decays with the
+    /// recommended `gamma` of `0.55`. The noise is seeded by `seed`.
+    pub fn new(net: T, eta: Scalar, seed: u64) -> Self {
+        Self {
+            net,
+            eta,
+            gamma: 0.55,
+            step: 0,
+            rng: Rng::with_seed(seed),
+        }
+    }
+
+    /// Returns the number of training steps taken.
+    pub fn steps(&self) -> usize {
+        self.step
+    }
+
+    /// Returns the standard deviation of the noise of the next training step.
+    pub fn std_dev(&self) -> Scalar {
+        (self.eta / (1.0 + self.step as Scalar).powf(self.gamma)).sqrt()
+    }
+}
+
+impl<T> Network for GradientNoise<T>
+where
+    T: Network + Parameterized,
+{
+    type In = T::In;
+
+    type Out = T::Out;
+
+    type Inter = T::Inter;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        self.net.intermediate(inputs)
+    }
+
+    fn train_deriv(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        let gradients = self
+            .net
+            .train_deriv(inputs, intermediate, gradients, learning_rate);
+        let scale = learning_rate * self.std_dev();
+        let mut params = self.net.params();
+        for param in &mut params {
+            *param -= scale * gaussian(&mut self.rng);
+        }
+        self.net.read_params(&params);
+        self.step += 1;
+        gradients
+    }
+
+    fn eval(&self, inputs: &Self::In) -> Self::Out {
+        self.net.eval(inputs)
+    }
+
+    fn find_layer(&self, name: &str) -> Option<&dyn Any> {
+        self.net.find_layer(name)
+    }
+
+    fn find_layer_mut(&mut self, name: &str) -> Option<&mut dyn Any> {
+        self.net.find_layer_mut(name)
+    }
+}
+
+impl<T> Supervised for GradientNoise<T>
+where
+    T: Supervised + Parameterized,
+{
+    type Target = T::Target;
+
+    fn set_target(&mut self, target: &Self::Target) {
+        self.net.set_target(target);
+    }
+}
+
+impl<T> Inspect for GradientNoise<T>
+where
+    T: Inspect + Parameterized,
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        self.net.visit_layers(intermediate, f);
+    }
+
+    fn write_dot(
+        &self,
+        intermediate: &Self::Inter,
+        dot: &mut DotGraph,
+        inputs: Vec<usize>,
+    ) -> Vec<usize> {
+        self.net.write_dot(intermediate, dot, inputs)
+    }
+}
+
+impl<T> Parameterized for GradientNoise<T>
+where
+    T: Parameterized,
+{
+    fn num_params(&self) -> usize {
+        self.net.num_params()
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        self.net.write_params(params);
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        self.net.read_params(params);
+    }
+}
@@ -0,0 +1,171 @@
+use arrayvec::ArrayVec;
+use rann_traits::{
+    inspect::{Inspect, LayerView},
+    params::Parameterized,
+    Intermediate, Network, Scalar,
+};
+
+/// Layer normalization over the `N` features of a single sample, followed by a learnable scale
+/// and shift.
+///
+/// Unlike batch normalization, the statistics are computed over the features of one input, so
+/// this layer can be trained one sample at a time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayerNorm<const N: usize> {
+    /// The learnable scale (gamma) applied to each normalized feature.
+    pub scale: [Scalar; N],
+    /// The learnable shift (beta) added to each scaled feature.
+    pub shift: [Scalar; N],
+    /// Small constant added to the variance to avoid dividing by zero.
+    pub epsilon: Scalar,
+    // The norm of the gradients over the parameters in the last training step.
+    grad_norm: Scalar,
+}
+
+impl<const N: usize> LayerNorm<N> {
+    /// The default value of [`LayerNorm::epsilon`].
+    pub const EPSILON: Scalar = 1e-5;
+
+    /// Creates a layer normalization with a scale of one and a shift of zero, such that it
+    /// initially only normalizes its inputs.
+    pub fn new() -> Self {
+        Self {
+            scale: [1.0; N],
+            shift: [0.0; N],
+            epsilon: Self::EPSILON,
+            grad_norm: 0.0,
+        }
+    }
+}
+
+impl<const N: usize> Default for LayerNorm<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Network for LayerNorm<N> {
+    type In = [Scalar; N];
+
+    type Out = [Scalar; N];
+
+    type Inter = LayerNormInter<N>;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        let n = N as Scalar;
+        let mean = inputs.iter().sum::<Scalar>() / n;
+        let var = inputs
+            .iter()
+            .map(|x| (x - mean) * (x - mean))
+            .sum::<Scalar>()
+            / n;
+        let inv_std = 1.0 / (var + self.epsilon).sqrt();
+        // Normalize the inputs...
+        let normalized = inputs.map(|x| (x - mean) * inv_std);
+        // ...and scale and shift them.
+        let mut outputs = normalized;
+        for ((out, scale), shift) in outputs.iter_mut().zip(self.scale).zip(self.shift) {
+            *out = *out * scale + shift;
+        }
+        LayerNormInter {
+            normalized,
+            inv_std,
+            outputs,
+        }
+    }
+
+    fn train_deriv(
+        &mut self,
+        _inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        let n = N as Scalar;
+        let x_hat = &intermediate.normalized;
+        // Calculate the gradients over the normalized values, using the scale before updating it.
+        let grad: ArrayVec<Scalar, N> = gradients
+            .iter()
+            .zip(self.scale)
+            .map(|(gr, scale)| gr * scale)
+            .collect();
+        let grad_sum: Scalar = grad.iter().sum();
+        let grad_dot: Scalar = grad.iter().zip(x_hat).map(|(g, x)| g * x).sum();
+        self.grad_norm = gradients
+            .iter()
+            .zip(x_hat)
+            .map(|(g, x)| g * g * (x * x + 1.0))
+            .sum::<Scalar>()
+            .sqrt();
+        // Update the scale and shift.
+        for (((scale, shift), gr), x) in self
+            .scale
+            .iter_mut()
+            .zip(self.shift.iter_mut())
+            .zip(gradients)
+            .zip(x_hat)
+        {
+            *scale -= gr * x * learning_rate;
+            *shift -= gr * learning_rate;
+        }
+        // Backpropagate through the normalization.
+        let out: ArrayVec<Scalar, N> = grad
+            .iter()
+            .zip(x_hat)
+            .map(|(g, x)| intermediate.inv_std / n * (n * g - grad_sum - x * grad_dot))
+            .collect();
+        out.into_inner()
+            .expect("Capacity of ArrayVec should equal N.")
+    }
+}
+
+impl<const N: usize> Inspect for LayerNorm<N> {
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        f(&LayerView {
+            kind: "LayerNorm",
+            num_inputs: N,
+            params: &[&self.scale, &self.shift],
+            activations: intermediate.output(),
+            gradient_norm: self.grad_norm,
+        });
+    }
+}
+
+// The scale is followed by the shift.
+impl<const N: usize> Parameterized for LayerNorm<N> {
+    fn num_params(&self) -> usize {
+        2 * N
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        let (scale, shift) = params.split_at_mut(N);
+        scale.copy_from_slice(&self.scale);
+        shift.copy_from_slice(&self.shift);
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        let (scale, shift) = params.split_at(N);
+        self.scale.copy_from_slice(scale);
+        self.shift.copy_from_slice(shift);
+    }
+}
+
+/// The intermediate calculations for an evaluation of [`LayerNorm`].
+#[derive(Clone, Debug)]
+pub struct LayerNormInter<const N: usize> {
+    normalized: [Scalar; N],
+    inv_std: Scalar,
+    outputs: [Scalar; N],
+}
+
+impl<const N: usize> Intermediate for LayerNormInter<N> {
+    type Out = [Scalar; N];
+
+    fn output(&self) -> &Self::Out {
+        &self.outputs
+    }
+
+    fn into_output(self) -> Self::Out {
+        self.outputs
+    }
+}
@@ -0,0 +1,505 @@
+/*!
+Reading and writing parameters in the NumPy `.npy` and `.npz` formats, to exchange them with
+models trained in Python and with analysis notebooks.
+
+An [`Array`] is an n-dimensional array of floats in row-major order, which is stored in a `.npy`
+file, as written by `numpy.save` and read by `numpy.load`. An [`Npz`] is a collection of named
+arrays, which is stored in a `.npz` archive, as written by `numpy.savez`. Arrays of 32 and 64 bit
+floats of either byte order and in either memory order can be read, and are converted to
+[`Scalar`]s. Compressed archives, written by `numpy.savez_compressed`, are not supported.
+
+[`Full::write_npz()`](crate::Full::write_npz) and [`Full::read_npz()`](crate::Full::read_npz)
+store the parameters of a layer as the arrays `weight` and `bias`, with the same shapes as a
+PyTorch `Linear` layer. Prefixing the names with the index of every layer, such as `0.weight`,
+matches the `state_dict` of a PyTorch `Sequential` model.
+
+# Examples
+```rust
+use rann_base::{activ::Logistic, npy::Npz, testing, Full};
+use rann_traits::Network;
+
+let net = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1))
+    .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)));
+let mut npz = Npz::new();
+net.first.write_npz(&mut npz, "0.");
+net.second.write_npz(&mut npz, "1.");
+assert_eq!(npz.get("0.weight").unwrap().shape, [3, 2]);
+assert_eq!(npz.get("1.bias").unwrap().shape, [1]);
+
+let mut bytes = Vec::new();
+npz.write_to(&mut bytes)?;
+let npz = Npz::read_from(&bytes[..])?;
+let mut copy = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(3))
+    .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(4)));
+copy.first.read_npz(&npz, "0.")?;
+copy.second.read_npz(&npz, "1.")?;
+assert_eq!(copy.eval(&[1.0, 0.0]), net.eval(&[1.0, 0.0]));
+# Ok::<(), std::io::Error>(())
+```
+
+# Format
+`.npy` files are written in version 1.0 of the format, as little-endian 32 bit floats in
+row-major order. `.npz` archives are zip archives without compression, holding a `.npy` file for
+every array, named after the array.
+*/
+
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use rann_traits::Scalar;
+
+use crate::{
+    boundary::png::crc32,
+    checkpoint::{invalid, read_array},
+};
+
+const MAGIC: &[u8; 6] = b"\x93NUMPY";
+// The total length of the header of `.npy` files is padded to a multiple of this.
+const ALIGNMENT: usize = 64;
+
+/// An n-dimensional array of scalars, as stored in a `.npy` file. See
+/// [module level documentation](self) for more info.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Array {
+    /// The length of every dimension. A matrix has the shape `[rows, columns]`.
+    pub shape: Vec<usize>,
+    /// The values in row-major order, in which the last index changes fastest.
+    pub data: Vec<Scalar>,
+}
+
+impl Array {
+    /// Creates an array with `shape` holding `data` in row-major order.
+    ///
+    /// # Panics
+    /// Panics if the length of `data` is not the product of `shape`.
+    pub fn new(shape: Vec<usize>, data: Vec<Scalar>) -> Self {
+        assert_eq!(
+            shape.iter().product::<usize>(),
+            data.len(),
+            "The length of the data should be the product of the shape."
+        );
+        Self { shape, data }
+    }
+
+    /// Writes the array to `writer` as a `.npy` file.
+    pub fn write_npy(&self, mut writer: impl Write) -> io::Result<()> {
+        let shape = match self.shape.as_slice() {
+            [len] => format!("({len},)"),
+            shape => {
+                let lens: Vec<_> = shape.iter().map(usize::to_string).collect();
+                format!("({})", lens.join(", "))
+            }
+        };
+        let mut header = format!("{{'descr': '<f4', 'fortran_order': False, 'shape': {shape}, }}");
+        // The magic bytes, the version and the length of the header, and a final newline.
+        let fixed = MAGIC.len() + 2 + 2 + 1;
+        let padded = (fixed + header.len()).next_multiple_of(ALIGNMENT);
+        header.extend(std::iter::repeat_n(' ', padded - fixed - header.len()));
+        header.push('\n');
+
+        writer.write_all(MAGIC)?;
+        match u16::try_from(header.len()) {
+            Ok(len) => {
+                writer.write_all(&[1, 0])?;
+                writer.write_all(&len.to_le_bytes())?;
+            }
+            Err(_) => {
+                // Version 2.0 only differs in the length of the header.
+                writer.write_all(&[2, 0])?;
+                writer.write_all(&(header.len() as u32).to_le_bytes())?;
+            }
+        }
+        writer.write_all(header.as_bytes())?;
+        for value in &self.data {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Reads an array from a `.npy` file in `reader`.
+    ///
+    /// # Errors
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if the data is not a `.npy` file,
+    /// or does not hold 32 or 64 bit floats.
+    pub fn read_npy(mut reader: impl Read) -> io::Result<Self> {
+        let magic: [u8; 6] = read_array(&mut reader)?;
+        if &magic != MAGIC {
+            return Err(invalid("not a .npy file"));
+        }
+        let [major, _minor] = read_array(&mut reader)?;
+        let len = match major {
+            1 => u16::from_le_bytes(read_array(&mut reader)?) as usize,
+            2 | 3 => u32::from_le_bytes(read_array(&mut reader)?) as usize,
+            _ => return Err(invalid(format!("unsupported .npy version {major}"))),
+        };
+        let mut header = Vec::new();
+        (&mut reader).take(len as u64).read_to_end(&mut header)?;
+        if header.len() != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let header = String::from_utf8(header).map_err(|_| invalid("header is not UTF-8"))?;
+
+        let descr = field(&header, "descr")?
+            .strip_prefix('\'')
+            .and_then(|rest| rest.split('\'').next())
+            .ok_or_else(|| invalid("invalid descr in header"))?;
+        let (little_endian, size) = match descr {
+            "<f4" => (true, 4),
+            ">f4" => (false, 4),
+            "<f8" => (true, 8),
+            ">f8" => (false, 8),
+            _ => return Err(invalid(format!("unsupported dtype {descr}"))),
+        };
+        let fortran_order = field(&header, "fortran_order")?.starts_with("True");
+        let shape = field(&header, "shape")?
+            .strip_prefix('(')
+            .and_then(|rest| rest.split(')').next())
+            .ok_or_else(|| invalid("invalid shape in header"))?
+            .split(',')
+            .map(str::trim)
+            .filter(|len| !len.is_empty())
+            .map(|len| len.parse().map_err(|_| invalid("invalid shape in header")))
+            .collect::<io::Result<Vec<usize>>>()?;
+        let num_values = shape
+            .iter()
+            .try_fold(1usize, |n, &len| n.checked_mul(len))
+            .ok_or_else(|| invalid("array is too large"))?;
+
+        let mut bytes = Vec::new();
+        // Limit the read to the data actually present, instead of trusting the shape.
+        (&mut reader)
+            .take(num_values as u64 * size as u64)
+            .read_to_end(&mut bytes)?;
+        if bytes.len() != num_values * size {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let data = bytes
+            .chunks_exact(size)
+            .map(|b| match (size, little_endian) {
+                (4, true) => f32::from_le_bytes(b.try_into().expect("Chunks should fit.")),
+                (4, false) => f32::from_be_bytes(b.try_into().expect("Chunks should fit.")),
+                (_, true) => f64::from_le_bytes(b.try_into().expect("Chunks should fit.")) as f32,
+                (_, false) => f64::from_be_bytes(b.try_into().expect("Chunks should fit.")) as f32,
+            })
+            .collect();
+        let data = if fortran_order {
+            to_row_major(&shape, data)
+        } else {
+            data
+        };
+        Ok(Self { shape, data })
+    }
+
+    /// Writes the array to a `.npy` file at `path`, replacing it if it exists.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_npy(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Reads an array from the `.npy` file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read_npy(BufReader::new(File::open(path)?))
+    }
+}
+
+// Returns the value of `key` in the dictionary of a `.npy` header, followed by the rest of it.
+fn field<'a>(header: &'a str, key: &str) -> io::Result<&'a str> {
+    let start = header
+        .find(&format!("'{key}'"))
+        .ok_or_else(|| invalid(format!("missing {key} in header")))?;
+    let rest = header[start + key.len() + 2..].trim_start();
+    rest.strip_prefix(':')
+        .map(str::trim_start)
+        .ok_or_else(|| invalid(format!("invalid {key} in header")))
+}
+
+// Reorders `data` of an array with `shape` from column-major to row-major order.
+fn to_row_major(shape: &[usize], data: Vec<Scalar>) -> Vec<Scalar> {
+    let strides: Vec<usize> = shape
+        .iter()
+        .scan(1, |stride, &len| {
+            let current = *stride;
+            *stride *= len;
+            Some(current)
+        })
+        .collect();
+    let mut index = vec![0; shape.len()];
+    let mut out = Vec::with_capacity(data.len());
+    for _ in 0..data.len() {
+        out.push(
+            data[index
+                .iter()
+                .zip(&strides)
+                .map(|(i, s)| i * s)
+                .sum::<usize>()],
+        );
+        for (i, &len) in index.iter_mut().zip(shape).rev() {
+            *i += 1;
+            if *i < len {
+                break;
+            }
+            *i = 0;
+        }
+    }
+    out
+}
+
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+const END_OF_CENTRAL: u32 = 0x0605_4b50;
+const ZIP64_END_OF_CENTRAL: u32 = 0x0606_4b50;
+const ZIP64_LOCATOR: u32 = 0x0706_4b50;
+const ZIP64_EXTRA: u16 = 0x0001;
+// Version 2.0 of the zip format, and 1980-01-01 as the modification date.
+const ZIP_VERSION: u16 = 20;
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+/// A collection of named arrays, as stored in a `.npz` archive. See
+/// [module level documentation](self) for more info.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Npz {
+    /// The arrays with their names, in the order they are stored in.
+    pub arrays: Vec<(String, Array)>,
+}
+
+impl Npz {
+    /// Creates an empty collection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `array` named `name`, replacing the array with the same name if there is one.
+    pub fn insert(&mut self, name: impl Into<String>, array: Array) {
+        let name = name.into();
+        match self.arrays.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, existing)) => *existing = array,
+            None => self.arrays.push((name, array)),
+        }
+    }
+
+    /// Borrows the array named `name`, if there is one.
+    pub fn get(&self, name: &str) -> Option<&Array> {
+        self.arrays
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, array)| array)
+    }
+
+    /// Borrows the values of the array named `name`.
+    ///
+    /// # Errors
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if there is no such array, or if
+    /// it does not have `shape`.
+    pub fn get_shaped(&self, name: &str, shape: &[usize]) -> io::Result<&[Scalar]> {
+        let array = self
+            .get(name)
+            .ok_or_else(|| invalid(format!("missing array {name}")))?;
+        if array.shape != shape {
+            return Err(invalid(format!(
+                "array {name} has shape {:?}, but should have shape {shape:?}",
+                array.shape
+            )));
+        }
+        Ok(&array.data)
+    }
+
+    /// Writes the arrays to `writer` as a `.npz` archive.
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        let too_large = || invalid("arrays are too large for a .npz archive");
+        let mut central = Vec::new();
+        let mut offset = 0u32;
+        for (name, array) in &self.arrays {
+            let name = format!("{name}.npy");
+            let mut npy = Vec::new();
+            array.write_npy(&mut npy)?;
+            let size = u32::try_from(npy.len()).map_err(|_| too_large())?;
+            let crc = crc32(&npy);
+
+            let mut local = Vec::with_capacity(30 + name.len());
+            local.extend(LOCAL_HEADER.to_le_bytes());
+            local.extend(ZIP_VERSION.to_le_bytes());
+            entry_fields(&mut local, crc, size, &name);
+            local.extend(0u16.to_le_bytes());
+            local.extend(name.as_bytes());
+            writer.write_all(&local)?;
+            writer.write_all(&npy)?;
+
+            central.extend(CENTRAL_HEADER.to_le_bytes());
+            central.extend(ZIP_VERSION.to_le_bytes());
+            central.extend(ZIP_VERSION.to_le_bytes());
+            entry_fields(&mut central, crc, size, &name);
+            // No extra field, no comment, disk 0 and no attributes.
+            central.extend([0; 12]);
+            central.extend(offset.to_le_bytes());
+            central.extend(name.as_bytes());
+
+            offset = (local.len() as u32)
+                .checked_add(size)
+                .and_then(|len| offset.checked_add(len))
+                .ok_or_else(too_large)?;
+        }
+        let count = u16::try_from(self.arrays.len()).map_err(|_| too_large())?;
+        let central_len = u32::try_from(central.len()).map_err(|_| too_large())?;
+        writer.write_all(&central)?;
+        writer.write_all(&END_OF_CENTRAL.to_le_bytes())?;
+        writer.write_all(&[0; 4])?;
+        writer.write_all(&count.to_le_bytes())?;
+        writer.write_all(&count.to_le_bytes())?;
+        writer.write_all(&central_len.to_le_bytes())?;
+        writer.write_all(&offset.to_le_bytes())?;
+        writer.write_all(&[0; 2])
+    }
+
+    /// Reads the arrays from a `.npz` archive in `reader`. Files in the archive that are not
+    /// `.npy` files are skipped.
+    ///
+    /// # Errors
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if the data is not a `.npz`
+    /// archive, or if it is compressed.
+    pub fn read_from(mut reader: impl Read) -> io::Result<Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let bytes = bytes.as_slice();
+
+        let end = (0..=bytes.len().saturating_sub(22))
+            .rev()
+            .find(|&i| bytes[i..].starts_with(&END_OF_CENTRAL.to_le_bytes()))
+            .ok_or_else(|| invalid("not a .npz archive"))?;
+        let mut count = u16::from_le_bytes(le(bytes, end + 10)?) as usize;
+        let mut position = u32::from_le_bytes(le(bytes, end + 16)?) as usize;
+        if count == 0xFFFF || position == 0xFFFF_FFFF {
+            // The actual values are in the zip64 end of central directory record.
+            let locator = end.checked_sub(20).ok_or_else(truncated)?;
+            if u32::from_le_bytes(le(bytes, locator)?) != ZIP64_LOCATOR {
+                return Err(invalid("missing zip64 end of central directory"));
+            }
+            let end = index(u64::from_le_bytes(le(bytes, locator + 8)?))?;
+            if u32::from_le_bytes(le(bytes, end)?) != ZIP64_END_OF_CENTRAL {
+                return Err(invalid("invalid zip64 end of central directory"));
+            }
+            count = index(u64::from_le_bytes(le(bytes, after(end, 32)?)?))?;
+            position = index(u64::from_le_bytes(le(bytes, after(end, 48)?)?))?;
+        }
+
+        let mut npz = Self::new();
+        for _ in 0..count {
+            if u32::from_le_bytes(le(bytes, position)?) != CENTRAL_HEADER {
+                return Err(invalid("invalid central directory"));
+            }
+            let method = u16::from_le_bytes(le(bytes, position + 10)?);
+            let crc = u32::from_le_bytes(le(bytes, position + 16)?);
+            let mut size = u32::from_le_bytes(le(bytes, position + 20)?) as u64;
+            let mut uncompressed = u32::from_le_bytes(le(bytes, position + 24)?) as u64;
+            let name_len = u16::from_le_bytes(le(bytes, position + 28)?) as usize;
+            let extra_len = u16::from_le_bytes(le(bytes, position + 30)?) as usize;
+            let comment_len = u16::from_le_bytes(le(bytes, position + 32)?) as usize;
+            let mut offset = u32::from_le_bytes(le(bytes, position + 42)?) as u64;
+            let name = slice(bytes, position + 46, name_len)?;
+            let extra = slice(bytes, position + 46 + name_len, extra_len)?;
+            position += 46 + name_len + extra_len + comment_len;
+            zip64_fields(extra, [&mut uncompressed, &mut size, &mut offset])?;
+
+            let Some(name) = std::str::from_utf8(name)
+                .ok()
+                .and_then(|name| name.strip_suffix(".npy"))
+            else {
+                continue;
+            };
+            if method != 0 {
+                return Err(invalid(format!(
+                    "array {name} is compressed, which is not supported"
+                )));
+            }
+            let local = index(offset)?;
+            if u32::from_le_bytes(le(bytes, local)?) != LOCAL_HEADER {
+                return Err(invalid("invalid local file header"));
+            }
+            let local_name_len = u16::from_le_bytes(le(bytes, local + 26)?) as usize;
+            let local_extra_len = u16::from_le_bytes(le(bytes, local + 28)?) as usize;
+            let data = slice(
+                bytes,
+                local + 30 + local_name_len + local_extra_len,
+                size as usize,
+            )?;
+            if crc32(data) != crc {
+                return Err(invalid(format!("array {name} has an invalid checksum")));
+            }
+            npz.insert(name, Array::read_npy(data)?);
+        }
+        Ok(npz)
+    }
+
+    /// Writes the arrays to a `.npz` archive at `path`, replacing it if it exists.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Reads the arrays from the `.npz` archive at `path`.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+}
+
+// Appends the fields shared by local and central headers, from the flags up to the length of the
+// name, for a stored file.
+fn entry_fields(header: &mut Vec<u8>, crc: u32, size: u32, name: &str) {
+    // No flags, no compression, and midnight on the modification date.
+    header.extend([0; 6]);
+    header.extend(DOS_DATE.to_le_bytes());
+    header.extend(crc.to_le_bytes());
+    header.extend(size.to_le_bytes());
+    header.extend(size.to_le_bytes());
+    header.extend((name.len() as u16).to_le_bytes());
+}
+
+// Replaces the sizes and the offset of a central directory entry by their values in its zip64
+// extra field, if they did not fit in 32 bits.
+fn zip64_fields(mut extra: &[u8], fields: [&mut u64; 3]) -> io::Result<()> {
+    while extra.len() >= 4 {
+        let id = u16::from_le_bytes(le(extra, 0)?);
+        let len = u16::from_le_bytes(le(extra, 2)?) as usize;
+        if id == ZIP64_EXTRA {
+            // The values are only present for the fields that did not fit, in this order.
+            let mut values = slice(extra, 4, len)?;
+            for field in fields {
+                if *field == 0xFFFF_FFFF {
+                    *field = u64::from_le_bytes(le(values, 0)?);
+                    values = &values[8..];
+                }
+            }
+            return Ok(());
+        }
+        extra = slice(extra, 4 + len, extra.len().saturating_sub(4 + len))?;
+    }
+    Ok(())
+}
+
+fn truncated() -> io::Error {
+    invalid("truncated .npz archive")
+}
+
+// Converts an offset or a count read from the archive, which does not fit in memory if it does not
+// fit in a `usize`.
+fn index(value: u64) -> io::Result<usize> {
+    usize::try_from(value).map_err(|_| truncated())
+}
+
+fn after(start: usize, len: usize) -> io::Result<usize> {
+    start.checked_add(len).ok_or_else(truncated)
+}
+
+fn slice(bytes: &[u8], start: usize, len: usize) -> io::Result<&[u8]> {
+    bytes.get(start..after(start, len)?).ok_or_else(truncated)
+}
+
+fn le<const N: usize>(bytes: &[u8], start: usize) -> io::Result<[u8; N]> {
+    Ok(slice(bytes, start, N)?
+        .try_into()
+        .expect("Slices should have length N."))
+}
@@ -0,0 +1,170 @@
+/*!
+Online learning from data streams.
+
+An [`Online`] network is trained one sample at a time using [`Online::partial_fit()`], for when
+samples arrive as a stream instead of a fixed dataset. It tracks a fast and a slow moving
+average of the error, which it uses to:
+- detect drift: when the fast average rises well above the slow average, the data has likely
+  changed,
+- adapt the learning rate: it grows while the error decreases and shrinks when it increases, and
+  is reset when drift is detected so the network can quickly adapt to the new data.
+*/
+
+use rann_traits::{LearningRate, Scalar, Supervised};
+
+use crate::train;
+
+/// The configuration of an [`Online`] network.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OnlineConfig {
+    /// The initial learning rate.
+    pub learning_rate: LearningRate,
+    /// The weight of a new error in the fast moving average.
+    pub fast_smoothing: Scalar,
+    /// The weight of a new error in the slow moving average.
+    pub slow_smoothing: Scalar,
+    /// The number of samples before drift is detected, such that the averages can settle.
+    pub warmup: usize,
+    /// Drift is detected when the fast average exceeds the slow average times this threshold. No
+    /// drift is detected if this is `None`.
+    pub drift_threshold: Option<Scalar>,
+    /// How to adapt the learning rate, or `None` for a constant learning rate.
+    pub adaptation: Option<Adaptation>,
+}
+
+impl Default for OnlineConfig {
+    fn default() -> Self {
+        Self {
+            learning_rate: LearningRate(0.1),
+            fast_smoothing: 0.1,
+            slow_smoothing: 0.01,
+            warmup: 100,
+            drift_threshold: Some(2.0),
+            adaptation: None,
+        }
+    }
+}
+
+/// How the learning rate of an [`Online`] network is adapted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Adaptation {
+    /// The factor the learning rate is multiplied by when the fast average decreases.
+    pub increase: Scalar,
+    /// The factor the learning rate is multiplied by when the fast average increases.
+    pub decrease: Scalar,
+    /// The smallest learning rate.
+    pub min: Scalar,
+    /// The largest learning rate.
+    pub max: Scalar,
+}
+
+impl Default for Adaptation {
+    fn default() -> Self {
+        Self {
+            increase: 1.01,
+            decrease: 0.95,
+            min: 1e-4,
+            max: 1.0,
+        }
+    }
+}
+
+/// The result of a call to [`Online::partial_fit()`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Update {
+    /// The error of the sample, before training on it.
+    pub error: Scalar,
+    /// Whether drift was detected at this sample.
+    pub drift: bool,
+}
+
+/// A network trained on a stream of samples. See [module level documentation](self) for more
+/// info.
+#[derive(Clone, Debug)]
+pub struct Online<N> {
+    /// The trained network.
+    pub net: N,
+    /// The configuration.
+    pub config: OnlineConfig,
+    learning_rate: LearningRate,
+    fast: Scalar,
+    slow: Scalar,
+    samples: usize,
+}
+
+impl<N> Online<N>
+where
+    N: Supervised,
+{
+    /// Wraps `net` for online learning.
+    pub fn new(net: N, config: OnlineConfig) -> Self {
+        Self {
+            net,
+            config,
+            learning_rate: config.learning_rate,
+            fast: 0.0,
+            slow: 0.0,
+            samples: 0,
+        }
+    }
+
+    /// Trains the network on a single sample.
+    pub fn partial_fit(&mut self, inputs: &N::In, target: &N::Target) -> Update {
+        let error = train::train_step(&mut self.net, inputs, target, self.learning_rate);
+
+        let prev_fast = self.fast;
+        if self.samples == 0 {
+            self.fast = error;
+            self.slow = error;
+        } else {
+            self.fast += self.config.fast_smoothing * (error - self.fast);
+            self.slow += self.config.slow_smoothing * (error - self.slow);
+        }
+        self.samples += 1;
+
+        let drift = self.samples > self.config.warmup
+            && self
+                .config
+                .drift_threshold
+                .is_some_and(|threshold| self.fast > self.slow * threshold);
+        if let Some(adapt) = self.config.adaptation {
+            if drift {
+                self.learning_rate = self.config.learning_rate;
+            } else if self.samples > 1 {
+                let factor = if self.fast < prev_fast {
+                    adapt.increase
+                } else {
+                    adapt.decrease
+                };
+                let rate = self.learning_rate.get() * factor;
+                self.learning_rate = LearningRate(rate.clamp(adapt.min, adapt.max));
+            }
+        }
+        if drift {
+            // Start tracking the new data, such that the drift is only reported once.
+            self.slow = self.fast;
+            self.samples = 1;
+        }
+        Update { error, drift }
+    }
+
+    /// Returns the current learning rate.
+    pub fn learning_rate(&self) -> LearningRate {
+        self.learning_rate
+    }
+
+    /// Returns the fast moving average of the error.
+    pub fn recent_error(&self) -> Scalar {
+        self.fast
+    }
+
+    /// Returns the slow moving average of the error.
+    pub fn long_term_error(&self) -> Scalar {
+        self.slow
+    }
+
+    /// Returns the trained network.
+    pub fn into_inner(self) -> N {
+        self.net
+    }
+}
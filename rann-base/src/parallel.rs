@@ -0,0 +1,481 @@
+/*!
+Data-parallel training across replicas of a network on multiple threads, and parallel batch
+inference.
+
+A [`ParallelTrainer`] trains a network on batches of samples, like
+[`train_epoch_batched()`](crate::train::train_epoch_batched), but splits every batch into shards
+that are trained on by replicas of the network in parallel. Every replica starts from the
+parameters of the network, and finds the updates of the samples of its shard. The updates of all
+samples are averaged and applied to the network in a single synchronized update, after which the
+replicas start the next batch from the updated parameters.
+
+The updates are summed in a fixed tree order with
+[`par_sum_gradients()`](crate::reduce::par_sum_gradients), so training results in bit for bit the
+same network with any number of replicas and threads: these only change how fast an epoch is
+trained. Up to rounding, it is also the same network as training on the batch with
+[`train_batch()`](crate::train::train_batch).
+
+# Hogwild training
+A [`HogwildTrainer`] instead trains without synchronizing at all, in the style of Hogwild!: the
+parameters are stored once as [`SharedParams`], and every thread trains its own replica on a shard
+of the dataset, one sample at a time. Before every step a thread reads the shared parameters, and
+after it adds the change of every parameter it updated to the shared parameters, using an atomic
+addition per parameter. No locks are taken, so threads never wait on each other.
+
+This is only consistent per parameter: no update is ever lost, but a thread may read parameters
+while another thread is halfway through adding its update, and its own update is computed from
+parameters that other threads may have changed since. Training is therefore not deterministic, and
+differs from training sequentially. This works well when the updates of samples are sparse, and
+rarely change the same parameters, such as for embeddings, but when every sample updates every
+parameter, the stale reads slow down convergence, and a [`ParallelTrainer`] is usually the better
+choice.
+
+# Batch inference
+[`eval_many()`] evaluates a network on a batch of inputs in parallel, by splitting the batch into
+a chunk per thread, of which each is evaluated using [`Network::eval_many()`], such that layers
+that evaluate a batch at once, such as [`Full`](crate::Full), still do so for every chunk.
+
+# Examples
+```rust
+use rann_base::{
+    activ::Logistic, error::SquareError, parallel::ParallelTrainer, testing, train, Full,
+};
+use rann_traits::{LearningRate, Network};
+
+let net = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1))
+    .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)))
+    .chain(SquareError { expected: [0.0] });
+let mut parallel = net.clone();
+let mut sequential = net;
+
+let trainer = ParallelTrainer {
+    epochs: 10,
+    learning_rate: LearningRate(0.5),
+    batch_size: 4,
+    replicas: 2,
+    ..Default::default()
+};
+let fit = trainer.fit(&mut parallel, &testing::XOR);
+assert_eq!(fit.errors.len(), 10);
+
+for _ in 0..10 {
+    train::train_epoch_batched(&mut sequential, &testing::XOR, 4, 0.5);
+}
+let error = train::mean_error(&mut parallel, &testing::XOR);
+assert!((error - train::mean_error(&mut sequential, &testing::XOR)).abs() < 1e-5);
+```
+
+Hogwild training:
+```rust
+use rann_base::{
+    activ::Logistic, error::SquareError, parallel::HogwildTrainer, testing, train, Full,
+};
+use rann_traits::{LearningRate, Network};
+
+let mut net = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1))
+    .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)))
+    .chain(SquareError { expected: [0.0] });
+let error = train::mean_error(&mut net, &testing::XOR);
+
+let trainer = HogwildTrainer {
+    epochs: 10,
+    learning_rate: LearningRate(0.5),
+    threads: 2,
+    ..Default::default()
+};
+let fit = trainer.fit(&mut net, &testing::XOR);
+assert_eq!(fit.steps, 10 * testing::XOR.len());
+assert!(train::mean_error(&mut net, &testing::XOR) < error);
+```
+*/
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use rann_traits::{params::Parameterized, LearningRate, Network, Scalar, Supervised};
+use rayon::prelude::*;
+
+use crate::{
+    reduce,
+    train::{train_step, CancellationToken, Fit},
+};
+
+/// Trains replicas of a network on shards of every batch in parallel. See
+/// [module level documentation](self) for more info.
+#[derive(Clone, Debug)]
+pub struct ParallelTrainer {
+    /// The number of epochs to train for.
+    pub epochs: usize,
+    /// The learning rate.
+    pub learning_rate: LearningRate,
+    /// The number of samples of which the updates are averaged into a single update. The last
+    /// batch of an epoch is smaller if this does not divide the number of samples.
+    pub batch_size: usize,
+    /// The number of replicas of the network, which is the largest number of shards a batch is
+    /// split into.
+    pub replicas: usize,
+    /// A token to stop training early, checked before every batch.
+    pub cancel: Option<CancellationToken>,
+}
+
+impl Default for ParallelTrainer {
+    fn default() -> Self {
+        Self {
+            epochs: 100,
+            learning_rate: LearningRate(0.1),
+            batch_size: 32,
+            replicas: rayon::current_num_threads(),
+            cancel: None,
+        }
+    }
+}
+
+// A replica of the network, with a buffer for the parameters after training on a sample.
+struct Replica<N> {
+    net: N,
+    after: Vec<Scalar>,
+}
+
+impl<N> Replica<N>
+where
+    N: Supervised + Parameterized,
+{
+    // Trains on every sample of `shard` from the parameters `before`, and writes the update and the
+    // error of every sample to `updates` and `errors`.
+    fn train_shard(
+        &mut self,
+        shard: &[(N::In, N::Target)],
+        updates: &mut [Vec<Scalar>],
+        errors: &mut [Scalar],
+        before: &[Scalar],
+        learning_rate: LearningRate,
+    ) {
+        for (((inputs, target), update), error) in shard.iter().zip(updates).zip(errors) {
+            self.net.read_params(before);
+            *error = train_step(&mut self.net, inputs, target, learning_rate);
+            self.net.write_params(&mut self.after);
+            for ((update, before), after) in update.iter_mut().zip(before).zip(&self.after) {
+                *update = before - after;
+            }
+        }
+    }
+}
+
+// The replicas of a network, with a buffer for the update of every sample of a batch.
+struct Replicas<N> {
+    replicas: Vec<Replica<N>>,
+    updates: Vec<Vec<Scalar>>,
+}
+
+impl<N: Parameterized + Clone> Replicas<N> {
+    fn new(net: &N, replicas: usize, batch_size: usize) -> Self {
+        let num_params = net.num_params();
+        Self {
+            replicas: (0..replicas)
+                .map(|_| Replica {
+                    net: net.clone(),
+                    after: vec![0.0; num_params],
+                })
+                .collect(),
+            updates: vec![vec![0.0; num_params]; batch_size],
+        }
+    }
+}
+
+impl ParallelTrainer {
+    /// Trains `net` on the batches of every epoch of `dataset` in order, and returns the mean
+    /// error over the samples of every epoch before their batches were trained on. The steps of
+    /// the returned [`Fit`] are the number of samples trained on. An empty dataset trains no
+    /// epochs.
+    ///
+    /// If cancelled, training stops before the next batch, and the errors of the completed
+    /// epochs are returned.
+    ///
+    /// # Panics
+    /// Panics if the batch size or the number of replicas is zero.
+    pub fn fit<N>(&self, net: &mut N, dataset: &[(N::In, N::Target)]) -> Fit
+    where
+        N: Supervised + Parameterized + Clone + Send,
+        N::In: Sync,
+        N::Target: Sync,
+    {
+        assert!(self.batch_size > 0, "The batch size should be positive.");
+        assert!(self.replicas > 0, "There should be replicas.");
+        if dataset.is_empty() {
+            return Fit::default();
+        }
+        let batch_size = self.batch_size.min(dataset.len());
+        let mut replicas = Replicas::new(net, self.replicas.min(batch_size), batch_size);
+        let mut fit = Fit::default();
+        for _ in 0..self.epochs {
+            let mut sum = 0.0;
+            for batch in dataset.chunks(self.batch_size) {
+                if self.cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+                    fit.cancelled = true;
+                    return fit;
+                }
+                sum += self.step(net, &mut replicas, batch) * batch.len() as Scalar;
+                fit.steps += batch.len();
+            }
+            fit.errors.push(sum / dataset.len() as Scalar);
+        }
+        fit
+    }
+
+    /// Trains `net` once on `batch` with the average of the updates of its samples, found by the
+    /// replicas in parallel, and returns the mean error over the samples before training.
+    ///
+    /// # Panics
+    /// Panics if `batch` is empty, or if the number of replicas is zero.
+    pub fn train_batch<N>(&self, net: &mut N, batch: &[(N::In, N::Target)]) -> Scalar
+    where
+        N: Supervised + Parameterized + Clone + Send,
+        N::In: Sync,
+        N::Target: Sync,
+    {
+        assert!(self.replicas > 0, "There should be replicas.");
+        let mut replicas = Replicas::new(net, self.replicas.min(batch.len()), batch.len());
+        self.step(net, &mut replicas, batch)
+    }
+
+    fn step<N>(
+        &self,
+        net: &mut N,
+        replicas: &mut Replicas<N>,
+        batch: &[(N::In, N::Target)],
+    ) -> Scalar
+    where
+        N: Supervised + Parameterized + Send,
+        N::In: Sync,
+        N::Target: Sync,
+    {
+        assert!(!batch.is_empty(), "The batch should not be empty.");
+        let before = net.params();
+        let shard_size = batch.len().div_ceil(replicas.replicas.len());
+        let updates = &mut replicas.updates[..batch.len()];
+        let mut errors = vec![0.0; batch.len()];
+        replicas
+            .replicas
+            .par_iter_mut()
+            .zip(batch.par_chunks(shard_size))
+            .zip(updates.par_chunks_mut(shard_size))
+            .zip(errors.par_chunks_mut(shard_size))
+            .for_each(|(((replica, shard), updates), errors)| {
+                replica.train_shard(shard, updates, errors, &before, self.learning_rate)
+            });
+
+        // Sum the updates of the samples in a fixed order, such that the result depends neither on
+        // the number of replicas nor on their scheduling.
+        let sum = reduce::par_sum_gradients(updates);
+        let len = batch.len() as Scalar;
+        let mut params = before;
+        for (param, update) in params.iter_mut().zip(&sum) {
+            *param -= update / len;
+        }
+        net.read_params(&params);
+        reduce::pairwise_sum(&errors) / len
+    }
+}
+
+/// Parameters that are shared by threads, and updated without locks by atomically adding to every
+/// parameter separately. See [module level documentation](self) for more info.
+#[derive(Debug, Default)]
+pub struct SharedParams(Vec<AtomicU32>);
+
+impl SharedParams {
+    /// Creates shared parameters with the values of `params`.
+    pub fn new(params: &[Scalar]) -> Self {
+        Self(params.iter().map(|p| AtomicU32::new(p.to_bits())).collect())
+    }
+
+    /// Returns the number of parameters.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns whether there are no parameters.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the value of the parameter at `index`.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> Scalar {
+        Scalar::from_bits(self.0[index].load(Ordering::Relaxed))
+    }
+
+    /// Atomically adds `delta` to the parameter at `index`, such that concurrent additions are
+    /// never lost.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn add(&self, index: usize, delta: Scalar) {
+        // The closure always returns `Some`, so the update can not fail.
+        let _ = self.0[index].fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+            Some((Scalar::from_bits(bits) + delta).to_bits())
+        });
+    }
+
+    /// Copies the values of the parameters into `params`. Every value is read atomically, but
+    /// values that are updated concurrently may be read before or after the update, independently
+    /// of each other.
+    ///
+    /// # Panics
+    /// Panics if the length of `params` differs from the number of parameters.
+    pub fn load(&self, params: &mut [Scalar]) {
+        assert_eq!(params.len(), self.len(), "The lengths should match.");
+        for (param, shared) in params.iter_mut().zip(&self.0) {
+            *param = Scalar::from_bits(shared.load(Ordering::Relaxed));
+        }
+    }
+
+    /// Returns the values of the parameters, see [`SharedParams::load()`].
+    pub fn to_vec(&self) -> Vec<Scalar> {
+        let mut params = vec![0.0; self.len()];
+        self.load(&mut params);
+        params
+    }
+}
+
+/// Trains replicas of a network on shards of a dataset in parallel, on parameters that are shared
+/// without locks. See [module level documentation](self) for more info.
+#[derive(Clone, Debug)]
+pub struct HogwildTrainer {
+    /// The number of epochs to train for.
+    pub epochs: usize,
+    /// The learning rate.
+    pub learning_rate: LearningRate,
+    /// The number of threads, which is the largest number of shards the dataset is split into.
+    pub threads: usize,
+    /// A token to stop training early, checked before every step.
+    pub cancel: Option<CancellationToken>,
+}
+
+impl Default for HogwildTrainer {
+    fn default() -> Self {
+        Self {
+            epochs: 100,
+            learning_rate: LearningRate(0.1),
+            threads: rayon::current_num_threads(),
+            cancel: None,
+        }
+    }
+}
+
+// A replica of the network, with buffers for the shared parameters before a step and for its
+// parameters after it.
+struct Worker<N> {
+    net: N,
+    before: Vec<Scalar>,
+    after: Vec<Scalar>,
+}
+
+impl<N> Worker<N>
+where
+    N: Supervised + Parameterized,
+{
+    // Trains on every sample of `shard` from the shared parameters, and returns the sum of their
+    // errors and the number of samples trained on.
+    fn train_shard(
+        &mut self,
+        shard: &[(N::In, N::Target)],
+        shared: &SharedParams,
+        trainer: &HogwildTrainer,
+    ) -> (Scalar, usize) {
+        let mut sum = 0.0;
+        for (steps, (inputs, target)) in shard.iter().enumerate() {
+            if trainer.cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+                return (sum, steps);
+            }
+            shared.load(&mut self.before);
+            self.net.read_params(&self.before);
+            sum += train_step(&mut self.net, inputs, target, trainer.learning_rate);
+            self.net.write_params(&mut self.after);
+            // Only the parameters that changed are written, such that sparse updates rarely
+            // touch the same parameters as the updates of other threads.
+            for (i, (before, after)) in self.before.iter().zip(&self.after).enumerate() {
+                if before != after {
+                    shared.add(i, after - before);
+                }
+            }
+        }
+        (sum, shard.len())
+    }
+}
+
+impl HogwildTrainer {
+    /// Trains `net` on every epoch of `dataset`, of which every thread trains on a contiguous
+    /// shard, and returns the mean error over the samples of every epoch before they were
+    /// trained on. The threads are joined after every epoch. The steps of the returned [`Fit`]
+    /// are the number of samples trained on.
+    ///
+    /// If cancelled, every thread stops before its next step, `net` gets the parameters updated
+    /// so far, and the errors of the completed epochs are returned.
+    ///
+    /// # Panics
+    /// Panics if the number of threads is zero.
+    pub fn fit<N>(&self, net: &mut N, dataset: &[(N::In, N::Target)]) -> Fit
+    where
+        N: Supervised + Parameterized + Clone + Send,
+        N::In: Sync,
+        N::Target: Sync,
+    {
+        assert!(self.threads > 0, "There should be threads.");
+        let shared = SharedParams::new(&net.params());
+        let mut workers: Vec<_> = (0..self.threads.min(dataset.len()))
+            .map(|_| Worker {
+                net: net.clone(),
+                before: vec![0.0; shared.len()],
+                after: vec![0.0; shared.len()],
+            })
+            .collect();
+        let mut fit = Fit::default();
+        if !workers.is_empty() {
+            let shard_size = dataset.len().div_ceil(workers.len());
+            for _ in 0..self.epochs {
+                let (sum, steps) = workers
+                    .par_iter_mut()
+                    .zip(dataset.par_chunks(shard_size))
+                    .map(|(worker, shard)| worker.train_shard(shard, &shared, self))
+                    .reduce(|| (0.0, 0), |a, b| (a.0 + b.0, a.1 + b.1));
+                fit.steps += steps;
+                if steps < dataset.len() {
+                    fit.cancelled = true;
+                    break;
+                }
+                fit.errors.push(sum / dataset.len() as Scalar);
+            }
+        }
+        net.read_params(&shared.to_vec());
+        fit
+    }
+}
+
+/// Evaluates `net` on every input of `inputs` in parallel, and returns their outputs in order.
+/// See [module level documentation](self) for more info.
+///
+/// # Examples
+/// ```rust
+/// use rann_base::{activ::Logistic, parallel, testing, Full};
+/// use rann_traits::Network;
+///
+/// let net = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1))
+///     .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)));
+/// let inputs: Vec<_> = (0..100).map(|i| [i as f32 / 100.0, 1.0]).collect();
+/// let outputs = parallel::eval_many(&net, &inputs);
+/// assert_eq!(outputs.len(), 100);
+/// assert!((outputs[42][0] - net.eval(&inputs[42])[0]).abs() < 1e-6);
+/// ```
+pub fn eval_many<N>(net: &N, inputs: &[N::In]) -> Vec<N::Out>
+where
+    N: Network + Sync,
+    N::In: Sync,
+    N::Out: Send,
+{
+    let chunk_size = inputs.len().div_ceil(rayon::current_num_threads()).max(1);
+    inputs
+        .par_chunks(chunk_size)
+        .flat_map_iter(|chunk| net.eval_many(chunk))
+        .collect()
+}
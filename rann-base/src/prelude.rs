@@ -0,0 +1,36 @@
+/*!
+The commonly used types and traits of RANN, to be imported at once.
+
+The prelude contains the network traits of [`rann_traits`], the [`Full`] layer, the activation
+functions, the error functions, the [`Random`] generator and the [`Trainer`] with its
+[`LearningRate`]. Everything else lives in its own module, such as [`crate::conv`] for
+convolutions or [`crate::metrics`] for evaluating networks.
+
+# Examples
+```rust
+use rann_base::{prelude::*, testing};
+
+let mut net = chain!(
+    Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1)),
+    Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)),
+    SquareError { expected: [0.0] },
+);
+let trainer = Trainer { epochs: 2000, learning_rate: LearningRate(1.0), ..Default::default() };
+let fit = trainer.fit(&mut net, &testing::XOR);
+assert!(fit.errors.last().unwrap() < &0.01);
+let _random = Full::<2, 3, _>::new(Tanh, Random);
+```
+*/
+
+pub use rann_traits::{
+    chain, deriv::Deriv, params::Parameterized, Intermediate, LearningRate, Network, Scalar,
+    Supervised,
+};
+
+pub use crate::{
+    activ::{LeakyRelu, Logistic, Selu, Softplus, Tanh},
+    error::{CrossEntropy, LogisticWithBce, SoftmaxWithCe, SquareError, SumError},
+    gen::Random,
+    train::Trainer,
+    Full,
+};
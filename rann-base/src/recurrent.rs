@@ -0,0 +1,678 @@
+/*!
+Recurrent networks, which process a sequence one step at a time while keeping a state.
+
+A recurrent cell is a network that takes the state after the previous step and the inputs of a
+step as a tuple `([Scalar; H], [Scalar; I])`, and outputs the state after that step, a
+`[Scalar; H]`. [`Recurrent`] unrolls a cell over the `T` steps of a sequence, starting from a
+state of zeros, and outputs the state after every step.
+
+A [`Recurrent`] network is trained by backpropagation through time: the gradients over the states
+flow back from the last step to the first, and the cell is trained at every step, like a
+[`Shared`](rann_traits::compose::Shared) network that is used `T` times. [`Recurrent::run()`] and
+[`Recurrent::backprop()`] start from a given state and return the gradients over it, to pass the
+state between recurrent networks. Over the padded steps of a [masked](rann_traits::mask) sequence,
+the state is kept.
+
+[`Rnn`] is a plain (Elman) recurrent cell, which activates the weighted sums of the inputs and the
+state. [`Gru`] is a gated recurrent unit: its update gate decides how much of the state is kept
+every step, and its reset gate how much of the state is used for the candidate that replaces the
+rest. It learns longer dependencies than an [`Rnn`], at three times its cost.
+
+# Examples
+```rust
+use rann_base::{recurrent::{Gru, Recurrent}, testing};
+use rann_traits::{Intermediate, Network};
+
+let mut net = Recurrent::<_, 5>::new(Gru::<2, 3>::new(testing::seeded_gen(1)));
+let inputs = [[1.0, 0.0], [0.0, 1.0], [0.0, 0.0], [0.5, 0.5], [-1.0, 0.0]];
+let inter = net.intermediate(&inputs);
+// The state after every step.
+assert_eq!(inter.output().len(), 5);
+assert_eq!(inter.state(), &inter.output()[4]);
+
+// Trains the network to decrease the first element of the last state.
+let mut gradients = [[0.0; 3]; 5];
+gradients[4][0] = 1.0;
+net.train_deriv(&inputs, &inter, &gradients, 0.1);
+assert!(net.eval(&inputs)[4][0] < inter.state()[0]);
+```
+*/
+
+use std::any::Any;
+
+use arrayvec::ArrayVec;
+use nalgebra::{SMatrix, SVector};
+use rann_traits::{
+    deriv::{Deriv, Elementwise},
+    inspect::{Inspect, LayerView},
+    mask::Masking,
+    params::Parameterized,
+    Intermediate, Network, Scalar,
+};
+
+use crate::{activ::Logistic, FullInter};
+
+/// Unrolls a recurrent cell over a sequence of `T` steps. See [module level documentation](self)
+/// for more info.
+#[derive(Clone, Debug)]
+pub struct Recurrent<C, const T: usize> {
+    /// The cell that is evaluated every step.
+    pub cell: C,
+}
+
+impl<C, const T: usize> Recurrent<C, T> {
+    /// Unrolls `cell` over `T` steps.
+    ///
+    /// # Panics
+    /// Panics if `T` is zero.
+    pub fn new(cell: C) -> Self {
+        assert!(T > 0, "A Recurrent should have at least one step.");
+        Self { cell }
+    }
+}
+
+impl<C, const I: usize, const H: usize, const T: usize> Recurrent<C, T>
+where
+    C: Network<In = ([Scalar; H], [Scalar; I]), Out = [Scalar; H]>,
+{
+    /// Evaluates the cell on every step of `inputs`, starting from `state`.
+    pub fn run(
+        &self,
+        state: &[Scalar; H],
+        inputs: &[[Scalar; I]; T],
+    ) -> RecurrentInter<C::Inter, H, T> {
+        self.run_masked(state, inputs, &[true; T])
+    }
+
+    /// Evaluates the cell on every step of `inputs` like [`Self::run()`], but keeps the state
+    /// over the steps of which the flag in `mask` is `false`. Padded steps are evaluated as well,
+    /// such that every step has intermediate calculations, but their outputs are discarded.
+    pub fn run_masked(
+        &self,
+        state: &[Scalar; H],
+        inputs: &[[Scalar; I]; T],
+        mask: &[bool; T],
+    ) -> RecurrentInter<C::Inter, H, T> {
+        let mut steps = ArrayVec::<C::Inter, T>::new();
+        let mut outputs = [*state; T];
+        for t in 0..T {
+            // Every step uses the state after the previous step.
+            let state = if t == 0 { state } else { &outputs[t - 1] };
+            let step = self.cell.intermediate(&(*state, inputs[t]));
+            outputs[t] = if mask[t] { *step.output() } else { *state };
+            steps.push(step);
+        }
+        RecurrentInter {
+            steps: steps
+                .into_inner()
+                .unwrap_or_else(|_| panic!("Capacity of ArrayVec should equal T.")),
+            outputs,
+        }
+    }
+
+    /// Trains the cell by backpropagation through time, using an evaluation by [`Self::run()`]
+    /// from `state`, and the gradients over the state after every step. Returns the gradients
+    /// over the initial state and over the inputs of every step.
+    pub fn backprop(
+        &mut self,
+        state: &[Scalar; H],
+        inputs: &[[Scalar; I]; T],
+        intermediate: &RecurrentInter<C::Inter, H, T>,
+        gradients: &[[Scalar; H]; T],
+        learning_rate: Scalar,
+    ) -> ([Scalar; H], [[Scalar; I]; T]) {
+        self.backprop_masked(
+            state,
+            inputs,
+            intermediate,
+            gradients,
+            &[true; T],
+            learning_rate,
+        )
+    }
+
+    /// Trains the cell like [`Self::backprop()`], using an evaluation by [`Self::run_masked()`].
+    /// The cell is not trained on padded steps: the gradients over the states pass them
+    /// unchanged, the gradients over their outputs are ignored, and the gradients over their
+    /// inputs are zero.
+    pub fn backprop_masked(
+        &mut self,
+        state: &[Scalar; H],
+        inputs: &[[Scalar; I]; T],
+        intermediate: &RecurrentInter<C::Inter, H, T>,
+        gradients: &[[Scalar; H]; T],
+        mask: &[bool; T],
+        learning_rate: Scalar,
+    ) -> ([Scalar; H], [[Scalar; I]; T]) {
+        let mut input_grads = [[0.0; I]; T];
+        // The gradients over the state after the current step, from the steps after it.
+        let mut state_grads = [0.0; H];
+        for t in (0..T).rev() {
+            if !mask[t] {
+                continue;
+            }
+            let mut grads = gradients[t];
+            for (grad, state_grad) in grads.iter_mut().zip(state_grads) {
+                *grad += state_grad;
+            }
+            let state = if t == 0 {
+                *state
+            } else {
+                intermediate.outputs[t - 1]
+            };
+            (state_grads, input_grads[t]) = self.cell.train_deriv(
+                &(state, inputs[t]),
+                &intermediate.steps[t],
+                &grads,
+                learning_rate,
+            );
+        }
+        (state_grads, input_grads)
+    }
+}
+
+impl<C, const I: usize, const H: usize, const T: usize> Network for Recurrent<C, T>
+where
+    C: Network<In = ([Scalar; H], [Scalar; I]), Out = [Scalar; H]>,
+{
+    type In = [[Scalar; I]; T];
+
+    type Out = [[Scalar; H]; T];
+
+    type Inter = RecurrentInter<C::Inter, H, T>;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        self.run(&[0.0; H], inputs)
+    }
+
+    fn train_deriv(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        self.backprop(&[0.0; H], inputs, intermediate, gradients, learning_rate)
+            .1
+    }
+
+    fn find_layer(&self, name: &str) -> Option<&dyn Any> {
+        self.cell.find_layer(name)
+    }
+
+    fn find_layer_mut(&mut self, name: &str) -> Option<&mut dyn Any> {
+        self.cell.find_layer_mut(name)
+    }
+}
+
+impl<C, const I: usize, const H: usize, const T: usize> Masking<T> for Recurrent<C, T>
+where
+    C: Network<In = ([Scalar; H], [Scalar; I]), Out = [Scalar; H]>,
+{
+    fn intermediate_masked(&self, inputs: &Self::In, mask: &[bool; T]) -> Self::Inter {
+        self.run_masked(&[0.0; H], inputs, mask)
+    }
+
+    fn train_masked(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        mask: &[bool; T],
+        learning_rate: Scalar,
+    ) -> Self::In {
+        self.backprop_masked(
+            &[0.0; H],
+            inputs,
+            intermediate,
+            gradients,
+            mask,
+            learning_rate,
+        )
+        .1
+    }
+}
+
+// The cell is visited with its evaluation of the last step.
+impl<C, const I: usize, const H: usize, const T: usize> Inspect for Recurrent<C, T>
+where
+    C: Inspect<In = ([Scalar; H], [Scalar; I]), Out = [Scalar; H]>,
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        self.cell.visit_layers(&intermediate.steps[T - 1], f);
+    }
+}
+
+impl<C: Parameterized, const T: usize> Parameterized for Recurrent<C, T> {
+    fn num_params(&self) -> usize {
+        self.cell.num_params()
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        self.cell.write_params(params);
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        self.cell.read_params(params);
+    }
+}
+
+/// The intermediate calculations for an evaluation of a [`Recurrent`] network.
+#[derive(Clone, Debug)]
+pub struct RecurrentInter<S, const H: usize, const T: usize> {
+    /// The intermediate calculations of the cell in every step.
+    pub steps: [S; T],
+    outputs: [[Scalar; H]; T],
+}
+
+impl<S, const H: usize, const T: usize> RecurrentInter<S, H, T> {
+    /// Returns the state after the last step.
+    pub fn state(&self) -> &[Scalar; H] {
+        &self.outputs[T - 1]
+    }
+}
+
+impl<S, const H: usize, const T: usize> Intermediate for RecurrentInter<S, H, T> {
+    type Out = [[Scalar; H]; T];
+
+    fn output(&self) -> &Self::Out {
+        &self.outputs
+    }
+
+    fn into_output(self) -> Self::Out {
+        self.outputs
+    }
+}
+
+/// A plain recurrent cell with `I` inputs, a state of `H` elements and the activation function
+/// `A`. See [module level documentation](self) for more info.
+///
+/// A step from the state `h` with inputs `x` is `h' = act(W x + U h + b)`.
+#[derive(Clone, Debug)]
+pub struct Rnn<const I: usize, const H: usize, A> {
+    gate: Gate<I, H>,
+    act: A,
+    // The norm of the gradients over the parameters in the last training step.
+    grad_norm: Scalar,
+}
+
+impl<const I: usize, const H: usize, A> Rnn<I, H, A> {
+    /// Creates a plain recurrent cell with weights and biases generated using the given
+    /// generator functions, like [`Full::new()`](crate::Full::new). The weights over the inputs
+    /// are generated first, then those over the state and the biases.
+    pub fn new<T, F, G>(activation: A, gen: T) -> Self
+    where
+        T: Into<(F, G)>,
+        F: FnMut(usize, usize) -> Scalar,
+        G: FnMut(usize) -> Scalar,
+    {
+        let (mut weight_gen, mut bias_gen) = gen.into();
+        Self {
+            gate: Gate::new(&mut weight_gen, &mut bias_gen),
+            act: activation,
+            grad_norm: 0.0,
+        }
+    }
+}
+
+impl<const I: usize, const H: usize, A> Network for Rnn<I, H, A>
+where
+    A: Deriv<In = Scalar, Out = Scalar>,
+{
+    type In = ([Scalar; H], [Scalar; I]);
+
+    type Out = [Scalar; H];
+
+    type Inter = FullInter<H>;
+
+    fn intermediate(&self, (state, inputs): &Self::In) -> Self::Inter {
+        let mut weighted_sums = self.gate.input_sums(inputs);
+        for (sum, state_sum) in weighted_sums.iter_mut().zip(self.gate.state_sums(state)) {
+            *sum += state_sum;
+        }
+        FullInter {
+            outputs: Elementwise::new(&self.act).call(&weighted_sums),
+            weighted_sums,
+        }
+    }
+
+    fn train_deriv(
+        &mut self,
+        (state, inputs): &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        let sum_grads =
+            Elementwise::new(&self.act).backprop(&intermediate.weighted_sums, gradients);
+        let mut input_grads = SVector::<Scalar, I>::zeros();
+        let mut state_grads = SVector::<Scalar, H>::zeros();
+        let squared_norm = self.gate.backprop(
+            inputs,
+            state,
+            sum_grads,
+            sum_grads,
+            &mut input_grads,
+            &mut state_grads,
+            learning_rate,
+        );
+        self.grad_norm = squared_norm.sqrt();
+        (state_grads.data.0[0], input_grads.data.0[0])
+    }
+}
+
+impl<const I: usize, const H: usize, A> Inspect for Rnn<I, H, A>
+where
+    A: Deriv<In = Scalar, Out = Scalar>,
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        f(&LayerView {
+            kind: "Rnn",
+            num_inputs: I,
+            params: &self.gate.params(),
+            activations: &intermediate.outputs,
+            gradient_norm: self.grad_norm,
+        });
+    }
+}
+
+// The weights over the inputs are followed by the weights over the state and the biases.
+impl<const I: usize, const H: usize, A> Parameterized for Rnn<I, H, A> {
+    fn num_params(&self) -> usize {
+        H * I + H * H + H
+    }
+
+    fn write_params(&self, mut params: &mut [Scalar]) {
+        assert_eq!(params.len(), self.num_params());
+        for values in self.gate.params() {
+            let (head, tail) = params.split_at_mut(values.len());
+            head.copy_from_slice(values);
+            params = tail;
+        }
+    }
+
+    fn read_params(&mut self, mut params: &[Scalar]) {
+        assert_eq!(params.len(), self.num_params());
+        for values in self.gate.params_mut() {
+            let (head, tail) = params.split_at(values.len());
+            values.copy_from_slice(head);
+            params = tail;
+        }
+    }
+}
+
+/// A gated recurrent unit with `I` inputs and a state of `H` elements. See
+/// [module level documentation](self) for more info.
+///
+/// With the update gate `z`, the reset gate `r` and the candidate `n`, a step from the state `h`
+/// with inputs `x` is:
+/// ```text
+/// z = logistic(W_z x + U_z h + b_z)
+/// r = logistic(W_r x + U_r h + b_r)
+/// n = tanh(W_n x + r * (U_n h) + b_n)
+/// h' = (1 - z) * n + z * h
+/// ```
+#[derive(Clone, Debug)]
+pub struct Gru<const I: usize, const H: usize> {
+    update: Gate<I, H>,
+    reset: Gate<I, H>,
+    candidate: Gate<I, H>,
+    // The norm of the gradients over the parameters in the last training step.
+    grad_norm: Scalar,
+}
+
+// The weights over the inputs and the state, and the biases, of a gate or of an `Rnn`.
+#[derive(Clone, Debug)]
+struct Gate<const I: usize, const H: usize> {
+    input: SMatrix<Scalar, H, I>,
+    state: SMatrix<Scalar, H, H>,
+    bias: [Scalar; H],
+}
+
+impl<const I: usize, const H: usize> Gate<I, H> {
+    fn new(
+        weight_gen: &mut impl FnMut(usize, usize) -> Scalar,
+        bias_gen: &mut impl FnMut(usize) -> Scalar,
+    ) -> Self {
+        Self {
+            input: SMatrix::from_fn(&mut *weight_gen),
+            state: SMatrix::from_fn(weight_gen),
+            bias: std::array::from_fn(bias_gen),
+        }
+    }
+
+    // Returns the weighted sums of the inputs, plus the biases.
+    fn input_sums(&self, inputs: &[Scalar; I]) -> [Scalar; H] {
+        let mut sums = (self.input * SVector::from(*inputs)).data.0[0];
+        for (sum, bias) in sums.iter_mut().zip(self.bias) {
+            *sum += bias;
+        }
+        sums
+    }
+
+    // Returns the weighted sums of the state.
+    fn state_sums(&self, state: &[Scalar; H]) -> [Scalar; H] {
+        (self.state * SVector::from(*state)).data.0[0]
+    }
+
+    // Adds the gradients over the inputs and the state to `input_grads` and `state_grads`, for the
+    // gradients over the sums of `input_sums()` and `state_sums()`, and updates the parameters.
+    // Returns the squared norm of the gradients over the parameters.
+    #[allow(clippy::too_many_arguments)]
+    fn backprop(
+        &mut self,
+        inputs: &[Scalar; I],
+        state: &[Scalar; H],
+        input_sum_grads: [Scalar; H],
+        state_sum_grads: [Scalar; H],
+        input_grads: &mut SVector<Scalar, I>,
+        state_grads: &mut SVector<Scalar, H>,
+        learning_rate: Scalar,
+    ) -> Scalar {
+        let input_sum_grads = SVector::from(input_sum_grads);
+        let state_sum_grads = SVector::from(state_sum_grads);
+        let inputs = SVector::from(*inputs);
+        let state = SVector::from(*state);
+        *input_grads += self.input.tr_mul(&input_sum_grads);
+        *state_grads += self.state.tr_mul(&state_sum_grads);
+        self.input
+            .ger(-learning_rate, &input_sum_grads, &inputs, 1.0);
+        self.state
+            .ger(-learning_rate, &state_sum_grads, &state, 1.0);
+        for (bias, grad) in self.bias.iter_mut().zip(&input_sum_grads) {
+            *bias -= grad * learning_rate;
+        }
+        input_sum_grads.norm_squared() * (inputs.norm_squared() + 1.0)
+            + state_sum_grads.norm_squared() * state.norm_squared()
+    }
+
+    fn params(&self) -> [&[Scalar]; 3] {
+        [self.input.as_slice(), self.state.as_slice(), &self.bias]
+    }
+
+    fn params_mut(&mut self) -> [&mut [Scalar]; 3] {
+        [
+            self.input.as_mut_slice(),
+            self.state.as_mut_slice(),
+            &mut self.bias,
+        ]
+    }
+}
+
+impl<const I: usize, const H: usize> Gru<I, H> {
+    /// Creates a gated recurrent unit with weights and biases generated using the given
+    /// generator functions, like [`Full::new()`](crate::Full::new). The parameters of the update
+    /// gate are generated first, then those of the reset gate and those of the candidate.
+    pub fn new<T, F, G>(gen: T) -> Self
+    where
+        T: Into<(F, G)>,
+        F: FnMut(usize, usize) -> Scalar,
+        G: FnMut(usize) -> Scalar,
+    {
+        let (mut weight_gen, mut bias_gen) = gen.into();
+        Self {
+            update: Gate::new(&mut weight_gen, &mut bias_gen),
+            reset: Gate::new(&mut weight_gen, &mut bias_gen),
+            candidate: Gate::new(&mut weight_gen, &mut bias_gen),
+            grad_norm: 0.0,
+        }
+    }
+
+    fn gates(&self) -> [&Gate<I, H>; 3] {
+        [&self.update, &self.reset, &self.candidate]
+    }
+
+    fn gates_mut(&mut self) -> [&mut Gate<I, H>; 3] {
+        [&mut self.update, &mut self.reset, &mut self.candidate]
+    }
+}
+
+impl<const I: usize, const H: usize> Network for Gru<I, H> {
+    type In = ([Scalar; H], [Scalar; I]);
+
+    type Out = [Scalar; H];
+
+    type Inter = GruInter<H>;
+
+    fn intermediate(&self, (state, inputs): &Self::In) -> Self::Inter {
+        let mut update = self.update.input_sums(inputs);
+        for (z, sum) in update.iter_mut().zip(self.update.state_sums(state)) {
+            *z = Logistic.call(&(*z + sum));
+        }
+        let mut reset = self.reset.input_sums(inputs);
+        for (r, sum) in reset.iter_mut().zip(self.reset.state_sums(state)) {
+            *r = Logistic.call(&(*r + sum));
+        }
+        let recurrent = self.candidate.state_sums(state);
+        let mut candidate = self.candidate.input_sums(inputs);
+        for ((n, r), sum) in candidate.iter_mut().zip(reset).zip(recurrent) {
+            *n = (*n + r * sum).tanh();
+        }
+        let outputs =
+            std::array::from_fn(|j| (1.0 - update[j]) * candidate[j] + update[j] * state[j]);
+        GruInter {
+            update,
+            reset,
+            candidate,
+            recurrent,
+            outputs,
+        }
+    }
+
+    fn train_deriv(
+        &mut self,
+        (state, inputs): &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        let GruInter {
+            update,
+            reset,
+            candidate,
+            recurrent,
+            ..
+        } = intermediate;
+        // Calculate the gradients over the sums of every gate...
+        let mut update_grads = [0.0; H];
+        let mut reset_grads = [0.0; H];
+        let mut candidate_grads = [0.0; H];
+        let mut recurrent_grads = [0.0; H];
+        // ...and over the state that is kept by the update gate.
+        let mut state_grads = SVector::<Scalar, H>::zeros();
+        for j in 0..H {
+            let grad = gradients[j];
+            state_grads[j] = grad * update[j];
+            update_grads[j] = grad * (state[j] - candidate[j]) * update[j] * (1.0 - update[j]);
+            candidate_grads[j] = grad * (1.0 - update[j]) * (1.0 - candidate[j] * candidate[j]);
+            recurrent_grads[j] = candidate_grads[j] * reset[j];
+            reset_grads[j] = candidate_grads[j] * recurrent[j] * reset[j] * (1.0 - reset[j]);
+        }
+        // Backpropagate through the gates, and update them.
+        let mut input_grads = SVector::<Scalar, I>::zeros();
+        let mut squared_norm = 0.0;
+        for (gate, input_sum_grads, state_sum_grads) in [
+            (&mut self.update, update_grads, update_grads),
+            (&mut self.reset, reset_grads, reset_grads),
+            (&mut self.candidate, candidate_grads, recurrent_grads),
+        ] {
+            squared_norm += gate.backprop(
+                inputs,
+                state,
+                input_sum_grads,
+                state_sum_grads,
+                &mut input_grads,
+                &mut state_grads,
+                learning_rate,
+            );
+        }
+        self.grad_norm = squared_norm.sqrt();
+        (state_grads.data.0[0], input_grads.data.0[0])
+    }
+}
+
+impl<const I: usize, const H: usize> Inspect for Gru<I, H> {
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        let [update, reset, candidate] = self.gates().map(Gate::params);
+        f(&LayerView {
+            kind: "Gru",
+            num_inputs: I,
+            params: &[update, reset, candidate].concat(),
+            activations: &intermediate.outputs,
+            gradient_norm: self.grad_norm,
+        });
+    }
+}
+
+// The parameters of the update gate are followed by those of the reset gate and the candidate.
+// Every gate has its weights over the inputs, its weights over the state and its biases.
+impl<const I: usize, const H: usize> Parameterized for Gru<I, H> {
+    fn num_params(&self) -> usize {
+        3 * (H * I + H * H + H)
+    }
+
+    fn write_params(&self, mut params: &mut [Scalar]) {
+        assert_eq!(params.len(), self.num_params());
+        for gate in self.gates() {
+            for values in gate.params() {
+                let (head, tail) = params.split_at_mut(values.len());
+                head.copy_from_slice(values);
+                params = tail;
+            }
+        }
+    }
+
+    fn read_params(&mut self, mut params: &[Scalar]) {
+        assert_eq!(params.len(), self.num_params());
+        for gate in self.gates_mut() {
+            for values in gate.params_mut() {
+                let (head, tail) = params.split_at(values.len());
+                values.copy_from_slice(head);
+                params = tail;
+            }
+        }
+    }
+}
+
+/// The intermediate calculations for an evaluation of a [`Gru`].
+#[derive(Clone, Debug)]
+pub struct GruInter<const H: usize> {
+    update: [Scalar; H],
+    reset: [Scalar; H],
+    candidate: [Scalar; H],
+    // The weighted sums of the state for the candidate, before the reset gate is applied.
+    recurrent: [Scalar; H],
+    outputs: [Scalar; H],
+}
+
+impl<const H: usize> Intermediate for GruInter<H> {
+    type Out = [Scalar; H];
+
+    fn output(&self) -> &Self::Out {
+        &self.outputs
+    }
+
+    fn into_output(self) -> Self::Out {
+        self.outputs
+    }
+}
@@ -0,0 +1,299 @@
+/*!
+Deterministic and accurate reductions, for reproducible (parallel) training.
+
+Floating point addition is not associative, so the result of summing losses or gradients depends
+on the order of the additions. The reductions in this module always combine values in the same
+fixed tree order: the values are split in halves recursively, down to blocks of [`BLOCK`] values
+which are reduced sequentially. The parallel versions use the same tree, and only run its
+branches on different threads, so they give bit-for-bit the same results as the sequential
+versions, regardless of the number of threads or the scheduling of the work.
+
+As a bonus, pairwise summation accumulates far less rounding error than naive summation: the
+error grows with the logarithm of the number of values, instead of linearly. For long running
+sums, such as the error over an epoch, [`KahanSum`] compensates the rounding error altogether;
+[`Summation`] selects between both, e.g. for the [`Trainer`](crate::train::Trainer), and
+[`ElementwiseSum`] applies it to sums of vectors, such as the updates averaged by
+[`train_batch_with()`](crate::train::train_batch_with). The error of a single sample is a sum
+over only its outputs, which error functions always sum naively.
+
+# Examples
+```rust
+use rann_base::reduce;
+
+let values = vec![0.1; 100_000];
+let sum = reduce::pairwise_sum(&values);
+# #[cfg(feature = "rayon")]
+assert_eq!(sum, reduce::par_pairwise_sum(&values));
+assert!((sum - 10_000.0).abs() < 0.01);
+```
+*/
+
+use rann_traits::Scalar;
+
+/// The number of values at the leaves of the reduction tree, which are reduced sequentially.
+pub const BLOCK: usize = 32;
+
+/// Reduces `items` in a fixed tree order: each block of at most [`BLOCK`] items is reduced by
+/// `leaf`, and the results are combined pairwise by `combine`. See
+/// [module level documentation](self) for more info.
+///
+/// `leaf` is called once with an empty slice if `items` is empty.
+pub fn tree_reduce<T, R>(items: &[T], leaf: impl Fn(&[T]) -> R, combine: impl Fn(R, R) -> R) -> R {
+    fn reduce<T, R>(items: &[T], leaf: &impl Fn(&[T]) -> R, combine: &impl Fn(R, R) -> R) -> R {
+        if items.len() <= BLOCK {
+            return leaf(items);
+        }
+        let (left, right) = items.split_at(split(items.len()));
+        combine(reduce(left, leaf, combine), reduce(right, leaf, combine))
+    }
+    reduce(items, &leaf, &combine)
+}
+
+/// Reduces `items` like [`tree_reduce()`] with the same tree order, but runs the branches of the
+/// tree in parallel. The result is identical to that of [`tree_reduce()`].
+#[cfg(feature = "rayon")]
+pub fn par_tree_reduce<T, R>(
+    items: &[T],
+    leaf: impl Fn(&[T]) -> R + Sync,
+    combine: impl Fn(R, R) -> R + Sync,
+) -> R
+where
+    T: Sync,
+    R: Send,
+{
+    fn reduce<T, R>(
+        items: &[T],
+        leaf: &(impl Fn(&[T]) -> R + Sync),
+        combine: &(impl Fn(R, R) -> R + Sync),
+    ) -> R
+    where
+        T: Sync,
+        R: Send,
+    {
+        if items.len() <= BLOCK {
+            return leaf(items);
+        }
+        let (left, right) = items.split_at(split(items.len()));
+        let (left, right) = rayon::join(
+            || reduce(left, leaf, combine),
+            || reduce(right, leaf, combine),
+        );
+        combine(left, right)
+    }
+    reduce(items, &leaf, &combine)
+}
+
+// The split point of a branch of the tree, such that the left half is a whole number of blocks.
+fn split(len: usize) -> usize {
+    (len / 2).div_ceil(BLOCK) * BLOCK
+}
+
+/// Sums `values` in a fixed tree order.
+pub fn pairwise_sum(values: &[Scalar]) -> Scalar {
+    tree_reduce(values, |block| block.iter().sum(), |a, b| a + b)
+}
+
+/// Sums `values` in parallel, with the same result as [`pairwise_sum()`].
+#[cfg(feature = "rayon")]
+pub fn par_pairwise_sum(values: &[Scalar]) -> Scalar {
+    par_tree_reduce(values, |block| block.iter().sum(), |a, b| a + b)
+}
+
+/// Sums `gradients` element-wise in a fixed tree order, e.g. to accumulate the gradients of the
+/// samples in a batch. Returns an empty vector if there are no gradients.
+///
+/// # Panics
+/// Panics if the gradients do not all have the same length.
+pub fn sum_gradients(gradients: &[Vec<Scalar>]) -> Vec<Scalar> {
+    tree_reduce(gradients, sum_block, add_assign)
+}
+
+/// Sums `gradients` element-wise in parallel, with the same result as [`sum_gradients()`].
+///
+/// # Panics
+/// Panics if the gradients do not all have the same length.
+#[cfg(feature = "rayon")]
+pub fn par_sum_gradients(gradients: &[Vec<Scalar>]) -> Vec<Scalar> {
+    par_tree_reduce(gradients, sum_block, add_assign)
+}
+
+fn sum_block(gradients: &[Vec<Scalar>]) -> Vec<Scalar> {
+    let Some((first, rest)) = gradients.split_first() else {
+        return Vec::new();
+    };
+    let mut sum = first.clone();
+    for grad in rest {
+        add(&mut sum, grad);
+    }
+    sum
+}
+
+fn add_assign(mut sum: Vec<Scalar>, other: Vec<Scalar>) -> Vec<Scalar> {
+    add(&mut sum, &other);
+    sum
+}
+
+fn add(sum: &mut [Scalar], other: &[Scalar]) {
+    assert_eq!(
+        sum.len(),
+        other.len(),
+        "All gradients should have the same length."
+    );
+    sum.iter_mut().zip(other).for_each(|(s, o)| *s += o);
+}
+
+/// A running sum using compensated (Kahan-Babuška) summation in double precision, which tracks
+/// the rounding error of every addition. The error of the sum does not grow with the number of
+/// values, which makes it suitable for long running accumulations, such as the error over an
+/// epoch.
+///
+/// # Examples
+/// ```rust
+/// use rann_base::reduce::KahanSum;
+///
+/// let mut sum = KahanSum::new();
+/// for _ in 0..1_000_000 {
+///     sum.add(0.1);
+/// }
+/// assert_eq!(sum.sum(), 100_000.0);
+/// assert_eq!(std::iter::repeat(0.1).take(10).collect::<KahanSum>().sum(), 1.0);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct KahanSum {
+    sum: f64,
+    // The accumulated low order bits lost by rounding the sum.
+    compensation: f64,
+}
+
+impl KahanSum {
+    /// Creates a sum of zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `value` to the sum.
+    pub fn add(&mut self, value: Scalar) {
+        let value = f64::from(value);
+        let sum = self.sum + value;
+        self.compensation += if self.sum.abs() >= value.abs() {
+            (self.sum - sum) + value
+        } else {
+            (value - sum) + self.sum
+        };
+        self.sum = sum;
+    }
+
+    /// Returns the sum.
+    pub fn sum(&self) -> Scalar {
+        (self.sum + self.compensation) as Scalar
+    }
+}
+
+impl Extend<Scalar> for KahanSum {
+    fn extend<I: IntoIterator<Item = Scalar>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|value| self.add(value));
+    }
+}
+
+impl FromIterator<Scalar> for KahanSum {
+    fn from_iter<I: IntoIterator<Item = Scalar>>(iter: I) -> Self {
+        let mut sum = Self::new();
+        sum.extend(iter);
+        sum
+    }
+}
+
+/// How to sum a sequence of values, trading speed for accuracy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Summation {
+    /// Adds the values one after another. This is the fastest, but the rounding error grows
+    /// linearly with the number of values.
+    #[default]
+    Naive,
+    /// Uses compensated summation, see [`KahanSum`].
+    Compensated,
+}
+
+impl Summation {
+    /// Sums `values`.
+    pub fn sum(self, values: impl IntoIterator<Item = Scalar>) -> Scalar {
+        match self {
+            Self::Naive => values.into_iter().sum(),
+            Self::Compensated => values.into_iter().collect::<KahanSum>().sum(),
+        }
+    }
+}
+
+/// A running element-wise sum of vectors of the same length, such as the updates of the samples
+/// of a batch, using a [`Summation`].
+///
+/// # Examples
+/// ```rust
+/// use rann_base::reduce::{ElementwiseSum, Summation};
+///
+/// let mut sum = ElementwiseSum::new(Summation::Compensated, 2);
+/// for _ in 0..1_000_000 {
+///     sum.add([0.1, 1.0]);
+/// }
+/// assert_eq!(sum.to_vec(), [100_000.0, 1_000_000.0]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ElementwiseSum(Sums);
+
+#[derive(Clone, Debug, PartialEq)]
+enum Sums {
+    Naive(Vec<Scalar>),
+    Compensated(Vec<KahanSum>),
+}
+
+impl ElementwiseSum {
+    /// Creates sums of zero of `len` elements, which are summed using `summation`.
+    pub fn new(summation: Summation, len: usize) -> Self {
+        Self(match summation {
+            Summation::Naive => Sums::Naive(vec![0.0; len]),
+            Summation::Compensated => Sums::Compensated(vec![KahanSum::new(); len]),
+        })
+    }
+
+    /// Adds `values` to the sums element-wise.
+    ///
+    /// # Panics
+    /// Panics if the number of values differs from the number of sums.
+    pub fn add<I>(&mut self, values: I)
+    where
+        I: IntoIterator<Item = Scalar>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let values = values.into_iter();
+        assert_eq!(
+            values.len(),
+            self.len(),
+            "All vectors should have the same length."
+        );
+        match &mut self.0 {
+            Sums::Naive(sums) => sums.iter_mut().zip(values).for_each(|(s, v)| *s += v),
+            Sums::Compensated(sums) => sums.iter_mut().zip(values).for_each(|(s, v)| s.add(v)),
+        }
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            Sums::Naive(sums) => sums.len(),
+            Sums::Compensated(sums) => sums.len(),
+        }
+    }
+
+    /// Returns whether there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the sums.
+    pub fn to_vec(&self) -> Vec<Scalar> {
+        match &self.0 {
+            Sums::Naive(sums) => sums.clone(),
+            Sums::Compensated(sums) => sums.iter().map(KahanSum::sum).collect(),
+        }
+    }
+}
@@ -0,0 +1,266 @@
+/*!
+Reinforcement learning using policy gradients.
+
+A [`Policy`] wraps a network that maps a state to a logit for each of `N` actions. Actions are
+sampled from the softmax of the logits, and after an episode the policy is trained with the
+REINFORCE algorithm: the log-probability of every taken action is increased in proportion to the
+discounted return that followed it.
+
+The gradients of the policy are passed to the network using [`Network::train_deriv`], so any
+network with `N` outputs can be used as a policy.
+
+For value-based methods such as DQN, a [`ReplayBuffer`] stores past experience to train on, and a
+[`TargetNetwork`] keeps a frozen copy of the trained network to compute stable targets with.
+*/
+
+use fastrand::Rng;
+use rann_traits::{Intermediate, Network, Scalar};
+
+use crate::{activ::softmax, sample::draw};
+
+/// Returns the discounted return of every step of an episode with the given `rewards`: the sum
+/// of all following rewards, discounted by `gamma` for every step.
+pub fn discounted_returns(rewards: &[Scalar], gamma: Scalar) -> Vec<Scalar> {
+    let mut returns = vec![0.0; rewards.len()];
+    let mut acc = 0.0;
+    for (ret, reward) in returns.iter_mut().zip(rewards).rev() {
+        acc = reward + gamma * acc;
+        *ret = acc;
+    }
+    returns
+}
+
+/// Normalizes `values` to a mean of zero and a standard deviation of one, which reduces the
+/// variance of policy gradients. Values that are all equal are set to zero.
+pub fn normalize(values: &mut [Scalar]) {
+    let n = values.len() as Scalar;
+    let mean = values.iter().sum::<Scalar>() / n;
+    let var = values
+        .iter()
+        .map(|v| (v - mean) * (v - mean))
+        .sum::<Scalar>()
+        / n;
+    let std = var.sqrt().max(Scalar::EPSILON);
+    for v in values {
+        *v = (*v - mean) / std;
+    }
+}
+
+/// A single step of an episode.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Step<S> {
+    /// The state the action was taken in.
+    pub state: S,
+    /// The taken action.
+    pub action: usize,
+    /// The reward received for the action.
+    pub reward: Scalar,
+}
+
+/// A stochastic policy over `N` actions. See [module level documentation](self) for more info.
+#[derive(Clone, Debug)]
+pub struct Policy<T, const N: usize> {
+    /// The network mapping states to the logits of the actions.
+    pub net: T,
+    /// The discount factor of future rewards.
+    pub gamma: Scalar,
+    /// Whether to normalize the returns of an episode before training.
+    pub normalize: bool,
+}
+
+impl<T, const N: usize> Policy<T, N>
+where
+    T: Network<Out = [Scalar; N]>,
+{
+    /// Creates a policy from `net`, discounting future rewards by `gamma` and normalizing the
+    /// returns.
+    pub fn new(net: T, gamma: Scalar) -> Self {
+        Self {
+            net,
+            gamma,
+            normalize: true,
+        }
+    }
+
+    /// Returns the probability of each action in `state`.
+    pub fn probs(&self, state: &T::In) -> [Scalar; N] {
+        softmax(&self.net.eval(state))
+    }
+
+    /// Samples an action for `state`.
+    pub fn act(&self, state: &T::In, rng: &mut Rng) -> usize {
+        draw(&self.probs(state), rng)
+    }
+
+    /// Returns the most probable action for `state`.
+    pub fn greedy(&self, state: &T::In) -> usize {
+        let logits = self.net.eval(state);
+        (0..N)
+            .max_by(|&a, &b| logits[a].total_cmp(&logits[b]))
+            .expect("There should be at least one action.")
+    }
+
+    /// Trains the policy on an `episode` using REINFORCE.
+    pub fn reinforce(&mut self, episode: &[Step<T::In>], learning_rate: Scalar) {
+        if episode.is_empty() {
+            return;
+        }
+        let rewards: Vec<_> = episode.iter().map(|s| s.reward).collect();
+        let mut returns = discounted_returns(&rewards, self.gamma);
+        if self.normalize {
+            normalize(&mut returns);
+        }
+        for (step, ret) in episode.iter().zip(returns) {
+            let inter = self.net.intermediate(&step.state);
+            // The gradient of -ret * ln(softmax(logits)[action]) over the logits.
+            let mut grad = softmax(inter.output());
+            grad[step.action] -= 1.0;
+            let grad = grad.map(|g| g * ret);
+            self.net
+                .train_deriv(&step.state, &inter, &grad, learning_rate);
+        }
+    }
+}
+
+/// A fixed capacity buffer of experience, which overwrites the oldest entries once full.
+#[derive(Clone, Debug)]
+pub struct ReplayBuffer<T> {
+    entries: Vec<T>,
+    capacity: usize,
+    // The index of the next entry to overwrite.
+    next: usize,
+}
+
+impl<T> ReplayBuffer<T> {
+    /// Creates an empty buffer holding up to `capacity` entries.
+    ///
+    /// # Panics
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "The capacity should not be zero.");
+        Self {
+            entries: Vec::with_capacity(capacity),
+            capacity,
+            next: 0,
+        }
+    }
+
+    /// Adds `entry` to the buffer, returning the entry it replaced if the buffer was full.
+    pub fn push(&mut self, entry: T) -> Option<T> {
+        let replaced = if self.entries.len() < self.capacity {
+            self.entries.push(entry);
+            None
+        } else {
+            Some(std::mem::replace(&mut self.entries[self.next], entry))
+        };
+        self.next = (self.next + 1) % self.capacity;
+        replaced
+    }
+
+    /// Returns the number of entries in the buffer.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the buffer is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the maximum number of entries in the buffer.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Iterates over the entries, from oldest to newest.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        let (newer, older) = self.entries.split_at(self.next % self.entries.len().max(1));
+        older.iter().chain(newer)
+    }
+
+    /// Samples `batch` entries uniformly with replacement. Returns no entries if the buffer is
+    /// empty.
+    pub fn sample(&self, batch: usize, rng: &mut Rng) -> Vec<&T> {
+        if self.entries.is_empty() {
+            return Vec::new();
+        }
+        (0..batch)
+            .map(|_| &self.entries[rng.usize(..self.entries.len())])
+            .collect()
+    }
+
+    /// Removes all entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.next = 0;
+    }
+}
+
+/// A frozen copy of a network, which is synchronized with the trained network every `period`
+/// steps.
+///
+/// # Examples
+/// ```rust
+/// use rann_base::{activ::Logistic, rl::TargetNetwork, Full};
+/// use rann_traits::Network;
+///
+/// let gen = (|i, j| (i + j) as f32 * 0.1, |_| 0.0);
+/// let mut online = Full::<2, 1, _>::new(Logistic, gen);
+/// let mut target = TargetNetwork::new(&online, 2);
+///
+/// let inter = online.intermediate(&[1.0, 1.0]);
+/// online.train_deriv(&[1.0, 1.0], &inter, &[1.0], 0.5);
+/// assert!(!target.step(&online));
+/// assert_ne!(target.net().eval(&[1.0, 1.0]), online.eval(&[1.0, 1.0]));
+/// // Synchronized every second step.
+/// assert!(target.step(&online));
+/// assert_eq!(target.net().eval(&[1.0, 1.0]), online.eval(&[1.0, 1.0]));
+/// ```
+#[derive(Clone, Debug)]
+pub struct TargetNetwork<T> {
+    net: T,
+    /// The number of steps between synchronizations.
+    pub period: usize,
+    steps: usize,
+}
+
+impl<T> TargetNetwork<T>
+where
+    T: Clone,
+{
+    /// Creates a copy of `online`, synchronized every `period` steps.
+    ///
+    /// # Panics
+    /// Panics if `period` is zero.
+    pub fn new(online: &T, period: usize) -> Self {
+        assert!(period > 0, "The period should not be zero.");
+        Self {
+            net: online.clone(),
+            period,
+            steps: 0,
+        }
+    }
+
+    /// Borrows the frozen network.
+    pub fn net(&self) -> &T {
+        &self.net
+    }
+
+    /// Counts a training step of `online`, and synchronizes with it if `period` steps have
+    /// passed since the last synchronization. Returns whether it was synchronized.
+    pub fn step(&mut self, online: &T) -> bool {
+        self.steps += 1;
+        if self.steps >= self.period {
+            self.sync(online);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Synchronizes with `online` immediately.
+    pub fn sync(&mut self, online: &T) {
+        self.net.clone_from(online);
+        self.steps = 0;
+    }
+}
@@ -0,0 +1,179 @@
+/*!
+Sampling from the outputs of networks, for generative and sequence experiments.
+
+A [`Sampler`] turns the logits a network outputs into a probability distribution, and draws an
+index from it with a seeded random generator:
+- the temperature divides the logits before their softmax: below one sharpens the distribution
+  towards the most likely index, above one flattens it, see [`softmax_with_temperature()`],
+- top-k sampling only keeps the `k` most likely indices, see [`top_k()`],
+- nucleus (top-p) sampling only keeps the most likely indices whose probabilities sum to at
+  least `p`, see [`top_p()`].
+
+The filters are applied in that order, after which the remaining probabilities are
+renormalized.
+
+# Examples
+```rust
+use rann_base::sample::Sampler;
+
+let logits = [2.0, 1.0, 0.5, -1.0];
+let mut sampler = Sampler::new(7).with_temperature(0.8).with_top_k(2);
+let probs = sampler.probs(&logits);
+assert_eq!(probs[2..], [0.0, 0.0]);
+assert!(sampler.sample(&logits) < 2);
+```
+*/
+
+use fastrand::Rng;
+use rann_traits::Scalar;
+
+use crate::activ::softmax;
+
+/// Returns the softmax of `logits` divided by `temperature`.
+///
+/// # Panics
+/// Panics if `temperature` is not positive.
+pub fn softmax_with_temperature<const N: usize>(
+    logits: &[Scalar; N],
+    temperature: Scalar,
+) -> [Scalar; N] {
+    assert!(temperature > 0.0, "The temperature should be positive.");
+    softmax(&logits.map(|l| l / temperature))
+}
+
+/// Draws an index from the discrete distribution `probs`.
+pub fn draw(probs: &[Scalar], rng: &mut Rng) -> usize {
+    let mut x = rng.f32();
+    for (i, p) in probs.iter().enumerate() {
+        if x < *p {
+            return i;
+        }
+        x -= p;
+    }
+    // Rounding errors can leave some probability mass at the end.
+    probs.len() - 1
+}
+
+/// Sets all but the `k` largest of `probs` to zero, and renormalizes the others. Ties are broken
+/// by the lowest index.
+///
+/// # Panics
+/// Panics if `k` is zero.
+pub fn top_k(probs: &mut [Scalar], k: usize) {
+    assert!(k > 0, "At least one index should be kept.");
+    let order = descending(probs);
+    for &i in order.iter().skip(k) {
+        probs[i] = 0.0;
+    }
+    renormalize(probs);
+}
+
+/// Keeps the smallest set of the largest of `probs` whose sum is at least `p`, sets the others
+/// to zero, and renormalizes the kept probabilities. The largest probability is always kept.
+///
+/// # Panics
+/// Panics if `p` is not in `(0, 1]`.
+pub fn top_p(probs: &mut [Scalar], p: Scalar) {
+    assert!(p > 0.0 && p <= 1.0, "The nucleus {p} should be in (0, 1].");
+    let order = descending(probs);
+    let mut sum = 0.0;
+    let mut kept = order.len();
+    for (n, &i) in order.iter().enumerate() {
+        sum += probs[i];
+        if sum >= p {
+            kept = n + 1;
+            break;
+        }
+    }
+    for &i in &order[kept..] {
+        probs[i] = 0.0;
+    }
+    renormalize(probs);
+}
+
+// Returns the indices of `probs` from the largest to the smallest probability.
+fn descending(probs: &[Scalar]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..probs.len()).collect();
+    order.sort_by(|&a, &b| probs[b].total_cmp(&probs[a]).then(a.cmp(&b)));
+    order
+}
+
+fn renormalize(probs: &mut [Scalar]) {
+    let sum: Scalar = probs.iter().sum();
+    if sum > 0.0 {
+        for p in probs {
+            *p /= sum;
+        }
+    }
+}
+
+/// Draws indices from the logits output by a network. See [module level documentation](self)
+/// for more info.
+#[derive(Clone, Debug)]
+pub struct Sampler {
+    /// The temperature the logits are divided by.
+    pub temperature: Scalar,
+    /// The number of most likely indices to keep, or all if `None`.
+    pub top_k: Option<usize>,
+    /// The probability mass of most likely indices to keep, or all if `None`.
+    pub top_p: Option<Scalar>,
+    rng: Rng,
+}
+
+impl Sampler {
+    /// Creates a sampler with a temperature of one, keeping all indices, seeded by `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            temperature: 1.0,
+            top_k: None,
+            top_p: None,
+            rng: Rng::with_seed(seed),
+        }
+    }
+
+    /// Divides the logits by `temperature`.
+    pub fn with_temperature(self, temperature: Scalar) -> Self {
+        Self {
+            temperature,
+            ..self
+        }
+    }
+
+    /// Only keeps the `k` most likely indices, see [`top_k()`].
+    pub fn with_top_k(self, k: usize) -> Self {
+        Self {
+            top_k: Some(k),
+            ..self
+        }
+    }
+
+    /// Only keeps the most likely indices with a probability mass of `p`, see [`top_p()`].
+    pub fn with_top_p(self, p: Scalar) -> Self {
+        Self {
+            top_p: Some(p),
+            ..self
+        }
+    }
+
+    /// Returns the distribution indices are drawn from for `logits`.
+    ///
+    /// # Panics
+    /// Panics if the temperature is not positive, if the top k is zero or if the top p is not
+    /// in `(0, 1]`.
+    pub fn probs<const N: usize>(&self, logits: &[Scalar; N]) -> [Scalar; N] {
+        let mut probs = softmax_with_temperature(logits, self.temperature);
+        if let Some(k) = self.top_k {
+            top_k(&mut probs, k);
+        }
+        if let Some(p) = self.top_p {
+            top_p(&mut probs, p);
+        }
+        probs
+    }
+
+    /// Draws an index from the distribution of [`Self::probs()`] for `logits`.
+    pub fn sample<const N: usize>(&mut self, logits: &[Scalar; N]) -> usize {
+        let probs = self.probs(logits);
+        draw(&probs, &mut self.rng)
+    }
+}
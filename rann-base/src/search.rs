@@ -0,0 +1,117 @@
+/*!
+Parallel hyperparameter search.
+
+A search runs a number of [`Trial`]s in parallel, each training a network with its own
+hyperparameters, and ranks them by their validation error. Every trial gets its own [`Rng`],
+seeded by the trial, such that the results do not depend on the scheduling of the trials.
+
+Networks of different sizes have different types, so the network of a trial is constructed and
+trained by a user supplied function, which can match on the hyperparameters of the trial.
+
+# Examples
+```rust
+use rann_base::{activ::Logistic, error::SquareError, search, testing, train, Full};
+use rann_traits::Network;
+
+let trials = search::grid(&[2, 4], &[0.1, 0.5], 0x5eed);
+let results = search::search(trials, |trial, rng| {
+    let gen = testing::seeded_gen(rng.u64(..));
+    let dataset = testing::XOR;
+    macro_rules! run {
+        ($hidden:literal) => {{
+            let mut net = Full::<2, $hidden, _>::new(Logistic, gen.clone())
+                .chain(Full::<$hidden, 1, _>::new(Logistic, gen))
+                .chain(SquareError { expected: [0.0] });
+            for _ in 0..100 {
+                train::train_epoch(&mut net, &dataset, trial.learning_rate);
+            }
+            train::mean_error(&mut net, &dataset)
+        }};
+    }
+    match trial.params {
+        2 => run!(2),
+        _ => run!(4),
+    }
+});
+println!("Best hidden size: {}", results[0].trial.params);
+```
+*/
+
+use fastrand::Rng;
+use rann_traits::{LearningRate, Scalar};
+use rayon::prelude::*;
+
+/// A single configuration of hyperparameters to train a network with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Trial<P> {
+    /// User defined hyperparameters, such as layer sizes and activation functions.
+    pub params: P,
+    /// The learning rate.
+    pub learning_rate: LearningRate,
+    /// The seed of the random number generator of this trial.
+    pub seed: u64,
+}
+
+/// The result of a [`Trial`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrialResult<P> {
+    /// The trial.
+    pub trial: Trial<P>,
+    /// The validation error of the trained network.
+    pub validation_error: Scalar,
+}
+
+/// Returns a trial for every combination of `params` and `learning_rates`, each with a different
+/// seed derived from `seed`.
+pub fn grid<P: Clone>(params: &[P], learning_rates: &[Scalar], seed: u64) -> Vec<Trial<P>> {
+    let mut rng = Rng::with_seed(seed);
+    params
+        .iter()
+        .flat_map(|p| {
+            learning_rates.iter().map(move |&learning_rate| Trial {
+                params: p.clone(),
+                learning_rate: learning_rate.into(),
+                seed: 0,
+            })
+        })
+        .map(|trial| Trial {
+            seed: rng.u64(..),
+            ..trial
+        })
+        .collect()
+}
+
+/// Runs all `trials` in parallel and returns their results, ordered from the lowest to the
+/// highest validation error.
+///
+/// `run` trains a network for a trial, using the given random number generator for all
+/// randomness, and returns its validation error. Trials with a NaN validation error are ordered
+/// last.
+pub fn search<P, F>(trials: Vec<Trial<P>>, run: F) -> Vec<TrialResult<P>>
+where
+    P: Send,
+    F: Fn(&Trial<P>, &mut Rng) -> Scalar + Sync,
+{
+    let mut results: Vec<_> = trials
+        .into_par_iter()
+        .map(|trial| {
+            let mut rng = Rng::with_seed(trial.seed);
+            let validation_error = run(&trial, &mut rng);
+            TrialResult {
+                trial,
+                validation_error,
+            }
+        })
+        .collect();
+    results.sort_by(|a, b| {
+        let key = |r: &TrialResult<P>| {
+            if r.validation_error.is_nan() {
+                Scalar::INFINITY
+            } else {
+                r.validation_error
+            }
+        };
+        key(a).total_cmp(&key(b))
+    });
+    results
+}
@@ -0,0 +1,278 @@
+/*!
+Sequence-to-sequence networks, which encode a source sequence into a state and decode a target
+sequence from it.
+
+An [`EncoderDecoder`] runs a [`Recurrent`] encoder over the `S` steps of the source, and passes its
+final state to a [`Recurrent`] decoder as its initial state. After every step of the decoder, a
+head maps its state to the outputs of that step, such as the logits of a token. The decoder takes
+the outputs of the previous step as the inputs of a step, and inputs of zeros in the first step.
+
+During training, the decoder uses teacher forcing: the network takes the source and the expected
+target sequence as inputs, and the decoder takes the expected outputs of the previous step rather
+than its own. The gradients over the state of the decoder are passed back to the final state of
+the encoder, such that both are trained together. At inference, [`EncoderDecoder::generate()`]
+runs the decoder freely, one step at a time, on its own outputs.
+
+# Examples
+```rust
+use rann_base::{
+    activ::LeakyRelu,
+    recurrent::{Gru, Recurrent},
+    seq2seq::EncoderDecoder,
+    testing, Full,
+};
+use rann_traits::Network;
+
+// Encodes sequences of three steps, and decodes sequences of two steps.
+let mut net = EncoderDecoder::new(
+    Recurrent::<_, 3>::new(Gru::<1, 4>::new(testing::seeded_gen(1))),
+    Recurrent::<_, 2>::new(Gru::<2, 4>::new(testing::seeded_gen(2))),
+    Full::<4, 2, _>::new(LeakyRelu(1.0), testing::seeded_gen(3)),
+);
+let source = [[1.0], [0.0], [-1.0]];
+let target = [[1.0, 0.0], [0.0, 1.0]];
+
+// Teacher forcing: the decoder takes the expected outputs of the previous step.
+let inter = net.intermediate(&(source, target));
+let outputs = net.eval(&(source, target));
+net.train_deriv(&(source, target), &inter, &outputs, 0.01);
+
+// Free-running generation, of which the first step takes inputs of zeros, as with teacher
+// forcing.
+let generated = net.generate(&source);
+assert_eq!(generated[0], net.eval(&(source, [[0.0; 2]; 2]))[0]);
+```
+*/
+
+use std::any::Any;
+
+use rann_traits::{
+    compose::{TimeDistributed, TimeDistributedInter},
+    inspect::{Inspect, LayerView},
+    params::Parameterized,
+    Intermediate, Network, Scalar,
+};
+
+use crate::recurrent::{Recurrent, RecurrentInter};
+
+/// A recurrent encoder of sequences of `S` steps, of which the final state is the initial state
+/// of a recurrent decoder of sequences of `T` steps. See [module level documentation](self) for
+/// more info.
+#[derive(Clone, Debug)]
+pub struct EncoderDecoder<E, D, O, const S: usize, const T: usize> {
+    /// The encoder of the source.
+    pub encoder: Recurrent<E, S>,
+    /// The decoder of the target.
+    pub decoder: Recurrent<D, T>,
+    /// The head that maps the state of the decoder after every step to the outputs of that step.
+    pub head: TimeDistributed<O, T>,
+}
+
+impl<E, D, O, const S: usize, const T: usize> EncoderDecoder<E, D, O, S, T> {
+    /// Connects `encoder` to `decoder`, of which every state is mapped to outputs by `head`.
+    pub fn new(encoder: Recurrent<E, S>, decoder: Recurrent<D, T>, head: O) -> Self {
+        Self {
+            encoder,
+            decoder,
+            head: TimeDistributed::new(head),
+        }
+    }
+}
+
+impl<E, D, O, const I: usize, const H: usize, const J: usize, const S: usize, const T: usize>
+    EncoderDecoder<E, D, O, S, T>
+where
+    E: Network<In = ([Scalar; H], [Scalar; I]), Out = [Scalar; H]>,
+    D: Network<In = ([Scalar; H], [Scalar; J]), Out = [Scalar; H]>,
+    O: Network<In = [Scalar; H], Out = [Scalar; J]>,
+{
+    /// Returns the final state of the encoder for `source`.
+    pub fn encode(&self, source: &[[Scalar; I]; S]) -> [Scalar; H] {
+        *self.encoder.intermediate(source).state()
+    }
+
+    /// Decodes the outputs of every step from the final state of the encoder for `source`,
+    /// running the decoder freely: every step takes the outputs of the previous step.
+    pub fn generate(&self, source: &[[Scalar; I]; S]) -> [[Scalar; J]; T] {
+        self.generate_with(source, |outputs| *outputs)
+    }
+
+    /// Decodes the outputs of every step like [`Self::generate()`], but every step takes the
+    /// outputs of the previous step mapped by `next`, such as to the one-hot encoding of the most
+    /// likely token.
+    pub fn generate_with(
+        &self,
+        source: &[[Scalar; I]; S],
+        mut next: impl FnMut(&[Scalar; J]) -> [Scalar; J],
+    ) -> [[Scalar; J]; T] {
+        let mut state = self.encode(source);
+        let mut inputs = [0.0; J];
+        std::array::from_fn(|_| {
+            state = self.decoder.cell.eval(&(state, inputs));
+            let outputs = self.head.network.eval(&state);
+            inputs = next(&outputs);
+            outputs
+        })
+    }
+}
+
+// The decoder takes inputs of zeros, followed by the teacher sequence without its last step.
+fn forced_inputs<const J: usize, const T: usize>(teacher: &[[Scalar; J]; T]) -> [[Scalar; J]; T] {
+    std::array::from_fn(|t| if t == 0 { [0.0; J] } else { teacher[t - 1] })
+}
+
+impl<E, D, O, const I: usize, const H: usize, const J: usize, const S: usize, const T: usize>
+    Network for EncoderDecoder<E, D, O, S, T>
+where
+    E: Network<In = ([Scalar; H], [Scalar; I]), Out = [Scalar; H]>,
+    D: Network<In = ([Scalar; H], [Scalar; J]), Out = [Scalar; H]>,
+    O: Network<In = [Scalar; H], Out = [Scalar; J]>,
+{
+    /// The source sequence, and the teacher sequence of the outputs the decoder is forced to take.
+    type In = ([[Scalar; I]; S], [[Scalar; J]; T]);
+
+    type Out = [[Scalar; J]; T];
+
+    type Inter = EncoderDecoderInter<E::Inter, D::Inter, O::Inter, H, J, S, T>;
+
+    fn intermediate(&self, (source, teacher): &Self::In) -> Self::Inter {
+        let encoder = self.encoder.intermediate(source);
+        let decoder = self.decoder.run(encoder.state(), &forced_inputs(teacher));
+        let head = self.head.intermediate(decoder.output());
+        EncoderDecoderInter {
+            encoder,
+            decoder,
+            head,
+        }
+    }
+
+    fn train_deriv(
+        &mut self,
+        (source, teacher): &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        let EncoderDecoderInter {
+            encoder,
+            decoder,
+            head,
+        } = intermediate;
+        let state_grads = self
+            .head
+            .train_deriv(decoder.output(), head, gradients, learning_rate);
+        // Backpropagate through the decoder to the final state of the encoder...
+        let (final_state_grads, decoder_input_grads) = self.decoder.backprop(
+            encoder.state(),
+            &forced_inputs(teacher),
+            decoder,
+            &state_grads,
+            learning_rate,
+        );
+        // ...and through the encoder, of which only the final state is used.
+        let mut encoder_grads = [[0.0; H]; S];
+        encoder_grads[S - 1] = final_state_grads;
+        let source_grads = self
+            .encoder
+            .train_deriv(source, encoder, &encoder_grads, learning_rate);
+        // Every step of the teacher sequence but the last is the input of the next step.
+        let teacher_grads =
+            std::array::from_fn(|t| decoder_input_grads.get(t + 1).copied().unwrap_or([0.0; J]));
+        (source_grads, teacher_grads)
+    }
+
+    fn find_layer(&self, name: &str) -> Option<&dyn Any> {
+        self.encoder
+            .find_layer(name)
+            .or_else(|| self.decoder.find_layer(name))
+            .or_else(|| self.head.find_layer(name))
+    }
+
+    fn find_layer_mut(&mut self, name: &str) -> Option<&mut dyn Any> {
+        if let Some(layer) = self.encoder.find_layer_mut(name) {
+            return Some(layer);
+        }
+        if let Some(layer) = self.decoder.find_layer_mut(name) {
+            return Some(layer);
+        }
+        self.head.find_layer_mut(name)
+    }
+}
+
+// The layers of the encoder are followed by those of the decoder and the head.
+impl<E, D, O, const I: usize, const H: usize, const J: usize, const S: usize, const T: usize>
+    Inspect for EncoderDecoder<E, D, O, S, T>
+where
+    E: Inspect<In = ([Scalar; H], [Scalar; I]), Out = [Scalar; H]>,
+    D: Inspect<In = ([Scalar; H], [Scalar; J]), Out = [Scalar; H]>,
+    O: Inspect<In = [Scalar; H], Out = [Scalar; J]>,
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        self.encoder.visit_layers(&intermediate.encoder, f);
+        self.decoder.visit_layers(&intermediate.decoder, f);
+        self.head.visit_layers(&intermediate.head, f);
+    }
+}
+
+// The parameters of the encoder are followed by those of the decoder and the head.
+impl<E, D, O, const S: usize, const T: usize> Parameterized for EncoderDecoder<E, D, O, S, T>
+where
+    E: Parameterized,
+    D: Parameterized,
+    O: Parameterized,
+{
+    fn num_params(&self) -> usize {
+        self.encoder.num_params() + self.decoder.num_params() + self.head.num_params()
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        let (encoder, params) = params.split_at_mut(self.encoder.num_params());
+        let (decoder, head) = params.split_at_mut(self.decoder.num_params());
+        self.encoder.write_params(encoder);
+        self.decoder.write_params(decoder);
+        self.head.write_params(head);
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        let (encoder, params) = params.split_at(self.encoder.num_params());
+        let (decoder, head) = params.split_at(self.decoder.num_params());
+        self.encoder.read_params(encoder);
+        self.decoder.read_params(decoder);
+        self.head.read_params(head);
+    }
+}
+
+/// The intermediate calculations for an evaluation of an [`EncoderDecoder`].
+#[derive(Clone, Debug)]
+pub struct EncoderDecoderInter<
+    E,
+    D,
+    O,
+    const H: usize,
+    const J: usize,
+    const S: usize,
+    const T: usize,
+> {
+    /// The intermediate calculations of the encoder.
+    pub encoder: RecurrentInter<E, H, S>,
+    /// The intermediate calculations of the decoder, with teacher forcing.
+    pub decoder: RecurrentInter<D, H, T>,
+    /// The intermediate calculations of the head for every step.
+    pub head: TimeDistributedInter<O, [Scalar; J], T>,
+}
+
+impl<E, D, O, const H: usize, const J: usize, const S: usize, const T: usize> Intermediate
+    for EncoderDecoderInter<E, D, O, H, J, S, T>
+where
+    O: Intermediate<Out = [Scalar; J]>,
+{
+    type Out = [[Scalar; J]; T];
+
+    fn output(&self) -> &Self::Out {
+        self.head.output()
+    }
+
+    fn into_output(self) -> Self::Out {
+        self.head.into_output()
+    }
+}
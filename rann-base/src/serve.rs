@@ -0,0 +1,217 @@
+/*!
+Serving a trained network over TCP, to test it interactively.
+
+[`serve()`] answers requests on a [`TcpListener`] by evaluating a network. Every request is a
+JSON array of numbers holding the inputs, and every response a JSON array holding the outputs, or
+a JSON object `{"error": "..."}` if the request is invalid. Two protocols are understood, so
+models can be tried out with `nc` or `curl` alike:
+
+- Plain lines: every line sent on a connection is a request, and is answered with one line.
+- HTTP: a connection starting with a `POST` request line is answered with a single HTTP response,
+  taking the body of the request as the inputs. The body needs a `Content-Length`, as chunked
+  requests are not supported.
+
+Every connection is handled on a thread of its own, such that a slow client does not hold up the
+others, and is closed if no data is received for [`READ_TIMEOUT`]. Networks that can not be shared
+between threads can still answer the requests of a single connection with [`handle_stream()`].
+
+```sh
+$ echo '[1, 0]' | nc localhost 8080
+[0.98134]
+$ curl -d '[1, 0]' localhost:8080
+[0.98134]
+```
+
+This module is only available with the `serve` feature.
+
+# Examples
+```rust
+use rann_base::{activ::Logistic, serve, testing, Full};
+
+let net = Full::<2, 1, _>::new(Logistic, testing::seeded_gen(1));
+let mut responses = Vec::new();
+serve::handle(&net, &b"[1, 0]\n[1]\n"[..], &mut responses).unwrap();
+
+let responses = String::from_utf8(responses).unwrap();
+let mut lines = responses.lines();
+assert!(lines.next().unwrap().starts_with('['));
+assert_eq!(lines.next(), Some(r#"{"error": "expected 2 inputs, got 1"}"#));
+```
+*/
+
+use std::{
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    thread,
+    time::Duration,
+};
+
+use rann_traits::{Network, Scalar};
+
+/// The time after which a connection on which no data is received is closed.
+pub const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Serves `net` on `listener` forever, handling every connection on a thread of its own. Errors
+/// on individual connections, such as clients disconnecting early or timing out, are ignored. See
+/// [module level documentation](self) for more info.
+pub fn serve<N, const I: usize, const O: usize>(net: &N, listener: &TcpListener) -> io::Result<()>
+where
+    N: Network<In = [Scalar; I], Out = [Scalar; O]> + Sync,
+{
+    thread::scope(|scope| {
+        for stream in listener.incoming() {
+            let stream = stream?;
+            scope.spawn(move || handle_stream(net, stream));
+        }
+        Ok(())
+    })
+}
+
+/// Answers all requests on `stream` until it is closed, or until no data is received for
+/// [`READ_TIMEOUT`].
+pub fn handle_stream<N, const I: usize, const O: usize>(
+    net: &N,
+    stream: TcpStream,
+) -> io::Result<()>
+where
+    N: Network<In = [Scalar; I], Out = [Scalar; O]>,
+{
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    handle(net, BufReader::new(stream.try_clone()?), stream)
+}
+
+/// Answers all requests read from `reader` by writing to `writer`, until `reader` is exhausted.
+pub fn handle<N, const I: usize, const O: usize>(
+    net: &N,
+    mut reader: impl BufRead,
+    mut writer: impl Write,
+) -> io::Result<()>
+where
+    N: Network<In = [Scalar; I], Out = [Scalar; O]>,
+{
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 {
+        if line.starts_with("POST ") {
+            return handle_http(net, reader, writer);
+        }
+        if !line.trim().is_empty() {
+            writeln!(writer, "{}", respond(net, &line).unwrap_or_else(|e| e))?;
+            writer.flush()?;
+        }
+        line.clear();
+    }
+    Ok(())
+}
+
+// Answers a single HTTP request, of which the request line has already been read.
+fn handle_http<N, const I: usize, const O: usize>(
+    net: &N,
+    mut reader: impl BufRead,
+    mut writer: impl Write,
+) -> io::Result<()>
+where
+    N: Network<In = [Scalar; I], Out = [Scalar; O]>,
+{
+    let mut length = 0;
+    let mut chunked = false;
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidData, "invalid Content-Length")
+                })?;
+            } else if name.trim().eq_ignore_ascii_case("transfer-encoding") {
+                chunked = true;
+            }
+        }
+        header.clear();
+    }
+    let (status, body) = if chunked {
+        let message = "chunked requests are not supported, send a Content-Length instead";
+        ("501 Not Implemented", error(message))
+    } else {
+        let mut body = String::new();
+        reader.take(length).read_to_string(&mut body)?;
+        match respond(net, &body) {
+            Ok(body) => ("200 OK", body),
+            Err(body) => ("400 Bad Request", body),
+        }
+    };
+    write!(
+        writer,
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    writer.flush()
+}
+
+// Returns the outputs of `net` for the inputs in `request`, or an error object.
+fn respond<N, const I: usize, const O: usize>(net: &N, request: &str) -> Result<String, String>
+where
+    N: Network<In = [Scalar; I], Out = [Scalar; O]>,
+{
+    let values = parse_array(request).map_err(|e| error(&e))?;
+    let inputs: [Scalar; I] = values.try_into().map_err(|values: Vec<Scalar>| {
+        error(&format!("expected {I} inputs, got {}", values.len()))
+    })?;
+    Ok(format_array(&net.eval(&inputs)))
+}
+
+// Returns a JSON error object with `message`, escaped as a JSON string.
+fn error(message: &str) -> String {
+    let mut text = String::from(r#"{"error": ""#);
+    for c in message.chars() {
+        match c {
+            '"' => text.push_str("\\\""),
+            '\\' => text.push_str("\\\\"),
+            c if c.is_control() => write!(text, "\\u{:04x}", c as u32).unwrap(),
+            c => text.push(c),
+        }
+    }
+    text.push_str(r#""}"#);
+    text
+}
+
+/// Parses a JSON array of numbers, such as `[1, -0.5, 2e-3]`.
+pub fn parse_array(text: &str) -> Result<Vec<Scalar>, String> {
+    let inner = text
+        .trim()
+        .strip_prefix('[')
+        .and_then(|text| text.strip_suffix(']'))
+        .ok_or("expected a JSON array of numbers")?;
+    if inner.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    inner
+        .split(',')
+        .map(|value| {
+            let value = value.trim();
+            value
+                .parse::<Scalar>()
+                .ok()
+                .filter(|v| v.is_finite())
+                .ok_or_else(|| format!("invalid number {value}"))
+        })
+        .collect()
+}
+
+/// Formats `values` as a JSON array. Non-finite values, which JSON can not represent, are written
+/// as `null`.
+pub fn format_array(values: &[Scalar]) -> String {
+    let mut text = String::from("[");
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            text.push_str(", ");
+        }
+        if value.is_finite() {
+            write!(text, "{value}").unwrap();
+        } else {
+            text.push_str("null");
+        }
+    }
+    text.push(']');
+    text
+}
@@ -0,0 +1,117 @@
+/*!
+Streaming datasets, for training on datasets larger than memory.
+
+A [`StreamingDataset`] produces its samples from an iterator instead of a slice, such as one
+reading them from disk. The samples of every epoch are produced by a worker thread, which
+prefetches a bounded number of samples ahead into a channel, so reading the next samples overlaps
+with training on the current ones while only a few samples are in memory at any time. Train on it
+with [`Trainer::fit_streaming()`](crate::train::Trainer::fit_streaming).
+
+# Examples
+```rust
+use rann_base::{
+    activ::Logistic, error::SquareError, stream::StreamingDataset, testing, train::Trainer, Full,
+};
+use rann_traits::Network;
+
+let mut net = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1))
+    .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)))
+    .chain(SquareError { expected: [0.0] });
+// E.g. reading and parsing the lines of a file every epoch.
+let dataset = StreamingDataset::new(|| testing::XOR.into_iter(), 16);
+let fit = Trainer { epochs: 10, ..Default::default() }.fit_streaming(&mut net, &dataset);
+assert_eq!(fit.steps, 40);
+```
+*/
+
+use std::{
+    panic,
+    sync::mpsc::{self, Receiver},
+    thread::{self, JoinHandle},
+};
+
+/// A dataset producing its samples anew every epoch, prefetched on a worker thread. See
+/// [module level documentation](self) for more info.
+#[derive(Clone, Debug)]
+pub struct StreamingDataset<F> {
+    source: F,
+    /// The number of samples the worker thread produces ahead of training.
+    pub prefetch: usize,
+}
+
+impl<F, I> StreamingDataset<F>
+where
+    F: Fn() -> I,
+    I: IntoIterator,
+    I::IntoIter: Send + 'static,
+    I::Item: Send + 'static,
+{
+    /// Creates a dataset producing the samples of every epoch from the iterator returned by
+    /// `source`, prefetching at most `prefetch` samples.
+    pub fn new(source: F, prefetch: usize) -> Self {
+        Self { source, prefetch }
+    }
+
+    /// Returns an iterator over the samples of one epoch.
+    pub fn iter(&self) -> Prefetch<I::Item> {
+        Prefetch::new((self.source)().into_iter(), self.prefetch)
+    }
+}
+
+/// An iterator whose items are produced ahead on a worker thread, see [`StreamingDataset`].
+///
+/// Dropping it stops the worker thread after the item it is producing.
+#[derive(Debug)]
+pub struct Prefetch<T> {
+    receiver: Option<Receiver<T>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl<T: Send + 'static> Prefetch<T> {
+    /// Starts producing the items of `iter` on a worker thread, at most `capacity` items ahead
+    /// of the consumer.
+    pub fn new(iter: impl Iterator<Item = T> + Send + 'static, capacity: usize) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        let worker = thread::spawn(move || {
+            for item in iter {
+                // The consumer was dropped.
+                if sender.send(item).is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            receiver: Some(receiver),
+            worker: Some(worker),
+        }
+    }
+}
+
+impl<T> Iterator for Prefetch<T> {
+    type Item = T;
+
+    /// Returns the next item, waiting for the worker thread to produce it.
+    ///
+    /// # Panics
+    /// Panics if the iterator of the worker thread panicked.
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.receiver.as_ref()?.recv().ok();
+        if item.is_none() {
+            self.receiver = None;
+            if let Some(Err(payload)) = self.worker.take().map(JoinHandle::join) {
+                panic::resume_unwind(payload);
+            }
+        }
+        item
+    }
+}
+
+impl<T> Drop for Prefetch<T> {
+    fn drop(&mut self) {
+        // Disconnect first, such that a worker waiting to send stops.
+        self.receiver = None;
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
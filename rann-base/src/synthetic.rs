@@ -0,0 +1,131 @@
+/*!
+Synthetic toy datasets, for tests and examples.
+
+Every generator draws its samples from a generator seeded with `seed`, such that datasets are
+reproducible, and returns a dataset of arrays, as trained on by networks of [`Full`](crate::Full)
+layers. The samples cycle through the classes (or the corners, for XOR), so the datasets can be
+trained on in order.
+
+- [`xor()`]: noisy corners of the unit square, labeled with the XOR of their coordinates,
+- [`spirals()`]: two interleaved spirals,
+- [`moons()`]: two interleaved half circles,
+- [`blobs()`]: Gaussian clusters, one for each of any number of classes,
+- [`sine()`]: noisy samples of the sine function, for regression.
+
+Binary classification datasets have a single target of `0.0` or `1.0`, and multiclass datasets
+have one-hot targets. [`to_vecs()`] converts a dataset to vectors, for networks with sizes
+chosen at runtime.
+
+# Examples
+```rust
+use rann_base::{activ::Logistic, error::SquareError, synthetic, testing, Full};
+use rann_traits::Network;
+
+let dataset = synthetic::moons(200, 0.1, 1);
+let mut net = Full::<2, 8, _>::new(Logistic, testing::seeded_gen(1))
+    .chain(Full::<8, 1, _>::new(Logistic, testing::seeded_gen(2)))
+    .chain(SquareError { expected: [0.0] });
+testing::assert_network_converges(&mut net, &dataset, 300, 0.5, 0.05);
+```
+*/
+
+use std::f32::consts::{PI, TAU};
+
+use fastrand::Rng;
+use rann_traits::Scalar;
+
+use crate::gen::gaussian;
+
+/// `len` samples of XOR: the corners of the unit square in the order of
+/// [`testing::XOR`](crate::testing::XOR), with Gaussian noise with standard deviation `noise`,
+/// labeled `1.0` if exactly one of their coordinates is one.
+pub fn xor(len: usize, noise: Scalar, seed: u64) -> Vec<([Scalar; 2], [Scalar; 1])> {
+    let mut rng = Rng::with_seed(seed);
+    (0..len)
+        .map(|i| {
+            let (a, b) = (((i / 2) % 2) as Scalar, (i % 2) as Scalar);
+            let inputs = [a, b].map(|x| x + noise * gaussian(&mut rng));
+            (inputs, [if a != b { 1.0 } else { 0.0 }])
+        })
+        .collect()
+}
+
+/// `len` samples of two interleaved spirals of two turns around the origin, with a radius of up
+/// to one and Gaussian noise with standard deviation `noise`, labeled `0.0` and `1.0`.
+pub fn spirals(len: usize, noise: Scalar, seed: u64) -> Vec<([Scalar; 2], [Scalar; 1])> {
+    let mut rng = Rng::with_seed(seed);
+    (0..len)
+        .map(|i| {
+            let class = i % 2;
+            let radius = rng.f32();
+            let angle = 2.0 * TAU * radius + class as Scalar * PI;
+            let inputs = [radius * angle.cos(), radius * angle.sin()]
+                .map(|x| x + noise * gaussian(&mut rng));
+            (inputs, [class as Scalar])
+        })
+        .collect()
+}
+
+/// `len` samples of two interleaved half circles with a radius of one, with Gaussian noise with
+/// standard deviation `noise`: the upper moon labeled `0.0`, and the lower `1.0`.
+pub fn moons(len: usize, noise: Scalar, seed: u64) -> Vec<([Scalar; 2], [Scalar; 1])> {
+    let mut rng = Rng::with_seed(seed);
+    (0..len)
+        .map(|i| {
+            let class = i % 2;
+            let angle = PI * rng.f32();
+            let (x, y) = (angle.cos(), angle.sin());
+            let point = if class == 0 {
+                [x, y]
+            } else {
+                [1.0 - x, 0.5 - y]
+            };
+            let inputs = point.map(|x| x + noise * gaussian(&mut rng));
+            (inputs, [class as Scalar])
+        })
+        .collect()
+}
+
+/// `len` samples of `C` Gaussian clusters in `N` dimensions with standard deviation `std_dev`,
+/// labeled with one-hot targets. The centers of the clusters are drawn uniformly from
+/// `[-5, 5)` in every dimension.
+pub fn blobs<const N: usize, const C: usize>(
+    len: usize,
+    std_dev: Scalar,
+    seed: u64,
+) -> Vec<([Scalar; N], [Scalar; C])> {
+    let mut rng = Rng::with_seed(seed);
+    let centers: [[Scalar; N]; C] =
+        std::array::from_fn(|_| std::array::from_fn(|_| rng.f32() * 10.0 - 5.0));
+    (0..len)
+        .map(|i| {
+            let class = i % C;
+            let inputs = centers[class].map(|c| c + std_dev * gaussian(&mut rng));
+            let mut target = [0.0; C];
+            target[class] = 1.0;
+            (inputs, target)
+        })
+        .collect()
+}
+
+/// `len` samples of the sine function, with inputs drawn uniformly from `[-π, π)` and Gaussian
+/// noise with standard deviation `noise` added to the targets.
+pub fn sine(len: usize, noise: Scalar, seed: u64) -> Vec<([Scalar; 1], [Scalar; 1])> {
+    let mut rng = Rng::with_seed(seed);
+    (0..len)
+        .map(|_| {
+            let x = PI * (2.0 * rng.f32() - 1.0);
+            ([x], [x.sin() + noise * gaussian(&mut rng)])
+        })
+        .collect()
+}
+
+/// Converts a dataset of arrays into a dataset of vectors.
+pub fn to_vecs<const N: usize, const T: usize>(
+    dataset: &[([Scalar; N], [Scalar; T])],
+) -> Vec<(Vec<Scalar>, Vec<Scalar>)> {
+    dataset
+        .iter()
+        .map(|(inputs, target)| (inputs.to_vec(), target.to_vec()))
+        .collect()
+}
@@ -0,0 +1,86 @@
+/*!
+Utilities for testing networks and layers.
+
+These are the fixtures and assertions used by the tests of RANN itself, such that users writing
+their own layers can test them in the same way. All randomness is seeded, such that failing tests
+can be reproduced.
+
+With the `proptest` feature enabled, [`strategies`] provides [proptest](https://docs.rs/proptest)
+strategies for generating layers and their inputs.
+*/
+
+#[cfg(feature = "proptest")]
+pub mod strategies;
+
+use fastrand::Rng;
+use rann_traits::{compose::Chain, Network, Scalar, Supervised};
+
+use crate::{activ::Logistic, error::SquareError, train, Full};
+
+/// The XOR function as a dataset, with `0.0` for false and `1.0` for true.
+pub const XOR: [([Scalar; 2], [Scalar; 1]); 4] = [
+    ([0.0, 0.0], [0.0]),
+    ([0.0, 1.0], [1.0]),
+    ([1.0, 0.0], [1.0]),
+    ([1.0, 1.0], [0.0]),
+];
+
+/// Returns generators for weights and biases that draw from `[-2, 2)`, like [`crate::gen::Random`],
+/// but seeded with `seed`.
+///
+/// Clones of the generators continue from the same state, so layers created from clones of the
+/// same generators start out equal.
+pub fn seeded_gen(
+    seed: u64,
+) -> (
+    impl FnMut(usize, usize) -> Scalar + Clone,
+    impl FnMut(usize) -> Scalar + Clone,
+) {
+    let rng = Rng::with_seed(seed);
+    (
+        {
+            let mut rng = rng.clone();
+            move |_, _| rng.f32() * 4.0 - 2.0
+        },
+        {
+            let mut rng = rng.clone();
+            move |_| rng.f32() * 4.0 - 2.0
+        },
+    )
+}
+
+/// The network of [`xor_net()`].
+pub type XorNet = Chain<Chain<Full<2, 3, Logistic>, Full<3, 1, Logistic>>, SquareError<1>>;
+
+/// Returns a network that can learn [`XOR`]: a [`Full`] layer of three hidden neurons and one of
+/// an output neuron, with [`Logistic`] activations, ending in a [`SquareError`]. The parameters
+/// of the layers are generated by [`seeded_gen()`] with `seed` and `seed + 1`.
+pub fn xor_net(seed: u64) -> XorNet {
+    Full::new(Logistic, seeded_gen(seed))
+        .chain(Full::new(Logistic, seeded_gen(seed + 1)))
+        .chain(SquareError { expected: [0.0] })
+}
+
+/// Trains `net` on `dataset` for `epochs` epochs and returns the final mean error.
+///
+/// # Panics
+/// Panics if the error becomes NaN during training (the network diverges), or if the final mean
+/// error over the dataset is larger than `tolerance` (the network does not converge).
+pub fn assert_network_converges<N: Supervised>(
+    net: &mut N,
+    dataset: &[(N::In, N::Target)],
+    epochs: usize,
+    learning_rate: Scalar,
+    tolerance: Scalar,
+) -> Scalar {
+    for epoch in 0..epochs {
+        let err = train::train_epoch(net, dataset, learning_rate);
+        assert!(!err.is_nan(), "Network diverged in epoch {epoch}.");
+    }
+    let err = train::mean_error(net, dataset);
+    assert!(
+        err <= tolerance,
+        "Mean error {err} is larger than the tolerance {tolerance} after {epochs} epochs."
+    );
+    err
+}
@@ -0,0 +1,48 @@
+/*!
+[Proptest](https://docs.rs/proptest) strategies for generating layers and their inputs.
+*/
+
+use proptest::{array::uniform, collection::vec, prelude::*};
+use rann_traits::{deriv::Deriv, Scalar};
+
+use crate::{Full, LayerNorm};
+
+/// Strategy for finite scalars in `[-range, range]`.
+pub fn scalar(range: Scalar) -> impl Strategy<Value = Scalar> + Clone {
+    -range..=range
+}
+
+/// Strategy for inputs of `N` scalars in `[-range, range]`.
+pub fn inputs<const N: usize>(range: Scalar) -> impl Strategy<Value = [Scalar; N]> + Clone {
+    uniform(scalar(range))
+}
+
+/// Strategy for fully connected layers with weights and biases in `[-range, range]`.
+pub fn full<const NUM_IN: usize, const NUM_OUT: usize, A>(
+    activation: A,
+    range: Scalar,
+) -> impl Strategy<Value = Full<NUM_IN, NUM_OUT, A>>
+where
+    A: Deriv<In = Scalar, Out = Scalar> + Clone + std::fmt::Debug,
+{
+    (
+        vec(scalar(range), NUM_IN * NUM_OUT),
+        vec(scalar(range), NUM_OUT),
+    )
+        .prop_map(move |(weights, biases)| {
+            Full::new(
+                activation.clone(),
+                (|row, col| weights[row * NUM_IN + col], |i| biases[i]),
+            )
+        })
+}
+
+/// Strategy for layer normalizations with their scale and shift in `[-range, range]`.
+pub fn layer_norm<const N: usize>(range: Scalar) -> impl Strategy<Value = LayerNorm<N>> {
+    (inputs(range), inputs(range)).prop_map(|(scale, shift)| {
+        let mut norm = LayerNorm::new();
+        norm.scale = scale;
+        norm.shift = shift;
+        norm
+    })
+}
@@ -0,0 +1,847 @@
+/*!
+Training of supervised networks on datasets.
+
+A dataset is a slice of pairs of inputs and targets. The networks are trained using
+[`Supervised`], which sets the target of the error function the network ends in.
+
+A [`Trainer`] trains a network for a number of epochs, and can be stopped cleanly from another
+thread (or a Ctrl-C handler) using a [`CancellationToken`]. It can write checkpoints during
+training and resume from them, see [`crate::checkpoint`], and train through the stages of a
+[`Curriculum`], see [`crate::curriculum`], and on datasets larger than memory, see
+[`crate::stream`]. Samples can be augmented as they are trained on, see [`crate::augment`], and
+students can be distilled from teachers, see [`crate::distill`]. Networks can be validated with
+other metrics than their error after every epoch, see [`crate::metrics`], and their layers can be
+dumped to files after every epoch, see [`crate::dump`]. The hardest samples can be trained on
+more often, see [`crate::mining`]. While debugging a run that diverges, every step can be checked
+for non-finite values with [`Trainer::fit_checked()`].
+
+Rather than after every sample, networks can also be trained once per batch of samples, with the
+average of the updates of the samples, using [`train_batch()`] and [`train_epoch_batched()`]. For
+large batches, [`train_batch_with()`] and [`train_epoch_batched_with()`] can sum the updates and
+errors with compensated summation, see [`Summation`].
+*/
+
+use std::{
+    convert::Infallible,
+    io,
+    ops::{ControlFlow, Range},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use rann_traits::{
+    guard::{self, NonFinite},
+    inspect::Inspect,
+    params::Parameterized,
+    Intermediate, LearningRate, Network, Scalar, Supervised, Terminal,
+};
+
+use fastrand::Rng;
+
+use crate::{
+    augment::{self, Augment},
+    checkpoint::{Checkpoint, Checkpointer},
+    curriculum::Curriculum,
+    dump::Dumper,
+    metrics::{Validate, Validated},
+    mining::HardMining,
+    reduce::{ElementwiseSum, Summation},
+    stream::StreamingDataset,
+};
+
+/// Evaluates `net` on `inputs`, trains it towards `target` and returns the error before training.
+pub fn train_step<N: Supervised>(
+    net: &mut N,
+    inputs: &N::In,
+    target: &N::Target,
+    learning_rate: impl Into<LearningRate>,
+) -> Scalar {
+    train_step_into(net, inputs, target, learning_rate.into(), &mut None)
+}
+
+// Like `train_step()`, but evaluates `net` into the intermediate calculations of a previous step
+// if there are any, such that their buffers are reused.
+fn train_step_into<N: Supervised>(
+    net: &mut N,
+    inputs: &N::In,
+    target: &N::Target,
+    learning_rate: LearningRate,
+    inter: &mut Option<N::Inter>,
+) -> Scalar {
+    net.set_target(target);
+    let inter = intermediate_into(net, inputs, inter);
+    let err = inter.output()[0];
+    net.train_error(inputs, inter, learning_rate);
+    err
+}
+
+// Evaluates `net` into the intermediate calculations of a previous evaluation if there are any.
+fn intermediate_into<'a, N: Network>(
+    net: &N,
+    inputs: &N::In,
+    inter: &'a mut Option<N::Inter>,
+) -> &'a N::Inter {
+    match inter {
+        Some(inter) => {
+            net.intermediate_into(inputs, inter);
+            inter
+        }
+        None => inter.insert(net.intermediate(inputs)),
+    }
+}
+
+/// Trains `net` on every sample of `dataset` in order, and returns the mean error over the
+/// samples before they were trained on, or zero if `dataset` is empty.
+pub fn train_epoch<N: Supervised>(
+    net: &mut N,
+    dataset: &[(N::In, N::Target)],
+    learning_rate: impl Into<LearningRate>,
+) -> Scalar {
+    let learning_rate = learning_rate.into();
+    let mut inter = None;
+    let sum: Scalar = dataset
+        .iter()
+        .map(|(inputs, target)| train_step_into(net, inputs, target, learning_rate, &mut inter))
+        .sum();
+    mean(sum, dataset.len())
+}
+
+/// Evaluates `net` on every sample of `batch`, and trains it once with the average of the
+/// updates of the samples, rather than after every sample like [`train_step()`]. Returns the mean
+/// error over the samples before training.
+///
+/// Averaging over a batch smooths out the updates of single samples that pull the network in
+/// opposite directions, so the error decreases more steadily.
+///
+/// # Panics
+/// Panics if `batch` is empty.
+pub fn train_batch<N>(
+    net: &mut N,
+    batch: &[(N::In, N::Target)],
+    learning_rate: impl Into<LearningRate>,
+) -> Scalar
+where
+    N: Supervised + Parameterized,
+{
+    train_batch_with(net, batch, learning_rate, Summation::Naive)
+}
+
+/// Trains `net` once on `batch` like [`train_batch()`], summing the updates and the errors of the
+/// samples using `summation`.
+///
+/// # Panics
+/// Panics if `batch` is empty.
+pub fn train_batch_with<N>(
+    net: &mut N,
+    batch: &[(N::In, N::Target)],
+    learning_rate: impl Into<LearningRate>,
+    summation: Summation,
+) -> Scalar
+where
+    N: Supervised + Parameterized,
+{
+    assert!(!batch.is_empty(), "The batch should not be empty.");
+    let learning_rate = learning_rate.into();
+    let before = net.params();
+    let mut after = vec![0.0; before.len()];
+    let mut updates = ElementwiseSum::new(summation, before.len());
+    let errors: Vec<Scalar> = batch
+        .iter()
+        .map(|(inputs, target)| {
+            net.read_params(&before);
+            let error = train_step(net, inputs, target, learning_rate);
+            net.write_params(&mut after);
+            updates.add(before.iter().zip(&after).map(|(b, a)| b - a));
+            error
+        })
+        .collect();
+    let len = batch.len() as Scalar;
+    for ((param, before), update) in after.iter_mut().zip(&before).zip(updates.to_vec()) {
+        *param = before - update / len;
+    }
+    net.read_params(&after);
+    summation.sum(errors) / len
+}
+
+/// Trains `net` on the batches of `batch_size` samples of `dataset` in order, like
+/// [`train_batch()`], and returns the mean error over the samples before their batches were
+/// trained on, or zero if `dataset` is empty. The last batch is smaller if `batch_size` does not
+/// divide the number of samples.
+///
+/// # Panics
+/// Panics if `batch_size` is zero.
+pub fn train_epoch_batched<N>(
+    net: &mut N,
+    dataset: &[(N::In, N::Target)],
+    batch_size: usize,
+    learning_rate: impl Into<LearningRate>,
+) -> Scalar
+where
+    N: Supervised + Parameterized,
+{
+    train_epoch_batched_with(net, dataset, batch_size, learning_rate, Summation::Naive)
+}
+
+/// Trains `net` on the batches of `dataset` like [`train_epoch_batched()`], summing the updates
+/// and the errors of the samples using `summation`, see [`train_batch_with()`].
+///
+/// # Panics
+/// Panics if `batch_size` is zero.
+pub fn train_epoch_batched_with<N>(
+    net: &mut N,
+    dataset: &[(N::In, N::Target)],
+    batch_size: usize,
+    learning_rate: impl Into<LearningRate>,
+    summation: Summation,
+) -> Scalar
+where
+    N: Supervised + Parameterized,
+{
+    assert!(batch_size > 0, "The batch size should be positive.");
+    let learning_rate = learning_rate.into();
+    let sum = summation.sum(dataset.chunks(batch_size).map(|batch| {
+        train_batch_with(net, batch, learning_rate, summation) * batch.len() as Scalar
+    }));
+    mean(sum, dataset.len())
+}
+
+/// Returns the mean error of `net` over `dataset`, without training it, or zero if `dataset` is
+/// empty.
+pub fn mean_error<N: Supervised>(net: &mut N, dataset: &[(N::In, N::Target)]) -> Scalar {
+    mean_error_with(net, dataset, Summation::Naive)
+}
+
+/// Returns the mean error of `net` over `dataset` like [`mean_error()`], summing the errors of
+/// the samples using `summation`.
+pub fn mean_error_with<N: Supervised>(
+    net: &mut N,
+    dataset: &[(N::In, N::Target)],
+    summation: Summation,
+) -> Scalar {
+    let sum = summation.sum(dataset.iter().map(|(inputs, target)| {
+        net.set_target(target);
+        net.eval(inputs)[0]
+    }));
+    mean(sum, dataset.len())
+}
+
+// Returns the mean of `len` values that sum to `sum`, or zero if there are none.
+pub(crate) fn mean(sum: Scalar, len: usize) -> Scalar {
+    if len == 0 {
+        0.0
+    } else {
+        sum / len as Scalar
+    }
+}
+
+/// A token to cooperatively cancel training. Clones share the same state, such that training
+/// can be cancelled from another thread.
+///
+/// Cancellation is only checked between training steps, so the network is never left with a
+/// partially applied update.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Creates a token that has not been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests cancellation of everything using this token.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether cancellation has been requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Cancels this token when the process receives Ctrl-C (SIGINT), instead of terminating the
+    /// process.
+    ///
+    /// # Errors
+    /// Returns an error if a Ctrl-C handler has already been set, or could not be set.
+    #[cfg(feature = "ctrlc")]
+    pub fn cancel_on_ctrl_c(&self) -> Result<(), ctrlc::Error> {
+        let token = self.clone();
+        ctrlc::set_handler(move || token.cancel())
+    }
+}
+
+/// The configuration of a training run. See [module level documentation](self) for more info.
+///
+/// # Examples
+/// ```rust
+/// use rann_base::{
+///     activ::Logistic, error::SquareError, testing, train::{CancellationToken, Trainer}, Full,
+/// };
+/// use rann_traits::Network;
+///
+/// let mut net = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1))
+///     .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)))
+///     .chain(SquareError { expected: [0.0] });
+/// let cancel = CancellationToken::new();
+/// let trainer = Trainer {
+///     epochs: 1000,
+///     cancel: Some(cancel.clone()),
+///     ..Default::default()
+/// };
+///
+/// // E.g. from another thread, or a Ctrl-C handler.
+/// cancel.cancel();
+/// let fit = trainer.fit(&mut net, &testing::XOR);
+/// assert!(fit.cancelled);
+/// assert!(fit.errors.is_empty());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Trainer {
+    /// The number of epochs to train for.
+    pub epochs: usize,
+    /// The learning rate.
+    pub learning_rate: LearningRate,
+    /// A token to stop training early, checked before every training step.
+    pub cancel: Option<CancellationToken>,
+    /// How to sum the errors of the samples of an epoch (and of the validation set). Use
+    /// [`Summation::Compensated`] for accurate error histories of large datasets.
+    pub summation: Summation,
+    /// Hard example mining: after the first epoch, every epoch trains on the hardest samples of
+    /// the dataset again, after all samples, and its error is the mean over all its steps. See
+    /// [`crate::mining`]. Not used when training on streamed, augmented or distilled samples.
+    pub mining: Option<HardMining>,
+}
+
+impl Default for Trainer {
+    fn default() -> Self {
+        Self {
+            epochs: 100,
+            learning_rate: LearningRate(0.1),
+            cancel: None,
+            summation: Summation::Naive,
+            mining: None,
+        }
+    }
+}
+
+/// The result of [`Trainer::fit()`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Fit {
+    /// The mean error of every completed epoch, before training on each sample.
+    pub errors: Vec<Scalar>,
+    /// The number of training steps taken, including those of an epoch that was cancelled.
+    pub steps: usize,
+    /// Whether training was cancelled before all epochs completed.
+    pub cancelled: bool,
+}
+
+impl Trainer {
+    /// Trains `net` on every sample of `dataset` in order, for every epoch. An empty dataset
+    /// trains no epochs.
+    ///
+    /// If cancelled, training stops before the next step, and the errors of the completed
+    /// epochs are returned.
+    pub fn fit<N: Supervised>(&self, net: &mut N, dataset: &[(N::In, N::Target)]) -> Fit {
+        let result = self.run(net, dataset, Fit::default(), |_, _| {
+            Ok::<_, Infallible>(ControlFlow::Continue(()))
+        });
+        match result {
+            Ok(fit) => fit,
+            Err(never) => match never {},
+        }
+    }
+
+    /// Trains `net` like [`Self::fit()`], checking every step for non-finite values: the inputs,
+    /// the activations of every layer, the error, the gradients and updated parameters of every
+    /// layer and the gradients over the inputs, see [`rann_traits::guard`]. Training stops at the
+    /// first non-finite value, which is returned, and the network may be corrupted by its step.
+    ///
+    /// Checking every value is slow, so this is meant for debugging a run that diverges.
+    pub fn fit_checked<N>(
+        &self,
+        net: &mut N,
+        dataset: &[(N::In, N::Target)],
+    ) -> Result<Fit, NonFinite>
+    where
+        N: Supervised + Inspect,
+        N::In: AsRef<[Scalar]>,
+    {
+        let step = |net: &mut N, inputs: &N::In, target: &N::Target, inter: &mut Option<_>| {
+            guard::check_inputs(inputs.as_ref())?;
+            net.set_target(target);
+            let inter = intermediate_into(net, inputs, inter);
+            let err = inter.output()[0];
+            guard::train_error_checked(net, inputs, inter, self.learning_rate)?;
+            Ok(err)
+        };
+        self.run_with(net, dataset, Fit::default(), step, |_, _| {
+            Ok(ControlFlow::Continue(()))
+        })
+    }
+
+    /// Trains `net` like [`Self::fit()`], while writing checkpoints using `checkpointer`.
+    ///
+    /// A checkpoint is written whenever one is due, after the last epoch, and when training is
+    /// cancelled. Checkpoints are scored by the mean error on `validation`, or by the training
+    /// error if `validation` is empty.
+    pub fn fit_checkpointed<N>(
+        &self,
+        net: &mut N,
+        dataset: &[(N::In, N::Target)],
+        validation: &[(N::In, N::Target)],
+        checkpointer: &mut Checkpointer,
+    ) -> io::Result<Fit>
+    where
+        N: Supervised + Parameterized,
+    {
+        self.checkpointed(net, dataset, validation, checkpointer, Fit::default())
+    }
+
+    /// Restores `net` and the state of training from the latest checkpoint of `checkpointer`,
+    /// and continues training it like [`Self::fit_checkpointed()`] until all epochs completed.
+    /// Starts from scratch if there is no checkpoint.
+    ///
+    /// The learning rate is restored from the checkpoint. The returned errors include those of
+    /// the epochs before the checkpoint, but the steps only count those of this call.
+    pub fn resume<N>(
+        &self,
+        net: &mut N,
+        dataset: &[(N::In, N::Target)],
+        validation: &[(N::In, N::Target)],
+        checkpointer: &mut Checkpointer,
+    ) -> io::Result<Fit>
+    where
+        N: Supervised + Parameterized,
+    {
+        match checkpointer.latest()? {
+            Some(checkpoint) => {
+                checkpoint.restore(net);
+                let trainer = Trainer {
+                    learning_rate: checkpoint.learning_rate,
+                    ..self.clone()
+                };
+                let fit = Fit {
+                    errors: checkpoint.errors,
+                    ..Default::default()
+                };
+                trainer.checkpointed(net, dataset, validation, checkpointer, fit)
+            }
+            None => self.fit_checkpointed(net, dataset, validation, checkpointer),
+        }
+    }
+
+    fn checkpointed<N>(
+        &self,
+        net: &mut N,
+        dataset: &[(N::In, N::Target)],
+        validation: &[(N::In, N::Target)],
+        checkpointer: &mut Checkpointer,
+        fit: Fit,
+    ) -> io::Result<Fit>
+    where
+        N: Supervised + Parameterized,
+    {
+        let every = checkpointer.config.every;
+        let mut save = |net: &mut N, errors: &[Scalar]| {
+            let validation_error =
+                (!validation.is_empty()).then(|| mean_error_with(net, validation, self.summation));
+            checkpointer.save(&Checkpoint {
+                epoch: errors.len(),
+                learning_rate: self.learning_rate,
+                errors: errors.to_vec(),
+                validation_error,
+                params: net.params(),
+            })
+        };
+        let fit = self.run(
+            net,
+            dataset,
+            fit,
+            |net, fit| -> io::Result<ControlFlow<()>> {
+                let epoch = fit.errors.len();
+                if (every > 0 && epoch.is_multiple_of(every)) || epoch == self.epochs {
+                    save(net, &fit.errors)?;
+                }
+                Ok(ControlFlow::Continue(()))
+            },
+        )?;
+        if fit.cancelled {
+            save(net, &fit.errors)?;
+        }
+        Ok(fit)
+    }
+
+    /// Trains `net` like [`Self::fit()`], while scoring it on `validation` with `metric` after
+    /// every epoch. Every report is passed to `report` with the result of training so far, such
+    /// as for logging, and returned. See [`crate::metrics`] for more info.
+    pub fn fit_validated<N, V>(
+        &self,
+        net: &mut N,
+        dataset: &[(N::In, N::Target)],
+        validation: &[(N::In, N::Target)],
+        metric: &V,
+        mut report: impl FnMut(&Fit, &V::Report),
+    ) -> Validated<V::Report>
+    where
+        N: Supervised,
+        V: Validate<N>,
+    {
+        let mut reports = Vec::new();
+        let result = self.run(net, dataset, Fit::default(), |net, fit| {
+            let validated = metric.validate(net, validation);
+            report(fit, &validated);
+            reports.push(validated);
+            Ok::<_, Infallible>(ControlFlow::Continue(()))
+        });
+        match result {
+            Ok(fit) => Validated { fit, reports },
+            Err(never) => match never {},
+        }
+    }
+
+    /// Trains `net` like [`Self::fit()`], while writing a dump of its layers after every epoch
+    /// using `dumper`, as well as before training as the dump of epoch zero. The activations in
+    /// the dumps are those of an evaluation on `probe`. See [`crate::dump`] for more info.
+    pub fn fit_dumped<N>(
+        &self,
+        net: &mut N,
+        dataset: &[(N::In, N::Target)],
+        dumper: &Dumper,
+        probe: &N::In,
+    ) -> io::Result<Fit>
+    where
+        N: Supervised + Inspect,
+    {
+        dumper.dump(net, probe, 0)?;
+        self.run(net, dataset, Fit::default(), |net, fit| {
+            dumper.dump(net, probe, fit.errors.len())?;
+            Ok(ControlFlow::Continue(()))
+        })
+    }
+
+    /// Trains `net` through the stages of `curriculum` in order, and returns the result of each
+    /// stage that was started. The epochs and learning rate of this trainer are ignored in favor
+    /// of those of the stages. See [`crate::curriculum`] for more info.
+    ///
+    /// If cancelled, training stops before the next step, and the result of the current stage is
+    /// the last result.
+    pub fn fit_curriculum<N>(
+        &self,
+        net: &mut N,
+        dataset: &[(N::In, N::Target)],
+        curriculum: &Curriculum<'_, (N::In, N::Target)>,
+    ) -> Vec<Fit>
+    where
+        N: Supervised,
+        N::In: Clone,
+        N::Target: Clone,
+    {
+        let mut fits = Vec::with_capacity(curriculum.stages.len());
+        for stage in &curriculum.stages {
+            let samples: Vec<_> = dataset
+                .iter()
+                .filter(|sample| (stage.filter)(sample))
+                .cloned()
+                .collect();
+            let trainer = Trainer {
+                epochs: stage.epochs,
+                learning_rate: stage.learning_rate,
+                ..self.clone()
+            };
+            let result = trainer.run(net, &samples, Fit::default(), |_, fit| {
+                let done = stage
+                    .threshold
+                    .zip(fit.errors.last())
+                    .is_some_and(|(threshold, error)| *error <= threshold);
+                Ok::<_, Infallible>(if done {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                })
+            });
+            let fit = match result {
+                Ok(fit) => fit,
+                Err(never) => match never {},
+            };
+            let cancelled = fit.cancelled;
+            fits.push(fit);
+            if cancelled {
+                break;
+            }
+        }
+        fits
+    }
+
+    /// Trains `net` on the samples of `dataset` in order, for every epoch, while the samples are
+    /// prefetched on a worker thread. See [`crate::stream`] for more info.
+    ///
+    /// If cancelled, training stops before the next step, and the errors of the completed
+    /// epochs are returned.
+    pub fn fit_streaming<N, F, I>(&self, net: &mut N, dataset: &StreamingDataset<F>) -> Fit
+    where
+        N: Supervised,
+        N::In: Send + 'static,
+        N::Target: Send + 'static,
+        F: Fn() -> I,
+        I: IntoIterator<Item = (N::In, N::Target)>,
+        I::IntoIter: Send + 'static,
+    {
+        let mut fit = Fit::default();
+        for _ in 0..self.epochs {
+            if self.train_samples(net, dataset.iter(), &mut fit).is_break() {
+                break;
+            }
+        }
+        fit
+    }
+
+    /// Trains `net` on every sample of `dataset` in order, for every epoch, while augmenting the
+    /// inputs of every sample with `augment`, using random numbers generated from `seed`. See
+    /// [`crate::augment`] for more info.
+    ///
+    /// If cancelled, training stops before the next step, and the errors of the completed
+    /// epochs are returned.
+    pub fn fit_augmented<N>(
+        &self,
+        net: &mut N,
+        dataset: &[(N::In, N::Target)],
+        augment: &impl Augment,
+        seed: u64,
+    ) -> Fit
+    where
+        N: Supervised,
+        N::In: Clone + AsMut<[Scalar]>,
+        N::Target: Clone,
+    {
+        let mut rng = Rng::with_seed(seed);
+        let mut fit = Fit::default();
+        for _ in 0..self.epochs {
+            let samples = augment::augmented(dataset, augment, &mut rng);
+            if self.train_samples(net, samples, &mut fit).is_break() {
+                break;
+            }
+        }
+        fit
+    }
+
+    /// Trains `student` on every sample of `dataset` in order, for every epoch, towards the
+    /// outputs of `teacher` as well as the labels in `dataset`. The student ends in a
+    /// [`DistillationLoss`](crate::distill::DistillationLoss), and the teacher returns logits and
+    /// is evaluated on every sample as it is trained on. See [`crate::distill`] for more info.
+    ///
+    /// If cancelled, training stops before the next step, and the errors of the completed
+    /// epochs are returned.
+    pub fn fit_distilled<T, N, const C: usize>(
+        &self,
+        teacher: &T,
+        student: &mut N,
+        dataset: &[(N::In, [Scalar; C])],
+    ) -> Fit
+    where
+        T: Network<In = N::In, Out = [Scalar; C]>,
+        N: Supervised<Target = ([Scalar; C], [Scalar; C])>,
+        N::In: Clone,
+    {
+        let mut fit = Fit::default();
+        for _ in 0..self.epochs {
+            let samples = dataset
+                .iter()
+                .map(|(inputs, labels)| (inputs.clone(), (teacher.eval(inputs), *labels)));
+            if self.train_samples(student, samples, &mut fit).is_break() {
+                break;
+            }
+        }
+        fit
+    }
+
+    // Trains on `samples` in order as one epoch, adding its mean error and steps to `fit`. Breaks
+    // if cancelled, leaving out the error of the incomplete epoch, or if there are no samples.
+    fn train_samples<N: Supervised>(
+        &self,
+        net: &mut N,
+        samples: impl Iterator<Item = (N::In, N::Target)>,
+        fit: &mut Fit,
+    ) -> ControlFlow<()> {
+        let mut steps = 0;
+        let mut cancelled = false;
+        let mut inter = None;
+        let sum = self.summation.sum(samples.map_while(|(inputs, target)| {
+            cancelled = self.is_cancelled();
+            if cancelled {
+                return None;
+            }
+            steps += 1;
+            Some(train_step_into(
+                net,
+                &inputs,
+                &target,
+                self.learning_rate,
+                &mut inter,
+            ))
+        }));
+        fit.steps += steps;
+        if cancelled {
+            fit.cancelled = true;
+            return ControlFlow::Break(());
+        }
+        if steps == 0 {
+            return ControlFlow::Break(());
+        }
+        fit.errors.push(sum / steps as Scalar);
+        ControlFlow::Continue(())
+    }
+
+    // Trains from the epoch after the errors in `fit`, calling `after_epoch` after each epoch,
+    // which can stop training early. Trains no epochs on an empty dataset.
+    fn run<N, E>(
+        &self,
+        net: &mut N,
+        dataset: &[(N::In, N::Target)],
+        fit: Fit,
+        after_epoch: impl FnMut(&mut N, &Fit) -> Result<ControlFlow<()>, E>,
+    ) -> Result<Fit, E>
+    where
+        N: Supervised,
+    {
+        let learning_rate = self.learning_rate;
+        let step = |net: &mut N, inputs: &N::In, target: &N::Target, inter: &mut Option<_>| {
+            Ok(train_step_into(net, inputs, target, learning_rate, inter))
+        };
+        self.run_with(net, dataset, fit, step, after_epoch)
+    }
+
+    // Like `run()`, but trains on every sample using `step`, which returns the error before
+    // training.
+    fn run_with<N, E>(
+        &self,
+        net: &mut N,
+        dataset: &[(N::In, N::Target)],
+        mut fit: Fit,
+        mut step: impl FnMut(&mut N, &N::In, &N::Target, &mut Option<N::Inter>) -> Result<Scalar, E>,
+        mut after_epoch: impl FnMut(&mut N, &Fit) -> Result<ControlFlow<()>, E>,
+    ) -> Result<Fit, E>
+    where
+        N: Supervised,
+    {
+        if dataset.is_empty() {
+            return Ok(fit);
+        }
+        let mut errors = Vec::with_capacity(dataset.len());
+        // The loss of every sample when it was last trained on, and the hardest samples to train
+        // on again in the next epoch.
+        let mut losses = vec![0.0; dataset.len()];
+        let mut hardest = Vec::new();
+        let mut inter = None;
+        for _ in fit.errors.len()..self.epochs {
+            errors.clear();
+            for i in (0..dataset.len()).chain(hardest.iter().copied()) {
+                if self.is_cancelled() {
+                    fit.cancelled = true;
+                    return Ok(fit);
+                }
+                let (inputs, target) = &dataset[i];
+                losses[i] = step(net, inputs, target, &mut inter)?;
+                errors.push(losses[i]);
+                fit.steps += 1;
+            }
+            let sum = self.summation.sum(errors.iter().copied());
+            fit.errors.push(sum / errors.len() as Scalar);
+            if let Some(mining) = &self.mining {
+                hardest = mining.hardest(&losses);
+            }
+            if after_epoch(net, &fit)?.is_break() {
+                break;
+            }
+        }
+        Ok(fit)
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+}
+
+/// The aggregated results of a [`cross_validate`]ion.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CrossValidation {
+    /// The metric of each fold.
+    pub scores: Vec<Scalar>,
+    /// The mean of the scores.
+    pub mean: Scalar,
+    /// The (population) standard deviation of the scores.
+    pub std_dev: Scalar,
+}
+
+impl CrossValidation {
+    /// Aggregates the scores of the folds.
+    pub fn new(scores: Vec<Scalar>) -> Self {
+        let n = scores.len() as Scalar;
+        let mean = scores.iter().sum::<Scalar>() / n;
+        let var = scores
+            .iter()
+            .map(|s| (s - mean) * (s - mean))
+            .sum::<Scalar>()
+            / n;
+        Self {
+            scores,
+            mean,
+            std_dev: var.sqrt(),
+        }
+    }
+}
+
+/// Returns the ranges of `k` contiguous folds of a dataset of length `len`, whose lengths differ
+/// by at most one.
+///
+/// # Panics
+/// Panics if `k` is zero or larger than `len`.
+pub fn folds(len: usize, k: usize) -> impl Iterator<Item = Range<usize>> {
+    assert!(
+        0 < k && k <= len,
+        "Cannot split {len} samples into {k} folds."
+    );
+    (0..k).map(move |i| (i * len / k)..((i + 1) * len / k))
+}
+
+/// Performs k-fold cross-validation of networks on `dataset`.
+///
+/// The dataset is split into `k` contiguous [`folds`], so it should be shuffled beforehand. For
+/// every fold, a fresh network is created by `factory`, trained by `train` on all other folds,
+/// and scored by `metric` on the fold itself.
+///
+/// # Panics
+/// Panics if `k` is zero or larger than the length of the dataset.
+pub fn cross_validate<N, F, T, M>(
+    dataset: &[(N::In, N::Target)],
+    k: usize,
+    mut factory: F,
+    mut train: T,
+    mut metric: M,
+) -> CrossValidation
+where
+    N: Supervised,
+    N::In: Clone,
+    N::Target: Clone,
+    F: FnMut(usize) -> N,
+    T: FnMut(&mut N, &[(N::In, N::Target)]),
+    M: FnMut(&mut N, &[(N::In, N::Target)]) -> Scalar,
+{
+    let scores = folds(dataset.len(), k)
+        .enumerate()
+        .map(|(i, fold)| {
+            let training: Vec<_> = dataset[..fold.start]
+                .iter()
+                .chain(&dataset[fold.end..])
+                .cloned()
+                .collect();
+            let mut net = factory(i);
+            train(&mut net, &training);
+            metric(&mut net, &dataset[fold])
+        })
+        .collect();
+    CrossValidation::new(scores)
+}
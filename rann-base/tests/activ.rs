@@ -0,0 +1,130 @@
+use rann_base::activ::{log_softmax, softmax, LeakyRelu, Logistic, Selu, Softplus, Tanh};
+use rann_traits::deriv::{check_deriv, check_finite, Deriv, Elementwise};
+
+const TOLERANCE: f32 = 1e-3;
+
+#[test]
+fn leaky_relu_deriv() {
+    // Zero is not differentiable, and lies halfway between two checked points.
+    for slope in [0.0, 0.01, 0.3] {
+        check_deriv(&LeakyRelu(slope), -10.0..=10.0, TOLERANCE).unwrap();
+    }
+}
+
+#[test]
+fn tanh_deriv() {
+    check_deriv(&Tanh, -20.0..=20.0, TOLERANCE).unwrap();
+    assert_eq!(Tanh.deriv(&0.0), 1.0);
+}
+
+#[test]
+fn logistic_deriv() {
+    check_deriv(&Logistic, -30.0..=30.0, TOLERANCE).unwrap();
+    assert_eq!(Logistic.deriv(&0.0), 0.25);
+}
+
+#[test]
+fn finds_wrong_derivatives() {
+    let mismatch = check_deriv(
+        &(|x: f32| x.tanh(), |x: f32| x.tanh()),
+        -5.0..=5.0,
+        TOLERANCE,
+    )
+    .unwrap_err();
+    assert_eq!(mismatch.x, -5.0);
+    assert!((mismatch.numerical - (1.0 - 5f32.tanh().powi(2))).abs() < 1e-3);
+    assert!(mismatch.to_string().starts_with("derivative"));
+    // Non-finite derivatives are mismatches.
+    assert!(check_deriv(&(|x: f32| x, |_: f32| f32::NAN), 0.0..=1.0, TOLERANCE).is_err());
+}
+
+#[test]
+fn softplus_deriv() {
+    check_deriv(&Softplus, -30.0..=30.0, TOLERANCE).unwrap();
+    assert_eq!(Softplus.call(&0.0), 2f32.ln());
+}
+
+#[test]
+fn activations_are_finite_at_extremes() {
+    check_finite(&LeakyRelu(0.01)).unwrap();
+    check_finite(&Tanh).unwrap();
+    check_finite(&Logistic).unwrap();
+    check_finite(&Softplus).unwrap();
+    let naive_softplus = (|x: f32| x.exp().ln_1p(), |x: f32| 1.0 / (1.0 + (-x).exp()));
+    assert_eq!(check_finite(&naive_softplus), Err(89.0));
+}
+
+#[test]
+fn logistic_keeps_precision_at_extremes() {
+    for x in [-80.0, -40.0, -20.0, 20.0, 40.0, 80.0] {
+        let e = (-(x as f64)).exp();
+        let (exact, deriv) = (1.0 / (1.0 + e), e / ((1.0 + e) * (1.0 + e)));
+        assert!(((Logistic.call(&x) as f64 - exact) / exact).abs() < 1e-6);
+        assert!(
+            ((Logistic.deriv(&x) as f64 - deriv) / deriv).abs() < 1e-5,
+            "{x}"
+        );
+    }
+    assert!(Logistic.call(&-100.0) > 0.0);
+    assert_eq!(Logistic.call(&-1e30), 0.0);
+    assert_eq!(Logistic.call(&1e30), 1.0);
+}
+
+#[test]
+fn softplus_is_linear_at_extremes() {
+    assert_eq!(Softplus.call(&1e4), 1e4);
+    assert_eq!(Softplus.call(&-1e4), 0.0);
+    assert!((Softplus.call(&-50.0) - (-50f32).exp()).abs() < 1e-27);
+    assert_eq!(Softplus.deriv(&1e4), 1.0);
+}
+
+#[test]
+fn elementwise_activations() {
+    let sums = [-2.0, 0.0, 3.0];
+    let arrays = Elementwise::new(Tanh);
+    assert_eq!(arrays.call(&sums), sums.map(f32::tanh));
+    assert_eq!(arrays.deriv(&sums), sums.map(|x| Tanh.deriv(&x)));
+    assert_eq!(
+        arrays.backprop(&sums, &[1.0, 2.0, 0.0]),
+        [Tanh.deriv(&-2.0), 2.0, 0.0]
+    );
+    let vectors = Elementwise::new(&LeakyRelu(0.5));
+    assert_eq!(vectors.call(&vec![-2.0, 1.0]), vec![-1.0, 1.0]);
+    assert_eq!(vectors.deriv(&vec![-2.0, 1.0]), vec![0.5, 1.0]);
+}
+
+#[test]
+#[should_panic(expected = "Lengths should be equal.")]
+fn elementwise_vectors_of_different_lengths() {
+    Elementwise::new(Logistic).backprop(&vec![1.0, 2.0], &vec![1.0]);
+}
+
+#[test]
+fn selu_deriv() {
+    check_deriv(&Selu, -20.0..=20.0, TOLERANCE).unwrap();
+    assert_eq!(Selu.call(&2.0), 2.0 * Selu::LAMBDA);
+    // Negative inputs saturate to `-λα`.
+    assert!((Selu.call(&-30.0) + Selu::LAMBDA * Selu::ALPHA).abs() < 1e-6);
+}
+
+#[test]
+fn softmax_sums_to_one() {
+    let probs = softmax(&[1000.0, 0.0, -1000.0]);
+    assert_eq!(probs, [1.0, 0.0, 0.0]);
+    let probs = softmax(&[1.0, 2.0, 3.0]);
+    assert!((probs.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+    assert!(probs[0] < probs[1] && probs[1] < probs[2]);
+}
+
+#[test]
+fn log_softmax_of_unlikely_logits() {
+    let logits = [1.0, 2.0, 3.0];
+    for (log_p, p) in log_softmax(&logits).iter().zip(softmax(&logits)) {
+        assert!((log_p - p.ln()).abs() < 1e-6);
+    }
+    // The softmax rounds to zero, but its logarithm stays finite.
+    assert_eq!(
+        log_softmax(&[1000.0, 0.0, -1000.0]),
+        [0.0, -1000.0, -2000.0]
+    );
+}
@@ -0,0 +1,68 @@
+use rann_base::{
+    activ::Logistic,
+    adversarial::{self, Attack},
+    error::SquareError,
+    testing, train, Full,
+};
+use rann_traits::{compose::Chain, Network, Supervised};
+
+type Net = Chain<Chain<Full<2, 4, Logistic>, Full<4, 1, Logistic>>, SquareError<1>>;
+
+fn net() -> Net {
+    Full::new(Logistic, testing::seeded_gen(1))
+        .chain(Full::new(Logistic, testing::seeded_gen(2)))
+        .chain(SquareError { expected: [0.0] })
+}
+
+// Points on a grid, labeled by which side of the diagonal they are on.
+fn dataset() -> Vec<([f32; 2], [f32; 1])> {
+    let mut dataset = Vec::new();
+    for i in 0..8 {
+        for j in 0..8 {
+            let (x, y) = (i as f32 / 7.0, j as f32 / 7.0);
+            if (x - y).abs() > 0.1 {
+                dataset.push(([x, y], [(x > y) as u8 as f32]));
+            }
+        }
+    }
+    dataset
+}
+
+#[test]
+fn attacks_stay_within_epsilon_and_increase_the_error() {
+    let mut net = net();
+    let (inputs, target) = ([0.2, 0.9], [1.0]);
+    net.set_target(&target);
+    let error = net.eval(&inputs)[0];
+
+    let fgsm = Attack::fgsm(0.1).perturb(&net, &inputs, &target);
+    let pgd = Attack::pgd(0.1, 10).perturb(&net, &inputs, &target);
+    for example in [fgsm, pgd] {
+        for (x, original) in example.iter().zip(inputs) {
+            assert!((x - original).abs() <= 0.1 + 1e-6);
+        }
+        assert!(net.eval(&example)[0] > error);
+    }
+
+    let bounded = Attack::fgsm(0.5)
+        .with_bounds(0.0, 1.0)
+        .perturb(&net, &inputs, &target);
+    assert!(bounded.iter().all(|x| (0.0..=1.0).contains(x)));
+}
+
+#[test]
+fn adversarial_training_improves_robustness() {
+    let dataset = dataset();
+    let attack = Attack::pgd(0.1, 5);
+    let (mut plain, mut robust) = (net(), net());
+    for _ in 0..300 {
+        train::train_epoch(&mut plain, &dataset, 1.0);
+        adversarial::train_epoch(&mut robust, &dataset, &attack, 0.5, 1.0);
+    }
+    let plain_error = adversarial::adversarial_error(&mut plain, &dataset, &attack);
+    let robust_error = adversarial::adversarial_error(&mut robust, &dataset, &attack);
+    assert!(
+        robust_error < plain_error,
+        "{robust_error} >= {plain_error}"
+    );
+}
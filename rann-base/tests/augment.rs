@@ -0,0 +1,126 @@
+use fastrand::Rng;
+use rann_base::{
+    activ::Logistic,
+    augment::{
+        self, Augment, FeatureDropout, FlipHorizontal, FlipVertical, Noise, RandomCrop, Scale,
+    },
+    error::SquareError,
+    testing,
+    train::Trainer,
+    Full,
+};
+use rann_traits::{params::Parameterized, Network};
+
+// A 3x2 image with two channels.
+const IMAGE: [f32; 12] = [
+    1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0,
+];
+
+// Applies `augment` until it changes the inputs, as flips only happen half of the time.
+fn changed(augment: impl Augment, inputs: [f32; 12]) -> [f32; 12] {
+    let mut rng = Rng::with_seed(0);
+    loop {
+        let mut augmented = inputs;
+        augment.augment(&mut augmented, &mut rng);
+        if augmented != inputs {
+            return augmented;
+        }
+    }
+}
+
+#[test]
+fn flips_images() {
+    let flip = FlipHorizontal {
+        width: 3,
+        height: 2,
+    };
+    assert_eq!(
+        changed(flip, IMAGE),
+        [3.0, 2.0, 1.0, 6.0, 5.0, 4.0, 9.0, 8.0, 7.0, 12.0, 11.0, 10.0]
+    );
+    let flip = FlipVertical {
+        width: 3,
+        height: 2,
+    };
+    assert_eq!(
+        changed(flip, IMAGE),
+        [4.0, 5.0, 6.0, 1.0, 2.0, 3.0, 10.0, 11.0, 12.0, 7.0, 8.0, 9.0]
+    );
+}
+
+#[test]
+fn crops_shift_images() {
+    let crop = RandomCrop {
+        width: 3,
+        height: 2,
+        padding: 1,
+    };
+    let mut rng = Rng::with_seed(3);
+    for _ in 0..20 {
+        let mut image = IMAGE;
+        crop.augment(&mut image, &mut rng);
+        // Every pixel is either shifted in as zero, or comes from the same channel.
+        for (channel, original) in image.chunks(6).zip(IMAGE.chunks(6)) {
+            assert!(channel.iter().all(|x| *x == 0.0 || original.contains(x)));
+        }
+    }
+    let no_padding = RandomCrop {
+        width: 3,
+        height: 2,
+        padding: 0,
+    };
+    let mut image = IMAGE;
+    no_padding.augment(&mut image, &mut rng);
+    assert_eq!(image, IMAGE);
+}
+
+#[test]
+fn pipelines_are_reproducible() {
+    let pipeline = Noise { std_dev: 0.1 }
+        .then(Scale { min: 0.5, max: 2.0 })
+        .then(FeatureDropout { p: 0.5 });
+    let run = |seed| {
+        let mut inputs = IMAGE;
+        pipeline.augment(&mut inputs, &mut Rng::with_seed(seed));
+        inputs
+    };
+    assert_eq!(run(1), run(1));
+    assert_ne!(run(1), run(2));
+    assert!(run(1).contains(&0.0));
+}
+
+#[test]
+fn augmented_samples_are_fresh_copies() {
+    let dataset = [([1.0, 1.0], [0.0]), ([2.0, 2.0], [1.0])];
+    let mut rng = Rng::with_seed(1);
+    let samples: Vec<_> =
+        augment::augmented(&dataset, &Scale { min: 2.0, max: 2.0 }, &mut rng).collect();
+    assert_eq!(samples, [([2.0, 2.0], [0.0]), ([4.0, 4.0], [1.0])]);
+    assert_eq!(dataset[0].0, [1.0, 1.0]);
+}
+
+#[test]
+fn trainer_trains_on_augmented_samples() {
+    let net = || {
+        Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1))
+            .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)))
+            .chain(SquareError { expected: [0.0] })
+    };
+    let trainer = Trainer {
+        epochs: 5,
+        ..Default::default()
+    };
+    let noise = Noise { std_dev: 0.05 };
+    let (mut a, mut b, mut c) = (net(), net(), net());
+    let fit = trainer.fit_augmented(&mut a, &testing::XOR, &noise, 7);
+    trainer.fit_augmented(&mut b, &testing::XOR, &noise, 7);
+    trainer.fit(&mut c, &testing::XOR);
+    assert_eq!((fit.errors.len(), fit.steps), (5, 20));
+    assert_eq!(a.params(), b.params());
+    assert_ne!(a.params(), c.params());
+
+    // Without augmentation, training is unchanged.
+    let mut d = net();
+    trainer.fit_augmented(&mut d, &testing::XOR, &Noise { std_dev: 0.0 }, 7);
+    assert_eq!(d.params(), c.params());
+}
@@ -0,0 +1,77 @@
+use fastrand::Rng;
+use rann_base::{activ::Logistic, autoencoder::Autoencoder, Full};
+use rann_traits::{compose::Shared, Network};
+
+// Patterns that can be compressed into two values.
+const PATTERNS: [[f32; 4]; 2] = [[1.0, 0.0, 1.0, 0.0], [0.0, 1.0, 0.0, 1.0]];
+const RATE: f32 = 0.5;
+const ITER: usize = 2000;
+
+fn gen(
+    seed: u64,
+) -> (
+    impl FnMut(usize, usize) -> f32 + Clone,
+    impl FnMut(usize) -> f32 + Clone,
+) {
+    let rng = Rng::with_seed(seed);
+    (
+        {
+            let mut rng = rng.clone();
+            move |_, _| rng.f32() * 2.0 - 1.0
+        },
+        {
+            let mut rng = rng.clone();
+            move |_| rng.f32() * 2.0 - 1.0
+        },
+    )
+}
+
+// Trains the autoencoder and returns the total reconstruction error before and after training.
+fn pretrain<E, D>(auto: &mut Autoencoder<E, D>) -> (f32, f32)
+where
+    E: Network<In = [f32; 4]>,
+    D: Network<In = E::Out, Out = [f32; 4]>,
+{
+    let mut errors = (0.0, 0.0);
+    for i in 0..ITER {
+        let err: f32 = PATTERNS
+            .iter()
+            .map(|p| auto.train_reconstruction(p, RATE))
+            .sum();
+        if i == 0 {
+            errors.0 = err;
+        }
+        errors.1 = err;
+    }
+    errors
+}
+
+#[test]
+fn untied_reconstruction() {
+    let encoder =
+        Full::<4, 3, _>::new(Logistic, gen(1)).chain(Full::<3, 2, _>::new(Logistic, gen(2)));
+    let mut auto = Autoencoder::new(encoder, gen(3));
+    let (before, after) = pretrain(&mut auto);
+    assert!(after < before, "Error {after} should be below {before}.");
+    assert!(after < 0.1, "Error {after} is too large.");
+
+    // The extracted encoder can be chained to other networks.
+    let net = auto
+        .into_encoder()
+        .chain(Full::<2, 1, _>::new(Logistic, gen(4)));
+    assert_eq!(net.eval(&PATTERNS[0]).len(), 1);
+}
+
+#[test]
+fn tied_reconstruction() {
+    let first = Shared::new(Full::<4, 3, _>::new(Logistic, gen(1)));
+    let second = Shared::new(Full::<3, 2, _>::new(Logistic, gen(2)));
+    let mut auto = Autoencoder::new(first.clone().chain(second.clone()), gen(3));
+
+    // Both the encoder and the decoder use the layers.
+    assert_eq!(first.uses(), 3);
+    assert_eq!(second.uses(), 3);
+
+    let (before, after) = pretrain(&mut auto);
+    assert!(after < before, "Error {after} should be below {before}.");
+}
@@ -0,0 +1,117 @@
+use std::io;
+
+use rann_base::{
+    activ::{Logistic, Tanh},
+    bake::Baker,
+    npy::{Array, Npz},
+    testing, Full,
+};
+use rann_traits::{inspect::Inspect, Network};
+
+include!("data/xor_baked.rs");
+
+fn xor_npz() -> Npz {
+    let mut npz = Npz::new();
+    npz.insert("0.weight", Array::new(vec![2, 2], vec![1.0; 4]));
+    npz.insert("0.bias", Array::new(vec![2], vec![0.0, -1.0]));
+    npz.insert("1.weight", Array::new(vec![1, 2], vec![1.0, -2.0]));
+    npz.insert("1.bias", Array::new(vec![1], vec![0.0]));
+    npz
+}
+
+fn xor_baker() -> Baker {
+    Baker::new("xor")
+        .with_layer("rann_base::activ::LeakyRelu(0.0)")
+        .with_layer("rann_base::activ::LeakyRelu(0.0)")
+}
+
+#[test]
+fn baked_models_are_included() {
+    assert_eq!(
+        xor_baker().source(&xor_npz()).unwrap(),
+        include_str!("data/xor_baked.rs")
+    );
+    let net = xor();
+    for (input, output) in [
+        ([0.0, 0.0], 0.0),
+        ([0.0, 1.0], 1.0),
+        ([1.0, 0.0], 1.0),
+        ([1.0, 1.0], 0.0),
+    ] {
+        assert_eq!(net.eval(&input), [output]);
+    }
+    let mut kinds = Vec::new();
+    net.visit_layers(&net.intermediate(&[0.0, 1.0]), &mut |layer| {
+        kinds.push(layer.kind.to_owned())
+    });
+    assert_eq!(kinds, ["FullView (LeakyRelu)", "FullView (LeakyRelu)"]);
+}
+
+#[test]
+fn parameters_are_baked_exactly() {
+    let net = Full::<3, 2, _, _>::without_bias(Tanh, testing::seeded_gen(1))
+        .chain(Full::<2, 1, _>::new(Logistic, testing::seeded_gen(2)));
+    let mut npz = Npz::new();
+    net.first.write_npz(&mut npz, "0.");
+    net.second.write_npz(&mut npz, "1.");
+    let source = Baker::new("net")
+        .with_layer("rann_base::activ::Tanh")
+        .with_layer("rann_base::activ::Logistic")
+        .source(&npz)
+        .unwrap();
+    assert!(!source.contains("NET_BIAS_0"));
+    assert!(source.contains("FullView::<3, 2, _>::new(rann_base::activ::Tanh, &NET_WEIGHT_0, &[])"));
+    // Every value is printed such that it is parsed back to the same value.
+    let line = source
+        .lines()
+        .find(|line| line.starts_with("static NET_WEIGHT_0"))
+        .unwrap();
+    let values = line.split_once("= [").unwrap().1;
+    let values: Vec<f32> = values
+        .trim_end_matches("];")
+        .split(", ")
+        .map(|value| value.parse().unwrap())
+        .collect();
+    assert_eq!(values, npz.get("0.weight").unwrap().data);
+}
+
+#[test]
+fn invalid_models_are_rejected() {
+    let kind = |baker: Baker, npz: &Npz| baker.source(npz).unwrap_err().kind();
+    let npz = xor_npz();
+    assert_eq!(kind(Baker::new("empty"), &npz), io::ErrorKind::InvalidData);
+    let three = xor_baker().with_layer("rann_base::activ::Logistic");
+    assert_eq!(kind(three, &npz), io::ErrorKind::InvalidData);
+
+    let mut mismatched = xor_npz();
+    mismatched.insert("1.weight", Array::new(vec![1, 3], vec![1.0; 3]));
+    assert_eq!(kind(xor_baker(), &mismatched), io::ErrorKind::InvalidData);
+
+    let mut infinite = xor_npz();
+    infinite.insert("1.bias", Array::new(vec![1], vec![f32::INFINITY]));
+    assert_eq!(kind(xor_baker(), &infinite), io::ErrorKind::InvalidData);
+}
+
+#[test]
+#[should_panic]
+fn names_are_identifiers() {
+    Baker::new("Xor-net");
+}
+
+#[test]
+fn unchanged_sources_are_not_rewritten() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("xor.rs");
+    xor_baker().save(&xor_npz(), &path).unwrap();
+    let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    xor_baker().save(&xor_npz(), &path).unwrap();
+    assert_eq!(
+        std::fs::metadata(&path).unwrap().modified().unwrap(),
+        modified
+    );
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        include_str!("data/xor_baked.rs")
+    );
+}
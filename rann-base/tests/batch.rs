@@ -0,0 +1,103 @@
+use rann_base::{
+    testing,
+    train::{self, train_batch, train_epoch_batched, train_step, Fit, Trainer},
+};
+use rann_traits::{params::Parameterized, Network, Scalar, Supervised};
+
+#[test]
+fn batches_of_one_sample_train_like_single_steps() {
+    let (mut batched, mut single) = (testing::xor_net(1), testing::xor_net(1));
+    for sample in &testing::XOR {
+        let error = train_batch(&mut batched, std::slice::from_ref(sample), 0.5);
+        assert_eq!(error, train_step(&mut single, &sample.0, &sample.1, 0.5));
+    }
+    assert_eq!(batched.params(), single.params());
+}
+
+#[test]
+fn updates_are_averaged_over_the_batch() {
+    let mut net = testing::xor_net(1);
+    let before = net.params();
+    // The update of every sample on its own.
+    let updates: Vec<Vec<Scalar>> = testing::XOR
+        .iter()
+        .map(|(inputs, target)| {
+            let mut net = net.clone();
+            train_step(&mut net, inputs, target, 0.5);
+            before
+                .iter()
+                .zip(net.params())
+                .map(|(b, a)| b - a)
+                .collect()
+        })
+        .collect();
+    let errors: Vec<_> = testing::XOR
+        .iter()
+        .map(|(inputs, target)| {
+            let mut net = net.clone();
+            net.set_target(target);
+            net.eval(inputs)[0]
+        })
+        .collect();
+
+    let error = train_batch(&mut net, &testing::XOR, 0.5);
+    assert!((error - errors.iter().sum::<Scalar>() / 4.0).abs() < 1e-6);
+    for (i, after) in net.params().iter().enumerate() {
+        let update = updates.iter().map(|u| u[i]).sum::<Scalar>() / 4.0;
+        assert!((before[i] - update - after).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn full_batches_decrease_the_error_steadily() {
+    let mut net = testing::xor_net(1);
+    let errors: Vec<_> = (0..200)
+        .map(|_| train_epoch_batched(&mut net, &testing::XOR, 4, 0.5))
+        .collect();
+    assert!(errors.windows(2).all(|e| e[1] <= e[0]), "{errors:?}");
+
+    // Training after every sample makes the error of the samples jump back and forth.
+    let mut net = testing::xor_net(1);
+    let mut single = Vec::new();
+    for _ in 0..200 {
+        for (inputs, target) in &testing::XOR {
+            single.push(train_step(&mut net, inputs, target, 0.5));
+        }
+    }
+    assert!(single.windows(2).any(|e| e[1] > e[0]));
+}
+
+#[test]
+fn smaller_last_batches_are_weighted_by_their_samples() {
+    let mut net = testing::xor_net(1);
+    let mut expected = net.clone();
+    let error = train_epoch_batched(&mut net, &testing::XOR, 3, 0.5);
+    let first = train_batch(&mut expected, &testing::XOR[..3], 0.5);
+    let last = train_batch(&mut expected, &testing::XOR[3..], 0.5);
+    assert!((error - (3.0 * first + last) / 4.0).abs() < 1e-6);
+    assert_eq!(net.params(), expected.params());
+    // Batches as large as the dataset train on it at once.
+    let mut whole = testing::xor_net(1);
+    train_epoch_batched(&mut whole, &testing::XOR, 10, 0.5);
+    let mut once = testing::xor_net(1);
+    train_batch(&mut once, &testing::XOR, 0.5);
+    assert_eq!(whole.params(), once.params());
+}
+
+#[test]
+#[should_panic(expected = "The batch should not be empty.")]
+fn empty_batches_panic() {
+    train_batch(&mut testing::xor_net(1), &[], 0.5);
+}
+
+#[test]
+fn empty_datasets_train_nothing() {
+    let mut net = testing::xor_net(1);
+    let before = net.params();
+    let empty: &[([Scalar; 2], [Scalar; 1])] = &[];
+    assert_eq!(train_epoch_batched(&mut net, empty, 2, 0.5), 0.0);
+    assert_eq!(train::train_epoch(&mut net, empty, 0.5), 0.0);
+    assert_eq!(train::mean_error(&mut net, empty), 0.0);
+    assert_eq!(Trainer::default().fit(&mut net, empty), Fit::default());
+    assert_eq!(net.params(), before);
+}
@@ -0,0 +1,98 @@
+use rann_base::{
+    activ::{LeakyRelu, Tanh},
+    beam::BeamSearch,
+    testing, Full, FullView,
+};
+use rann_traits::{Network, Scalar};
+
+fn one_hot(_: &[Scalar; 3], token: usize) -> [Scalar; 3] {
+    std::array::from_fn(|i| (i == token) as u8 as Scalar)
+}
+
+fn log_prob(net: &impl Network<In = [Scalar; 3], Out = [Scalar; 3]>, tokens: &[usize]) -> Scalar {
+    let mut input = one_hot(&[0.0; 3], 0);
+    let mut score = 0.0;
+    for &token in tokens {
+        let logits = net.eval(&input);
+        let sum: Scalar = logits.iter().map(|l| l.exp()).sum();
+        score += (logits[token].exp() / sum).ln();
+        input = one_hot(&input, token);
+    }
+    score
+}
+
+#[test]
+fn greedy_search_picks_the_most_likely_token() {
+    let net = Full::<3, 3, _>::new(Tanh, testing::seeded_gen(1));
+    let search = BeamSearch {
+        width: 1,
+        max_len: 5,
+        end: None,
+    };
+    let found = search.search(&net, one_hot(&[0.0; 3], 0), one_hot);
+    assert_eq!(found.len(), 1);
+    let mut input = one_hot(&[0.0; 3], 0);
+    for &token in &found[0].tokens {
+        let logits = net.eval(&input);
+        assert!(logits.iter().all(|&l| l <= logits[token]));
+        input = one_hot(&input, token);
+    }
+    assert_eq!(found[0].tokens.len(), 5);
+    assert!(!found[0].finished);
+}
+
+#[test]
+fn wide_beams_are_exhaustive() {
+    let net = Full::<3, 3, _>::new(Tanh, testing::seeded_gen(2));
+    let search = BeamSearch {
+        width: 27,
+        max_len: 3,
+        end: None,
+    };
+    let found = search.search(&net, one_hot(&[0.0; 3], 0), one_hot);
+    assert_eq!(found.len(), 27);
+    assert!(found.windows(2).all(|w| w[0].score >= w[1].score));
+    for hypothesis in &found {
+        assert!((hypothesis.score - log_prob(&net, &hypothesis.tokens)).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn beams_find_what_greedy_search_misses() {
+    // Token 0 is slightly more likely at first, but is followed by a flat distribution, while
+    // token 1 is certainly followed by the end token 2.
+    let net = FullView::<3, 3, _>::new(
+        LeakyRelu(1.0),
+        &[0.1, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 9.0, 0.0],
+        &[],
+    );
+    let search = |width| {
+        BeamSearch {
+            width,
+            max_len: 2,
+            end: Some(2),
+        }
+        .search(&net, one_hot(&[0.0; 3], 0), one_hot)
+    };
+    assert_eq!(search(1)[0].tokens[0], 0);
+    let best = &search(2)[0];
+    assert_eq!(best.tokens, [1, 2]);
+    assert!(best.finished);
+    assert!((best.score - log_prob(&net, &[1, 2])).abs() < 1e-5);
+}
+
+#[test]
+fn finished_hypotheses_stop() {
+    // The end token is always the most likely.
+    let net = FullView::<3, 3, _>::new(LeakyRelu(1.0), &[0.0; 9], &[0.0, 0.0, 5.0]);
+    let found = BeamSearch {
+        width: 3,
+        max_len: 10,
+        end: Some(2),
+    }
+    .search(&net, one_hot(&[0.0; 3], 0), one_hot);
+    assert_eq!(found[0].tokens, [2]);
+    assert!(found[0].finished);
+    assert!((found[0].score - log_prob(&net, &[2])).abs() < 1e-5);
+    assert!(found.iter().all(|h| h.tokens.len() <= 10));
+}
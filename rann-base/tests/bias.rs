@@ -0,0 +1,33 @@
+use rann_base::{activ::Logistic, error::SquareError, testing, Full};
+use rann_traits::{params::Parameterized, Network, Scalar};
+
+#[test]
+fn bias_free_layers_have_only_weights() {
+    let with = Full::<3, 2, _>::new(Logistic, testing::seeded_gen(1));
+    let without = Full::<3, 2, _, _>::without_bias(Logistic, testing::seeded_gen(1));
+    assert_eq!(with.num_params(), 8);
+    assert_eq!(without.num_params(), 6);
+    let mut params = vec![0.0; 6];
+    without.write_params(&mut params);
+    assert!(params.iter().all(|p| *p != 0.0));
+}
+
+#[test]
+fn zero_inputs_stay_zero() {
+    let linear = (|x: Scalar| x, |_: Scalar| 1.0);
+    let mut net = Full::<2, 1, _, _>::without_bias(linear, testing::seeded_gen(1))
+        .chain(SquareError { expected: [1.0] });
+    for _ in 0..10 {
+        let inter = net.intermediate(&[0.0, 0.0]);
+        net.train(&[0.0, 0.0], &inter, 0.1);
+    }
+    assert_eq!(net.first.eval(&[0.0, 0.0]), [0.0]);
+}
+
+#[test]
+fn bias_free_hidden_layers_learn_xor() {
+    let mut net = Full::<2, 4, _, _>::without_bias(Logistic, testing::seeded_gen(1))
+        .chain(Full::<4, 1, _>::new(Logistic, testing::seeded_gen(2)))
+        .chain(SquareError { expected: [0.0] });
+    testing::assert_network_converges(&mut net, &testing::XOR, 2000, 1.0, 0.01);
+}
@@ -0,0 +1,98 @@
+use rann_base::{activ::Logistic, boundary::DecisionSurface, testing, Full};
+use rann_traits::{Network, Scalar};
+
+#[test]
+fn grid_goes_from_top_left_to_bottom_right() {
+    let surface = DecisionSurface::from_fn(|[x, y]| 10.0 * x + y, (0.0, 2.0), (0.0, 1.0), 3, 2);
+    assert_eq!(surface.values, [1.0, 11.0, 21.0, 0.0, 10.0, 20.0]);
+    assert_eq!(surface.point(0, 2), [2.0, 1.0]);
+    assert_eq!(surface.point(1, 0), [0.0, 0.0]);
+}
+
+#[test]
+fn single_points_are_centered() {
+    let surface = DecisionSurface::from_fn(|[x, y]| x + y, (0.0, 2.0), (-1.0, 3.0), 1, 1);
+    assert_eq!(surface.values, [2.0]);
+}
+
+#[test]
+fn evaluates_networks() {
+    let net = Full::<2, 1, _>::new(Logistic, testing::seeded_gen(1));
+    let surface = DecisionSurface::new(&net, (-1.0, 1.0), (-1.0, 1.0), 5, 4);
+    assert_eq!(surface.values.len(), 20);
+    for row in 0..4 {
+        for column in 0..5 {
+            let value = surface.values[row * 5 + column];
+            assert_eq!(value, net.eval(&surface.point(row, column))[0]);
+        }
+    }
+}
+
+#[test]
+fn csv_has_a_line_per_row() {
+    let surface = DecisionSurface::from_fn(|[x, y]| x - y, (0.0, 1.0), (0.0, 1.0), 2, 2);
+    assert_eq!(surface.to_csv(), "-1,0\n0,1\n");
+}
+
+// Reads the pixels back from an image of stored deflate blocks.
+fn decode_png(png: &[u8]) -> (u32, u32, Vec<u8>) {
+    assert_eq!(
+        png[..8],
+        [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n']
+    );
+    let (mut width, mut height, mut pixels) = (0, 0, Vec::new());
+    let mut rest = &png[8..];
+    while !rest.is_empty() {
+        let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+        let (kind, data) = (&rest[4..8], &rest[8..8 + len]);
+        match kind {
+            b"IHDR" => {
+                width = u32::from_be_bytes(data[..4].try_into().unwrap());
+                height = u32::from_be_bytes(data[4..8].try_into().unwrap());
+                assert_eq!(data[8..], [8, 0, 0, 0, 0]);
+            }
+            b"IDAT" => {
+                let mut blocks = &data[2..data.len() - 4];
+                while !blocks.is_empty() {
+                    let len = u16::from_le_bytes([blocks[1], blocks[2]]) as usize;
+                    pixels.extend(&blocks[5..5 + len]);
+                    blocks = &blocks[5 + len..];
+                }
+            }
+            _ => {}
+        }
+        rest = &rest[12 + len..];
+    }
+    (width, height, pixels)
+}
+
+#[test]
+fn png_spans_the_outputs() {
+    let surface = DecisionSurface::from_fn(|[x, _]| *x, (-1.0, 1.0), (0.0, 1.0), 3, 2);
+    let (width, height, pixels) = decode_png(&surface.to_png());
+    assert_eq!((width, height), (3, 2));
+    // Every row starts with a filter type of zero.
+    assert_eq!(pixels, [0, 0, 128, 255, 0, 0, 128, 255]);
+}
+
+#[test]
+fn png_of_constant_and_non_finite_outputs() {
+    let surface = DecisionSurface::from_fn(
+        |[x, _]| if *x > 0.0 { Scalar::NAN } else { 0.5 },
+        (-1.0, 1.0),
+        (0.0, 0.0),
+        2,
+        1,
+    );
+    assert_eq!(decode_png(&surface.to_png()).2, [0, 0, 0]);
+}
+
+#[test]
+fn large_images_span_several_blocks() {
+    let surface = DecisionSurface::from_fn(|[x, y]| x * y, (0.0, 1.0), (0.0, 1.0), 300, 300);
+    let (width, height, pixels) = decode_png(&surface.to_png());
+    assert_eq!((width, height), (300, 300));
+    assert_eq!(pixels.len(), 301 * 300);
+    // The top right corner is the brightest.
+    assert_eq!(pixels[300], 255);
+}
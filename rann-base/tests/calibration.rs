@@ -0,0 +1,124 @@
+use rann_base::{
+    activ::Logistic,
+    calibration::{self, Calibrated, ReliabilityDiagram},
+    error::CrossEntropy,
+    testing, Full,
+};
+use rann_traits::{Network, Scalar};
+
+// A linear classifier of one input into two classes, with logits `scale` times the input.
+fn classifier(
+    scale: Scalar,
+) -> Full<
+    1,
+    2,
+    (
+        impl Fn(Scalar) -> Scalar + Clone,
+        impl Fn(Scalar) -> Scalar + Clone,
+    ),
+> {
+    let linear = (|x: Scalar| x, |_: Scalar| 1.0);
+    Full::new(
+        linear,
+        (move |r, _| if r == 0 { scale } else { -scale }, |_| 0.0),
+    )
+}
+
+// A dataset where the first class is right with probability `logistic(2x)` for input `x`, so
+// `classifier(1.0)` is calibrated.
+fn dataset() -> Vec<([Scalar; 1], [Scalar; 2])> {
+    (-10..=10)
+        .flat_map(|i| {
+            let x = i as Scalar / 10.0;
+            let p = 1.0 / (1.0 + (-2.0 * x).exp());
+            (0..100).map(move |j| {
+                let first = (j as Scalar) < 100.0 * p;
+                ([x], if first { [1.0, 0.0] } else { [0.0, 1.0] })
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn fits_overconfident_and_underconfident_classifiers() {
+    let dataset = dataset();
+    for scale in [0.5, 1.0, 4.0] {
+        let temperature = calibration::fit_temperature(&classifier(scale), &dataset);
+        assert!(
+            (temperature - scale).abs() < 0.1 * scale,
+            "Temperature {temperature} should be close to {scale}."
+        );
+    }
+}
+
+#[test]
+fn fitted_temperature_minimizes_the_cross_entropy() {
+    let dataset = dataset();
+    let net = classifier(3.0);
+    let logits: Vec<_> = dataset
+        .iter()
+        .map(|(inputs, targets)| (net.eval(inputs), *targets))
+        .collect();
+    let temperature = calibration::fit_temperature(&net, &dataset);
+    let best = calibration::nll(&logits, temperature);
+    for other in [temperature * 0.8, temperature * 1.2, 1.0] {
+        assert!(best <= calibration::nll(&logits, other));
+    }
+}
+
+#[test]
+fn calibration_keeps_the_predicted_class() {
+    let calibrated = Calibrated::new(classifier(3.0), 2.0);
+    for x in [-1.0, -0.25, 0.5] {
+        let probs = calibrated.probabilities(&[x]);
+        assert!((probs.iter().sum::<Scalar>() - 1.0).abs() < 1e-6);
+        assert_eq!(probs[0] > probs[1], x > 0.0);
+        assert_eq!(calibrated.eval(&[x]), classifier(1.5).eval(&[x]));
+    }
+}
+
+#[test]
+fn calibration_reduces_the_calibration_error() {
+    let dataset = dataset();
+    let net = classifier(5.0);
+    let before = ReliabilityDiagram::new(&net, &dataset, 10);
+    let after = ReliabilityDiagram::new(&Calibrated::fit(net, &dataset), &dataset, 10);
+    assert!(after.expected_calibration_error() < 0.05);
+    assert!(after.expected_calibration_error() < before.expected_calibration_error());
+}
+
+#[test]
+fn reliability_diagram_bins() {
+    let dataset = [
+        ([1.0], [1.0, 0.0]),
+        ([1.0], [0.0, 1.0]),
+        ([0.0], [1.0, 0.0]),
+    ];
+    let diagram = ReliabilityDiagram::new(&classifier(10.0), &dataset, 4);
+    // Two confident predictions, of which one is right, and one prediction of one half.
+    assert_eq!(diagram.bins[3].count, 2);
+    assert_eq!(diagram.bins[3].accuracy, 0.5);
+    assert!(diagram.bins[3].confidence > 0.99);
+    assert_eq!(diagram.bins[2].count, 1);
+    assert_eq!(diagram.bins[2].confidence, 0.5);
+    assert_eq!(diagram.bins[2].accuracy, 1.0);
+
+    let csv = diagram.to_csv();
+    let lines: Vec<_> = csv.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert_eq!(lines[0], "lower,upper,count,confidence,accuracy");
+    assert_eq!(lines[1], "0,0.25,0,0,0");
+    assert_eq!(lines[3], "0.5,0.75,1,0.5,1");
+}
+
+// Training through the wrapper trains the wrapped network.
+#[test]
+fn trains_through_the_wrapper() {
+    let net = Full::<2, 4, _>::new(Logistic, testing::seeded_gen(1)).chain(Full::<4, 2, _>::new(
+        (|x: Scalar| x, |_: Scalar| 1.0),
+        testing::seeded_gen(2),
+    ));
+    let mut net = Calibrated::new(net, 0.5).chain(CrossEntropy::<2>::new());
+    let dataset = testing::XOR.map(|(inputs, [t])| (inputs, [1.0 - t, t]));
+    testing::assert_network_converges(&mut net, &dataset, 3000, 0.5, 0.1);
+}
@@ -0,0 +1,38 @@
+use std::{thread, time::Duration};
+
+use rann_base::{
+    testing,
+    train::{CancellationToken, Trainer},
+};
+
+#[test]
+fn completes_without_cancellation() {
+    let trainer = Trainer {
+        epochs: 10,
+        cancel: Some(CancellationToken::new()),
+        ..Default::default()
+    };
+    let fit = trainer.fit(&mut testing::xor_net(1), &testing::XOR);
+    assert!(!fit.cancelled);
+    assert_eq!(fit.errors.len(), 10);
+    assert_eq!(fit.steps, 40);
+}
+
+#[test]
+fn cancels_from_another_thread() {
+    let cancel = CancellationToken::new();
+    let trainer = Trainer {
+        epochs: usize::MAX,
+        cancel: Some(cancel.clone()),
+        ..Default::default()
+    };
+    let handle = thread::spawn(move || trainer.fit(&mut testing::xor_net(1), &testing::XOR));
+    thread::sleep(Duration::from_millis(50));
+    cancel.cancel();
+    let fit = handle.join().unwrap();
+    assert!(fit.cancelled);
+    assert!(fit.steps > 0);
+    // Partial epochs are not reported.
+    assert_eq!(fit.errors.len(), fit.steps / testing::XOR.len());
+    assert!(fit.errors.iter().all(|e| e.is_finite()));
+}
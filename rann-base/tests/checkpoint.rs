@@ -0,0 +1,156 @@
+use rann_base::{
+    checkpoint::{Checkpoint, CheckpointConfig, Checkpointer},
+    testing,
+    train::{CancellationToken, Trainer},
+};
+use rann_traits::{params::Parameterized, LearningRate};
+
+#[test]
+fn round_trips() {
+    let checkpoint = Checkpoint {
+        epoch: 7,
+        learning_rate: LearningRate(0.25),
+        errors: vec![1.0, 0.5, f32::NAN],
+        validation_error: Some(0.125),
+        params: testing::xor_net(1).params(),
+    };
+    let mut bytes = Vec::new();
+    checkpoint.write_to(&mut bytes).unwrap();
+    let read = Checkpoint::read_from(&bytes[..]).unwrap();
+    assert_eq!(read.params, checkpoint.params);
+    assert_eq!(read.validation_error, Some(0.125));
+    assert!(read.errors[2].is_nan());
+
+    // Truncated and foreign data is rejected.
+    assert!(Checkpoint::read_from(&bytes[..bytes.len() - 1]).is_err());
+    assert!(Checkpoint::read_from(&b"RANNMODL\x01\0\0\0"[..]).is_err());
+    // The length of the errors follows the header of 29 bytes.
+    bytes[29..37].copy_from_slice(&u64::MAX.to_le_bytes());
+    let error = Checkpoint::read_from(&bytes[..]).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn keeps_last_and_best() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = CheckpointConfig {
+        keep_last: 2,
+        ..CheckpointConfig::new(dir.path())
+    };
+    let mut checkpointer = Checkpointer::new(config).unwrap();
+    // The validation error is lowest at epoch 2.
+    for (epoch, error) in [(1, 0.5), (2, 0.1), (3, 0.3), (4, 0.4), (5, 0.2)] {
+        checkpointer
+            .save(&Checkpoint {
+                epoch,
+                learning_rate: LearningRate(0.1),
+                errors: vec![0.0; epoch],
+                validation_error: Some(error),
+                params: Vec::new(),
+            })
+            .unwrap();
+    }
+    let mut files: Vec<_> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    files.sort();
+    assert_eq!(
+        files,
+        [
+            "checkpoint-00000002.ckpt",
+            "checkpoint-00000004.ckpt",
+            "checkpoint-00000005.ckpt"
+        ]
+    );
+    assert_eq!(checkpointer.best().unwrap().unwrap().epoch, 2);
+    assert_eq!(checkpointer.latest().unwrap().unwrap().epoch, 5);
+
+    // A new checkpointer finds the existing checkpoints.
+    let reopened = Checkpointer::new(checkpointer.config.clone()).unwrap();
+    assert_eq!(reopened.checkpoints().count(), 3);
+    assert_eq!(reopened.skipped().count(), 0);
+}
+
+#[test]
+fn skips_corrupt_checkpoints() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut checkpointer = Checkpointer::new(CheckpointConfig::new(dir.path())).unwrap();
+    let checkpoint = Checkpoint {
+        epoch: 1,
+        learning_rate: LearningRate(0.1),
+        errors: vec![0.5],
+        validation_error: None,
+        params: Vec::new(),
+    };
+    checkpointer.save(&checkpoint).unwrap();
+    // A crash while writing the second checkpoint, and a later truncated third one.
+    let tmp = dir.path().join("checkpoint-00000002.tmp");
+    std::fs::write(&tmp, b"RANNCKPT").unwrap();
+    let corrupt = checkpointer.path(3);
+    std::fs::write(&corrupt, b"RANNCKPT").unwrap();
+
+    let reopened = Checkpointer::new(checkpointer.config.clone()).unwrap();
+    assert_eq!(reopened.latest().unwrap(), Some(checkpoint));
+    let skipped: Vec<_> = reopened.skipped().map(|(path, _)| path).collect();
+    assert_eq!(skipped, [corrupt.as_path()]);
+    assert!(!tmp.exists());
+}
+
+#[test]
+fn resumes_where_it_left_off() {
+    let dataset = testing::XOR;
+    let trainer = Trainer {
+        epochs: 20,
+        learning_rate: LearningRate(0.5),
+        ..Default::default()
+    };
+    let mut uninterrupted = testing::xor_net(1);
+    let expected = trainer.fit(&mut uninterrupted, &dataset);
+
+    let dir = tempfile::tempdir().unwrap();
+    let config = CheckpointConfig {
+        every: 5,
+        ..CheckpointConfig::new(dir.path())
+    };
+    // Train for 10 epochs, then start over with a fresh network and resume.
+    let mut first = testing::xor_net(1);
+    let partial = Trainer {
+        epochs: 10,
+        ..trainer.clone()
+    };
+    let mut checkpointer = Checkpointer::new(config.clone()).unwrap();
+    partial
+        .fit_checkpointed(&mut first, &dataset, &dataset[..2], &mut checkpointer)
+        .unwrap();
+
+    let mut resumed = testing::xor_net(3);
+    let mut checkpointer = Checkpointer::new(config).unwrap();
+    let fit = trainer
+        .resume(&mut resumed, &dataset, &dataset[..2], &mut checkpointer)
+        .unwrap();
+    assert_eq!(fit.errors, expected.errors);
+    assert_eq!(fit.steps, 10 * dataset.len());
+    assert_eq!(resumed.params(), uninterrupted.params());
+    assert_eq!(checkpointer.latest().unwrap().unwrap().epoch, 20);
+}
+
+#[test]
+fn saves_when_cancelled() {
+    let dir = tempfile::tempdir().unwrap();
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let trainer = Trainer {
+        cancel: Some(cancel),
+        ..Default::default()
+    };
+    let mut net = testing::xor_net(1);
+    let mut checkpointer = Checkpointer::new(CheckpointConfig::new(dir.path())).unwrap();
+    let fit = trainer
+        .fit_checkpointed(&mut net, &testing::XOR, &[], &mut checkpointer)
+        .unwrap();
+    assert!(fit.cancelled);
+    let checkpoint = checkpointer.latest().unwrap().unwrap();
+    assert_eq!(checkpoint.epoch, 0);
+    assert_eq!(checkpoint.params, net.params());
+}
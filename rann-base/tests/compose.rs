@@ -1,5 +1,5 @@
 use float_cmp::{ApproxEq, F32Margin};
-use rann_base::{activ::Logistic, error::SquareError, full::Full, gen::Random};
+use rann_base::{activ::Logistic, error::SquareError, full::Full, testing};
 use rann_traits::{compose::zip, Intermediate, Network};
 
 #[test]
@@ -15,17 +15,19 @@ fn simple_compose() {
     const EXPECTED: [f32; 6] = [0.99, 0.1, 0.5, 0.3, 0.789, 0.6];
     // Network inputs.
     const INPUT: ([f32; 1], [f32; 5]) = ([5.0], [2.0; 5]);
+    // Seeded generators for the weights and biases, such that the test is reproducible.
+    let gen = testing::seeded_gen;
     // Builds a chain of layers, forming a network.
-    let net = Full::<1, 5, _>::new(Logistic, Random)
+    let net = Full::<1, 5, _>::new(Logistic, gen(1))
         // You can even remove some intermediate const parameters.
-        .chain(Full::new(Logistic, Random))
+        .chain(Full::new(Logistic, gen(2)))
         //          ^^^
         //      params removed here
-        .chain(Full::<10, 5, _>::new(Logistic, Random))
-        .chain(Full::<5, 1, _>::new(Logistic, Random));
+        .chain(Full::<10, 5, _>::new(Logistic, gen(3)))
+        .chain(Full::<5, 1, _>::new(Logistic, gen(4)));
 
     // Here we declare an independent layer...
-    let other = Full::<5, 5, _>::new(Logistic, Random);
+    let other = Full::<5, 5, _>::new(Logistic, gen(5));
     // ... which we zip together with the previously defined network.
     let net = net.zip(other, zip::Stacker::<1, 5, { 1 + 5 }>);
 
@@ -68,3 +70,78 @@ fn simple_compose() {
         "{out:?} is too different from {EXPECTED:?}."
     );
 }
+
+#[test]
+fn blended_networks_train_both_branches() {
+    let gen = testing::seeded_gen;
+    let net = Full::<2, 1, _>::new(Logistic, gen(1))
+        .zip_owned(Full::<2, 1, _>::new(Logistic, gen(2)), zip::blend(0.25));
+    let mut net = net.chain(SquareError { expected: [0.8] });
+    let inputs = ([1.0, -1.0], [0.5, 2.0]);
+    let inter = net.intermediate(&inputs);
+    let [top] = net.first.top.eval(&inputs.0);
+    let [bot] = net.first.bot.eval(&inputs.1);
+    let blended = 0.25 * top + 0.75 * bot;
+    assert!((inter.first.output()[0] - blended).abs() < 1e-6);
+
+    // The gradients over the inputs of both branches are scaled by their weight.
+    let (top_grads, bot_grads) = net.train_deriv(&inputs, &inter, &[1.0], 0.0);
+    let expected_top = net
+        .first
+        .top
+        .gradient(&inputs.0, &[0.25 * 2.0 * (blended - 0.8)]);
+    let expected_bot = net
+        .first
+        .bot
+        .gradient(&inputs.1, &[0.75 * 2.0 * (blended - 0.8)]);
+    for (g, e) in top_grads
+        .iter()
+        .chain(&bot_grads)
+        .zip(expected_top.iter().chain(&expected_bot))
+    {
+        assert!((g - e).abs() < 1e-6);
+    }
+
+    for _ in 0..500 {
+        let inter = net.intermediate(&inputs);
+        net.train_deriv(&inputs, &inter, &[1.0], 0.5);
+    }
+    assert!(net.eval(&inputs)[0] < 1e-4);
+}
+
+#[test]
+fn chain_macro_flattens_long_chains() {
+    let gen = testing::seeded_gen;
+    let mut net = rann_traits::chain!(
+        Full::<2, 4, _>::new(Logistic, gen(1)),
+        Full::<4, 4, _>::new(Logistic, gen(2)),
+        Full::<4, 3, _>::new(Logistic, gen(3)),
+        Full::<3, 1, _>::new(Logistic, gen(4)),
+        SquareError { expected: [0.5] },
+    );
+    // The same nesting as chaining the networks one by one.
+    let nested = Full::<2, 4, _>::new(Logistic, gen(1))
+        .chain(Full::<4, 4, _>::new(Logistic, gen(2)))
+        .chain(Full::<4, 3, _>::new(Logistic, gen(3)))
+        .chain(Full::<3, 1, _>::new(Logistic, gen(4)))
+        .chain(SquareError { expected: [0.5] });
+    assert_eq!(net.eval(&[1.0, 2.0]), nested.eval(&[1.0, 2.0]));
+
+    let inter = net.intermediate(&[1.0, 2.0]);
+    let (a, b, c, d, loss) = inter.layers::<5>();
+    assert_eq!(a.output(), inter.first.first.first.first.output());
+    assert_eq!(b.output().len() + c.output().len() + d.output().len(), 8);
+    assert_eq!(loss.output(), inter.output());
+
+    let (first, ..) = net.layers_mut::<5>();
+    *first = Full::new(Logistic, gen(5));
+    let (first, _, _, _, loss) = net.layers::<5>();
+    assert_eq!(loss.expected, [0.5]);
+    assert_eq!(
+        first.eval(&[1.0, 2.0]),
+        Full::<2, 4, _>::new(Logistic, gen(5)).eval(&[1.0, 2.0])
+    );
+    // Fewer parts flatten the chain less deeply.
+    let (_, last) = net.layers::<2>();
+    assert_eq!(last.expected, [0.5]);
+}
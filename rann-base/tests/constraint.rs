@@ -0,0 +1,46 @@
+use rann_base::{
+    activ::Logistic,
+    constraint::{Constrained, WeightConstraint},
+    error::SquareError,
+    testing, train, Full,
+};
+use rann_traits::{params::Parameterized, Network};
+
+// Returns the incoming weights of each neuron of a layer with 2 inputs and 3 outputs.
+fn neurons(layer: &impl Parameterized) -> Vec<[f32; 2]> {
+    // The weights are stored in column-major order, followed by the biases.
+    let params = layer.params();
+    (0..3).map(|n| [params[n], params[3 + n]]).collect()
+}
+
+#[test]
+fn constraints_hold_after_every_step() {
+    let hidden = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(4));
+    let constraint = WeightConstraint::max_norm(1.5).and_non_negative();
+    let mut net = Constrained::new(hidden, constraint)
+        .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(5)))
+        .chain(SquareError { expected: [0.0] });
+    for _ in 0..50 {
+        train::train_epoch(&mut net, &testing::XOR, 2.0);
+        for weights in neurons(&net.first.first) {
+            assert!(weights.iter().all(|w| *w >= 0.0), "{weights:?}");
+            let norm = (weights[0] * weights[0] + weights[1] * weights[1]).sqrt();
+            assert!(norm <= 1.5 + 1e-5, "{norm}");
+        }
+    }
+}
+
+#[test]
+fn max_norm_scales_neurons_down() {
+    let mut weights = [3.0, -4.0];
+    WeightConstraint::max_norm(1.0).apply(&mut weights);
+    assert_eq!(weights, [0.6, -0.8]);
+
+    let mut weights = [0.3, -0.4];
+    WeightConstraint::max_norm(1.0).apply(&mut weights);
+    assert_eq!(weights, [0.3, -0.4], "Small weights are left unchanged.");
+
+    let mut weights = [3.0, -4.0];
+    WeightConstraint::non_negative().apply(&mut weights);
+    assert_eq!(weights, [3.0, 0.0]);
+}
@@ -0,0 +1,458 @@
+use rann_base::{
+    activ::Tanh,
+    conv::{ConvAlgorithm, ConvConfig, Convolutional, Padding, TransposedConv},
+    image::Image,
+    testing,
+};
+use rann_traits::{params::Parameterized, Network, Scalar};
+
+#[test]
+fn output_shapes() {
+    let config = |kernel| ConvConfig::new(kernel);
+    assert_eq!(config([3, 1]).output_shape([5, 4]), Some([3, 4]));
+    assert_eq!(config([7, 1]).output_shape([7, 1]), Some([1, 1]));
+    assert_eq!(config([4, 1]).output_shape([3, 3]), None);
+    assert_eq!(config([0, 1]).output_shape([3, 3]), None);
+    assert_eq!(config([1, 1]).output_shape([0, 3]), None);
+    assert_eq!(config([3, 1]).transposed_output_shape([3, 4]), Some([5, 4]));
+    let strided = config([2, 2]).with_stride([0, 1]);
+    assert_eq!(strided.output_shape([3, 3]), None);
+    assert_eq!(strided.transposed_output_shape([3, 3]), None);
+    assert_eq!(
+        config([2, 2]).with_dilation([1, 0]).output_shape([3, 3]),
+        None
+    );
+}
+
+// Returns the output size of a convolution along one dimension, by trying every position.
+fn brute_force_output(input: usize, kernel: usize, stride: usize, dilation: usize) -> usize {
+    let span = dilation * (kernel - 1) + 1;
+    (0..input).filter(|p| p * stride + span <= input).count()
+}
+
+#[test]
+fn exhaustive_output_shapes() {
+    for input in 1..=12 {
+        for kernel in 1..=4 {
+            for stride in 1..=3 {
+                for dilation in 1..=3 {
+                    let valid = ConvConfig::new([kernel, 1])
+                        .with_stride([stride, 1])
+                        .with_dilation([dilation, 1]);
+                    let expected = brute_force_output(input, kernel, stride, dilation);
+                    let output = valid.output_shape([input, 1]);
+                    assert_eq!(output, (expected > 0).then_some([expected, 1]));
+
+                    let same = valid.with_padding(Padding::Same);
+                    let output = same.output_shape([input, 1]).unwrap();
+                    assert_eq!(output, [input.div_ceil(stride), 1]);
+                    if stride == 1 {
+                        assert_eq!(output, [input, 1]);
+                    }
+
+                    // The transposed convolution reverses the smallest convolution.
+                    for config in [valid, same] {
+                        let transposed = config.transposed_output_shape([input, 1]).unwrap();
+                        assert_eq!(config.output_shape(transposed), Some([input, 1]));
+                        let smaller = [transposed[0] - 1, 1];
+                        assert_ne!(config.output_shape(smaller), Some([input, 1]));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "Outputs should be OW by OH.")]
+fn mismatched_shapes_panic() {
+    Convolutional::<4, 4, 3, 3, _>::new(Tanh, [3, 3], testing::seeded_gen(1));
+}
+
+#[test]
+fn kernels_are_stored_row_by_row() {
+    let linear = (|x: Scalar| x, |_: Scalar| 1.0);
+    let mut conv = Convolutional::<3, 2, 2, 2, _>::new(linear, [2, 1], testing::seeded_gen(1));
+    conv.read_params(&[1.0, 10.0, 0.0]);
+    let image = Image::from([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+    assert_eq!(conv.eval(&image), Image::from([[21.0, 32.0], [54.0, 65.0]]));
+    let mut transposed =
+        TransposedConv::<2, 2, 3, 2, _>::new(linear, [2, 1], testing::seeded_gen(1));
+    transposed.read_params(&[1.0, 10.0, 0.0]);
+    let image = Image::from([[1.0, 2.0], [3.0, 4.0]]);
+    assert_eq!(
+        transposed.eval(&image),
+        Image::from([[1.0, 12.0, 20.0], [3.0, 34.0, 40.0]])
+    );
+}
+
+// Returns the numerical gradients of the sum of the outputs of `net` weighted by `gradients`,
+// over the inputs and over the parameters.
+fn numerical_gradients<
+    N,
+    const W: usize,
+    const H: usize,
+    const C: usize,
+    const OW: usize,
+    const OH: usize,
+    const OC: usize,
+>(
+    net: &N,
+    inputs: &Image<W, H, C>,
+    gradients: &Image<OW, OH, OC>,
+) -> (Vec<Scalar>, Vec<Scalar>)
+where
+    N: Network<In = Image<W, H, C>, Out = Image<OW, OH, OC>> + Parameterized + Clone,
+{
+    const STEP: Scalar = 1e-2;
+    let error = |net: &N, inputs: &Image<W, H, C>| -> Scalar {
+        let outputs = net.eval(inputs);
+        let outputs = outputs.as_slice().iter();
+        outputs.zip(gradients.as_slice()).map(|(o, g)| o * g).sum()
+    };
+    let input_grads = (0..Image::<W, H, C>::LEN)
+        .map(|i| {
+            let (mut above, mut below) = (*inputs, *inputs);
+            above.as_mut_slice()[i] += STEP;
+            below.as_mut_slice()[i] -= STEP;
+            (error(net, &above) - error(net, &below)) / (2.0 * STEP)
+        })
+        .collect();
+    let params = net.params();
+    let param_grads = (0..params.len())
+        .map(|i| {
+            let (mut above, mut below) = (net.clone(), net.clone());
+            let mut p = params.clone();
+            p[i] += STEP;
+            above.read_params(&p);
+            p[i] -= 2.0 * STEP;
+            below.read_params(&p);
+            (error(&above, inputs) - error(&below, inputs)) / (2.0 * STEP)
+        })
+        .collect();
+    (input_grads, param_grads)
+}
+
+// Returns an image of random values in `[-1, 1)`.
+fn random_image<const W: usize, const H: usize, const C: usize>(
+    rng: &mut fastrand::Rng,
+) -> Image<W, H, C> {
+    Image::from_fn(|_, _, _| rng.f32() * 2.0 - 1.0)
+}
+
+fn assert_gradients_match<
+    N,
+    const W: usize,
+    const H: usize,
+    const C: usize,
+    const OW: usize,
+    const OH: usize,
+    const OC: usize,
+>(
+    mut net: N,
+) where
+    N: Network<In = Image<W, H, C>, Out = Image<OW, OH, OC>> + Parameterized + Clone,
+{
+    let mut rng = fastrand::Rng::with_seed(3);
+    let inputs = random_image(&mut rng);
+    let gradients = random_image(&mut rng);
+    let (input_grads, param_grads) = numerical_gradients(&net, &inputs, &gradients);
+
+    let before = net.params();
+    let inter = net.intermediate(&inputs);
+    let backprop = net.train_deriv(&inputs, &inter, &gradients, 1.0);
+    let trained: Vec<_> = before
+        .iter()
+        .zip(net.params())
+        .map(|(before, after)| before - after)
+        .collect();
+
+    for (analytic, numerical) in backprop.as_slice().iter().zip(&input_grads) {
+        assert!(
+            (analytic - numerical).abs() < 1e-2,
+            "{analytic} != {numerical}"
+        );
+    }
+    for (analytic, numerical) in trained.iter().zip(&param_grads) {
+        assert!(
+            (analytic - numerical).abs() < 1e-2,
+            "{analytic} != {numerical}"
+        );
+    }
+}
+
+#[test]
+fn convolution_gradients_match_finite_differences() {
+    assert_gradients_match(Convolutional::<5, 4, 3, 3, _>::new(
+        Tanh,
+        [3, 2],
+        testing::seeded_gen(1),
+    ));
+    assert_gradients_match(Convolutional::<8, 1, 6, 1, _>::new(
+        Tanh,
+        [3, 1],
+        testing::seeded_gen(2),
+    ));
+}
+
+#[test]
+fn transposed_gradients_match_finite_differences() {
+    assert_gradients_match(TransposedConv::<3, 2, 5, 4, _>::new(
+        Tanh,
+        [3, 3],
+        testing::seeded_gen(1),
+    ));
+    assert_gradients_match(TransposedConv::<2, 3, 3, 3, _>::new(
+        Tanh,
+        [2, 1],
+        testing::seeded_gen(2),
+    ));
+}
+
+#[test]
+fn transposed_convolution_is_the_transpose() {
+    let linear = (|x: Scalar| x, |_: Scalar| 1.0);
+    let mut conv = Convolutional::<4, 3, 3, 2, _>::new(linear, [2, 2], testing::seeded_gen(1));
+    let mut transposed =
+        TransposedConv::<3, 2, 4, 3, _>::new(linear, [2, 2], testing::seeded_gen(1));
+    // Without biases, the dot products of x with the transpose of y and of y with the
+    // convolution of x are equal.
+    let mut params = conv.params();
+    *params.last_mut().unwrap() = 0.0;
+    conv.read_params(&params);
+    transposed.read_params(&params);
+    let x = Image::from_fn(|x, y, _| (4 * y + x) as Scalar - 5.0);
+    let y = Image::from_fn(|x, y, _| (x * y) as Scalar / 4.0);
+    let dot = |a: &[Scalar], b: &[Scalar]| a.iter().zip(b).map(|(a, b)| a * b).sum::<Scalar>();
+    let forward = dot(conv.eval(&x).as_slice(), y.as_slice());
+    let backward = dot(x.as_slice(), transposed.eval(&y).as_slice());
+    assert!((forward - backward).abs() < 1e-3, "{forward} != {backward}");
+}
+
+// Asserts that `net` computes the same outputs and gradients with both algorithms.
+fn assert_algorithms_agree<
+    N,
+    const W: usize,
+    const H: usize,
+    const C: usize,
+    const OW: usize,
+    const OH: usize,
+    const OC: usize,
+>(
+    direct: N,
+    im2col: N,
+) where
+    N: Network<In = Image<W, H, C>, Out = Image<OW, OH, OC>> + Parameterized,
+{
+    let (mut direct, mut im2col) = (direct, im2col);
+    let mut rng = fastrand::Rng::with_seed(4);
+    let close = |a: &[Scalar], b: &[Scalar]| a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-5);
+    for _ in 0..3 {
+        let inputs = random_image(&mut rng);
+        let gradients = random_image(&mut rng);
+        let (a, b) = (direct.eval(&inputs), im2col.eval(&inputs));
+        assert!(close(a.as_slice(), b.as_slice()));
+        let inter = direct.intermediate(&inputs);
+        let a = direct.train_deriv(&inputs, &inter, &gradients, 0.1);
+        let inter = im2col.intermediate(&inputs);
+        let b = im2col.train_deriv(&inputs, &inter, &gradients, 0.1);
+        assert!(close(a.as_slice(), b.as_slice()));
+        assert!(close(&direct.params(), &im2col.params()));
+    }
+}
+
+#[test]
+fn im2col_matches_the_direct_algorithm() {
+    let conv = Convolutional::<6, 5, 4, 4, _>::new(Tanh, [3, 2], testing::seeded_gen(1));
+    assert_eq!(conv.algorithm(), ConvAlgorithm::Direct);
+    let im2col = conv.clone().with_algorithm(ConvAlgorithm::Im2col);
+    assert_eq!(im2col.algorithm(), ConvAlgorithm::Im2col);
+    assert_algorithms_agree(conv, im2col);
+
+    let transposed = TransposedConv::<4, 3, 6, 5, _>::new(Tanh, [3, 3], testing::seeded_gen(2));
+    let im2col = transposed.clone().with_algorithm(ConvAlgorithm::Im2col);
+    assert_algorithms_agree(transposed, im2col);
+}
+
+#[test]
+fn im2col_gradients_match_finite_differences() {
+    let conv = Convolutional::<5, 4, 3, 3, _>::new(Tanh, [3, 2], testing::seeded_gen(1));
+    assert_gradients_match(conv.with_algorithm(ConvAlgorithm::Im2col));
+    let transposed = TransposedConv::<3, 2, 5, 4, _>::new(Tanh, [3, 3], testing::seeded_gen(1));
+    assert_gradients_match(transposed.with_algorithm(ConvAlgorithm::Im2col));
+}
+
+#[test]
+fn switching_back_to_the_direct_algorithm() {
+    let conv = Convolutional::<3, 3, 2, 2, _>::new(Tanh, [2, 2], testing::seeded_gen(1));
+    let inputs = Image::filled(0.5);
+    let expected = conv.eval(&inputs);
+    let conv = conv
+        .with_algorithm(ConvAlgorithm::Im2col)
+        .with_algorithm(ConvAlgorithm::Direct);
+    assert_eq!(conv.eval(&inputs), expected);
+}
+
+#[test]
+#[should_panic(expected = "Kernel, stride and dilation should be positive")]
+fn zero_strides_panic() {
+    let config = ConvConfig::new([3, 3]).with_stride([0, 1]);
+    Convolutional::<4, 4, 2, 2, _>::new(Tanh, config, testing::seeded_gen(1));
+}
+
+fn same_config() -> ConvConfig {
+    ConvConfig::new([3, 2])
+        .with_stride([2, 1])
+        .with_padding(Padding::Same)
+        .with_dilation([1, 2])
+}
+
+fn dilated_config() -> ConvConfig {
+    ConvConfig::new([3, 3])
+        .with_stride([2, 2])
+        .with_dilation([2, 1])
+}
+
+#[test]
+fn strided_padded_and_dilated_gradients_match_finite_differences() {
+    for algorithm in [ConvAlgorithm::Direct, ConvAlgorithm::Im2col] {
+        let same = Convolutional::<7, 6, 4, 6, _>::new(Tanh, same_config(), testing::seeded_gen(1));
+        assert_gradients_match(same.with_algorithm(algorithm));
+        let dilated =
+            Convolutional::<7, 6, 2, 2, _>::new(Tanh, dilated_config(), testing::seeded_gen(2));
+        assert_gradients_match(dilated.with_algorithm(algorithm));
+        let same =
+            TransposedConv::<4, 6, 7, 6, _>::new(Tanh, same_config(), testing::seeded_gen(3));
+        assert_gradients_match(same.with_algorithm(algorithm));
+        // The outputs are larger than the smallest for these inputs.
+        let dilated =
+            TransposedConv::<2, 2, 7, 6, _>::new(Tanh, dilated_config(), testing::seeded_gen(4));
+        assert_gradients_match(dilated.with_algorithm(algorithm));
+    }
+}
+
+#[test]
+fn configured_algorithms_agree() {
+    let conv = Convolutional::<7, 6, 4, 6, _>::new(Tanh, same_config(), testing::seeded_gen(1));
+    let im2col = conv.clone().with_algorithm(ConvAlgorithm::Im2col);
+    assert_algorithms_agree(conv, im2col);
+    let transposed =
+        TransposedConv::<2, 2, 7, 6, _>::new(Tanh, dilated_config(), testing::seeded_gen(2));
+    let im2col = transposed.clone().with_algorithm(ConvAlgorithm::Im2col);
+    assert_algorithms_agree(transposed, im2col);
+}
+
+#[test]
+fn configured_transposed_convolution_is_the_transpose() {
+    let linear = (|x: Scalar| x, |_: Scalar| 1.0);
+    let mut conv =
+        Convolutional::<7, 6, 4, 6, _>::new(linear, same_config(), testing::seeded_gen(1));
+    let mut transposed =
+        TransposedConv::<4, 6, 7, 6, _>::new(linear, same_config(), testing::seeded_gen(1));
+    assert_eq!(transposed.config(), conv.config());
+    let mut params = conv.params();
+    *params.last_mut().unwrap() = 0.0;
+    conv.read_params(&params);
+    transposed.read_params(&params);
+    let x = Image::from_fn(|x, y, _| (x * y) as Scalar / 8.0 - 1.0);
+    let y = Image::from_fn(|x, y, _| (x + 2 * y) as Scalar / 4.0);
+    let dot = |a: &[Scalar], b: &[Scalar]| a.iter().zip(b).map(|(a, b)| a * b).sum::<Scalar>();
+    let forward = dot(conv.eval(&x).as_slice(), y.as_slice());
+    let backward = dot(x.as_slice(), transposed.eval(&y).as_slice());
+    assert!((forward - backward).abs() < 1e-3, "{forward} != {backward}");
+}
+
+#[test]
+fn channels_are_stored_together() {
+    let linear = (|x: Scalar| x, |_: Scalar| 1.0);
+    // Two input channels to three output channels, with a 1x1 kernel.
+    let mut conv =
+        Convolutional::<2, 1, 2, 1, _, 2, 3>::new(linear, [1, 1], testing::seeded_gen(1));
+    assert_eq!(conv.num_params(), 2 * 3 + 3);
+    conv.read_params(&[1.0, 0.0, 0.0, 1.0, 1.0, 10.0, 0.5, 0.0, -1.0]);
+    let image = Image {
+        pixels: [[[1.0, 2.0], [3.0, 4.0]]],
+    };
+    assert_eq!(
+        conv.eval(&image),
+        Image {
+            pixels: [[[1.5, 2.0, 20.0], [3.5, 4.0, 42.0]]],
+        }
+    );
+}
+
+#[test]
+fn biases_are_trained_per_channel() {
+    let linear = (|x: Scalar| x, |_: Scalar| 1.0);
+    let mut conv =
+        Convolutional::<3, 3, 2, 2, _, 2, 2>::new(linear, [2, 2], testing::seeded_gen(1));
+    let before = conv.params();
+    let inputs = Image::filled(0.0);
+    let inter = conv.intermediate(&inputs);
+    let gradients = Image::from_fn(|x, _, c| if c == 0 { x as Scalar } else { 1.0 });
+    conv.train_deriv(&inputs, &inter, &gradients, 1.0);
+    let after = conv.params();
+    // Without inputs, only the biases change, by the gradients summed over every channel.
+    let biases = before.len() - 2;
+    assert_eq!(before[..biases], after[..biases]);
+    assert_eq!(before[biases] - after[biases], 2.0);
+    assert_eq!(before[biases + 1] - after[biases + 1], 4.0);
+}
+
+#[test]
+fn multi_channel_gradients_match_finite_differences() {
+    for algorithm in [ConvAlgorithm::Direct, ConvAlgorithm::Im2col] {
+        let conv = Convolutional::<5, 4, 3, 3, _, 3, 2>::new(Tanh, [3, 2], testing::seeded_gen(1));
+        assert_gradients_match(conv.with_algorithm(algorithm));
+        let same =
+            Convolutional::<7, 6, 4, 6, _, 2, 3>::new(Tanh, same_config(), testing::seeded_gen(2));
+        assert_gradients_match(same.with_algorithm(algorithm));
+        let transposed =
+            TransposedConv::<3, 2, 5, 4, _, 2, 3>::new(Tanh, [3, 3], testing::seeded_gen(3));
+        assert_gradients_match(transposed.with_algorithm(algorithm));
+        let dilated = TransposedConv::<2, 2, 7, 6, _, 3, 2>::new(
+            Tanh,
+            dilated_config(),
+            testing::seeded_gen(4),
+        );
+        assert_gradients_match(dilated.with_algorithm(algorithm));
+    }
+}
+
+#[test]
+fn multi_channel_algorithms_agree() {
+    let conv =
+        Convolutional::<7, 6, 4, 6, _, 3, 4>::new(Tanh, same_config(), testing::seeded_gen(1));
+    let im2col = conv.clone().with_algorithm(ConvAlgorithm::Im2col);
+    assert_algorithms_agree(conv, im2col);
+    let transposed =
+        TransposedConv::<2, 2, 7, 6, _, 4, 3>::new(Tanh, dilated_config(), testing::seeded_gen(2));
+    let im2col = transposed.clone().with_algorithm(ConvAlgorithm::Im2col);
+    assert_algorithms_agree(transposed, im2col);
+}
+
+#[test]
+fn multi_channel_transposed_convolution_is_the_transpose() {
+    let linear = (|x: Scalar| x, |_: Scalar| 1.0);
+    let mut conv =
+        Convolutional::<7, 6, 2, 2, _, 2, 3>::new(linear, dilated_config(), testing::seeded_gen(1));
+    let mut transposed = TransposedConv::<2, 2, 7, 6, _, 3, 2>::new(
+        linear,
+        dilated_config(),
+        testing::seeded_gen(2),
+    );
+    // The kernels are shared, and the biases of both layers, one per output channel, are zero.
+    let kernels = 3 * 3 * 2 * 3;
+    let mut params = conv.params();
+    params[kernels..].fill(0.0);
+    conv.read_params(&params);
+    let mut transposed_params = params[..kernels].to_vec();
+    transposed_params.extend([0.0; 2]);
+    transposed.read_params(&transposed_params);
+    let x = Image::from_fn(|x, y, c| (x * y + c) as Scalar / 8.0 - 1.0);
+    let y = Image::from_fn(|x, y, c| (x + 2 * y) as Scalar / 4.0 - c as Scalar);
+    let dot = |a: &[Scalar], b: &[Scalar]| a.iter().zip(b).map(|(a, b)| a * b).sum::<Scalar>();
+    let forward = dot(conv.eval(&x).as_slice(), y.as_slice());
+    let backward = dot(x.as_slice(), transposed.eval(&y).as_slice());
+    assert!((forward - backward).abs() < 1e-3, "{forward} != {backward}");
+}
@@ -0,0 +1,51 @@
+use rann_base::{
+    activ::{softmax, LeakyRelu},
+    error::CrossEntropy,
+    testing, train, Full,
+};
+use rann_traits::{Network, Supervised};
+
+#[test]
+fn gradients_match_finite_differences() {
+    let logits = [0.3, -1.2, 2.0, 0.1];
+    for smoothing in [0.0, 0.2] {
+        let mut loss = CrossEntropy::<4>::new().with_label_smoothing(smoothing);
+        loss.set_target(&[0.0, 0.0, 1.0, 0.0]);
+        let gradients = loss.gradient(&logits, &[1.0]);
+        const H: f32 = 1e-2;
+        for i in 0..4 {
+            let (mut above, mut below) = (logits, logits);
+            above[i] += H;
+            below[i] -= H;
+            let numeric = (loss.eval(&above)[0] - loss.eval(&below)[0]) / (2.0 * H);
+            assert!((numeric - gradients[i]).abs() < 1e-3);
+        }
+    }
+}
+
+#[test]
+fn large_logits_do_not_overflow() {
+    let mut loss = CrossEntropy::<2>::new();
+    loss.set_target(&[1.0, 0.0]);
+    assert_eq!(loss.eval(&[1000.0, 0.0]), [0.0]);
+    assert_eq!(loss.eval(&[0.0, 1000.0]), [1000.0]);
+}
+
+#[test]
+fn label_smoothing_limits_confidence() {
+    let dataset = [([1.0, 0.0], [1.0, 0.0]), ([0.0, 1.0], [0.0, 1.0])];
+    let new = |smoothing| {
+        Full::<2, 2, _>::new(LeakyRelu(1.0), testing::seeded_gen(3))
+            .chain(CrossEntropy::new().with_label_smoothing(smoothing))
+    };
+    let (mut sharp, mut smooth) = (new(0.0), new(0.2));
+    for _ in 0..2000 {
+        train::train_epoch(&mut sharp, &dataset, 0.5);
+        train::train_epoch(&mut smooth, &dataset, 0.5);
+    }
+    let sharp = softmax(&sharp.first.eval(&[1.0, 0.0]))[0];
+    let smooth = softmax(&smooth.first.eval(&[1.0, 0.0]))[0];
+    assert!(smooth < sharp);
+    // The optimal prediction with label smoothing is the smoothed target.
+    assert!((smooth - 0.9).abs() < 0.01, "{smooth}");
+}
@@ -0,0 +1,50 @@
+use rann_base::{activ::Logistic, error::SquareError, testing, train, Full};
+use rann_traits::Network;
+
+#[test]
+fn folds_cover_dataset() {
+    let folds: Vec<_> = train::folds(10, 3).collect();
+    assert_eq!(folds, [0..3, 3..6, 6..10]);
+}
+
+#[test]
+fn k_fold_linear_regression() {
+    // Samples of y = x / 2 + 0.25.
+    let dataset: Vec<_> = (0..20)
+        .map(|i| {
+            let x = i as f32 / 20.0;
+            ([x], [x / 2.0 + 0.25])
+        })
+        .collect();
+
+    let mut created = Vec::new();
+    let result = train::cross_validate(
+        &dataset,
+        4,
+        |fold| {
+            created.push(fold);
+            Full::<1, 1, _>::new(Logistic, testing::seeded_gen(fold as u64))
+                .chain(SquareError { expected: [0.0] })
+        },
+        |net, training| {
+            // Every fold is trained on the other three folds.
+            assert_eq!(training.len(), 15);
+            for _ in 0..500 {
+                train::train_epoch(net, training, 0.5);
+            }
+        },
+        |net, validation| {
+            assert_eq!(validation.len(), 5);
+            train::mean_error(net, validation)
+        },
+    );
+
+    assert_eq!(created, [0, 1, 2, 3]);
+    assert_eq!(result.scores.len(), 4);
+    assert!(
+        result.mean < 0.01,
+        "Mean error {} is too large.",
+        result.mean
+    );
+    assert!(result.std_dev >= 0.0);
+}
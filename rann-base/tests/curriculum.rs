@@ -0,0 +1,49 @@
+use rann_base::{
+    curriculum::{Curriculum, Stage},
+    testing,
+    train::{CancellationToken, Trainer},
+};
+use rann_traits::Scalar;
+
+type Sample = ([f32; 2], [f32; 1]);
+
+#[test]
+fn stages_train_on_their_samples_for_their_epochs() {
+    let mut net = testing::xor_net(1);
+    let curriculum = Curriculum::new()
+        .stage(Stage::new(3, 0.1).with_filter(|(_, target): &Sample| target[0] == 1.0))
+        .stage(Stage::new(5, 0.1));
+    let fits = Trainer::default().fit_curriculum(&mut net, &testing::XOR, &curriculum);
+    assert_eq!(fits.len(), 2);
+    assert_eq!((fits[0].errors.len(), fits[0].steps), (3, 3 * 2));
+    assert_eq!((fits[1].errors.len(), fits[1].steps), (5, 5 * 4));
+}
+
+#[test]
+fn stages_end_at_their_threshold() {
+    let mut net = testing::xor_net(1);
+    // The threshold is met after the first epoch.
+    let curriculum = Curriculum::new()
+        .stage(Stage::new(100, 0.1).until(Scalar::INFINITY))
+        .stage(Stage::new(2, 0.1).until(-1.0));
+    let fits = Trainer::default().fit_curriculum(&mut net, &testing::XOR, &curriculum);
+    assert_eq!(fits[0].errors.len(), 1);
+    assert_eq!(fits[1].errors.len(), 2);
+}
+
+#[test]
+fn cancellation_stops_the_curriculum() {
+    let mut net = testing::xor_net(1);
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let trainer = Trainer {
+        cancel: Some(cancel),
+        ..Default::default()
+    };
+    let curriculum: Curriculum<Sample> = Curriculum::new()
+        .stage(Stage::new(10, 0.1))
+        .stage(Stage::new(10, 0.1));
+    let fits = trainer.fit_curriculum(&mut net, &testing::XOR, &curriculum);
+    assert_eq!(fits.len(), 1);
+    assert!(fits[0].cancelled);
+}
@@ -0,0 +1,10 @@
+// Baked by `rann_base::bake`, do not edit.
+static XOR_WEIGHT_0: [rann_traits::Scalar; 4] = [1.0, 1.0, 1.0, 1.0];
+static XOR_BIAS_0: [rann_traits::Scalar; 2] = [0.0, -1.0];
+static XOR_WEIGHT_1: [rann_traits::Scalar; 2] = [1.0, -2.0];
+static XOR_BIAS_1: [rann_traits::Scalar; 1] = [0.0];
+
+/// Returns the baked model `xor`.
+pub const fn xor() -> impl rann_traits::inspect::Inspect<In = [rann_traits::Scalar; 2], Out = [rann_traits::Scalar; 1]> {
+    rann_traits::compose::Chain { first: rann_base::FullView::<2, 2, _>::new(rann_base::activ::LeakyRelu(0.0), &XOR_WEIGHT_0, &XOR_BIAS_0), second: rann_base::FullView::<2, 1, _>::new(rann_base::activ::LeakyRelu(0.0), &XOR_WEIGHT_1, &XOR_BIAS_1) }
+}
@@ -0,0 +1,94 @@
+use rann_base::{
+    activ::Logistic, distill::DistillationLoss, error::CrossEntropy, testing, train::Trainer, Full,
+};
+use rann_traits::{LearningRate, Network, Scalar, Supervised};
+
+const LOGITS: [Scalar; 3] = [0.5, -1.0, 2.0];
+const TEACHER: [Scalar; 3] = [1.5, 0.25, -0.5];
+const LABELS: [Scalar; 3] = [1.0, 0.0, 0.0];
+
+fn loss(temperature: Scalar, alpha: Scalar) -> DistillationLoss<3> {
+    let mut loss = DistillationLoss::new(temperature, alpha);
+    loss.set_target(&(TEACHER, LABELS));
+    loss
+}
+
+#[test]
+fn without_teacher_is_cross_entropy() {
+    let mut ce = CrossEntropy::<3>::new();
+    ce.set_target(&LABELS);
+    let loss = loss(3.0, 0.0);
+    assert!((loss.eval(&LOGITS)[0] - ce.eval(&LOGITS)[0]).abs() < 1e-6);
+    for (a, b) in loss
+        .gradient(&LOGITS, &[1.0])
+        .iter()
+        .zip(ce.gradient(&LOGITS, &[1.0]))
+    {
+        assert!((a - b).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn matching_the_teacher_has_no_divergence() {
+    let loss = loss(2.0, 1.0);
+    assert!(loss.eval(&TEACHER)[0].abs() < 1e-6);
+    assert!(loss
+        .gradient(&TEACHER, &[1.0])
+        .iter()
+        .all(|g| g.abs() < 1e-6));
+    assert!(loss.eval(&LOGITS)[0] > 0.0);
+}
+
+#[test]
+fn gradients_match_numerical() {
+    const H: Scalar = 1e-2;
+    for (temperature, alpha) in [(1.0, 0.5), (4.0, 0.3), (0.5, 0.9)] {
+        let loss = loss(temperature, alpha);
+        let grads = loss.gradient(&LOGITS, &[1.0]);
+        for i in 0..3 {
+            let (mut plus, mut minus) = (LOGITS, LOGITS);
+            plus[i] += H;
+            minus[i] -= H;
+            let numerical = (loss.eval(&plus)[0] - loss.eval(&minus)[0]) / (2.0 * H);
+            assert!(
+                (grads[i] - numerical).abs() < 1e-2,
+                "Gradient {} of logit {i} should be close to {numerical}.",
+                grads[i]
+            );
+        }
+    }
+}
+
+#[test]
+fn student_learns_from_teacher() {
+    let linear = (|x: Scalar| x, |_: Scalar| 1.0);
+    let dataset = testing::XOR.map(|(inputs, [t])| (inputs, [1.0 - t, t]));
+    let trainer = Trainer {
+        epochs: 3000,
+        learning_rate: LearningRate(0.5),
+        ..Default::default()
+    };
+
+    let mut teacher = Full::<2, 8, _>::new(Logistic, testing::seeded_gen(1))
+        .chain(Full::<8, 2, _>::new(linear, testing::seeded_gen(2)))
+        .chain(CrossEntropy::new());
+    trainer.fit(&mut teacher, &dataset);
+    let teacher = teacher.first;
+
+    // Only learning from the teacher, without labels.
+    let mut student = Full::<2, 4, _>::new(Logistic, testing::seeded_gen(3))
+        .chain(Full::<4, 2, _>::new(linear, testing::seeded_gen(4)))
+        .chain(DistillationLoss::new(2.0, 1.0));
+    let fit = trainer.fit_distilled(&teacher, &mut student, &dataset);
+    assert_eq!(fit.steps, 3000 * 4);
+    assert!(*fit.errors.last().unwrap() < 0.01);
+
+    for (inputs, [_, t]) in dataset {
+        let [a, b] = student.first.eval(&inputs);
+        assert_eq!(
+            b > a,
+            t == 1.0,
+            "The student should classify {inputs:?} correctly."
+        );
+    }
+}
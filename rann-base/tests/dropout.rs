@@ -0,0 +1,142 @@
+use fastrand::Rng;
+use rann_base::{
+    activ::{Logistic, Selu},
+    dropout::{AlphaDropout, Dropout, McDropout},
+    error::SquareError,
+    gen, testing, train, Full,
+};
+use rann_traits::{deriv::Deriv, Intermediate, Network, Scalar};
+
+const INPUTS: [Scalar; 8] = [1.0, -2.0, 3.0, 0.5, -1.0, 2.0, 0.25, 4.0];
+
+#[test]
+fn drops_and_scales_inputs() {
+    let dropout = Dropout::<8>::new(0.5, 1);
+    let inter = dropout.intermediate(&INPUTS);
+    let dropped = inter.dropped();
+    assert!(dropped.contains(&true) && dropped.contains(&false));
+    for ((out, x), dropped) in inter.output().iter().zip(INPUTS).zip(dropped) {
+        assert_eq!(*out, if dropped { 0.0 } else { 2.0 * x });
+    }
+}
+
+#[test]
+fn gradients_follow_the_dropped_inputs() {
+    let mut dropout = Dropout::<8>::new(0.25, 2);
+    let inter = dropout.intermediate(&INPUTS);
+    let grads = dropout.train_deriv(&INPUTS, &inter, &[1.0; 8], 0.1);
+    for (gr, dropped) in grads.iter().zip(inter.dropped()) {
+        assert_eq!(*gr, if dropped { 0.0 } else { 1.0 / 0.75 });
+    }
+}
+
+#[test]
+fn evaluations_draw_new_masks() {
+    let dropout = Dropout::<8>::new(0.5, 3);
+    let first = dropout.intermediate(&INPUTS).dropped();
+    assert!((0..10).any(|_| dropout.intermediate(&INPUTS).dropped() != first));
+}
+
+#[test]
+fn inactive_dropout_is_the_identity() {
+    let mut dropout = Dropout::<8>::new(0.9, 4);
+    dropout.active = false;
+    assert_eq!(dropout.eval(&INPUTS), INPUTS);
+}
+
+#[test]
+#[should_panic]
+fn rejects_invalid_probabilities() {
+    Dropout::<2>::new(1.0, 0);
+}
+
+#[test]
+fn mc_dropout_estimates_uncertainty() {
+    let net = Dropout::<8>::new(0.5, 5);
+    let estimate = net.mc_dropout_eval(&INPUTS, 10000);
+    // Every output is zero or twice the input with equal probability.
+    for ((mean, var), x) in estimate.mean.iter().zip(estimate.variance).zip(INPUTS) {
+        assert!(
+            (mean - x).abs() < 0.05 * x.abs() + 0.05,
+            "{mean} should be close to {x}."
+        );
+        assert!(
+            (var - x * x).abs() < 0.1 * x * x,
+            "{var} should be close to {}.",
+            x * x
+        );
+    }
+}
+
+#[test]
+fn deterministic_networks_have_no_variance() {
+    let mut net = Full::<2, 4, _>::new(Logistic, testing::seeded_gen(6))
+        .chain(Dropout::<4>::new(0.5, 6))
+        .chain(Full::<4, 1, _>::new(Logistic, testing::seeded_gen(7)));
+    net.first.second.active = false;
+    let estimate = net.mc_dropout_eval(&[1.0, 0.0], 10);
+    assert_eq!(estimate.mean, net.eval(&[1.0, 0.0]));
+    assert_eq!(estimate.variance, [0.0]);
+    assert_eq!(estimate.std_dev(), [0.0]);
+}
+
+#[test]
+fn trains_with_dropout() {
+    let mut net = Full::<2, 16, _>::new(Logistic, testing::seeded_gen(8))
+        .chain(Dropout::<16>::new(0.1, 8))
+        .chain(Full::<16, 1, _>::new(Logistic, testing::seeded_gen(9)))
+        .chain(SquareError { expected: [0.0] });
+    for _ in 0..5000 {
+        train::train_epoch(&mut net, &testing::XOR, 0.5);
+    }
+    net.first.first.second.active = false;
+    assert!(train::mean_error(&mut net, &testing::XOR) < 0.05);
+}
+
+#[test]
+fn alpha_dropout_keeps_mean_and_variance() {
+    let dropout = AlphaDropout::<64>::new(0.2, 10);
+    let mut rng = Rng::with_seed(10);
+    let (mut sum, mut sq_sum, mut count) = (0.0, 0.0, 0.0);
+    for _ in 0..1000 {
+        let inputs = std::array::from_fn(|_| gen::gaussian(&mut rng));
+        for out in dropout.eval(&inputs) {
+            sum += out as f64;
+            sq_sum += (out * out) as f64;
+            count += 1.0;
+        }
+    }
+    let mean = sum / count;
+    let variance = sq_sum / count - mean * mean;
+    assert!(mean.abs() < 0.02, "{mean} should be close to zero.");
+    assert!(
+        (variance - 1.0).abs() < 0.03,
+        "{variance} should be close to one."
+    );
+}
+
+#[test]
+fn alpha_dropout_sets_dropped_inputs_to_the_saturation() {
+    let mut dropout = AlphaDropout::<8>::new(0.5, 11);
+    let inter = dropout.intermediate(&INPUTS);
+    let dropped = inter.dropped();
+    assert!(dropped.contains(&true) && dropped.contains(&false));
+    let outputs = inter.output();
+    let value = outputs[dropped.iter().position(|&d| d).unwrap()];
+    let grads = dropout.train_deriv(&INPUTS, &inter, &[1.0; 8], 0.1);
+    for ((out, gr), dropped) in outputs.iter().zip(grads).zip(dropped) {
+        if dropped {
+            assert_eq!((*out, gr), (value, 0.0));
+        } else {
+            assert!(gr > 0.0);
+        }
+    }
+    // Dropped inputs are set to the value the activation saturates to.
+    let saturated = AlphaDropout::<8>::new(0.5, 12).eval(&[Selu.call(&-100.0); 8]);
+    for out in saturated {
+        assert!((out - value).abs() < 1e-5);
+    }
+
+    dropout.active = false;
+    assert_eq!(dropout.eval(&INPUTS), INPUTS);
+}
@@ -0,0 +1,121 @@
+use std::fs;
+
+use rann_base::{
+    dump::{Dump, DumpFormat, Dumper, LayerDump},
+    testing,
+    train::Trainer,
+};
+use rann_traits::{params::Parameterized, LearningRate};
+
+fn example() -> Dump {
+    Dump {
+        epoch: 12,
+        layers: vec![
+            LayerDump {
+                kind: "Full (Logistic)".to_owned(),
+                num_inputs: 2,
+                params: vec![vec![0.5, -1.25], vec![0.1]],
+                activations: vec![0.75],
+                gradient_norm: 0.0625,
+            },
+            LayerDump {
+                kind: "SquareError".to_owned(),
+                num_inputs: 1,
+                params: vec![],
+                activations: vec![f32::NAN],
+                gradient_norm: 0.0,
+            },
+        ],
+    }
+}
+
+#[test]
+fn binary_dumps_round_trip() {
+    let dump = example();
+    let mut bytes = Vec::new();
+    dump.write_to(&mut bytes).unwrap();
+    let read = Dump::read_from(&bytes[..]).unwrap();
+    assert_eq!(read.epoch, 12);
+    assert_eq!(read.layers[0], dump.layers[0]);
+    assert_eq!(read.layers[1].kind, "SquareError");
+    assert!(read.layers[1].activations[0].is_nan());
+
+    // Truncated and foreign data is rejected.
+    assert!(Dump::read_from(&bytes[..bytes.len() - 1]).is_err());
+    assert!(Dump::read_from(&b"RANNCKPT\x01\0\0\0"[..]).is_err());
+}
+
+#[test]
+fn text_dumps_list_every_layer() {
+    let mut text = Vec::new();
+    example().write_text(&mut text).unwrap();
+    let expected = "epoch 12\n\
+        \n\
+        layer 0: Full (Logistic)\n\
+        inputs: 2\n\
+        gradient norm: 0.0625\n\
+        params 0: 0.5 -1.25\n\
+        params 1: 0.1\n\
+        activations: 0.75\n\
+        \n\
+        layer 1: SquareError\n\
+        inputs: 1\n\
+        gradient norm: 0\n\
+        activations: NaN\n";
+    assert_eq!(String::from_utf8(text).unwrap(), expected);
+}
+
+#[test]
+fn dumps_match_the_network() {
+    let net = testing::xor_net(1);
+    let dir = tempfile::tempdir().unwrap();
+    let dir = dir.path();
+    let dumper = Dumper::new(dir).with_format(DumpFormat::Binary);
+    let path = dumper.dump(&net, &[1.0, 0.0], 4).unwrap();
+    assert_eq!(path, dir.join("dump-00000004.dump"));
+    let dump = Dump::load(&path).unwrap();
+    let params: Vec<f32> = dump
+        .layers
+        .iter()
+        .flat_map(|layer| layer.params.iter().flatten().copied())
+        .collect();
+    assert_eq!(params, net.params());
+    assert_eq!(dump.layers.len(), 3);
+    assert_eq!(dump.layers[1].num_inputs, 3);
+}
+
+#[test]
+fn training_dumps_every_epoch() {
+    let mut net = testing::xor_net(1);
+    let dir = tempfile::tempdir().unwrap();
+    let dir = dir.path();
+    let dumper = Dumper::new(dir);
+    let trainer = Trainer {
+        epochs: 3,
+        learning_rate: LearningRate(0.5),
+        ..Default::default()
+    };
+    let fit = trainer
+        .fit_dumped(&mut net, &testing::XOR, &dumper, &[0.0, 1.0])
+        .unwrap();
+    assert_eq!(fit.errors.len(), 3);
+    let mut files: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    files.sort();
+    assert_eq!(
+        files,
+        [
+            "dump-00000000.txt",
+            "dump-00000001.txt",
+            "dump-00000002.txt",
+            "dump-00000003.txt"
+        ]
+    );
+    // The weights changed between the epochs.
+    let first = fs::read_to_string(dumper.path(0)).unwrap();
+    let last = fs::read_to_string(dumper.path(3)).unwrap();
+    assert!(first.starts_with("epoch 0\n"));
+    assert_ne!(first.lines().nth(5), last.lines().nth(5));
+}
@@ -0,0 +1,42 @@
+use rann_base::{activ::Logistic, ema::EmaWeights, error::SquareError, testing, train, Full};
+use rann_traits::{params::Parameterized, Network};
+
+#[test]
+fn average_follows_the_parameters() {
+    let net = Full::<2, 2, _>::new(Logistic, testing::seeded_gen(1));
+    let start = net.params();
+    let mut ema = EmaWeights::new(net, 0.9).chain(SquareError {
+        expected: [1.0, 0.0],
+    });
+    assert_eq!(ema.first.average(), start);
+
+    let inputs = [0.5, -0.5];
+    let inter = ema.intermediate(&inputs);
+    ema.train_deriv(&inputs, &inter, &[1.0], 0.5);
+    let trained = ema.first.params();
+    assert_ne!(trained, start);
+    for ((avg, s), t) in ema.first.average().iter().zip(&start).zip(&trained) {
+        assert!((avg - (0.9 * s + 0.1 * t)).abs() < 1e-6);
+    }
+
+    // Evaluating with the average does not change the trained parameters.
+    let averaged = ema.first.averaged();
+    assert_eq!(averaged.params(), ema.first.average());
+    assert_eq!(ema.first.params(), trained);
+}
+
+#[test]
+fn averaged_network_learns() {
+    let net = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1))
+        .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)));
+    let mut ema = EmaWeights::new(net, 0.99).chain(SquareError { expected: [0.0] });
+    let before = train::mean_error(&mut ema, &testing::XOR);
+    for _ in 0..3000 {
+        train::train_epoch(&mut ema, &testing::XOR, 0.5);
+    }
+    let mut averaged = ema
+        .first
+        .into_averaged()
+        .chain(SquareError { expected: [0.0] });
+    assert!(train::mean_error(&mut averaged, &testing::XOR) < before / 2.0);
+}
@@ -0,0 +1,47 @@
+use rann_base::{
+    activ::Logistic,
+    error::SquareError,
+    evolution::{Evolution, EvolutionConfig},
+    testing, train, Full,
+};
+use rann_traits::{params::Parameterized, Network};
+
+#[test]
+fn params_round_trip() {
+    let mut net = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1))
+        .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)));
+    assert_eq!(net.num_params(), 2 * 3 + 3 + 3 + 1);
+
+    let params: Vec<f32> = (0..net.num_params()).map(|i| i as f32 * 0.1).collect();
+    net.read_params(&params);
+    assert_eq!(net.params(), params);
+}
+
+#[test]
+fn evolve_xor() {
+    let config = EvolutionConfig {
+        seed: 0xe0,
+        ..Default::default()
+    };
+    let mut evolution = Evolution::new(config, |rng| {
+        Full::<2, 3, _>::new(Logistic, testing::seeded_gen(rng.u64(..)))
+            .chain(Full::<3, 1, _>::new(
+                Logistic,
+                testing::seeded_gen(rng.u64(..)),
+            ))
+            .chain(SquareError { expected: [0.0] })
+    });
+
+    let first = evolution.generation(|net| train::mean_error(net, &testing::XOR));
+    let mut last = first;
+    for _ in 0..200 {
+        last = evolution.generation(|net| train::mean_error(net, &testing::XOR));
+    }
+    // Elites are kept, so the best fitness never gets worse.
+    assert!(last <= first);
+    assert!(last < 0.05, "Best error {last} is too large.");
+
+    let best = evolution.best().unwrap().fitness;
+    let mut net = evolution.into_best();
+    assert_eq!(train::mean_error(&mut net, &testing::XOR), best);
+}
@@ -0,0 +1,121 @@
+use rann_base::{
+    activ::Logistic,
+    error::SquareError,
+    features::{self, Polynomial, Rbf},
+    testing, Full,
+};
+use rann_traits::{Network, Scalar};
+
+// The step used for the central differences.
+const H: Scalar = 1e-2;
+// The maximum allowed difference between the analytical and numerical gradients.
+const TOLERANCE: Scalar = 1e-2;
+
+// Checks the input gradients of `net` against central differences of the outputs weighted by
+// `weights`.
+fn check_gradients<const N: usize, const M: usize>(
+    mut net: impl Network<In = [Scalar; N], Out = [Scalar; M]>,
+    input: [Scalar; N],
+    weights: [Scalar; M],
+) {
+    let loss = |net: &dyn Fn(&[Scalar; N]) -> [Scalar; M], input: &[Scalar; N]| -> Scalar {
+        net(input).iter().zip(weights).map(|(y, w)| y * w).sum()
+    };
+    let inter = net.intermediate(&input);
+    let grad = net.train_deriv(&input, &inter, &weights, 0.0);
+    let eval = |x: &[Scalar; N]| net.eval(x);
+    for i in 0..N {
+        let (mut plus, mut minus) = (input, input);
+        plus[i] += H;
+        minus[i] -= H;
+        let numerical = (loss(&eval, &plus) - loss(&eval, &minus)) / (2.0 * H);
+        assert!(
+            (grad[i] - numerical).abs() < TOLERANCE,
+            "Gradient {} of input {i} should be close to {numerical}.",
+            grad[i]
+        );
+    }
+}
+
+#[test]
+fn counts_monomials() {
+    assert_eq!(features::num_monomials(2, 1), 2);
+    assert_eq!(features::num_monomials(2, 2), 5);
+    assert_eq!(features::num_monomials(3, 2), 9);
+    assert_eq!(features::num_monomials(2, 3), 9);
+}
+
+#[test]
+fn polynomial_features() {
+    let poly = Polynomial::<2, 9>::new(3);
+    assert_eq!(
+        poly.exponents(),
+        &[
+            [1, 0],
+            [0, 1],
+            [2, 0],
+            [1, 1],
+            [0, 2],
+            [3, 0],
+            [2, 1],
+            [1, 2],
+            [0, 3]
+        ]
+    );
+    assert_eq!(
+        poly.eval(&[2.0, 3.0]),
+        [2.0, 3.0, 4.0, 6.0, 9.0, 8.0, 12.0, 18.0, 27.0]
+    );
+}
+
+#[test]
+#[should_panic]
+fn polynomial_checks_feature_count() {
+    Polynomial::<2, 6>::new(2);
+}
+
+#[test]
+fn polynomial_gradients_match_numerical() {
+    check_gradients(
+        Polynomial::<3, 19>::new(3),
+        [0.5, -1.25, 0.75],
+        std::array::from_fn(|i| (i as Scalar * 0.37).sin()),
+    );
+}
+
+#[test]
+fn rbf_features() {
+    let rbf = Rbf::new([[0.0, 0.0], [1.0, 1.0]], 0.5);
+    let out = rbf.eval(&[1.0, 1.0]);
+    assert!((out[0] - (-1.0 as Scalar).exp()).abs() < 1e-6);
+    assert_eq!(out[1], 1.0);
+}
+
+#[test]
+fn rbf_gradients_match_numerical() {
+    check_gradients(
+        Rbf::new([[0.0, 0.5], [1.0, -1.0], [-0.5, 0.25]], 0.8),
+        [0.3, -0.2],
+        [0.7, -1.1, 0.4],
+    );
+}
+
+// A single layer fits XOR on RBF features centered on the samples.
+#[test]
+fn rbf_fits_xor() {
+    let centers = testing::XOR.map(|(inputs, _)| inputs);
+    let mut net = Rbf::new(centers, 2.0)
+        .chain(Full::<4, 1, _>::new(Logistic, testing::seeded_gen(3)))
+        .chain(SquareError { expected: [0.0] });
+    testing::assert_network_converges(&mut net, &testing::XOR, 5000, 1.0, 0.01);
+}
+
+// The gradients pass through the features to the layers before them.
+#[test]
+fn trains_layers_before_features() {
+    let mut net = Full::<2, 2, _>::new(Logistic, testing::seeded_gen(4))
+        .chain(Polynomial::<2, 5>::new(2))
+        .chain(Full::<5, 1, _>::new(Logistic, testing::seeded_gen(5)))
+        .chain(SquareError { expected: [0.0] });
+    testing::assert_network_converges(&mut net, &testing::XOR, 10000, 1.0, 0.05);
+}
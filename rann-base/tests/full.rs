@@ -0,0 +1,142 @@
+use rann_base::{
+    activ::{LeakyRelu, Logistic},
+    testing, Full, FullError, FullView, NoBias, TiedFull,
+};
+use rann_traits::{compose::Shared, params::Parameterized, Intermediate, Network, Scalar};
+
+#[test]
+fn finite_generators_create_the_same_layer() {
+    let layer = Full::<3, 2, _>::try_new(Logistic, testing::seeded_gen(1)).unwrap();
+    let expected = Full::<3, 2, _>::new(Logistic, testing::seeded_gen(1));
+    assert_eq!(layer.params(), expected.params());
+}
+
+#[test]
+fn non_finite_weights_are_rejected() {
+    let gen = (
+        |output, input| {
+            if (output, input) == (1, 2) {
+                Scalar::NAN
+            } else {
+                0.5
+            }
+        },
+        |_| 0.0,
+    );
+    let Err(FullError::NonFiniteWeight {
+        output,
+        input,
+        value,
+    }) = Full::<3, 2, _>::try_new(Logistic, gen)
+    else {
+        panic!("The weight should be rejected.");
+    };
+    assert_eq!((output, input), (1, 2));
+    assert!(value.is_nan());
+    let layer = Full::<3, 2, _, NoBias>::try_without_bias(Logistic, gen);
+    assert!(matches!(
+        layer,
+        Err(FullError::NonFiniteWeight {
+            output: 1,
+            input: 2,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn non_finite_biases_are_rejected() {
+    let gen = (|_, _| 0.5, |output| 1.0 / (output as Scalar - 1.0));
+    let error = Full::<3, 2, _>::try_new(Logistic, gen).unwrap_err();
+    assert_eq!(
+        error,
+        FullError::NonFiniteBias {
+            output: 1,
+            value: Scalar::INFINITY,
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "bias of output 1 is inf, but should be finite"
+    );
+    // Without biases, the bias generator is not used.
+    assert!(Full::<3, 2, _, NoBias>::try_without_bias(Logistic, gen).is_ok());
+}
+
+#[test]
+fn eval_matches_intermediate() {
+    let full = Full::<3, 2, _>::new(Logistic, testing::seeded_gen(1));
+    let unbiased = Full::<3, 2, _, _>::without_bias(LeakyRelu(0.1), testing::seeded_gen(2));
+    let view = FullView::<3, 2, _>::new(Logistic, &[0.5, -1.0, 2.0, 0.0, 1.0, -0.5], &[0.1, -0.2]);
+    let tied = TiedFull::<2, 3, _, _>::new(Shared::new(full.clone()), Logistic, |i| i as Scalar);
+    for input in [[0.0, 0.0, 0.0], [1.0, -2.0, 0.5], [-0.3, 0.2, 4.0]] {
+        assert_eq!(full.eval(&input), full.intermediate(&input).into_output());
+        assert_eq!(
+            unbiased.eval(&input),
+            unbiased.intermediate(&input).into_output()
+        );
+        assert_eq!(view.eval(&input), view.intermediate(&input).into_output());
+        let hidden = full.eval(&input);
+        assert_eq!(tied.eval(&hidden), tied.intermediate(&hidden).into_output());
+    }
+}
+
+// Asserts that evaluating `inputs` at once matches evaluating them one by one, up to rounding.
+fn assert_eval_many<N, const M: usize>(net: &N, inputs: &[[Scalar; 3]])
+where
+    N: Network<In = [Scalar; 3], Out = [Scalar; M]>,
+{
+    let outputs = net.eval_many(inputs);
+    assert_eq!(outputs.len(), inputs.len());
+    for (input, outputs) in inputs.iter().zip(outputs) {
+        for (a, b) in outputs.iter().zip(net.eval(input)) {
+            assert!((a - b).abs() < 1e-5, "{a} != {b}");
+        }
+    }
+}
+
+#[test]
+fn eval_many_matches_eval() {
+    let full = Full::<3, 20, _>::new(Logistic, testing::seeded_gen(1));
+    let unbiased = Full::<20, 2, _, _>::without_bias(LeakyRelu(0.1), testing::seeded_gen(2));
+    let view = FullView::<3, 2, _>::new(Logistic, &[0.5, -1.0, 2.0, 0.0, 1.0, -0.5], &[0.1, -0.2]);
+    let inputs: Vec<_> = (0..37)
+        .map(|i| [i as Scalar / 10.0, -1.0, (i % 3) as Scalar])
+        .collect();
+    assert_eval_many(&full, &inputs);
+    assert_eval_many(&view, &inputs);
+    assert_eval_many(&full.chain(unbiased), &inputs);
+    assert_eval_many(&view, &[]);
+}
+
+// Evaluates a layer with a ReLU activation from its parameters.
+fn eval_params<const NUM_IN: usize, const NUM_OUT: usize>(
+    params: &[Scalar],
+    input: &[Scalar; NUM_IN],
+) -> [Scalar; NUM_OUT] {
+    std::array::from_fn(|out| {
+        let sum = (0..NUM_IN)
+            .map(|i| params[i * NUM_OUT + out] * input[i])
+            .sum::<Scalar>()
+            + params[NUM_IN * NUM_OUT + out];
+        sum.max(0.0)
+    })
+}
+
+#[test]
+fn tiny_and_general_layers_match_their_parameters() {
+    let tiny = Full::<4, 3, _>::new(LeakyRelu(0.0), testing::seeded_gen(1));
+    let general = Full::<9, 2, _>::new(LeakyRelu(0.0), testing::seeded_gen(2));
+    for seed in 0..10 {
+        let input: [Scalar; 9] = std::array::from_fn(|i| ((i + seed) % 5) as Scalar - 2.0);
+        let tiny_input = [input[0], input[1], input[2], input[3]];
+        let expected = eval_params::<4, 3>(&tiny.params(), &tiny_input);
+        for (a, b) in tiny.eval(&tiny_input).iter().zip(expected) {
+            assert!((a - b).abs() < 1e-5);
+        }
+        let expected = eval_params::<9, 2>(&general.params(), &input);
+        for (a, b) in general.eval(&input).iter().zip(expected) {
+            assert!((a - b).abs() < 1e-5);
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use rann_base::{
+    activ::Logistic,
+    error::{LogisticWithBce, SoftmaxWithCe},
+    testing, Full,
+};
+use rann_traits::{Network, Scalar, Supervised};
+
+// The binary cross-entropy, computed naively from probabilities.
+fn naive_bce(logits: &[Scalar], targets: &[Scalar]) -> Scalar {
+    logits
+        .iter()
+        .zip(targets)
+        .map(|(x, t)| {
+            let p = 1.0 / (1.0 + (-x).exp());
+            -t * p.ln() - (1.0 - t) * (1.0 - p).ln()
+        })
+        .sum()
+}
+
+#[test]
+fn matches_naive_bce_for_small_logits() {
+    let logits = [0.3, -1.2, 2.0];
+    let mut loss = LogisticWithBce::<3>::new();
+    loss.set_target(&[1.0, 0.0, 0.25]);
+    let error = loss.eval(&logits)[0];
+    assert!((error - naive_bce(&logits, &loss.expected)).abs() < 1e-5);
+}
+
+#[test]
+fn gradients_match_finite_differences() {
+    let logits = [0.3, -1.2, 2.0];
+    let mut loss = LogisticWithBce::<3>::new();
+    loss.set_target(&[1.0, 0.0, 0.25]);
+    let gradients = loss.gradient(&logits, &[1.0]);
+    const H: f32 = 1e-2;
+    for i in 0..3 {
+        let (mut above, mut below) = (logits, logits);
+        above[i] += H;
+        below[i] -= H;
+        let numeric = (loss.eval(&above)[0] - loss.eval(&below)[0]) / (2.0 * H);
+        assert!((numeric - gradients[i]).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn large_logits_stay_finite() {
+    let mut loss = LogisticWithBce::<2>::new();
+    loss.set_target(&[1.0, 0.0]);
+    // The naive computation takes the logarithm of zero.
+    assert!(naive_bce(&[-200.0, 0.0], &loss.expected).is_infinite());
+    assert_eq!(loss.eval(&[-200.0, 0.0]), [200.0 + 2f32.ln()]);
+    assert_eq!(loss.eval(&[200.0, -200.0]), [0.0]);
+    assert_eq!(loss.gradient(&[200.0, -200.0], &[1.0]), [0.0, 0.0]);
+    assert_eq!(
+        LogisticWithBce::probabilities(&[-1000.0, 1000.0]),
+        [0.0, 1.0]
+    );
+}
+
+#[test]
+fn softmax_with_ce_is_stable() {
+    let mut loss = SoftmaxWithCe::<2>::new();
+    loss.set_target(&[0.0, 1.0]);
+    assert_eq!(loss.eval(&[1000.0, 0.0]), [1000.0]);
+}
+
+#[test]
+fn learns_xor_from_logits() {
+    let linear = (|x: Scalar| x, |_: Scalar| 1.0);
+    let mut net = Full::<2, 4, _>::new(Logistic, testing::seeded_gen(1))
+        .chain(Full::<4, 1, _>::new(linear, testing::seeded_gen(2)))
+        .chain(LogisticWithBce::new());
+    testing::assert_network_converges(&mut net, &testing::XOR, 2000, 0.5, 0.01);
+    for (inputs, [target]) in testing::XOR {
+        let [p] = LogisticWithBce::probabilities(&net.first.eval(&inputs));
+        assert!((p - target).abs() < 0.1);
+    }
+}
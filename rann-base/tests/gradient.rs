@@ -0,0 +1,91 @@
+use rann_base::{activ::Logistic, error::SquareError, testing, Full};
+use rann_traits::{params::Parameterized, Network, Supervised};
+
+#[test]
+fn input_gradient_matches_finite_differences() {
+    let net = Full::<3, 4, _>::new(Logistic, testing::seeded_gen(1))
+        .chain(Full::<4, 2, _>::new(Logistic, testing::seeded_gen(2)))
+        .chain(SquareError { expected: [0.0; 2] });
+    let inputs = [0.3, -0.7, 1.1];
+    let target = [1.0, 0.0];
+    let params = net.params();
+
+    let gradient = net.input_gradient(&inputs, &target);
+
+    let error = |inputs: &[f32; 3]| {
+        let mut net = net.clone();
+        net.set_target(&target);
+        net.eval(inputs)[0]
+    };
+    const H: f32 = 1e-2;
+    for i in 0..3 {
+        let (mut above, mut below) = (inputs, inputs);
+        above[i] += H;
+        below[i] -= H;
+        let numeric = (error(&above) - error(&below)) / (2.0 * H);
+        assert!(
+            (numeric - gradient[i]).abs() < 1e-3,
+            "{numeric} != {}",
+            gradient[i]
+        );
+    }
+    // Neither the parameters nor the target changed.
+    assert_eq!(net.params(), params);
+    assert_eq!(net.second.expected, [0.0; 2]);
+}
+
+#[test]
+fn gradient_does_not_train() {
+    let net = Full::<2, 2, _>::new(Logistic, testing::seeded_gen(3));
+    let before = net.eval(&[1.0, 2.0]);
+    let gradient = net.gradient(&[1.0, 2.0], &[1.0, -1.0]);
+    assert_ne!(gradient, [0.0; 2]);
+    assert_eq!(net.eval(&[1.0, 2.0]), before);
+}
+
+#[test]
+fn train_seeds_gradients_of_one() {
+    let mut net = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1))
+        .chain(SquareError { expected: [0.5; 3] });
+    let mut explicit = net.clone();
+    let inputs = [0.4, -0.2];
+    let inter = net.intermediate(&inputs);
+    net.train(&inputs, &inter, 0.5);
+    explicit.train_deriv(&inputs, &inter, &[1.0], 0.5);
+    assert_eq!(net.params(), explicit.params());
+
+    // Networks without an error function are trained as if their outputs are the error.
+    let mut layer = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1));
+    let mut explicit = layer.clone();
+    let inter = layer.intermediate(&inputs);
+    layer.train(&inputs, &inter, 0.5);
+    explicit.train_deriv(&inputs, &inter, &[1.0; 3], 0.5);
+    assert_eq!(layer.params(), explicit.params());
+}
+
+#[test]
+fn ones_like_outputs() {
+    use rann_traits::Gradient;
+
+    assert_eq!([3.0, -1.0].ones_like(), [1.0; 2]);
+    assert_eq!(vec![0.0; 4].ones_like(), vec![1.0; 4]);
+    assert_eq!(Vec::<f32>::new().ones_like(), Vec::<f32>::new());
+}
+
+#[test]
+fn terminal_networks_start_backpropagation() {
+    use rann_traits::Terminal;
+
+    let mut net = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(2))
+        .chain(SquareError { expected: [0.5; 3] });
+    let mut explicit = net.clone();
+    let inputs = [0.4, -0.2];
+    let inter = net.intermediate(&inputs);
+    assert_eq!(net.seed(), [1.0]);
+    let gradients = net.train_error(&inputs, &inter, 0.5);
+    assert_eq!(
+        gradients,
+        explicit.train_deriv(&inputs, &inter, &[1.0], 0.5)
+    );
+    assert_eq!(net.params(), explicit.params());
+}
@@ -0,0 +1,207 @@
+use rann_base::{activ::Logistic, testing, Full};
+use rann_traits::{
+    compose::{
+        graph::{EvalScratch, Graph, GraphBuilder, GraphError, Source},
+        zip,
+    },
+    Intermediate, Network,
+};
+
+fn layers() -> (
+    Full<2, 3, Logistic>,
+    Full<2, 2, Logistic>,
+    Full<5, 1, Logistic>,
+) {
+    let gen = testing::seeded_gen;
+    (
+        Full::new(Logistic, gen(1)),
+        Full::new(Logistic, gen(2)),
+        Full::new(Logistic, gen(3)),
+    )
+}
+
+#[test]
+fn matches_zip_and_chain() {
+    // Both branches use the same inputs, so duplicate them for the zip.
+    let (a, b, joined) = layers();
+    let mut composed = a.zip(b, zip::Stacker::<3, 2, 5>).chain(joined);
+
+    let (a, b, joined) = layers();
+    let mut builder = GraphBuilder::<2, 1>::new();
+    let a = builder.add_node(a);
+    let b = builder.add_node(b);
+    // Nodes may be added in any order.
+    let joined_id = builder.add_node(joined);
+    builder
+        .connect(a, joined_id)
+        .connect(b, joined_id)
+        .connect(Source::Input, a)
+        .connect(Source::Input, b)
+        .output(joined_id);
+    let mut graph = builder.build().unwrap();
+    assert_eq!(graph.len(), 3);
+
+    for _ in 0..10 {
+        for (inputs, target) in &testing::XOR {
+            let zipped = (*inputs, *inputs);
+            let c_inter = composed.intermediate(&zipped);
+            let g_inter = graph.intermediate(inputs);
+            assert_eq!(c_inter.output(), g_inter.output());
+
+            let grads = [c_inter.output()[0] - target[0]];
+            let c_grads = composed.train_deriv(&zipped, &c_inter, &grads, 0.5);
+            let g_grads = graph.train_deriv(inputs, &g_inter, &grads, 0.5);
+            // The gradients of inputs used twice are summed.
+            for ((a, b), g) in c_grads.0.iter().zip(c_grads.1).zip(g_grads) {
+                assert!((a + b - g).abs() < 1e-6, "{} != {g}", a + b);
+            }
+        }
+    }
+}
+
+fn joined_graph() -> Graph<2, 1> {
+    let (a, b, joined) = layers();
+    let mut builder = GraphBuilder::<2, 1>::new();
+    let a = builder.add_node(a);
+    let b = builder.add_node(b);
+    let joined = builder.add_node(joined);
+    builder
+        .connect(Source::Input, a)
+        .connect(Source::Input, b)
+        .connect(a, joined)
+        .connect(b, joined)
+        .output(joined);
+    builder.build().unwrap()
+}
+
+#[test]
+fn scratch_is_reused() {
+    let mut graph = joined_graph();
+    let mut reference = joined_graph();
+    let mut scratch = EvalScratch::new();
+    for _ in 0..10 {
+        for (inputs, target) in &testing::XOR {
+            let inter = graph.intermediate(inputs);
+            assert_eq!(graph.eval_with(inputs, &mut scratch), *inter.output());
+            let grads = [inter.output()[0] - target[0]];
+            let found = graph.train_deriv_with(&inter, &grads, 0.5, &mut scratch);
+            let expected =
+                reference.train_deriv(inputs, &reference.intermediate(inputs), &grads, 0.5);
+            assert_eq!(found, expected);
+        }
+    }
+
+    // The same scratch can be used with other graphs.
+    let (a, _, _) = layers();
+    let mut builder = GraphBuilder::<2, 5>::new();
+    let a = builder.add_node(a);
+    builder
+        .connect(Source::Input, a)
+        .output(a)
+        .output(Source::Input);
+    let other = builder.build().unwrap();
+    assert_eq!(
+        other.eval_with(&[0.5, 1.0], &mut scratch),
+        other.eval(&[0.5, 1.0])
+    );
+    assert_eq!(
+        graph.eval_with(&[0.5, 1.0], &mut scratch),
+        reference.eval(&[0.5, 1.0])
+    );
+}
+
+#[test]
+fn intermediates_are_reused() {
+    let mut graph = joined_graph();
+    let mut reference = joined_graph();
+    let mut inter = graph.intermediate(&[0.0, 0.0]);
+    for _ in 0..10 {
+        for (inputs, target) in &testing::XOR {
+            graph.intermediate_into(inputs, &mut inter);
+            let expected = reference.intermediate(inputs);
+            assert_eq!(inter.output(), expected.output());
+            let grads = [inter.output()[0] - target[0]];
+            assert_eq!(
+                graph.train_deriv(inputs, &inter, &grads, 0.5),
+                reference.train_deriv(inputs, &expected, &grads, 0.5)
+            );
+        }
+    }
+
+    // Intermediates of other networks are replaced.
+    let mut other = GraphBuilder::<2, 1>::new();
+    let node = other.add_node(Full::<2, 1, _>::new(Logistic, testing::seeded_gen(4)));
+    other.connect(Source::Input, node).output(node);
+    let other = other.build().unwrap();
+    other.intermediate_into(&[1.0, 0.0], &mut inter);
+    assert_eq!(*inter.output(), other.eval(&[1.0, 0.0]));
+
+    // Chains reuse the intermediates of their parts.
+    let net = joined_graph().chain(Full::<1, 2, _>::new(Logistic, testing::seeded_gen(5)));
+    let mut inter = net.intermediate(&[0.0, 0.0]);
+    net.intermediate_into(&[1.0, 0.0], &mut inter);
+    assert_eq!(*inter.output(), net.eval(&[1.0, 0.0]));
+}
+
+#[test]
+fn outputs_can_be_inputs() {
+    let (a, _, _) = layers();
+    let mut builder = GraphBuilder::<2, 5>::new();
+    let a = builder.add_node(a);
+    builder
+        .connect(Source::Input, a)
+        .output(Source::Input)
+        .output(a);
+    let graph = builder.build().unwrap();
+    let out = graph.eval(&[0.25, 0.75]);
+    assert_eq!(out[..2], [0.25, 0.75]);
+    assert_eq!(out[2..], layers().0.eval(&[0.25, 0.75]));
+}
+
+#[test]
+fn rejects_invalid_graphs() {
+    let (a, b, joined) = layers();
+    let mut builder = GraphBuilder::<2, 1>::new();
+    let a = builder.add_node(a);
+    let b = builder.add_node(b);
+    builder.connect(Source::Input, a).connect(Source::Input, b);
+    builder.output(a);
+    assert_eq!(
+        builder.build().err(),
+        Some(GraphError::Mismatch {
+            node: None,
+            expected: 1,
+            found: 3
+        })
+    );
+
+    let mut builder = GraphBuilder::<2, 1>::new();
+    let joined = builder.add_node(joined);
+    builder.connect(Source::Input, joined).output(joined);
+    assert_eq!(
+        builder.build().err(),
+        Some(GraphError::Mismatch {
+            node: Some(joined),
+            expected: 5,
+            found: 2
+        })
+    );
+
+    // Two nodes using each others outputs.
+    let mut builder = GraphBuilder::<2, 2>::new();
+    let x = builder.add_node(Full::<2, 2, _>::new(Logistic, testing::seeded_gen(1)));
+    let y = builder.add_node(Full::<2, 2, _>::new(Logistic, testing::seeded_gen(2)));
+    builder.connect(x, y).connect(y, x).output(y);
+    assert_eq!(builder.build().err(), Some(GraphError::Cycle));
+
+    // A node of another graph.
+    let mut other = GraphBuilder::<2, 2>::new();
+    other.add_node(Full::<2, 2, _>::new(Logistic, testing::seeded_gen(1)));
+    let foreign = other.add_node(Full::<2, 2, _>::new(Logistic, testing::seeded_gen(2)));
+    let mut builder = GraphBuilder::<2, 2>::new();
+    builder.output(foreign);
+    assert_eq!(
+        builder.build().err(),
+        Some(GraphError::UnknownNode(foreign))
+    );
+}
@@ -0,0 +1,134 @@
+use rann_base::{
+    activ::LeakyRelu, conv::Convolutional, error::SquareError, image::Image, testing,
+    train::Trainer, Full, LayerNorm,
+};
+use rann_traits::{
+    guard::{self, Kind},
+    LearningRate, Network,
+};
+
+#[test]
+fn finite_training_succeeds() {
+    let gen = (|i, j| (i * 2 + j) as f32 * 0.1, |_| 0.0);
+    let mut net = Full::<2, 2, _>::new(LeakyRelu(0.1), gen).chain(SquareError {
+        expected: [1.0, 0.0],
+    });
+    let inputs = [0.5, 1.0];
+    let inter = net.intermediate(&inputs);
+    assert!(guard::train_deriv_checked(&mut net, &inputs, &inter, &[1.0], 0.1).is_ok());
+}
+
+#[test]
+fn non_finite_activation_is_located() {
+    let gen = (|_, _| 1.0, |_| 0.0);
+    let mut net = Full::<2, 3, _>::new(LeakyRelu(0.1), gen)
+        .chain(LayerNorm::<3>::new())
+        .chain(SquareError { expected: [0.0; 3] });
+    let inputs = [f32::NAN, 1.0];
+    let inter = net.intermediate(&inputs);
+    let err = guard::train_deriv_checked(&mut net, &inputs, &inter, &[1.0], 0.1).unwrap_err();
+    assert_eq!(err.layer, 0);
+    assert_eq!(err.kind, Kind::Activation);
+    assert_eq!(err.element, Some(0));
+}
+
+#[test]
+fn exploding_parameters_are_located() {
+    let gen = (|_, _| 1.0, |_| 0.0);
+    let mut net = Full::<2, 2, _>::new(LeakyRelu(0.1), gen)
+        .chain(Full::<2, 1, _>::new(LeakyRelu(0.1), gen))
+        .chain(SquareError { expected: [0.0] });
+    let inputs = [1e10, 1e10];
+    let inter = net.intermediate(&inputs);
+    // The activations are still finite...
+    assert!(guard::check_activations(&net, &inter).is_ok());
+    // ...but the updates are not.
+    let err = guard::train_deriv_checked(&mut net, &inputs, &inter, &[1.0], 1.0).unwrap_err();
+    assert_eq!(err.kind, Kind::Gradient);
+    assert_eq!(err.layer, 0);
+}
+
+#[test]
+fn non_finite_inputs_are_located_before_evaluation() {
+    let gen = (|_, _| 1.0, |_| 0.0);
+    let net = Full::<3, 2, _>::new(LeakyRelu(0.1), gen).chain(SquareError { expected: [0.0; 2] });
+    assert!(guard::intermediate_checked(&net, &[1.0, 2.0, 3.0]).is_ok());
+    let Err(err) = guard::intermediate_checked(&net, &[1.0, 2.0, f32::INFINITY]) else {
+        panic!("The input should be rejected.");
+    };
+    assert_eq!(err.kind, Kind::Input);
+    assert_eq!(err.element, Some(2));
+    assert_eq!(err.value, f32::INFINITY);
+    assert_eq!(err.to_string(), "non-finite input inf at element 2");
+}
+
+#[test]
+fn images_are_checked_pixel_by_pixel() {
+    let net = Convolutional::<3, 2, 2, 1, _>::new(LeakyRelu(0.1), [2, 2], testing::seeded_gen(1));
+    let mut image = Image::filled(0.5);
+    assert!(guard::eval_checked(&net, &image).is_ok());
+    image.pixels[1][2] = [f32::NAN];
+    let err = guard::eval_checked(&net, &image).unwrap_err();
+    assert_eq!(err.element, Some(5));
+}
+
+#[test]
+fn non_finite_errors_and_input_gradients_are_located() {
+    let mut net = Full::<1, 1, _>::new(LeakyRelu(1.0), (|_, _| 1e21, |_| 0.0))
+        .chain(SquareError { expected: [1e18] });
+    let inputs = [1e-30];
+    // The gradient over the input overflows, while the updated parameters are still finite.
+    let inter = net.intermediate(&inputs);
+    let err = guard::train_error_checked(&mut net, &inputs, &inter, 0.1).unwrap_err();
+    assert_eq!(
+        (err.kind, err.layer, err.element),
+        (Kind::InputGradient, 0, Some(0))
+    );
+    assert_eq!(
+        err.to_string(),
+        "non-finite input gradient -inf at element 0"
+    );
+
+    net.second.expected = [f32::MAX];
+    let inter = net.intermediate(&[1.0]);
+    let err = guard::train_error_checked(&mut net, &[1.0], &inter, 0.1).unwrap_err();
+    assert_eq!(
+        (err.kind, err.layer, err.element),
+        (Kind::Activation, 1, Some(0))
+    );
+}
+
+#[test]
+fn checked_training_stops_at_the_first_non_finite_value() {
+    let trainer = Trainer {
+        epochs: 10,
+        learning_rate: LearningRate(0.5),
+        ..Default::default()
+    };
+    let fit = trainer
+        .fit_checked(&mut testing::xor_net(1), &testing::XOR)
+        .unwrap();
+    assert_eq!(fit.errors.len(), 10);
+
+    let mut dataset = testing::XOR.to_vec();
+    dataset[2].0[1] = f32::NAN;
+    let err = trainer
+        .fit_checked(&mut testing::xor_net(1), &dataset)
+        .unwrap_err();
+    assert_eq!((err.kind, err.element), (Kind::Input, Some(1)));
+
+    // A learning rate this large makes the parameters of unbounded layers explode.
+    let gen = (|_, _| 1.0, |_| 0.0);
+    let mut net = Full::<2, 2, _>::new(LeakyRelu(0.1), gen)
+        .chain(Full::<2, 1, _>::new(LeakyRelu(0.1), gen))
+        .chain(SquareError { expected: [0.0] });
+    let diverging = Trainer {
+        learning_rate: LearningRate(1e30),
+        ..trainer
+    };
+    let err = diverging.fit_checked(&mut net, &testing::XOR).unwrap_err();
+    assert!(
+        matches!(err.kind, Kind::Gradient | Kind::Parameter),
+        "{err}"
+    );
+}
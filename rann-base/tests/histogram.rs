@@ -0,0 +1,46 @@
+use rann_base::{activ::Logistic, testing, Full};
+use rann_traits::{
+    histogram::{self, Histogram, Summary},
+    Network,
+};
+
+#[test]
+fn histograms_count_every_finite_value() {
+    let values = [-1.0, -0.5, 0.0, 0.25, 0.5, 1.0, f32::NAN, f32::INFINITY];
+    let histogram = Histogram::new(&values, 4);
+    assert_eq!((histogram.min, histogram.max), (-1.0, 1.0));
+    assert_eq!(histogram.counts, [1, 1, 2, 2]);
+    assert_eq!(histogram.non_finite, 2);
+    assert_eq!(histogram.edges(), [-1.0, -0.5, 0.0, 0.5, 1.0]);
+
+    let constant = Histogram::new(&[3.0; 5], 3);
+    assert_eq!(constant.counts, [5, 0, 0]);
+}
+
+#[test]
+fn summaries_interpolate_quantiles() {
+    let values: Vec<f32> = (0..=100).map(|i| i as f32).collect();
+    let summary = Summary::new(&values);
+    assert_eq!(summary.p5, 5.0);
+    assert_eq!(summary.q1, 25.0);
+    assert_eq!(summary.median, 50.0);
+    assert_eq!(summary.p95, 95.0);
+    assert_eq!(summary.mean, 50.0);
+    assert_eq!(histogram::quantile(&[1.0, 2.0], 0.25), 1.25);
+    assert!(Summary::new(&[]).median.is_nan());
+}
+
+#[test]
+fn histograms_of_networks() {
+    let net = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1))
+        .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)));
+    assert_eq!(histogram::of_params(&net, 10).total(), 9 + 4);
+
+    let layers = histogram::of_layers(&net, &net.intermediate(&[0.0, 1.0]), 10);
+    let totals: Vec<Vec<usize>> = layers
+        .iter()
+        .map(|groups| groups.iter().map(Histogram::total).collect())
+        .collect();
+    // The weights and biases of each layer.
+    assert_eq!(totals, [vec![6, 3], vec![3, 1]]);
+}
@@ -0,0 +1,47 @@
+use std::cell::Cell;
+
+use rann_base::{activ::Logistic, error::SquareError, Full};
+use rann_traits::{compose::Recorder, inspect::Inspect, Network};
+
+#[test]
+fn hooks_see_inputs_and_outputs_of_their_layer() {
+    let gen = (|i, j| (i + 2 * j) as f32 * 0.1, |_| 0.1);
+    let first = Full::<2, 3, _>::new(Logistic, gen);
+    let second = Full::<3, 1, _>::new(Logistic, gen);
+    let inputs = Recorder::new();
+    let outputs = Recorder::new();
+    let net = first
+        .clone()
+        .chain(second.clone().hook(inputs.inputs()).hook(outputs.outputs()));
+
+    let out = net.eval(&[0.5, -0.5]);
+    assert_eq!(inputs.take(), [first.eval(&[0.5, -0.5])]);
+    assert_eq!(outputs.take(), [out]);
+    assert!(inputs.is_empty());
+}
+
+#[test]
+fn hooks_are_called_once_per_evaluation() {
+    let gen = (|_, _| 0.5, |_| 0.0);
+    let calls = Cell::new(0);
+    let mut net = Full::<2, 2, _>::new(Logistic, gen)
+        .hook(|_, _| calls.set(calls.get() + 1))
+        .chain(SquareError { expected: [1.0; 2] });
+
+    for _ in 0..3 {
+        let inter = net.intermediate(&[1.0, 0.0]);
+        net.train_deriv(&[1.0, 0.0], &inter, &[1.0], 0.1);
+    }
+    // Training does not evaluate the network again.
+    assert_eq!(calls.get(), 3);
+}
+
+#[test]
+fn hooked_networks_can_be_inspected() {
+    let gen = (|_, _| 0.5, |_| 0.0);
+    let recorder = Recorder::new();
+    let net = Full::<2, 2, _>::new(Logistic, gen).hook(recorder.outputs());
+    let inter = net.intermediate(&[1.0, 0.0]);
+    assert_eq!(net.layer_stats(&inter).len(), 1);
+    assert_eq!(recorder.len(), 1);
+}
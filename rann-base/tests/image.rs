@@ -0,0 +1,69 @@
+use rann_base::{
+    activ::Logistic,
+    conv::Convolutional,
+    error::SquareError,
+    image::{Flatten, GlobalAvgPool, Image},
+    testing, Full,
+};
+use rann_traits::{deriv::Elements, params::Parameterized, Gradient, Network};
+
+#[test]
+fn pixels_are_stored_row_by_row() {
+    let image = Image::<2, 3, 2>::from_fn(|x, y, c| (100 * y + 10 * x + c) as f32);
+    assert_eq!(image.pixel(1, 2), &[210.0, 211.0]);
+    assert_eq!(image.as_slice().len(), Image::<2, 3, 2>::LEN);
+    assert_eq!(image.as_slice()[..6], [0.0, 1.0, 10.0, 11.0, 100.0, 101.0]);
+}
+
+#[test]
+fn elementwise_operations() {
+    let mut image = Image::from([[1.0, 2.0], [3.0, 4.0]]);
+    assert_eq!(
+        image.map_elements(|x| x * x),
+        Image::from([[1.0, 4.0], [9.0, 16.0]])
+    );
+    let ones = image.ones_like();
+    assert_eq!(ones, Image::filled(1.0));
+    image.as_mut_slice()[3] = 0.0;
+    assert_eq!(
+        image.zip_elements(&ones, |a, b| a - b),
+        Image::from([[0.0, 1.0], [2.0, -1.0]])
+    );
+    assert_eq!(Image::<4, 4, 3>::default(), Image::filled(0.0));
+}
+
+#[test]
+fn flatten_keeps_the_order_of_the_values() {
+    let mut flatten = Flatten::<2, 2, 2, 8>::new();
+    let image = Image::from_fn(|x, y, c| (4 * y + 2 * x + c) as f32);
+    let flat = flatten.eval(&image);
+    assert_eq!(flat, [0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+    let inter = flatten.intermediate(&image);
+    assert_eq!(flatten.train_deriv(&image, &inter, &flat, 1.0), image);
+}
+
+#[test]
+fn global_average_pooling_averages_every_channel() {
+    let mut pool = GlobalAvgPool::<3, 2, 2>::new();
+    let image = Image::from_fn(|x, y, c| if c == 0 { (x + y) as f32 } else { 1.0 });
+    // The first channel sums to 0 + 1 + 2 + 1 + 2 + 3.
+    assert_eq!(pool.eval(&image), [1.5, 1.0]);
+    let inter = pool.intermediate(&image);
+    let gradients = pool.train_deriv(&image, &inter, &[6.0, 12.0], 1.0);
+    assert_eq!(gradients, Image::from_fn(|_, _, c| [1.0, 2.0][c]));
+    assert_eq!(pool.num_params(), 0);
+}
+
+#[test]
+fn pooled_convolutions_classify_images() {
+    let mut net = Convolutional::<4, 4, 3, 3, _>::new(Logistic, [2, 2], testing::seeded_gen(1))
+        .chain(GlobalAvgPool::new())
+        .chain(Full::<1, 1, _>::new(Logistic, testing::seeded_gen(2)))
+        .chain(SquareError { expected: [0.0] });
+    // Diagonal lines, which a 2x2 kernel can tell apart from the others.
+    let dataset = [
+        (Image::from_fn(|x, y, _| (x == y) as u8 as f32), [1.0]),
+        (Image::from_fn(|x, y, _| (x + y == 3) as u8 as f32), [0.0]),
+    ];
+    testing::assert_network_converges(&mut net, &dataset, 2000, 1.0, 0.01);
+}
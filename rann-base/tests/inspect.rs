@@ -0,0 +1,130 @@
+use rann_base::{activ::Logistic, error::SquareError, Full, LayerNorm};
+use rann_traits::{
+    compose::zip,
+    inspect::{short_type_name, Inspect, MemoryFootprint},
+    Network,
+};
+
+const INPUT: [f32; 3] = [0.5, -1.0, 2.0];
+
+#[test]
+fn stats_per_layer() {
+    let gen = (|i, j| (i + j) as f32 * 0.1 - 0.2, |_| 0.1);
+    let mut net = Full::<3, 4, _>::new(Logistic, gen)
+        .chain(LayerNorm::<4>::new())
+        .chain(SquareError {
+            expected: [1.0, 0.0, 0.5, 0.25],
+        });
+
+    let inter = net.intermediate(&INPUT);
+    let stats = net.layer_stats(&inter);
+    // One entry for each layer, including the error function.
+    assert_eq!(stats.len(), 3);
+    for s in &stats {
+        assert_eq!(s.gradient_norm, 0.0, "Nothing has been trained yet.");
+        assert!(s.min_activation <= s.max_activation);
+    }
+    // Logistic activations are within (0, 1).
+    assert!(stats[0].min_activation > 0.0 && stats[0].max_activation < 1.0);
+    // The scale of the normalization has a norm of 2.
+    assert_eq!(stats[1].weight_norm, 2.0);
+
+    net.train_deriv(&INPUT, &inter, &[1.0], 0.1);
+    let stats = net.layer_stats(&net.intermediate(&INPUT));
+    assert!(stats[0].gradient_norm > 0.0);
+    assert!(stats[1].gradient_norm > 0.0);
+}
+
+#[test]
+fn gradient_norm_matches_update() {
+    const RATE: f32 = 0.01;
+    let mut norm = LayerNorm::<3>::new();
+    let before = norm.clone();
+    let inter = norm.intermediate(&INPUT);
+    norm.train_deriv(&INPUT, &inter, &[0.3, -0.2, 0.9], RATE);
+
+    // The update of the parameters is the gradient, scaled by the learning rate.
+    let update: f32 = before
+        .scale
+        .iter()
+        .zip(&norm.scale)
+        .chain(before.shift.iter().zip(&norm.shift))
+        .map(|(a, b)| (a - b) * (a - b))
+        .sum::<f32>()
+        .sqrt();
+    let stats = norm.layer_stats(&inter);
+    assert!((stats[0].gradient_norm * RATE - update).abs() < 1e-5);
+}
+
+#[test]
+fn dot_of_sequential_network() {
+    let gen = (|_, _| 0.1, |_| 0.0);
+    let net = Full::<3, 4, _>::new(Logistic, gen)
+        .chain(LayerNorm::<4>::new())
+        .chain(SquareError { expected: [0.0; 4] });
+    let dot = net.to_dot(&net.intermediate(&INPUT));
+
+    assert!(dot.starts_with("digraph network {"));
+    assert!(dot.contains(r#"layer1 [label="Full (Logistic)\n3 → 4\n16 params"];"#));
+    assert!(dot.contains(r#"layer2 [label="LayerNorm\n4 → 4\n8 params"];"#));
+    // The input, the three layers and the output are connected in order.
+    for i in 0..4 {
+        assert!(dot.contains(&format!("layer{i} -> layer{};", i + 1)));
+    }
+}
+
+#[test]
+fn dot_of_zipped_network() {
+    let gen = (|_, _| 0.1, |_| 0.0);
+    let net = Full::<3, 1, _>::new(Logistic, gen)
+        .zip(Full::<3, 2, _>::new(Logistic, gen), zip::Stacker::<1, 2, 3>);
+    let dot = net.to_dot(&net.intermediate(&(INPUT, INPUT)));
+
+    // Both branches take the input, and both lead to the output.
+    for edge in [
+        "layer0 -> layer1",
+        "layer0 -> layer2",
+        "layer1 -> layer3",
+        "layer2 -> layer3",
+    ] {
+        assert!(dot.contains(edge), "{edge} missing from {dot}");
+    }
+}
+
+#[test]
+fn short_type_names() {
+    assert_eq!(
+        short_type_name::<Full<2, 3, Logistic>>(),
+        "Full<2, 3, Logistic>"
+    );
+    assert_eq!(short_type_name::<Option<[f32; 2]>>(), "Option<[f32; 2]>");
+}
+
+#[test]
+fn memory_footprint_per_layer() {
+    let gen = (|i, j| (i + j) as f32 * 0.1, |_| 0.1);
+    let net = Full::<3, 4, _>::new(Logistic, gen)
+        .chain(LayerNorm::<4>::new())
+        .chain(SquareError {
+            expected: [1.0, 0.0, 0.5, 0.25],
+        });
+    let inter = net.intermediate(&INPUT);
+    let footprints = net.memory_footprint(&inter, 2);
+    assert_eq!(
+        footprints[0],
+        MemoryFootprint {
+            params: (3 * 4 + 4) * 4,
+            optimizer: (3 * 4 + 4) * 2 * 4,
+            intermediate: 4 * 4,
+        }
+    );
+    // The scale and shift of the normalization.
+    assert_eq!(footprints[1].params, 2 * 4 * 4);
+    assert_eq!(footprints[2].params, 0);
+    let total: MemoryFootprint = footprints.iter().copied().sum();
+    assert_eq!(
+        total.total(),
+        footprints.iter().map(MemoryFootprint::total).sum::<usize>()
+    );
+    assert_eq!(net.memory_footprint(&inter, 0)[0].optimizer, 0);
+}
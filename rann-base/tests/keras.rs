@@ -0,0 +1,463 @@
+#![cfg(feature = "keras")]
+
+use std::io;
+
+use rann_base::{
+    activ::{LeakyRelu, Logistic, Tanh},
+    keras::KerasModel,
+    testing, Full,
+};
+use rann_traits::{Network, Scalar};
+
+// A minimal writer of HDF5 files in the format h5py uses for Keras models: a version 0
+// superblock, version 1 object headers, groups stored in symbol tables, contiguous datasets of
+// floats, and attributes of strings.
+enum Attribute {
+    Fixed(Vec<String>),
+    Variable(String),
+    // An empty array, as Keras writes for the weight names of layers without weights.
+    Empty,
+}
+
+enum Node {
+    Group(Vec<(String, Attribute)>, Vec<(String, Node)>),
+    Dataset(Vec<usize>, Vec<Scalar>),
+}
+
+fn padded(bytes: &[u8]) -> Vec<u8> {
+    let mut bytes = bytes.to_vec();
+    bytes.resize(bytes.len().next_multiple_of(8), 0);
+    bytes
+}
+
+fn dataspace(dims: &[usize]) -> Vec<u8> {
+    let mut dataspace = vec![1, dims.len() as u8, 0, 0, 0, 0, 0, 0];
+    for &dim in dims {
+        dataspace.extend((dim as u64).to_le_bytes());
+    }
+    dataspace
+}
+
+fn string_type(size: u32) -> Vec<u8> {
+    let mut datatype = vec![0x13, 0, 0, 0];
+    datatype.extend(size.to_le_bytes());
+    datatype
+}
+
+// Encodes version 1 header messages.
+fn messages(messages: &[(u16, Vec<u8>)]) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    for (kind, data) in messages {
+        let data = padded(data);
+        encoded.extend(kind.to_le_bytes());
+        encoded.extend((data.len() as u16).to_le_bytes());
+        encoded.extend([0; 4]);
+        encoded.extend(data);
+    }
+    encoded
+}
+
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn position(&mut self) -> u64 {
+        self.bytes.resize(self.bytes.len().next_multiple_of(8), 0);
+        self.bytes.len() as u64
+    }
+
+    fn write(&mut self, parts: &[&[u8]]) -> u64 {
+        let address = self.position();
+        for part in parts {
+            self.bytes.extend(*part);
+        }
+        address
+    }
+
+    // Writes an object header, with the `continued` messages in a continuation block.
+    fn header(&mut self, mut own: Vec<(u16, Vec<u8>)>, continued: &[(u16, Vec<u8>)]) -> u64 {
+        let count = own.len() + continued.len() + 1;
+        let continued = messages(continued);
+        let block = self.write(&[&continued]);
+        own.push((
+            0x10,
+            [block.to_le_bytes(), (continued.len() as u64).to_le_bytes()].concat(),
+        ));
+        let own = messages(&own);
+        self.write(&[
+            &[1, 0],
+            &(count as u16).to_le_bytes(),
+            &1u32.to_le_bytes(),
+            &(own.len() as u32).to_le_bytes(),
+            &[0; 4],
+            &own,
+        ])
+    }
+
+    fn attribute(&mut self, name: &str, attribute: &Attribute) -> Vec<u8> {
+        let (datatype, dims, data) = match attribute {
+            Attribute::Fixed(strings) => {
+                let size = strings.iter().map(String::len).max().unwrap_or(0).max(1);
+                let mut data = Vec::new();
+                for string in strings {
+                    data.extend(string.as_bytes());
+                    data.resize(data.len() + size - string.len(), 0);
+                }
+                (string_type(size as u32), vec![strings.len()], data)
+            }
+            Attribute::Variable(string) => {
+                let len = string.len() as u64;
+                let collection_len = 16 + 16 + len.next_multiple_of(8) + 16;
+                let heap = self.write(&[
+                    b"GCOL\x01\0\0\0",
+                    &collection_len.to_le_bytes(),
+                    &[1, 0, 1, 0, 0, 0, 0, 0],
+                    &len.to_le_bytes(),
+                    &padded(string.as_bytes()),
+                    &[0; 8],
+                    &0u64.to_le_bytes(),
+                ]);
+                let mut datatype = vec![0x19, 0x01, 0, 0];
+                datatype.extend(16u32.to_le_bytes());
+                datatype.extend(string_type(1));
+                let data = [
+                    &(len as u32).to_le_bytes()[..],
+                    &heap.to_le_bytes(),
+                    &1u32.to_le_bytes(),
+                ]
+                .concat();
+                (datatype, vec![], data)
+            }
+            Attribute::Empty => {
+                let mut datatype = vec![0x11, 0x20, 0x3F, 0];
+                datatype.extend(8u32.to_le_bytes());
+                (datatype, vec![0], vec![])
+            }
+        };
+        let dataspace = dataspace(&dims);
+        let mut message = vec![1, 0];
+        message.extend((name.len() as u16 + 1).to_le_bytes());
+        message.extend((datatype.len() as u16).to_le_bytes());
+        message.extend((dataspace.len() as u16).to_le_bytes());
+        message.extend(padded(&[name.as_bytes(), &[0]].concat()));
+        message.extend(padded(&datatype));
+        message.extend(padded(&dataspace));
+        message.extend(data);
+        message
+    }
+
+    fn node(&mut self, node: &Node) -> u64 {
+        match node {
+            Node::Dataset(shape, data) => {
+                let bytes: Vec<u8> = data.iter().flat_map(|v| v.to_le_bytes()).collect();
+                let address = self.write(&[&bytes]);
+                let mut datatype = vec![0x11, 0x20, 0x1F, 0];
+                datatype.extend(4u32.to_le_bytes());
+                datatype.extend([0, 0, 32, 0, 23, 8, 0, 23, 127, 0, 0, 0]);
+                let layout = [
+                    &[3, 1][..],
+                    &address.to_le_bytes(),
+                    &(bytes.len() as u64).to_le_bytes(),
+                ]
+                .concat();
+                let messages = vec![(0x01, dataspace(shape)), (0x03, datatype), (0x08, layout)];
+                self.header(messages, &[])
+            }
+            Node::Group(attributes, children) => {
+                let children: Vec<(&str, u64)> = children
+                    .iter()
+                    .map(|(name, child)| (name.as_str(), self.node(child)))
+                    .collect();
+                // The names in the local heap follow an empty name, like h5py writes them.
+                let mut names = vec![0; 8];
+                let mut offsets = Vec::new();
+                for (name, _) in &children {
+                    offsets.push(names.len() as u64);
+                    names = padded(&[&names, name.as_bytes(), &[0]].concat());
+                }
+                let data = self.write(&[&names]);
+                let heap = self.write(&[
+                    b"HEAP\0\0\0\0",
+                    &(names.len() as u64).to_le_bytes(),
+                    &u64::MAX.to_le_bytes(),
+                    &data.to_le_bytes(),
+                ]);
+                let mut symbols = b"SNOD\x01\0".to_vec();
+                symbols.extend((children.len() as u16).to_le_bytes());
+                for ((_, address), offset) in children.iter().zip(&offsets) {
+                    symbols.extend(offset.to_le_bytes());
+                    symbols.extend(address.to_le_bytes());
+                    symbols.extend([0; 24]);
+                }
+                let symbols = self.write(&[&symbols]);
+                let tree = self.write(&[
+                    b"TREE\0\0\x01\0",
+                    &u64::MAX.to_le_bytes(),
+                    &u64::MAX.to_le_bytes(),
+                    &0u64.to_le_bytes(),
+                    &symbols.to_le_bytes(),
+                    &offsets.last().copied().unwrap_or(0).to_le_bytes(),
+                ]);
+                let attributes: Vec<_> = attributes
+                    .iter()
+                    .map(|(name, attribute)| (0x0C, self.attribute(name, attribute)))
+                    .collect();
+                let table = [tree.to_le_bytes(), heap.to_le_bytes()].concat();
+                self.header(vec![(0x11, table)], &attributes)
+            }
+        }
+    }
+}
+
+fn hdf5(root: &Node) -> Vec<u8> {
+    let mut writer = Writer { bytes: vec![0; 96] };
+    let root = writer.node(root);
+    let end = writer.bytes.len() as u64;
+    let superblock = [
+        &b"\x89HDF\r\n\x1a\n"[..],
+        // The versions, and the sizes of offsets and lengths.
+        &[0, 0, 0, 0, 0, 8, 8, 0],
+        &[4, 0, 16, 0, 0, 0, 0, 0],
+        &0u64.to_le_bytes(),
+        &u64::MAX.to_le_bytes(),
+        &end.to_le_bytes(),
+        &u64::MAX.to_le_bytes(),
+        // The symbol table entry of the root group.
+        &0u64.to_le_bytes(),
+        &root.to_le_bytes(),
+        &[0; 24],
+    ]
+    .concat();
+    writer.bytes[..96].copy_from_slice(&superblock);
+    writer.bytes
+}
+
+fn strings(strings: &[&str]) -> Attribute {
+    Attribute::Fixed(strings.iter().map(|s| s.to_string()).collect())
+}
+
+// A layer with its weights, named like `dense/kernel:0`.
+struct Layer {
+    name: &'static str,
+    weights: Vec<(&'static str, Vec<usize>, Vec<Scalar>)>,
+}
+
+fn layer_group(layer: &Layer) -> Node {
+    let names: Vec<_> = layer.weights.iter().map(|(name, ..)| *name).collect();
+    let weight_names = match names.is_empty() {
+        true => Attribute::Empty,
+        false => strings(&names),
+    };
+    let mut children = Vec::new();
+    for (name, shape, data) in &layer.weights {
+        let (group, dataset) = name.split_once('/').unwrap();
+        let dataset = (
+            dataset.to_string(),
+            Node::Dataset(shape.clone(), data.clone()),
+        );
+        match children.iter_mut().find(|(name, _)| name == group) {
+            Some((_, Node::Group(_, datasets))) => datasets.push(dataset),
+            _ => children.push((group.to_string(), Node::Group(vec![], vec![dataset]))),
+        }
+    }
+    Node::Group(vec![("weight_names".into(), weight_names)], children)
+}
+
+// A file written by `model.save()` with `config`, or by `model.save_weights()` without.
+fn keras_file(config: Option<Attribute>, layers: &[Layer]) -> Vec<u8> {
+    let names: Vec<_> = layers.iter().map(|layer| layer.name).collect();
+    let mut attributes = vec![("layer_names".into(), strings(&names))];
+    let groups = layers
+        .iter()
+        .map(|layer| (layer.name.to_string(), layer_group(layer)))
+        .collect();
+    attributes.push(("backend".into(), strings(&["tensorflow"])));
+    let Some(config) = config else {
+        return hdf5(&Node::Group(attributes, groups));
+    };
+    let weights = Node::Group(attributes, groups);
+    hdf5(&Node::Group(
+        vec![
+            ("keras_version".into(), strings(&["2.15.0"])),
+            ("model_config".into(), config),
+        ],
+        vec![("model_weights".into(), weights)],
+    ))
+}
+
+const KERNEL: [Scalar; 6] = [0.5, -1.0, 2.0, 0.25, 1.5, -0.75];
+const BIAS: [Scalar; 3] = [0.1, -0.2, 0.3];
+const KERNEL_1: [Scalar; 3] = [1.0, -2.0, 0.5];
+const BIAS_1: [Scalar; 1] = [-0.5];
+
+fn layers() -> Vec<Layer> {
+    vec![
+        Layer {
+            name: "dense",
+            weights: vec![
+                ("dense/kernel:0", vec![2, 3], KERNEL.to_vec()),
+                ("dense/bias:0", vec![3], BIAS.to_vec()),
+            ],
+        },
+        Layer {
+            name: "dropout",
+            weights: vec![],
+        },
+        Layer {
+            name: "dense_1",
+            weights: vec![
+                ("dense_1/kernel:0", vec![3, 1], KERNEL_1.to_vec()),
+                ("dense_1/bias:0", vec![1], BIAS_1.to_vec()),
+            ],
+        },
+    ]
+}
+
+fn config(activation: &str, extra: &str) -> String {
+    format!(
+        r#"{{"class_name": "Sequential", "config": {{"name": "sequential", "layers": [
+            {{"class_name": "InputLayer", "config": {{"batch_input_shape": [null, 2], "name": "input_1"}}}},
+            {{"class_name": "Dense", "config": {{"name": "dense", "units": 3, "activation": "{activation}", "use_bias": true, "note": "a\/b \"c\" é"}}}},
+            {{"class_name": "Dropout", "config": {{"name": "dropout", "rate": 0.5, "seed": null}}}},
+            {{"class_name": "Dense", "config": {{"name": "dense_1", "units": 1, "activation": "sigmoid", "l2": 1e-4}}}}{extra}
+        ]}}}}"#
+    )
+}
+
+fn expected(x: [Scalar; 2]) -> Scalar {
+    let hidden: Vec<Scalar> = (0..3)
+        .map(|o| (x[0] * KERNEL[o] + x[1] * KERNEL[3 + o] + BIAS[o]).tanh())
+        .collect();
+    let sum: Scalar = hidden.iter().zip(KERNEL_1).map(|(h, w)| h * w).sum();
+    1.0 / (1.0 + (-(sum + BIAS_1[0])).exp())
+}
+
+fn net() -> impl Network<In = [Scalar; 2], Out = [Scalar; 1]> + rann_base::keras::ImportDense {
+    Full::<2, 3, _>::new(Tanh, testing::seeded_gen(1))
+        .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)))
+}
+
+fn assert_imported(model: &KerasModel) {
+    let mut net = net();
+    model.import(&mut net).unwrap();
+    for x in [[0.0, 0.0], [1.0, -1.0], [0.5, 2.0]] {
+        assert!((net.eval(&x)[0] - expected(x)).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn imports_saved_models() {
+    let file = keras_file(Some(strings(&[&config("tanh", "")])), &layers());
+    let model = KerasModel::from_bytes(&file).unwrap();
+    assert_eq!(model.layers.len(), 2);
+    assert_eq!(model.layers[0].name, "dense");
+    assert_eq!(model.layers[0].activation.as_deref(), Some("tanh"));
+    assert_eq!(model.layers[1].activation.as_deref(), Some("sigmoid"));
+    assert_eq!(model.layers[0].kernel.shape, [2, 3]);
+    assert_eq!(model.layers[0].kernel.data, KERNEL);
+    assert_eq!(model.layers[1].bias.as_ref().unwrap().data, BIAS_1);
+    assert_imported(&model);
+}
+
+#[test]
+fn imports_saved_weights_without_activations() {
+    let model = KerasModel::from_bytes(&keras_file(None, &layers())).unwrap();
+    assert_eq!(model.layers.len(), 2);
+    assert_eq!(model.layers[0].activation, None);
+    assert_imported(&model);
+
+    // Without a configuration, the activations are not checked.
+    let mut net = Full::<2, 3, _>::new(LeakyRelu(0.0), testing::seeded_gen(1))
+        .chain(Full::<3, 1, _>::new(LeakyRelu(0.1), testing::seeded_gen(2)));
+    model.import(&mut net).unwrap();
+}
+
+#[test]
+fn reads_variable_length_configurations() {
+    let config = Attribute::Variable(config("tanh", ""));
+    let model = KerasModel::from_bytes(&keras_file(Some(config), &layers())).unwrap();
+    assert_eq!(model.layers[0].activation.as_deref(), Some("tanh"));
+    assert_imported(&model);
+}
+
+#[test]
+fn rejects_mismatched_networks() {
+    let file = keras_file(Some(strings(&[&config("tanh", "")])), &layers());
+    let model = KerasModel::from_bytes(&file).unwrap();
+    let message = |result: io::Result<()>| {
+        let err = result.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        err.to_string()
+    };
+
+    let mut net = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1))
+        .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)));
+    let err = message(model.import(&mut net));
+    assert!(err.contains("tanh") && err.contains("Logistic"), "{err}");
+
+    let mut net = Full::<2, 4, _>::new(Tanh, testing::seeded_gen(1))
+        .chain(Full::<4, 1, _>::new(Logistic, testing::seeded_gen(2)));
+    assert!(message(model.import(&mut net)).contains("[2, 4]"));
+
+    let mut net =
+        Full::<2, 3, _, _>::without_bias(Tanh, testing::seeded_gen(1))
+            .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)));
+    assert!(message(model.import(&mut net)).contains("biases"));
+
+    let mut net = Full::<2, 3, _>::new(Tanh, testing::seeded_gen(1));
+    assert!(message(model.import(&mut net)).contains("dense_1"));
+
+    let net = net.chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)));
+    let mut net = net.chain(Full::<1, 1, _>::new(Logistic, testing::seeded_gen(3)));
+    assert!(message(model.import(&mut net)).contains("fewer"));
+}
+
+#[test]
+fn rejects_unsupported_layers() {
+    let activation = r#", {"class_name": "Activation", "config": {"name": "act"}}"#;
+    let mut layers = layers();
+    layers.push(Layer {
+        name: "act",
+        weights: vec![],
+    });
+    let file = keras_file(Some(strings(&[&config("tanh", activation)])), &layers);
+    let err = KerasModel::from_bytes(&file).unwrap_err();
+    assert!(err.to_string().contains("Activation"), "{err}");
+
+    // Custom activations are objects instead of names.
+    let config = config("tanh", "").replace(r#""tanh""#, r#"{"class_name": "Custom"}"#);
+    let file = keras_file(Some(strings(&[&config])), &self::layers());
+    let err = KerasModel::from_bytes(&file).unwrap_err();
+    assert!(err.to_string().contains("custom activation"), "{err}");
+
+    let mut layers = self::layers();
+    layers[0]
+        .weights
+        .push(("dense/moving_mean:0", vec![3], vec![0.0; 3]));
+    assert!(KerasModel::from_bytes(&keras_file(None, &layers)).is_err());
+}
+
+#[test]
+fn rejects_other_files() {
+    let file = keras_file(None, &layers());
+    assert!(KerasModel::from_bytes(b"not an HDF5 file").is_err());
+    assert!(KerasModel::from_bytes(&[]).is_err());
+    // Truncated files are rejected without panicking.
+    for len in (0..file.len()).step_by(7) {
+        assert!(KerasModel::from_bytes(&file[..len]).is_err());
+    }
+    // An HDF5 file that was not written by Keras.
+    let file = hdf5(&Node::Group(vec![], vec![]));
+    let err = KerasModel::from_bytes(&file).unwrap_err();
+    assert!(err.to_string().contains("layer_names"), "{err}");
+}
+
+#[test]
+fn loads_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("model.h5");
+    let config = Attribute::Variable(config("tanh", ""));
+    std::fs::write(&path, keras_file(Some(config), &layers())).unwrap();
+    assert_imported(&KerasModel::load(&path).unwrap());
+}
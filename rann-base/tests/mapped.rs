@@ -0,0 +1,200 @@
+use std::io;
+
+use rann_base::{
+    activ::{LeakyRelu, Logistic},
+    error::SquareError,
+    mapped::{self, Aligned, Mapped, ALIGNMENT},
+    model::{self, Metadata},
+    npy::{Array, Npz},
+    testing, Full, FullView,
+};
+use rann_traits::{params::Parameterized, Network};
+
+fn file(npz: &Npz) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    mapped::write_to(npz, &mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn arrays_are_aligned_and_borrowed_in_place() {
+    let mut npz = Npz::new();
+    npz.insert("a", Array::new(vec![3], vec![1.0, 2.0, 3.0]));
+    npz.insert("empty", Array::new(vec![0, 2], vec![]));
+    npz.insert("b", Array::new(vec![2, 1], vec![4.0, 5.0]));
+    let file = Mapped::new(file(&npz)).unwrap();
+    assert_eq!(file.names().collect::<Vec<_>>(), ["a", "empty", "b"]);
+    for (name, array) in &npz.arrays {
+        let (shape, values) = file.get(name).unwrap();
+        assert_eq!(shape, array.shape);
+        assert_eq!(values, array.data);
+        let offset = values.as_ptr() as usize - file.data().as_ptr() as usize;
+        assert_eq!(offset % ALIGNMENT, 0);
+    }
+    assert!(file.get("c").is_none());
+    assert_eq!(
+        file.get_shaped("b", &[2]).unwrap_err().kind(),
+        io::ErrorKind::InvalidData
+    );
+}
+
+#[test]
+fn invalid_files_are_rejected() {
+    let mut npz = Npz::new();
+    npz.insert("a", Array::new(vec![4], vec![0.0; 4]));
+    let bytes = file(&npz);
+    assert!(Mapped::new(&bytes[..bytes.len() - 1]).is_err());
+    assert!(Mapped::new(&bytes[..20]).is_err());
+    assert!(Mapped::new(&b"RANNCKPT\x01\0\0\0"[..]).is_err());
+
+    // An array with a dimension that overflows the length of its values.
+    let mut overflowing = bytes.clone();
+    let dim = 8 + 4 + 8 + 8 + 1 + 8;
+    overflowing[dim..dim + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+    assert!(Mapped::new(&overflowing[..]).is_err());
+}
+
+#[test]
+fn model_files_are_not_mapped() {
+    let net = Full::<2, 1, _>::new(Logistic, testing::seeded_gen(1));
+    let mut bytes = Vec::new();
+    model::write_to(&net, &Metadata::describe(&net, "xor"), &mut bytes).unwrap();
+    let err = Mapped::new(&bytes[..]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert!(err.to_string().starts_with("model files can not be mapped"));
+
+    // A model is mapped by writing the arrays of its network.
+    let mut loaded = Full::<2, 1, _>::new(Logistic, (|_, _| 0.0, |_| 0.0));
+    model::read_from(&mut loaded, &bytes[..]).unwrap();
+    let mut npz = Npz::new();
+    loaded.write_npz(&mut npz, "");
+    let file = Mapped::new(file(&npz)).unwrap();
+    let view = file.full::<2, 1, _>("", Logistic).unwrap();
+    assert_eq!(view.eval(&[1.0, 0.0]), net.eval(&[1.0, 0.0]));
+}
+
+#[test]
+fn views_match_layers() {
+    let net = Full::<3, 4, _>::new(Logistic, testing::seeded_gen(1)).chain(
+        Full::<4, 2, _, _>::without_bias(LeakyRelu(0.1), testing::seeded_gen(2)),
+    );
+    let mut npz = Npz::new();
+    net.first.write_npz(&mut npz, "0.");
+    net.second.write_npz(&mut npz, "1.");
+    let file = Mapped::new(file(&npz)).unwrap();
+    let view = file
+        .full::<3, 4, _>("0.", Logistic)
+        .unwrap()
+        .chain(file.full::<4, 2, _>("1.", LeakyRelu(0.1)).unwrap());
+    assert!(view.second.biases().is_empty());
+    for input in [[0.0, 0.0, 0.0], [1.0, -2.0, 0.5], [0.3, 0.2, -0.1]] {
+        let expected = net.eval(&input);
+        for (a, b) in view.eval(&input).iter().zip(expected) {
+            assert!((a - b).abs() < 1e-6);
+        }
+    }
+
+    // Mismatched layers are rejected.
+    assert!(file.full::<4, 3, _>("0.", Logistic).is_err());
+    assert!(file.full::<3, 4, _>("2.", Logistic).is_err());
+
+    // Training backpropagates through the frozen views, without changing them.
+    let mut net = view.chain(SquareError {
+        expected: [1.0, 0.0],
+    });
+    let input = [1.0, -2.0, 0.5];
+    let inter = net.intermediate(&input);
+    let grads = net.train_deriv(&input, &inter, &[1.0], 0.5);
+    assert!(grads.iter().any(|grad| *grad != 0.0));
+    assert_eq!(net.first.first.weights(), npz.get("0.weight").unwrap().data);
+}
+
+#[test]
+fn input_gradients_match_layers() {
+    let mut layer = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(3));
+    let mut npz = Npz::new();
+    layer.write_npz(&mut npz, "");
+    let weight = npz.get("weight").unwrap().data.clone();
+    let bias = npz.get("bias").unwrap().data.clone();
+    let mut view = FullView::<2, 3, _>::new(Logistic, &weight, &bias);
+    let input = [0.5, -1.0];
+    let grads = [1.0, -0.5, 0.25];
+    let expected = layer.train_deriv(&input, &layer.intermediate(&input), &grads, 0.0);
+    let found = view.train_deriv(&input, &view.intermediate(&input), &grads, 0.1);
+    for (a, b) in found.iter().zip(expected) {
+        assert!((a - b).abs() < 1e-6);
+    }
+    assert_eq!(layer.params().len(), weight.len() + bias.len());
+}
+
+#[test]
+fn files_are_saved() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("model.rannmap");
+    let mut npz = Npz::new();
+    Full::<2, 1, _>::new(Logistic, testing::seeded_gen(1)).write_npz(&mut npz, "");
+    mapped::save(&npz, &path).unwrap();
+    let file = Mapped::new(std::fs::read(&path).unwrap()).unwrap();
+    assert_eq!(
+        file.get("weight").unwrap().1,
+        npz.get("weight").unwrap().data
+    );
+}
+
+// Solves XOR with two layers with a ReLU activation.
+static XOR: &Aligned<[u8]> = &Aligned(*include_bytes!("data/xor.rannmap"));
+
+fn xor_npz() -> Npz {
+    let mut npz = Npz::new();
+    npz.insert("0.weight", Array::new(vec![2, 2], vec![1.0; 4]));
+    npz.insert("0.bias", Array::new(vec![2], vec![0.0, -1.0]));
+    npz.insert("1.weight", Array::new(vec![1, 2], vec![1.0, -2.0]));
+    npz.insert("1.bias", Array::new(vec![1], vec![0.0]));
+    npz
+}
+
+#[test]
+fn embedded_files_are_borrowed_in_place() {
+    assert_eq!(XOR.0, file(&xor_npz()));
+    let file = Mapped::new(&XOR.0).unwrap();
+    let (_, weights) = file.get("0.weight").unwrap();
+    assert!(XOR.0.as_ptr_range().contains(&weights.as_ptr().cast()));
+    let net = file
+        .full::<2, 2, _>("0.", LeakyRelu(0.0))
+        .unwrap()
+        .chain(file.full::<2, 1, _>("1.", LeakyRelu(0.0)).unwrap());
+    for (input, output) in [
+        ([0.0, 0.0], 0.0),
+        ([0.0, 1.0], 1.0),
+        ([1.0, 0.0], 1.0),
+        ([1.0, 1.0], 0.0),
+    ] {
+        assert_eq!(net.eval(&input), [output]);
+    }
+}
+
+// A constant layer, that computes the sum and the difference of its inputs.
+const SUM_DIFF: FullView<'static, 2, 2, LeakyRelu> =
+    FullView::new(LeakyRelu(1.0), &[1.0, 1.0, 1.0, -1.0], &[]);
+
+#[test]
+fn views_are_constant() {
+    assert_eq!(SUM_DIFF.eval(&[3.0, 1.0]), [4.0, 2.0]);
+    // Every column holds the incoming weights of an output.
+    assert_eq!(SUM_DIFF.weight_matrix().column(1).as_slice(), [1.0, -1.0]);
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn files_are_mapped() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("model.rannmap");
+    let layer = Full::<2, 1, _>::new(Logistic, testing::seeded_gen(1));
+    let mut npz = Npz::new();
+    layer.write_npz(&mut npz, "");
+    mapped::save(&npz, &path).unwrap();
+    // SAFETY: The file is not modified while it is mapped.
+    let file = unsafe { Mapped::open(&path) }.unwrap();
+    let view = file.full::<2, 1, _>("", Logistic).unwrap();
+    assert_eq!(view.eval(&[1.0, 0.0]), layer.eval(&[1.0, 0.0]));
+}
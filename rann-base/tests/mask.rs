@@ -0,0 +1,127 @@
+use rann_base::{
+    activ::{Logistic, Tanh},
+    error::{BatchLoss, ReduceMean, ReduceSum, SquareError},
+    recurrent::{Gru, Recurrent},
+    testing,
+    train::Trainer,
+    Full,
+};
+use rann_traits::{
+    compose::TimeDistributed,
+    mask::{Masked, Masking},
+    params::Parameterized,
+    Intermediate, LearningRate, Network, Scalar,
+};
+
+fn assert_close(a: &[Scalar], b: &[Scalar]) {
+    assert_eq!(a.len(), b.len());
+    for (a, b) in a.iter().zip(b) {
+        assert!((a - b).abs() < 1e-6, "{a} != {b}");
+    }
+}
+
+#[test]
+fn padded_sequences_match_short_ones() {
+    let mut padded = Recurrent::<_, 4>::new(Gru::<1, 3>::new(testing::seeded_gen(1)));
+    let mut short = Recurrent::<_, 2>::new(padded.cell.clone());
+    let sequence = Masked::padded(&[[1.0], [-0.5]], [3.0]);
+    let inter = padded.intermediate_masked(&sequence.steps, &sequence.mask);
+    let short_inter = short.intermediate(&[[1.0], [-0.5]]);
+    // The state is kept over the padding.
+    assert_eq!(inter.state(), short_inter.state());
+    assert_eq!(inter.output()[..2], short_inter.output()[..]);
+
+    // The gradients over the outputs of padded steps are ignored.
+    let gradients = [[0.5, -1.0, 0.2], [1.0, 0.0, -0.3], [9.0; 3], [-9.0; 3]];
+    let input_grads = padded.train_masked(&sequence.steps, &inter, &gradients, &sequence.mask, 0.1);
+    let short_grads = short.train_deriv(
+        &[[1.0], [-0.5]],
+        &short_inter,
+        &[gradients[0], gradients[1]],
+        0.1,
+    );
+    assert_eq!(input_grads[..2], short_grads[..]);
+    assert_eq!(input_grads[2..], [[0.0], [0.0]]);
+    assert_close(&padded.params(), &short.params());
+}
+
+#[test]
+fn padded_steps_are_not_trained_on() {
+    let mut net =
+        TimeDistributed::<_, 3>::new(Full::<2, 1, _>::new(Logistic, testing::seeded_gen(2)));
+    let mut layer = net.network.clone();
+    let inputs = [[1.0, 0.0], [0.5, 0.5], [0.0, 1.0]];
+    let gradients = [[1.0], [1.0], [-1.0]];
+    let mask = [true, false, true];
+    let inter = net.intermediate_masked(&inputs, &mask);
+    let input_grads = net.train_masked(&inputs, &inter, &gradients, &mask, 0.1);
+    assert_eq!(input_grads[1], [0.0, 0.0]);
+    for t in [0, 2] {
+        layer.train_deriv(&inputs[t], &inter.steps[t], &gradients[t], 0.1);
+    }
+    assert_eq!(net.params(), layer.params());
+}
+
+#[test]
+fn sequence_losses_leave_padding_out() {
+    let mut mean = BatchLoss::<_, _, 3>::new(SquareError { expected: [0.0] }, ReduceMean);
+    let inputs = [[1.0], [2.0], [4.0]];
+    let mask = [true, true, false];
+    let inter = mean.intermediate_masked(&inputs, &mask);
+    assert_eq!(inter.output, [2.5]);
+    assert_eq!(
+        mean.train_masked(&inputs, &inter, &[1.0], &mask, 0.0),
+        [[1.0], [2.0], [0.0]]
+    );
+
+    let sum = BatchLoss::<_, _, 3>::new(SquareError { expected: [0.0] }, ReduceSum);
+    assert_eq!(sum.intermediate_masked(&inputs, &mask).output, [5.0]);
+    // Without steps, the mean is zero rather than NaN.
+    assert_eq!(mean.intermediate_masked(&inputs, &[false; 3]).output, [0.0]);
+}
+
+#[test]
+fn variable_length_sequences_are_trained() {
+    // Every step should output half of the input of the step before it, in sequences of two to
+    // five steps.
+    const T: usize = 5;
+    let sequences: [&[[Scalar; 1]]; 4] = [
+        &[[1.0], [-1.0]],
+        &[[-1.0], [1.0], [1.0], [-1.0], [1.0]],
+        &[[1.0], [1.0], [-1.0]],
+        &[[-1.0], [-1.0], [1.0], [-1.0]],
+    ];
+    let dataset: Vec<_> = sequences
+        .iter()
+        .map(|steps| {
+            let targets: Vec<_> = (0..steps.len())
+                .map(|t| [if t == 0 { 0.0 } else { steps[t - 1][0] / 2.0 }])
+                .collect();
+            (
+                Masked::<_, T>::padded(steps, [0.0]),
+                Masked::<_, T>::padded(&targets, [0.0]).steps,
+            )
+        })
+        .collect();
+    let mut net = Recurrent::<_, T>::new(Gru::<1, 4>::new(testing::seeded_gen(3)))
+        .chain(TimeDistributed::<_, T>::new(Full::<4, 1, _>::new(
+            Tanh,
+            testing::seeded_gen(4),
+        )))
+        .chain(BatchLoss::<_, _, T>::new(
+            SquareError { expected: [0.0] },
+            ReduceMean,
+        ))
+        .masked();
+    let trainer = Trainer {
+        epochs: 1000,
+        learning_rate: LearningRate(0.5),
+        ..Default::default()
+    };
+    let fit = trainer.fit(&mut net, &dataset);
+    assert!(
+        fit.errors.last().unwrap() < &(0.05 * fit.errors[0]),
+        "{:?}",
+        fit.errors.last()
+    );
+}
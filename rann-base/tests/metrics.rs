@@ -0,0 +1,269 @@
+use rann_base::{
+    activ::Logistic,
+    error::{CrossEntropy, SquareError},
+    metrics::{
+        self, Classification, ClassificationReport, ConfusionMatrix, Regression, RegressionReport,
+        RocCurve,
+    },
+    synthetic, testing,
+    train::{CancellationToken, Trainer},
+    Full,
+};
+use rann_traits::{LearningRate, Network, Scalar};
+
+#[test]
+fn classes_of_outputs() {
+    assert_eq!(metrics::class_of(&[0.2]), 0);
+    assert_eq!(metrics::class_of(&[0.5]), 1);
+    assert_eq!(metrics::class_of(&[0.1, 0.7, 0.2]), 1);
+    assert_eq!(metrics::class_of(&[-1.0, -3.0]), 0);
+}
+
+#[test]
+fn per_class_metrics() {
+    let mut confusion = ConfusionMatrix::new(3);
+    // Class 0: 3 right, 1 predicted as 1.
+    for (actual, predicted) in [(0, 0), (0, 0), (0, 0), (0, 1)] {
+        confusion.add(actual, predicted);
+    }
+    // Class 1: 1 right, 1 predicted as 0.
+    for (actual, predicted) in [(1, 1), (1, 0)] {
+        confusion.add(actual, predicted);
+    }
+    assert_eq!(confusion.total(), 6);
+    assert_eq!(confusion.count(0, 1), 1);
+
+    let report = ClassificationReport::new(confusion);
+    assert!((report.accuracy - 4.0 / 6.0).abs() < 1e-6);
+    let class0 = report.classes[0];
+    assert_eq!(
+        (class0.precision, class0.recall, class0.support),
+        (0.75, 0.75, 4)
+    );
+    assert!((class0.f1 - 0.75).abs() < 1e-6);
+    let class1 = report.classes[1];
+    assert_eq!(
+        (class1.precision, class1.recall, class1.support),
+        (0.5, 0.5, 2)
+    );
+    // Class 2 has no samples and no predictions.
+    assert_eq!(report.classes[2].f1, 0.0);
+    assert!((report.macro_f1 - (0.75 + 0.5) / 3.0).abs() < 1e-6);
+
+    let table = report.to_string();
+    assert!(table.starts_with("class  precision  recall  f1      support\n0      0.7500"));
+    assert!(table.ends_with("accuracy 0.6667, macro F1 0.4167"));
+}
+
+#[test]
+fn validates_every_epoch() {
+    let mut net = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1))
+        .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)))
+        .chain(SquareError { expected: [0.0] });
+    let trainer = Trainer {
+        epochs: 2000,
+        learning_rate: LearningRate(1.0),
+        ..Default::default()
+    };
+    let mut logged = 0;
+    let validated = trainer.fit_validated(
+        &mut net,
+        &testing::XOR,
+        &testing::XOR,
+        &Classification,
+        |fit, report| {
+            logged += 1;
+            assert_eq!(fit.errors.len(), logged);
+            assert_eq!(report.confusion.total(), 4);
+        },
+    );
+    assert_eq!(logged, 2000);
+    assert_eq!(validated.reports.len(), 2000);
+    assert_eq!(validated.fit.errors.len(), 2000);
+    let last = validated.reports.last().unwrap();
+    assert_eq!(last.accuracy, 1.0);
+    assert_eq!(last.classes[1].support, 2);
+}
+
+#[test]
+fn validates_multiclass_networks() {
+    let linear = (|x: Scalar| x, |_: Scalar| 1.0);
+    let dataset = synthetic::blobs::<2, 3>(90, 0.3, 7);
+    let mut net = Full::<2, 3, _>::new(linear, testing::seeded_gen(4)).chain(CrossEntropy::new());
+    let trainer = Trainer {
+        epochs: 50,
+        learning_rate: LearningRate(0.05),
+        ..Default::default()
+    };
+    let validated = trainer.fit_validated(&mut net, &dataset, &dataset, &Classification, |_, _| {});
+    let last = validated.reports.last().unwrap();
+    assert_eq!(last.classes.len(), 3);
+    assert!(last.macro_f1 > 0.95);
+    assert!(last.classes.iter().all(|c| c.support == 30));
+}
+
+#[test]
+fn cancelled_epochs_are_not_validated() {
+    let mut net = Full::<2, 1, _>::new(Logistic, testing::seeded_gen(1))
+        .chain(SquareError { expected: [0.0] });
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let trainer = Trainer {
+        cancel: Some(cancel),
+        ..Default::default()
+    };
+    let validated = trainer.fit_validated(
+        &mut net,
+        &testing::XOR,
+        &testing::XOR,
+        &Classification,
+        |_, _| panic!("No epoch should complete."),
+    );
+    assert!(validated.fit.cancelled);
+    assert!(validated.reports.is_empty());
+}
+
+#[test]
+fn regression_metrics() {
+    let targets = [[1.0], [2.0], [3.0], [4.0]];
+    let predictions = [[1.5], [2.5], [2.5], [4.5]];
+    let report = RegressionReport::new(&predictions, &targets);
+    assert_eq!(report.mae, 0.5);
+    assert_eq!(report.rmse, 0.5);
+    // The variance of the targets is 1.25.
+    assert!((report.r2 - (1.0 - 0.25 / 1.25)).abs() < 1e-6);
+    // The errors have a mean of -0.25 and a variance of 0.1875.
+    assert!((report.explained_variance - (1.0 - 0.1875 / 1.25)).abs() < 1e-6);
+    assert_eq!(
+        report.to_string(),
+        "MAE 0.5000, RMSE 0.5000, R² 0.8000, explained variance 0.8500"
+    );
+}
+
+#[test]
+fn explained_variance_ignores_offsets() {
+    let targets = [[1.0, -1.0], [2.0, 0.0], [3.0, 1.0]];
+    let predictions = targets.map(|[a, b]| [a + 1.0, b]);
+    let report = RegressionReport::new(&predictions, &targets);
+    assert!((report.explained_variance - 1.0).abs() < 1e-6);
+    // The first output has an R² of 1 - 1 / (2 / 3), and the second one of 1.
+    assert!((report.r2 - (1.0 - 1.5 + 1.0) / 2.0).abs() < 1e-6);
+    assert!((report.mae - 0.5).abs() < 1e-6);
+}
+
+#[test]
+fn constant_targets() {
+    let targets = [[2.0], [2.0]];
+    assert_eq!(RegressionReport::new(&targets, &targets).r2, 1.0);
+    assert_eq!(RegressionReport::new(&[[1.0], [3.0]], &targets).r2, 0.0);
+}
+
+#[test]
+fn validates_regression_networks() {
+    let linear = (|x: Scalar| x, |_: Scalar| 1.0);
+    let dataset = synthetic::sine(100, 0.0, 1);
+    let mut net = Full::<1, 8, _>::new(Logistic, testing::seeded_gen(5))
+        .chain(Full::<8, 1, _>::new(linear, testing::seeded_gen(6)))
+        .chain(SquareError { expected: [0.0] });
+    let trainer = Trainer {
+        epochs: 500,
+        learning_rate: LearningRate(0.05),
+        ..Default::default()
+    };
+    let validated = trainer.fit_validated(&mut net, &dataset, &dataset, &Regression, |_, _| {});
+    let (first, last) = (validated.reports[0], *validated.reports.last().unwrap());
+    assert!(last.rmse < first.rmse);
+    assert!(last.r2 > 0.9, "R² {} should be close to one.", last.r2);
+}
+
+#[test]
+fn roc_curve_points() {
+    let scores = [0.9, 0.8, 0.7, 0.6, 0.55, 0.4];
+    let labels = [true, true, false, true, false, false];
+    let roc = RocCurve::new(&scores, &labels);
+    let rates: Vec<_> = roc
+        .points
+        .iter()
+        .map(|p| (p.false_positive_rate, p.true_positive_rate))
+        .collect();
+    let third = 1.0 / 3.0;
+    let expected = [
+        (0.0, 0.0),
+        (0.0, third),
+        (0.0, 2.0 * third),
+        (third, 2.0 * third),
+        (third, 1.0),
+        (2.0 * third, 1.0),
+        (1.0, 1.0),
+    ];
+    assert_eq!(rates.len(), expected.len());
+    for ((fpr, tpr), (e_fpr, e_tpr)) in rates.into_iter().zip(expected) {
+        assert!((fpr - e_fpr).abs() < 1e-6 && (tpr - e_tpr).abs() < 1e-6);
+    }
+    assert_eq!(roc.points[0].threshold, Scalar::INFINITY);
+    assert_eq!(roc.points[3].threshold, 0.7);
+    // 8 of the 9 pairs of a positive and a negative sample are ordered correctly.
+    assert!((roc.auc() - 8.0 / 9.0).abs() < 1e-6);
+}
+
+#[test]
+fn best_threshold_balances_the_rates() {
+    let scores = [0.9, 0.8, 0.7, 0.6, 0.5, 0.4, 0.3];
+    let labels = [true, true, false, true, false, false, false];
+    // The threshold of 0.6 finds all positives, and one of four negatives.
+    let best = RocCurve::new(&scores, &labels).best_threshold();
+    assert_eq!(best.threshold, 0.6);
+    assert_eq!(best.false_positive_rate, 0.25);
+}
+
+#[test]
+fn roc_ties_count_as_one_half() {
+    let roc = RocCurve::new(&[0.5, 0.5, 0.5, 0.5], &[true, false, true, false]);
+    assert_eq!(roc.points.len(), 2);
+    assert!((roc.auc() - 0.5).abs() < 1e-6);
+}
+
+#[test]
+fn perfect_and_inverted_classifiers() {
+    let labels = [false, false, true, true];
+    assert_eq!(RocCurve::new(&[0.1, 0.2, 0.8, 0.9], &labels).auc(), 1.0);
+    assert_eq!(RocCurve::new(&[0.9, 0.8, 0.2, 0.1], &labels).auc(), 0.0);
+}
+
+#[test]
+fn confusion_at_thresholds() {
+    let scores = [0.9, 0.8, 0.7, 0.6, 0.55, 0.4];
+    let labels = [true, true, false, true, false, false];
+    let confusion = metrics::confusion_at(&scores, &labels, 0.6);
+    // True negatives, false positives, false negatives and true positives.
+    assert_eq!(
+        [(0, 0), (0, 1), (1, 0), (1, 1)].map(|(a, p)| confusion.count(a, p)),
+        [2, 1, 0, 3]
+    );
+    let roc = RocCurve::new(&scores, &labels);
+    for point in &roc.points[1..] {
+        let confusion = metrics::confusion_at(&scores, &labels, point.threshold);
+        let report = ClassificationReport::new(confusion);
+        assert!((report.classes[1].recall - point.true_positive_rate).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn roc_of_trained_classifier() {
+    let dataset = synthetic::moons(200, 0.1, 2);
+    let mut net = Full::<2, 8, _>::new(Logistic, testing::seeded_gen(1))
+        .chain(Full::<8, 1, _>::new(Logistic, testing::seeded_gen(2)))
+        .chain(SquareError { expected: [0.0] });
+    let before = RocCurve::of(&net, &dataset).auc();
+    Trainer {
+        epochs: 300,
+        learning_rate: LearningRate(0.5),
+        ..Default::default()
+    }
+    .fit(&mut net, &dataset);
+    let after = RocCurve::of(&net, &dataset).auc();
+    assert!(
+        after > 0.99 && after > before,
+        "AUC {after} should be close to one."
+    );
+}
@@ -0,0 +1,73 @@
+use rann_base::{
+    mining::{self, HardMining},
+    testing,
+    train::{CancellationToken, Trainer},
+};
+use rann_traits::{params::Parameterized, LearningRate, Scalar, Supervised};
+
+fn trainer() -> Trainer {
+    Trainer {
+        epochs: 20,
+        learning_rate: LearningRate(0.5),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn hardest_samples_are_ordered_by_loss() {
+    let losses = [0.1, 0.5, Scalar::NAN, 0.5, 0.0];
+    assert_eq!(mining::hardest(&losses, 3), [2, 1, 3]);
+    assert_eq!(mining::hardest(&losses, 10).len(), 5);
+    let mining = HardMining { ratio: 0.5 };
+    assert_eq!(mining.count(5), 3);
+    assert_eq!(mining.hardest(&losses), [2, 1, 3]);
+    assert_eq!(HardMining { ratio: -1.0 }.count(5), 0);
+    assert_eq!(HardMining { ratio: 3.0 }.count(5), 5);
+}
+
+#[test]
+fn without_mining_training_matches_fit() {
+    let (mut mined, mut plain) = (testing::xor_net(1), testing::xor_net(1));
+    let fit = Trainer {
+        mining: Some(HardMining { ratio: 0.0 }),
+        ..trainer()
+    }
+    .fit(&mut mined, &testing::XOR);
+    assert_eq!(fit, trainer().fit(&mut plain, &testing::XOR));
+    assert_eq!(mined.params(), plain.params());
+}
+
+#[test]
+fn hardest_samples_are_trained_on_more_often() {
+    // Every sample but the last is easy, as it is far from the others.
+    let mut dataset = vec![([0.0, 0.0], [0.0]); 9];
+    dataset.push(([1.0, 1.0], [1.0]));
+    let mining_trainer = Trainer {
+        mining: Some(HardMining { ratio: 0.1 }),
+        ..trainer()
+    };
+    let (mut mined, mut plain) = (testing::xor_net(1), testing::xor_net(1));
+    let fit = mining_trainer.fit(&mut mined, &dataset);
+    assert_eq!(fit.steps, 10 + 19 * 11);
+    trainer().fit(&mut plain, &dataset);
+    assert!(error(&mut mined, &dataset[9]) < error(&mut plain, &dataset[9]));
+}
+
+fn error<N: Supervised>(net: &mut N, (inputs, target): &(N::In, N::Target)) -> Scalar {
+    net.set_target(target);
+    net.eval(inputs)[0]
+}
+
+#[test]
+fn cancelled_mining_stops_before_the_next_step() {
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let trainer = Trainer {
+        cancel: Some(cancel),
+        mining: Some(HardMining::default()),
+        ..trainer()
+    };
+    let fit = trainer.fit(&mut testing::xor_net(1), &testing::XOR);
+    assert!(fit.cancelled);
+    assert_eq!(fit.steps, 0);
+}
@@ -0,0 +1,59 @@
+use rann_base::{
+    activ::{LeakyRelu, Logistic},
+    error::SquareError,
+    mixed::MixedFull,
+    testing, Full,
+};
+use rann_traits::{params::Parameterized, Network};
+
+const INPUT: [f32; 3] = [0.25, -1.5, 2.0];
+
+#[test]
+fn conversion_preserves_layer() {
+    let full = Full::<3, 2, _>::new(Logistic, testing::seeded_gen(1));
+    let mixed = MixedFull::<3, 2, _, f64>::from_full(&full);
+    assert_eq!(mixed.params(), full.params());
+    assert_eq!(mixed.to_full().params(), full.params());
+
+    for (a, b) in full.eval(&INPUT).iter().zip(mixed.eval(&INPUT)) {
+        assert!((a - b).abs() < 1e-6, "{a} should be close to {b}.");
+    }
+}
+
+#[test]
+fn matches_full_training() {
+    let mut full = Full::<3, 2, _>::new(Logistic, testing::seeded_gen(2));
+    let mut mixed = MixedFull::<3, 2, _, f32>::from_full(&full);
+    for _ in 0..10 {
+        let inter = full.intermediate(&INPUT);
+        full.train_deriv(&INPUT, &inter, &[1.0, -1.0], 0.1);
+        let inter = mixed.intermediate(&INPUT);
+        mixed.train_deriv(&INPUT, &inter, &[1.0, -1.0], 0.1);
+    }
+    for (a, b) in full.params().iter().zip(mixed.params()) {
+        assert!((a - b).abs() < 1e-5, "{a} should be close to {b}.");
+    }
+}
+
+#[test]
+fn mixed_precision_xor() {
+    let gen = testing::seeded_gen(0x2);
+    let mut net = MixedFull::<2, 4, _, f64>::new(LeakyRelu(0.1), gen.clone())
+        .chain(Full::<4, 1, _>::new(LeakyRelu(0.1), gen))
+        .chain(SquareError { expected: [0.0] });
+    testing::assert_network_converges(&mut net, &testing::XOR, 5000, 0.05, 0.01);
+}
+
+#[cfg(feature = "half")]
+#[test]
+fn half_precision_storage() {
+    let full = Full::<3, 2, _>::new(Logistic, testing::seeded_gen(3));
+    let mixed = MixedFull::<3, 2, _, half::f16>::from_full(&full);
+    // Half precision has about three significant decimal digits.
+    for (a, b) in full.params().iter().zip(mixed.params()) {
+        assert!(
+            (a - b).abs() <= a.abs() * 1e-3,
+            "{a} should be close to {b}."
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use rann_base::{
+    mlp::{Activation, Init, Mlp, MlpBuilder, SquareLoss},
+    model,
+    train::Trainer,
+};
+use rann_traits::{inspect::Inspect, params::Parameterized, LearningRate, Network};
+
+#[test]
+fn configures_every_layer() {
+    let mlp = Mlp::builder(3)
+        .layer(5)
+        .activation(Activation::Tanh)
+        .no_bias()
+        .layer(4)
+        .layer(2)
+        .activation(Activation::LeakyRelu(0.0))
+        .build()
+        .unwrap();
+    assert_eq!(mlp.sizes(), [3, 5, 4, 2]);
+    assert_eq!(
+        mlp.activations(),
+        [
+            Activation::Tanh,
+            Activation::Logistic,
+            Activation::LeakyRelu(0.0)
+        ]
+    );
+    assert_eq!(mlp.activation(), None);
+    assert_eq!(mlp.biases(), [false, true, true]);
+    assert_eq!(mlp.num_params(), 3 * 5 + 5 * 4 + 4 + 4 * 2 + 2);
+    assert_eq!(mlp.eval(&vec![1.0, 2.0, 3.0]).len(), 2);
+}
+
+#[test]
+fn new_builds_uniform_networks() {
+    let built = Mlp::builder(2)
+        .seed(3)
+        .layer(3)
+        .activation(Activation::Tanh)
+        .layer(1)
+        .activation(Activation::Tanh)
+        .build()
+        .unwrap();
+    let mlp = Mlp::new(&[2, 3, 1], Activation::Tanh, 3);
+    assert_eq!(built, mlp);
+    assert_eq!(mlp.activation(), Some(Activation::Tanh));
+    assert!(mlp.params().iter().all(|p| (-2.0..2.0).contains(p)));
+}
+
+#[test]
+fn initializers() {
+    let mlp = Mlp::builder(6)
+        .layer(10)
+        .initializer(Init::Xavier)
+        .layer(4)
+        .initializer(Init::He)
+        .build()
+        .unwrap();
+    let params = mlp.params();
+    let (first, second) = params.split_at(6 * 10 + 10);
+    let xavier = (6.0f32 / 16.0).sqrt();
+    assert!(first[..60].iter().all(|w| w.abs() < xavier));
+    assert!(first[60..].iter().all(|&b| b == 0.0));
+    let he = (6.0f32 / 10.0).sqrt();
+    assert!(second[..40].iter().all(|w| w.abs() < he));
+    assert!(second[40..].iter().all(|&b| b == 0.0));
+}
+
+#[test]
+fn rejects_invalid_networks() {
+    let error = |builder: MlpBuilder| builder.build().unwrap_err().to_string();
+    assert_eq!(
+        error(Mlp::builder(2).no_bias().layer(1)),
+        "no_bias is set before the first layer"
+    );
+    assert_eq!(error(Mlp::builder(2)), "the network has no layers");
+    assert_eq!(error(Mlp::builder(0).layer(1)), "the network has no inputs");
+    assert_eq!(
+        error(Mlp::builder(2).layer(3).layer(0)),
+        "layer 1 has no outputs"
+    );
+}
+
+#[test]
+fn bias_free_networks_train() {
+    let dataset: Vec<_> = rann_base::synthetic::to_vecs(&rann_base::testing::XOR);
+    let mlp = Mlp::builder(2)
+        .seed(1)
+        .layer(4)
+        .no_bias()
+        .layer(1)
+        .build()
+        .unwrap();
+    let mut net = SquareLoss::new(mlp);
+    let fit = Trainer {
+        epochs: 3000,
+        learning_rate: LearningRate(0.5),
+        ..Default::default()
+    }
+    .fit(&mut net, &dataset);
+    assert!(fit.errors.last().unwrap() < fit.errors.first().unwrap());
+}
+
+#[test]
+fn evaluates_into_previous_intermediates() {
+    let mlp = Mlp::new(&[2, 4, 1], Activation::Tanh, 1);
+    let mut inter = mlp.intermediate(&vec![1.0, 0.0]);
+    let buffers: Vec<_> = inter
+        .outputs
+        .iter()
+        .map(|outputs| outputs.as_ptr())
+        .collect();
+    mlp.intermediate_into(&vec![0.0, 1.0], &mut inter);
+    assert_eq!(inter, mlp.intermediate(&vec![0.0, 1.0]));
+    // The buffers are reused.
+    let reused: Vec<_> = inter
+        .outputs
+        .iter()
+        .map(|outputs| outputs.as_ptr())
+        .collect();
+    assert_eq!(reused, buffers);
+}
+
+#[test]
+fn inspects_every_layer() {
+    let mlp = Mlp::builder(2)
+        .layer(4)
+        .activation(Activation::Tanh)
+        .no_bias()
+        .layer(1)
+        .build()
+        .unwrap();
+    let mut net = SquareLoss::new(mlp);
+    let inputs = vec![1.0, 0.0];
+    let dot = net.to_dot(&net.intermediate(&inputs));
+    assert!(dot.contains(r#"layer1 [label="Full (tanh)\n2 → 4\n8 params"];"#));
+    assert!(dot.contains(r#"layer2 [label="Full (logistic)\n4 → 1\n5 params"];"#));
+    assert!(dot.contains(r#"layer3 [label="SquareError\n1 → 1\n0 params"];"#));
+    for i in 0..4 {
+        assert!(dot.contains(&format!("layer{i} -> layer{};", i + 1)));
+    }
+
+    rann_base::train::train_step(&mut net, &inputs, &vec![1.0], 0.5);
+    let stats = net.layer_stats(&net.intermediate(&inputs));
+    assert_eq!(stats.len(), 3);
+    assert!(stats[0].gradient_norm > 0.0 && stats[1].gradient_norm > 0.0);
+}
+
+#[test]
+fn metrics_of_empty_datasets_are_zero() {
+    let mut net = SquareLoss::new(Mlp::new(&[2, 1], Activation::Logistic, 1));
+    let metrics = net.metrics(&[]);
+    assert_eq!((metrics.error, metrics.accuracy), (0.0, 0.0));
+}
+
+#[test]
+fn only_top_k_neurons_are_trained() {
+    let mut mlp = Mlp::builder(2)
+        .layer(4)
+        .activation(Activation::LeakyRelu(1.0))
+        .top_k(2)
+        .build()
+        .unwrap();
+    let before = mlp.params();
+    let inter = mlp.intermediate(&vec![1.0, 1.0]);
+    mlp.train_deriv(&vec![1.0, 1.0], &inter, &vec![0.1, -2.0, 0.05, 1.0], 0.1);
+    // The biases follow the weights; only those of the second and fourth neuron changed.
+    let changed: Vec<bool> = (0..4)
+        .map(|n| before[8 + n] != mlp.params()[8 + n])
+        .collect();
+    assert_eq!(changed, [false, true, false, true]);
+}
+
+#[test]
+fn saves_and_loads_configured_networks() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("mixed.rann");
+    let mlp = Mlp::builder(2)
+        .seed(5)
+        .layer(3)
+        .activation(Activation::Tanh)
+        .no_bias()
+        .layer(1)
+        .build()
+        .unwrap();
+    model::save(&mlp, &mlp.metadata("mixed"), &path).unwrap();
+    let (loaded, metadata) = Mlp::load(&path).unwrap();
+    assert_eq!(loaded, mlp);
+    assert_eq!(metadata.hyperparameters["activations"], "tanh,logistic");
+    assert_eq!(metadata.hyperparameters["biases"], "false,true");
+}
+
+#[test]
+fn mlp_params_round_trip() {
+    let mlp = Mlp::new(&[3, 4, 2], Activation::Tanh, 1);
+    assert_eq!(mlp.sizes(), [3, 4, 2]);
+    assert_eq!(mlp.num_params(), 3 * 4 + 4 + 4 * 2 + 2);
+    let mut other = Mlp::new(&[3, 4, 2], Activation::Tanh, 2);
+    assert_ne!(other, mlp);
+    other.read_params(&mlp.params());
+    assert_eq!(other, mlp);
+    assert_eq!(
+        other.eval(&vec![1.0, 2.0, 3.0]),
+        mlp.eval(&vec![1.0, 2.0, 3.0])
+    );
+}
@@ -0,0 +1,56 @@
+use rann_base::{
+    activ::Logistic,
+    error::SquareError,
+    model::{self, Metadata},
+    testing,
+    train::Trainer,
+    Full,
+};
+use rann_traits::{params::Parameterized, Network};
+
+#[test]
+fn saves_and_loads_with_metadata() {
+    let mut net = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1))
+        .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)))
+        .chain(SquareError { expected: [0.0] });
+    let trainer = Trainer {
+        epochs: 5,
+        ..Default::default()
+    };
+    let fit = trainer.fit(&mut net, &testing::XOR);
+    let metadata = Metadata::describe(&net, "xor = exclusive or")
+        .with_comment("Trained on\nXOR.\\")
+        .with_hyperparameter("hidden=units", 3)
+        .with_training(&trainer, &fit)
+        .with_metric("empty", Vec::new());
+    assert_eq!(metadata.num_params, 13);
+    assert!(metadata.architecture.contains("Full"));
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("xor.rann");
+    model::save(&net, &metadata, &path).unwrap();
+
+    assert_eq!(model::peek_metadata(&path).unwrap(), metadata);
+    let mut loaded = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(3))
+        .chain(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(4)))
+        .chain(SquareError { expected: [0.0] });
+    assert_eq!(model::load(&mut loaded, &path).unwrap(), metadata);
+    assert_eq!(loaded.params(), net.params());
+    assert_eq!(metadata.metrics["error"], fit.errors);
+}
+
+#[test]
+fn rejects_mismatched_networks() {
+    let net = Full::<2, 3, Logistic>::new(Logistic, testing::seeded_gen(1));
+    let mut file = Vec::new();
+    model::write_to(&net, &Metadata::describe(&net, "small"), &mut file).unwrap();
+
+    let mut other = Full::<3, 3, Logistic>::new(Logistic, testing::seeded_gen(1));
+    let before = other.params();
+    let err = model::read_from(&mut other, &file[..]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(other.params(), before);
+
+    // Checkpoints are not models.
+    assert!(model::read_metadata(&b"RANNCKPT\x01\0\0\0"[..]).is_err());
+}
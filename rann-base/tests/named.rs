@@ -0,0 +1,54 @@
+use rann_base::{
+    activ::{LeakyRelu, Logistic},
+    autoencoder::Autoencoder,
+    testing, Full,
+};
+use rann_traits::{compose::zip, params::Parameterized, Network};
+
+#[test]
+fn finds_layers_in_chains_and_zips() {
+    let gen = testing::seeded_gen;
+    let net = Full::<2, 2, _>::new(Logistic, gen(1))
+        .named("top")
+        .zip(
+            Full::<2, 2, _>::new(Logistic, gen(2)).named("bot"),
+            zip::Stacker::<2, 2, 4>,
+        )
+        .chain(Full::<4, 1, _>::new(LeakyRelu(0.1), gen(3)).named("out"));
+    let inputs = ([1.0, 2.0], [3.0, 4.0]);
+    let top = net.layer::<Full<2, 2, Logistic>>("top").unwrap();
+    assert_eq!(top.eval(&inputs.0), net.first.top.eval(&inputs.0));
+    let bot = net.layer::<Full<2, 2, Logistic>>("bot").unwrap();
+    assert_eq!(bot.eval(&inputs.1), net.first.bot.eval(&inputs.1));
+    assert!(net.layer::<Full<4, 1, LeakyRelu>>("out").is_some());
+    assert!(net.layer::<Full<4, 1, LeakyRelu>>("missing").is_none());
+}
+
+#[test]
+fn finds_first_and_nested_names() {
+    let gen = testing::seeded_gen;
+    let mut net = Full::<2, 2, _>::new(Logistic, gen(1))
+        .named("dup")
+        .chain(Full::<2, 2, _>::new(Logistic, gen(2)).named("dup"))
+        .named("outer");
+    assert!(net.layer::<Full<2, 2, Logistic>>("dup").is_some());
+    let outer_params = net.params();
+    let first = net.layer_mut::<Full<2, 2, Logistic>>("dup").unwrap();
+    // The first of two equally named layers is found.
+    *first = Full::new(Logistic, (|_, _| 0.0, |_| 0.0));
+    assert_eq!(net.inner.first.inner.params(), [0.0; 6]);
+    assert_eq!(net.inner.second.params(), outer_params[6..]);
+    assert!(net.find_layer("outer").is_some());
+}
+
+#[test]
+fn finds_layers_in_autoencoders() {
+    let gen = testing::seeded_gen;
+    let encoder = Full::<4, 2, _>::new(Logistic, gen(1)).named("code");
+    let mut auto = Autoencoder {
+        net: encoder.chain(Full::<2, 4, _>::new(Logistic, gen(2))),
+    };
+    let code = auto.layer_mut::<Full<4, 2, Logistic>>("code").unwrap();
+    code.read_params(&[0.0; 10]);
+    assert_eq!(auto.eval(&[1.0; 4]), auto.net.second.eval(&[0.5; 2]));
+}
@@ -0,0 +1,41 @@
+use rann_base::{activ::Logistic, error::SquareError, noise::GradientNoise, testing, Full};
+use rann_traits::{params::Parameterized, Network};
+
+fn step(eta: f32, seed: u64) -> Vec<f32> {
+    let net = Full::<2, 2, _>::new(Logistic, testing::seeded_gen(1));
+    let mut net = GradientNoise::new(net, eta, seed).chain(SquareError {
+        expected: [1.0, 0.0],
+    });
+    let inter = net.intermediate(&[0.5, 1.0]);
+    net.train_deriv(&[0.5, 1.0], &inter, &[1.0], 0.1);
+    net.first.params()
+}
+
+#[test]
+fn noise_is_seeded_and_vanishes_without_eta() {
+    let plain = {
+        let mut net = Full::<2, 2, _>::new(Logistic, testing::seeded_gen(1)).chain(SquareError {
+            expected: [1.0, 0.0],
+        });
+        let inter = net.intermediate(&[0.5, 1.0]);
+        net.train_deriv(&[0.5, 1.0], &inter, &[1.0], 0.1);
+        net.first.params()
+    };
+    assert_eq!(step(0.0, 1), plain);
+    assert_eq!(step(0.3, 1), step(0.3, 1));
+    assert_ne!(step(0.3, 1), step(0.3, 2));
+    assert_ne!(step(0.3, 1), plain);
+}
+
+#[test]
+fn noise_is_annealed() {
+    let net = Full::<2, 2, _>::new(Logistic, testing::seeded_gen(1));
+    let mut net = GradientNoise::new(net, 1.0, 0);
+    assert_eq!(net.std_dev(), 1.0);
+    for _ in 0..10 {
+        let inter = net.intermediate(&[0.5, 1.0]);
+        net.train_deriv(&[0.5, 1.0], &inter, &[1.0, 1.0], 0.1);
+    }
+    assert_eq!(net.steps(), 10);
+    assert!((net.std_dev() - 11f32.powf(-0.55).sqrt()).abs() < 1e-6);
+}
@@ -0,0 +1,97 @@
+use rann_base::LayerNorm;
+use rann_traits::{Network, Scalar};
+
+// The step used for the central differences.
+const H: f64 = 1e-3;
+// The maximum allowed difference between the analytical and numerical gradients.
+const TOLERANCE: Scalar = 1e-2;
+
+const INPUT: [Scalar; 4] = [0.5, -1.25, 2.0, 0.75];
+// Weights used to reduce the outputs to a single scalar loss.
+const WEIGHTS: [Scalar; 4] = [0.3, -0.7, 1.1, 0.2];
+
+fn layer() -> LayerNorm<4> {
+    let mut norm = LayerNorm::new();
+    norm.scale = [1.5, 0.5, -0.8, 1.2];
+    norm.shift = [0.1, -0.2, 0.3, 0.0];
+    norm
+}
+
+// The loss whose derivatives are checked, calculated in double precision.
+fn loss(norm: &LayerNorm<4>, input: &[f64; 4]) -> f64 {
+    let n = 4.0;
+    let mean = input.iter().sum::<f64>() / n;
+    let var = input.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / n;
+    let inv_std = 1.0 / (var + norm.epsilon as f64).sqrt();
+    (0..4)
+        .map(|i| {
+            let y = (input[i] - mean) * inv_std * norm.scale[i] as f64 + norm.shift[i] as f64;
+            y * WEIGHTS[i] as f64
+        })
+        .sum()
+}
+
+#[test]
+fn outputs_are_normalized() {
+    let norm = LayerNorm::<4>::new();
+    let out = norm.eval(&INPUT);
+    let mean = out.iter().sum::<Scalar>() / 4.0;
+    let var = out.iter().map(|x| (x - mean) * (x - mean)).sum::<Scalar>() / 4.0;
+    assert!(mean.abs() < 1e-5, "Mean {mean} should be zero.");
+    assert!((var - 1.0).abs() < 1e-3, "Variance {var} should be one.");
+}
+
+#[test]
+fn input_gradients_match_numerical() {
+    let mut norm = layer();
+    let inter = norm.intermediate(&INPUT);
+    // A learning rate of zero leaves the layer untouched.
+    let grad = norm.train_deriv(&INPUT, &inter, &WEIGHTS, 0.0);
+
+    let input = INPUT.map(|x| x as f64);
+    for i in 0..4 {
+        let (mut plus, mut minus) = (input, input);
+        plus[i] += H;
+        minus[i] -= H;
+        let numerical = ((loss(&norm, &plus) - loss(&norm, &minus)) / (2.0 * H)) as Scalar;
+        assert!(
+            (grad[i] - numerical).abs() < TOLERANCE,
+            "Gradient {} of input {i} should be close to {numerical}.",
+            grad[i]
+        );
+    }
+}
+
+#[test]
+fn parameter_gradients_match_numerical() {
+    const RATE: Scalar = 1e-2;
+    let before = layer();
+    let mut after = before.clone();
+    let inter = after.intermediate(&INPUT);
+    after.train_deriv(&INPUT, &inter, &WEIGHTS, RATE);
+
+    let input = INPUT.map(|x| x as f64);
+    for i in 0..4 {
+        // The analytical gradients follow from the applied update.
+        let scale_grad = (before.scale[i] - after.scale[i]) / RATE;
+        let shift_grad = (before.shift[i] - after.shift[i]) / RATE;
+
+        let numerical = |perturb: fn(&mut LayerNorm<4>, usize, Scalar)| {
+            let (mut plus, mut minus) = (before.clone(), before.clone());
+            perturb(&mut plus, i, H as Scalar);
+            perturb(&mut minus, i, -H as Scalar);
+            ((loss(&plus, &input) - loss(&minus, &input)) / (2.0 * H)) as Scalar
+        };
+        let num_scale = numerical(|n, i, h| n.scale[i] += h);
+        let num_shift = numerical(|n, i, h| n.shift[i] += h);
+
+        assert!(
+            (scale_grad - num_scale).abs() < TOLERANCE,
+            "Scale gradient {scale_grad} of feature {i} should be close to {num_scale}."
+        );
+        assert!(
+            (shift_grad - num_shift).abs() < TOLERANCE,
+            "Shift gradient {shift_grad} of feature {i} should be close to {num_shift}."
+        );
+    }
+}
@@ -0,0 +1,191 @@
+use std::io;
+
+use rann_base::{
+    activ::Logistic,
+    npy::{Array, Npz},
+    testing, Full,
+};
+use rann_traits::{params::Parameterized, Network};
+
+// Builds a `.npy` file like NumPy does, with a header padded to 64 bytes.
+fn npy(descr: &str, fortran_order: bool, shape: &str, data: &[u8]) -> Vec<u8> {
+    let order = if fortran_order { "True" } else { "False" };
+    let mut header =
+        format!("{{'descr': '{descr}', 'fortran_order': {order}, 'shape': {shape}, }}");
+    while (10 + header.len() + 1) % 64 != 0 {
+        header.push(' ');
+    }
+    header.push('\n');
+    let mut bytes = b"\x93NUMPY\x01\x00".to_vec();
+    bytes.extend((header.len() as u16).to_le_bytes());
+    bytes.extend(header.as_bytes());
+    bytes.extend(data);
+    bytes
+}
+
+#[test]
+fn arrays_round_trip() {
+    for shape in [vec![], vec![3], vec![2, 3], vec![2, 1, 2]] {
+        let len = shape.iter().product::<usize>();
+        let array = Array::new(shape, (0..len).map(|i| i as f32 / 4.0).collect());
+        let mut bytes = Vec::new();
+        array.write_npy(&mut bytes).unwrap();
+        assert_eq!((bytes.len() - 4 * len) % 64, 0);
+        assert_eq!(Array::read_npy(&bytes[..]).unwrap(), array);
+    }
+}
+
+#[test]
+fn headers_match_numpy() {
+    let mut bytes = Vec::new();
+    Array::new(vec![3], vec![1.0, 2.0, 3.0])
+        .write_npy(&mut bytes)
+        .unwrap();
+    let data: Vec<u8> = [1.0f32, 2.0, 3.0]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect();
+    assert_eq!(bytes, npy("<f4", false, "(3,)", &data));
+}
+
+#[test]
+fn doubles_big_endian_and_column_major_arrays_are_read() {
+    // The matrix [[1, 2, 3], [4, 5, 6]], stored column by column.
+    let data: Vec<u8> = [1.0f64, 4.0, 2.0, 5.0, 3.0, 6.0]
+        .iter()
+        .flat_map(|v| v.to_be_bytes())
+        .collect();
+    let array = Array::read_npy(&npy(">f8", true, "(2, 3)", &data)[..]).unwrap();
+    assert_eq!(array.shape, [2, 3]);
+    assert_eq!(array.data, [1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+}
+
+#[test]
+fn invalid_arrays_are_rejected() {
+    let data = 1.0f32.to_le_bytes();
+    let kind = |bytes: &[u8]| Array::read_npy(bytes).unwrap_err().kind();
+    assert_eq!(
+        kind(&npy("<i8", false, "(1,)", &data)),
+        io::ErrorKind::InvalidData
+    );
+    assert_eq!(
+        kind(&npy("<f4", false, "(2,)", &data)),
+        io::ErrorKind::UnexpectedEof
+    );
+    assert_eq!(kind(b"RANNCKPT\x01\0\0\0"), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn archives_round_trip() {
+    let mut npz = Npz::new();
+    npz.insert("a", Array::new(vec![2], vec![1.0, 2.0]));
+    npz.insert("b", Array::new(vec![1, 1], vec![3.0]));
+    npz.insert("a", Array::new(vec![1], vec![4.0]));
+    let mut bytes = Vec::new();
+    npz.write_to(&mut bytes).unwrap();
+    assert_eq!(bytes[..4], *b"PK\x03\x04");
+
+    let read = Npz::read_from(&bytes[..]).unwrap();
+    assert_eq!(read, npz);
+    assert_eq!(read.get("a").unwrap().data, [4.0]);
+    assert!(read.get("c").is_none());
+
+    // Corrupted and truncated archives are rejected.
+    assert!(Npz::read_from(&bytes[..bytes.len() - 1]).is_err());
+    // The first value of `a`, after the local header, its name and the header of the array.
+    bytes[30 + 5 + 64] ^= 1;
+    assert!(Npz::read_from(&bytes[..]).is_err());
+}
+
+#[test]
+fn zip64_offsets_out_of_range_are_rejected() {
+    let mut npz = Npz::new();
+    npz.insert("a", Array::new(vec![1], vec![1.0]));
+    let mut bytes = Vec::new();
+    npz.write_to(&mut bytes).unwrap();
+
+    // Defer the count to a zip64 end of central directory record at the largest offset, with a
+    // locator in front of the end of central directory record.
+    let end = bytes.len() - 22;
+    bytes[end + 10..end + 12].copy_from_slice(&[0xFF; 2]);
+    let mut locator = 0x0706_4b50u32.to_le_bytes().to_vec();
+    locator.extend(0u32.to_le_bytes());
+    locator.extend(u64::MAX.to_le_bytes());
+    locator.extend(1u32.to_le_bytes());
+    bytes.splice(end..end, locator);
+    let error = Npz::read_from(&bytes[..]).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn layers_round_trip() {
+    let layer = Full::<3, 2, _>::new(Logistic, testing::seeded_gen(1));
+    let mut npz = Npz::new();
+    layer.write_npz(&mut npz, "");
+    let weights = npz.get("weight").unwrap();
+    assert_eq!(weights.shape, [2, 3]);
+    // Every row holds the incoming weights of an output.
+    let gen = (|_, _| 0.0, |_| 0.0);
+    for output in 0..2 {
+        for input in 0..3 {
+            let mut probe = Full::<3, 2, _>::new(Logistic, gen);
+            let mut one_hot = Npz::new();
+            let mut data = vec![0.0; 6];
+            data[output * 3 + input] = 1.0;
+            one_hot.insert("weight", Array::new(vec![2, 3], data));
+            one_hot.insert("bias", Array::new(vec![2], vec![0.0; 2]));
+            probe.read_npz(&one_hot, "").unwrap();
+            let mut inputs = [0.0; 3];
+            inputs[input] = 1.0;
+            assert!(probe.eval(&inputs)[output] > 0.5);
+            assert_eq!(probe.eval(&inputs)[1 - output], 0.5);
+        }
+    }
+
+    let mut copy = Full::<3, 2, _>::new(Logistic, testing::seeded_gen(2));
+    copy.read_npz(&npz, "").unwrap();
+    assert_eq!(copy.params(), layer.params());
+}
+
+#[test]
+fn layers_without_biases() {
+    let layer = Full::<2, 2, _, _>::without_bias(Logistic, testing::seeded_gen(1));
+    let mut npz = Npz::new();
+    layer.write_npz(&mut npz, "0.");
+    assert!(npz.get("0.bias").is_none());
+
+    let mut copy = Full::<2, 2, _, _>::without_bias(Logistic, testing::seeded_gen(2));
+    copy.read_npz(&npz, "0.").unwrap();
+    assert_eq!(copy.params(), layer.params());
+
+    // Biases can not be dropped silently.
+    Full::<2, 2, _>::new(Logistic, testing::seeded_gen(3)).write_npz(&mut npz, "0.");
+    assert!(copy.read_npz(&npz, "0.").is_err());
+}
+
+#[test]
+fn mismatched_layers_are_rejected_unchanged() {
+    let mut npz = Npz::new();
+    Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1)).write_npz(&mut npz, "");
+    let mut layer = Full::<3, 2, _>::new(Logistic, testing::seeded_gen(2));
+    let params = layer.params();
+    let err = layer.read_npz(&npz, "").unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert!(err.to_string().contains("[3, 2]"));
+    assert!(layer.read_npz(&npz, "1.").is_err());
+    assert_eq!(layer.params(), params);
+}
+
+#[test]
+fn archives_are_saved_to_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("params.npz");
+    let mut npz = Npz::new();
+    Full::<2, 1, _>::new(Logistic, testing::seeded_gen(1)).write_npz(&mut npz, "");
+    npz.save(&path).unwrap();
+    assert_eq!(Npz::load(&path).unwrap(), npz);
+
+    let path = dir.path().join("weight.npy");
+    npz.get("weight").unwrap().save(&path).unwrap();
+    assert_eq!(&Array::load(&path).unwrap(), npz.get("weight").unwrap());
+}
@@ -0,0 +1,59 @@
+use fastrand::Rng;
+use rann_base::{
+    activ::LeakyRelu,
+    error::SquareError,
+    online::{Adaptation, Online, OnlineConfig},
+    testing, Full,
+};
+use rann_traits::{Network, Supervised};
+
+// A linear network, trained on a stream of samples of y = slope * x.
+fn stream(
+    config: OnlineConfig,
+    slopes: &[f32],
+    len: usize,
+) -> (
+    Online<impl Supervised<In = [f32; 1], Target = [f32; 1]>>,
+    Vec<usize>,
+) {
+    let net = Full::<1, 1, _>::new(LeakyRelu(1.0), testing::seeded_gen(1))
+        .chain(SquareError { expected: [0.0] });
+    let mut online = Online::new(net, config);
+    let mut rng = Rng::with_seed(1);
+    let mut drifts = Vec::new();
+    for (i, slope) in slopes.iter().enumerate() {
+        for j in 0..len {
+            let x = rng.f32() * 2.0 - 1.0;
+            if online.partial_fit(&[x], &[slope * x]).drift {
+                drifts.push(i * len + j);
+            }
+        }
+    }
+    (online, drifts)
+}
+
+#[test]
+fn detects_drift() {
+    let (online, drifts) = stream(OnlineConfig::default(), &[1.0, -2.0], 1000);
+    assert_eq!(drifts.len(), 1, "Drift at {drifts:?}.");
+    assert!((1000..1100).contains(&drifts[0]), "Drift at {drifts:?}.");
+    // The network adapted to the new data.
+    assert!(online.recent_error() < 1e-3);
+}
+
+#[test]
+fn adapts_learning_rate() {
+    let adaptation = Adaptation {
+        min: 0.01,
+        max: 0.5,
+        ..Default::default()
+    };
+    let config = OnlineConfig {
+        adaptation: Some(adaptation),
+        ..Default::default()
+    };
+    let (online, drifts) = stream(config, &[0.5], 2000);
+    assert!(drifts.is_empty());
+    let rate = online.learning_rate().get();
+    assert!((0.01..=0.5).contains(&rate), "{rate} is out of bounds.");
+}
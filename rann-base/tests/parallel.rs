@@ -0,0 +1,236 @@
+#![cfg(feature = "rayon")]
+
+use std::thread;
+
+use rann_base::{
+    activ::Logistic,
+    error::SquareError,
+    parallel::{self, HogwildTrainer, ParallelTrainer, SharedParams},
+    testing, train,
+    train::{CancellationToken, Fit},
+    Full,
+};
+use rann_traits::{chain, compose::Chain, params::Parameterized, LearningRate, Network};
+
+type Net = Chain<Chain<Full<2, 4, Logistic>, Full<4, 1, Logistic>>, SquareError<1>>;
+
+fn net() -> Net {
+    chain!(
+        Full::<2, 4, _>::new(Logistic, testing::seeded_gen(1)),
+        Full::<4, 1, _>::new(Logistic, testing::seeded_gen(2)),
+        SquareError { expected: [0.0] },
+    )
+}
+
+fn dataset() -> Vec<([f32; 2], [f32; 1])> {
+    (0..37)
+        .map(|i| {
+            let (a, b) = ((i % 5) as f32 / 4.0, (i % 3) as f32 / 2.0);
+            ([a, b], [(a - b).abs()])
+        })
+        .collect()
+}
+
+fn assert_close(a: &[f32], b: &[f32]) {
+    for (a, b) in a.iter().zip(b) {
+        assert!((a - b).abs() < 1e-5, "{a} != {b}");
+    }
+}
+
+#[test]
+fn batches_match_sequential_batches() {
+    let dataset = dataset();
+    for replicas in [1, 2, 3, 8, 64] {
+        let trainer = ParallelTrainer {
+            learning_rate: LearningRate(0.5),
+            replicas,
+            ..Default::default()
+        };
+        let mut parallel = net();
+        let mut sequential = net();
+        for batch in dataset.chunks(10) {
+            let error = trainer.train_batch(&mut parallel, batch);
+            assert!((error - train::train_batch(&mut sequential, batch, 0.5)).abs() < 1e-6);
+            assert_close(&parallel.params(), &sequential.params());
+        }
+    }
+}
+
+#[test]
+fn epochs_match_sequential_epochs() {
+    let dataset = dataset();
+    let trainer = ParallelTrainer {
+        epochs: 20,
+        learning_rate: LearningRate(0.5),
+        batch_size: 8,
+        replicas: 3,
+        cancel: None,
+    };
+    let mut parallel = net();
+    let fit = trainer.fit(&mut parallel, &dataset);
+    assert_eq!(fit.errors.len(), 20);
+    assert_eq!(fit.steps, 20 * dataset.len());
+    assert!(fit.errors[19] < fit.errors[0]);
+
+    let mut sequential = net();
+    for epoch in 0..20 {
+        let error = train::train_epoch_batched(&mut sequential, &dataset, 8, 0.5);
+        assert!((fit.errors[epoch] - error).abs() < 1e-5);
+    }
+    assert_close(&parallel.params(), &sequential.params());
+}
+
+#[test]
+fn replicas_and_threads_give_identical_networks() {
+    let dataset = dataset();
+    // Batches larger than a block of the reduction tree, and a smaller last batch.
+    let fit = |replicas, threads| {
+        let trainer = ParallelTrainer {
+            epochs: 5,
+            learning_rate: LearningRate(0.5),
+            batch_size: 33,
+            replicas,
+            cancel: None,
+        };
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        let mut net = net();
+        let fit = pool.install(|| trainer.fit(&mut net, &dataset));
+        (fit, net.params())
+    };
+    let expected = fit(1, 1);
+    for (replicas, threads) in [(2, 1), (3, 2), (8, 4), (33, 8)] {
+        assert_eq!(fit(replicas, threads), expected);
+    }
+}
+
+#[test]
+fn cancelled_training_stops_before_the_next_batch() {
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let trainer = ParallelTrainer {
+        cancel: Some(cancel),
+        ..Default::default()
+    };
+    let mut net = net();
+    let params = net.params();
+    let fit = trainer.fit(&mut net, &dataset());
+    assert_eq!(
+        fit,
+        Fit {
+            cancelled: true,
+            ..Default::default()
+        }
+    );
+    assert_eq!(net.params(), params);
+}
+
+#[test]
+fn empty_datasets_train_no_epochs() {
+    let mut net = net();
+    let params = net.params();
+    let fit = ParallelTrainer::default().fit(&mut net, &[]);
+    assert_eq!(fit, Fit::default());
+    assert_eq!(net.params(), params);
+    let fit = HogwildTrainer::default().fit(&mut net, &[]);
+    assert_eq!(fit, Fit::default());
+}
+
+#[test]
+#[should_panic]
+fn empty_batches_panic() {
+    ParallelTrainer::default().train_batch(&mut net(), &[]);
+}
+
+#[test]
+fn concurrent_additions_are_not_lost() {
+    let shared = SharedParams::new(&[0.0, 1.0, -1.0, 0.5]);
+    thread::scope(|scope| {
+        for t in 0..8 {
+            let shared = &shared;
+            scope.spawn(move || {
+                for i in 0..10_000 {
+                    shared.add(i % 4, 1.0);
+                    // Every thread also updates a parameter of its own half of the time.
+                    if i % 2 == t % 2 {
+                        shared.add(t % 4, 0.5);
+                    }
+                }
+            });
+        }
+    });
+    assert_eq!(shared.len(), 4);
+    assert_eq!(shared.to_vec(), [25_000.0, 25_001.0, 24_999.0, 25_000.5]);
+}
+
+#[test]
+fn single_threaded_hogwild_matches_sequential_epochs() {
+    let dataset = dataset();
+    let trainer = HogwildTrainer {
+        epochs: 5,
+        learning_rate: LearningRate(0.5),
+        threads: 1,
+        cancel: None,
+    };
+    let mut hogwild = net();
+    let fit = trainer.fit(&mut hogwild, &dataset);
+    assert_eq!(fit.steps, 5 * dataset.len());
+
+    let mut sequential = net();
+    for epoch in 0..5 {
+        let error = train::train_epoch(&mut sequential, &dataset, 0.5);
+        assert!((fit.errors[epoch] - error).abs() < 1e-5);
+    }
+    assert_close(&hogwild.params(), &sequential.params());
+}
+
+#[test]
+fn hogwild_training_converges_under_contention() {
+    let dataset: Vec<_> = dataset().into_iter().cycle().take(1000).collect();
+    let mut net = net();
+    let error = train::mean_error(&mut net, &dataset);
+    for threads in [2, 8, 32] {
+        let trainer = HogwildTrainer {
+            epochs: 5,
+            learning_rate: LearningRate(0.5),
+            threads,
+            cancel: None,
+        };
+        let fit = trainer.fit(&mut net, &dataset);
+        assert_eq!(fit.steps, 5 * dataset.len());
+        assert!(!fit.cancelled);
+        assert!(net.params().iter().all(|param| param.is_finite()));
+    }
+    assert!(train::mean_error(&mut net, &dataset) < error);
+}
+
+#[test]
+fn cancelled_hogwild_training_keeps_the_parameters() {
+    let cancel = CancellationToken::new();
+    cancel.cancel();
+    let trainer = HogwildTrainer {
+        cancel: Some(cancel),
+        ..Default::default()
+    };
+    let mut net = net();
+    let params = net.params();
+    let fit = trainer.fit(&mut net, &dataset());
+    assert!(fit.cancelled);
+    assert_eq!(fit.steps, 0);
+    assert!(fit.errors.is_empty());
+    assert_eq!(net.params(), params);
+}
+
+#[test]
+fn parallel_eval_many_keeps_the_order() {
+    let net = net().first;
+    let inputs: Vec<_> = dataset().into_iter().map(|(inputs, _)| inputs).collect();
+    let outputs = parallel::eval_many(&net, &inputs);
+    assert_eq!(outputs.len(), inputs.len());
+    for (inputs, outputs) in inputs.iter().zip(&outputs) {
+        assert_close(outputs, &net.eval(inputs));
+    }
+    assert!(parallel::eval_many(&net, &[]).is_empty());
+}
@@ -0,0 +1,58 @@
+use rann_base::{
+    activ::{Logistic, Tanh},
+    autoencoder::Autoencoder,
+    error::SquareError,
+    testing, Full, LayerNorm,
+};
+use rann_traits::{
+    compose::{zip::Stacker, Shared},
+    params::Parameterized,
+    Network,
+};
+
+// Checks that the parameters survive a round trip, and returns them.
+fn round_trip<N: Parameterized>(net: &mut N) -> Vec<f32> {
+    let params: Vec<f32> = (0..net.num_params()).map(|i| i as f32 * 0.01).collect();
+    net.read_params(&params);
+    assert_eq!(net.params(), params);
+    params
+}
+
+#[test]
+fn copying_params_copies_behaviour() {
+    let build = |seed| {
+        let gen = testing::seeded_gen(seed);
+        Full::<2, 3, _>::new(Tanh, gen.clone())
+            .chain(LayerNorm::<3>::new())
+            .zip(Full::<1, 1, _>::new(Logistic, gen), Stacker::<3, 1, 4>)
+            .chain(SquareError { expected: [0.0; 4] })
+    };
+    let mut a = build(1);
+    let mut b = build(2);
+    assert_eq!(a.num_params(), (2 * 3 + 3) + 2 * 3 + (1 + 1));
+
+    round_trip(&mut a);
+    let inputs = ([0.3, -0.4], [0.8]);
+    assert_ne!(a.eval(&inputs), b.eval(&inputs));
+    b.read_params(&a.params());
+    assert_eq!(a.eval(&inputs), b.eval(&inputs));
+}
+
+#[test]
+fn shared_and_tied_params() {
+    let gen = testing::seeded_gen(3);
+    let layer = Shared::new(Full::<3, 2, _>::new(Logistic, gen.clone()));
+    let mut auto = Autoencoder::new(layer.clone(), gen);
+    // The shared weights and biases, followed by the biases of the tied decoder.
+    assert_eq!(auto.num_params(), (3 * 2 + 2) + 3);
+
+    let params = round_trip(&mut auto);
+    assert_eq!(layer.params(), params[..8]);
+}
+
+#[test]
+#[should_panic]
+fn wrong_length_panics() {
+    let mut net = LayerNorm::<3>::new();
+    net.read_params(&[0.0; 5]);
+}
@@ -0,0 +1,52 @@
+use std::cell::Cell;
+
+use rann_base::testing;
+use rann_traits::{Network, Scalar};
+
+fn inputs() -> impl Iterator<Item = [Scalar; 2]> + Clone {
+    (0..23).map(|i| [i as Scalar / 10.0, (i % 4) as Scalar])
+}
+
+#[test]
+fn predictions_match_eval_for_any_chunk_size() {
+    let net = testing::xor_net(1).first;
+    let expected: Vec<_> = inputs().map(|input| net.eval(&input)).collect();
+    for chunk_size in [1, 2, 5, 23, 64] {
+        let outputs: Vec<_> = net
+            .predict_iter(inputs())
+            .with_chunk_size(chunk_size)
+            .collect();
+        assert_eq!(outputs.len(), expected.len());
+        for (a, b) in outputs.iter().zip(&expected) {
+            assert!((a[0] - b[0]).abs() < 1e-6);
+        }
+    }
+    assert_eq!(net.predict_iter(Vec::new()).next(), None);
+}
+
+#[test]
+fn inputs_are_taken_a_chunk_at_a_time() {
+    let net = testing::xor_net(1).first;
+    let taken = Cell::new(0);
+    let inputs = inputs().inspect(|_| taken.set(taken.get() + 1));
+    let mut outputs = net.predict_iter(inputs).with_chunk_size(5);
+    assert_eq!(outputs.size_hint(), (23, Some(23)));
+    assert_eq!(taken.get(), 0);
+    outputs.next();
+    assert_eq!(taken.get(), 5);
+    assert_eq!(outputs.size_hint(), (22, Some(22)));
+    outputs.nth(3);
+    assert_eq!(taken.get(), 5);
+    outputs.next();
+    assert_eq!(taken.get(), 10);
+    assert_eq!(outputs.count(), 17);
+}
+
+#[test]
+#[should_panic]
+fn empty_chunks_panic() {
+    let _ = testing::xor_net(1)
+        .first
+        .predict_iter(inputs())
+        .with_chunk_size(0);
+}
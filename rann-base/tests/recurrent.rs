@@ -0,0 +1,180 @@
+use rann_base::{
+    activ::Tanh,
+    recurrent::{Gru, Recurrent, Rnn},
+    testing,
+};
+use rann_traits::{params::Parameterized, Intermediate, Network, Scalar};
+
+// Generates weights and biases in `[-0.5, 0.5)`, such that the gates do not saturate.
+fn small_gen(
+    seed: u64,
+) -> (
+    impl FnMut(usize, usize) -> Scalar,
+    impl FnMut(usize) -> Scalar,
+) {
+    let (mut weights, mut biases) = testing::seeded_gen(seed);
+    (move |i, j| weights(i, j) / 4.0, move |i| biases(i) / 4.0)
+}
+
+// Asserts that `gradient` is the gradient of `f` at `x`, by finite differences.
+fn assert_gradient<const N: usize>(
+    f: impl Fn(&[Scalar; N]) -> Scalar,
+    x: [Scalar; N],
+    gradient: [Scalar; N],
+) {
+    const H: Scalar = 1e-2;
+    for i in 0..N {
+        let (mut above, mut below) = (x, x);
+        above[i] += H;
+        below[i] -= H;
+        let numeric = (f(&above) - f(&below)) / (2.0 * H);
+        assert!(
+            (numeric - gradient[i]).abs() < 1e-3,
+            "{numeric} != {}",
+            gradient[i]
+        );
+    }
+}
+
+fn dot<const N: usize>(a: &[Scalar; N], b: &[Scalar; N]) -> Scalar {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+#[test]
+fn gru_gradients_match_finite_differences() {
+    let gru = Gru::<2, 3>::new(small_gen(1));
+    let state = [0.3, -0.5, 0.8];
+    let inputs = [1.0, -0.7];
+    let weights = [0.7, -1.2, 0.4];
+    let (state_grads, input_grads) = gru.gradient(&(state, inputs), &weights);
+    assert_gradient(
+        |state| dot(&gru.eval(&(*state, inputs)), &weights),
+        state,
+        state_grads,
+    );
+    assert_gradient(
+        |inputs| dot(&gru.eval(&(state, *inputs)), &weights),
+        inputs,
+        input_grads,
+    );
+}
+
+#[test]
+fn rnn_gradients_match_finite_differences() {
+    let rnn = Rnn::<2, 3, _>::new(Tanh, small_gen(7));
+    let state = [0.3, -0.5, 0.8];
+    let inputs = [1.0, -0.7];
+    let weights = [0.7, -1.2, 0.4];
+    let (state_grads, input_grads) = rnn.gradient(&(state, inputs), &weights);
+    assert_gradient(
+        |state| dot(&rnn.eval(&(*state, inputs)), &weights),
+        state,
+        state_grads,
+    );
+    assert_gradient(
+        |inputs| dot(&rnn.eval(&(state, *inputs)), &weights),
+        inputs,
+        input_grads,
+    );
+}
+
+#[test]
+fn unrolled_gradients_match_finite_differences() {
+    let net = Recurrent::<_, 4>::new(Gru::<2, 3>::new(small_gen(2)));
+    let inputs = [[1.0, 0.0], [-0.5, 0.5], [0.2, -1.0], [0.0, 0.3]];
+    let weights = [
+        [0.5, -0.3, 0.1],
+        [0.0, 0.0, 0.0],
+        [-1.0, 0.2, 0.6],
+        [0.8, 0.4, -0.9],
+    ];
+    let error = |inputs: &[Scalar; 8]| {
+        let inputs = std::array::from_fn(|t| [inputs[2 * t], inputs[2 * t + 1]]);
+        net.eval(&inputs)
+            .iter()
+            .zip(&weights)
+            .map(|(out, w)| dot(out, w))
+            .sum()
+    };
+    let gradient = net.gradient(&inputs, &weights);
+    assert_gradient(
+        error,
+        *inputs.as_flattened().first_chunk().unwrap(),
+        *gradient.as_flattened().first_chunk().unwrap(),
+    );
+
+    // The gradients over an initial state, as passed between recurrent networks.
+    let state = [0.1, 0.2, -0.3];
+    let mut trained = net.clone();
+    let inter = trained.run(&state, &inputs);
+    let (state_grads, _) = trained.backprop(&state, &inputs, &inter, &weights, 0.0);
+    let error = |state: &[Scalar; 3]| {
+        net.run(state, &inputs)
+            .output()
+            .iter()
+            .zip(&weights)
+            .map(|(out, w)| dot(out, w))
+            .sum()
+    };
+    assert_gradient(error, state, state_grads);
+}
+
+#[test]
+fn states_are_passed_between_steps() {
+    let net = Recurrent::<_, 3>::new(Gru::<1, 2>::new(small_gen(3)));
+    let inputs = [[1.0], [0.5], [-1.0]];
+    let mut state = [0.4, -0.2];
+    let inter = net.run(&state, &inputs);
+    for (t, inputs) in inputs.iter().enumerate() {
+        state = net.cell.eval(&(state, *inputs));
+        assert_eq!(inter.output()[t], state);
+    }
+    assert_eq!(inter.state(), &state);
+    // Networks start from a state of zeros.
+    assert_eq!(net.eval(&inputs), net.run(&[0.0; 2], &inputs).into_output());
+}
+
+#[test]
+fn params_round_trip() {
+    let gru = Gru::<2, 3>::new(small_gen(4));
+    assert_eq!(gru.num_params(), 3 * (3 * 2 + 3 * 3 + 3));
+    let mut other = Recurrent::<_, 2>::new(Gru::<2, 3>::new(small_gen(5)));
+    other.read_params(&gru.params());
+    assert_eq!(other.params(), gru.params());
+    assert_eq!(
+        other.cell.eval(&([0.1; 3], [0.2; 2])),
+        gru.eval(&([0.1; 3], [0.2; 2]))
+    );
+
+    let rnn = Rnn::<2, 3, _>::new(Tanh, small_gen(8));
+    assert_eq!(rnn.num_params(), 3 * 2 + 3 * 3 + 3);
+    let mut other = Rnn::<2, 3, _>::new(Tanh, small_gen(9));
+    other.read_params(&rnn.params());
+    assert_eq!(other.params(), rnn.params());
+}
+
+#[test]
+fn gru_remembers_the_first_step() {
+    // The copy-memory task: the sign of the first input should be recalled after all steps.
+    const T: usize = 8;
+    let sequence =
+        |sign: Scalar| std::array::from_fn::<_, T, _>(|t| [if t == 0 { sign } else { 0.0 }]);
+    let samples = [(sequence(1.0), 0.5), (sequence(-1.0), -0.5)];
+    let mut net = Recurrent::<_, T>::new(Gru::<1, 4>::new(small_gen(6)));
+    let error = |net: &Recurrent<Gru<1, 4>, T>| {
+        samples
+            .iter()
+            .map(|(inputs, target)| (net.eval(inputs)[T - 1][0] - target).powi(2))
+            .sum::<Scalar>()
+    };
+    let initial = error(&net);
+    for _ in 0..2000 {
+        for (inputs, target) in &samples {
+            let inter = net.intermediate(inputs);
+            let mut gradients = [[0.0; 4]; T];
+            gradients[T - 1][0] = inter.state()[0] - target;
+            net.train_deriv(inputs, &inter, &gradients, 1.0);
+        }
+    }
+    assert!(error(&net) < 0.01 * initial, "{} >= {initial}", error(&net));
+}
@@ -0,0 +1,129 @@
+use rann_base::{
+    activ::Logistic,
+    error::SquareError,
+    reduce::{self, ElementwiseSum, Summation},
+    testing,
+    train::{self, Trainer},
+    Full,
+};
+use rann_traits::{params::Parameterized, Network};
+
+fn values(len: usize, seed: u64) -> Vec<f32> {
+    let mut rng = fastrand::Rng::with_seed(seed);
+    (0..len).map(|_| rng.f32() * 2.0 - 1.0).collect()
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn parallel_sum_is_identical_to_sequential_sum() {
+    for len in [0, 1, reduce::BLOCK, reduce::BLOCK + 1, 1000, 100_003] {
+        let values = values(len, len as u64);
+        let sequential = reduce::pairwise_sum(&values);
+        for threads in [1, 2, 7] {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            let parallel = pool.install(|| reduce::par_pairwise_sum(&values));
+            assert_eq!(sequential.to_bits(), parallel.to_bits());
+        }
+    }
+}
+
+#[test]
+fn pairwise_sum_is_more_accurate_than_naive_sum() {
+    let values = vec![0.1f32; 1_000_000];
+    let naive: f32 = values.iter().sum();
+    let pairwise = reduce::pairwise_sum(&values);
+    assert!((pairwise - 100_000.0).abs() < (naive - 100_000.0).abs());
+    assert!((pairwise - 100_000.0).abs() < 1.0);
+}
+
+#[test]
+fn gradient_sums_are_deterministic() {
+    let gradients: Vec<_> = (0..500).map(|i| values(10, i)).collect();
+    let sequential = reduce::sum_gradients(&gradients);
+    #[cfg(feature = "rayon")]
+    assert_eq!(sequential, reduce::par_sum_gradients(&gradients));
+    for (j, sum) in sequential.iter().enumerate() {
+        let expected: f32 = gradients.iter().map(|g| g[j]).sum();
+        assert!((sum - expected).abs() < 1e-3);
+    }
+    assert!(reduce::sum_gradients(&[]).is_empty());
+}
+
+#[test]
+#[should_panic]
+fn gradients_of_different_lengths_panic() {
+    reduce::sum_gradients(&[vec![1.0], vec![1.0, 2.0]]);
+}
+
+#[test]
+fn compensated_sum_does_not_drift() {
+    let values = || std::iter::repeat_n(0.1f32, 10_000_000);
+    let naive = Summation::Naive.sum(values());
+    let compensated = Summation::Compensated.sum(values());
+    assert!((naive - 1_000_000.0).abs() > 1000.0);
+    assert_eq!(compensated, 1_000_000.0);
+    // Cancellation of large values does not lose the small ones.
+    assert_eq!(Summation::Compensated.sum([1.0, 1e10, 1.0, -1e10]), 2.0);
+}
+
+#[test]
+fn trainer_sums_compensated() {
+    let new = || {
+        Full::<2, 1, _>::new(Logistic, testing::seeded_gen(3))
+            .chain(SquareError { expected: [0.0] })
+    };
+    let (mut net, mut compensated) = (new(), new());
+    let trainer = Trainer {
+        epochs: 5,
+        ..Default::default()
+    };
+    let naive = trainer.fit(&mut net, &testing::XOR);
+    let fit = Trainer {
+        summation: Summation::Compensated,
+        ..trainer
+    }
+    .fit(&mut compensated, &testing::XOR);
+    for (a, b) in naive.errors.iter().zip(&fit.errors) {
+        assert!((a - b).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn compensated_batches_match_naive_batches() {
+    let (mut naive, mut compensated) = (testing::xor_net(1), testing::xor_net(1));
+    for _ in 0..10 {
+        let a = train::train_epoch_batched(&mut naive, &testing::XOR, 3, 0.5);
+        let b = train::train_epoch_batched_with(
+            &mut compensated,
+            &testing::XOR,
+            3,
+            0.5,
+            Summation::Compensated,
+        );
+        assert!((a - b).abs() < 1e-6);
+    }
+    for (a, b) in naive.params().iter().zip(compensated.params()) {
+        assert!((a - b).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn elementwise_sums_do_not_drift() {
+    let mut naive = ElementwiseSum::new(Summation::Naive, 2);
+    let mut compensated = ElementwiseSum::new(Summation::Compensated, 2);
+    for _ in 0..1_000_000 {
+        naive.add([0.1, -0.1]);
+        compensated.add([0.1, -0.1]);
+    }
+    assert!((naive.to_vec()[0] - 100_000.0).abs() > 100.0);
+    assert_eq!(compensated.to_vec(), [100_000.0, -100_000.0]);
+}
+
+#[test]
+#[should_panic]
+fn elementwise_sums_of_different_lengths_panic() {
+    ElementwiseSum::new(Summation::Compensated, 2).add([1.0]);
+}
@@ -0,0 +1,59 @@
+use rann_base::error::{BatchLoss, CrossEntropy, ReduceMean, ReduceNone, ReduceSum, SquareError};
+use rann_traits::{inspect::Inspect, params::Parameterized, Network, Supervised, Terminal};
+
+#[test]
+fn mean_losses_are_comparable_across_batch_sizes() {
+    let mut small = BatchLoss::<_, _, 2>::new(SquareError { expected: [0.0; 2] }, ReduceMean);
+    let mut large = BatchLoss::<_, _, 4>::new(SquareError { expected: [0.0; 2] }, ReduceMean);
+    small.set_target(&[[1.0, 0.0], [0.0, 1.0]]);
+    large.set_target(&[[1.0, 0.0], [0.0, 1.0], [1.0, 0.0], [0.0, 1.0]]);
+    let inputs = [[0.5, 0.5], [0.0, 0.0]];
+    assert_eq!(
+        small.eval(&inputs),
+        large.eval(&[inputs[0], inputs[1], inputs[0], inputs[1]])
+    );
+
+    let mut sum = BatchLoss::<_, _, 2>::new(SquareError { expected: [0.0; 2] }, ReduceSum);
+    sum.set_target(&[[1.0, 0.0], [0.0, 1.0]]);
+    assert_eq!(sum.eval(&inputs), [2.0 * small.eval(&inputs)[0]]);
+}
+
+#[test]
+fn gradients_are_scaled_by_the_reduction() {
+    let mut single = SquareError { expected: [0.0; 2] };
+    single.set_target(&[1.0, 0.0]);
+    let expected = single.gradient(&[0.5, 0.5], &[1.0]);
+
+    let mut mean = BatchLoss::<_, _, 4>::new(single.clone(), ReduceMean);
+    let inputs = [[0.5, 0.5]; 4];
+    let inter = mean.intermediate(&inputs);
+    for gradients in mean.train_error(&inputs, &inter, 0.1) {
+        assert_eq!(gradients, expected.map(|g| g / 4.0));
+    }
+
+    let mut sum = BatchLoss::<_, _, 4>::new(single, ReduceSum);
+    let inter = sum.intermediate(&inputs);
+    assert_eq!(sum.train_error(&inputs, &inter, 0.1), [expected; 4]);
+}
+
+#[test]
+fn per_sample_losses_are_kept() {
+    let mut loss = BatchLoss::<_, _, 3>::new(CrossEntropy::<2>::new(), ReduceMean);
+    loss.set_target(&[[1.0, 0.0], [0.0, 1.0], [1.0, 0.0]]);
+    let inputs = [[2.0, 0.0], [2.0, 0.0], [0.0, 0.0]];
+    let inter = loss.intermediate(&inputs);
+    let losses = inter.losses();
+    // The second sample is the hardest.
+    assert!(losses[1] > losses[2] && losses[2] > losses[0]);
+    assert!((inter.output[0] - losses.iter().sum::<f32>() / 3.0).abs() < 1e-6);
+    assert_eq!(loss.layer_stats(&inter)[0].max_activation, losses[1]);
+    assert_eq!(loss.num_params(), 0);
+
+    let mut unreduced = BatchLoss::<_, _, 3>::new(CrossEntropy::<2>::new(), ReduceNone);
+    unreduced.losses = loss.losses.clone();
+    assert_eq!(unreduced.eval(&inputs), losses);
+    // Only the samples with a gradient are trained on.
+    let gradients = unreduced.gradient(&inputs, &[0.0, 1.0, 0.0]);
+    assert_eq!(gradients[0], [0.0, 0.0]);
+    assert!(gradients[1][0] > 0.0);
+}
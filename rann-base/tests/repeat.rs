@@ -0,0 +1,56 @@
+use rann_base::{activ::Logistic, testing, Full};
+use rann_traits::{
+    compose::Repeat, inspect::Inspect, params::Parameterized, Intermediate, Network,
+};
+
+fn layer(i: usize) -> Full<3, 3, Logistic> {
+    Full::new(Logistic, testing::seeded_gen(i as u64))
+}
+
+#[test]
+fn matches_chain() {
+    let mut chain = layer(0).chain(layer(1)).chain(layer(2));
+    let mut repeat = Repeat::<_, 3>::new(layer);
+    assert_eq!(repeat.params(), chain.params());
+
+    let inputs = [1.0, -0.5, 0.25];
+    for _ in 0..10 {
+        let c_inter = chain.intermediate(&inputs);
+        let r_inter = repeat.intermediate(&inputs);
+        assert_eq!(c_inter.output(), r_inter.output());
+        assert_eq!(chain.layer_stats(&c_inter), repeat.layer_stats(&r_inter));
+
+        let grads = [1.0, 0.0, -1.0];
+        let c_grads = chain.train_deriv(&inputs, &c_inter, &grads, 0.5);
+        let r_grads = repeat.train_deriv(&inputs, &r_inter, &grads, 0.5);
+        assert_eq!(c_grads, r_grads);
+    }
+    assert_eq!(repeat.params(), chain.params());
+}
+
+#[test]
+fn layers_are_independent() {
+    let mut repeat = Repeat::<_, 4>::new(|_| layer(0));
+    repeat[2].read_params(&[0.0; 12]);
+    for (i, layer) in repeat.iter().enumerate() {
+        assert_eq!(layer.params() == [0.0; 12], i == 2);
+    }
+
+    let params: Vec<f32> = (0..repeat.num_params()).map(|i| i as f32).collect();
+    repeat.read_params(&params);
+    assert_eq!(repeat.params(), params);
+    assert_eq!(repeat[1].params(), params[12..24]);
+}
+
+#[test]
+fn finds_named_layers() {
+    let repeat = Repeat::<_, 3>::new(|i| layer(i).named(format!("layer {i}")));
+    let second = repeat.layer::<Full<3, 3, Logistic>>("layer 1").unwrap();
+    assert_eq!(second.params(), layer(1).params());
+}
+
+#[test]
+#[should_panic]
+fn rejects_empty() {
+    Repeat::<Full<3, 3, Logistic>, 0>::from_layers([]);
+}
@@ -0,0 +1,143 @@
+use fastrand::Rng;
+use rann_base::{
+    activ::LeakyRelu,
+    rl::{self, Policy, ReplayBuffer, Step, TargetNetwork},
+    Full,
+};
+use rann_traits::Network;
+
+// A pole balanced on a cart, which can be pushed left or right. The episode ends when the pole
+// falls over, the cart leaves the track, or after `MAX_STEPS` steps.
+struct CartPole {
+    x: f32,
+    dx: f32,
+    theta: f32,
+    dtheta: f32,
+}
+
+const MAX_STEPS: usize = 200;
+
+impl CartPole {
+    fn new(rng: &mut Rng) -> Self {
+        let mut init = || (rng.f32() - 0.5) * 0.1;
+        Self {
+            x: init(),
+            dx: init(),
+            theta: init(),
+            dtheta: init(),
+        }
+    }
+
+    fn state(&self) -> [f32; 4] {
+        [self.x, self.dx, self.theta, self.dtheta]
+    }
+
+    // Applies `action` and returns whether the pole is still balanced.
+    fn step(&mut self, action: usize) -> bool {
+        const GRAVITY: f32 = 9.8;
+        const CART_MASS: f32 = 1.0;
+        const POLE_MASS: f32 = 0.1;
+        const HALF_LEN: f32 = 0.5;
+        const TAU: f32 = 0.02;
+        let total = CART_MASS + POLE_MASS;
+        let force = if action == 1 { 10.0 } else { -10.0 };
+        let (sin, cos) = self.theta.sin_cos();
+        let temp = (force + POLE_MASS * HALF_LEN * self.dtheta * self.dtheta * sin) / total;
+        let ddtheta =
+            (GRAVITY * sin - cos * temp) / (HALF_LEN * (4.0 / 3.0 - POLE_MASS * cos * cos / total));
+        let ddx = temp - POLE_MASS * HALF_LEN * ddtheta * cos / total;
+        self.x += TAU * self.dx;
+        self.dx += TAU * ddx;
+        self.theta += TAU * self.dtheta;
+        self.dtheta += TAU * ddtheta;
+        self.x.abs() < 2.4 && self.theta.abs() < 0.21
+    }
+}
+
+fn episode<T: Network<In = [f32; 4], Out = [f32; 2]>>(
+    policy: &Policy<T, 2>,
+    rng: &mut Rng,
+) -> Vec<Step<[f32; 4]>> {
+    let mut env = CartPole::new(rng);
+    let mut steps = Vec::new();
+    while steps.len() < MAX_STEPS {
+        let state = env.state();
+        let action = policy.act(&state, rng);
+        let balanced = env.step(action);
+        steps.push(Step {
+            state,
+            action,
+            reward: 1.0,
+        });
+        if !balanced {
+            break;
+        }
+    }
+    steps
+}
+
+#[test]
+fn discounted_returns() {
+    let returns = rl::discounted_returns(&[1.0, 0.0, 2.0], 0.5);
+    assert_eq!(returns, [1.5, 1.0, 2.0]);
+}
+
+#[test]
+fn learns_cart_pole() {
+    // A linear policy, starting with uniform action probabilities.
+    let net = Full::<4, 2, _>::new(LeakyRelu(1.0), (|_, _| 0.0, |_| 0.0));
+    let mut policy = Policy::new(net, 0.99);
+    let mut rng = Rng::with_seed(1);
+    let mut lengths = Vec::new();
+    for _ in 0..1000 {
+        let steps = episode(&policy, &mut rng);
+        lengths.push(steps.len());
+        policy.reinforce(&steps, 0.003);
+    }
+    let mean = |lengths: &[usize]| lengths.iter().sum::<usize>() as f32 / lengths.len() as f32;
+    let (first, last) = (mean(&lengths[..50]), mean(&lengths[950..]));
+    assert!(
+        last > 2.0 * first && last > 100.0,
+        "Mean episode length went from {first} to {last}."
+    );
+}
+
+#[test]
+fn replay_buffer_overwrites_oldest() {
+    let mut buffer = ReplayBuffer::new(3);
+    assert!(buffer.is_empty());
+    for i in 0..3 {
+        assert_eq!(buffer.push(i), None);
+    }
+    assert_eq!(buffer.push(3), Some(0));
+    assert_eq!(buffer.push(4), Some(1));
+    assert_eq!(buffer.len(), 3);
+    assert_eq!(buffer.iter().copied().collect::<Vec<_>>(), [2, 3, 4]);
+
+    let mut rng = Rng::with_seed(1);
+    let batch = buffer.sample(100, &mut rng);
+    assert_eq!(batch.len(), 100);
+    assert!(batch.iter().all(|&&i| (2..5).contains(&i)));
+
+    buffer.clear();
+    assert!(buffer.sample(10, &mut rng).is_empty());
+}
+
+#[test]
+fn target_network_syncs_periodically() {
+    let mut online = Full::<1, 1, _>::new(LeakyRelu(1.0), (|_, _| 1.0, |_| 0.0));
+    let mut target = TargetNetwork::new(&online, 3);
+    let mut syncs = Vec::new();
+    for step in 0..9 {
+        let inter = online.intermediate(&[1.0]);
+        online.train_deriv(&[1.0], &inter, &[1.0], 0.1);
+        if target.step(&online) {
+            syncs.push(step);
+        }
+        assert_eq!(
+            target.net().eval(&[1.0]) == online.eval(&[1.0]),
+            syncs.last() == Some(&step)
+        );
+    }
+    assert_eq!(syncs, [2, 5, 8]);
+}
@@ -0,0 +1,85 @@
+use fastrand::Rng;
+use rann_base::{
+    activ::softmax,
+    sample::{self, Sampler},
+};
+
+fn assert_close(a: &[f32], b: &[f32]) {
+    for (a, b) in a.iter().zip(b) {
+        assert!((a - b).abs() < 1e-6, "{a} != {b}");
+    }
+}
+
+#[test]
+fn temperature_sharpens_and_flattens() {
+    let logits = [1.0, 2.0, 0.0];
+    let probs = sample::softmax_with_temperature(&logits, 1.0);
+    assert_close(&probs, &softmax(&logits));
+    let sharp = sample::softmax_with_temperature(&logits, 0.1);
+    let flat = sample::softmax_with_temperature(&logits, 100.0);
+    assert!(sharp[1] > probs[1] && probs[1] > flat[1]);
+    assert!(sharp[1] > 0.99);
+    assert!(flat.iter().all(|p| (p - 1.0 / 3.0).abs() < 0.01));
+}
+
+#[test]
+fn top_k_keeps_the_largest() {
+    let mut probs = [0.1, 0.4, 0.2, 0.3];
+    sample::top_k(&mut probs, 2);
+    assert_close(&probs, &[0.0, 4.0 / 7.0, 0.0, 3.0 / 7.0]);
+    let mut probs = [0.5, 0.5];
+    sample::top_k(&mut probs, 1);
+    assert_eq!(probs, [1.0, 0.0]);
+}
+
+#[test]
+fn top_p_keeps_the_nucleus() {
+    let mut probs = [0.1, 0.4, 0.2, 0.3];
+    sample::top_p(&mut probs, 0.6);
+    assert_close(&probs, &[0.0, 4.0 / 7.0, 0.0, 3.0 / 7.0]);
+    let mut probs = [0.1, 0.9];
+    sample::top_p(&mut probs, 0.5);
+    assert_eq!(probs, [0.0, 1.0]);
+    let mut probs = [0.1, 0.4, 0.2, 0.3];
+    sample::top_p(&mut probs, 1.0);
+    assert_close(&probs, &[0.1, 0.4, 0.2, 0.3]);
+}
+
+#[test]
+fn samplers_are_seeded_and_filtered() {
+    let logits = [0.5, 1.0, 0.0, 1.5];
+    let draw = |mut sampler: Sampler| {
+        (0..100)
+            .map(|_| sampler.sample(&logits))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(draw(Sampler::new(3)), draw(Sampler::new(3)));
+    assert!(draw(Sampler::new(3).with_top_k(2))
+        .iter()
+        .all(|&i| i == 1 || i == 3));
+    assert!(draw(Sampler::new(3).with_top_p(0.01))
+        .iter()
+        .all(|&i| i == 3));
+
+    // All indices are drawn roughly as often as their probabilities.
+    let mut sampler = Sampler::new(5).with_temperature(2.0);
+    let probs = sampler.probs(&logits);
+    let mut counts = [0; 4];
+    for _ in 0..10_000 {
+        counts[sampler.sample(&logits)] += 1;
+    }
+    for (count, p) in counts.iter().zip(probs) {
+        assert!((*count as f32 / 10_000.0 - p).abs() < 0.02);
+    }
+}
+
+#[test]
+fn draw_follows_probs() {
+    let mut rng = Rng::with_seed(1);
+    let counts = (0..10_000).fold([0; 3], |mut counts, _| {
+        counts[sample::draw(&[0.2, 0.0, 0.8], &mut rng)] += 1;
+        counts
+    });
+    assert_eq!(counts[1], 0);
+    assert!((1800..2200).contains(&counts[0]), "{counts:?}");
+}
@@ -0,0 +1,41 @@
+#![cfg(feature = "rayon")]
+
+use rann_base::{activ::LeakyRelu, error::SquareError, search, testing, train, Full};
+use rann_traits::Network;
+
+// Trains a small regression network with the given activation slope.
+fn run(trial: &search::Trial<f32>, rng: &mut fastrand::Rng) -> f32 {
+    let gen = testing::seeded_gen(rng.u64(..));
+    let activation = LeakyRelu(trial.params);
+    let mut net = Full::<1, 4, _>::new(activation, gen.clone())
+        .chain(Full::<4, 1, _>::new(activation, gen))
+        .chain(SquareError { expected: [0.0] });
+    let dataset = [([0.0], [0.0]), ([0.5], [0.25]), ([1.0], [1.0])];
+    for _ in 0..50 {
+        train::train_epoch(&mut net, &dataset, trial.learning_rate);
+    }
+    train::mean_error(&mut net, &dataset)
+}
+
+#[test]
+fn search_is_reproducible_and_ordered() {
+    let trials = search::grid(&[0.0, 0.1, 0.5], &[0.01, 0.1], 0x5eed);
+    assert_eq!(trials.len(), 6);
+
+    let results = search::search(trials.clone(), run);
+    assert_eq!(results.len(), 6);
+    // Diverged trials have a NaN error and are ordered last.
+    let errors: Vec<f32> = results
+        .iter()
+        .map(|r| r.validation_error)
+        .map(|e| if e.is_nan() { f32::INFINITY } else { e })
+        .collect();
+    assert!(errors.windows(2).all(|w| w[0] <= w[1]), "{errors:?}");
+
+    // Every trial has its own seeded generator, so the results are the same every run.
+    let again = search::search(trials, run);
+    for (a, b) in results.iter().zip(&again) {
+        assert_eq!(a.trial, b.trial);
+        assert_eq!(a.validation_error.to_bits(), b.validation_error.to_bits());
+    }
+}
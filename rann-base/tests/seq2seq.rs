@@ -0,0 +1,154 @@
+use rann_base::{
+    activ::{LeakyRelu, Tanh},
+    recurrent::{Gru, Recurrent},
+    seq2seq::EncoderDecoder,
+    testing, Full,
+};
+use rann_traits::{params::Parameterized, Intermediate, Network, Scalar};
+
+// Generates weights and biases in `[-0.5, 0.5)`, such that the gates do not saturate.
+fn small_gen(
+    seed: u64,
+) -> (
+    impl FnMut(usize, usize) -> Scalar,
+    impl FnMut(usize) -> Scalar,
+) {
+    let (mut weights, mut biases) = testing::seeded_gen(seed);
+    (move |i, j| weights(i, j) / 4.0, move |i| biases(i) / 4.0)
+}
+
+type Net = EncoderDecoder<Gru<1, 3>, Gru<2, 3>, Full<3, 2, LeakyRelu>, 3, 2>;
+
+fn net(seed: u64) -> Net {
+    EncoderDecoder::new(
+        Recurrent::new(Gru::new(small_gen(seed))),
+        Recurrent::new(Gru::new(small_gen(seed + 1))),
+        Full::new(LeakyRelu(1.0), small_gen(seed + 2)),
+    )
+}
+
+#[test]
+fn gradients_match_finite_differences() {
+    let net = net(1);
+    let source = [[0.5], [-1.0], [0.3]];
+    let teacher = [[1.0, -0.5], [0.2, 0.8]];
+    let weights = [[0.4, -1.0], [0.7, 0.3]];
+    let error = |source: &[[Scalar; 1]; 3], teacher: &[[Scalar; 2]; 2]| -> Scalar {
+        let outputs = net.eval(&(*source, *teacher));
+        outputs
+            .as_flattened()
+            .iter()
+            .zip(weights.as_flattened())
+            .map(|(o, w)| o * w)
+            .sum()
+    };
+    let (source_grads, teacher_grads) = net.gradient(&(source, teacher), &weights);
+    const H: Scalar = 1e-2;
+    for i in 0..3 {
+        let (mut above, mut below) = (source, source);
+        above[i][0] += H;
+        below[i][0] -= H;
+        let numeric = (error(&above, &teacher) - error(&below, &teacher)) / (2.0 * H);
+        assert!(
+            (numeric - source_grads[i][0]).abs() < 1e-3,
+            "{numeric} != {}",
+            source_grads[i][0]
+        );
+    }
+    for (t, j) in [(0, 0), (0, 1), (1, 0), (1, 1)] {
+        let (mut above, mut below) = (teacher, teacher);
+        above[t][j] += H;
+        below[t][j] -= H;
+        let numeric = (error(&source, &above) - error(&source, &below)) / (2.0 * H);
+        assert!(
+            (numeric - teacher_grads[t][j]).abs() < 1e-3,
+            "{numeric} != {}",
+            teacher_grads[t][j]
+        );
+    }
+    // The last step of the teacher sequence is not the input of any step.
+    assert_eq!(teacher_grads[1], [0.0; 2]);
+}
+
+#[test]
+fn free_running_generation_takes_its_own_outputs() {
+    let net = net(4);
+    let source = [[1.0], [0.0], [-1.0]];
+    let generated = net.generate(&source);
+    // Forcing the generated outputs decodes the same outputs.
+    assert_eq!(net.eval(&(source, generated)), generated);
+
+    let mut steps = Vec::new();
+    let mapped = net.generate_with(&source, |outputs| {
+        steps.push(*outputs);
+        [1.0, 0.0]
+    });
+    assert_eq!(steps, mapped);
+    assert_eq!(net.eval(&(source, [[1.0, 0.0]; 2])), mapped);
+}
+
+#[test]
+fn params_round_trip() {
+    let net = net(7);
+    assert_eq!(
+        net.num_params(),
+        net.encoder.num_params() + net.decoder.num_params() + 3 * 2 + 2
+    );
+    let mut other = self::net(10);
+    other.read_params(&net.params());
+    let source = [[0.2], [0.4], [-0.6]];
+    assert_eq!(other.generate(&source), net.generate(&source));
+}
+
+#[test]
+fn sequences_are_reversed() {
+    // Decodes the source of three steps in reverse, as half the source.
+    let sources: Vec<[[Scalar; 1]; 3]> = (0..8)
+        .map(|i| std::array::from_fn(|t| [if i >> t & 1 == 1 { 1.0 } else { -1.0 }]))
+        .collect();
+    let target =
+        |source: &[[Scalar; 1]; 3]| std::array::from_fn::<_, 3, _>(|t| [source[2 - t][0] / 2.0]);
+    let mut net = EncoderDecoder::new(
+        Recurrent::<_, 3>::new(Gru::<1, 6>::new(small_gen(11))),
+        Recurrent::<_, 3>::new(Gru::<1, 6>::new(small_gen(12))),
+        Full::<6, 1, _>::new(Tanh, small_gen(13)),
+    );
+    let error = |net: &EncoderDecoder<_, _, _, 3, 3>, generate: bool| -> Scalar {
+        sources
+            .iter()
+            .map(|source| {
+                let target = target(source);
+                let outputs = if generate {
+                    net.generate(source)
+                } else {
+                    net.eval(&(*source, target))
+                };
+                outputs
+                    .iter()
+                    .zip(target)
+                    .map(|(o, t)| (o[0] - t[0]).powi(2))
+                    .sum::<Scalar>()
+            })
+            .sum()
+    };
+    let initial = error(&net, true);
+    for _ in 0..2000 {
+        for source in &sources {
+            let target = target(source);
+            let inter = net.intermediate(&(*source, target));
+            let gradients = std::array::from_fn(|t| [inter.output()[t][0] - target[t][0]]);
+            net.train_deriv(&(*source, target), &inter, &gradients, 0.1);
+        }
+    }
+    assert!(
+        error(&net, false) < 0.05 * initial,
+        "{} >= {initial}",
+        error(&net, false)
+    );
+    // Trained with teacher forcing, the network also decodes freely.
+    assert!(
+        error(&net, true) < 0.05 * initial,
+        "{} >= {initial}",
+        error(&net, true)
+    );
+}
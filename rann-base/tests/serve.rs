@@ -0,0 +1,126 @@
+#![cfg(feature = "serve")]
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    thread,
+};
+
+use rann_base::{activ::Logistic, serve, testing, Full};
+use rann_traits::Network;
+
+fn net() -> Full<2, 1, Logistic> {
+    Full::new(Logistic, testing::seeded_gen(1))
+}
+
+#[test]
+fn parses_and_formats_arrays() {
+    assert_eq!(
+        serve::parse_array(" [1, -0.5,2e-3] "),
+        Ok(vec![1.0, -0.5, 2e-3])
+    );
+    assert_eq!(serve::parse_array("[]"), Ok(vec![]));
+    assert!(serve::parse_array("1, 2").is_err());
+    assert!(serve::parse_array("[1, x]").is_err());
+    assert!(serve::parse_array("[NaN]").is_err());
+    assert_eq!(
+        serve::format_array(&[1.0, f32::NAN, -0.25]),
+        "[1, null, -0.25]"
+    );
+}
+
+#[test]
+fn answers_lines_over_tcp() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        serve::handle_stream(&net(), stream).unwrap();
+    });
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut line = String::new();
+    for inputs in testing::XOR.map(|(inputs, _)| inputs) {
+        writeln!(stream, "{}", serve::format_array(&inputs)).unwrap();
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(serve::parse_array(&line), Ok(net().eval(&inputs).to_vec()));
+    }
+    writeln!(stream, "[1, 2, 3]").unwrap();
+    line.clear();
+    reader.read_line(&mut line).unwrap();
+    assert!(line.contains("expected 2 inputs, got 3"));
+
+    drop((stream, reader));
+    server.join().unwrap();
+}
+
+#[test]
+fn answers_connections_concurrently() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    // The server runs until the tests exit.
+    thread::spawn(move || serve::serve(&net(), &listener));
+
+    // An idle connection does not hold up the next one.
+    let idle = TcpStream::connect(addr).unwrap();
+    let mut stream = TcpStream::connect(addr).unwrap();
+    writeln!(stream, "[1, 0]").unwrap();
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line).unwrap();
+    assert_eq!(
+        serve::parse_array(&line),
+        Ok(net().eval(&[1.0, 0.0]).to_vec())
+    );
+    drop(idle);
+}
+
+#[test]
+fn answers_http_requests() {
+    let body = "[1, 0]";
+    let request = format!(
+        "POST / HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    );
+    let mut response = Vec::new();
+    serve::handle(&net(), request.as_bytes(), &mut response).unwrap();
+    let response = String::from_utf8(response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    let (_, body) = response.split_once("\r\n\r\n").unwrap();
+    assert_eq!(
+        serve::parse_array(body),
+        Ok(net().eval(&[1.0, 0.0]).to_vec())
+    );
+
+    let mut response = String::new();
+    let mut bad = Vec::new();
+    serve::handle(
+        &net(),
+        &b"POST / HTTP/1.1\r\nContent-Length: 3\r\n\r\nabc"[..],
+        &mut bad,
+    )
+    .unwrap();
+    bad.as_slice().read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+}
+
+#[test]
+fn escapes_error_messages() {
+    let mut response = Vec::new();
+    serve::handle(&net(), &b"[1, \"\\\x07]\n"[..], &mut response).unwrap();
+    assert_eq!(
+        String::from_utf8(response).unwrap().trim_end(),
+        r#"{"error": "invalid number \"\\\u0007"}"#
+    );
+}
+
+#[test]
+fn rejects_chunked_http_requests() {
+    let request = "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n6\r\n[1, 0]\r\n0\r\n\r\n";
+    let mut response = Vec::new();
+    serve::handle(&net(), request.as_bytes(), &mut response).unwrap();
+    let response = String::from_utf8(response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 501 Not Implemented\r\n"));
+    assert!(response.contains("Content-Length instead"));
+}
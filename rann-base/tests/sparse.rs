@@ -0,0 +1,42 @@
+use rann_base::{activ::Logistic, error::SquareError, testing, train, Full};
+use rann_traits::{params::Parameterized, Network};
+
+#[test]
+fn top_k_of_all_outputs_is_dense() {
+    let dense = Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1));
+    let mut sparse = dense.clone().with_top_k(3);
+    let mut dense = dense;
+    let inter = dense.intermediate(&[0.5, -1.0]);
+    let grads = [0.3, -0.1, 0.2];
+    let a = dense.train_deriv(&[0.5, -1.0], &inter, &grads, 0.1);
+    let b = sparse.train_deriv(&[0.5, -1.0], &inter, &grads, 0.1);
+    assert_eq!(a, b);
+    assert_eq!(dense.params(), sparse.params());
+}
+
+#[test]
+fn only_top_k_neurons_are_trained() {
+    let layer = Full::<2, 4, _>::new(Logistic, testing::seeded_gen(2)).with_top_k(2);
+    let before = layer.params();
+    let mut layer = layer;
+    let inter = layer.intermediate(&[1.0, 1.0]);
+    layer.train_deriv(&[1.0, 1.0], &inter, &[0.1, -2.0, 0.05, 1.0], 0.1);
+    // The biases follow the weights; only those of the second and fourth neuron changed.
+    let changed: Vec<bool> = (0..4)
+        .map(|n| before[8 + n] != layer.params()[8 + n])
+        .collect();
+    assert_eq!(changed, [false, true, false, true]);
+}
+
+#[test]
+fn sparse_backprop_still_learns() {
+    let mut net = Full::<2, 8, _>::new(Logistic, testing::seeded_gen(3))
+        .with_top_k(4)
+        .chain(Full::<8, 1, _>::new(Logistic, testing::seeded_gen(4)))
+        .chain(SquareError { expected: [0.0] });
+    let before = train::mean_error(&mut net, &testing::XOR);
+    for _ in 0..3000 {
+        train::train_epoch(&mut net, &testing::XOR, 0.5);
+    }
+    assert!(train::mean_error(&mut net, &testing::XOR) < before / 2.0);
+}
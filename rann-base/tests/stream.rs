@@ -0,0 +1,85 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use rann_base::{
+    reduce::Summation,
+    stream::{Prefetch, StreamingDataset},
+    testing,
+    train::{CancellationToken, Trainer},
+};
+use rann_traits::{params::Parameterized, LearningRate, Network};
+
+#[test]
+fn streaming_training_matches_training_on_slices() {
+    for summation in [Summation::Naive, Summation::Compensated] {
+        let trainer = Trainer {
+            epochs: 50,
+            learning_rate: LearningRate(0.5),
+            summation,
+            ..Default::default()
+        };
+        let (mut a, mut b) = (testing::xor_net(1), testing::xor_net(1));
+        let fit = trainer.fit(&mut a, &testing::XOR);
+        let dataset = StreamingDataset::new(|| testing::XOR.into_iter(), 1);
+        let streamed = trainer.fit_streaming(&mut b, &dataset);
+        assert_eq!(fit, streamed);
+        assert_eq!(a.params(), b.params());
+    }
+}
+
+#[test]
+fn streaming_training_can_be_cancelled() {
+    let cancel = CancellationToken::new();
+    let trainer = Trainer {
+        epochs: 10,
+        cancel: Some(cancel.clone()),
+        ..Default::default()
+    };
+    let dataset = StreamingDataset::new(|| testing::XOR.into_iter().cycle(), 0);
+    // Cancels during the sixth step.
+    let steps = AtomicUsize::new(0);
+    let mut net = testing::xor_net(1).hook(|_, _| {
+        if steps.fetch_add(1, Ordering::SeqCst) == 5 {
+            cancel.cancel();
+        }
+    });
+    let fit = trainer.fit_streaming(&mut net, &dataset);
+    assert!(fit.cancelled);
+    assert!(fit.errors.is_empty());
+    assert_eq!(fit.steps, 6);
+}
+
+#[test]
+fn prefetching_is_bounded() {
+    let produced = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&produced);
+    let items = (0..100).inspect(move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+    let mut prefetch = Prefetch::new(items, 4);
+    assert_eq!(prefetch.next(), Some(0));
+    thread::sleep(Duration::from_millis(50));
+    // The received item, the buffered items, and one waiting to be sent.
+    assert!(produced.load(Ordering::SeqCst) <= 1 + 4 + 1);
+    assert_eq!(prefetch.sum::<i32>(), (1..100).sum());
+}
+
+#[test]
+fn dropping_stops_the_worker() {
+    let mut prefetch = Prefetch::new(0.., 2);
+    assert_eq!(prefetch.next(), Some(0));
+    drop(prefetch);
+}
+
+#[test]
+#[should_panic(expected = "corrupt sample")]
+fn worker_panics_are_propagated() {
+    let items = (0..3).map(|i| if i == 2 { panic!("corrupt sample") } else { i });
+    Prefetch::new(items, 1).for_each(drop);
+}
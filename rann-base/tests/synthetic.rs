@@ -0,0 +1,102 @@
+use rann_base::{
+    activ::Logistic,
+    error::{CrossEntropy, SquareError},
+    synthetic, testing, Full,
+};
+use rann_traits::{Network, Scalar};
+
+#[test]
+fn datasets_are_reproducible() {
+    assert_eq!(
+        synthetic::spirals(50, 0.1, 1),
+        synthetic::spirals(50, 0.1, 1)
+    );
+    assert_ne!(
+        synthetic::spirals(50, 0.1, 1),
+        synthetic::spirals(50, 0.1, 2)
+    );
+    assert_eq!(synthetic::sine(10, 0.0, 3), synthetic::sine(10, 0.0, 3));
+    assert_eq!(
+        synthetic::blobs::<3, 4>(20, 0.5, 4),
+        synthetic::blobs::<3, 4>(20, 0.5, 4)
+    );
+}
+
+#[test]
+fn xor_without_noise_is_the_xor_function() {
+    let dataset = synthetic::xor(8, 0.0, 1);
+    assert_eq!(dataset.len(), 8);
+    assert_eq!(dataset[..4], testing::XOR[..]);
+    assert_eq!(dataset[4..], testing::XOR[..]);
+}
+
+#[test]
+fn classes_alternate() {
+    for (i, (_, [label])) in synthetic::moons(10, 0.1, 1).iter().enumerate() {
+        assert_eq!(*label, (i % 2) as Scalar);
+    }
+    for (i, (_, target)) in synthetic::blobs::<2, 3>(9, 0.1, 1).iter().enumerate() {
+        let mut expected = [0.0; 3];
+        expected[i % 3] = 1.0;
+        assert_eq!(*target, expected);
+    }
+}
+
+#[test]
+fn spirals_have_a_radius_of_at_most_one() {
+    for ([x, y], _) in synthetic::spirals(100, 0.0, 2) {
+        assert!((x * x + y * y).sqrt() <= 1.0 + 1e-6);
+    }
+}
+
+#[test]
+fn sine_without_noise() {
+    for ([x], [y]) in synthetic::sine(100, 0.0, 3) {
+        assert!((-std::f32::consts::PI..std::f32::consts::PI).contains(&x));
+        assert_eq!(y, x.sin());
+    }
+}
+
+#[test]
+fn blobs_cluster_around_their_centers() {
+    let dataset = synthetic::blobs::<2, 2>(200, 0.1, 5);
+    let mean = |class: usize| {
+        let samples: Vec<_> = dataset.iter().skip(class).step_by(2).collect();
+        let sum = samples
+            .iter()
+            .fold([0.0; 2], |acc, (x, _)| [acc[0] + x[0], acc[1] + x[1]]);
+        sum.map(|s| s / samples.len() as Scalar)
+    };
+    for class in 0..2 {
+        let center = mean(class);
+        for (x, _) in dataset.iter().skip(class).step_by(2) {
+            let dist = ((x[0] - center[0]).powi(2) + (x[1] - center[1]).powi(2)).sqrt();
+            assert!(dist < 1.0, "{x:?} should be close to {center:?}.");
+        }
+    }
+}
+
+#[test]
+fn converts_to_vectors() {
+    let dataset = synthetic::xor(4, 0.0, 1);
+    let vecs = synthetic::to_vecs(&dataset);
+    assert_eq!(vecs[1], (vec![0.0, 1.0], vec![1.0]));
+    assert_eq!(vecs.len(), 4);
+}
+
+#[test]
+fn networks_learn_noisy_xor() {
+    let dataset = synthetic::xor(100, 0.1, 6);
+    let mut net = Full::<2, 4, _>::new(Logistic, testing::seeded_gen(2))
+        .chain(Full::<4, 1, _>::new(Logistic, testing::seeded_gen(3)))
+        .chain(SquareError { expected: [0.0] });
+    testing::assert_network_converges(&mut net, &dataset, 500, 0.5, 0.05);
+}
+
+#[test]
+fn networks_learn_blobs() {
+    let linear = (|x: Scalar| x, |_: Scalar| 1.0);
+    let dataset = synthetic::blobs::<2, 3>(90, 0.3, 7);
+    let mut net = Full::<2, 3, _>::new(linear, testing::seeded_gen(4)).chain(CrossEntropy::new());
+    testing::assert_network_converges(&mut net, &dataset, 200, 0.05, 0.1);
+}
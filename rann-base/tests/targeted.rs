@@ -0,0 +1,45 @@
+use rann_base::{
+    activ::Logistic,
+    error::{self, SquareError, Target, TargetLengthError, TargetedLoss},
+    testing, train, Full,
+};
+use rann_traits::Network;
+
+#[test]
+fn targets_are_validated() {
+    let rows: Vec<Vec<f32>> = vec![vec![0.5], vec![0.1, 0.2, 0.3]];
+    let targets = error::targets::<3, _>(&rows).unwrap();
+    assert_eq!(targets, [Target::All(0.5), Target::Each([0.1, 0.2, 0.3])]);
+
+    let rows = [vec![0.1, 0.2]];
+    assert_eq!(
+        error::targets::<3, _>(&rows),
+        Err(TargetLengthError {
+            expected: 3,
+            found: 2
+        })
+    );
+}
+
+#[test]
+fn broadcast_regression() {
+    let gen = testing::seeded_gen(0x7);
+    let mut net = Full::<1, 3, _>::new(Logistic, gen.clone())
+        .chain(Full::<3, 2, _>::new(Logistic, gen))
+        .chain(TargetedLoss::<SquareError<2>>::square(0.0).unwrap());
+
+    // Both outputs should approximate the same scalar target.
+    let dataset = [([0.0], 0.2.into()), ([1.0], 0.8.into())];
+    for _ in 0..2000 {
+        train::train_epoch(&mut net, &dataset, 0.5);
+    }
+    for (inputs, target) in &dataset {
+        let out = net.first.eval(inputs);
+        for o in out {
+            assert!(
+                (o - target.to_array()[0]).abs() < 0.05,
+                "{o} should be close to {target:?}."
+            );
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use rann_base::{
+    activ::{Logistic, Tanh},
+    recurrent::{Gru, Recurrent},
+    testing, Full,
+};
+use rann_traits::{compose::TimeDistributed, params::Parameterized, Intermediate, Network, Scalar};
+
+#[test]
+fn steps_are_evaluated_independently() {
+    let net = TimeDistributed::<_, 3>::new(Full::<2, 2, _>::new(Logistic, testing::seeded_gen(1)));
+    let inputs = [[1.0, 0.0], [0.5, -0.5], [0.0, 2.0]];
+    let outputs = inputs.map(|inputs| net.network.eval(&inputs));
+    assert_eq!(net.eval(&inputs), outputs);
+    assert_eq!(net.intermediate(&inputs).into_output(), outputs);
+    let reversed = [inputs[2], inputs[1], inputs[0]];
+    assert_eq!(
+        net.eval_many(&[inputs, reversed]),
+        [outputs, [outputs[2], outputs[1], outputs[0]]]
+    );
+}
+
+#[test]
+fn every_step_trains_the_shared_network() {
+    let mut net =
+        TimeDistributed::<_, 2>::new(Full::<2, 1, _>::new(Logistic, testing::seeded_gen(2)));
+    let mut layer = net.network.clone();
+    let inputs = [[1.0, -1.0], [0.3, 0.6]];
+    let gradients = [[0.5], [-1.0]];
+    let inter = net.intermediate(&inputs);
+    let input_grads = net.train_deriv(&inputs, &inter, &gradients, 0.1);
+    // All steps are evaluated before the network is trained.
+    let inters = inputs.map(|inputs| layer.intermediate(&inputs));
+    for t in 0..2 {
+        assert_eq!(
+            layer.train_deriv(&inputs[t], &inters[t], &gradients[t], 0.1),
+            input_grads[t]
+        );
+    }
+    assert_eq!(net.params(), layer.params());
+}
+
+const T: usize = 6;
+
+// Every step should output half of the input of the step before it.
+const SEQUENCES: [[[Scalar; 1]; T]; 4] = [
+    [[1.0], [-1.0], [1.0], [1.0], [-1.0], [-1.0]],
+    [[-1.0], [-1.0], [1.0], [-1.0], [1.0], [1.0]],
+    [[1.0], [1.0], [-1.0], [1.0], [-1.0], [1.0]],
+    [[-1.0], [1.0], [1.0], [-1.0], [-1.0], [1.0]],
+];
+
+fn targets(inputs: &[[Scalar; 1]; T]) -> [[Scalar; 1]; T] {
+    std::array::from_fn(|t| [if t == 0 { 0.0 } else { inputs[t - 1][0] / 2.0 }])
+}
+
+fn error(net: &impl Network<In = [[Scalar; 1]; T], Out = [[Scalar; 1]; T]>) -> Scalar {
+    SEQUENCES
+        .iter()
+        .map(|inputs| {
+            let outputs = net.eval(inputs);
+            outputs
+                .iter()
+                .zip(targets(inputs))
+                .map(|(out, target)| (out[0] - target[0]).powi(2))
+                .sum::<Scalar>()
+        })
+        .sum()
+}
+
+#[test]
+fn dense_layers_read_out_recurrent_states() {
+    let mut net =
+        Recurrent::<_, T>::new(Gru::<1, 4>::new(testing::seeded_gen(3))).chain(TimeDistributed::<
+            _,
+            T,
+        >::new(
+            Full::<4, 1, _>::new(Tanh, testing::seeded_gen(4)),
+        ));
+    let initial = error(&net);
+    for _ in 0..1000 {
+        for inputs in &SEQUENCES {
+            let inter = net.intermediate(inputs);
+            let mut gradients = *inter.output();
+            for (grad, target) in gradients.iter_mut().zip(targets(inputs)) {
+                grad[0] -= target[0];
+            }
+            net.train_deriv(inputs, &inter, &gradients, 0.1);
+        }
+    }
+    assert!(error(&net) < 0.05 * initial, "{} >= {initial}", error(&net));
+}
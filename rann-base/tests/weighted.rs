@@ -0,0 +1,51 @@
+use rann_base::{
+    activ::Logistic,
+    error::{ClassWeighted, SquareError},
+    testing, train, Full,
+};
+use rann_traits::{Network, Supervised};
+
+#[test]
+fn error_and_gradients_are_weighted_by_the_target_class() {
+    let inputs = [0.25, 0.75, 0.5];
+    let mut plain = SquareError { expected: [0.0; 3] };
+    let mut weighted = ClassWeighted::new(plain.clone(), [1.0, 4.0, 0.5]);
+    for (target, weight) in [([0.0, 1.0, 0.0], 4.0), ([0.2, 0.0, 0.8], 0.6)] {
+        plain.set_target(&target);
+        weighted.set_target(&target);
+        assert_eq!(weighted.weight(), weight);
+
+        let inter = weighted.intermediate(&inputs);
+        assert_eq!(inter.output[0], plain.eval(&inputs)[0] * weight);
+        let gradients = weighted.train_deriv(&inputs, &inter, &[1.0], 0.1);
+        let expected = plain.gradient(&inputs, &[weight]);
+        assert_eq!(gradients, expected);
+    }
+}
+
+#[test]
+fn weights_favor_the_rare_class() {
+    // Three quarters of the samples belong to the first class, and the inputs do not tell them
+    // apart, so the network can only predict the class frequencies.
+    let dataset = [
+        ([1.0], [1.0, 0.0]),
+        ([1.0], [1.0, 0.0]),
+        ([1.0], [1.0, 0.0]),
+        ([1.0], [0.0, 1.0]),
+    ];
+    let new = || Full::<1, 2, _>::new(Logistic, testing::seeded_gen(5));
+    let mut plain = new().chain(SquareError { expected: [0.0; 2] });
+    let mut weighted = new().chain(ClassWeighted::new(
+        SquareError { expected: [0.0; 2] },
+        [1.0, 3.0],
+    ));
+    for _ in 0..2000 {
+        train::train_epoch(&mut plain, &dataset, 0.1);
+        train::train_epoch(&mut weighted, &dataset, 0.1);
+    }
+    let plain = plain.first.eval(&[1.0]);
+    let weighted = weighted.first.eval(&[1.0]);
+    assert!(plain[0] > plain[1]);
+    // Weighting balances the classes, up to the bias of training on the samples in order.
+    assert!((weighted[0] - weighted[1]).abs() < 0.1, "{weighted:?}");
+}
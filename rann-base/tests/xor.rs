@@ -1,5 +1,9 @@
 use fastrand::Rng;
-use rann_base::{activ::LeakyRelu, error::SumError, Full};
+use rann_base::{
+    activ::LeakyRelu,
+    error::{SquareError, SumError},
+    testing, Full,
+};
 use rann_traits::{Intermediate, Network};
 
 // Trains a neural network that approximates the XOR function, and tests if it doesn't diverge.
@@ -21,16 +25,7 @@ fn xor_divergence() {
     // The generator for the network values. For the test to be deterministic, we have to seed the
     // generator.
     let mut rng = Rng::with_seed(0x2);
-    let gen = (
-        {
-            let mut rng = rng.clone();
-            move |_, _| rng.f32() * 4.0 - 2.0
-        },
-        {
-            let mut rng = rng.clone();
-            move |_| rng.f32() * 4.0 - 2.0
-        },
-    );
+    let gen = testing::seeded_gen(0x2);
 
     // Initializes the neural network with 2 input, 3 hidden and 1 output layer.
     let mut net = Full::<2, 3, _>::new(activation, gen.clone())
@@ -94,3 +89,13 @@ fn xor_divergence() {
         );
     }
 }
+
+// Trains a neural network on the XOR dataset using the testing harness.
+#[test]
+fn xor_converges() {
+    let gen = testing::seeded_gen(0x2);
+    let mut net = Full::<2, 4, _>::new(LeakyRelu(0.1), gen.clone())
+        .chain(Full::<4, 1, _>::new(LeakyRelu(0.1), gen))
+        .chain(SquareError { expected: [0.0] });
+    testing::assert_network_converges(&mut net, &testing::XOR, 5000, 0.05, 0.01);
+}
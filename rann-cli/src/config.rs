@@ -0,0 +1,158 @@
+/*!
+Architecture and training configuration.
+
+A config file holds one `key = value` entry per line. Empty lines and everything after a `#` are
+ignored. The keys are:
+
+- `layers`: the comma separated sizes of the layers, starting with the number of inputs and
+  ending with the number of outputs. Required.
+- `activation`: the activation function of every layer: `logistic` (the default), `tanh`, `relu`
+  or `leaky_relu`.
+- `epochs`: the number of epochs to train for, 100 by default.
+- `learning_rate`: the learning rate, 0.1 by default.
+- `seed`: the seed of the initial parameters, 0 by default.
+
+# Examples
+```rust
+use rann_base::mlp::Activation;
+use rann_cli::Config;
+
+let config: Config = "
+    layers = 2, 3, 1 # XOR
+    activation = tanh
+    epochs = 2000
+"
+.parse()
+.unwrap();
+assert_eq!(config.layers, [2, 3, 1]);
+assert_eq!(config.activation, Activation::Tanh);
+assert_eq!(config.learning_rate, 0.1);
+```
+*/
+
+use std::{
+    fmt::{self, Display},
+    fs, io,
+    path::Path,
+    str::FromStr,
+};
+
+use rann_base::mlp::{Activation, Mlp, ParseActivationError};
+use rann_traits::Scalar;
+
+/// The architecture of a network and how to train it. See [module level documentation](self)
+/// for more info.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    /// The sizes of the layers, from the number of inputs to the number of outputs.
+    pub layers: Vec<usize>,
+    /// The activation function of every layer.
+    pub activation: Activation,
+    /// The number of epochs to train for.
+    pub epochs: usize,
+    /// The learning rate.
+    pub learning_rate: Scalar,
+    /// The seed of the initial parameters.
+    pub seed: u64,
+}
+
+impl Config {
+    /// Reads the config file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Returns the number of inputs of the network.
+    pub fn inputs(&self) -> usize {
+        self.layers[0]
+    }
+
+    /// Returns the number of outputs of the network.
+    pub fn outputs(&self) -> usize {
+        self.layers[self.layers.len() - 1]
+    }
+
+    /// Creates the network described by this config.
+    pub fn mlp(&self) -> Mlp {
+        Mlp::new(&self.layers, self.activation, self.seed)
+    }
+}
+
+impl FromStr for Config {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut layers = None;
+        let mut config = Self {
+            layers: Vec::new(),
+            activation: Activation::Logistic,
+            epochs: 100,
+            learning_rate: 0.1,
+            seed: 0,
+        };
+        for (i, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let at_line = |e: ConfigError| ConfigError::new(format!("line {}: {e}", i + 1));
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| at_line(ConfigError::new("expected `key = value`")))?;
+            let value = value.trim();
+            match key.trim() {
+                "layers" => {
+                    layers = Some(
+                        value
+                            .split(',')
+                            .map(|size| parse(size.trim()))
+                            .collect::<Result<_, _>>()
+                            .map_err(at_line)?,
+                    )
+                }
+                "activation" => {
+                    config.activation = value.parse().map_err(|e: ParseActivationError| {
+                        at_line(ConfigError::new(e.to_string()))
+                    })?
+                }
+                "epochs" => config.epochs = parse(value).map_err(at_line)?,
+                "learning_rate" => config.learning_rate = parse(value).map_err(at_line)?,
+                "seed" => config.seed = parse(value).map_err(at_line)?,
+                key => return Err(at_line(ConfigError::new(format!("unknown key {key:?}")))),
+            }
+        }
+        config.layers = layers.ok_or_else(|| ConfigError::new("missing `layers`"))?;
+        if config.layers.len() < 2 || config.layers.contains(&0) {
+            return Err(ConfigError::new(
+                "`layers` should hold at least two non-zero sizes",
+            ));
+        }
+        Ok(config)
+    }
+}
+
+fn parse<T: FromStr>(value: &str) -> Result<T, ConfigError> {
+    value
+        .parse()
+        .map_err(|_| ConfigError::new(format!("invalid value {value:?}")))
+}
+
+/// An invalid config file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigError(String);
+
+impl ConfigError {
+    fn new(message: impl Into<String>) -> Self {
+        Self(message.into())
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ConfigError {}
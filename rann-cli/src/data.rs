@@ -0,0 +1,68 @@
+/*!
+Reading datasets from CSV files.
+
+Every row of a dataset holds the inputs of a sample followed by its targets, as comma separated
+numbers. A first row that is not numeric is taken to be a header and skipped, as are empty rows.
+
+# Examples
+```rust
+use rann_cli::data;
+
+let dataset = data::parse_csv("x,y,xor\n0,0,0\n0,1,1\n", 2).unwrap();
+assert_eq!(dataset, [(vec![0.0, 0.0], vec![0.0]), (vec![0.0, 1.0], vec![1.0])]);
+```
+*/
+
+use std::{fs, io, path::Path};
+
+use rann_traits::Scalar;
+
+/// A dataset of inputs and targets with sizes known at runtime.
+pub type Dataset = Vec<(Vec<Scalar>, Vec<Scalar>)>;
+
+/// Parses a dataset from CSV `text`, where the first `inputs` columns of every row are the
+/// inputs and the remaining columns the targets.
+///
+/// # Errors
+/// Returns an error if a row has a number of columns different from the first row, has no
+/// targets, or holds a value that is not a number.
+pub fn parse_csv(text: &str, inputs: usize) -> Result<Dataset, String> {
+    let mut dataset = Dataset::new();
+    let mut columns = None;
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let row: Result<Vec<Scalar>, _> =
+            line.split(',').map(|value| value.trim().parse()).collect();
+        let row = match row {
+            Ok(row) => row,
+            Err(_) if i == 0 => continue,
+            Err(_) => return Err(format!("line {}: invalid number", i + 1)),
+        };
+        let columns = *columns.get_or_insert(row.len());
+        if row.len() != columns {
+            return Err(format!(
+                "line {}: expected {columns} columns, got {}",
+                i + 1,
+                row.len()
+            ));
+        }
+        if row.len() <= inputs {
+            return Err(format!(
+                "line {}: expected more than {inputs} columns, got {}",
+                i + 1,
+                row.len()
+            ));
+        }
+        let (inputs, targets) = row.split_at(inputs);
+        dataset.push((inputs.to_vec(), targets.to_vec()));
+    }
+    Ok(dataset)
+}
+
+/// Reads the dataset in the CSV file at `path`. See [`parse_csv()`].
+pub fn load_csv(path: impl AsRef<Path>, inputs: usize) -> io::Result<Dataset> {
+    parse_csv(&fs::read_to_string(path)?, inputs)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
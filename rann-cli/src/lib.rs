@@ -0,0 +1,20 @@
+/*!
+# Rann-cli
+
+A command line companion for quick experiments with multilayer perceptrons, without writing any
+Rust code. The `rann` binary reads a dataset from a CSV file and an architecture from a config
+file, trains the network, reports its metrics and saves it as a [model file](rann_base::model).
+
+```sh
+$ rann train --data xor.csv --config xor.conf --out xor.rann
+```
+
+The layers of RANN have their sizes fixed at compile time, so the binary trains an
+[`Mlp`](rann_base::mlp::Mlp) instead: a chain of fully connected layers with sizes read at runtime.
+See [`config`] for the format of the config file, and [`data`] for the format of the dataset.
+*/
+
+pub mod config;
+pub mod data;
+
+pub use config::Config;
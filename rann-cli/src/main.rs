@@ -0,0 +1,148 @@
+use std::{env, io, process::ExitCode, str::FromStr};
+
+use rann_base::mlp::{Metrics, Mlp, SquareLoss};
+use rann_base::{model, train::Trainer};
+use rann_cli::{data, Config};
+
+const USAGE: &str = "\
+Usage:
+  rann train --data <csv> --config <file> [--validation <csv>] [--out <model>]
+             [--epochs <n>] [--learning-rate <lr>]
+  rann eval --model <model> --data <csv>
+
+Trains a multilayer perceptron on a CSV dataset, or evaluates a trained model on one.
+See the documentation of rann-cli for the formats of datasets and config files.";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("train") => Options::parse(&args[1..]).and_then(|options| train(&options)),
+        Some("eval") => Options::parse(&args[1..]).and_then(|options| eval(&options)),
+        Some("help" | "--help" | "-h") => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        _ => Err(invalid("expected a command")),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[derive(Default)]
+struct Options {
+    data: Option<String>,
+    config: Option<String>,
+    validation: Option<String>,
+    out: Option<String>,
+    model: Option<String>,
+    epochs: Option<usize>,
+    learning_rate: Option<f32>,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> io::Result<Self> {
+        let mut options = Self::default();
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| invalid(format!("missing value for {flag}")))?
+                .clone();
+            match flag.as_str() {
+                "--data" => options.data = Some(value),
+                "--config" => options.config = Some(value),
+                "--validation" => options.validation = Some(value),
+                "--out" => options.out = Some(value),
+                "--model" => options.model = Some(value),
+                "--epochs" => options.epochs = Some(number(flag, &value)?),
+                "--learning-rate" => options.learning_rate = Some(number(flag, &value)?),
+                _ => return Err(invalid(format!("unknown option {flag}"))),
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn number<T: FromStr>(flag: &str, value: &str) -> io::Result<T> {
+    value
+        .parse()
+        .map_err(|_| invalid(format!("invalid value for {flag}: {value}")))
+}
+
+fn required<'a>(value: &'a Option<String>, flag: &str) -> io::Result<&'a str> {
+    value
+        .as_deref()
+        .ok_or_else(|| invalid(format!("missing {flag}")))
+}
+
+fn invalid(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message.into())
+}
+
+fn train(options: &Options) -> io::Result<()> {
+    let mut config = Config::load(required(&options.config, "--config")?)?;
+    config.epochs = options.epochs.unwrap_or(config.epochs);
+    config.learning_rate = options.learning_rate.unwrap_or(config.learning_rate);
+    let dataset = load(required(&options.data, "--data")?, &config.layers)?;
+    let validation = match &options.validation {
+        Some(path) => Some(load(path, &config.layers)?),
+        None => None,
+    };
+
+    let mut net = SquareLoss::new(config.mlp());
+    let trainer = Trainer {
+        epochs: config.epochs,
+        learning_rate: config.learning_rate.into(),
+        ..Default::default()
+    };
+    let fit = trainer.fit(&mut net, &dataset);
+    print_metrics("training", &net.metrics(&dataset));
+    if let Some(validation) = &validation {
+        print_metrics("validation", &net.metrics(validation));
+    }
+
+    if let Some(out) = &options.out {
+        let metadata = net
+            .mlp
+            .metadata("rann-cli")
+            .with_hyperparameter("seed", config.seed)
+            .with_training(&trainer, &fit);
+        model::save(&net.mlp, &metadata, out)?;
+        println!("saved model to {out}");
+    }
+    Ok(())
+}
+
+fn eval(options: &Options) -> io::Result<()> {
+    let (mlp, _) = Mlp::load(required(&options.model, "--model")?)?;
+    let dataset = load(required(&options.data, "--data")?, &mlp.sizes())?;
+    print_metrics("evaluation", &SquareLoss::new(mlp).metrics(&dataset));
+    Ok(())
+}
+
+// Loads a dataset, checking that it matches the sizes of the layers.
+fn load(path: &str, layers: &[usize]) -> io::Result<data::Dataset> {
+    let dataset = data::load_csv(path, layers[0])?;
+    let outputs = layers[layers.len() - 1];
+    match dataset.first() {
+        None => Err(invalid(format!("{path}: the dataset is empty"))),
+        Some((_, targets)) if targets.len() != outputs => Err(invalid(format!(
+            "{path}: expected {outputs} targets, got {}",
+            targets.len()
+        ))),
+        Some(_) => Ok(dataset),
+    }
+}
+
+fn print_metrics(name: &str, metrics: &Metrics) {
+    println!(
+        "{name}: error {:.6}, accuracy {:.2}%",
+        metrics.error,
+        metrics.accuracy * 100.0
+    );
+}
@@ -0,0 +1,132 @@
+use std::{fs, process::Command};
+
+use rann_base::mlp::Activation;
+use rann_cli::{data, Config};
+
+const XOR: &str = "a,b,xor\n0,0,0\n0,1,1\n1,0,1\n1,1,0\n";
+
+#[test]
+fn parses_configs() {
+    let config: Config = "layers=4,8,2\nactivation = relu # hidden\nlearning_rate=0.01\nseed=7"
+        .parse()
+        .unwrap();
+    assert_eq!(config.layers, [4, 8, 2]);
+    assert_eq!((config.inputs(), config.outputs()), (4, 2));
+    assert_eq!(config.activation, Activation::LeakyRelu(0.0));
+    assert_eq!(
+        (config.epochs, config.learning_rate, config.seed),
+        (100, 0.01, 7)
+    );
+
+    assert!("activation = tanh".parse::<Config>().is_err());
+    assert!("layers = 2".parse::<Config>().is_err());
+    let error = "layers = 2, 1\nepochs = many"
+        .parse::<Config>()
+        .unwrap_err();
+    assert_eq!(error.to_string(), "line 2: invalid value \"many\"");
+}
+
+#[test]
+fn parses_datasets() {
+    let dataset = data::parse_csv(XOR, 2).unwrap();
+    assert_eq!(dataset.len(), 4);
+    assert_eq!(dataset[1], (vec![0.0, 1.0], vec![1.0]));
+    assert!(data::parse_csv("0,0,0\n0,1\n", 2).is_err());
+    assert!(data::parse_csv("0,0\n", 2).is_err());
+    assert!(data::parse_csv("0,0,0\n0,x,1\n", 2).is_err());
+}
+
+#[test]
+fn trains_and_evaluates_from_the_command_line() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = |name| dir.path().join(name).to_str().unwrap().to_string();
+    fs::write(path("xor.csv"), XOR).unwrap();
+    fs::write(
+        path("xor.conf"),
+        "layers = 2, 3, 1\nepochs = 3000\nlearning_rate = 0.5\nseed = 1\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rann"))
+        .args([
+            "train",
+            "--data",
+            &path("xor.csv"),
+            "--config",
+            &path("xor.conf"),
+        ])
+        .args(["--out", &path("xor.rann")])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("training: error"), "{stdout}");
+    assert!(stdout.contains("accuracy 100.00%"), "{stdout}");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rann"))
+        .args([
+            "eval",
+            "--model",
+            &path("xor.rann"),
+            "--data",
+            &path("xor.csv"),
+        ])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("evaluation: error"), "{stdout}");
+    assert!(stdout.contains("accuracy 100.00%"), "{stdout}");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rann"))
+        .args(["train", "--data", &path("xor.csv")])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("missing --config"));
+}
+
+#[test]
+fn overrides_the_config_from_the_command_line() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = |name| dir.path().join(name).to_str().unwrap().to_string();
+    fs::write(path("xor.csv"), XOR).unwrap();
+    fs::write(
+        path("xor.conf"),
+        "layers = 2, 3, 1\nepochs = 1\nlearning_rate = 0.01\nseed = 1\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rann"))
+        .args([
+            "train",
+            "--data",
+            &path("xor.csv"),
+            "--config",
+            &path("xor.conf"),
+        ])
+        .args(["--epochs", "3000", "--learning-rate", "0.5"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("accuracy 100.00%"), "{stdout}");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_rann"))
+        .args([
+            "train",
+            "--data",
+            &path("xor.csv"),
+            "--config",
+            &path("xor.conf"),
+        ])
+        .args(["--learning-rate", "fast"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("invalid value for --learning-rate: fast"));
+}
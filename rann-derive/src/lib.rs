@@ -0,0 +1,219 @@
+/*!
+# Rann-derive
+
+Derive macros for the RANN ecosystem. These are re-exported by `rann-traits` when its `derive`
+feature is enabled, and should be used from there.
+
+# `#[derive(Network)]`
+
+Implements `Network` for a struct whose fields are networks, chained in declaration order. This
+gives architectures a name and named layers, instead of nested `Chain<Chain<…>>` types.
+
+An intermediate type named after the struct (e.g. `MlpInter` for `Mlp`) is generated alongside
+it, holding the intermediate of every field in a field of the same name. Its output is the output
+of the last field. Named layers (see `Network::named()`) are searched for in every field, in
+order.
+
+```rust
+use rann_base::{activ::Logistic, Full};
+use rann_traits::Network;
+
+#[derive(Network)]
+struct Mlp {
+    hidden: Full<2, 3, Logistic>,
+    output: Full<3, 1, Logistic>,
+}
+
+let gen = (|_, _| 0.5, |_| 0.0);
+let mut mlp = Mlp {
+    hidden: Full::new(Logistic, gen),
+    output: Full::new(Logistic, gen),
+};
+let inter: MlpInter = mlp.intermediate(&[1.0, 0.0]);
+mlp.train(&[1.0, 0.0], &inter, 0.1);
+```
+*/
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Error, Fields, Index, Member};
+
+/// Implements `Network` for a struct whose fields are networks, chained in declaration order.
+/// See [crate level documentation](crate) for more info.
+#[proc_macro_derive(Network)]
+pub fn derive_network(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    network(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn network(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new(
+            Span::call_site(),
+            "`Network` can only be derived for structs.",
+        ));
+    };
+    if data.fields.is_empty() {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "`Network` can only be derived for structs with at least one field.",
+        ));
+    }
+    let members: Vec<Member> = data
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(i)),
+        })
+        .collect();
+    let types: Vec<_> = data.fields.iter().map(|field| &field.ty).collect();
+    // Local variables holding the intermediate and the gradients of each field.
+    let inters: Vec<_> = (0..members.len())
+        .map(|i| format_ident!("__inter_{}", i))
+        .collect();
+    let grads: Vec<_> = (0..members.len())
+        .map(|i| format_ident!("__grad_{}", i))
+        .collect();
+
+    let name = &input.ident;
+    let vis = &input.vis;
+    let inter_name = format_ident!("{}Inter", name);
+    let network = quote!(::rann_traits::Network);
+    let intermediate = quote!(::rann_traits::Intermediate);
+
+    // Every field is a network taking the outputs of the previous field.
+    let mut generics = input.generics.clone();
+    let where_clause = generics.make_where_clause();
+    for ty in &types {
+        where_clause.predicates.push(parse_quote!(#ty: #network));
+    }
+    for pair in types.windows(2) {
+        let (prev, ty) = (pair[0], pair[1]);
+        where_clause
+            .predicates
+            .push(parse_quote!(#ty: #network<In = <#prev as #network>::Out>));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let first = types[0];
+    let last = *types.last().expect("There should be at least one field.");
+    let last_member = members.last().expect("There should be at least one field.");
+    // The where clause of tuple structs follows their fields.
+    let inter_struct = match &data.fields {
+        Fields::Named(_) => quote! {
+            #vis struct #inter_name #impl_generics #where_clause {
+                #(pub #members: <#types as #network>::Inter,)*
+            }
+        },
+        _ => quote! {
+            #vis struct #inter_name #impl_generics (
+                #(pub <#types as #network>::Inter,)*
+            ) #where_clause;
+        },
+    };
+    let inter_doc =
+        format!("The intermediate of a [`{name}`], holding the intermediate of each field.");
+
+    // The first field takes the inputs, and each following field the outputs of the previous.
+    let eval_inputs = std::iter::once(quote!(inputs))
+        .chain(
+            inters[..inters.len() - 1]
+                .iter()
+                .map(|inter| quote!(#intermediate::output(&#inter))),
+        )
+        .collect::<Vec<_>>();
+    // The last field takes the gradients, and each preceding field those of the next.
+    let train_grads = grads[1..]
+        .iter()
+        .map(|grad| quote!(&#grad))
+        .chain(std::iter::once(quote!(gradients)));
+    let train_inputs = std::iter::once(quote!(inputs)).chain(
+        members[..members.len() - 1]
+            .iter()
+            .map(|member| quote!(#intermediate::output(&intermediate.#member))),
+    );
+    let train = members
+        .iter()
+        .zip(&grads)
+        .zip(train_inputs.zip(train_grads))
+        .map(|((member, grad), (inputs, grads))| {
+            quote! {
+                let #grad = #network::train_deriv(
+                    &mut self.#member,
+                    #inputs,
+                    &intermediate.#member,
+                    #grads,
+                    learning_rate,
+                );
+            }
+        })
+        .collect::<Vec<_>>();
+    // Train from the last field to the first.
+    let train = train.iter().rev();
+    let first_grad = &grads[0];
+
+    Ok(quote! {
+        #[doc = #inter_doc]
+        #inter_struct
+
+        impl #impl_generics #intermediate for #inter_name #ty_generics #where_clause {
+            type Out = <#last as #network>::Out;
+
+            fn output(&self) -> &Self::Out {
+                #intermediate::output(&self.#last_member)
+            }
+
+            fn into_output(self) -> Self::Out {
+                #intermediate::into_output(self.#last_member)
+            }
+        }
+
+        impl #impl_generics #network for #name #ty_generics #where_clause {
+            type In = <#first as #network>::In;
+
+            type Out = <#last as #network>::Out;
+
+            type Inter = #inter_name #ty_generics;
+
+            fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+                #(let #inters = #network::intermediate(&self.#members, #eval_inputs);)*
+                #inter_name { #(#members: #inters,)* }
+            }
+
+            fn train_deriv(
+                &mut self,
+                inputs: &Self::In,
+                intermediate: &Self::Inter,
+                gradients: &Self::Out,
+                learning_rate: ::rann_traits::Scalar,
+            ) -> Self::In {
+                #(#train)*
+                #first_grad
+            }
+
+            fn find_layer(&self, name: &str) -> ::std::option::Option<&dyn ::std::any::Any> {
+                ::std::option::Option::None
+                    #(.or_else(|| #network::find_layer(&self.#members, name)))*
+            }
+
+            fn find_layer_mut(
+                &mut self,
+                name: &str,
+            ) -> ::std::option::Option<&mut dyn ::std::any::Any> {
+                #(
+                    if let ::std::option::Option::Some(layer) =
+                        #network::find_layer_mut(&mut self.#members, name)
+                    {
+                        return ::std::option::Option::Some(layer);
+                    }
+                )*
+                ::std::option::Option::None
+            }
+        }
+    })
+}
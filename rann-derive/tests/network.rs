@@ -0,0 +1,104 @@
+use rann_base::{
+    activ::{LeakyRelu, Logistic},
+    error::SquareError,
+    testing, Full,
+};
+use rann_traits::{Intermediate, Network};
+
+#[derive(Network)]
+struct Mlp {
+    hidden: Full<2, 4, LeakyRelu>,
+    output: Full<4, 1, Logistic>,
+}
+
+#[derive(Network)]
+struct Tuple(Full<2, 4, LeakyRelu>, Full<4, 1, Logistic>);
+
+#[derive(Network)]
+struct Generic<A, B> {
+    first: A,
+    second: B,
+}
+
+#[derive(Network)]
+struct Single {
+    layer: Full<2, 1, Logistic>,
+}
+
+fn layers() -> (Full<2, 4, LeakyRelu>, Full<4, 1, Logistic>) {
+    (
+        Full::new(LeakyRelu(0.01), testing::seeded_gen(1)),
+        Full::new(Logistic, testing::seeded_gen(2)),
+    )
+}
+
+// Trains `net` on XOR, returning its outputs.
+fn train<N: Network<In = [f32; 2], Out = [f32; 1]>>(net: &mut N) -> Vec<[f32; 1]> {
+    for _ in 0..100 {
+        for (inputs, target) in &testing::XOR {
+            let mut error = SquareError { expected: *target };
+            let inter = net.intermediate(inputs);
+            let err = error.intermediate(inter.output());
+            let grads = error.train_deriv(inter.output(), &err, &[1.0], 0.1);
+            net.train_deriv(inputs, &inter, &grads, 0.1);
+        }
+    }
+    testing::XOR
+        .iter()
+        .map(|(inputs, _)| net.eval(inputs))
+        .collect()
+}
+
+#[test]
+fn named_fields_match_chain() {
+    let (hidden, output) = layers();
+    let mut chain = hidden.chain(output);
+    let (hidden, output) = layers();
+    let mut mlp = Mlp { hidden, output };
+
+    let inter: MlpInter = mlp.intermediate(&[1.0, 0.0]);
+    assert_eq!(inter.output(), &chain.eval(&[1.0, 0.0]));
+    assert_eq!(inter.hidden.output(), &chain.first.eval(&[1.0, 0.0]));
+    assert_eq!(train(&mut mlp), train(&mut chain));
+}
+
+#[test]
+fn tuple_fields_match_chain() {
+    let (hidden, output) = layers();
+    let mut chain = hidden.chain(output);
+    let (hidden, output) = layers();
+    let mut tuple = Tuple(hidden, output);
+
+    let inter: TupleInter = tuple.intermediate(&[0.0, 1.0]);
+    assert_eq!(inter.0.output(), &chain.first.eval(&[0.0, 1.0]));
+    assert_eq!(train(&mut tuple), train(&mut chain));
+}
+
+#[test]
+fn generic_fields_match_chain() {
+    let (hidden, output) = layers();
+    let mut chain = hidden.chain(output);
+    let (first, second) = layers();
+    let mut generic = Generic { first, second };
+    assert_eq!(train(&mut generic), train(&mut chain));
+}
+
+#[test]
+fn single_field() {
+    let layer = Full::new(Logistic, testing::seeded_gen(1));
+    let single = Single {
+        layer: layer.clone(),
+    };
+    assert_eq!(single.eval(&[1.0, 0.5]), layer.eval(&[1.0, 0.5]));
+}
+
+#[test]
+fn finds_named_layers_in_fields() {
+    let mut generic = Generic {
+        first: Full::<2, 2, _>::new(Logistic, testing::seeded_gen(1)),
+        second: Full::<2, 1, _>::new(Logistic, testing::seeded_gen(2)).named("out"),
+    };
+    assert!(generic.layer::<Full<2, 1, Logistic>>("out").is_some());
+    assert!(generic.layer_mut::<Full<2, 1, Logistic>>("out").is_some());
+    assert!(generic.find_layer("first").is_none());
+}
@@ -0,0 +1,51 @@
+/*
+ * C interface to RANN networks. See the documentation of the rann-ffi crate for more info.
+ *
+ * Functions that can fail return a null pointer or a non-zero status, and set a message that can
+ * be read with rann_last_error() on the same thread.
+ */
+
+#ifndef RANN_H
+#define RANN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* A network, owned by the caller until freed with rann_network_free(). */
+typedef struct RannNetwork RannNetwork;
+
+/* Returns the message of the last error on this thread, or an empty string. */
+const char *rann_last_error(void);
+
+/* Creates a network with layer sizes from inputs to outputs, an activation such as "logistic",
+ * "tanh", "relu" or "leaky_relu", and random parameters generated from seed. */
+RannNetwork *rann_network_new(const size_t *sizes, size_t num_sizes, const char *activation,
+                              uint64_t seed);
+
+/* Loads a network from a model file. */
+RannNetwork *rann_network_load(const char *path);
+
+/* Saves a network to a model file. Returns zero on success. */
+int rann_network_save(const RannNetwork *net, const char *path);
+
+/* Returns the number of inputs and outputs of a network. */
+size_t rann_network_num_inputs(const RannNetwork *net);
+size_t rann_network_num_outputs(const RannNetwork *net);
+
+/* Evaluates a network on num_inputs floats, writing num_outputs floats. Returns zero on
+ * success. */
+int rann_network_eval(const RannNetwork *net, const float *inputs, size_t num_inputs,
+                      float *outputs, size_t num_outputs);
+
+/* Frees a network. Does nothing if net is null. */
+void rann_network_free(RannNetwork *net);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* RANN_H */
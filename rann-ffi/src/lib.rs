@@ -0,0 +1,209 @@
+/*!
+# Rann-ffi
+
+A stable C ABI for embedding trained networks in C and C++ applications, such as game engines.
+The crate builds a shared and a static library, with the declarations in `include/rann.h`.
+
+Networks are the multilayer perceptrons of [`rann_base::mlp`], created with [`rann_network_new()`]
+or loaded from a model file saved by the `rann` binary or the Python bindings with
+[`rann_network_load()`]. They are evaluated on buffers of floats with [`rann_network_eval()`], and
+freed with [`rann_network_free()`].
+
+Functions that can fail return a null pointer or a non-zero status, and set a message that can be
+read with [`rann_last_error()`] on the same thread. Panics never cross the C boundary.
+
+```c
+#include "rann.h"
+
+RannNetwork *net = rann_network_load("xor.rann");
+if (!net) {
+    fprintf(stderr, "%s\n", rann_last_error());
+    return 1;
+}
+float inputs[2] = {1.0f, 0.0f};
+float outputs[1];
+rann_network_eval(net, inputs, 2, outputs, 1);
+rann_network_free(net);
+```
+*/
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, c_int, CStr, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr, slice,
+};
+
+use rann_base::{
+    mlp::{Activation, Mlp},
+    model,
+};
+use rann_traits::{Network, Scalar};
+
+/// A network owned by C code. It is opaque to C.
+pub struct RannNetwork(Mlp);
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn set_error(message: impl Into<String>) {
+    let message = message.into().replace('\0', " ");
+    LAST_ERROR.with(|error| *error.borrow_mut() = CString::new(message).unwrap_or_default());
+}
+
+// Runs `f`, turning errors and panics into the last error and `default`.
+fn guard<T>(default: T, f: impl FnOnce() -> Result<T, String>) -> T {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => value,
+        Ok(Err(message)) => {
+            set_error(message);
+            default
+        }
+        Err(_) => {
+            set_error("internal error: rann panicked");
+            default
+        }
+    }
+}
+
+unsafe fn str_arg<'a>(s: *const c_char, name: &str) -> Result<&'a str, String> {
+    if s.is_null() {
+        return Err(format!("{name} is null"));
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|_| format!("{name} is not valid UTF-8"))
+}
+
+unsafe fn net_arg<'a>(net: *const RannNetwork) -> Result<&'a Mlp, String> {
+    net.as_ref()
+        .map(|net| &net.0)
+        .ok_or_else(|| "the network is null".to_string())
+}
+
+/// Returns the message of the last error on this thread, or an empty string. The message is
+/// valid until the next call to a function of this library on the same thread.
+#[no_mangle]
+pub extern "C" fn rann_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| error.borrow().as_ptr())
+}
+
+/// Creates a network with `num_sizes` layer sizes from `sizes`, from the number of inputs to the
+/// number of outputs, using the activation named `activation` (such as `"logistic"`), with
+/// random parameters generated from `seed`. Returns null on failure.
+///
+/// # Safety
+/// `sizes` must point to `num_sizes` sizes, and `activation` to a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rann_network_new(
+    sizes: *const usize,
+    num_sizes: usize,
+    activation: *const c_char,
+    seed: u64,
+) -> *mut RannNetwork {
+    guard(ptr::null_mut(), || {
+        if sizes.is_null() {
+            return Err("sizes is null".to_string());
+        }
+        let sizes = slice::from_raw_parts(sizes, num_sizes);
+        if sizes.len() < 2 || sizes.contains(&0) {
+            return Err("sizes should hold at least two non-zero sizes".to_string());
+        }
+        let activation: Activation = str_arg(activation, "activation")?
+            .parse()
+            .map_err(|e| format!("{e}"))?;
+        let net = RannNetwork(Mlp::new(sizes, activation, seed));
+        Ok(Box::into_raw(Box::new(net)))
+    })
+}
+
+/// Loads the network in the model file at `path`. Returns null on failure.
+///
+/// # Safety
+/// `path` must point to a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rann_network_load(path: *const c_char) -> *mut RannNetwork {
+    guard(ptr::null_mut(), || {
+        let path = str_arg(path, "path")?;
+        let (mlp, _) = Mlp::load(path).map_err(|e| format!("{path}: {e}"))?;
+        Ok(Box::into_raw(Box::new(RannNetwork(mlp))))
+    })
+}
+
+/// Saves `net` to a model file at `path`. Returns zero on success.
+///
+/// # Safety
+/// `net` must be a network returned by this library that has not been freed, and `path` must
+/// point to a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn rann_network_save(net: *const RannNetwork, path: *const c_char) -> c_int {
+    guard(-1, || {
+        let (net, path) = (net_arg(net)?, str_arg(path, "path")?);
+        model::save(net, &net.metadata("rann-ffi"), path).map_err(|e| format!("{path}: {e}"))?;
+        Ok(0)
+    })
+}
+
+/// Returns the number of inputs of `net`, or zero if `net` is null.
+///
+/// # Safety
+/// `net` must be null or a network returned by this library that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn rann_network_num_inputs(net: *const RannNetwork) -> usize {
+    guard(0, || Ok(net_arg(net)?.sizes()[0]))
+}
+
+/// Returns the number of outputs of `net`, or zero if `net` is null.
+///
+/// # Safety
+/// `net` must be null or a network returned by this library that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn rann_network_num_outputs(net: *const RannNetwork) -> usize {
+    guard(0, || Ok(*net_arg(net)?.sizes().last().unwrap()))
+}
+
+/// Evaluates `net` on the `num_inputs` floats in `inputs`, and writes its outputs to the
+/// `num_outputs` floats in `outputs`. Returns zero on success, or non-zero if the buffers do not
+/// match the sizes of the network.
+///
+/// # Safety
+/// `net` must be a network returned by this library that has not been freed, `inputs` must point
+/// to `num_inputs` floats and `outputs` to `num_outputs` writable floats.
+#[no_mangle]
+pub unsafe extern "C" fn rann_network_eval(
+    net: *const RannNetwork,
+    inputs: *const Scalar,
+    num_inputs: usize,
+    outputs: *mut Scalar,
+    num_outputs: usize,
+) -> c_int {
+    guard(-1, || {
+        let net = net_arg(net)?;
+        let sizes = net.sizes();
+        let (expected_in, expected_out) = (sizes[0], sizes[sizes.len() - 1]);
+        if num_inputs != expected_in || num_outputs != expected_out {
+            return Err(format!(
+                "expected {expected_in} inputs and {expected_out} outputs, got {num_inputs} and {num_outputs}"
+            ));
+        }
+        if inputs.is_null() || outputs.is_null() {
+            return Err("the buffers are null".to_string());
+        }
+        let inputs = slice::from_raw_parts(inputs, num_inputs);
+        let outputs = slice::from_raw_parts_mut(outputs, num_outputs);
+        outputs.copy_from_slice(&net.eval(&inputs.to_vec()));
+        Ok(0)
+    })
+}
+
+/// Frees `net`. Does nothing if `net` is null.
+///
+/// # Safety
+/// `net` must be null or a network returned by this library that has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn rann_network_free(net: *mut RannNetwork) {
+    if !net.is_null() {
+        drop(Box::from_raw(net));
+    }
+}
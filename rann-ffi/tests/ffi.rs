@@ -0,0 +1,76 @@
+use std::{
+    ffi::{CStr, CString},
+    ptr,
+};
+
+use rann_base::mlp::{Activation, Mlp};
+use rann_ffi::*;
+use rann_traits::Network;
+
+fn last_error() -> String {
+    unsafe { CStr::from_ptr(rann_last_error()) }
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn creates_evaluates_and_frees_networks() {
+    let activation = CString::new("tanh").unwrap();
+    unsafe {
+        let net = rann_network_new([3, 4, 2].as_ptr(), 3, activation.as_ptr(), 5);
+        assert!(!net.is_null());
+        assert_eq!(rann_network_num_inputs(net), 3);
+        assert_eq!(rann_network_num_outputs(net), 2);
+
+        let inputs = [0.5, -1.0, 2.0];
+        let mut outputs = [0.0; 2];
+        let status = rann_network_eval(net, inputs.as_ptr(), 3, outputs.as_mut_ptr(), 2);
+        assert_eq!(status, 0);
+        let expected = Mlp::new(&[3, 4, 2], Activation::Tanh, 5).eval(&inputs.to_vec());
+        assert_eq!(outputs.to_vec(), expected);
+
+        let status = rann_network_eval(net, inputs.as_ptr(), 2, outputs.as_mut_ptr(), 2);
+        assert_ne!(status, 0);
+        assert_eq!(last_error(), "expected 3 inputs and 2 outputs, got 2 and 2");
+        rann_network_free(net);
+        rann_network_free(ptr::null_mut());
+    }
+}
+
+#[test]
+fn saves_and_loads_networks() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = CString::new(dir.path().join("net.rann").to_str().unwrap()).unwrap();
+    let activation = CString::new("logistic").unwrap();
+    unsafe {
+        let net = rann_network_new([2, 1].as_ptr(), 2, activation.as_ptr(), 1);
+        assert_eq!(rann_network_save(net, path.as_ptr()), 0);
+        let loaded = rann_network_load(path.as_ptr());
+        assert!(!loaded.is_null());
+
+        let inputs = [1.0, 0.0];
+        let (mut a, mut b) = ([0.0], [0.0]);
+        rann_network_eval(net, inputs.as_ptr(), 2, a.as_mut_ptr(), 1);
+        rann_network_eval(loaded, inputs.as_ptr(), 2, b.as_mut_ptr(), 1);
+        assert_eq!(a, b);
+        rann_network_free(net);
+        rann_network_free(loaded);
+    }
+}
+
+#[test]
+fn reports_errors() {
+    let missing = CString::new("/nonexistent/net.rann").unwrap();
+    let sine = CString::new("sine").unwrap();
+    unsafe {
+        assert!(rann_network_load(missing.as_ptr()).is_null());
+        assert!(last_error().starts_with("/nonexistent/net.rann: "));
+        assert!(rann_network_load(ptr::null()).is_null());
+        assert_eq!(last_error(), "path is null");
+        assert!(rann_network_new([2, 1].as_ptr(), 2, sine.as_ptr(), 0).is_null());
+        assert_eq!(last_error(), "unknown activation \"sine\"");
+        assert!(rann_network_new([2].as_ptr(), 1, sine.as_ptr(), 0).is_null());
+        assert_eq!(rann_network_num_inputs(ptr::null()), 0);
+    }
+}
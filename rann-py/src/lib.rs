@@ -0,0 +1,266 @@
+/*!
+# Rann-py
+
+Python bindings, to drive networks trained in Rust from notebooks and compare them against other
+libraries. The module is built with [maturin](https://www.maturin.rs):
+
+```sh
+$ cd rann-py && maturin develop --release
+```
+
+It exposes the multilayer perceptron of [`rann_base::mlp`] as `rann.Network`, and the
+[`Trainer`](train::Trainer) of `rann-base` as `rann.Trainer`:
+
+```python
+import rann
+
+inputs = [[0, 0], [0, 1], [1, 0], [1, 1]]
+targets = [[0], [1], [1], [0]]
+
+net = rann.Network([2, 3, 1], activation="logistic", seed=1)
+errors = rann.Trainer(epochs=3000, learning_rate=0.5).fit(net, inputs, targets)
+print(net.eval([1, 0]), net.metrics(inputs, targets))
+
+net.save("xor.rann")
+net = rann.Network.load("xor.rann")
+```
+
+Model files are the same as those of the `rann` binary, so models can be trained from the command
+line and inspected from Python, and vice versa.
+*/
+
+// The code generated by `#[pymethods]` converts every `PyResult` into itself.
+#![allow(clippy::useless_conversion)]
+
+use std::path::PathBuf;
+
+use pyo3::{
+    exceptions::{PyIOError, PyValueError},
+    prelude::*,
+};
+use rann_base::{
+    mlp::{Activation, Mlp, SquareLoss},
+    model, train,
+};
+use rann_traits::{params::Parameterized, Network, Scalar};
+
+/// A multilayer perceptron, exposed to Python as `rann.Network`. See [`Mlp`].
+#[pyclass(name = "Network", module = "rann")]
+#[derive(Clone, Debug)]
+pub struct PyNetwork {
+    net: SquareLoss,
+}
+
+#[pymethods]
+impl PyNetwork {
+    /// Creates a network with layers of the given sizes, from the number of inputs to the number
+    /// of outputs.
+    #[new]
+    #[pyo3(signature = (layers, activation = "logistic", seed = 0))]
+    pub fn new(layers: Vec<usize>, activation: &str, seed: u64) -> PyResult<Self> {
+        if layers.len() < 2 || layers.contains(&0) {
+            return Err(PyValueError::new_err(
+                "layers should hold at least two non-zero sizes",
+            ));
+        }
+        let activation: Activation = activation
+            .parse()
+            .map_err(|e| PyValueError::new_err(format!("{e}")))?;
+        Ok(Self {
+            net: SquareLoss::new(Mlp::new(&layers, activation, seed)),
+        })
+    }
+
+    /// The sizes of the layers.
+    #[getter]
+    pub fn layers(&self) -> Vec<usize> {
+        self.net.mlp.sizes()
+    }
+
+    /// The parameters of the network, see [`Parameterized`].
+    #[getter]
+    pub fn params(&self) -> Vec<Scalar> {
+        self.net.mlp.params()
+    }
+
+    #[setter]
+    pub fn set_params(&mut self, params: Vec<Scalar>) -> PyResult<()> {
+        if params.len() != self.net.mlp.num_params() {
+            return Err(PyValueError::new_err(format!(
+                "expected {} parameters, got {}",
+                self.net.mlp.num_params(),
+                params.len()
+            )));
+        }
+        self.net.mlp.read_params(&params);
+        Ok(())
+    }
+
+    /// Returns the outputs of the network for `inputs`.
+    pub fn eval(&self, inputs: Vec<Scalar>) -> PyResult<Vec<Scalar>> {
+        self.check(&inputs, &[])?;
+        Ok(self.net.mlp.eval(&inputs))
+    }
+
+    /// Trains the network on a single sample with backpropagation, and returns the error before
+    /// training.
+    pub fn backprop(
+        &mut self,
+        inputs: Vec<Scalar>,
+        targets: Vec<Scalar>,
+        learning_rate: Scalar,
+    ) -> PyResult<Scalar> {
+        self.check(&inputs, &targets)?;
+        Ok(train::train_step(
+            &mut self.net,
+            &inputs,
+            &targets,
+            learning_rate,
+        ))
+    }
+
+    /// Returns the mean error and the accuracy of the network on a dataset, see
+    /// [`SquareLoss::metrics()`].
+    pub fn metrics(
+        &mut self,
+        inputs: Vec<Vec<Scalar>>,
+        targets: Vec<Vec<Scalar>>,
+    ) -> PyResult<(Scalar, Scalar)> {
+        let dataset = self.dataset(inputs, targets)?;
+        let metrics = self.net.metrics(&dataset);
+        Ok((metrics.error, metrics.accuracy))
+    }
+
+    /// Saves the network to a model file.
+    pub fn save(&self, path: PathBuf) -> PyResult<()> {
+        let metadata = self.net.mlp.metadata("rann-py");
+        model::save(&self.net.mlp, &metadata, path).map_err(|e| PyIOError::new_err(e.to_string()))
+    }
+
+    /// Loads a network from a model file.
+    #[staticmethod]
+    pub fn load(path: PathBuf) -> PyResult<Self> {
+        let (mlp, _) = Mlp::load(path).map_err(|e| PyIOError::new_err(e.to_string()))?;
+        Ok(Self {
+            net: SquareLoss::new(mlp),
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        match self.net.mlp.activation() {
+            Some(activation) => format!(
+                "Network(layers={:?}, activation='{activation}')",
+                self.layers()
+            ),
+            None => {
+                let activations = self.net.mlp.activations();
+                let activations: Vec<String> = activations.iter().map(|a| a.to_string()).collect();
+                format!(
+                    "Network(layers={:?}, activations={activations:?})",
+                    self.layers()
+                )
+            }
+        }
+    }
+}
+
+impl PyNetwork {
+    // Checks the sizes of a sample, where empty targets are not checked.
+    fn check(&self, inputs: &[Scalar], targets: &[Scalar]) -> PyResult<()> {
+        let sizes = self.layers();
+        let (num_in, num_out) = (sizes[0], sizes[sizes.len() - 1]);
+        if inputs.len() != num_in {
+            return Err(PyValueError::new_err(format!(
+                "expected {num_in} inputs, got {}",
+                inputs.len()
+            )));
+        }
+        if !targets.is_empty() && targets.len() != num_out {
+            return Err(PyValueError::new_err(format!(
+                "expected {num_out} targets, got {}",
+                targets.len()
+            )));
+        }
+        Ok(())
+    }
+
+    fn dataset(
+        &self,
+        inputs: Vec<Vec<Scalar>>,
+        targets: Vec<Vec<Scalar>>,
+    ) -> PyResult<Vec<(Vec<Scalar>, Vec<Scalar>)>> {
+        if inputs.len() != targets.len() {
+            return Err(PyValueError::new_err(format!(
+                "got {} inputs, but {} targets",
+                inputs.len(),
+                targets.len()
+            )));
+        }
+        for (inputs, targets) in inputs.iter().zip(&targets) {
+            if targets.is_empty() {
+                return Err(PyValueError::new_err("targets should not be empty"));
+            }
+            self.check(inputs, targets)?;
+        }
+        Ok(inputs.into_iter().zip(targets).collect())
+    }
+}
+
+/// The configuration of a training run, exposed to Python as `rann.Trainer`. See
+/// [`train::Trainer`].
+#[pyclass(name = "Trainer", module = "rann")]
+#[derive(Clone, Debug)]
+pub struct PyTrainer {
+    /// The number of epochs to train for.
+    #[pyo3(get, set)]
+    pub epochs: usize,
+    /// The learning rate.
+    #[pyo3(get, set)]
+    pub learning_rate: Scalar,
+}
+
+#[pymethods]
+impl PyTrainer {
+    #[new]
+    #[pyo3(signature = (epochs = 100, learning_rate = 0.1))]
+    pub fn new(epochs: usize, learning_rate: Scalar) -> Self {
+        Self {
+            epochs,
+            learning_rate,
+        }
+    }
+
+    /// Trains `net` on the samples of `inputs` and `targets`, and returns the mean error of every
+    /// epoch. The GIL is released while training.
+    pub fn fit(
+        &self,
+        py: Python<'_>,
+        mut net: PyRefMut<'_, PyNetwork>,
+        inputs: Vec<Vec<Scalar>>,
+        targets: Vec<Vec<Scalar>>,
+    ) -> PyResult<Vec<Scalar>> {
+        let dataset = net.dataset(inputs, targets)?;
+        let trainer = train::Trainer {
+            epochs: self.epochs,
+            learning_rate: self.learning_rate.into(),
+            ..Default::default()
+        };
+        let net = &mut net.net;
+        Ok(py.allow_threads(|| trainer.fit(net, &dataset)).errors)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Trainer(epochs={}, learning_rate={})",
+            self.epochs, self.learning_rate
+        )
+    }
+}
+
+/// The `rann` Python module.
+#[pymodule]
+pub fn rann(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyNetwork>()?;
+    m.add_class::<PyTrainer>()?;
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use pyo3::{prelude::*, types::PyDict, wrap_pymodule};
+
+// Runs the Python `code` with the module imported as `rann`, and `path` defined.
+fn run(code: &str, path: &str) {
+    Python::with_gil(|py| {
+        let globals = PyDict::new_bound(py);
+        globals
+            .set_item("rann", wrap_pymodule!(rann::rann)(py))
+            .unwrap();
+        globals.set_item("path", path).unwrap();
+        if let Err(e) = py.run_bound(code, Some(&globals), None) {
+            e.print(py);
+            panic!("{e}");
+        }
+    });
+}
+
+#[test]
+fn trains_xor_from_python() {
+    run(
+        r#"
+inputs = [[0, 0], [0, 1], [1, 0], [1, 1]]
+targets = [[0], [1], [1], [0]]
+net = rann.Network([2, 3, 1], seed=1)
+assert net.layers == [2, 3, 1]
+assert len(net.params) == 13
+errors = rann.Trainer(epochs=3000, learning_rate=0.5).fit(net, inputs, targets)
+assert len(errors) == 3000
+assert errors[-1] < errors[0]
+error, accuracy = net.metrics(inputs, targets)
+assert accuracy == 1.0, accuracy
+assert net.eval([1, 0])[0] > 0.5
+error = net.backprop([1, 0], [1], 0.1)
+assert error < 0.1
+"#,
+        "",
+    );
+}
+
+#[test]
+fn saves_and_loads_from_python() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("net.rann");
+    run(
+        r#"
+net = rann.Network([3, 2], activation="tanh", seed=2)
+net.save(path)
+loaded = rann.Network.load(path)
+assert loaded.params == net.params
+assert repr(loaded) == "Network(layers=[3, 2], activation='tanh')"
+"#,
+        path.to_str().unwrap(),
+    );
+}
+
+#[test]
+fn rejects_invalid_arguments() {
+    run(
+        r#"
+def raises(f, error):
+    try:
+        f()
+    except error:
+        return
+    raise AssertionError("expected " + error.__name__)
+
+net = rann.Network([2, 1])
+raises(lambda: rann.Network([2]), ValueError)
+raises(lambda: rann.Network([2, 1], activation="sine"), ValueError)
+raises(lambda: net.eval([1]), ValueError)
+raises(lambda: net.backprop([1, 0], [1, 1], 0.1), ValueError)
+raises(lambda: rann.Trainer().fit(net, [[0, 0]], []), ValueError)
+raises(lambda: rann.Network.load("/nonexistent/net.rann"), OSError)
+"#,
+        "",
+    );
+}
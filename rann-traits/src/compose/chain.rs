@@ -1,8 +1,15 @@
-use crate::{Intermediate, Network, Scalar};
+use std::any::Any;
+
+use crate::{Intermediate, Network, Scalar, Supervised};
 
 /**
 Chains two networks together, after eachother.
 
+Longer chains nest: `a.chain(b).chain(c)` is a `Chain<Chain<A, B>, C>`, such that `first` is
+everything before the last network, such as a network before its error function. The [`chain!`]
+macro builds such chains from a list of networks, and [`Chain::layers()`] borrows the networks
+of a chain as a flat tuple, instead of through `first.first.second`.
+
 # Examples
 ```rust
 use rann_traits::Network;
@@ -33,7 +40,7 @@ net.train(&inputs, &inter, rate);
 
 ```
 */
-
+#[derive(Clone, Debug)]
 pub struct Chain<T, U> {
     /// The first part of the chain.
     pub first: T,
@@ -60,6 +67,17 @@ where
         ChainInter { first, second }
     }
 
+    fn intermediate_into(&self, input: &Self::In, intermediate: &mut Self::Inter) {
+        self.first.intermediate_into(input, &mut intermediate.first);
+        self.second
+            .intermediate_into(intermediate.first.output(), &mut intermediate.second);
+    }
+
+    fn eval_many(&self, inputs: &[Self::In]) -> Vec<Self::Out> {
+        // Every layer evaluates the whole batch, such that layers can evaluate it at once.
+        self.second.eval_many(&self.first.eval_many(inputs))
+    }
+
     fn train_deriv(
         &mut self,
         inputs: &Self::In,
@@ -81,6 +99,74 @@ where
         // Output gradients are of first layer.
         first
     }
+
+    fn find_layer(&self, name: &str) -> Option<&dyn Any> {
+        self.first
+            .find_layer(name)
+            .or_else(|| self.second.find_layer(name))
+    }
+
+    fn find_layer_mut(&mut self, name: &str) -> Option<&mut dyn Any> {
+        match self.first.find_layer_mut(name) {
+            Some(layer) => Some(layer),
+            None => self.second.find_layer_mut(name),
+        }
+    }
+}
+
+impl<T, U> Chain<T, U> {
+    /// Returns references to the `N` networks of this chain, in order, for a chain of `N`
+    /// networks built by [`chain!`] or by chaining networks one by one.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use rann_base::{activ::Logistic, error::SquareError, testing, Full};
+    /// use rann_traits::{chain, Intermediate, Network};
+    ///
+    /// let mut net = chain!(
+    ///     Full::<2, 3, _>::new(Logistic, testing::seeded_gen(1)),
+    ///     Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2)),
+    ///     SquareError { expected: [1.0] },
+    /// );
+    /// let (hidden, _, loss) = net.layers::<3>();
+    /// assert_eq!(loss.expected, [1.0]);
+    ///
+    /// // The intermediate values can be flattened in the same way.
+    /// let inter = net.intermediate(&[0.0, 1.0]);
+    /// let (hidden_inter, _, _) = inter.layers::<3>();
+    /// assert_eq!(*hidden_inter.output(), hidden.eval(&[0.0, 1.0]));
+    ///
+    /// let (_, _, loss) = net.layers_mut::<3>();
+    /// loss.expected = [0.0];
+    /// assert_eq!(net.second.expected, [0.0]);
+    /// ```
+    pub fn layers<const N: usize>(&self) -> <Self as Flat<N>>::Refs<'_>
+    where
+        Self: Flat<N>,
+    {
+        Flat::<N>::flat(self)
+    }
+
+    /// Returns mutable references to the `N` networks of this chain, like [`Self::layers()`].
+    pub fn layers_mut<const N: usize>(&mut self) -> <Self as Flat<N>>::Muts<'_>
+    where
+        Self: Flat<N>,
+    {
+        Flat::<N>::flat_mut(self)
+    }
+}
+
+// A chain ending in an error function is trained towards the target of that error function.
+impl<T, U> Supervised for Chain<T, U>
+where
+    T: Network,
+    U: Supervised<In = T::Out>,
+{
+    type Target = U::Target;
+
+    fn set_target(&mut self, target: &Self::Target) {
+        self.second.set_target(target);
+    }
 }
 
 /// The intermediate values of an evaluation of a [`Chain`].
@@ -107,3 +193,108 @@ where
     }
 }
 
+impl<T, U> ChainInter<T, U> {
+    /// Returns references to the intermediate values of the `N` networks of a chain, in order,
+    /// like [`Chain::layers()`].
+    pub fn layers<const N: usize>(&self) -> <Self as Flat<N>>::Refs<'_>
+    where
+        Self: Flat<N>,
+    {
+        Flat::<N>::flat(self)
+    }
+
+    /// Returns mutable references to the intermediate values of the `N` networks of a chain.
+    pub fn layers_mut<const N: usize>(&mut self) -> <Self as Flat<N>>::Muts<'_>
+    where
+        Self: Flat<N>,
+    {
+        Flat::<N>::flat_mut(self)
+    }
+}
+
+/// Chains networks together, after eachother, into a [`Chain`]: `chain!(a, b, c)` is
+/// `a.chain(b).chain(c)`. See [`Chain::layers()`] to access the networks of the chain.
+#[macro_export]
+macro_rules! chain {
+    ($first:expr $(, $rest:expr)* $(,)?) => {{
+        let net = $first;
+        $(let net = $crate::Network::chain(net, $rest);)*
+        net
+    }};
+}
+
+/// Nested [`Chain`]s, or [`ChainInter`]s, of `N` parts, which can be borrowed as flat tuples of
+/// their parts. Implemented for up to 12 parts.
+pub trait Flat<const N: usize> {
+    /// A tuple of references to the parts.
+    type Refs<'a>
+    where
+        Self: 'a;
+    /// A tuple of mutable references to the parts.
+    type Muts<'a>
+    where
+        Self: 'a;
+    /// Returns references to the parts, in order.
+    fn flat(&self) -> Self::Refs<'_>;
+    /// Returns mutable references to the parts, in order.
+    fn flat_mut(&mut self) -> Self::Muts<'_>;
+}
+
+// The type of `$s`s nested to the left, such as `Chain<Chain<A, B>, C>`.
+macro_rules! left_nested {
+    ($s:ident; $acc:ty;) => { $acc };
+    ($s:ident; $acc:ty; $next:ident $(, $rest:ident)*) => {
+        left_nested!($s; $s<$acc, $next>; $($rest),*)
+    };
+}
+
+// Implements `Flat<$n>` for `$s`s of `$n` parts by appending the last part to the flattened
+// first part of `$m` parts.
+macro_rules! impl_flat {
+    ($s:ident, $n:literal, $m:literal; $t0:ident $v0:ident $(, $t:ident $v:ident)*; $last:ident) => {
+        impl<$t0, $($t,)* $last> Flat<$n> for $s<left_nested!($s; $t0; $($t),*), $last> {
+            type Refs<'a> = (&'a $t0, $(&'a $t,)* &'a $last) where Self: 'a;
+            type Muts<'a> = (&'a mut $t0, $(&'a mut $t,)* &'a mut $last) where Self: 'a;
+
+            fn flat(&self) -> Self::Refs<'_> {
+                let ($v0, $($v,)*) = Flat::<$m>::flat(&self.first);
+                ($v0, $($v,)* &self.second)
+            }
+
+            fn flat_mut(&mut self) -> Self::Muts<'_> {
+                let ($v0, $($v,)*) = Flat::<$m>::flat_mut(&mut self.first);
+                ($v0, $($v,)* &mut self.second)
+            }
+        }
+    };
+}
+
+macro_rules! impl_flat_all {
+    ($s:ident) => {
+        impl<T, U> Flat<2> for $s<T, U> {
+            type Refs<'a> = (&'a T, &'a U) where Self: 'a;
+            type Muts<'a> = (&'a mut T, &'a mut U) where Self: 'a;
+
+            fn flat(&self) -> Self::Refs<'_> {
+                (&self.first, &self.second)
+            }
+
+            fn flat_mut(&mut self) -> Self::Muts<'_> {
+                (&mut self.first, &mut self.second)
+            }
+        }
+        impl_flat!($s, 3, 2; A a, B b; C);
+        impl_flat!($s, 4, 3; A a, B b, C c; D);
+        impl_flat!($s, 5, 4; A a, B b, C c, D d; E);
+        impl_flat!($s, 6, 5; A a, B b, C c, D d, E e; F);
+        impl_flat!($s, 7, 6; A a, B b, C c, D d, E e, F f; G);
+        impl_flat!($s, 8, 7; A a, B b, C c, D d, E e, F f, G g; H);
+        impl_flat!($s, 9, 8; A a, B b, C c, D d, E e, F f, G g, H h; I);
+        impl_flat!($s, 10, 9; A a, B b, C c, D d, E e, F f, G g, H h, I i; J);
+        impl_flat!($s, 11, 10; A a, B b, C c, D d, E e, F f, G g, H h, I i, J j; K);
+        impl_flat!($s, 12, 11; A a, B b, C c, D d, E e, F f, G g, H h, I i, J j, K k; L);
+    };
+}
+
+impl_flat_all!(Chain);
+impl_flat_all!(ChainInter);
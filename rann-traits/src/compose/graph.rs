@@ -0,0 +1,651 @@
+use std::{any::Any, error::Error, fmt::Display};
+
+use crate::{Intermediate, Network, Scalar};
+
+/**
+A network of nodes connected in an arbitrary directed acyclic graph.
+
+Where [`Chain`](super::Chain) and [`Zip`](super::Zip) fix the architecture in the type of the
+network, a [`Graph`] is built at runtime using a [`GraphBuilder`]: nodes are added, and edges
+connect the graph inputs or the outputs of nodes to the inputs of other nodes. The inputs of a
+node are the concatenated outputs of its sources, in the order they were connected, and the
+outputs of a node can be used by any number of other nodes.
+
+The graph is evaluated in topological order, and trained in reverse: the gradients of nodes used
+by multiple other nodes are summed. An [`EvalScratch`] holds the buffers of an evaluation or a
+training step, such that [`Graph::eval_with()`] and [`Graph::train_deriv_with()`] can reuse them
+across calls instead of allocating them every time.
+
+# Examples
+```rust
+use rann_traits::{compose::graph::{GraphBuilder, Source}, Network};
+use rann_base::{Full, activ::Logistic};
+
+let gen = (|_, _| 0.5, |_| 0.0);
+let mut builder = GraphBuilder::<2, 1>::new();
+// Two branches using the same inputs...
+let a = builder.add_node(Full::<2, 3, _>::new(Logistic, gen));
+let b = builder.add_node(Full::<2, 2, _>::new(Logistic, gen));
+builder.connect(Source::Input, a);
+builder.connect(Source::Input, b);
+// ...joined by a node using the outputs of both, and the graph inputs.
+let joined = builder.add_node(Full::<7, 1, _>::new(Logistic, gen));
+builder.connect(a, joined);
+builder.connect(b, joined);
+builder.connect(Source::Input, joined);
+builder.output(joined);
+let mut net = builder.build().unwrap();
+
+let inter = net.intermediate(&[1.0, 0.0]);
+net.train(&[1.0, 0.0], &inter, 0.1);
+```
+*/
+pub struct Graph<const NUM_IN: usize, const NUM_OUT: usize> {
+    nodes: Vec<NodeEntry>,
+    outputs: Vec<Source>,
+    // The indices of the nodes in topological order.
+    order: Vec<usize>,
+    // The offsets of the outputs of every node in the concatenated outputs of all nodes, followed
+    // by the total number of outputs.
+    offsets: Vec<usize>,
+}
+
+/// Identifies a node in a [`Graph`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+/// The source of the values on an edge of a [`Graph`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Source {
+    /// The inputs of the graph.
+    Input,
+    /// The outputs of a node.
+    Node(NodeId),
+}
+
+impl From<NodeId> for Source {
+    fn from(id: NodeId) -> Self {
+        Source::Node(id)
+    }
+}
+
+/// Object safe version of [`Network`], for the nodes of a [`Graph`]. Implemented for every
+/// network mapping an array of scalars to an array of scalars.
+pub trait Node {
+    /// Returns the number of inputs of the node.
+    fn num_inputs(&self) -> usize;
+
+    /// Returns the number of outputs of the node.
+    fn num_outputs(&self) -> usize;
+
+    /// Evaluates the node, returning its outputs and its intermediate calculations.
+    fn forward(&self, inputs: &[Scalar]) -> (Vec<Scalar>, Box<dyn Any>);
+
+    /// Trains the node using the intermediate calculations returned by [`Self::forward()`], and
+    /// returns the gradients over the inputs.
+    fn backward(
+        &mut self,
+        inputs: &[Scalar],
+        intermediate: &dyn Any,
+        gradients: &[Scalar],
+        learning_rate: Scalar,
+    ) -> Vec<Scalar>;
+
+    /// Evaluates the node like [`Self::forward()`], but stores the outputs and the intermediate
+    /// calculations in `outputs` and `intermediate`, reusing them if they were returned by the
+    /// same node.
+    fn forward_into(
+        &self,
+        inputs: &[Scalar],
+        outputs: &mut Vec<Scalar>,
+        intermediate: &mut Box<dyn Any>,
+    ) {
+        (*outputs, *intermediate) = self.forward(inputs);
+    }
+
+    /// Evaluates the node, writing its outputs to `outputs`, without keeping its intermediate
+    /// calculations.
+    fn eval_into(&self, inputs: &[Scalar], outputs: &mut [Scalar]) {
+        outputs.copy_from_slice(&self.forward(inputs).0);
+    }
+
+    /// Like [`Self::backward()`], but writes the gradients over the inputs to `input_grads`.
+    fn backward_into(
+        &mut self,
+        inputs: &[Scalar],
+        intermediate: &dyn Any,
+        gradients: &[Scalar],
+        learning_rate: Scalar,
+        input_grads: &mut [Scalar],
+    ) {
+        input_grads.copy_from_slice(&self.backward(inputs, intermediate, gradients, learning_rate));
+    }
+}
+
+impl<T, const NUM_IN: usize, const NUM_OUT: usize> Node for T
+where
+    T: Network<In = [Scalar; NUM_IN], Out = [Scalar; NUM_OUT]>,
+    T::Inter: 'static,
+{
+    fn num_inputs(&self) -> usize {
+        NUM_IN
+    }
+
+    fn num_outputs(&self) -> usize {
+        NUM_OUT
+    }
+
+    fn forward(&self, inputs: &[Scalar]) -> (Vec<Scalar>, Box<dyn Any>) {
+        let inter = self.intermediate(as_array(inputs));
+        (inter.output().to_vec(), Box::new(inter))
+    }
+
+    fn backward(
+        &mut self,
+        inputs: &[Scalar],
+        intermediate: &dyn Any,
+        gradients: &[Scalar],
+        learning_rate: Scalar,
+    ) -> Vec<Scalar> {
+        let intermediate = intermediate
+            .downcast_ref()
+            .expect("Intermediate should be returned by the same node.");
+        self.train_deriv(
+            as_array(inputs),
+            intermediate,
+            as_array(gradients),
+            learning_rate,
+        )
+        .to_vec()
+    }
+
+    fn forward_into(
+        &self,
+        inputs: &[Scalar],
+        outputs: &mut Vec<Scalar>,
+        intermediate: &mut Box<dyn Any>,
+    ) {
+        let inputs = as_array(inputs);
+        match intermediate.downcast_mut() {
+            Some(inter) => self.intermediate_into(inputs, inter),
+            None => *intermediate = Box::new(self.intermediate(inputs)),
+        }
+        let inter: &T::Inter = intermediate
+            .downcast_ref()
+            .expect("Intermediate should be stored by this node.");
+        outputs.clear();
+        outputs.extend_from_slice(inter.output());
+    }
+
+    fn eval_into(&self, inputs: &[Scalar], outputs: &mut [Scalar]) {
+        outputs.copy_from_slice(&self.eval(as_array(inputs)));
+    }
+
+    fn backward_into(
+        &mut self,
+        inputs: &[Scalar],
+        intermediate: &dyn Any,
+        gradients: &[Scalar],
+        learning_rate: Scalar,
+        input_grads: &mut [Scalar],
+    ) {
+        let intermediate = intermediate
+            .downcast_ref()
+            .expect("Intermediate should be returned by the same node.");
+        input_grads.copy_from_slice(&self.train_deriv(
+            as_array(inputs),
+            intermediate,
+            as_array(gradients),
+            learning_rate,
+        ));
+    }
+}
+
+fn as_array<const N: usize>(values: &[Scalar]) -> &[Scalar; N] {
+    values
+        .try_into()
+        .expect("Length should be validated when building the graph.")
+}
+
+struct NodeEntry {
+    node: Box<dyn Node>,
+    sources: Vec<Source>,
+}
+
+/// Builds a [`Graph`]. See [`Graph`] for more info.
+pub struct GraphBuilder<const NUM_IN: usize, const NUM_OUT: usize> {
+    nodes: Vec<NodeEntry>,
+    outputs: Vec<Source>,
+    // The first node connected to that is not in the graph.
+    unknown: Option<NodeId>,
+}
+
+impl<const NUM_IN: usize, const NUM_OUT: usize> Default for GraphBuilder<NUM_IN, NUM_OUT> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const NUM_IN: usize, const NUM_OUT: usize> GraphBuilder<NUM_IN, NUM_OUT> {
+    /// Creates a builder for an empty graph.
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            outputs: Vec::new(),
+            unknown: None,
+        }
+    }
+
+    /// Adds `node` to the graph, without any connections.
+    pub fn add_node(&mut self, node: impl Node + 'static) -> NodeId {
+        self.nodes.push(NodeEntry {
+            node: Box::new(node),
+            sources: Vec::new(),
+        });
+        NodeId(self.nodes.len() - 1)
+    }
+
+    /// Appends the values of `source` to the inputs of `to`.
+    pub fn connect(&mut self, source: impl Into<Source>, to: NodeId) -> &mut Self {
+        let source = source.into();
+        match self.nodes.get_mut(to.0) {
+            Some(entry) => entry.sources.push(source),
+            // Reported when building, such that this can be chained.
+            None => {
+                self.unknown.get_or_insert(to);
+            }
+        }
+        self
+    }
+
+    /// Appends the values of `source` to the outputs of the graph.
+    pub fn output(&mut self, source: impl Into<Source>) -> &mut Self {
+        self.outputs.push(source.into());
+        self
+    }
+
+    /// Validates the graph and builds it.
+    ///
+    /// # Errors
+    /// Returns an error if an edge refers to a node that does not exist, if the graph contains
+    /// a cycle, or if the number of values on the edges to a node or to the outputs of the
+    /// graph does not match the number of inputs of that node or of outputs of the graph.
+    pub fn build(self) -> Result<Graph<NUM_IN, NUM_OUT>, GraphError> {
+        if let Some(id) = self.unknown {
+            return Err(GraphError::UnknownNode(id));
+        }
+        let len = |source: &Source| match *source {
+            Source::Input => Ok(NUM_IN),
+            Source::Node(id) => self
+                .nodes
+                .get(id.0)
+                .map(|entry| entry.node.num_outputs())
+                .ok_or(GraphError::UnknownNode(id)),
+        };
+        for (i, entry) in self.nodes.iter().enumerate() {
+            let found = entry.sources.iter().map(len).sum::<Result<usize, _>>()?;
+            let expected = entry.node.num_inputs();
+            if found != expected {
+                return Err(GraphError::Mismatch {
+                    node: Some(NodeId(i)),
+                    expected,
+                    found,
+                });
+            }
+        }
+        let found = self.outputs.iter().map(len).sum::<Result<usize, _>>()?;
+        if found != NUM_OUT {
+            return Err(GraphError::Mismatch {
+                node: None,
+                expected: NUM_OUT,
+                found,
+            });
+        }
+
+        // Sort the nodes topologically using Kahn's algorithm.
+        let mut dependents = vec![Vec::new(); self.nodes.len()];
+        let mut remaining: Vec<usize> = vec![0; self.nodes.len()];
+        for (i, entry) in self.nodes.iter().enumerate() {
+            for source in &entry.sources {
+                if let Source::Node(id) = source {
+                    dependents[id.0].push(i);
+                    remaining[i] += 1;
+                }
+            }
+        }
+        let mut order: Vec<usize> = (0..self.nodes.len())
+            .filter(|&i| remaining[i] == 0)
+            .collect();
+        let mut next = 0;
+        while let Some(&i) = order.get(next) {
+            next += 1;
+            for &dependent in &dependents[i] {
+                remaining[dependent] -= 1;
+                if remaining[dependent] == 0 {
+                    order.push(dependent);
+                }
+            }
+        }
+        if order.len() < self.nodes.len() {
+            return Err(GraphError::Cycle);
+        }
+
+        let mut offsets = vec![0];
+        for entry in &self.nodes {
+            offsets.push(offsets[offsets.len() - 1] + entry.node.num_outputs());
+        }
+        Ok(Graph {
+            nodes: self.nodes,
+            outputs: self.outputs,
+            order,
+            offsets,
+        })
+    }
+}
+
+/// Error returned when building an invalid [`Graph`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphError {
+    /// An edge refers to a node that is not in the graph.
+    UnknownNode(NodeId),
+    /// The graph contains a cycle.
+    Cycle,
+    /// The number of values on the edges to a node, or to the outputs of the graph if `node` is
+    /// `None`, does not match the number of values expected.
+    Mismatch {
+        /// The node, or `None` for the outputs of the graph.
+        node: Option<NodeId>,
+        /// The number of values expected.
+        expected: usize,
+        /// The number of values on the edges.
+        found: usize,
+    },
+}
+
+impl Display for GraphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GraphError::UnknownNode(id) => write!(f, "node {} is not in the graph", id.0),
+            GraphError::Cycle => write!(f, "graph contains a cycle"),
+            GraphError::Mismatch {
+                node,
+                expected,
+                found,
+            } => {
+                match node {
+                    Some(id) => write!(f, "node {}", id.0)?,
+                    None => write!(f, "graph output")?,
+                }
+                write!(f, " expects {expected} values, but is connected to {found}")
+            }
+        }
+    }
+}
+
+impl Error for GraphError {}
+
+/// Reusable buffers for evaluating and training a [`Graph`], see [`Graph::eval_with()`] and
+/// [`Graph::train_deriv_with()`].
+///
+/// The buffers grow to the size needed by the largest graph they are used with, after which
+/// evaluations and training steps do not allocate them again. A scratch can be used with any
+/// graph.
+#[derive(Clone, Debug, Default)]
+pub struct EvalScratch {
+    // The concatenated outputs, or the gradients over the outputs, of all nodes.
+    values: Vec<Scalar>,
+    // The inputs of the node being evaluated, or the gradients over them.
+    node_values: Vec<Scalar>,
+}
+
+impl EvalScratch {
+    /// Creates a scratch with empty buffers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// The intermediate values of an evaluation of a [`Graph`].
+pub struct GraphInter<const NUM_OUT: usize> {
+    // The evaluation of every node, by index.
+    nodes: Vec<NodeInter>,
+    output: [Scalar; NUM_OUT],
+}
+
+struct NodeInter {
+    inputs: Vec<Scalar>,
+    outputs: Vec<Scalar>,
+    inter: Box<dyn Any>,
+}
+
+impl<const NUM_OUT: usize> Intermediate for GraphInter<NUM_OUT> {
+    type Out = [Scalar; NUM_OUT];
+
+    fn output(&self) -> &Self::Out {
+        &self.output
+    }
+
+    fn into_output(self) -> Self::Out {
+        self.output
+    }
+}
+
+impl<const NUM_IN: usize, const NUM_OUT: usize> Graph<NUM_IN, NUM_OUT> {
+    /// Returns the number of nodes in the graph.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns whether the graph has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    // Concatenates the values of `sources` into `values`.
+    fn gather<'a>(
+        sources: &[Source],
+        inputs: &'a [Scalar],
+        outputs: impl Fn(usize) -> &'a [Scalar],
+        values: &mut Vec<Scalar>,
+    ) {
+        values.clear();
+        for source in sources {
+            match *source {
+                Source::Input => values.extend_from_slice(inputs),
+                Source::Node(id) => values.extend_from_slice(outputs(id.0)),
+            }
+        }
+    }
+
+    // Adds the gradients over the concatenated values of `sources` to the gradients of each
+    // source, where the gradients of the nodes are concatenated like their outputs.
+    fn scatter(
+        &self,
+        sources: &[Source],
+        gradients: &[Scalar],
+        input_grads: &mut [Scalar],
+        node_grads: &mut [Scalar],
+    ) {
+        let mut gradients = gradients;
+        for source in sources {
+            let target = match *source {
+                Source::Input => &mut *input_grads,
+                Source::Node(id) => &mut node_grads[self.range(id.0)],
+            };
+            let (head, tail) = gradients.split_at(target.len());
+            for (t, g) in target.iter_mut().zip(head) {
+                *t += g;
+            }
+            gradients = tail;
+        }
+    }
+
+    // The range of the outputs of node `i` in the concatenated outputs of all nodes.
+    fn range(&self, i: usize) -> std::ops::Range<usize> {
+        self.offsets[i]..self.offsets[i + 1]
+    }
+
+    /// Evaluates the graph like [`Network::eval()`], using the buffers of `scratch` instead of
+    /// allocating them.
+    pub fn eval_with(
+        &self,
+        inputs: &[Scalar; NUM_IN],
+        scratch: &mut EvalScratch,
+    ) -> [Scalar; NUM_OUT] {
+        let EvalScratch {
+            values,
+            node_values,
+        } = scratch;
+        values.clear();
+        values.resize(self.offsets[self.nodes.len()], 0.0);
+        for &i in &self.order {
+            let entry = &self.nodes[i];
+            Self::gather(
+                &entry.sources,
+                inputs,
+                |j| &values[self.range(j)],
+                node_values,
+            );
+            entry
+                .node
+                .eval_into(node_values, &mut values[self.range(i)]);
+        }
+        Self::gather(
+            &self.outputs,
+            inputs,
+            |j| &values[self.range(j)],
+            node_values,
+        );
+        *as_array(node_values)
+    }
+
+    /// Trains the graph like [`Network::train_deriv()`], using the buffers of `scratch` instead
+    /// of allocating them.
+    pub fn train_deriv_with(
+        &mut self,
+        intermediate: &GraphInter<NUM_OUT>,
+        gradients: &[Scalar; NUM_OUT],
+        learning_rate: Scalar,
+        scratch: &mut EvalScratch,
+    ) -> [Scalar; NUM_IN] {
+        let EvalScratch {
+            values: node_grads,
+            node_values: grads,
+        } = scratch;
+        let mut input_grads = [0.0; NUM_IN];
+        node_grads.clear();
+        node_grads.resize(self.offsets[self.nodes.len()], 0.0);
+        self.scatter(&self.outputs, gradients, &mut input_grads, node_grads);
+        for &i in self.order.iter().rev() {
+            let node = &intermediate.nodes[i];
+            let range = self.range(i);
+            let entry = &mut self.nodes[i];
+            grads.clear();
+            grads.resize(node.inputs.len(), 0.0);
+            entry.node.backward_into(
+                &node.inputs,
+                node.inter.as_ref(),
+                &node_grads[range],
+                learning_rate,
+                grads,
+            );
+            self.scatter(&self.nodes[i].sources, grads, &mut input_grads, node_grads);
+        }
+        input_grads
+    }
+}
+
+impl<const NUM_IN: usize, const NUM_OUT: usize> Network for Graph<NUM_IN, NUM_OUT> {
+    type In = [Scalar; NUM_IN];
+
+    type Out = [Scalar; NUM_OUT];
+
+    type Inter = GraphInter<NUM_OUT>;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        let mut nodes: Vec<Option<NodeInter>> = (0..self.nodes.len()).map(|_| None).collect();
+        for &i in &self.order {
+            let entry = &self.nodes[i];
+            let mut node_inputs = Vec::new();
+            Self::gather(
+                &entry.sources,
+                inputs,
+                |j| {
+                    &nodes[j]
+                        .as_ref()
+                        .expect("Sources should be evaluated first.")
+                        .outputs
+                },
+                &mut node_inputs,
+            );
+            let (outputs, inter) = entry.node.forward(&node_inputs);
+            nodes[i] = Some(NodeInter {
+                inputs: node_inputs,
+                outputs,
+                inter,
+            });
+        }
+        let nodes: Vec<_> = nodes
+            .into_iter()
+            .map(|node| node.expect("Every node should be evaluated."))
+            .collect();
+        let mut output = Vec::new();
+        Self::gather(&self.outputs, inputs, |j| &nodes[j].outputs, &mut output);
+        GraphInter {
+            output: *as_array(&output),
+            nodes,
+        }
+    }
+
+    fn intermediate_into(&self, inputs: &Self::In, intermediate: &mut Self::Inter) {
+        let nodes = &mut intermediate.nodes;
+        if nodes.len() != self.nodes.len() {
+            *intermediate = self.intermediate(inputs);
+            return;
+        }
+        for &i in &self.order {
+            let entry = &self.nodes[i];
+            let mut node_inputs = std::mem::take(&mut nodes[i].inputs);
+            Self::gather(
+                &entry.sources,
+                inputs,
+                |j| &nodes[j].outputs,
+                &mut node_inputs,
+            );
+            let node = &mut nodes[i];
+            node.inputs = node_inputs;
+            entry
+                .node
+                .forward_into(&node.inputs, &mut node.outputs, &mut node.inter);
+        }
+        let mut output = intermediate.output.as_mut_slice();
+        for source in &self.outputs {
+            let values = match *source {
+                Source::Input => &inputs[..],
+                Source::Node(id) => &nodes[id.0].outputs,
+            };
+            let (head, tail) = output.split_at_mut(values.len());
+            head.copy_from_slice(values);
+            output = tail;
+        }
+    }
+
+    fn train_deriv(
+        &mut self,
+        _inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        self.train_deriv_with(
+            intermediate,
+            gradients,
+            learning_rate,
+            &mut EvalScratch::new(),
+        )
+    }
+
+    fn eval(&self, inputs: &Self::In) -> Self::Out {
+        self.eval_with(inputs, &mut EvalScratch::new())
+    }
+}
@@ -0,0 +1,227 @@
+use std::{
+    any::Any,
+    cell::{Ref, RefCell},
+    fmt::{self, Debug},
+    rc::Rc,
+};
+
+use crate::{
+    inspect::{DotGraph, Inspect, LayerView},
+    params::Parameterized,
+    Intermediate, Network, Scalar, Supervised,
+};
+
+/**
+A network with a forward hook, which is called with the inputs and outputs of the network every
+time it is evaluated.
+
+Hooks observe a layer without modifying its code, e.g. to record activation histograms, detect
+dead neurons or visualize features. As evaluation only borrows the network immutably, the hook is
+a [`Fn`]; use a [`Recorder`] (or another type with interior mutability) to collect values.
+
+# Examples
+```rust
+use rann_traits::{compose::Recorder, Network};
+use rann_base::{Full, activ::LeakyRelu};
+
+let gen = (|i, _| if i == 0 { 1.0 } else { -1.0 }, |_| 0.0);
+let hidden = Recorder::new();
+let net = Full::<2, 2, _>::new(LeakyRelu(0.0), gen)
+    .hook(hidden.outputs())
+    .chain(Full::<2, 1, _>::new(LeakyRelu(0.0), gen));
+
+for inputs in [[1.0, 0.5], [0.2, 0.3], [2.0, 0.0]] {
+    net.eval(&inputs);
+}
+
+// The second hidden neuron never activates: it is dead.
+let dead: Vec<bool> = (0..2)
+    .map(|n| hidden.borrow().iter().all(|out| out[n] <= 0.0))
+    .collect();
+assert_eq!(dead, [false, true]);
+```
+*/
+#[derive(Clone)]
+pub struct Hooked<T, F> {
+    /// The hooked network.
+    pub inner: T,
+    /// The hook, called with the inputs and outputs of every evaluation.
+    pub hook: F,
+}
+
+impl<T, F> Hooked<T, F> {
+    /// Hooks `hook` into `net`.
+    pub fn new(net: T, hook: F) -> Self {
+        Self { inner: net, hook }
+    }
+}
+
+impl<T: Debug, F> Debug for Hooked<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hooked")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, F> Network for Hooked<T, F>
+where
+    T: Network,
+    F: Fn(&T::In, &T::Out),
+{
+    type In = T::In;
+
+    type Out = T::Out;
+
+    type Inter = T::Inter;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        let intermediate = self.inner.intermediate(inputs);
+        (self.hook)(inputs, intermediate.output());
+        intermediate
+    }
+
+    fn intermediate_into(&self, inputs: &Self::In, intermediate: &mut Self::Inter) {
+        self.inner.intermediate_into(inputs, intermediate);
+        (self.hook)(inputs, intermediate.output());
+    }
+
+    fn train_deriv(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        self.inner
+            .train_deriv(inputs, intermediate, gradients, learning_rate)
+    }
+
+    fn find_layer(&self, name: &str) -> Option<&dyn Any> {
+        self.inner.find_layer(name)
+    }
+
+    fn find_layer_mut(&mut self, name: &str) -> Option<&mut dyn Any> {
+        self.inner.find_layer_mut(name)
+    }
+}
+
+impl<T, F> Supervised for Hooked<T, F>
+where
+    T: Supervised,
+    F: Fn(&T::In, &T::Out),
+{
+    type Target = T::Target;
+
+    fn set_target(&mut self, target: &Self::Target) {
+        self.inner.set_target(target);
+    }
+}
+
+impl<T, F> Inspect for Hooked<T, F>
+where
+    T: Inspect,
+    F: Fn(&T::In, &T::Out),
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        self.inner.visit_layers(intermediate, f);
+    }
+
+    fn write_dot(
+        &self,
+        intermediate: &Self::Inter,
+        dot: &mut DotGraph,
+        inputs: Vec<usize>,
+    ) -> Vec<usize> {
+        self.inner.write_dot(intermediate, dot, inputs)
+    }
+}
+
+impl<T, F> Parameterized for Hooked<T, F>
+where
+    T: Parameterized,
+{
+    fn num_params(&self) -> usize {
+        self.inner.num_params()
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        self.inner.write_params(params);
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        self.inner.read_params(params);
+    }
+}
+
+/// A list of values recorded by hooks, see [`Hooked`].
+///
+/// Cloning a [`Recorder`] creates another handle to the same list, such that a clone can be
+/// moved into a hook while the original is used to read the recorded values.
+#[derive(Debug)]
+pub struct Recorder<T>(Rc<RefCell<Vec<T>>>);
+
+impl<T> Recorder<T> {
+    /// Creates an empty recorder.
+    pub fn new() -> Self {
+        Self(Rc::default())
+    }
+
+    /// Appends `value` to the recorded values.
+    pub fn record(&self, value: T) {
+        self.0.borrow_mut().push(value);
+    }
+
+    /// Borrows the recorded values, in order of recording.
+    ///
+    /// # Panics
+    /// Panics if a value is being recorded.
+    pub fn borrow(&self) -> Ref<'_, Vec<T>> {
+        self.0.borrow()
+    }
+
+    /// Removes and returns the recorded values, in order of recording.
+    pub fn take(&self) -> Vec<T> {
+        self.0.take()
+    }
+
+    /// Returns the number of recorded values.
+    pub fn len(&self) -> usize {
+        self.0.borrow().len()
+    }
+
+    /// Returns whether no values have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a hook recording the outputs of the hooked network.
+    pub fn outputs<I: ?Sized>(&self) -> impl Fn(&I, &T)
+    where
+        T: Clone,
+    {
+        let recorder = self.clone();
+        move |_, outputs| recorder.record(outputs.clone())
+    }
+
+    /// Returns a hook recording the inputs of the hooked network.
+    pub fn inputs<O: ?Sized>(&self) -> impl Fn(&T, &O)
+    where
+        T: Clone,
+    {
+        let recorder = self.clone();
+        move |inputs, _| recorder.record(inputs.clone())
+    }
+}
+
+impl<T> Clone for Recorder<T> {
+    fn clone(&self) -> Self {
+        Self(Rc::clone(&self.0))
+    }
+}
+
+impl<T> Default for Recorder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,12 +1,27 @@
 /*! Network composition.
 
-If you want to build a network, such as by connecting different layers or networks, then you
-have come to the right place! This module provides methods to compose networks in different
-ways, such as chaining and zipping.
+If you want to build a network, such as by connecting different layers or networks, then you have
+come to the right place! This module provides methods to compose networks in different ways, such as
+chaining, zipping and [`Repeat`]ing, or in an arbitrary [`Graph`] built at runtime. Networks can
+also be used at multiple places at once by [`Shared`] networks, and be given a name by [`Named`] to
+find them in a composed network. A network can be observed during evaluation by [`Hooked`]. A
+[`TimeDistributed`] network applies a network to every step of a sequence.
 */
 
 pub mod zip;
 pub mod chain;
+pub mod graph;
+pub mod hook;
+pub mod named;
+pub mod repeat;
+pub mod shared;
+pub mod time;
 
 pub use chain::*;
-pub use zip::{Zip, ZipInter};
+pub use graph::Graph;
+pub use hook::{Hooked, Recorder};
+pub use named::Named;
+pub use repeat::{Repeat, RepeatInter};
+pub use shared::Shared;
+pub use time::{TimeDistributed, TimeDistributedInter};
+pub use zip::{Zip, ZipInter, ZipOwned};
@@ -0,0 +1,149 @@
+use std::{any::Any, borrow::Cow};
+
+use crate::{
+    inspect::{DotGraph, Inspect, LayerView},
+    params::Parameterized,
+    Network, Scalar, Supervised,
+};
+
+/**
+A network with a name, such that it can be found in a composed network.
+
+Names are looked up using [`Network::layer()`] and [`Network::layer_mut()`], which search
+composed networks in order and return the first network with the given name. Networks inside a
+[`Shared`](super::Shared) network cannot be found, as they cannot be borrowed for long enough.
+
+# Examples
+```rust
+use rann_traits::Network;
+use rann_base::{Full, activ::Logistic};
+
+let gen = (|_, _| 0.5, |_| 0.0);
+let mut net = Full::<2, 3, _>::new(Logistic, gen)
+    .chain(Full::<3, 3, _>::new(Logistic, gen).named("hidden"))
+    .chain(Full::<3, 1, _>::new(Logistic, gen));
+
+// No need for `net.first.second.inner`.
+let hidden = net.layer_mut::<Full<3, 3, Logistic>>("hidden").unwrap();
+*hidden = Full::new(Logistic, (|_, _| 0.0, |_| 0.0));
+assert_eq!(net.first.second.eval(&[1.0; 3]), [0.5; 3]);
+```
+*/
+#[derive(Clone, Debug)]
+pub struct Named<T> {
+    /// The name of the network.
+    pub name: Cow<'static, str>,
+    /// The named network.
+    pub inner: T,
+}
+
+impl<T> Named<T> {
+    /// Names `net` with `name`.
+    pub fn new(net: T, name: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            name: name.into(),
+            inner: net,
+        }
+    }
+}
+
+impl<T> Network for Named<T>
+where
+    T: Network + 'static,
+{
+    type In = T::In;
+
+    type Out = T::Out;
+
+    type Inter = T::Inter;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        self.inner.intermediate(inputs)
+    }
+
+    fn intermediate_into(&self, inputs: &Self::In, intermediate: &mut Self::Inter) {
+        self.inner.intermediate_into(inputs, intermediate);
+    }
+
+    fn train_deriv(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        self.inner
+            .train_deriv(inputs, intermediate, gradients, learning_rate)
+    }
+
+    fn eval(&self, inputs: &Self::In) -> Self::Out {
+        self.inner.eval(inputs)
+    }
+
+    fn eval_many(&self, inputs: &[Self::In]) -> Vec<Self::Out> {
+        self.inner.eval_many(inputs)
+    }
+
+    // Named networks can contain other named networks.
+    fn find_layer(&self, name: &str) -> Option<&dyn Any> {
+        if self.name == name {
+            Some(&self.inner)
+        } else {
+            self.inner.find_layer(name)
+        }
+    }
+
+    fn find_layer_mut(&mut self, name: &str) -> Option<&mut dyn Any> {
+        if self.name == name {
+            Some(&mut self.inner)
+        } else {
+            self.inner.find_layer_mut(name)
+        }
+    }
+}
+
+impl<T> Supervised for Named<T>
+where
+    T: Supervised + 'static,
+{
+    type Target = T::Target;
+
+    fn set_target(&mut self, target: &Self::Target) {
+        self.inner.set_target(target);
+    }
+}
+
+impl<T> Inspect for Named<T>
+where
+    T: Inspect + 'static,
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        self.inner.visit_layers(intermediate, f);
+    }
+
+    fn write_dot(
+        &self,
+        intermediate: &Self::Inter,
+        dot: &mut DotGraph,
+        inputs: Vec<usize>,
+    ) -> Vec<usize> {
+        self.inner.write_dot(intermediate, dot, inputs)
+    }
+}
+
+impl<T> Parameterized for Named<T>
+where
+    T: Parameterized,
+{
+    fn num_params(&self) -> usize {
+        self.inner.num_params()
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        self.inner.write_params(params);
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        self.inner.read_params(params);
+    }
+}
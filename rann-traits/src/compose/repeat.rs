@@ -0,0 +1,201 @@
+use std::any::Any;
+
+use arrayvec::ArrayVec;
+
+use crate::{Intermediate, Network, Scalar};
+
+/**
+Chains `N` layers of the same type, each with their own parameters.
+
+Deep networks of identical layers would otherwise have nested types such as
+`Chain<Chain<Chain<T, T>, T>, T>`. The layers of a [`Repeat`] are stored in an array instead,
+such that they can be iterated over.
+
+# Examples
+```rust
+use rann_traits::{compose::Repeat, Network};
+use rann_base::{Full, activ::Logistic, testing};
+
+// Ten hidden layers, each initialized with a different seed.
+let hidden = Repeat::<_, 10>::new(|i| Full::<4, 4, _>::new(Logistic, testing::seeded_gen(i as u64)));
+let mut net = Full::<2, 4, _>::new(Logistic, testing::seeded_gen(10))
+    .chain(hidden)
+    .chain(Full::<4, 1, _>::new(Logistic, testing::seeded_gen(11)));
+
+let inter = net.intermediate(&[1.0, 0.0]);
+net.train(&[1.0, 0.0], &inter, 0.1);
+
+for layer in net.first.second.iter() {
+    assert_eq!(layer.eval(&[0.0; 4]).len(), 4);
+}
+```
+*/
+#[derive(Clone, Debug)]
+pub struct Repeat<T, const N: usize> {
+    /// The layers, in evaluation order.
+    pub layers: [T; N],
+}
+
+impl<T, const N: usize> Repeat<T, N> {
+    /// Creates `N` layers using `layer`, which is called with the index of each layer.
+    ///
+    /// # Panics
+    /// Panics if `N` is zero.
+    pub fn new(layer: impl FnMut(usize) -> T) -> Self {
+        Self::from_layers(std::array::from_fn(layer))
+    }
+
+    /// Chains `layers`, in order.
+    ///
+    /// # Panics
+    /// Panics if `N` is zero.
+    pub fn from_layers(layers: [T; N]) -> Self {
+        assert!(N > 0, "A Repeat should have at least one layer.");
+        Self { layers }
+    }
+
+    /// Iterates over the layers, in evaluation order.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.layers.iter()
+    }
+
+    /// Mutably iterates over the layers, in evaluation order.
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.layers.iter_mut()
+    }
+}
+
+impl<T, const N: usize> Network for Repeat<T, N>
+where
+    T: Network<Out = <T as Network>::In>,
+{
+    type In = T::In;
+
+    type Out = T::Out;
+
+    type Inter = RepeatInter<T::Inter, N>;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        let mut inters = ArrayVec::<T::Inter, N>::new();
+        for layer in &self.layers {
+            // Each layer uses the outputs of the previous layer.
+            let inputs = inters.last().map_or(inputs, Intermediate::output);
+            inters.push(layer.intermediate(inputs));
+        }
+        RepeatInter {
+            layers: inters
+                .into_inner()
+                .unwrap_or_else(|_| panic!("Capacity of ArrayVec should equal N.")),
+        }
+    }
+
+    fn eval_many(&self, inputs: &[Self::In]) -> Vec<Self::Out> {
+        let mut outputs = self.layers[0].eval_many(inputs);
+        for layer in &self.layers[1..] {
+            outputs = layer.eval_many(&outputs);
+        }
+        outputs
+    }
+
+    fn train_deriv(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        let layer_inputs = |i: usize| {
+            if i == 0 {
+                inputs
+            } else {
+                intermediate.layers[i - 1].output()
+            }
+        };
+        // Train the last layer on the gradients...
+        let mut grads = self.layers[N - 1].train_deriv(
+            layer_inputs(N - 1),
+            &intermediate.layers[N - 1],
+            gradients,
+            learning_rate,
+        );
+        // ...and each preceding layer on the gradients of the next.
+        for i in (0..N - 1).rev() {
+            grads = self.layers[i].train_deriv(
+                layer_inputs(i),
+                &intermediate.layers[i],
+                &grads,
+                learning_rate,
+            );
+        }
+        grads
+    }
+
+    fn find_layer(&self, name: &str) -> Option<&dyn Any> {
+        self.layers.iter().find_map(|layer| layer.find_layer(name))
+    }
+
+    fn find_layer_mut(&mut self, name: &str) -> Option<&mut dyn Any> {
+        self.layers
+            .iter_mut()
+            .find_map(|layer| layer.find_layer_mut(name))
+    }
+}
+
+impl<T, const N: usize> std::ops::Index<usize> for Repeat<T, N> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.layers[index]
+    }
+}
+
+impl<T, const N: usize> std::ops::IndexMut<usize> for Repeat<T, N> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.layers[index]
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a Repeat<T, N> {
+    type Item = &'a T;
+
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut Repeat<T, N> {
+    type Item = &'a mut T;
+
+    type IntoIter = std::slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// The intermediate values of an evaluation of a [`Repeat`].
+pub struct RepeatInter<T, const N: usize> {
+    /// The intermediate calculation of each layer.
+    pub layers: [T; N],
+}
+
+impl<T, const N: usize> Intermediate for RepeatInter<T, N>
+where
+    T: Intermediate,
+{
+    type Out = T::Out;
+
+    fn output(&self) -> &Self::Out {
+        self.layers[N - 1].output()
+    }
+
+    fn into_output(self) -> Self::Out {
+        self.layers
+            .into_iter()
+            .last()
+            .expect("A Repeat should have at least one layer.")
+            .into_output()
+    }
+}
@@ -0,0 +1,118 @@
+use std::{
+    cell::{Ref, RefCell, RefMut},
+    rc::Rc,
+};
+
+use crate::{Network, Scalar};
+
+/**
+A network that can appear at multiple places in a composed network, sharing its parameters.
+
+Cloning a [`Shared`] does not clone the network, but creates another handle to the same network.
+Every use trains the same parameters, so the gradients of all uses accumulate in the network.
+
+# Implementation note
+The updates of each use are applied one after another during backpropagation, instead of being
+summed and applied at once. For small learning rates, both are equivalent.
+
+# Examples
+```rust
+use rann_traits::{compose::Shared, Network};
+use rann_base::{Full, activ::Logistic};
+
+let gen = (|_, _| 0.5, |_| 0.0);
+let layer = Shared::new(Full::<3, 3, _>::new(Logistic, gen));
+
+// Apply the same layer twice.
+let mut net = layer.clone().chain(layer.clone());
+
+let inputs = [1.0, 0.0, -1.0];
+let inter = net.intermediate(&inputs);
+net.train(&inputs, &inter, 0.1);
+
+// Both uses refer to the same, updated, layer.
+assert_eq!(net.first.eval(&inputs), layer.eval(&inputs));
+```
+*/
+#[derive(Debug, Default)]
+pub struct Shared<T>(Rc<RefCell<T>>);
+
+impl<T> Shared<T> {
+    /// Wraps `net` so it can be shared.
+    pub fn new(net: T) -> Self {
+        Self(Rc::new(RefCell::new(net)))
+    }
+
+    /// Immutably borrows the shared network.
+    ///
+    /// # Panics
+    /// Panics if the network is currently mutably borrowed.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.0.borrow()
+    }
+
+    /// Mutably borrows the shared network.
+    ///
+    /// # Panics
+    /// Panics if the network is currently borrowed.
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        self.0.borrow_mut()
+    }
+
+    /// Returns the shared network if this is the only remaining handle, or `self` otherwise.
+    pub fn try_into_inner(self) -> Result<T, Self> {
+        Rc::try_unwrap(self.0)
+            .map(RefCell::into_inner)
+            .map_err(Self)
+    }
+
+    /// Returns the number of handles to the shared network.
+    pub fn uses(&self) -> usize {
+        Rc::strong_count(&self.0)
+    }
+}
+
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Self(Rc::clone(&self.0))
+    }
+}
+
+impl<T> Network for Shared<T>
+where
+    T: Network,
+{
+    type In = T::In;
+
+    type Out = T::Out;
+
+    type Inter = T::Inter;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        self.0.borrow().intermediate(inputs)
+    }
+
+    fn intermediate_into(&self, inputs: &Self::In, intermediate: &mut Self::Inter) {
+        self.0.borrow().intermediate_into(inputs, intermediate);
+    }
+
+    fn train_deriv(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        self.0
+            .borrow_mut()
+            .train_deriv(inputs, intermediate, gradients, learning_rate)
+    }
+
+    fn eval(&self, inputs: &Self::In) -> Self::Out {
+        self.0.borrow().eval(inputs)
+    }
+
+    fn eval_many(&self, inputs: &[Self::In]) -> Vec<Self::Out> {
+        self.0.borrow().eval_many(inputs)
+    }
+}
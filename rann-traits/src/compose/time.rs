@@ -0,0 +1,123 @@
+use std::any::Any;
+
+use crate::{Intermediate, Network, Scalar};
+
+/**
+Applies a network independently to every step of a sequence of `N` steps.
+
+A [`TimeDistributed`] network takes an array of the inputs of every step, and outputs an array of
+the outputs of the wrapped network for every step. All steps share the parameters of the wrapped
+network, which is trained on the gradients of every step. This connects feed-forward networks to
+recurrent ones, such as a fully connected layer that reads out the state of a recurrent network
+after every step.
+
+# Examples
+```rust
+use rann_traits::{compose::TimeDistributed, Network};
+use rann_base::{activ::Logistic, recurrent::{Gru, Recurrent}, testing, Full};
+
+let mut net = Recurrent::<_, 4>::new(Gru::<2, 3>::new(testing::seeded_gen(1)))
+    .chain(TimeDistributed::<_, 4>::new(Full::<3, 1, _>::new(Logistic, testing::seeded_gen(2))));
+let inputs = [[1.0, 0.0], [0.0, 1.0], [1.0, 1.0], [0.0, 0.0]];
+let outputs = net.eval(&inputs);
+assert_eq!(outputs[2], net.second.network.eval(&net.first.eval(&inputs)[2]));
+
+let inter = net.intermediate(&inputs);
+net.train_deriv(&inputs, &inter, &[[1.0]; 4], 0.1);
+```
+*/
+#[derive(Clone, Debug)]
+pub struct TimeDistributed<T, const N: usize> {
+    /// The network that is applied to every step.
+    pub network: T,
+}
+
+impl<T, const N: usize> TimeDistributed<T, N> {
+    /// Applies `network` to every step of sequences of `N` steps.
+    pub fn new(network: T) -> Self {
+        Self { network }
+    }
+}
+
+impl<T, const N: usize> Network for TimeDistributed<T, N>
+where
+    T: Network,
+    T::Out: Clone,
+{
+    type In = [T::In; N];
+
+    type Out = [T::Out; N];
+
+    type Inter = TimeDistributedInter<T::Inter, T::Out, N>;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        let steps: [T::Inter; N] = std::array::from_fn(|t| self.network.intermediate(&inputs[t]));
+        TimeDistributedInter {
+            outputs: std::array::from_fn(|t| steps[t].output().clone()),
+            steps,
+        }
+    }
+
+    fn eval(&self, inputs: &Self::In) -> Self::Out {
+        // All steps are evaluated at once.
+        let mut outputs = self.network.eval_many(inputs).into_iter();
+        std::array::from_fn(|_| outputs.next().expect("There should be N outputs."))
+    }
+
+    fn eval_many(&self, inputs: &[Self::In]) -> Vec<Self::Out> {
+        // The steps of all sequences are evaluated at once.
+        let mut outputs = self.network.eval_many(inputs.as_flattened()).into_iter();
+        inputs
+            .iter()
+            .map(|_| std::array::from_fn(|_| outputs.next().expect("There should be N outputs.")))
+            .collect()
+    }
+
+    fn train_deriv(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        std::array::from_fn(|t| {
+            self.network.train_deriv(
+                &inputs[t],
+                &intermediate.steps[t],
+                &gradients[t],
+                learning_rate,
+            )
+        })
+    }
+
+    fn find_layer(&self, name: &str) -> Option<&dyn Any> {
+        self.network.find_layer(name)
+    }
+
+    fn find_layer_mut(&mut self, name: &str) -> Option<&mut dyn Any> {
+        self.network.find_layer_mut(name)
+    }
+}
+
+/// The intermediate values of an evaluation of a [`TimeDistributed`] network.
+#[derive(Clone, Debug)]
+pub struct TimeDistributedInter<T, O, const N: usize> {
+    /// The intermediate calculation of every step.
+    pub steps: [T; N],
+    outputs: [O; N],
+}
+
+impl<T, O, const N: usize> Intermediate for TimeDistributedInter<T, O, N>
+where
+    T: Intermediate<Out = O>,
+{
+    type Out = [O; N];
+
+    fn output(&self) -> &Self::Out {
+        &self.outputs
+    }
+
+    fn into_output(self) -> Self::Out {
+        self.outputs
+    }
+}
@@ -1,3 +1,5 @@
+use std::any::Any;
+
 use arrayvec::ArrayVec;
 
 use crate::{Intermediate, Network, Scalar};
@@ -7,7 +9,7 @@ use crate::{Intermediate, Network, Scalar};
 /// - `T` and `U` represent the zipped networks.
 /// - `Z` is a function that combines the outputs of both networks into one.
 /// - `UnZ` must do exactly the reverse of `Z`: take the combined outputs of the networks and pull
-/// them apart.
+///   them apart.
 #[derive(Debug, Clone)]
 pub struct Zip<T, U, Z, UnZ> {
     pub top: T,
@@ -61,9 +63,111 @@ where
         // Combine gradients.
         (top, bot)
     }
+
+    fn find_layer(&self, name: &str) -> Option<&dyn Any> {
+        self.top
+            .find_layer(name)
+            .or_else(|| self.bot.find_layer(name))
+    }
+
+    fn find_layer_mut(&mut self, name: &str) -> Option<&mut dyn Any> {
+        match self.top.find_layer_mut(name) {
+            Some(layer) => Some(layer),
+            None => self.bot.find_layer_mut(name),
+        }
+    }
+}
+
+/// Zip two parallel networks into the same output, like [`Zip`], but with an unzipper that
+/// returns owned gradients.
+///
+/// The unzipper of a [`Zip`] must return references into the combined gradients, so it can only
+/// pull apart outputs that contain the outputs of both networks, such as [`Stacker`]. Zippers
+/// whose gradients have to be computed, such as [`blend()`], need a `ZipOwned`.
+///
+/// # Type parameters
+/// - `T` and `U` represent the zipped networks.
+/// - `Z` is a function that combines the outputs of both networks into one.
+/// - `UnZ` takes the gradients over the combined outputs, and returns the gradients over the
+///   outputs of both networks.
+///
+/// # Examples
+/// ```rust
+/// use rann_base::{Full, activ::Logistic, testing};
+/// use rann_traits::{compose::zip, Network};
+///
+/// let top = Full::<2, 1, _>::new(Logistic, testing::seeded_gen(1));
+/// let bot = Full::<2, 1, _>::new(Logistic, testing::seeded_gen(2));
+/// let (a, b) = (top.eval(&[1.0, 0.0])[0], bot.eval(&[0.0, 1.0])[0]);
+/// // Three quarters of the top network, and one quarter of the bottom network.
+/// let net = top.zip_owned(bot, zip::blend(0.75));
+/// let [blended] = net.eval(&([1.0, 0.0], [0.0, 1.0]));
+/// assert!((blended - (0.75 * a + 0.25 * b)).abs() < 1e-6);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ZipOwned<T, U, Z, UnZ> {
+    pub top: T,
+    pub bot: U,
+    pub zipper: Z,
+    pub unzipper: UnZ,
 }
 
-/// The intermediate values of an evaluation of a [`Zip`].
+impl<T, U, Z, UnZ, C> Network for ZipOwned<T, U, Z, UnZ>
+where
+    T: Network,
+    U: Network,
+    Z: Fn(&T::Out, &U::Out) -> C,
+    UnZ: Fn(&C) -> (T::Out, U::Out),
+{
+    type In = (T::In, U::In);
+
+    type Out = C;
+
+    type Inter = ZipInter<T::Inter, U::Inter, C>;
+
+    fn intermediate(&self, input: &Self::In) -> Self::Inter {
+        let top = self.top.intermediate(&input.0);
+        let bot = self.bot.intermediate(&input.1);
+        ZipInter {
+            zipped: (self.zipper)(top.output(), bot.output()),
+            top,
+            bot,
+        }
+    }
+
+    fn train_deriv(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        // Compute the gradients over the outputs of both networks.
+        let (top_gr, bot_gr) = (self.unzipper)(gradients);
+        let top = self
+            .top
+            .train_deriv(&inputs.0, &intermediate.top, &top_gr, learning_rate);
+        let bot = self
+            .bot
+            .train_deriv(&inputs.1, &intermediate.bot, &bot_gr, learning_rate);
+        (top, bot)
+    }
+
+    fn find_layer(&self, name: &str) -> Option<&dyn Any> {
+        self.top
+            .find_layer(name)
+            .or_else(|| self.bot.find_layer(name))
+    }
+
+    fn find_layer_mut(&mut self, name: &str) -> Option<&mut dyn Any> {
+        match self.top.find_layer_mut(name) {
+            Some(layer) => Some(layer),
+            None => self.bot.find_layer_mut(name),
+        }
+    }
+}
+
+/// The intermediate values of an evaluation of a [`Zip`] or a [`ZipOwned`].
 #[derive(Debug)]
 pub struct ZipInter<T, U, Z> {
     /// The intermediate values of the top network.
@@ -92,24 +196,12 @@ where
 
 // Zippers
 
-/// Stacks and unstacks constant arrays.
-#[derive(Clone, Copy, Debug)]
-pub struct Stacker<const A: usize, const B: usize, const SUM: usize>;
-
-impl<const A: usize, const B: usize, const SUM: usize>
-    Into<(
+crate::function_tuple! {
+    /// Stacks and unstacks constant arrays.
+    pub struct Stacker<const A: usize, const B: usize, const SUM: usize>: (
         fn(&[Scalar; A], &[Scalar; B]) -> [Scalar; SUM],
         fn(&[Scalar; SUM]) -> (&[Scalar; A], &[Scalar; B]),
-    )> for Stacker<A, B, SUM>
-{
-    fn into(
-        self,
-    ) -> (
-        for<'a, 'b> fn(&'a [f32; A], &'b [f32; B]) -> [f32; SUM],
-        for<'a> fn(&'a [f32; SUM]) -> (&'a [f32; A], &'a [f32; B]),
-    ) {
-        (stacked, unstacked)
-    }
+    ) = (stacked, unstacked);
 }
 
 /// Stacks the vectors.
@@ -119,7 +211,7 @@ pub fn stacked<const A: usize, const B: usize, const SUM: usize>(
 ) -> [Scalar; SUM] {
     top.iter()
         .chain(bot)
-        .map(|x| *x)
+        .copied()
         .collect::<ArrayVec<Scalar, SUM>>()
         .into_inner()
         .expect("SUM should be A + B.")
@@ -132,3 +224,26 @@ pub fn unstacked<const A: usize, const B: usize, const SUM: usize>(
     let (a, b) = x.split_at(A);
     (a.try_into().unwrap(), b.try_into().unwrap())
 }
+
+/// Blends the outputs of two networks of the same size, as `weight * top + (1 - weight) * bot`.
+/// The gradients are computed, so the blend must be zipped with [`ZipOwned`].
+// The closures capture the weight, so their types cannot be named.
+#[allow(clippy::type_complexity)]
+pub fn blend<const N: usize>(
+    weight: Scalar,
+) -> (
+    impl Fn(&[Scalar; N], &[Scalar; N]) -> [Scalar; N] + Clone,
+    impl Fn(&[Scalar; N]) -> ([Scalar; N], [Scalar; N]) + Clone,
+) {
+    (
+        move |top: &[Scalar; N], bot: &[Scalar; N]| {
+            std::array::from_fn(|i| weight * top[i] + (1.0 - weight) * bot[i])
+        },
+        move |gradients: &[Scalar; N]| {
+            (
+                gradients.map(|g| weight * g),
+                gradients.map(|g| (1.0 - weight) * g),
+            )
+        },
+    )
+}
@@ -8,8 +8,49 @@ trait is essential for the RANN ecosystem.
 
 # [`Deriv`]ative
 A simpler, one-dimensional version of [`NDeriv`].
+
+# Elementwise functions
+[`Elementwise`] applies a one-dimensional [`Deriv`] to every element of a container of
+[`Elements`], such as an array or a vector, which is itself a [`Deriv`] of the container type.
+Layers use it to apply their activation to all of their weighted sums, and to backpropagate
+through it.
+
+```rust
+use rann_traits::deriv::{Deriv, Elementwise};
+
+let square = (|x: f32| x * x, |x: f32| 2.0 * x);
+let arrays = Elementwise::new(square);
+assert_eq!(arrays.call(&[1.0, 2.0]), [1.0, 4.0]);
+// The gradients over the inputs, from the gradients over the outputs.
+assert_eq!(arrays.backprop(&[1.0, 2.0], &[0.5, 1.0]), [1.0, 4.0]);
+let vectors = Elementwise::new(square);
+assert_eq!(vectors.deriv(&vec![1.0, 2.0]), vec![2.0, 4.0]);
+```
+
+# Checking derivatives
+Hand-derived derivatives are easy to get wrong, and a wrong derivative does not fail loudly: the
+network just trains badly. [`check_deriv()`] compares a derivative to the numerical derivative
+of its function over a domain.
+
+```rust
+use rann_traits::deriv::check_deriv;
+
+let square = (|x: f32| x * x, |x: f32| 2.0 * x);
+assert!(check_deriv(&square, -10.0..=10.0, 1e-3).is_ok());
+let wrong = (|x: f32| x * x, |x: f32| x);
+let mismatch = check_deriv(&wrong, -10.0..=10.0, 1e-3).unwrap_err();
+assert_eq!(mismatch.x, -10.0);
+```
+
+Functions that are correct for moderate inputs can still overflow for extreme ones, so
+[`check_finite()`] evaluates a function and its derivative at [`EXTREME_INPUTS`].
 */
-use std::ops::Index;
+use std::{
+    error::Error,
+    fmt::Display,
+    marker::PhantomData,
+    ops::{Index, RangeInclusive},
+};
 
 use crate::Scalar;
 
@@ -41,7 +82,178 @@ where
     fn deriv(&self, &x: &Self::In) -> Self::Out {
         self.1(x)
     }
+}
+
+// References to derivatives are derivatives too, such that layers can lend their activation.
+impl<T> Deriv for &T
+where
+    T: Deriv + ?Sized,
+{
+    type In = T::In;
+    type Out = T::Out;
+
+    fn call(&self, x: &Self::In) -> Self::Out {
+        (**self).call(x)
+    }
+
+    fn deriv(&self, x: &Self::In) -> Self::Out {
+        (**self).deriv(x)
+    }
+}
+
+/// A container of scalars, over which one-dimensional functions can be applied [`Elementwise`].
+pub trait Elements: Sized {
+    /// Returns the container of `f` applied to every element.
+    fn map_elements(&self, f: impl FnMut(Scalar) -> Scalar) -> Self;
+    /// Returns the container of `f` applied to every pair of elements of `self` and `other`.
+    ///
+    /// # Panics
+    /// May panic if the containers have different lengths.
+    fn zip_elements(&self, other: &Self, f: impl FnMut(Scalar, Scalar) -> Scalar) -> Self;
+}
+
+impl<const N: usize> Elements for [Scalar; N] {
+    fn map_elements(&self, f: impl FnMut(Scalar) -> Scalar) -> Self {
+        self.map(f)
+    }
+
+    fn zip_elements(&self, other: &Self, mut f: impl FnMut(Scalar, Scalar) -> Scalar) -> Self {
+        std::array::from_fn(|i| f(self[i], other[i]))
+    }
+}
+
+impl Elements for Vec<Scalar> {
+    fn map_elements(&self, f: impl FnMut(Scalar) -> Scalar) -> Self {
+        self.iter().copied().map(f).collect()
+    }
 
+    fn zip_elements(&self, other: &Self, mut f: impl FnMut(Scalar, Scalar) -> Scalar) -> Self {
+        assert_eq!(self.len(), other.len(), "Lengths should be equal.");
+        self.iter().zip(other).map(|(&a, &b)| f(a, b)).collect()
+    }
+}
+
+/// A one-dimensional function applied to every element of a container `C`. Its derivative is the
+/// derivative of every element with respect to itself, since the elements are independent.
+pub struct Elementwise<D, C> {
+    /// The one-dimensional function.
+    pub f: D,
+    container: PhantomData<fn(C) -> C>,
+}
+
+impl<D, C> Elementwise<D, C> {
+    /// Applies `f` elementwise.
+    pub fn new(f: D) -> Self {
+        Self {
+            f,
+            container: PhantomData,
+        }
+    }
+}
+
+impl<D, C> Elementwise<D, C>
+where
+    D: Deriv<In = Scalar, Out = Scalar>,
+    C: Elements,
+{
+    /// Returns the gradients over the inputs `x`, given the `gradients` over the outputs: the
+    /// elementwise product of the derivatives and the gradients.
+    pub fn backprop(&self, x: &C, gradients: &C) -> C {
+        x.zip_elements(gradients, |x, g| self.f.deriv(&x) * g)
+    }
+}
+
+impl<D: Clone, C> Clone for Elementwise<D, C> {
+    fn clone(&self) -> Self {
+        Self::new(self.f.clone())
+    }
+}
+
+impl<D: Copy, C> Copy for Elementwise<D, C> {}
+
+impl<D: std::fmt::Debug, C> std::fmt::Debug for Elementwise<D, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Elementwise").field(&self.f).finish()
+    }
+}
+
+impl<D, C> Deriv for Elementwise<D, C>
+where
+    D: Deriv<In = Scalar, Out = Scalar>,
+    C: Elements,
+{
+    type In = C;
+    type Out = C;
+
+    fn call(&self, x: &C) -> C {
+        x.map_elements(|x| self.f.call(&x))
+    }
+
+    fn deriv(&self, x: &C) -> C {
+        x.map_elements(|x| self.f.deriv(&x))
+    }
+}
+
+/// The number of evenly spaced points at which [`check_deriv()`] checks a derivative.
+/// The number is even, such that the center of a symmetric domain is not among them.
+pub const CHECK_POINTS: usize = 1000;
+
+/// Error describing the first point where a derivative differs from the numerical derivative.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DerivMismatch {
+    /// The input.
+    pub x: Scalar,
+    /// The derivative, as returned by [`Deriv::deriv()`].
+    pub deriv: Scalar,
+    /// The numerical derivative, by central differences.
+    pub numerical: Scalar,
+}
+
+impl Display for DerivMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "derivative {} at {} differs from the numerical derivative {}",
+            self.deriv, self.x, self.numerical
+        )
+    }
+}
+
+impl Error for DerivMismatch {}
+
+/// Checks the derivative of `f` against central differences at [`CHECK_POINTS`] evenly spaced
+/// points of `domain`, returning the first point where they differ by more than `tolerance`,
+/// relative to the numerical derivative if that is larger than one.
+///
+/// Close to points where `f` is not differentiable, such as zero for
+/// [`LeakyRelu`](../../rann_base/activ/struct.LeakyRelu.html), the numerical derivative is
+/// meaningless, so these points should be excluded from `domain`, or at least not be among the
+/// checked points.
+pub fn check_deriv<D>(
+    f: &D,
+    domain: RangeInclusive<Scalar>,
+    tolerance: Scalar,
+) -> Result<(), DerivMismatch>
+where
+    D: Deriv<In = Scalar, Out = Scalar>,
+{
+    let (start, end) = domain.into_inner();
+    for i in 0..CHECK_POINTS {
+        let x = start + (end - start) * i as Scalar / (CHECK_POINTS - 1) as Scalar;
+        // Balances the truncation error of the difference and the rounding error of `f`.
+        let h = Scalar::EPSILON.cbrt() * x.abs().max(1.0);
+        let numerical = (f.call(&(x + h)) - f.call(&(x - h))) / (2.0 * h);
+        let deriv = f.deriv(&x);
+        let error = (deriv - numerical).abs();
+        if error.is_nan() || error > tolerance * numerical.abs().max(1.0) {
+            return Err(DerivMismatch {
+                x,
+                deriv,
+                numerical,
+            });
+        }
+    }
+    Ok(())
 }
 
 /// A multi-dimensional pure function with its derivatives.
@@ -70,3 +282,35 @@ impl<T> NDeriv for T where T: Deriv {
         self.deriv(&x[0])
     }
 }
+
+/// Extreme inputs at which [`check_finite()`] checks functions, around the points where
+/// exponentials overflow and up to the largest finite values.
+pub const EXTREME_INPUTS: [Scalar; 12] = [
+    Scalar::MIN,
+    -1e30,
+    -1e4,
+    -100.0,
+    -89.0,
+    -88.0,
+    88.0,
+    89.0,
+    100.0,
+    1e4,
+    1e30,
+    Scalar::MAX,
+];
+
+/// Checks that `f` and its derivative are finite at all [`EXTREME_INPUTS`], returning the first
+/// input where either is not.
+pub fn check_finite<D>(f: &D) -> Result<(), Scalar>
+where
+    D: Deriv<In = Scalar, Out = Scalar>,
+{
+    match EXTREME_INPUTS
+        .into_iter()
+        .find(|x| !f.call(x).is_finite() || !f.deriv(x).is_finite())
+    {
+        Some(x) => Err(x),
+        None => Ok(()),
+    }
+}
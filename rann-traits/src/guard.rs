@@ -0,0 +1,231 @@
+/*!
+Guarded training, which detects non-finite values.
+
+A single NaN or infinity in a network quickly spreads to all of its parameters, after which
+training silently continues on a corrupted network. [`train_deriv_checked`] trains a network like
+[`Network::train_deriv`](crate::Network::train_deriv), but first checks the activations of every
+layer and afterwards checks the gradients and updated parameters, and the gradients over the
+inputs, returning a [`NonFinite`] error describing the first non-finite value it finds.
+[`train_error_checked`] does the same for networks ending in an error function, and also checks
+the error. The `Trainer` of `rann-base` uses it to train a whole dataset checked.
+
+Non-finite values often come from the data rather than the network. [`intermediate_checked`] and
+[`eval_checked`] check the inputs before evaluating a network, such that bad samples are found
+before they poison training.
+
+# Examples
+```rust
+use rann_base::{activ::Logistic, testing, Full};
+use rann_traits::guard::{self, Kind};
+
+let net = Full::<3, 1, _>::new(Logistic, testing::seeded_gen(1));
+assert!(guard::eval_checked(&net, &[0.0, 1.0, 2.0]).is_ok());
+let err = guard::eval_checked(&net, &[0.0, f32::NAN, 2.0]).unwrap_err();
+assert_eq!((err.kind, err.element), (Kind::Input, Some(1)));
+assert_eq!(err.to_string(), "non-finite input NaN at element 1");
+```
+
+Checking every value after each step is slow, so this is meant to be used while debugging.
+*/
+
+use std::{error::Error, fmt::Display};
+
+use crate::{
+    inspect::{Inspect, LayerView},
+    Intermediate, LearningRate, Network, Scalar, Terminal,
+};
+
+/// The kind of value that became non-finite.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// An input of the network, before it is evaluated.
+    Input,
+    /// An output of the layer.
+    Activation,
+    /// The gradients over the parameters of the layer.
+    Gradient,
+    /// A parameter of the layer, after it was updated.
+    Parameter,
+    /// A gradient over an input of the network, returned by training it.
+    InputGradient,
+}
+
+/// Error describing the first non-finite value found in a network.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NonFinite {
+    /// The index of the layer, in evaluation order. Inputs and their gradients are those of the
+    /// first layer.
+    pub layer: usize,
+    /// The kind of value.
+    pub kind: Kind,
+    /// The index of the element within the inputs, the activations, the (flattened) parameters of
+    /// the layer or the gradients over the inputs. Gradients over the parameters are only checked
+    /// by their norm, so no element is known for them.
+    pub element: Option<usize>,
+    /// The non-finite value.
+    pub value: Scalar,
+}
+
+impl Display for NonFinite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            Kind::Input => "input",
+            Kind::Activation => "activation",
+            Kind::Gradient => "gradient",
+            Kind::Parameter => "parameter",
+            Kind::InputGradient => "input gradient",
+        };
+        write!(f, "non-finite {kind} {}", self.value)?;
+        if !matches!(self.kind, Kind::Input | Kind::InputGradient) {
+            write!(f, " in layer {}", self.layer)?;
+        }
+        if let Some(element) = self.element {
+            write!(f, " at element {element}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for NonFinite {}
+
+/// Checks `inputs` for non-finite values.
+pub fn check_inputs(inputs: &[Scalar]) -> Result<(), NonFinite> {
+    find(inputs.iter(), 0, Kind::Input).map_or(Ok(()), Err)
+}
+
+/// Evaluates `net` like [`Network::intermediate`], after checking the inputs for non-finite
+/// values.
+pub fn intermediate_checked<N>(net: &N, inputs: &N::In) -> Result<N::Inter, NonFinite>
+where
+    N: Network,
+    N::In: AsRef<[Scalar]>,
+{
+    check_inputs(inputs.as_ref())?;
+    Ok(net.intermediate(inputs))
+}
+
+/// Evaluates `net` like [`Network::eval`], after checking the inputs for non-finite values.
+pub fn eval_checked<N>(net: &N, inputs: &N::In) -> Result<N::Out, NonFinite>
+where
+    N: Network,
+    N::In: AsRef<[Scalar]>,
+{
+    check_inputs(inputs.as_ref())?;
+    Ok(net.eval(inputs))
+}
+
+/// Checks the activations of the layers of `net` in `intermediate` for non-finite values.
+pub fn check_activations<N: Inspect>(net: &N, intermediate: &N::Inter) -> Result<(), NonFinite> {
+    first_non_finite(net, intermediate, |layer, index| {
+        find(layer.activations.iter(), index, Kind::Activation)
+    })
+}
+
+/// Checks the gradient norms and parameters of the layers of `net` for non-finite values.
+pub fn check_parameters<N: Inspect>(net: &N, intermediate: &N::Inter) -> Result<(), NonFinite> {
+    first_non_finite(net, intermediate, |layer, index| {
+        if !layer.gradient_norm.is_finite() {
+            return Some(NonFinite {
+                layer: index,
+                kind: Kind::Gradient,
+                element: None,
+                value: layer.gradient_norm,
+            });
+        }
+        find(
+            layer.params.iter().flat_map(|p| p.iter()),
+            index,
+            Kind::Parameter,
+        )
+    })
+}
+
+/// Trains `net` like [`Network::train_deriv`](crate::Network::train_deriv), checking the
+/// activations in `intermediate` before training, and the gradients and updated parameters and
+/// the returned gradients over the inputs afterwards.
+///
+/// If the activations contain a non-finite value, the network is not trained. Otherwise, the
+/// network has been trained and may be corrupted if an error is returned.
+pub fn train_deriv_checked<N>(
+    net: &mut N,
+    inputs: &N::In,
+    intermediate: &N::Inter,
+    gradients: &N::Out,
+    learning_rate: Scalar,
+) -> Result<N::In, NonFinite>
+where
+    N: Inspect,
+    N::In: AsRef<[Scalar]>,
+{
+    check_activations(net, intermediate)?;
+    let gradients = net.train_deriv(inputs, intermediate, gradients, learning_rate);
+    check_parameters(net, intermediate)?;
+    match find(gradients.as_ref().iter(), 0, Kind::InputGradient) {
+        Some(non_finite) => Err(non_finite),
+        None => Ok(gradients),
+    }
+}
+
+/// Trains `net` like [`Terminal::train_error`], checking its error and all values checked by
+/// [`train_deriv_checked`]. The error is the activation of the last layer, the error function.
+///
+/// If the activations or the error contain a non-finite value, the network is not trained.
+/// Otherwise, the network has been trained and may be corrupted if an error is returned.
+pub fn train_error_checked<N>(
+    net: &mut N,
+    inputs: &N::In,
+    intermediate: &N::Inter,
+    learning_rate: impl Into<LearningRate>,
+) -> Result<N::In, NonFinite>
+where
+    N: Inspect + Terminal,
+    N::In: AsRef<[Scalar]>,
+{
+    check_activations(net, intermediate)?;
+    let error = intermediate.output()[0];
+    if !error.is_finite() {
+        let mut layers: usize = 0;
+        net.visit_layers(intermediate, &mut |_| layers += 1);
+        return Err(NonFinite {
+            layer: layers.saturating_sub(1),
+            kind: Kind::Activation,
+            element: Some(0),
+            value: error,
+        });
+    }
+    let seed = net.seed();
+    train_deriv_checked(net, inputs, intermediate, &seed, learning_rate.into().get())
+}
+
+// Returns the first non-finite value found by `check` in any layer.
+fn first_non_finite<N: Inspect>(
+    net: &N,
+    intermediate: &N::Inter,
+    mut check: impl FnMut(&LayerView<'_>, usize) -> Option<NonFinite>,
+) -> Result<(), NonFinite> {
+    let mut index = 0;
+    let mut found = None;
+    net.visit_layers(intermediate, &mut |layer| {
+        if found.is_none() {
+            found = check(layer, index);
+        }
+        index += 1;
+    });
+    found.map_or(Ok(()), Err)
+}
+
+fn find<'a>(
+    values: impl Iterator<Item = &'a Scalar>,
+    layer: usize,
+    kind: Kind,
+) -> Option<NonFinite> {
+    values
+        .enumerate()
+        .find(|(_, x)| !x.is_finite())
+        .map(|(element, &value)| NonFinite {
+            layer,
+            kind,
+            element: Some(element),
+            value,
+        })
+}
@@ -0,0 +1,178 @@
+/*!
+Histograms and quantile summaries of parameters.
+
+The distribution of the weights of a network tells a lot about training: weights that grow
+without bound, collapse to zero or drift away from their initialization all show up in their
+distribution long before they show up in the error. A [`Histogram`] counts the values in bins of
+equal width, and a [`Summary`] holds their quantiles, mean and standard deviation. Both can be
+made of all parameters of a [`Parameterized`] network, or of each parameter group (such as the
+weights and biases) of each layer of an [`Inspect`]able network, and compared across training.
+
+# Examples
+```rust
+use rann_traits::histogram::{Histogram, Summary};
+
+let values = [0.0, 0.1, 0.2, 0.3, 0.9, 1.0];
+let histogram = Histogram::new(&values, 2);
+assert_eq!(histogram.counts, [4, 2]);
+
+let summary = Summary::new(&values);
+assert_eq!(summary.median, 0.25);
+assert_eq!(summary.max, 1.0);
+```
+*/
+
+use crate::{inspect::Inspect, params::Parameterized, Scalar};
+
+/// The counts of values in bins of equal width.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Histogram {
+    /// The smallest finite value, and the lower edge of the first bin.
+    pub min: Scalar,
+    /// The largest finite value, and the upper edge of the last bin.
+    pub max: Scalar,
+    /// The number of values in each bin.
+    pub counts: Vec<usize>,
+    /// The number of values that are infinite or NaN, which are not in any bin.
+    pub non_finite: usize,
+}
+
+impl Histogram {
+    /// Counts `values` in `bins` bins of equal width, spanning the range of the finite values.
+    ///
+    /// # Panics
+    /// Panics if `bins` is zero.
+    pub fn new(values: &[Scalar], bins: usize) -> Self {
+        assert!(bins > 0, "A histogram needs at least one bin.");
+        let finite = || values.iter().copied().filter(|v| v.is_finite());
+        let min = finite().fold(Scalar::INFINITY, Scalar::min);
+        let max = finite().fold(Scalar::NEG_INFINITY, Scalar::max);
+        let mut counts = vec![0; bins];
+        let width = (max - min) / bins as Scalar;
+        for value in finite() {
+            // The largest value belongs to the last bin, and all values if they are equal.
+            let bin = if width > 0.0 {
+                (((value - min) / width) as usize).min(bins - 1)
+            } else {
+                0
+            };
+            counts[bin] += 1;
+        }
+        Self {
+            min,
+            max,
+            non_finite: values.len() - counts.iter().sum::<usize>(),
+            counts,
+        }
+    }
+
+    /// Returns the edges of the bins, from the lower edge of the first bin to the upper edge of
+    /// the last bin.
+    pub fn edges(&self) -> Vec<Scalar> {
+        let bins = self.counts.len();
+        (0..=bins)
+            .map(|i| self.min + (self.max - self.min) * i as Scalar / bins as Scalar)
+            .collect()
+    }
+
+    /// Returns the total number of finite values.
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+}
+
+/// Summary statistics of a set of values. Non-finite values are ignored; all statistics are NaN
+/// if there are no finite values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Summary {
+    /// The smallest value.
+    pub min: Scalar,
+    /// The 5th percentile.
+    pub p5: Scalar,
+    /// The first quartile.
+    pub q1: Scalar,
+    /// The median.
+    pub median: Scalar,
+    /// The third quartile.
+    pub q3: Scalar,
+    /// The 95th percentile.
+    pub p95: Scalar,
+    /// The largest value.
+    pub max: Scalar,
+    /// The mean.
+    pub mean: Scalar,
+    /// The (population) standard deviation.
+    pub std_dev: Scalar,
+}
+
+impl Summary {
+    /// Summarizes `values`.
+    pub fn new(values: &[Scalar]) -> Self {
+        let mut sorted: Vec<Scalar> = values.iter().copied().filter(|v| v.is_finite()).collect();
+        sorted.sort_by(Scalar::total_cmp);
+        let n = sorted.len() as Scalar;
+        let mean = sorted.iter().sum::<Scalar>() / n;
+        let var = sorted
+            .iter()
+            .map(|v| (v - mean) * (v - mean))
+            .sum::<Scalar>()
+            / n;
+        let q = |q| quantile_sorted(&sorted, q);
+        Self {
+            min: q(0.0),
+            p5: q(0.05),
+            q1: q(0.25),
+            median: q(0.5),
+            q3: q(0.75),
+            p95: q(0.95),
+            max: q(1.0),
+            mean,
+            std_dev: var.sqrt(),
+        }
+    }
+}
+
+/// Returns the `q`-quantile of the finite `values`, interpolating linearly between the closest
+/// values. Returns NaN if there are no finite values.
+///
+/// # Panics
+/// Panics if `q` is not in `0.0..=1.0`.
+pub fn quantile(values: &[Scalar], q: Scalar) -> Scalar {
+    let mut sorted: Vec<Scalar> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    sorted.sort_by(Scalar::total_cmp);
+    quantile_sorted(&sorted, q)
+}
+
+fn quantile_sorted(sorted: &[Scalar], q: Scalar) -> Scalar {
+    assert!((0.0..=1.0).contains(&q), "Quantiles should be in 0..=1.");
+    if sorted.is_empty() {
+        return Scalar::NAN;
+    }
+    let pos = q * (sorted.len() - 1) as Scalar;
+    let (lower, frac) = (pos.floor() as usize, pos.fract());
+    match sorted.get(lower + 1) {
+        Some(upper) => sorted[lower] + (upper - sorted[lower]) * frac,
+        None => sorted[lower],
+    }
+}
+
+/// Returns a histogram of all parameters of `net`, with `bins` bins.
+pub fn of_params<P: Parameterized>(net: &P, bins: usize) -> Histogram {
+    Histogram::new(&net.params(), bins)
+}
+
+/// Returns a histogram with `bins` bins of each parameter group (such as the weights and the
+/// biases) of each layer of `net`, in evaluation order. See [`Inspect::visit_layers()`].
+pub fn of_layers<N: Inspect>(net: &N, intermediate: &N::Inter, bins: usize) -> Vec<Vec<Histogram>> {
+    let mut histograms = Vec::new();
+    net.visit_layers(intermediate, &mut |layer| {
+        histograms.push(
+            layer
+                .params
+                .iter()
+                .map(|params| Histogram::new(params, bins))
+                .collect(),
+        );
+    });
+    histograms
+}
@@ -0,0 +1,473 @@
+/*!
+Introspection of (composed) networks.
+
+Networks implementing [`Inspect`] expose a [`LayerView`] of each of their layers: its parameters,
+its activations in an evaluation and the norm of its gradients in the last training step. From
+these, [`LayerStats`] such as the norms of the weights and the range of the activations can be
+found, which help to diagnose vanishing or exploding gradients. The [`MemoryFootprint`] of every
+layer helps to budget the memory of a network before deploying it to a small device.
+
+The structure of a network can also be exported to the [GraphViz](https://graphviz.org) DOT
+language using [`Inspect::to_dot()`], showing the kind and sizes of every layer and how they are
+connected.
+
+```rust
+# use rann_traits::{inspect::{Inspect, LayerView}, Network, Scalar};
+# struct Double;
+# impl Network for Double {
+#     type In = [Scalar; 1];
+#     type Out = [Scalar; 1];
+#     type Inter = [Scalar; 1];
+#     fn intermediate(&self, inputs: &[Scalar; 1]) -> [Scalar; 1] { [inputs[0] * 2.0] }
+#     fn train_deriv(&mut self, _: &[Scalar; 1], _: &[Scalar; 1], g: &[Scalar; 1], _: Scalar)
+#         -> [Scalar; 1] { [g[0] * 2.0] }
+# }
+# impl Inspect for Double {
+#     fn visit_layers(&self, inter: &[Scalar; 1], f: &mut dyn FnMut(&LayerView<'_>)) {
+#         f(&LayerView { kind: "Double", num_inputs: 1, params: &[], activations: inter, gradient_norm: 0.0 });
+#     }
+# }
+let net = Double.chain(Double);
+let inter = net.intermediate(&[1.0]);
+let dot = net.to_dot(&inter);
+assert!(dot.starts_with("digraph"));
+assert!(dot.contains("layer0 -> layer1"));
+```
+*/
+
+use std::{
+    fmt::{self, Display},
+    iter::Sum,
+    ops::Add,
+};
+
+use crate::{
+    compose::{Repeat, Shared, TimeDistributed, ZipOwned},
+    mask::{MaskedInputs, Masking},
+    Chain, Network, Scalar, Zip,
+};
+
+/// A view of a single layer of a network.
+#[derive(Clone, Copy, Debug)]
+pub struct LayerView<'a> {
+    /// A short description of the kind of layer, such as `Full (Logistic)`.
+    pub kind: &'a str,
+    /// The number of inputs of the layer.
+    pub num_inputs: usize,
+    /// The parameters of the layer, such as its weights and biases.
+    pub params: &'a [&'a [Scalar]],
+    /// The outputs of the layer in an evaluation.
+    pub activations: &'a [Scalar],
+    /// The L2 norm of the gradients over all parameters of the layer, from the last training step.
+    pub gradient_norm: Scalar,
+}
+
+/// Statistics of a single layer of a network.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LayerStats {
+    /// The L2 norm of all parameters of the layer.
+    pub weight_norm: Scalar,
+    /// The L2 norm of the gradients over all parameters of the layer, from the last training step.
+    pub gradient_norm: Scalar,
+    /// The smallest output of the layer in an evaluation.
+    pub min_activation: Scalar,
+    /// The largest output of the layer in an evaluation.
+    pub max_activation: Scalar,
+}
+
+impl From<&LayerView<'_>> for LayerStats {
+    fn from(layer: &LayerView<'_>) -> Self {
+        let weight_norm = layer
+            .params
+            .iter()
+            .flat_map(|p| p.iter())
+            .map(|x| x * x)
+            .sum::<Scalar>()
+            .sqrt();
+        let (min_activation, max_activation) = layer.activations.iter().fold(
+            (Scalar::INFINITY, Scalar::NEG_INFINITY),
+            |(min, max), &x| (min.min(x), max.max(x)),
+        );
+        Self {
+            weight_norm,
+            gradient_norm: layer.gradient_norm,
+            min_activation,
+            max_activation,
+        }
+    }
+}
+
+/// The memory used by a single layer of a network, in bytes.
+///
+/// Layers that are shared by multiple parts of a network, such as through
+/// [`Shared`](crate::compose::Shared), are counted once for every part.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryFootprint {
+    /// The memory used by the parameters of the layer.
+    pub params: usize,
+    /// The memory used by the state an optimizer keeps for the parameters of the layer, such as
+    /// their momentum.
+    pub optimizer: usize,
+    /// The memory used by the outputs of the layer in the intermediate values of an evaluation.
+    /// Some layers keep more intermediate values, such as their weighted sums, so this is a lower
+    /// bound.
+    pub intermediate: usize,
+}
+
+impl MemoryFootprint {
+    /// Returns the memory used by `layer`, when trained with an optimizer that keeps
+    /// `optimizer_states` values per parameter, such as 0 for plain gradient descent, 1 for
+    /// momentum and 2 for Adam.
+    pub fn of(layer: &LayerView<'_>, optimizer_states: usize) -> Self {
+        let num_params: usize = layer.params.iter().map(|p| p.len()).sum();
+        let size = std::mem::size_of::<Scalar>();
+        Self {
+            params: num_params * size,
+            optimizer: num_params * optimizer_states * size,
+            intermediate: std::mem::size_of_val(layer.activations),
+        }
+    }
+
+    /// Returns the total memory used, in bytes.
+    pub fn total(&self) -> usize {
+        self.params + self.optimizer + self.intermediate
+    }
+}
+
+impl Add for MemoryFootprint {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            params: self.params + other.params,
+            optimizer: self.optimizer + other.optimizer,
+            intermediate: self.intermediate + other.intermediate,
+        }
+    }
+}
+
+impl Sum for MemoryFootprint {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+/// Returns the name of `T` without module paths, e.g. `Full<2, 3, Logistic>` instead of
+/// `rann_base::full::Full<2, 3, rann_base::activ::Logistic>`.
+pub fn short_type_name<T: ?Sized>() -> String {
+    let name = std::any::type_name::<T>();
+    let mut short = String::with_capacity(name.len());
+    let mut segment = 0;
+    for (i, c) in name.char_indices() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            continue;
+        }
+        short.push_str(last_segment(&name[segment..i]));
+        short.push(c);
+        segment = i + c.len_utf8();
+    }
+    short.push_str(last_segment(&name[segment..]));
+    short
+}
+
+fn last_segment(path: &str) -> &str {
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+/// A graph of layers, written in the GraphViz DOT language by its [`Display`] implementation.
+///
+/// Built by [`Inspect::write_dot()`]; see [`Inspect::to_dot()`].
+#[derive(Clone, Debug, Default)]
+pub struct DotGraph {
+    // The label of each node.
+    nodes: Vec<String>,
+    edges: Vec<(usize, usize)>,
+}
+
+impl DotGraph {
+    /// Creates an empty graph.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a node with the given label, which may span multiple lines, and returns its index.
+    pub fn add_node(&mut self, label: impl Into<String>) -> usize {
+        self.nodes.push(label.into());
+        self.nodes.len() - 1
+    }
+
+    /// Adds a node describing `layer`, connects it to the nodes `from` and returns its index.
+    pub fn add_layer(&mut self, layer: &LayerView<'_>, from: &[usize]) -> usize {
+        let num_params: usize = layer.params.iter().map(|p| p.len()).sum();
+        let node = self.add_node(format!(
+            "{}\n{} → {}\n{} params",
+            layer.kind,
+            layer.num_inputs,
+            layer.activations.len(),
+            num_params
+        ));
+        for &from in from {
+            self.add_edge(from, node);
+        }
+        node
+    }
+
+    /// Connects node `from` to node `to`.
+    pub fn add_edge(&mut self, from: usize, to: usize) {
+        self.edges.push((from, to));
+    }
+}
+
+impl Display for DotGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "digraph network {{")?;
+        writeln!(f, "    rankdir=LR;")?;
+        writeln!(f, "    node [shape=box];")?;
+        for (i, label) in self.nodes.iter().enumerate() {
+            writeln!(
+                f,
+                "    layer{i} [label=\"{}\"];",
+                label
+                    .replace('\\', "\\\\")
+                    .replace('"', "\\\"")
+                    .replace('\n', "\\n")
+            )?;
+        }
+        for (from, to) in &self.edges {
+            writeln!(f, "    layer{from} -> layer{to};")?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+/// Trait implemented by networks that can expose their layers.
+pub trait Inspect: Network {
+    /// Calls `f` with a view of each layer of this network, in evaluation order, using the
+    /// `intermediate` values of an evaluation for the activations.
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>));
+
+    /// Returns the statistics of each layer of this network, in evaluation order.
+    fn layer_stats(&self, intermediate: &Self::Inter) -> Vec<LayerStats> {
+        let mut stats = Vec::new();
+        self.visit_layers(intermediate, &mut |layer| stats.push(layer.into()));
+        stats
+    }
+
+    /// Returns the memory used by each layer of this network, in evaluation order, when trained
+    /// with an optimizer that keeps `optimizer_states` values per parameter. See
+    /// [`MemoryFootprint::of()`].
+    ///
+    /// The footprint of the whole network is the sum of those of its layers:
+    /// ```rust
+    /// # use rann_traits::{inspect::{Inspect, LayerView, MemoryFootprint}, Network, Scalar};
+    /// # struct Scale(Scalar);
+    /// # impl Network for Scale {
+    /// #     type In = [Scalar; 1];
+    /// #     type Out = [Scalar; 1];
+    /// #     type Inter = [Scalar; 1];
+    /// #     fn intermediate(&self, inputs: &[Scalar; 1]) -> [Scalar; 1] { [inputs[0] * self.0] }
+    /// #     fn train_deriv(&mut self, _: &[Scalar; 1], _: &[Scalar; 1], g: &[Scalar; 1], _: Scalar)
+    /// #         -> [Scalar; 1] { [g[0] * self.0] }
+    /// # }
+    /// # impl Inspect for Scale {
+    /// #     fn visit_layers(&self, inter: &[Scalar; 1], f: &mut dyn FnMut(&LayerView<'_>)) {
+    /// #         f(&LayerView { kind: "Scale", num_inputs: 1, params: &[&[self.0]], activations: inter, gradient_norm: 0.0 });
+    /// #     }
+    /// # }
+    /// let net = Scale(2.0).chain(Scale(3.0));
+    /// let inter = net.intermediate(&[1.0]);
+    /// let total: MemoryFootprint = net.memory_footprint(&inter, 2).into_iter().sum();
+    /// assert_eq!(total.params, 2 * 4);
+    /// assert_eq!(total.optimizer, 2 * 2 * 4);
+    /// assert_eq!(total.intermediate, 2 * 4);
+    /// ```
+    fn memory_footprint(
+        &self,
+        intermediate: &Self::Inter,
+        optimizer_states: usize,
+    ) -> Vec<MemoryFootprint> {
+        let mut footprints = Vec::new();
+        self.visit_layers(intermediate, &mut |layer| {
+            footprints.push(MemoryFootprint::of(layer, optimizer_states));
+        });
+        footprints
+    }
+
+    /// Adds the layers of this network to `dot`, taking their inputs from the nodes `inputs`, and
+    /// returns the nodes holding the outputs of this network.
+    ///
+    /// By default, the layers are connected in evaluation order, which is only correct for
+    /// sequential networks; networks with parallel branches override this.
+    fn write_dot(
+        &self,
+        intermediate: &Self::Inter,
+        dot: &mut DotGraph,
+        inputs: Vec<usize>,
+    ) -> Vec<usize> {
+        let mut outputs = inputs;
+        self.visit_layers(intermediate, &mut |layer| {
+            outputs = vec![dot.add_layer(layer, &outputs)];
+        });
+        outputs
+    }
+
+    /// Returns a description of the structure of this network in the GraphViz DOT language,
+    /// using the `intermediate` values of an evaluation for the sizes of the layers. See
+    /// [module level documentation](self) for more info.
+    ///
+    /// Render it with e.g. `dot -Tsvg network.dot -o network.svg`.
+    fn to_dot(&self, intermediate: &Self::Inter) -> String {
+        let mut dot = DotGraph::new();
+        let input = dot.add_node("input");
+        let outputs = self.write_dot(intermediate, &mut dot, vec![input]);
+        let output = dot.add_node("output");
+        for from in outputs {
+            dot.add_edge(from, output);
+        }
+        dot.to_string()
+    }
+}
+
+impl<T, U> Inspect for Chain<T, U>
+where
+    T: Inspect,
+    U: Inspect<In = T::Out>,
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        self.first.visit_layers(&intermediate.first, f);
+        self.second.visit_layers(&intermediate.second, f);
+    }
+
+    fn write_dot(
+        &self,
+        intermediate: &Self::Inter,
+        dot: &mut DotGraph,
+        inputs: Vec<usize>,
+    ) -> Vec<usize> {
+        let hidden = self.first.write_dot(&intermediate.first, dot, inputs);
+        self.second.write_dot(&intermediate.second, dot, hidden)
+    }
+}
+
+impl<T, U, Z, UnZ, C> Inspect for Zip<T, U, Z, UnZ>
+where
+    T: Inspect,
+    U: Inspect,
+    Z: Fn(&T::Out, &U::Out) -> C,
+    UnZ: for<'a> Fn(&'a C) -> (&'a T::Out, &'a U::Out),
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        self.top.visit_layers(&intermediate.top, f);
+        self.bot.visit_layers(&intermediate.bot, f);
+    }
+
+    // Both branches take (their part of) the inputs, and the outputs of both are zipped.
+    fn write_dot(
+        &self,
+        intermediate: &Self::Inter,
+        dot: &mut DotGraph,
+        inputs: Vec<usize>,
+    ) -> Vec<usize> {
+        let mut outputs = self.top.write_dot(&intermediate.top, dot, inputs.clone());
+        outputs.extend(self.bot.write_dot(&intermediate.bot, dot, inputs));
+        outputs
+    }
+}
+
+impl<T, U, Z, UnZ, C> Inspect for ZipOwned<T, U, Z, UnZ>
+where
+    T: Inspect,
+    U: Inspect,
+    Z: Fn(&T::Out, &U::Out) -> C,
+    UnZ: Fn(&C) -> (T::Out, U::Out),
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        self.top.visit_layers(&intermediate.top, f);
+        self.bot.visit_layers(&intermediate.bot, f);
+    }
+
+    fn write_dot(
+        &self,
+        intermediate: &Self::Inter,
+        dot: &mut DotGraph,
+        inputs: Vec<usize>,
+    ) -> Vec<usize> {
+        let mut outputs = self.top.write_dot(&intermediate.top, dot, inputs.clone());
+        outputs.extend(self.bot.write_dot(&intermediate.bot, dot, inputs));
+        outputs
+    }
+}
+
+impl<T> Inspect for Shared<T>
+where
+    T: Inspect,
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        self.borrow().visit_layers(intermediate, f);
+    }
+
+    fn write_dot(
+        &self,
+        intermediate: &Self::Inter,
+        dot: &mut DotGraph,
+        inputs: Vec<usize>,
+    ) -> Vec<usize> {
+        self.borrow().write_dot(intermediate, dot, inputs)
+    }
+}
+
+impl<T, const N: usize> Inspect for Repeat<T, N>
+where
+    T: Inspect<Out = <T as Network>::In>,
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        for (layer, inter) in self.layers.iter().zip(&intermediate.layers) {
+            layer.visit_layers(inter, f);
+        }
+    }
+
+    fn write_dot(
+        &self,
+        intermediate: &Self::Inter,
+        dot: &mut DotGraph,
+        inputs: Vec<usize>,
+    ) -> Vec<usize> {
+        self.layers
+            .iter()
+            .zip(&intermediate.layers)
+            .fold(inputs, |inputs, (layer, inter)| {
+                layer.write_dot(inter, dot, inputs)
+            })
+    }
+}
+
+// The network is visited with its evaluation of the last step.
+impl<T, const N: usize> Inspect for TimeDistributed<T, N>
+where
+    T: Inspect,
+    T::Out: Clone,
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        if let Some(inter) = intermediate.steps.last() {
+            self.network.visit_layers(inter, f);
+        }
+    }
+}
+
+impl<T, S, const N: usize> Inspect for MaskedInputs<T>
+where
+    T: Inspect + Masking<N, In = [S; N]>,
+{
+    fn visit_layers(&self, intermediate: &Self::Inter, f: &mut dyn FnMut(&LayerView<'_>)) {
+        self.network.visit_layers(intermediate, f);
+    }
+
+    fn write_dot(
+        &self,
+        intermediate: &Self::Inter,
+        dot: &mut DotGraph,
+        inputs: Vec<usize>,
+    ) -> Vec<usize> {
+        self.network.write_dot(intermediate, dot, inputs)
+    }
+}
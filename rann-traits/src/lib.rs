@@ -17,26 +17,93 @@ train the network.
 
 The [`Network`] trait has two required methods:
 - [`Network::intermediate()`]: evaluates the network and returns the results and intermediate 
-calculations for training,
+  calculations for training,
 - [`Network::train_deriv()`]: trains the network using the previously mentioned calculations and 
-returns gradients for preceding parts of the network to train on.
+  returns gradients for preceding parts of the network to train on.
 
 # Composing networks
 
 This trait automatically implements methods for composing (connecting) multiple networks or
 layers into one network. See [`self::compose`] for more information.
 
+With the `derive` feature, `#[derive(Network)]` chains the fields of a struct in declaration
+order, for named architectures with named layers.
+
 */
 
 pub mod compose;
 pub mod deriv;
+pub mod guard;
+pub mod histogram;
+pub mod inspect;
+pub mod mask;
+pub mod params;
+pub mod predict;
+pub mod util;
+
+use std::{any::Any, borrow::Cow};
 
-use compose::{Chain, Zip};
-use num_traits::One;
+use compose::{zip::ZipOwned, Chain, Hooked, Named, Zip};
+use predict::Predict;
+
+/// Derives [`Network`] for a struct whose fields are networks, chained in declaration order.
+/// Requires the `derive` feature.
+#[cfg(feature = "derive")]
+pub use rann_derive::Network;
 
 /// The default scalar type.
 pub type Scalar = f32;
 
+/// The learning rate of a training step, the factor by which the gradients of the parameters are
+/// subtracted from them.
+///
+/// Training APIs take a learning rate as `impl Into<LearningRate>`, such that a bare [`Scalar`]
+/// can still be passed, while training loops and trainers store a `LearningRate`, such that it can
+/// not be mistaken for another hyperparameter. Layers receive the plain [`Scalar`] in
+/// [`Network::train_deriv()`], as they only multiply their gradients by it.
+///
+/// # Examples
+/// ```rust
+/// use rann_traits::LearningRate;
+///
+/// let rate = LearningRate::from(0.1);
+/// assert_eq!(rate, LearningRate(0.1));
+/// assert_eq!(rate.get(), 0.1);
+/// assert_eq!(rate.scaled(0.5), LearningRate(0.05));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct LearningRate(pub Scalar);
+
+impl LearningRate {
+    /// Returns the learning rate as a [`Scalar`].
+    pub const fn get(self) -> Scalar {
+        self.0
+    }
+
+    /// Returns this learning rate multiplied by `factor`, such as for a decay schedule.
+    pub fn scaled(self, factor: Scalar) -> Self {
+        Self(self.0 * factor)
+    }
+}
+
+impl From<Scalar> for LearningRate {
+    fn from(rate: Scalar) -> Self {
+        Self(rate)
+    }
+}
+
+impl From<LearningRate> for Scalar {
+    fn from(rate: LearningRate) -> Self {
+        rate.0
+    }
+}
+
+impl std::fmt::Display for LearningRate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
 /// Trait implemented by networks that can be evaluated and trained by backpropagation. 
 /// See [module level documentation](crate)
 /// for more info.
@@ -65,6 +132,19 @@ pub trait Network {
         learning_rate: Scalar,
     ) -> Self::In;
 
+    /// Evaluates the network like [`Self::intermediate()`], but stores the intermediate
+    /// calculations in `intermediate`, such that the buffers of a previous evaluation can be
+    /// reused. Training loops can evaluate every sample into the intermediate calculations of the
+    /// first sample, instead of allocating new ones for every sample.
+    ///
+    /// # Implementation note
+    /// The default implementation replaces `intermediate` with the result of
+    /// [`Self::intermediate()`]. Networks of which the intermediate calculations own heap
+    /// allocations, such as a [`Graph`](compose::Graph), should override this to reuse them.
+    fn intermediate_into(&self, inputs: &Self::In, intermediate: &mut Self::Inter) {
+        *intermediate = self.intermediate(inputs);
+    }
+
     /// Evaluate the network and return the outputs.
     ///
     /// # Implementation note
@@ -75,25 +155,59 @@ pub trait Network {
         self.intermediate(inputs).into_output()
     }
 
-    /// Trains the network using a previous evaluation and the associated inputs.
+    /// Evaluates the network on every input of `inputs`, and returns their outputs in order.
+    ///
+    /// # Implementation note
+    /// The default implementation evaluates every input using [`Self::eval()`]. Layers that can
+    /// evaluate a batch of inputs at once, such as by a single matrix product, should override
+    /// this, and compositions should pass the whole batch to their parts.
+    fn eval_many(&self, inputs: &[Self::In]) -> Vec<Self::Out> {
+        inputs.iter().map(|inputs| self.eval(inputs)).collect()
+    }
+
+    /// Evaluates the network on `inputs`, and returns the gradients over the inputs for the
+    /// `gradients` over the outputs, without training the network.
     ///
     /// # Implementation note
-    /// This method calls `train_deriv` with an array of gradients filled with ones.
-    fn train<T, const NUM_IN: usize>(
+    /// The default implementation trains a clone of the network with a learning rate of zero, such
+    /// that any state kept for training (such as gradient norms) is left untouched as well. Note
+    /// that cloning a [`Shared`](compose::Shared) network does not copy the shared network.
+    fn gradient(&self, inputs: &Self::In, gradients: &Self::Out) -> Self::In
+    where
+        Self: Sized + Clone,
+    {
+        let mut net = self.clone();
+        let intermediate = net.intermediate(inputs);
+        net.train_deriv(inputs, &intermediate, gradients, 0.0)
+    }
+
+    /// Returns an iterator that evaluates the network on the inputs of `inputs` as its outputs
+    /// are requested, in chunks of inputs that are evaluated at once with [`Self::eval_many()`].
+    /// See [`predict`] for more info.
+    fn predict_iter<I>(&self, inputs: I) -> Predict<'_, Self, I::IntoIter>
+    where
+        Self: Sized,
+        I: IntoIterator<Item = Self::In>,
+    {
+        Predict::new(self, inputs.into_iter())
+    }
+
+    /// Trains the network using a previous evaluation and the associated inputs, as if the
+    /// outputs are the error: every output has a gradient of one.
+    ///
+    /// # Implementation note
+    /// This method calls `train_deriv` with the gradients of [`Gradient::ones_like()`] the
+    /// output of the evaluation.
+    fn train(
         &mut self,
         inputs: &Self::In,
         intermediate: &Self::Inter,
-        learning_rate: Scalar,
+        learning_rate: impl Into<LearningRate>,
     ) where
-        for<'a> &'a [T; NUM_IN]: Into<&'a Self::Out>,
-        T: One + Copy,
+        Self::Out: Gradient,
     {
-        self.train_deriv(
-            inputs,
-            intermediate,
-            (&[T::one(); NUM_IN]).into(),
-            learning_rate,
-        );
+        let gradients = intermediate.output().ones_like();
+        self.train_deriv(inputs, intermediate, &gradients, learning_rate.into().get());
     }
 
     /// Chains `self` and `next` together, after eachother.
@@ -128,6 +242,178 @@ pub trait Network {
             unzipper,
         }
     }
+
+    /// Zips `self` and `other` together into one network, in parallel, like [`Self::zip()`],
+    /// but with an `unzipper` that computes the gradients over the outputs of both networks from
+    /// the gradients over the combined outputs. See [`ZipOwned`] for more info.
+    fn zip_owned<U, C, Z, UnZ>(
+        self,
+        other: U,
+        zipper: impl Into<(Z, UnZ)>,
+    ) -> ZipOwned<Self, U, Z, UnZ>
+    where
+        Self: Sized,
+        U: Network,
+        Z: Fn(&Self::Out, &U::Out) -> C,
+        UnZ: Fn(&C) -> (Self::Out, U::Out),
+    {
+        let (zipper, unzipper) = zipper.into();
+        ZipOwned {
+            top: self,
+            bot: other,
+            zipper,
+            unzipper,
+        }
+    }
+
+    /// Names this network, such that it can be found in a composed network using
+    /// [`Self::layer()`].
+    fn named(self, name: impl Into<Cow<'static, str>>) -> Named<Self>
+    where
+        Self: Sized + 'static,
+    {
+        Named::new(self, name)
+    }
+
+    /// Hooks `hook` into this network, such that it is called with the inputs and outputs of
+    /// every evaluation. See [`Hooked`] for more info.
+    fn hook<F>(self, hook: F) -> Hooked<Self, F>
+    where
+        Self: Sized,
+        F: Fn(&Self::In, &Self::Out),
+    {
+        Hooked::new(self, hook)
+    }
+
+    /// Finds the layer named `name` in this network, as created by [`Self::named()`].
+    ///
+    /// # Implementation note
+    /// The default implementation finds nothing. Networks containing other networks should
+    /// override this method and [`Self::find_layer_mut()`] to search those, in order.
+    fn find_layer(&self, name: &str) -> Option<&dyn Any> {
+        let _ = name;
+        None
+    }
+
+    /// Finds the layer named `name` in this network, and borrows it mutably. See
+    /// [`Self::find_layer()`].
+    fn find_layer_mut(&mut self, name: &str) -> Option<&mut dyn Any> {
+        let _ = name;
+        None
+    }
+
+    /// Borrows the first layer named `name`, if it exists and is of type `L`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use rann_traits::Network;
+    /// use rann_base::{Full, activ::Logistic};
+    ///
+    /// let gen = (|_, _| 0.5, |_| 0.0);
+    /// let net = Full::<2, 3, _>::new(Logistic, gen)
+    ///     .named("encoder")
+    ///     .chain(Full::<3, 1, _>::new(Logistic, gen));
+    ///
+    /// let encoder = net.layer::<Full<2, 3, Logistic>>("encoder").unwrap();
+    /// assert_eq!(encoder.eval(&[1.0, 1.0]), net.first.eval(&[1.0, 1.0]));
+    /// // The layer must be of the requested type.
+    /// assert!(net.layer::<Full<3, 1, Logistic>>("encoder").is_none());
+    /// ```
+    fn layer<L: 'static>(&self, name: &str) -> Option<&L> {
+        self.find_layer(name)?.downcast_ref()
+    }
+
+    /// Mutably borrows the first layer named `name`, if it exists and is of type `L`.
+    fn layer_mut<L: 'static>(&mut self, name: &str) -> Option<&mut L> {
+        self.find_layer_mut(name)?.downcast_mut()
+    }
+}
+
+/// Trait implemented by networks that end in an error function, such that they can be trained
+/// towards a target.
+///
+/// The output of such a network is its error, and training it minimizes that error.
+pub trait Supervised: Network<Out = [Scalar; 1]> {
+    /// Type for the expected outputs of the network.
+    type Target;
+
+    /// Sets the target that following evaluations and training steps are compared to.
+    fn set_target(&mut self, target: &Self::Target);
+
+    /// Returns the gradients of the error towards `target` over `inputs`, without training the
+    /// network or changing its target. These show how sensitive the error is to each input, as
+    /// used by saliency maps and adversarial examples. See [`Network::gradient()`].
+    fn input_gradient(&self, inputs: &Self::In, target: &Self::Target) -> Self::In
+    where
+        Self: Sized + Clone,
+    {
+        let mut net = self.clone();
+        net.set_target(target);
+        net.gradient(inputs, &net.seed())
+    }
+}
+
+/// Trait for networks that end in an error function, which start backpropagation themselves:
+/// the gradient of the error over itself is one. Implemented for all [`Supervised`] networks,
+/// such as any chain ending in an error function, and to be implemented by error functions that
+/// have no target.
+///
+/// # Examples
+/// ```rust
+/// use rann_base::{activ::Logistic, error::SquareError, testing, Full};
+/// use rann_traits::{Network, Terminal};
+///
+/// let mut net = Full::<2, 1, _>::new(Logistic, testing::seeded_gen(1))
+///     .chain(SquareError { expected: [1.0] });
+/// let inter = net.intermediate(&[0.5, 0.5]);
+/// // The gradients over the inputs, after training the network.
+/// let gradients = net.train_error(&[0.5, 0.5], &inter, 0.1);
+/// assert_eq!(gradients.len(), 2);
+/// ```
+pub trait Terminal: Network<Out = [Scalar; 1]> {
+    /// Returns the gradient over the error that backpropagation starts from.
+    fn seed(&self) -> [Scalar; 1] {
+        [1.0]
+    }
+
+    /// Trains the network using a previous evaluation and the associated inputs, minimizing its
+    /// error, and returns the gradients over the inputs.
+    fn train_error(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        learning_rate: impl Into<LearningRate>,
+    ) -> Self::In {
+        let seed = self.seed();
+        self.train_deriv(inputs, intermediate, &seed, learning_rate.into().get())
+    }
+}
+
+impl<N: Supervised> Terminal for N {}
+
+/// Trait for outputs of networks that can seed backpropagation with a gradient of one for every
+/// element, as used by [`Network::train()`].
+pub trait Gradient {
+    /// Returns gradients of the same shape as `self`, all equal to one.
+    fn ones_like(&self) -> Self;
+}
+
+impl Gradient for Scalar {
+    fn ones_like(&self) -> Self {
+        1.0
+    }
+}
+
+impl<const N: usize> Gradient for [Scalar; N] {
+    fn ones_like(&self) -> Self {
+        [1.0; N]
+    }
+}
+
+impl Gradient for Vec<Scalar> {
+    fn ones_like(&self) -> Self {
+        vec![1.0; self.len()]
+    }
 }
 
 /// Trait for types that represent the intermediate values of a network evaluation.
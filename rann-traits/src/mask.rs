@@ -0,0 +1,249 @@
+/*!
+Masks for sequences of variable length, which are padded to a fixed number of steps.
+
+Sequence networks take a fixed number of steps, so shorter sequences are padded to that number. A
+[`Masked`] sequence carries a flag for every step alongside its steps, which is `false` for
+padding. Networks implementing [`Masking`] respect these flags: they are not trained on padded
+steps, and return gradients of zero for them, such that padding a sequence does not change its
+gradients. Recurrent networks keep their state over padded steps, and sequence losses leave padded
+steps out.
+
+[`Masking::masked()`] wraps such a network into a [`MaskedInputs`] network, which takes
+[`Masked`] sequences as inputs, such that padded sequences can be trained on by any training loop.
+
+# Examples
+```rust
+use rann_base::{
+    activ::Tanh,
+    error::{BatchLoss, ReduceMean, SquareError},
+    recurrent::{Gru, Recurrent},
+    testing, Full,
+};
+use rann_traits::{
+    compose::TimeDistributed,
+    mask::{Masked, Masking},
+    Network, Supervised, Terminal,
+};
+
+// Sequences of at most four steps, of which every step is read out and compared to a target.
+let mut net = Recurrent::<_, 4>::new(Gru::<1, 3>::new(testing::seeded_gen(1)))
+    .chain(TimeDistributed::<_, 4>::new(Full::<3, 1, _>::new(Tanh, testing::seeded_gen(2))))
+    .chain(BatchLoss::<_, _, 4>::new(SquareError { expected: [0.0] }, ReduceMean))
+    .masked();
+net.set_target(&[[0.5], [-0.5], [0.0], [0.0]]);
+
+// A sequence of two steps, of which the padding does not matter.
+let sequence = Masked::padded(&[[1.0], [-1.0]], [0.0]);
+assert_eq!(sequence.mask, [true, true, false, false]);
+assert_eq!(net.eval(&sequence), net.eval(&Masked::padded(&[[1.0], [-1.0]], [5.0])));
+
+let inter = net.intermediate(&sequence);
+let gradients = net.train_error(&sequence, &inter, 0.1);
+assert_eq!(gradients.steps[2..], [[0.0], [0.0]]);
+```
+*/
+
+use std::any::Any;
+
+use crate::{
+    compose::{ChainInter, TimeDistributed},
+    Chain, Intermediate, Network, Scalar, Supervised,
+};
+
+/// A sequence of `N` steps with a flag for every step, which is `false` for padding. See
+/// [module level documentation](self) for more info.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Masked<S, const N: usize> {
+    /// The steps of the sequence, including the padding.
+    pub steps: [S; N],
+    /// Whether every step is part of the sequence, rather than padding.
+    pub mask: [bool; N],
+}
+
+impl<S, const N: usize> Masked<S, N> {
+    /// Creates a sequence of which all steps are part.
+    pub fn new(steps: [S; N]) -> Self {
+        Self {
+            steps,
+            mask: [true; N],
+        }
+    }
+
+    /// Creates a sequence of the steps of `steps`, followed by `padding` until it has `N` steps.
+    ///
+    /// # Panics
+    /// Panics if `steps` has more than `N` steps.
+    pub fn padded(steps: &[S], padding: S) -> Self
+    where
+        S: Clone,
+    {
+        assert!(
+            steps.len() <= N,
+            "A sequence of {} steps should be at most {N} steps long.",
+            steps.len()
+        );
+        Self {
+            steps: std::array::from_fn(|t| steps.get(t).unwrap_or(&padding).clone()),
+            mask: std::array::from_fn(|t| t < steps.len()),
+        }
+    }
+
+    /// Returns the number of steps that are part of the sequence.
+    pub fn num_steps(&self) -> usize {
+        self.mask.iter().filter(|&&valid| valid).count()
+    }
+}
+
+/// Trait implemented by networks over sequences of `N` steps that respect masks. See
+/// [module level documentation](self) for more info.
+pub trait Masking<const N: usize>: Network {
+    /// Evaluates the network like [`Network::intermediate()`], with the steps of which the flag
+    /// in `mask` is `false` as padding.
+    fn intermediate_masked(&self, inputs: &Self::In, mask: &[bool; N]) -> Self::Inter;
+
+    /// Trains the network like [`Network::train_deriv()`], using an evaluation by
+    /// [`Self::intermediate_masked()`], without training on padded steps. Returns the gradients
+    /// over the inputs, which are zero for padded steps.
+    fn train_masked(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        mask: &[bool; N],
+        learning_rate: Scalar,
+    ) -> Self::In;
+
+    /// Wraps this network into one that takes [`Masked`] sequences as inputs.
+    fn masked(self) -> MaskedInputs<Self>
+    where
+        Self: Sized,
+    {
+        MaskedInputs { network: self }
+    }
+}
+
+// The mask applies to the steps of both networks.
+impl<T, U, const N: usize> Masking<N> for Chain<T, U>
+where
+    T: Masking<N>,
+    U: Masking<N, In = T::Out>,
+{
+    fn intermediate_masked(&self, inputs: &Self::In, mask: &[bool; N]) -> Self::Inter {
+        let first = self.first.intermediate_masked(inputs, mask);
+        let second = self.second.intermediate_masked(first.output(), mask);
+        ChainInter { first, second }
+    }
+
+    fn train_masked(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        mask: &[bool; N],
+        learning_rate: Scalar,
+    ) -> Self::In {
+        let second = self.second.train_masked(
+            intermediate.first.output(),
+            &intermediate.second,
+            gradients,
+            mask,
+            learning_rate,
+        );
+        self.first
+            .train_masked(inputs, &intermediate.first, &second, mask, learning_rate)
+    }
+}
+
+// Every step is evaluated independently, so padded steps are evaluated like the other steps, but
+// are not trained on.
+impl<T, const I: usize, const O: usize, const N: usize> Masking<N> for TimeDistributed<T, N>
+where
+    T: Network<In = [Scalar; I], Out = [Scalar; O]>,
+{
+    fn intermediate_masked(&self, inputs: &Self::In, _mask: &[bool; N]) -> Self::Inter {
+        self.intermediate(inputs)
+    }
+
+    fn train_masked(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        mask: &[bool; N],
+        learning_rate: Scalar,
+    ) -> Self::In {
+        std::array::from_fn(|t| {
+            if !mask[t] {
+                return [0.0; I];
+            }
+            self.network.train_deriv(
+                &inputs[t],
+                &intermediate.steps[t],
+                &gradients[t],
+                learning_rate,
+            )
+        })
+    }
+}
+
+/// A network over sequences that takes [`Masked`] sequences as inputs, created by
+/// [`Masking::masked()`]. See [module level documentation](self) for more info.
+#[derive(Clone, Debug)]
+pub struct MaskedInputs<T> {
+    /// The network over the steps of the sequences.
+    pub network: T,
+}
+
+impl<T, S, const N: usize> Network for MaskedInputs<T>
+where
+    T: Masking<N, In = [S; N]>,
+{
+    type In = Masked<S, N>;
+
+    type Out = T::Out;
+
+    type Inter = T::Inter;
+
+    fn intermediate(&self, inputs: &Self::In) -> Self::Inter {
+        self.network
+            .intermediate_masked(&inputs.steps, &inputs.mask)
+    }
+
+    fn train_deriv(
+        &mut self,
+        inputs: &Self::In,
+        intermediate: &Self::Inter,
+        gradients: &Self::Out,
+        learning_rate: Scalar,
+    ) -> Self::In {
+        Masked {
+            steps: self.network.train_masked(
+                &inputs.steps,
+                intermediate,
+                gradients,
+                &inputs.mask,
+                learning_rate,
+            ),
+            mask: inputs.mask,
+        }
+    }
+
+    fn find_layer(&self, name: &str) -> Option<&dyn Any> {
+        self.network.find_layer(name)
+    }
+
+    fn find_layer_mut(&mut self, name: &str) -> Option<&mut dyn Any> {
+        self.network.find_layer_mut(name)
+    }
+}
+
+impl<T, S, const N: usize> Supervised for MaskedInputs<T>
+where
+    T: Masking<N, In = [S; N], Out = [Scalar; 1]> + Supervised,
+{
+    type Target = T::Target;
+
+    fn set_target(&mut self, target: &Self::Target) {
+        self.network.set_target(target);
+    }
+}
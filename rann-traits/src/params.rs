@@ -0,0 +1,194 @@
+/*!
+Access to the parameters of networks as flat vectors.
+
+Networks implementing [`Parameterized`] can export their parameters (such as weights and biases)
+to a slice of scalars and import them again. This allows optimizers, evolutionary strategies and
+serialization to treat any network as a flat parameter vector, independent of its structure.
+*/
+
+use crate::{
+    compose::{Repeat, Shared, TimeDistributed, ZipOwned},
+    mask::MaskedInputs,
+    Chain, Scalar, Zip,
+};
+
+/// Trait implemented by networks whose parameters can be exported and imported as a flat
+/// vector.
+///
+/// The order of the parameters is specific to the network, but is the same for writing and
+/// reading.
+pub trait Parameterized {
+    /// Returns the number of parameters of the network.
+    fn num_params(&self) -> usize;
+
+    /// Writes the parameters of the network to `params`.
+    ///
+    /// # Panics
+    /// Panics if the length of `params` does not equal [`Self::num_params()`].
+    fn write_params(&self, params: &mut [Scalar]);
+
+    /// Reads the parameters of the network from `params`.
+    ///
+    /// # Panics
+    /// Panics if the length of `params` does not equal [`Self::num_params()`].
+    fn read_params(&mut self, params: &[Scalar]);
+
+    /// Returns the parameters of the network as a vector.
+    fn params(&self) -> Vec<Scalar> {
+        let mut params = vec![0.0; self.num_params()];
+        self.write_params(&mut params);
+        params
+    }
+}
+
+// The parameters of the first network are followed by those of the second.
+impl<T, U> Parameterized for Chain<T, U>
+where
+    T: Parameterized,
+    U: Parameterized,
+{
+    fn num_params(&self) -> usize {
+        self.first.num_params() + self.second.num_params()
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        let (first, second) = params.split_at_mut(self.first.num_params());
+        self.first.write_params(first);
+        self.second.write_params(second);
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        let (first, second) = params.split_at(self.first.num_params());
+        self.first.read_params(first);
+        self.second.read_params(second);
+    }
+}
+
+// The parameters of the top network are followed by those of the bottom network.
+impl<T, U, Z, UnZ> Parameterized for Zip<T, U, Z, UnZ>
+where
+    T: Parameterized,
+    U: Parameterized,
+{
+    fn num_params(&self) -> usize {
+        self.top.num_params() + self.bot.num_params()
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        let (top, bot) = params.split_at_mut(self.top.num_params());
+        self.top.write_params(top);
+        self.bot.write_params(bot);
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        let (top, bot) = params.split_at(self.top.num_params());
+        self.top.read_params(top);
+        self.bot.read_params(bot);
+    }
+}
+
+impl<T, U, Z, UnZ> Parameterized for ZipOwned<T, U, Z, UnZ>
+where
+    T: Parameterized,
+    U: Parameterized,
+{
+    fn num_params(&self) -> usize {
+        self.top.num_params() + self.bot.num_params()
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        let (top, bot) = params.split_at_mut(self.top.num_params());
+        self.top.write_params(top);
+        self.bot.write_params(bot);
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        let (top, bot) = params.split_at(self.top.num_params());
+        self.top.read_params(top);
+        self.bot.read_params(bot);
+    }
+}
+
+// Every handle to a shared network exposes its parameters, so a network that is used at
+// multiple places also appears multiple times in the parameter vector.
+impl<T> Parameterized for Shared<T>
+where
+    T: Parameterized,
+{
+    fn num_params(&self) -> usize {
+        self.borrow().num_params()
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        self.borrow().write_params(params);
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        self.borrow_mut().read_params(params);
+    }
+}
+
+// The parameters of the layers follow each other, in evaluation order.
+impl<T, const N: usize> Parameterized for Repeat<T, N>
+where
+    T: Parameterized,
+{
+    fn num_params(&self) -> usize {
+        self.layers.iter().map(T::num_params).sum()
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        assert_eq!(params.len(), self.num_params());
+        let mut rest = params;
+        for layer in &self.layers {
+            let (head, tail) = rest.split_at_mut(layer.num_params());
+            layer.write_params(head);
+            rest = tail;
+        }
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        assert_eq!(params.len(), self.num_params());
+        let mut rest = params;
+        for layer in &mut self.layers {
+            let (head, tail) = rest.split_at(layer.num_params());
+            layer.read_params(head);
+            rest = tail;
+        }
+    }
+}
+
+// The steps share the parameters of the network.
+impl<T, const N: usize> Parameterized for TimeDistributed<T, N>
+where
+    T: Parameterized,
+{
+    fn num_params(&self) -> usize {
+        self.network.num_params()
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        self.network.write_params(params);
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        self.network.read_params(params);
+    }
+}
+
+impl<T> Parameterized for MaskedInputs<T>
+where
+    T: Parameterized,
+{
+    fn num_params(&self) -> usize {
+        self.network.num_params()
+    }
+
+    fn write_params(&self, params: &mut [Scalar]) {
+        self.network.write_params(params);
+    }
+
+    fn read_params(&mut self, params: &[Scalar]) {
+        self.network.read_params(params);
+    }
+}
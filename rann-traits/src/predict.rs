@@ -0,0 +1,101 @@
+/*!
+Lazy evaluation of networks over streams of inputs.
+
+[`Network::predict_iter()`] evaluates a network on the inputs of an iterator as its outputs are
+requested, without collecting the inputs or the outputs into memory first. The inputs are taken
+in chunks, of which every chunk is evaluated at once with [`Network::eval_many()`], such that
+layers that evaluate batches at once still do so. The size of the chunks trades the memory held
+for a chunk against the speed of evaluating larger batches, and is set using
+[`Predict::with_chunk_size()`].
+
+# Examples
+```rust
+use rann_traits::{Network, Scalar};
+# struct Double;
+# impl Network for Double {
+#     type In = [Scalar; 1];
+#     type Out = [Scalar; 1];
+#     type Inter = [Scalar; 1];
+#     fn intermediate(&self, inputs: &[Scalar; 1]) -> [Scalar; 1] { [inputs[0] * 2.0] }
+#     fn train_deriv(&mut self, _: &[Scalar; 1], _: &[Scalar; 1], g: &[Scalar; 1], _: Scalar)
+#         -> [Scalar; 1] { [g[0] * 2.0] }
+# }
+
+let net = Double.chain(Double);
+// An endless stream of inputs, of which only the first outputs are evaluated.
+let inputs = (0..).map(|i| [i as Scalar]);
+let outputs: Vec<_> = net.predict_iter(inputs).with_chunk_size(4).take(3).collect();
+assert_eq!(outputs, [[0.0], [4.0], [8.0]]);
+```
+*/
+
+use std::vec;
+
+use crate::Network;
+
+/// An iterator over the outputs of a network on the inputs of another iterator, created by
+/// [`Network::predict_iter()`]. See [module level documentation](self) for more info.
+pub struct Predict<'a, N: Network, I> {
+    net: &'a N,
+    inputs: I,
+    chunk_size: usize,
+    // The inputs of the next chunk, kept to reuse their buffer.
+    chunk: Vec<N::In>,
+    // The outputs of the last chunk that have not been returned yet.
+    outputs: vec::IntoIter<N::Out>,
+}
+
+impl<'a, N: Network, I> Predict<'a, N, I> {
+    /// The number of inputs that are evaluated at once by default.
+    pub const DEFAULT_CHUNK_SIZE: usize = 64;
+
+    pub(crate) fn new(net: &'a N, inputs: I) -> Self {
+        Self {
+            net,
+            inputs,
+            chunk_size: Self::DEFAULT_CHUNK_SIZE,
+            chunk: Vec::new(),
+            outputs: Vec::new().into_iter(),
+        }
+    }
+
+    /// Evaluates `chunk_size` inputs at once, instead of [`Self::DEFAULT_CHUNK_SIZE`].
+    ///
+    /// # Panics
+    /// Panics if `chunk_size` is zero.
+    pub fn with_chunk_size(self, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "The chunk size should be positive.");
+        Self { chunk_size, ..self }
+    }
+}
+
+impl<N, I> Iterator for Predict<'_, N, I>
+where
+    N: Network,
+    I: Iterator<Item = N::In>,
+{
+    type Item = N::Out;
+
+    fn next(&mut self) -> Option<N::Out> {
+        if let Some(outputs) = self.outputs.next() {
+            return Some(outputs);
+        }
+        self.chunk.clear();
+        self.chunk
+            .extend(self.inputs.by_ref().take(self.chunk_size));
+        if self.chunk.is_empty() {
+            return None;
+        }
+        self.outputs = self.net.eval_many(&self.chunk).into_iter();
+        self.outputs.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.inputs.size_hint();
+        let pending = self.outputs.len();
+        (
+            lower.saturating_add(pending),
+            upper.and_then(|upper| upper.checked_add(pending)),
+        )
+    }
+}
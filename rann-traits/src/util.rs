@@ -1,14 +1,55 @@
 /*!
 Utilities for common patterns in RANN.
+
+Constructors such as [`Network::zip()`](crate::Network::zip) or `Full::new` take pairs of
+functions as `impl Into<(F, G)>`, such that a pair can be passed as a single, named value.
+[`function_tuple!`](crate::function_tuple) declares such a value: a unit struct that converts
+into a tuple of function pointers.
+
+# Examples
+```rust
+use rann_traits::{function_tuple, Scalar};
+
+fn ones(_: usize, _: usize) -> Scalar {
+    1.0
+}
+
+fn zeros(_: usize) -> Scalar {
+    0.0
+}
+
+function_tuple! {
+    /// Initializes all weights to one and all biases to zero.
+    pub struct Ones: (fn(usize, usize) -> Scalar, fn(usize) -> Scalar) = (ones, zeros);
+}
+
+let (weights, biases): (fn(usize, usize) -> Scalar, fn(usize) -> Scalar) = Ones.into();
+assert_eq!((weights(1, 2), biases(3)), (1.0, 0.0));
+```
 */
 
+/// Declares a unit struct that converts into a tuple of function pointers. See
+/// [module level documentation](crate::util) for more info.
+///
+/// The struct may have const generic parameters, which can be used in the types of the
+/// functions.
+#[macro_export]
 macro_rules! function_tuple {
     (
-        $name:ident:
-        $($fs:item),+
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident $(<$(const $param:ident: $param_ty:ty),+>)?:
+        ($($fn_ty:ty),+ $(,)?) = ($($f:expr),+ $(,)?);
     ) => {
-        pub struct $name;
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug)]
+        $vis struct $name $(<$(const $param: $param_ty),+>)?;
 
-        impl Into<
+        impl $(<$(const $param: $param_ty),+>)? ::core::convert::From<$name $(<$($param),+>)?>
+            for ($($fn_ty,)+)
+        {
+            fn from(_: $name $(<$($param),+>)?) -> Self {
+                ($($f,)+)
+            }
+        }
     };
 }
@@ -0,0 +1,88 @@
+use proptest::prelude::*;
+use rann_base::{
+    activ::{LeakyRelu, Logistic, Tanh},
+    error::SquareError,
+    testing::strategies::{full, inputs, layer_norm},
+};
+use rann_traits::{
+    compose::{zip::Stacker, Shared},
+    Intermediate, Network,
+};
+
+// The range of the generated parameters and inputs.
+const RANGE: f32 = 2.0;
+
+// Checks the invariants that should hold for any network:
+// - evaluating equals taking the output of the intermediate values,
+// - training with a learning rate of zero returns finite gradients and leaves the network as is.
+fn check<N>(mut net: N, inputs: N::In) -> Result<(), TestCaseError>
+where
+    N: Network,
+    N::In: AsRef<[f32]>,
+    N::Out: PartialEq + std::fmt::Debug + Clone,
+{
+    let out = net.eval(&inputs);
+    let inter = net.intermediate(&inputs);
+    prop_assert_eq!(&out, inter.output());
+
+    let gradients = out.clone();
+    let grads = net.train_deriv(&inputs, &inter, &gradients, 0.0);
+    prop_assert_eq!(grads.as_ref().len(), inputs.as_ref().len());
+    prop_assert!(
+        grads.as_ref().iter().all(|g| g.is_finite()),
+        "{:?}",
+        grads.as_ref()
+    );
+    prop_assert_eq!(net.eval(&inputs), out);
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn full_layers(
+        net in full::<3, 4, _>(Logistic, RANGE),
+        inputs in inputs::<3>(RANGE),
+    ) {
+        check(net, inputs)?;
+    }
+
+    #[test]
+    fn layer_norms(net in layer_norm::<5>(RANGE), inputs in inputs::<5>(RANGE)) {
+        check(net, inputs)?;
+    }
+
+    #[test]
+    fn chains(
+        a in full::<2, 3, _>(LeakyRelu(0.1), RANGE),
+        b in layer_norm::<3>(RANGE),
+        c in full::<3, 2, _>(Tanh, RANGE),
+        expected in inputs::<2>(RANGE),
+        inputs in inputs::<2>(RANGE),
+    ) {
+        check(a.chain(b).chain(c).chain(SquareError { expected }), inputs)?;
+    }
+
+    #[test]
+    fn shared_chains(a in full::<3, 3, _>(Logistic, RANGE), inputs in inputs::<3>(RANGE)) {
+        let shared = Shared::new(a);
+        check(shared.clone().chain(shared), inputs)?;
+    }
+
+    #[test]
+    fn zips(
+        a in full::<2, 2, _>(Logistic, RANGE),
+        b in full::<3, 1, _>(Logistic, RANGE),
+        top in inputs::<2>(RANGE),
+        bot in inputs::<3>(RANGE),
+    ) {
+        let mut net = a.zip(b, Stacker::<2, 1, 3>);
+        let inputs = (top, bot);
+        let out = net.eval(&inputs);
+        let inter = net.intermediate(&inputs);
+        prop_assert_eq!(out, *inter.output());
+
+        let (top_grads, bot_grads) = net.train_deriv(&inputs, &inter, &out, 0.0);
+        prop_assert!(top_grads.iter().chain(&bot_grads).all(|g| g.is_finite()));
+        prop_assert_eq!(net.eval(&inputs), out);
+    }
+}
@@ -0,0 +1,137 @@
+/*!
+# Rann-wasm
+
+WebAssembly bindings for running RANN networks in the browser, through
+[`wasm-bindgen`](wasm_bindgen).
+
+Without its default `rayon` feature, `rann-base` uses no threads, and with its `wasm` feature its
+random generators are seeded from the browser, so the inference and training core compiles to
+`wasm32-unknown-unknown`. This crate depends on it that way, and exposes a [`PlaneClassifier`]: a
+small network classifying points in the plane, as used in interactive demos where points are
+added by clicking and the decision boundary is drawn while training.
+
+```sh
+$ wasm-pack build rann-wasm --target web
+```
+
+```js
+import init, { PlaneClassifier } from "./pkg/rann_wasm.js";
+
+await init();
+const net = new PlaneClassifier(1);
+const points = new Float32Array([0, 0, 0, 1, 1, 0, 1, 1]);
+const labels = new Float32Array([0, 1, 1, 0]);
+net.train(points, labels, 1000, 0.5);
+console.log(net.eval(new Float32Array([1, 0])));
+```
+
+# Examples
+The bindings are plain Rust as well:
+```rust
+use rann_wasm::PlaneClassifier;
+
+let mut net = PlaneClassifier::new(1);
+let points = [0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0];
+let labels = [0.0, 1.0, 1.0, 0.0];
+let error = net.train(&points, &labels, 2000, 0.5).unwrap();
+assert!(error < 0.05);
+assert!(net.eval(&[1.0, 0.0]).unwrap()[0] > 0.5);
+```
+*/
+
+use rann_base::{activ::Logistic, error::SquareError, testing, train, Full};
+use rann_traits::{compose::Chain, params::Parameterized, Network, Scalar};
+use wasm_bindgen::prelude::*;
+
+/// The number of hidden neurons of a [`PlaneClassifier`].
+pub const HIDDEN: usize = 8;
+
+type Net = Chain<Chain<Full<2, HIDDEN, Logistic>, Full<HIDDEN, 1, Logistic>>, SquareError<1>>;
+
+/// A network classifying points in the plane into two classes, with a hidden layer of [`HIDDEN`]
+/// neurons. See [crate level documentation](crate) for more info.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct PlaneClassifier {
+    net: Net,
+}
+
+#[wasm_bindgen]
+impl PlaneClassifier {
+    /// Creates a classifier with random parameters, generated from `seed`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: u32) -> Self {
+        let seed = u64::from(seed);
+        let net = Full::new(Logistic, testing::seeded_gen(seed))
+            .chain(Full::new(Logistic, testing::seeded_gen(seed + 1)))
+            .chain(SquareError { expected: [0.0] });
+        Self { net }
+    }
+
+    /// Returns the probability of the second class for the point `(inputs[0], inputs[1])`.
+    ///
+    /// # Errors
+    /// Fails if `inputs` does not hold two values.
+    pub fn eval(&self, inputs: &[Scalar]) -> Result<Vec<Scalar>, JsError> {
+        Ok(self.net.first.eval(&point(inputs)?).to_vec())
+    }
+
+    /// Trains the classifier for `epochs` epochs on the points in `points`, which holds the
+    /// coordinates of every point one after another, with the class of every point (`0.0` or
+    /// `1.0`) in `labels`. Returns the mean error of the last epoch, or zero without epochs.
+    ///
+    /// # Errors
+    /// Fails if `points` does not hold two values for every label.
+    pub fn train(
+        &mut self,
+        points: &[Scalar],
+        labels: &[Scalar],
+        epochs: usize,
+        learning_rate: Scalar,
+    ) -> Result<Scalar, JsError> {
+        if points.len() != 2 * labels.len() {
+            return Err(JsError::new(
+                "Every label needs a point of two coordinates.",
+            ));
+        }
+        let dataset: Vec<_> = points
+            .chunks(2)
+            .zip(labels)
+            .map(|(p, &label)| Ok((point(p)?, [label])))
+            .collect::<Result<_, JsError>>()?;
+        let mut error = 0.0;
+        for _ in 0..epochs {
+            error = train::train_epoch(&mut self.net, &dataset, learning_rate);
+        }
+        Ok(error)
+    }
+
+    /// Returns the parameters of the classifier, to store them.
+    pub fn params(&self) -> Vec<Scalar> {
+        self.net.params()
+    }
+
+    /// Replaces the parameters of the classifier by `params`, as returned by
+    /// [`Self::params()`].
+    ///
+    /// # Errors
+    /// Fails if the number of parameters does not match.
+    #[wasm_bindgen(js_name = setParams)]
+    pub fn set_params(&mut self, params: &[Scalar]) -> Result<(), JsError> {
+        if params.len() != self.net.num_params() {
+            return Err(JsError::new(&format!(
+                "Expected {} parameters, got {}.",
+                self.net.num_params(),
+                params.len()
+            )));
+        }
+        self.net.read_params(params);
+        Ok(())
+    }
+}
+
+fn point(inputs: &[Scalar]) -> Result<[Scalar; 2], JsError> {
+    inputs
+        .try_into()
+        .map_err(|_| JsError::new("A point should have two coordinates."))
+}
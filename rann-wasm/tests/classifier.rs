@@ -0,0 +1,52 @@
+use rann_wasm::PlaneClassifier;
+
+// Two classes separated by the line `y = x`.
+fn dataset() -> (Vec<f32>, Vec<f32>) {
+    let mut points = Vec::new();
+    let mut labels = Vec::new();
+    for i in 0..5 {
+        for j in 0..5 {
+            if i != j {
+                points.extend([i as f32 / 4.0, j as f32 / 4.0]);
+                labels.push(if j > i { 1.0 } else { 0.0 });
+            }
+        }
+    }
+    (points, labels)
+}
+
+#[test]
+fn learns_a_decision_boundary() {
+    let (points, labels) = dataset();
+    let mut net = PlaneClassifier::new(3);
+    let error = net.train(&points, &labels, 1000, 0.5).unwrap();
+    assert!(error < 0.05, "{error}");
+    assert!(net.eval(&[0.1, 0.9]).unwrap()[0] > 0.5);
+    assert!(net.eval(&[0.9, 0.1]).unwrap()[0] < 0.5);
+}
+
+#[test]
+fn reports_no_error_without_epochs() {
+    let (points, labels) = dataset();
+    assert_eq!(
+        PlaneClassifier::new(1)
+            .train(&points, &labels, 0, 0.5)
+            .unwrap(),
+        0.0
+    );
+}
+
+#[test]
+fn parameters_round_trip() {
+    let net = PlaneClassifier::new(1);
+    let mut other = PlaneClassifier::new(2);
+    assert_ne!(
+        other.eval(&[0.5, 0.5]).unwrap(),
+        net.eval(&[0.5, 0.5]).unwrap()
+    );
+    other.set_params(&net.params()).unwrap();
+    assert_eq!(
+        other.eval(&[0.5, 0.5]).unwrap(),
+        net.eval(&[0.5, 0.5]).unwrap()
+    );
+}
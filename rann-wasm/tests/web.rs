@@ -0,0 +1,26 @@
+//! Tests in a WebAssembly runtime, run with `wasm-pack test --node rann-wasm`.
+#![cfg(target_arch = "wasm32")]
+
+use rann_wasm::PlaneClassifier;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn trains_xor() {
+    let mut net = PlaneClassifier::new(1);
+    let points = [0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0];
+    let labels = [0.0, 1.0, 1.0, 0.0];
+    assert!(net.train(&points, &labels, 2000, 0.5).unwrap() < 0.05);
+    assert!(net.eval(&[0.0, 1.0]).unwrap()[0] > 0.5);
+    assert!(net.eval(&[1.0, 1.0]).unwrap()[0] < 0.5);
+}
+
+// Errors are JavaScript objects, which can only be created in a WebAssembly runtime.
+#[wasm_bindgen_test]
+fn rejects_invalid_inputs() {
+    let mut net = PlaneClassifier::new(1);
+    assert!(net.train(&[0.0, 0.0, 1.0], &[0.0, 1.0], 1, 0.1).is_err());
+    assert!(net.eval(&[1.0]).is_err());
+    let params = net.params();
+    assert!(net.set_params(&params[1..]).is_err());
+    assert!(net.set_params(&params).is_ok());
+}